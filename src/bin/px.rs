@@ -4,9 +4,9 @@
 //! with fuzzy search and frecency-based ranking.
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use rust_filesearch::config::PxConfig;
-use rust_filesearch::px::{commands, index::ProjectIndex};
+use clap::Parser;
+use rust_filesearch::logging::{self, LogConfig};
+use rust_filesearch::px::cli::{run, PxCommand};
 
 #[derive(Parser)]
 #[command(name = "px")]
@@ -14,68 +14,23 @@ use rust_filesearch::px::{commands, index::ProjectIndex};
 #[command(author, long_about = None)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// List all projects
-    List {
-        /// Filter projects (has-changes, inactive-30d, inactive-90d)
-        #[arg(long)]
-        filter: Option<String>,
-
-        /// Output format (json, path, pretty)
-        #[arg(long, default_value = "pretty")]
-        format: String,
-    },
-
-    /// Open project in editor
-    Open {
-        /// Project name/path query (fuzzy matched)
-        query: String,
-
-        /// Editor to use (code, cursor, vim, etc.)
-        #[arg(long)]
-        editor: Option<String>,
-    },
+    command: PxCommand,
 
-    /// Show project information
-    Info {
-        /// Project name/path query (fuzzy matched)
-        query: String,
-    },
-
-    /// Re-index projects by scanning configured directories
-    Sync,
-
-    /// Initialize px configuration
-    Init,
+    /// Screen-reader friendly output: no emoji, symbols or box drawing
+    #[arg(long, global = true)]
+    plain: bool,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let config = PxConfig::load()?;
-    let mut index = ProjectIndex::load()?;
-
-    match cli.command {
-        Commands::List { filter, format: _ } => {
-            commands::cmd_list(&index, filter)?;
-        }
-        Commands::Open { query, editor } => {
-            let editor = editor.unwrap_or(config.default_editor);
-            commands::cmd_open(&mut index, &query, &editor)?;
-        }
-        Commands::Info { query } => {
-            commands::cmd_info(&index, &query)?;
-        }
-        Commands::Sync => {
-            commands::cmd_sync(&mut index, &config.scan_dirs)?;
-        }
-        Commands::Init => {
-            commands::cmd_init()?;
-        }
+    if cli.plain {
+        rust_filesearch::plain::enable();
     }
-
+    // Warnings go through tracing, as in fexplorer
+    logging::init(&LogConfig {
+        no_color: cli.plain,
+        ..Default::default()
+    })?;
+    run(cli.command, "px")?;
     Ok(())
 }
@@ -1,6 +1,14 @@
 use crate::errors::{FsError, Result};
-use crate::models::{Column, EntryKind, OutputFormat, SortKey, SortOrder};
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::fs::enrich::TypeDetection;
+use crate::fs::unique::UniqueKey;
+use crate::models::{Column, EntryKind, OutputFormat, SortField, SortKey, SortOrder};
+use crate::output::aggregate::GroupSpec;
+use crate::output::color::ColorChoice;
+use crate::output::select::Selection;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgGroup, ArgMatches, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -10,10 +18,20 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
 
-    /// Disable colored output
+    /// Disable colored output (same as --color never)
     #[arg(long, global = true)]
     pub no_color: bool,
 
+    /// When to color output [default: auto, or always with CLICOLOR_FORCE,
+    /// never with NO_COLOR]
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    pub color: Option<ColorChoice>,
+
+    /// Screen-reader friendly output: no color, box drawing or emoji; entry
+    /// kinds are spelled out as DIR, FILE and LINK
+    #[arg(long, global = true)]
+    pub plain: bool,
+
     /// Quiet mode (suppress warnings)
     #[arg(long, short = 'q', global = true)]
     pub quiet: bool,
@@ -21,28 +39,68 @@ pub struct Cli {
     /// Verbose mode (show detailed output)
     #[arg(long, short = 'v', global = true)]
     pub verbose: bool,
+
+    /// Log level (error, warn, info, debug, trace) or a tracing filter directive
+    #[arg(long, global = true, value_name = "LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Append log output to a file instead of stderr
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+    /// Print a per-phase timing breakdown (walk, filter, sort, hash, output) to stderr
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// Language of summaries and messages (en, es) [default: from LC_ALL,
+    /// LC_MESSAGES or LANG]
+    #[arg(long, global = true, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Identify file types by content (magic bytes, shebangs) rather than by
+    /// extension, for --category and the mime and category columns
+    #[cfg(feature = "mime")]
+    #[arg(long, global = true)]
+    pub detect_type: bool,
 }
 
+impl Cli {
+    /// Where file types come from: content with `--detect-type`, else the
+    /// extension
+    pub fn type_detection(&self) -> TypeDetection {
+        #[cfg(feature = "mime")]
+        if self.detect_type {
+            return TypeDetection::Content;
+        }
+        TypeDetection::Extension
+    }
+}
+
+// Parsed once per process; boxing the larger variants buys nothing
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// List entries with metadata and sorting
     #[command(visible_alias = "ls")]
     List {
-        /// Root path to list
-        #[arg(default_value = ".")]
-        path: PathBuf,
+        /// Root paths to list (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
 
-        /// Sort by key
-        #[arg(long, value_name = "KEY")]
+        /// Sort keys, comma-separated, each optionally `:asc`/`:desc` (e.g. kind,size:desc,name)
+        #[arg(long, value_name = "KEYS")]
         sort: Option<String>,
 
-        /// Sort order (asc or desc)
+        /// Sort order for keys without an explicit direction (asc or desc)
         #[arg(long, default_value = "asc")]
         order: String,
 
-        /// Show directories first
-        #[arg(long)]
-        dirs_first: bool,
+        /// Show at most N entries (with --sort, the first N in sort order)
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Mimic `ls -la` output (implies --hidden, --no-gitignore, --max-depth 1)
+        #[arg(long, conflicts_with = "reproducible")]
+        ls: bool,
 
         #[command(flatten)]
         common: CommonArgs,
@@ -54,65 +112,255 @@ pub enum Commands {
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Show directories first
-        #[arg(long)]
-        dirs_first: bool,
-
         #[command(flatten)]
         common: CommonArgs,
     },
 
     /// Find files matching criteria
     Find {
-        /// Root path to search
-        #[arg(default_value = ".")]
-        path: PathBuf,
+        /// Root paths to search (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
 
-        /// Name glob patterns (repeatable)
-        #[arg(long = "name")]
-        names: Vec<String>,
+        /// Only show sibling names that differ just by case or Unicode
+        /// normalization (they collide on macOS/Windows checkouts)
+        #[arg(long)]
+        case_collisions: bool,
 
-        /// Regex pattern for names
+        /// Sort keys, comma-separated, each optionally `:asc`/`:desc` (e.g. size:desc)
+        #[arg(long, value_name = "KEYS")]
+        sort: Option<String>,
+
+        /// Compare against a previous `--format json` export and show only
+        /// added, removed and changed entries
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+
+        /// Where `--name` candidates come from: walk, mdfind (Spotlight) or
+        /// locate; falls back to walking when the index is unavailable
+        #[arg(long, default_value = "walk", value_name = "BACKEND")]
+        backend: String,
+
+        /// Read every file's metadata instead of reusing what earlier runs
+        /// stored for unchanged directories
+        #[cfg(feature = "cache")]
         #[arg(long)]
-        regex: Option<String>,
+        no_cache: bool,
 
-        /// File extensions (comma-separated)
-        #[arg(long, value_delimiter = ',')]
-        ext: Vec<String>,
+        #[command(flatten)]
+        filters: FilterArgs,
 
-        /// Minimum size (e.g., 10KB, 2MiB)
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Run a classic find(1) expression (e.g. `findx . -name '*.log' -mtime +30 -delete`)
+    Findx {
+        /// Make -delete unlink instead of moving to the trash
         #[arg(long)]
-        min_size: Option<String>,
+        permanent: bool,
+
+        /// Starting paths followed by find predicates, operators and actions
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "PATH... EXPRESSION"
+        )]
+        args: Vec<String>,
+    },
+
+    /// Change permissions of filtered files in batch
+    Chmod {
+        /// Mode to apply: octal (644, 0755) or symbolic (u+x, go-w, a=rX)
+        #[arg(allow_hyphen_values = true)]
+        mode: String,
+
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
 
-        /// Maximum size (e.g., 10MB, 2GiB)
+        /// Preview the exact changes without applying them
         #[arg(long)]
-        max_size: Option<String>,
+        dry_run: bool,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Change owner and/or group of filtered files in batch
+    Chown {
+        /// New owner: USER, USER:GROUP or :GROUP (names or numeric ids)
+        owner: String,
 
-        /// Modified after date (ISO8601 or YYYY-MM-DD)
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Preview the exact changes without applying them
         #[arg(long)]
-        after: Option<String>,
+        dry_run: bool,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Set modification times of filtered files (with journal and undo)
+    #[command(group(ArgGroup::new("mtime_source").required(true)))]
+    Touch {
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Set mtime to this date (same formats as --after/--before)
+        #[arg(long, value_name = "DATE", group = "mtime_source")]
+        set_mtime: Option<String>,
+
+        /// Use the photo's EXIF capture time (DateTimeOriginal)
+        #[cfg(feature = "exif")]
+        #[arg(long, group = "mtime_source")]
+        from_exif: bool,
+
+        /// Use the time of the last git commit touching the file
+        #[cfg(feature = "git")]
+        #[arg(long, group = "mtime_source")]
+        from_git: bool,
+
+        /// Restore mtimes from a touch journal (defaults to the most recent)
+        #[arg(long, value_name = "JOURNAL", num_args = 0..=1, group = "mtime_source")]
+        undo: Option<Option<PathBuf>>,
 
-        /// Modified before date (ISO8601 or YYYY-MM-DD)
+        /// Preview the exact changes without applying them
         #[arg(long)]
-        before: Option<String>,
+        dry_run: bool,
 
-        /// Filter by kind (file, dir, symlink)
-        #[arg(long, value_delimiter = ',')]
-        kind: Vec<String>,
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
 
-        /// Filter by category (source, build, config, docs, media, data, archive, executable)
+    /// Create a zip or tar archive of the entries the filters select
+    ///
+    /// Names inside are relative to the search root; nothing is removed. The
+    /// format comes from --format (zip, tar, tar.gz, tar.zst) or the --output
+    /// extension.
+    #[cfg(feature = "archive")]
+    Archive {
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Compression level (zip and gzip 0-9, zstd 1-22)
+        #[arg(long, value_name = "N")]
+        level: Option<i32>,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Archive filtered files into a verified .tar, .tar.gz or .tar.zst with a manifest
+    #[cfg(feature = "archive")]
+    Pack {
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Archive to create; compression follows the extension (.tar, .tar.gz, .tar.zst)
+        #[arg(long, value_name = "ARCHIVE")]
+        into: PathBuf,
+
+        /// Move the original files to the trash once the archive has been verified
         #[arg(long)]
-        category: Option<String>,
+        remove: bool,
+
+        /// Make --remove unlink the originals instead of trashing them
+        #[arg(long, requires = "remove")]
+        permanent: bool,
+
+        /// Compression level (gzip 0-9, zstd 1-22)
+        #[arg(long, value_name = "N")]
+        level: Option<i32>,
+
+        /// List the files that would be archived without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Show format, architecture, stripped/debug status and section sizes of executables
+    #[cfg(feature = "object")]
+    BinInfo {
+        /// Executables, shared libraries or object files
+        #[arg(required = true, value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Report developer caches (cargo, npm, pnpm, yarn, pip, Docker) and clean them up
+    DevCaches {
+        /// Project roots searched for cargo target/ directories
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Trash caches of these types (e.g. cargo-target,npm), or `all` safe ones
+        #[arg(long, value_delimiter = ',', value_name = "TYPE")]
+        clean: Vec<String>,
+
+        /// Show what --clean would delete without deleting anything
+        #[arg(long, requires = "clean")]
+        dry_run: bool,
+
+        /// Make --clean delete for good instead of moving to the trash
+        #[arg(long, requires = "clean")]
+        permanent: bool,
 
         #[command(flatten)]
         common: CommonArgs,
     },
 
+    /// Show disk usage of Docker/Podman images, containers, volumes and build cache
+    #[cfg(all(feature = "docker", unix))]
+    DockerUsage {
+        /// Daemon API socket [default: $DOCKER_HOST, /var/run/docker.sock, then Podman's]
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+
+        /// Measure a storage directory (e.g. /var/lib/docker) instead of asking the daemon
+        #[arg(long, value_name = "DIR", conflicts_with = "socket")]
+        storage: Option<PathBuf>,
+
+        /// List individual images, containers, volumes and build cache records
+        #[arg(long)]
+        items: bool,
+
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
     /// Calculate and display sizes
     Size {
-        /// Root path to analyze
-        #[arg(default_value = ".")]
-        path: PathBuf,
+        /// Root paths to analyze (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
 
         /// Show top N entries by size
         #[arg(long)]
@@ -122,10 +370,39 @@ pub enum Commands {
         #[arg(long)]
         aggregate: bool,
 
-        /// Display like 'du' command
+        /// Display like 'du' command (size<TAB>path; implies --hidden, --no-gitignore and
+        /// --max-depth only limits the printed depth)
         #[arg(long)]
         du: bool,
 
+        /// With --du, print sizes in human-readable form (like du -h)
+        #[arg(long, requires = "du")]
+        human_readable: bool,
+
+        /// With --du, report apparent sizes instead of disk usage
+        #[arg(long, requires = "du")]
+        apparent_size: bool,
+
+        /// With --du, include files as well as directories (like du -a)
+        #[arg(long, requires = "du")]
+        all: bool,
+
+        /// Show directories with the newest mtime found below them (last_activity),
+        /// least recently active first
+        #[arg(long, conflicts_with = "du")]
+        newest_child: bool,
+
+        /// Report symlinks with the size of their target, counting each file once
+        /// (links to directories are totalled with --aggregate --follow-symlinks)
+        #[arg(long, conflicts_with = "du")]
+        link_targets: bool,
+
+        /// Read every file's metadata instead of reusing what earlier runs
+        /// stored for unchanged directories
+        #[cfg(feature = "cache")]
+        #[arg(long)]
+        no_cache: bool,
+
         #[command(flatten)]
         common: CommonArgs,
     },
@@ -133,9 +410,9 @@ pub enum Commands {
     /// Search file contents (grep functionality)
     #[cfg(feature = "grep")]
     Grep {
-        /// Root path to search
-        #[arg(default_value = ".")]
-        path: PathBuf,
+        /// Root paths to search ('-' reads a path list from stdin), followed by the pattern
+        #[arg(value_name = "PATH", num_args = 1.., required = true)]
+        paths: Vec<PathBuf>,
 
         /// Pattern to search for
         #[arg(value_name = "PATTERN")]
@@ -149,6 +426,18 @@ pub enum Commands {
         #[arg(long, short = 'i')]
         case_insensitive: bool,
 
+        /// Treat the pattern as literal text (the default, as with rg -F)
+        #[arg(long, short = 'F', conflicts_with = "regex")]
+        fixed_strings: bool,
+
+        /// Only match whole words (the match starts and ends at a word boundary)
+        #[arg(long, short = 'w')]
+        word_regexp: bool,
+
+        /// Let matches span lines; with --regex, `.` matches newlines too
+        #[arg(long, short = 'U', conflicts_with = "replace")]
+        multiline: bool,
+
         /// File extensions to search (comma-separated)
         #[arg(long, value_delimiter = ',')]
         ext: Vec<String>,
@@ -161,6 +450,23 @@ pub enum Commands {
         #[arg(long, short = 'n')]
         line_numbers: bool,
 
+        /// Skip files larger than SIZE (e.g. 1MB) without reading them
+        #[arg(long, value_name = "SIZE")]
+        max_filesize: Option<String>,
+
+        /// Search binary files too (by default a NUL byte ends the search of a file)
+        #[arg(long)]
+        binary: bool,
+
+        /// Replace matches with TEXT in place (with --regex, `$1` and `${name}`
+        /// insert capture groups)
+        #[arg(long, value_name = "TEXT")]
+        replace: Option<String>,
+
+        /// Show the replacements as a diff without writing them
+        #[arg(long, requires = "replace")]
+        dry_run: bool,
+
         #[command(flatten)]
         common: CommonArgs,
     },
@@ -180,6 +486,91 @@ pub enum Commands {
         #[arg(long)]
         summary: bool,
 
+        /// Leave out groups whose files are all hard links to one file
+        #[arg(long)]
+        skip_linked: bool,
+
+        /// Experimental: report pairs of files sharing most of their content
+        /// (content-defined chunking) instead of exact duplicates
+        #[arg(long)]
+        fuzzy: bool,
+
+        /// Minimum share of the larger file found in the other, in percent (--fuzzy)
+        #[arg(long, default_value = "50", value_name = "PCT", requires = "fuzzy")]
+        similarity: f64,
+
+        /// Replace each duplicate with a copy-on-write clone of the first file
+        /// in its group (btrfs, XFS, APFS), reclaiming the wasted space
+        #[arg(long, conflicts_with = "fuzzy")]
+        reflink: bool,
+
+        /// Show which files --reflink would replace without changing them
+        #[arg(long, requires = "reflink")]
+        dry_run: bool,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Find files with near-identical names (copies, version suffixes, typos)
+    Similar {
+        /// Root paths (overlapping paths are only walked once)
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Maximum edit distance between normalized names
+        #[arg(long, default_value = "2", value_name = "N")]
+        max_distance: usize,
+
+        /// Compare names across directories, not just within each directory
+        #[arg(long)]
+        across_dirs: bool,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Check a project for hygiene problems (large committed files, mixed
+    /// line endings, trailing whitespace, orphaned lock files, case conflicts)
+    Lint {
+        /// Root paths to check
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Only run these rules (repeatable)
+        #[arg(long = "rule", value_name = "ID")]
+        rules: Vec<String>,
+
+        /// Skip these rules (repeatable)
+        #[arg(long = "skip-rule", value_name = "ID")]
+        skip_rules: Vec<String>,
+
+        /// List the available rules and exit
+        #[arg(long)]
+        list_rules: bool,
+
+        /// Size above which files committed to git are reported
+        #[arg(long, default_value = "100MB", value_name = "SIZE")]
+        max_file_size: String,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+
+    /// Audit paths for Windows portability (long paths, reserved names,
+    /// trailing spaces/dots, invalid characters, non-UTF-8 names)
+    Portability {
+        /// Root paths to audit; path lengths are measured from each root
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Longest allowed path relative to the root
+        #[arg(long, default_value = "260", value_name = "N")]
+        max_path: usize,
+
         #[command(flatten)]
         common: CommonArgs,
     },
@@ -203,7 +594,7 @@ pub enum Commands {
         common: CommonArgs,
     },
 
-    /// Interactive file explorer (TUI mode)
+    /// Interactive file explorer with a preview pane (TUI mode)
     #[cfg(feature = "tui")]
     #[command(visible_alias = "tui")]
     Interactive {
@@ -212,9 +603,13 @@ pub enum Commands {
         path: PathBuf,
     },
 
-    /// Save a filesystem snapshot for trend analysis
+    /// Save a filesystem snapshot for trend analysis, or manage saved ones
     #[cfg(feature = "trends")]
+    #[command(args_conflicts_with_subcommands = true)]
     Snapshot {
+        #[command(subcommand)]
+        command: Option<SnapshotCommand>,
+
         /// Root path to snapshot
         #[arg(default_value = ".")]
         path: PathBuf,
@@ -222,6 +617,10 @@ pub enum Commands {
         /// Description for this snapshot
         #[arg(long)]
         description: Option<String>,
+
+        /// Glob of paths to leave out of the snapshot (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
     },
 
     /// Analyze filesystem trends over time
@@ -231,13 +630,56 @@ pub enum Commands {
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Show trends since date
+        /// Only compare snapshots taken since this date
         #[arg(long)]
         since: Option<String>,
 
-        /// Display as ASCII chart
+        /// Add a sparkline of total size over time
         #[arg(long)]
         chart: bool,
+
+        /// Number of largest new and deleted files to list
+        #[arg(long, default_value = "10", value_name = "N")]
+        top: usize,
+
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+
+        /// Fail (and notify) when a threshold is crossed: growth=<size>,
+        /// growth=<percent>%, size=<size> or files=<count> (repeatable)
+        #[arg(long, value_name = "RULE")]
+        alert: Vec<String>,
+
+        /// Send fired alerts to a target: webhook=<url> or desktop (repeatable)
+        #[arg(long, value_name = "TARGET", requires = "alert")]
+        notify: Vec<String>,
+    },
+
+    /// Export the inventory of a path, or only what changed since a time
+    #[cfg(feature = "trends")]
+    Export {
+        /// Root path to export
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Only emit entries added, modified or removed since this time,
+        /// diffed against the newest snapshot taken by then
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Save a snapshot of the exported state, the baseline for the next
+        /// `--since` run
+        #[arg(long)]
+        snapshot: bool,
+
+        /// Glob of paths to leave out of the export (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Output format (ndjson, json)
+        #[arg(long, default_value = "ndjson")]
+        format: String,
     },
 
     /// Generate shell completions
@@ -247,6 +689,9 @@ pub enum Commands {
         shell: Shell,
     },
 
+    /// Print the JSON Schema of entries in --format json and ndjson output
+    Schema,
+
     /// Manage saved query profiles
     Profiles {
         #[command(subcommand)]
@@ -262,11 +707,49 @@ pub enum Commands {
         #[arg(long)]
         path: Option<PathBuf>,
 
+        /// Set a profile parameter, filling its `{{NAME}}` placeholders (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE")]
+        set: Vec<String>,
+
         /// Additional arguments to override profile settings
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
 
+    /// Exclude paths from Time Machine backups, or include them again (macOS)
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommand,
+    },
+
+    /// Enforce size and file-count limits on directories
+    Quota {
+        #[command(subcommand)]
+        command: QuotaCommand,
+    },
+
+    /// Show locally recorded usage statistics (opt-in via preferences.record_stats)
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+
+    /// Manage the metadata cache that speeds up repeated find and size runs
+    #[cfg(feature = "cache")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+
+    /// Find, open and manage git projects: the px commands
+    ///
+    /// Shares px's index and config, so `fexplorer projects open` and
+    /// `px open` see the same projects and frecency.
+    Projects {
+        #[command(subcommand)]
+        command: crate::px::cli::PxCommand,
+    },
+
     /// Watch for filesystem changes (requires watch feature)
     #[cfg(feature = "watch")]
     Watch {
@@ -281,9 +764,34 @@ pub enum Commands {
         /// Output format (ndjson recommended for watch)
         #[arg(long, default_value = "ndjson")]
         format: String,
+
+        /// Where events go: stdout, or syslog (journald/syslog, requires syslog feature)
+        #[arg(long, default_value = "stdout", value_name = "SINK")]
+        output: String,
+
+        /// Also send each event to a target: webhook=<url> or desktop (repeatable)
+        #[arg(long, value_name = "TARGET")]
+        notify: Vec<String>,
+
+        /// Keep the newest snapshot of PATH current from the events instead of
+        /// printing them, saving it as a new snapshot every --snapshot-interval
+        /// (requires trends feature)
+        #[cfg(feature = "trends")]
+        #[arg(long, conflicts_with_all = ["events", "output", "notify"])]
+        update_snapshot: bool,
+
+        /// How often --update-snapshot saves a snapshot, if anything changed
+        #[cfg(feature = "trends")]
+        #[arg(
+            long,
+            default_value = "1h",
+            value_name = "DURATION",
+            requires = "update_snapshot"
+        )]
+        snapshot_interval: String,
     },
 
-    /// Manage plugins (requires plugins feature)
+    /// Manage filter plugins (requires plugins feature)
     #[cfg(feature = "plugins")]
     Plugins {
         #[command(subcommand)]
@@ -307,6 +815,103 @@ pub enum ProfileCommand {
     Init,
 }
 
+/// Usage statistics subcommands
+#[derive(Subcommand, Debug)]
+pub enum StatsCommand {
+    /// Show which commands and profiles you run and how long they take
+    #[command(name = "self")]
+    Show {
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Delete all recorded statistics
+    Reset,
+}
+
+/// Metadata cache subcommands
+#[derive(Subcommand, Debug)]
+#[cfg(feature = "cache")]
+pub enum CacheCommand {
+    /// Delete all cached metadata
+    Clear,
+}
+
+/// Snapshot management subcommands
+#[derive(Subcommand, Debug)]
+#[cfg(feature = "trends")]
+pub enum SnapshotCommand {
+    /// List saved snapshots, oldest first
+    List {
+        /// Only list snapshots of this path
+        path: Option<PathBuf>,
+
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Delete snapshots by id
+    Delete {
+        #[arg(required = true, value_name = "ID")]
+        ids: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupCommand {
+    /// Exclude paths (and everything below them) from backups, like `tmutil addexclusion`
+    Exclude {
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Preview the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Back up previously excluded paths again, like `tmutil removeexclusion`
+    Include {
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Preview the changes without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QuotaCommand {
+    /// Exit non-zero with a report when a directory exceeds its limits
+    #[command(group(ArgGroup::new("limits").required(true).multiple(true)))]
+    Check {
+        /// Directories to check; each is measured separately
+        #[arg(default_value = ".", value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Maximum total size of all files (e.g. 50GB, 2TiB)
+        #[arg(long, group = "limits", value_name = "SIZE")]
+        max_size: Option<String>,
+
+        /// Maximum number of files (e.g. 5000, 1e6)
+        #[arg(long, group = "limits", value_name = "N")]
+        max_files: Option<String>,
+
+        /// Number of largest children to list for directories over quota
+        #[arg(long, default_value = "5", value_name = "N")]
+        top: usize,
+
+        /// Notify a target when a path is over quota: webhook=<url> or desktop (repeatable)
+        #[arg(long, value_name = "TARGET")]
+        notify: Vec<String>,
+
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+}
+
 /// Plugin subcommands
 #[derive(Subcommand, Debug)]
 #[cfg(feature = "plugins")]
@@ -314,6 +919,16 @@ pub enum PluginCommand {
     /// List installed plugins
     List,
 
+    /// Install a WebAssembly filter module (enabled right away)
+    Install {
+        /// Path to the module (.wasm, or .wat text format)
+        path: PathBuf,
+
+        /// Plugin name (default: the file name without extension)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
     /// Enable a plugin
     Enable {
         /// Plugin name
@@ -349,6 +964,148 @@ pub enum Shell {
     Elvish,
 }
 
+/// File selection filters shared by find and the batch commands
+#[derive(Parser, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Name glob patterns (repeatable)
+    #[arg(long = "name")]
+    pub names: Vec<String>,
+
+    /// Regex pattern for names
+    #[arg(long)]
+    pub regex: Option<String>,
+
+    /// File extensions (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub ext: Vec<String>,
+
+    /// Minimum size (e.g., 10KB, 2MiB)
+    #[arg(long)]
+    pub min_size: Option<String>,
+
+    /// Maximum size (e.g., 10MB, 2GiB)
+    #[arg(long)]
+    pub max_size: Option<String>,
+
+    /// Size constraint: +SIZE (at least), -SIZE (at most) or SIZE (exactly), repeatable
+    #[arg(long, value_name = "[+-]SIZE", allow_hyphen_values = true)]
+    pub size: Vec<String>,
+
+    /// Apply the size filters to directories too, by the total size of
+    /// everything under them (otherwise directories always pass)
+    #[arg(long)]
+    pub dir_size: bool,
+
+    /// Modified after date (ISO8601 or YYYY-MM-DD)
+    #[arg(long)]
+    pub after: Option<String>,
+
+    /// Modified before date (ISO8601 or YYYY-MM-DD)
+    #[arg(long)]
+    pub before: Option<String>,
+
+    /// Modified more than this long ago (e.g., 30d, 6mo, 1y)
+    #[arg(long, value_name = "DURATION")]
+    pub older_than: Option<String>,
+
+    /// Modified within this duration (e.g., 2h, 90min, 1w)
+    #[arg(long, value_name = "DURATION")]
+    pub newer_than: Option<String>,
+
+    /// Accessed after date (atime; often stale on relatime/noatime mounts)
+    #[arg(long, value_name = "DATE")]
+    pub accessed_after: Option<String>,
+
+    /// Accessed before date
+    #[arg(long, value_name = "DATE")]
+    pub accessed_before: Option<String>,
+
+    /// Status changed after date (Unix ctime: contents, permissions, owner)
+    #[arg(long, value_name = "DATE")]
+    pub changed_after: Option<String>,
+
+    /// Status changed before date
+    #[arg(long, value_name = "DATE")]
+    pub changed_before: Option<String>,
+
+    /// Created after date (birth time; entries without one never match)
+    #[arg(long, value_name = "DATE")]
+    pub created_after: Option<String>,
+
+    /// Created before date
+    #[arg(long, value_name = "DATE")]
+    pub created_before: Option<String>,
+
+    /// Filter by kind (file, dir, symlink)
+    #[arg(long, value_delimiter = ',')]
+    pub kind: Vec<String>,
+
+    /// Filter by category (source, build, config, docs, media, data, archive, executable)
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Filter by MIME type sniffed from content (image/png, image/*, text; comma-separated)
+    #[cfg(feature = "mime")]
+    #[arg(long, value_delimiter = ',', value_name = "TYPE")]
+    pub mime: Vec<String>,
+
+    /// Only sparse files (less space allocated on disk than their apparent size)
+    #[arg(long)]
+    pub sparse: bool,
+
+    /// Only items excluded from Time Machine backups (macOS)
+    #[arg(long)]
+    pub backup_excluded: bool,
+
+    /// Permission bits: exactly MODE (755, u=rwx,go=rx), all of -MODE, or any of /MODE
+    #[arg(long, value_name = "MODE", allow_hyphen_values = true)]
+    pub perm: Option<String>,
+
+    /// Only entries the current user may write
+    #[arg(long)]
+    pub writable: bool,
+
+    /// Only entries the current user may execute (or search, for directories)
+    #[arg(long)]
+    pub executable: bool,
+
+    /// Only entries anyone may write (same as --perm -002)
+    #[arg(long)]
+    pub world_writable: bool,
+
+    /// Only entries that couldn't be read (permission denied, symlink loops, ...)
+    #[arg(long)]
+    pub errors: bool,
+
+    /// Filter expression, e.g. 'size > 10MB and (ext = log or ext = tmp)'
+    #[arg(long = "where", value_name = "EXPR")]
+    pub where_expr: Option<String>,
+
+    /// Print the traversal settings and compiled filter tree instead of running
+    /// (as JSON with --format json)
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Keep entries for which a shell command succeeds, `{}` standing for the path
+    /// (runs after the other filters)
+    #[arg(long, value_name = "CMD")]
+    pub filter_exec: Option<String>,
+
+    /// How many --filter-exec commands run at once (default: one per CPU)
+    #[arg(long, value_name = "N", requires = "filter_exec")]
+    pub exec_jobs: Option<usize>,
+
+    /// Keep entries for which a Rhai script returns true (`entry` holds the entry's fields)
+    #[cfg(feature = "scripting")]
+    #[arg(long, value_name = "FILE")]
+    pub filter_script: Option<PathBuf>,
+
+    /// Don't apply the enabled filter plugins
+    #[cfg(feature = "plugins-wasm")]
+    #[arg(long)]
+    pub no_plugins: bool,
+}
+
 /// Common arguments shared across commands
 #[derive(Parser, Debug, Clone)]
 pub struct CommonArgs {
@@ -364,23 +1121,75 @@ pub struct CommonArgs {
     #[arg(long)]
     pub no_gitignore: bool,
 
+    /// Skip home directories in /home or /Users owned by other users
+    #[arg(long)]
+    pub skip_other_users: bool,
+
+    /// Prune paths matching a gitignore-style glob during the walk
+    /// (e.g. node_modules, target, '*.log'; repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
     /// Follow symbolic links
     #[arg(long)]
     pub follow_symlinks: bool,
 
-    /// Output format (pretty, json, ndjson, csv)
+    /// List directories before other entries
+    #[arg(long, conflicts_with = "files_first")]
+    pub dirs_first: bool,
+
+    /// List files before other entries
+    #[arg(long)]
+    pub files_first: bool,
+
+    /// Keep at most N entries from any single directory (the first N in sort order)
+    #[arg(long, value_name = "N")]
+    pub per_dir_limit: Option<usize>,
+
+    /// Keep one entry per name, size or hash (the first in sort order), with a count
+    #[arg(long, value_name = "KEY")]
+    pub unique_by: Option<String>,
+
+    /// Output format (pretty, json, ndjson, csv, gha, sarif; ndjson.zst with the ndjson-zst feature)
     #[arg(long, default_value = "pretty")]
     pub format: String,
 
-    /// Columns to display (comma-separated)
+    /// Write the results to PATH instead of stdout (gzip-compressed if it ends in .gz)
+    #[arg(long, visible_alias = "out", value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Columns to display (comma-separated; `@name` expands a column preset)
     #[arg(long, value_delimiter = ',')]
     pub columns: Vec<String>,
 
+    /// Computed output fields for csv/json, e.g. 'path, size/1024 as kb, upper(ext)'
+    #[arg(long, value_name = "EXPR", conflicts_with = "columns")]
+    pub select: Option<String>,
+
+    /// Output that can be diffed across runs and machines: entries by path,
+    /// UTC ISO-8601 times, no owner or other machine-specific fields
+    #[arg(long)]
+    pub reproducible: bool,
+
+    /// Group entries by these expressions and print one row per group
+    #[arg(long, value_name = "EXPR", conflicts_with_all = ["columns", "select"])]
+    pub group_by: Option<String>,
+
+    /// Aggregates per group, e.g. 'count, sum(size), max(mtime)' [default: count]
+    #[arg(long, value_name = "LIST", conflicts_with_all = ["columns", "select"])]
+    pub agg: Option<String>,
+
     /// Number of threads for parallel traversal
     #[cfg(feature = "parallel")]
     #[arg(long, default_value = "4")]
     pub threads: usize,
 
+    /// With several threads, keep entries in the order they were found
+    /// instead of sorting them by path (faster on large trees)
+    #[cfg(feature = "parallel")]
+    #[arg(long)]
+    pub unordered: bool,
+
     /// Show progress bar
     #[cfg(feature = "progress")]
     #[arg(long)]
@@ -390,6 +1199,19 @@ pub struct CommonArgs {
     #[cfg(feature = "templates")]
     #[arg(long)]
     pub template: Option<String>,
+
+    /// Read paths (NUL or newline separated) from FILE instead of walking; '-' for stdin
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// Read paths from stdin instead of walking (same as --files-from -)
+    #[arg(long, conflicts_with = "files_from")]
+    pub stdin: bool,
+
+    /// Use the entries of an earlier `--format json` or `ndjson` export instead
+    /// of walking (find, size, duplicates, grep); '-' for stdin
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["files_from", "stdin"])]
+    pub from_results: Option<PathBuf>,
 }
 
 impl Default for CommonArgs {
@@ -398,38 +1220,150 @@ impl Default for CommonArgs {
             max_depth: None,
             hidden: false,
             no_gitignore: false,
+            skip_other_users: false,
+            exclude: Vec::new(),
             follow_symlinks: false,
+            dirs_first: false,
+            files_first: false,
+            per_dir_limit: None,
+            unique_by: None,
             format: "pretty".to_string(),
+            output: None,
             columns: Vec::new(),
+            select: None,
+            reproducible: false,
+            group_by: None,
+            agg: None,
             #[cfg(feature = "parallel")]
             threads: 4,
+            #[cfg(feature = "parallel")]
+            unordered: false,
             #[cfg(feature = "progress")]
             progress: false,
             #[cfg(feature = "templates")]
             template: None,
+            files_from: None,
+            stdin: false,
+            from_results: None,
         }
     }
 }
 
+fn reject_flag(given: bool, flag: &str, command: &str) -> Result<()> {
+    if given {
+        return Err(FsError::InvalidFormat {
+            format: format!("{} doesn't support {}", command, flag),
+        });
+    }
+    Ok(())
+}
+
 impl CommonArgs {
+    /// Path list source from --files-from/--stdin, if entries shouldn't be walked
+    pub fn path_list_source(&self) -> Option<PathBuf> {
+        if self.stdin {
+            Some(PathBuf::from("-"))
+        } else {
+            self.files_from.clone()
+        }
+    }
+
+    /// Kind to list before everything else (`--dirs-first`/`--files-first`)
+    pub fn first_kind(&self) -> Option<EntryKind> {
+        if self.dirs_first {
+            Some(EntryKind::Dir)
+        } else if self.files_first {
+            Some(EntryKind::File)
+        } else {
+            None
+        }
+    }
+
+    /// Error when `--output` is given to a command that prints a report of its
+    /// own rather than entries
+    pub fn reject_output(&self, command: &str) -> Result<()> {
+        reject_flag(self.output.is_some(), "--output", command)
+    }
+
+    /// Error when `--from-results` is given to a command that doesn't take
+    /// its entries from an export
+    pub fn reject_from_results(&self, command: &str) -> Result<()> {
+        reject_flag(self.from_results.is_some(), "--from-results", command)
+    }
+
+    /// Error when `--reproducible` is given to a command whose output it
+    /// doesn't normalize
+    pub fn reject_reproducible(&self, command: &str) -> Result<()> {
+        reject_flag(self.reproducible, "--reproducible", command)
+    }
+
     pub fn output_format(&self) -> Result<OutputFormat> {
         OutputFormat::from_str(&self.format).ok_or_else(|| FsError::InvalidFormat {
             format: self.format.clone(),
         })
     }
 
+    /// Parsed `--select` list, if computed fields were requested
+    pub fn selection(&self) -> Result<Option<Selection>> {
+        self.select.as_deref().map(Selection::parse).transpose()
+    }
+
+    /// Grouping from `--group-by`/`--agg`, if a grouped summary was requested
+    pub fn group_spec(&self) -> Result<Option<GroupSpec>> {
+        if self.group_by.is_none() && self.agg.is_none() {
+            return Ok(None);
+        }
+        GroupSpec::parse(
+            self.group_by.as_deref(),
+            self.agg.as_deref().unwrap_or("count"),
+        )
+        .map(Some)
+    }
+
+    /// Columns whose metadata has to be filled in: displayed or used by
+    /// `--select`/`--group-by`/`--agg`
+    /// `--unique-by` key, if given
+    pub fn unique_key(&self) -> Result<Option<UniqueKey>> {
+        self.unique_by.as_deref().map(UniqueKey::parse).transpose()
+    }
+
+    pub fn enrich_columns(&self) -> Result<Vec<Column>> {
+        let mut columns = if let Some(spec) = self.group_spec()? {
+            spec.columns()
+        } else {
+            match self.selection()? {
+                Some(selection) => selection.columns(),
+                None => self.columns()?,
+            }
+        };
+        // `--unique-by hash` compares content hashes
+        if self.unique_key()? == Some(UniqueKey::Hash) {
+            columns.push(Column::Hash);
+        }
+        Ok(columns)
+    }
+
     pub fn columns(&self) -> Result<Vec<Column>> {
-        if self.columns.is_empty() {
-            // Default columns
-            return Ok(vec![
-                Column::Path,
-                Column::Size,
-                Column::Mtime,
-                Column::Kind,
-            ]);
+        // `@name` entries expand to a column preset from the config
+        let expanded;
+        let columns = if self.columns.iter().any(|c| c.starts_with('@')) {
+            let preferences = crate::config::Config::load()?.preferences;
+            expanded = preferences.expand_column_presets(&self.columns)?;
+            &expanded
+        } else {
+            &self.columns
+        };
+
+        if columns.is_empty() {
+            // Default columns, plus how many entries each `--unique-by` row stands for
+            let mut defaults = vec![Column::Path, Column::Size, Column::Mtime, Column::Kind];
+            if self.unique_by.is_some() {
+                defaults.push(Column::Count);
+            }
+            return Ok(defaults);
         }
 
-        self.columns
+        columns
             .iter()
             .map(|s| {
                 Column::from_str(s).ok_or_else(|| FsError::InvalidFormat {
@@ -440,6 +1374,106 @@ impl CommonArgs {
     }
 }
 
+/// Insert per-command config defaults into the command line
+///
+/// Each `key = value` from the subcommand's `[defaults.<command>]` table
+/// becomes a `--key=value` argument placed right after the subcommand name,
+/// unless that option was already given on the command line. Returns `None`
+/// when nothing needs to be added, otherwise the arguments to re-parse.
+pub fn apply_command_defaults(
+    args: &[OsString],
+    matches: &ArgMatches,
+    defaults: &HashMap<String, HashMap<String, serde_json::Value>>,
+) -> Result<Option<Vec<OsString>>> {
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        return Ok(None);
+    };
+    let Some(command_defaults) = defaults.get(name) else {
+        return Ok(None);
+    };
+
+    let cli = Cli::command();
+    let Some(command) = cli.find_subcommand(name) else {
+        return Ok(None);
+    };
+
+    let invalid = |message: String| FsError::InvalidFormat {
+        format: format!("[defaults.{}] {}", name, message),
+    };
+
+    let mut keys: Vec<&String> = command_defaults.keys().collect();
+    keys.sort();
+
+    let mut injected = Vec::new();
+    for key in keys {
+        let id = key.replace('-', "_");
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_id() == id.as_str())
+            .ok_or_else(|| invalid(format!("unknown option '{}'", key)))?;
+        let long = arg
+            .get_long()
+            .ok_or_else(|| invalid(format!("'{}' is not an option", key)))?;
+
+        if sub_matches.value_source(&id) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+
+        let value = &command_defaults[key];
+        let is_flag = matches!(arg.get_action(), ArgAction::SetTrue);
+        match value {
+            serde_json::Value::Bool(true) if is_flag => injected.push(format!("--{}", long)),
+            serde_json::Value::Bool(false) if is_flag => {}
+            _ if is_flag => return Err(invalid(format!("'{}' must be true or false", key))),
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    injected.push(format!(
+                        "--{}={}",
+                        long,
+                        default_value(item, key, &invalid)?
+                    ));
+                }
+            }
+            other => injected.push(format!(
+                "--{}={}",
+                long,
+                default_value(other, key, &invalid)?
+            )),
+        }
+    }
+
+    if injected.is_empty() {
+        return Ok(None);
+    }
+
+    // Defaults go right after the subcommand name, before any trailing arguments
+    let names: Vec<&str> = std::iter::once(name)
+        .chain(command.get_all_aliases())
+        .collect();
+    let position = args
+        .iter()
+        .skip(1)
+        .position(|a| a.to_str().is_some_and(|a| names.contains(&a)))
+        .map_or(args.len(), |i| i + 2);
+
+    let mut args = args.to_vec();
+    args.splice(position..position, injected.into_iter().map(OsString::from));
+    Ok(Some(args))
+}
+
+fn default_value(
+    value: &serde_json::Value,
+    key: &str,
+    invalid: &dyn Fn(String) -> FsError,
+) -> Result<String> {
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        serde_json::Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(invalid(format!("unsupported value for '{}'", key))),
+    }
+}
+
 // Helper functions (kept for backwards compatibility)
 
 pub fn parse_sort_key(s: &str) -> Result<SortKey> {
@@ -464,6 +1498,26 @@ pub fn parse_sort_order(s: &str) -> Result<SortOrder> {
     }
 }
 
+/// Parse a multi-key sort like `kind,size:desc,name`
+///
+/// Keys without an explicit `:asc`/`:desc` use `default_order` (`--order`).
+pub fn parse_sort_fields(s: &str, default_order: SortOrder) -> Result<Vec<SortField>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (key, order) = match part.split_once(':') {
+                Some((key, order)) => (key, parse_sort_order(order)?),
+                None => (part, default_order),
+            };
+            Ok(SortField {
+                key: parse_sort_key(key)?,
+                order,
+            })
+        })
+        .collect()
+}
+
 pub fn parse_entry_kinds(kinds: &[String]) -> Result<Vec<EntryKind>> {
     kinds
         .iter()
@@ -498,6 +1552,34 @@ mod tests {
         assert!(parse_sort_key("invalid").is_err());
     }
 
+    #[test]
+    fn test_parse_sort_fields() {
+        let fields = parse_sort_fields("kind, size:desc,name", SortOrder::Asc).unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                SortField {
+                    key: SortKey::Kind,
+                    order: SortOrder::Asc
+                },
+                SortField {
+                    key: SortKey::Size,
+                    order: SortOrder::Desc
+                },
+                SortField {
+                    key: SortKey::Name,
+                    order: SortOrder::Asc
+                },
+            ]
+        );
+        assert_eq!(
+            parse_sort_fields("mtime", SortOrder::Desc).unwrap()[0].order,
+            SortOrder::Desc
+        );
+        assert!(parse_sort_fields("size:sideways", SortOrder::Asc).is_err());
+        assert!(parse_sort_fields("colour", SortOrder::Asc).is_err());
+    }
+
     #[test]
     fn test_parse_sort_order() {
         assert!(matches!(parse_sort_order("asc").unwrap(), SortOrder::Asc));
@@ -509,6 +1591,56 @@ mod tests {
         assert!(parse_sort_order("invalid").is_err());
     }
 
+    #[test]
+    fn test_apply_command_defaults() {
+        let defaults: HashMap<String, HashMap<String, serde_json::Value>> = toml::from_str(
+            r#"
+            [find]
+            hidden = true
+            no-gitignore = false
+            max_depth = 2
+            ext = ["rs", "toml"]
+            "#,
+        )
+        .unwrap();
+
+        let args: Vec<OsString> = ["fexplorer", "-q", "find", "src", "--max-depth", "5"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        let matches = Cli::command().get_matches_from(&args);
+        let args = apply_command_defaults(&args, &matches, &defaults)
+            .unwrap()
+            .unwrap();
+
+        // The explicit --max-depth wins and false flags add nothing
+        assert_eq!(
+            args,
+            [
+                "fexplorer",
+                "-q",
+                "find",
+                "--ext=rs",
+                "--ext=toml",
+                "--hidden",
+                "src",
+                "--max-depth",
+                "5"
+            ]
+            .map(OsString::from)
+        );
+
+        let matches = Cli::command().get_matches_from(["fexplorer", "list"]);
+        assert!(apply_command_defaults(&args, &matches, &defaults)
+            .unwrap()
+            .is_none());
+
+        let bad: HashMap<String, HashMap<String, serde_json::Value>> =
+            toml::from_str("[find]\nno_such_option = 1").unwrap();
+        let matches = Cli::command().get_matches_from(["fexplorer", "find"]);
+        assert!(apply_command_defaults(&args, &matches, &bad).is_err());
+    }
+
     #[test]
     fn test_parse_entry_kinds() {
         let kinds = parse_entry_kinds(&["file".to_string(), "dir".to_string()]).unwrap();
@@ -1,4 +1,6 @@
 use crate::errors::{FsError, Result};
+use crate::models::SortKey;
+use crate::plain;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -13,6 +15,77 @@ pub struct Config {
     /// Saved query profiles
     #[serde(default)]
     pub profiles: HashMap<String, QueryProfile>,
+    /// Per-subcommand option defaults (`[defaults.find] hidden = true`),
+    /// applied unless the option is given on the command line
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: HashMap<String, HashMap<String, serde_json::Value>>,
+    /// Shell commands run before or after a subcommand
+    /// (`[hooks] post_duplicates = "notify-send ..."`, see [`crate::hooks`])
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hooks: HashMap<String, String>,
+    /// Interactive mode settings
+    #[serde(default, skip_serializing_if = "TuiConfig::is_default")]
+    pub tui: TuiConfig,
+}
+
+/// Interactive mode (`fexplorer tui`) settings, the `[tui]` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Navigation key set
+    pub keys: KeyPreset,
+    /// Keys per action, replacing the preset's (`copy = "y"`,
+    /// `quit = "q, ctrl+q"`); see `?` in the TUI for the action names
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub bindings: HashMap<String, String>,
+    pub theme: ThemeName,
+    /// Show hidden files at startup
+    pub show_hidden: bool,
+    /// List directories first at startup
+    pub dirs_first: bool,
+    /// Sort order at startup
+    pub sort: SortKey,
+}
+
+/// Navigation key sets for the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPreset {
+    /// Arrow keys plus `h` `j` `k` `l`
+    Vim,
+    /// Arrow keys only
+    Arrows,
+}
+
+/// Color themes for the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// For dark terminal backgrounds
+    Dark,
+    /// For light terminal backgrounds
+    Light,
+    /// No colors, only bold and reverse video
+    Mono,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            keys: KeyPreset::Vim,
+            bindings: HashMap::new(),
+            theme: ThemeName::Dark,
+            show_hidden: false,
+            dirs_first: true,
+            sort: SortKey::Name,
+        }
+    }
+}
+
+impl TuiConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// User preferences
@@ -30,8 +103,20 @@ pub struct Preferences {
     /// Respect gitignore by default
     #[serde(default = "default_true")]
     pub respect_gitignore: bool,
+    /// Record local command usage statistics (see `fexplorer stats self`)
+    #[serde(default)]
+    pub record_stats: bool,
+    /// Named column lists for `--columns @name` (override the built-in presets)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub column_presets: HashMap<String, Vec<String>>,
 }
 
+/// Column presets available without any configuration
+const BUILTIN_COLUMN_PRESETS: &[(&str, &[&str])] = &[
+    ("audit", &["path", "size", "owner", "perms", "mtime"]),
+    ("minimal", &["path"]),
+];
+
 fn default_format() -> String {
     "pretty".to_string()
 }
@@ -51,7 +136,56 @@ impl Default for Preferences {
             color: true,
             threads: 4,
             respect_gitignore: true,
+            record_stats: false,
+            column_presets: HashMap::new(),
+        }
+    }
+}
+
+impl Preferences {
+    /// Columns of a named preset, from the config or the built-ins
+    pub fn column_preset(&self, name: &str) -> Option<Vec<String>> {
+        self.column_presets.get(name).cloned().or_else(|| {
+            BUILTIN_COLUMN_PRESETS
+                .iter()
+                .find(|(preset, _)| *preset == name)
+                .map(|(_, columns)| columns.iter().map(|c| c.to_string()).collect())
+        })
+    }
+
+    /// Names of all available column presets, sorted
+    pub fn column_preset_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_COLUMN_PRESETS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .chain(self.column_presets.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Replace `@name` entries in a column list with the preset's columns
+    pub fn expand_column_presets(&self, columns: &[String]) -> Result<Vec<String>> {
+        let mut expanded = Vec::with_capacity(columns.len());
+        for column in columns {
+            match column.strip_prefix('@') {
+                Some(name) => {
+                    let preset =
+                        self.column_preset(name)
+                            .ok_or_else(|| FsError::InvalidFormat {
+                                format: format!(
+                                    "Unknown column preset '@{}' (available: {})",
+                                    name,
+                                    self.column_preset_names().join(", ")
+                                ),
+                            })?;
+                    expanded.extend(preset);
+                }
+                None => expanded.push(column.clone()),
+            }
         }
+        Ok(expanded)
     }
 }
 
@@ -66,6 +200,189 @@ pub struct QueryProfile {
     /// Command arguments as key-value pairs
     #[serde(default)]
     pub args: HashMap<String, serde_json::Value>,
+    /// Parameters and their defaults, filling `{{name}}` placeholders in
+    /// `args` (set with `fexplorer run <profile> --set name=value`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, serde_json::Value>,
+    /// Stages of a `pipeline` profile, run in order on the same entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<PipelineStage>,
+}
+
+/// One stage of a pipeline profile (`find`, `hash`, `sort`, `export`, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStage {
+    pub command: String,
+    #[serde(default)]
+    pub args: HashMap<String, serde_json::Value>,
+}
+
+impl QueryProfile {
+    /// `args` with placeholders filled from `set` (`name=value`), falling back
+    /// to the declared defaults
+    ///
+    /// A value must fit its default: numbers for numeric defaults, `true` or
+    /// `false` for booleans, and a comma-separated list for list defaults. An
+    /// argument that is a lone placeholder takes the parameter's value as is
+    /// (so `top = "{{n}}"` stays a number); elsewhere it's spliced in as text.
+    pub fn resolve_args(&self, set: &[String]) -> Result<HashMap<String, serde_json::Value>> {
+        let values = self.param_values(set)?;
+        self.fill(&self.args, &values)
+    }
+
+    /// `stages` with placeholders filled as for [`Self::resolve_args`]
+    pub fn resolve_stages(&self, set: &[String]) -> Result<Vec<PipelineStage>> {
+        let values = self.param_values(set)?;
+        self.stages
+            .iter()
+            .map(|stage| {
+                Ok(PipelineStage {
+                    command: stage.command.clone(),
+                    args: self.fill(&stage.args, &values)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Parameter values: the defaults, with `set` applied
+    fn param_values(&self, set: &[String]) -> Result<HashMap<String, serde_json::Value>> {
+        let mut values = self.params.clone();
+        for assignment in set {
+            let (name, value) = assignment.split_once('=').ok_or_else(|| {
+                invalid_param(format!("--set expects NAME=VALUE, got '{}'", assignment))
+            })?;
+            let default = self.params.get(name).ok_or_else(|| {
+                invalid_param(format!(
+                    "unknown parameter {} (declared: {})",
+                    name,
+                    self.declared()
+                ))
+            })?;
+            values.insert(name.to_string(), typed_param(name, value, default)?);
+        }
+        Ok(values)
+    }
+
+    fn fill(
+        &self,
+        args: &HashMap<String, serde_json::Value>,
+        values: &HashMap<String, serde_json::Value>,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        args.iter()
+            .map(|(key, value)| Ok((key.clone(), self.substitute(value, values)?)))
+            .collect()
+    }
+
+    fn substitute(
+        &self,
+        value: &serde_json::Value,
+        values: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        use serde_json::Value;
+
+        let lookup = |name: &str| {
+            values.get(name.trim()).ok_or_else(|| {
+                invalid_param(format!(
+                    "{} is used in args but not declared (declared: {})",
+                    name.trim(),
+                    self.declared()
+                ))
+            })
+        };
+
+        match value {
+            Value::String(text) => {
+                let whole = text
+                    .strip_prefix("{{")
+                    .and_then(|rest| rest.strip_suffix("}}"))
+                    .filter(|name| !name.contains("{{") && !name.contains("}}"));
+                if let Some(name) = whole {
+                    return lookup(name).cloned();
+                }
+
+                let mut result = String::new();
+                let mut rest = text.as_str();
+                while let Some(start) = rest.find("{{") {
+                    let end = rest[start..]
+                        .find("}}")
+                        .ok_or_else(|| invalid_param(format!("unclosed '{{{{' in '{}'", text)))?;
+                    result.push_str(&rest[..start]);
+                    match lookup(&rest[start + 2..start + end])? {
+                        Value::String(s) => result.push_str(s),
+                        Value::Array(items) => {
+                            let items: Vec<String> = items
+                                .iter()
+                                .map(|item| match item {
+                                    Value::String(s) => s.clone(),
+                                    other => other.to_string(),
+                                })
+                                .collect();
+                            result.push_str(&items.join(","));
+                        }
+                        other => result.push_str(&other.to_string()),
+                    }
+                    rest = &rest[start + end + 2..];
+                }
+                result.push_str(rest);
+                Ok(Value::String(result))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.substitute(item, values))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Declared parameter names, sorted, for error messages
+    fn declared(&self) -> String {
+        let mut names: Vec<&str> = self.params.keys().map(String::as_str).collect();
+        if names.is_empty() {
+            return "none".to_string();
+        }
+        names.sort_unstable();
+        names.join(", ")
+    }
+}
+
+/// Parse a `--set` value into the type of the parameter's default
+fn typed_param(name: &str, value: &str, default: &serde_json::Value) -> Result<serde_json::Value> {
+    use serde_json::Value;
+
+    let mismatch = |expected: &str| {
+        invalid_param(format!(
+            "{} expects {} (default {}), got '{}'",
+            name, expected, default, value
+        ))
+    };
+    match default {
+        Value::Number(_) => {
+            let number = value
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| value.parse::<f64>().map(Value::from));
+            number.map_err(|_| mismatch("a number"))
+        }
+        Value::Bool(_) => value
+            .parse::<bool>()
+            .map(Value::Bool)
+            .map_err(|_| mismatch("true or false")),
+        Value::Array(_) => Ok(Value::Array(
+            value
+                .split(',')
+                .map(|item| Value::String(item.trim().to_string()))
+                .filter(|item| item.as_str() != Some(""))
+                .collect(),
+        )),
+        _ => Ok(Value::String(value.to_string())),
+    }
+}
+
+fn invalid_param(message: String) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("Profile parameter: {}", message),
+    }
 }
 
 impl Config {
@@ -154,6 +471,8 @@ impl Config {
                     args.insert("min_size".to_string(), serde_json::json!("1MB"));
                     args
                 },
+                params: HashMap::new(),
+                stages: Vec::new(),
             },
         );
 
@@ -164,13 +483,20 @@ impl Config {
                 command: "find".to_string(),
                 args: {
                     let mut args = HashMap::new();
-                    args.insert(
-                        "ext".to_string(),
+                    args.insert("ext".to_string(), serde_json::json!("{{lang}}"));
+                    args.insert("after".to_string(), serde_json::json!("{{since}}"));
+                    args
+                },
+                params: {
+                    let mut params = HashMap::new();
+                    params.insert(
+                        "lang".to_string(),
                         serde_json::json!(["rs", "go", "ts", "py"]),
                     );
-                    args.insert("after".to_string(), serde_json::json!("7 days ago"));
-                    args
+                    params.insert("since".to_string(), serde_json::json!("7 days ago"));
+                    params
                 },
+                stages: Vec::new(),
             },
         );
 
@@ -185,6 +511,8 @@ impl Config {
                     args.insert("kind".to_string(), serde_json::json!(["file"]));
                     args
                 },
+                params: HashMap::new(),
+                stages: Vec::new(),
             },
         );
 
@@ -216,6 +544,32 @@ pub struct PxConfig {
     /// Optional Obsidian vault path for note integration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub obsidian_vault: Option<PathBuf>,
+
+    /// Where `px open` opens a shell in the project
+    #[serde(default)]
+    pub terminal: Terminal,
+
+    /// Commands to start in a project's new tmux session, by project name;
+    /// the first runs in the first window, each other one in a window of its own
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub startup: HashMap<String, Vec<String>>,
+
+    /// Templates for `px new`, by name: a directory or a git URL
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, String>,
+}
+
+/// Terminal integration for `px open`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Terminal {
+    /// Create or attach to a tmux session named after the project
+    #[default]
+    Tmux,
+    /// A new iTerm2 window, through AppleScript (macOS)
+    Iterm,
+    /// Only the editor
+    None,
 }
 
 fn default_scan_dirs() -> Vec<PathBuf> {
@@ -237,6 +591,9 @@ impl Default for PxConfig {
             scan_dirs: default_scan_dirs(),
             default_editor: default_editor(),
             obsidian_vault: None,
+            terminal: Terminal::default(),
+            startup: HashMap::new(),
+            templates: HashMap::new(),
         }
     }
 }
@@ -284,11 +641,13 @@ impl PxConfig {
 
     /// Get config file path (~/.config/px/config.toml)
     pub fn config_file_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir().ok_or_else(|| FsError::InvalidFormat {
-            format: "Could not determine config directory".to_string(),
-        })?;
+        Ok(crate::px::paths::config_dir()?.join("config.toml"))
+    }
 
-        Ok(config_dir.join("px").join("config.toml"))
+    /// Directory whose subdirectories are `px new` templates (~/.config/px/templates)
+    pub fn templates_dir() -> Result<PathBuf> {
+        let config_file = Self::config_file_path()?;
+        Ok(config_file.with_file_name("templates"))
     }
 
     /// Initialize default px config with helpful comments
@@ -304,11 +663,18 @@ impl PxConfig {
         let config = Self::default();
         config.save()?;
 
-        println!("✓ Created px config at: {}", config_path.display());
+        println!(
+            "{}Created px config at: {}",
+            plain::pick("✓ ", ""),
+            config_path.display()
+        );
         println!();
         println!("Edit this file to customize:");
         println!("  - scan_dirs: directories to search for projects");
         println!("  - default_editor: editor command (code, cursor, vim, etc.)");
+        println!("  - terminal: tmux, iterm or none, for `px open`");
+        println!("  - [startup]: commands for new tmux sessions, by project name");
+        println!("  - [templates]: directories or git URLs for `px new`, by name");
         println!("  - obsidian_vault: optional Obsidian vault path");
 
         Ok(())
@@ -326,6 +692,85 @@ mod tests {
         assert!(config.preferences.color);
         assert_eq!(config.preferences.threads, 4);
         assert!(config.preferences.respect_gitignore);
+        assert!(!config.preferences.record_stats);
+    }
+
+    #[test]
+    fn test_tui_config() {
+        let toml_str = r#"
+            [tui]
+            keys = "arrows"
+            theme = "light"
+            sort = "mtime"
+
+            [tui.bindings]
+            delete = "x, del"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.tui.keys, KeyPreset::Arrows);
+        assert_eq!(config.tui.theme, ThemeName::Light);
+        assert_eq!(config.tui.sort, SortKey::Mtime);
+        assert!(config.tui.dirs_first);
+        assert_eq!(config.tui.bindings["delete"], "x, del");
+
+        // Defaults stay out of a saved config
+        let saved = toml::to_string(&Config::default()).unwrap();
+        assert!(!saved.contains("[tui]"), "{}", saved);
+
+        assert!(toml::from_str::<Config>("[tui]\ntheme = \"neon\"").is_err());
+    }
+
+    #[test]
+    fn test_px_config() {
+        let toml_str = r#"
+            scan_dirs = ["/src"]
+            terminal = "iterm"
+
+            [startup]
+            api = ["docker compose up -d", "cargo watch -x run"]
+
+            [templates]
+            rust-cli = "~/templates/rust-cli"
+        "#;
+        let config: PxConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.terminal, Terminal::Iterm);
+        assert_eq!(config.startup["api"].len(), 2);
+        assert_eq!(config.templates["rust-cli"], "~/templates/rust-cli");
+        assert_eq!(config.default_editor, "code");
+
+        let config: PxConfig = toml::from_str("").unwrap();
+        assert_eq!(config.terminal, Terminal::Tmux);
+        assert!(config.startup.is_empty());
+        assert!(config.templates.is_empty());
+    }
+
+    #[test]
+    fn test_column_presets() {
+        let toml_str = r#"
+            [preferences.column_presets]
+            minimal = ["name"]
+            review = ["path", "owner"]
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let prefs = &config.preferences;
+
+        let expand = |cols: &[&str]| {
+            let cols: Vec<String> = cols.iter().map(|c| c.to_string()).collect();
+            prefs.expand_column_presets(&cols)
+        };
+
+        assert_eq!(
+            expand(&["@audit"]).unwrap(),
+            vec!["path", "size", "owner", "perms", "mtime"]
+        );
+        // Config presets override built-ins and mix with plain columns
+        assert_eq!(expand(&["@minimal", "size"]).unwrap(), vec!["name", "size"]);
+        assert_eq!(expand(&["@review"]).unwrap(), vec!["path", "owner"]);
+        assert!(expand(&["@nope"]).is_err());
+        assert_eq!(
+            prefs.column_preset_names(),
+            vec!["audit", "minimal", "review"]
+        );
     }
 
     #[test]
@@ -337,6 +782,8 @@ mod tests {
                 description: Some("Test profile".to_string()),
                 command: "list".to_string(),
                 args: HashMap::new(),
+                params: HashMap::new(),
+                stages: Vec::new(),
             },
         );
 
@@ -365,4 +812,71 @@ mod tests {
         assert_eq!(config.preferences.threads, 8);
         assert!(config.profiles.contains_key("example"));
     }
+
+    #[test]
+    fn test_profile_params() {
+        let toml_str = r#"
+            command = "size"
+            args = { ext = "{{lang}}", name = "*.{{lang}}", top = "{{n}}", after = "{{since}}" }
+            params = { lang = "rs", n = 10, since = "7 days ago" }
+        "#;
+        let profile: QueryProfile = toml::from_str(toml_str).unwrap();
+
+        let args = profile.resolve_args(&[]).unwrap();
+        assert_eq!(args["ext"], serde_json::json!("rs"));
+        assert_eq!(args["top"], serde_json::json!(10));
+
+        let set = ["lang=go".to_string(), "n=3".to_string()];
+        let args = profile.resolve_args(&set).unwrap();
+        assert_eq!(args["ext"], serde_json::json!("go"));
+        assert_eq!(args["name"], serde_json::json!("*.go"));
+        assert_eq!(args["top"], serde_json::json!(3));
+        assert_eq!(args["after"], serde_json::json!("7 days ago"));
+
+        assert!(profile.resolve_args(&["n=many".to_string()]).is_err());
+        assert!(profile.resolve_args(&["colour=red".to_string()]).is_err());
+        assert!(profile.resolve_args(&["lang".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_profile() {
+        let toml_str = r#"
+            [profiles.audit]
+            command = "pipeline"
+            params = { dir = "logs" }
+            stages = [
+                { command = "find", args = { ext = ["log"], path = "{{dir}}" } },
+                { command = "hash" },
+                { command = "export", args = { format = "sqlite", out = "{{dir}}.db" } },
+            ]
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let profile = config.get_profile("audit").unwrap();
+
+        let stages = profile
+            .resolve_stages(&["dir=/var/log".to_string()])
+            .unwrap();
+        let commands: Vec<&str> = stages.iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, ["find", "hash", "export"]);
+        assert_eq!(stages[0].args["path"], serde_json::json!("/var/log"));
+        assert_eq!(stages[2].args["out"], serde_json::json!("/var/log.db"));
+        assert!(stages[1].args.is_empty());
+    }
+
+    #[test]
+    fn test_profile_params_list_and_undeclared() {
+        let toml_str = r#"
+            command = "find"
+            args = { ext = "{{langs}}", where = "name ~ '{{prefix}}*'" }
+            params = { langs = ["rs", "toml"] }
+        "#;
+        let profile: QueryProfile = toml::from_str(toml_str).unwrap();
+        let err = profile.resolve_args(&[]).unwrap_err().to_string();
+        assert!(err.contains("prefix"), "{}", err);
+
+        let mut profile = profile;
+        profile.args.remove("where");
+        let args = profile.resolve_args(&["langs=py, go".to_string()]).unwrap();
+        assert_eq!(args["ext"], serde_json::json!(["py", "go"]));
+    }
 }
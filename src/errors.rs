@@ -46,6 +46,12 @@ pub enum FsError {
     #[error("Watch error: {0}")]
     Watch(String),
 
+    #[error("Notification error: {0}")]
+    Notify(String),
+
+    #[error("Hook failed: {0}")]
+    Hook(String),
+
     #[error("No entries found matching criteria")]
     NoEntriesFound,
 
@@ -0,0 +1,750 @@
+//! Archives of query results for `fexplorer archive`
+//!
+//! Unlike `pack`, which archives files under their full paths and can remove
+//! the originals, this stores exactly the selected files and directories
+//! under their paths relative to the search root, ready to be unpacked
+//! elsewhere. Symlinks are left out.
+//!
+//! Zip archives are written here directly: deflate from flate2, with sizes
+//! in a data descriptor after each file so nothing is buffered. Files of
+//! 4 GiB or more, offsets past 4 GiB and more than 65535 entries take the
+//! Zip64 extensions; smaller archives are plain zip 2.0.
+
+use crate::errors::{FsError, Result};
+use crate::fs::pack::{archive_name, Compression};
+use crate::models::{Entry, EntryKind};
+use crate::timings;
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Container and compression of an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = FsError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar" => Ok(ArchiveFormat::Tar),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            "tar.zst" | "tzst" => Ok(ArchiveFormat::TarZst),
+            _ => Err(FsError::InvalidFormat {
+                format: format!(
+                    "Unknown archive format: {} (use zip, tar, tar.gz or tar.zst)",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl ArchiveFormat {
+    /// The named format, or else the one `output`'s extension implies
+    pub fn for_output(name: Option<&str>, output: &Path) -> Result<Self> {
+        match name {
+            Some(name) => name.parse(),
+            None => Self::from_path(output),
+        }
+    }
+
+    /// From the archive's extension: `.zip`, `.tar`, `.tar.gz`/`.tgz` or `.tar.zst`/`.tzst`
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let is_zip = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if is_zip {
+            return Ok(ArchiveFormat::Zip);
+        }
+        match Compression::from_path(path) {
+            Ok(Compression::None) => Ok(ArchiveFormat::Tar),
+            Ok(Compression::Gzip) => Ok(ArchiveFormat::TarGz),
+            Ok(Compression::Zstd) => Ok(ArchiveFormat::TarZst),
+            Err(_) => Err(FsError::InvalidFormat {
+                format: format!(
+                    "Unsupported archive type: {} (use .zip, .tar, .tar.gz or .tar.zst, or --format)",
+                    path.display()
+                ),
+            }),
+        }
+    }
+}
+
+/// What went into an archive
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    pub files: usize,
+    pub dirs: usize,
+    /// Total size of the files before compression
+    pub total_size: u64,
+}
+
+/// Name of `path` inside the archive: relative to the deepest of `roots` it
+/// is under, or a root file's own name
+///
+/// Paths under no root keep their normal components, as `pack` stores them.
+pub fn relative_name(path: &Path, roots: &[PathBuf]) -> String {
+    let relative = roots
+        .iter()
+        .filter_map(|root| Some((root, path.strip_prefix(root).ok()?)))
+        .max_by_key(|(root, _)| root.components().count())
+        .map(|(_, relative)| relative);
+    match relative {
+        Some(relative) if relative.as_os_str().is_empty() => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        Some(relative) => archive_name(relative),
+        None => archive_name(path),
+    }
+}
+
+/// Write the files and directories among `entries` into a new archive
+///
+/// Roots themselves aren't stored as directories, only what's under them.
+/// `on_file` is called with each file's size once it's written.
+pub fn create_archive(
+    entries: &[Entry],
+    roots: &[PathBuf],
+    output: &Path,
+    format: ArchiveFormat,
+    level: Option<i32>,
+    mut on_file: impl FnMut(u64),
+) -> Result<ArchiveSummary> {
+    let _timer = timings::phase("archive");
+
+    if output.exists() {
+        return Err(FsError::InvalidFormat {
+            format: format!("Archive already exists: {}", output.display()),
+        });
+    }
+    let members: Vec<(&Entry, String)> = entries
+        .iter()
+        .filter(|e| match e.kind {
+            EntryKind::File => true,
+            EntryKind::Dir => !roots.contains(&e.path),
+            _ => false,
+        })
+        .map(|e| (e, relative_name(&e.path, roots)))
+        .filter(|(_, name)| !name.is_empty())
+        .collect();
+
+    let file = File::create(output).map_err(|e| FsError::PathAccess {
+        path: output.to_path_buf(),
+        source: e,
+    })?;
+    let writer = BufWriter::new(file);
+    let result = match format {
+        ArchiveFormat::Zip => write_zip(&members, writer, level, &mut on_file),
+        ArchiveFormat::Tar => write_tar(&members, writer, &mut on_file),
+        ArchiveFormat::TarGz => {
+            let level = flate2::Compression::new(level.unwrap_or(6).clamp(0, 9) as u32);
+            let encoder = flate2::write::GzEncoder::new(writer, level);
+            write_tar(&members, encoder, &mut on_file)
+        }
+        ArchiveFormat::TarZst => zstd::Encoder::new(writer, level.unwrap_or(3))
+            .map_err(FsError::from)
+            .and_then(|encoder| write_tar(&members, encoder.auto_finish(), &mut on_file)),
+    };
+
+    if result.is_err() {
+        // Don't leave a truncated archive behind
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+fn write_tar<W: Write>(
+    members: &[(&Entry, String)],
+    writer: W,
+    on_file: &mut impl FnMut(u64),
+) -> Result<ArchiveSummary> {
+    let mut builder = tar::Builder::new(writer);
+    let mut summary = ArchiveSummary::default();
+
+    for (entry, name) in members {
+        if entry.kind == EntryKind::Dir {
+            builder.append_dir(name, &entry.path)?;
+            summary.dirs += 1;
+            continue;
+        }
+        let mut source = File::open(&entry.path).map_err(|e| FsError::PathAccess {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        let size = source.metadata()?.len();
+        builder.append_file(name, &mut source)?;
+        summary.files += 1;
+        summary.total_size += size;
+        on_file(size);
+    }
+
+    // Dropping the encoder writes the compression trailer
+    builder.into_inner()?.flush()?;
+    Ok(summary)
+}
+
+fn write_zip<W: Write>(
+    members: &[(&Entry, String)],
+    writer: W,
+    level: Option<i32>,
+    on_file: &mut impl FnMut(u64),
+) -> Result<ArchiveSummary> {
+    let level = flate2::Compression::new(level.unwrap_or(6).clamp(0, 9) as u32);
+    let mut zip = ZipWriter::new(writer);
+    let mut summary = ArchiveSummary::default();
+
+    for (entry, name) in members {
+        let metadata = fs::metadata(&entry.path).map_err(|e| FsError::PathAccess {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        let modified = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or(entry.mtime);
+        let mode = unix_mode(&metadata);
+
+        if entry.kind == EntryKind::Dir {
+            zip.add_dir(name, modified, mode)?;
+            summary.dirs += 1;
+            continue;
+        }
+        let source = File::open(&entry.path).map_err(|e| FsError::PathAccess {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        let size = zip.add_file(
+            name,
+            BufReader::new(source),
+            metadata.len(),
+            modified,
+            mode,
+            level,
+        )?;
+        summary.files += 1;
+        summary.total_size += size;
+        on_file(size);
+    }
+
+    zip.finish()?.flush()?;
+    Ok(summary)
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode()
+}
+
+#[cfg(not(unix))]
+fn unix_mode(metadata: &fs::Metadata) -> u32 {
+    let kind = if metadata.is_dir() {
+        0o040755
+    } else {
+        0o100644
+    };
+    if metadata.permissions().readonly() {
+        kind & !0o222
+    } else {
+        kind
+    }
+}
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR: u32 = 0x0807_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x0606_4b50;
+const ZIP64_END_LOCATOR: u32 = 0x0706_4b50;
+/// Extra field holding the 64-bit sizes and offset
+const ZIP64_EXTRA: u16 = 0x0001;
+/// Zip 2.0, the version that introduced deflate
+const VERSION_NEEDED: u16 = 20;
+/// Zip 4.5, the version that introduced Zip64
+const VERSION_ZIP64: u16 = 45;
+/// Made on Unix (high byte 3), so readers take permissions from the
+/// external attributes
+const VERSION_MADE_BY: u16 = (3 << 8) | VERSION_ZIP64;
+/// Files this large get Zip64 sizes up front, leaving room for deflate to
+/// grow incompressible data
+const ZIP64_THRESHOLD: u64 = u32::MAX as u64 - (1 << 24);
+/// Stands in for a 32-bit field whose value is in the Zip64 extra field
+const ZIP64_MARKER: u32 = u32::MAX;
+/// Sizes and CRC follow the data
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+/// Names are UTF-8
+const FLAG_UTF8: u16 = 1 << 11;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// Streaming zip writer
+struct ZipWriter<W: Write> {
+    out: CountingWriter<W>,
+    central: Vec<u8>,
+    entries: usize,
+}
+
+/// What the central directory repeats about an entry
+struct ZipRecord<'a> {
+    name: &'a str,
+    flags: u16,
+    method: u16,
+    modified: DateTime<Utc>,
+    crc: u32,
+    compressed: u64,
+    size: u64,
+    mode: u32,
+    offset: u64,
+    /// The local header and data descriptor use Zip64 sizes
+    zip64: bool,
+}
+
+impl<W: Write> ZipWriter<W> {
+    fn new(out: W) -> Self {
+        Self {
+            out: CountingWriter {
+                inner: out,
+                count: 0,
+            },
+            central: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    /// Deflate `source`, expected to hold `size_hint` bytes, into the
+    /// archive; returns its uncompressed size
+    fn add_file(
+        &mut self,
+        name: &str,
+        source: impl Read,
+        size_hint: u64,
+        modified: DateTime<Utc>,
+        mode: u32,
+        level: flate2::Compression,
+    ) -> Result<u64> {
+        let offset = self.out.count;
+        let flags = FLAG_DATA_DESCRIPTOR | FLAG_UTF8;
+        let zip64 = size_hint >= ZIP64_THRESHOLD;
+        self.local_header(name, flags, METHOD_DEFLATED, modified, zip64)?;
+
+        let start = self.out.count;
+        let mut source = flate2::CrcReader::new(source);
+        let mut encoder = flate2::write::DeflateEncoder::new(&mut self.out, level);
+        let size = io::copy(&mut source, &mut encoder)?;
+        encoder.finish()?;
+        let crc = source.crc().sum();
+        let compressed = self.out.count - start;
+
+        self.out.write_all(&DATA_DESCRIPTOR.to_le_bytes())?;
+        self.out.write_all(&crc.to_le_bytes())?;
+        if zip64 {
+            self.out.write_all(&compressed.to_le_bytes())?;
+            self.out.write_all(&size.to_le_bytes())?;
+        } else if compressed.max(size) >= u64::from(ZIP64_MARKER) {
+            // The descriptor's 32-bit sizes can't hold it any more
+            return Err(FsError::InvalidFormat {
+                format: format!("{} grew past 4 GiB while being archived", name),
+            });
+        } else {
+            self.out.write_all(&(compressed as u32).to_le_bytes())?;
+            self.out.write_all(&(size as u32).to_le_bytes())?;
+        }
+        self.central_header(&ZipRecord {
+            name,
+            flags,
+            method: METHOD_DEFLATED,
+            modified,
+            crc,
+            compressed,
+            size,
+            mode,
+            offset,
+            zip64,
+        })?;
+        Ok(size)
+    }
+
+    fn add_dir(&mut self, name: &str, modified: DateTime<Utc>, mode: u32) -> Result<()> {
+        let name = format!("{}/", name);
+        let offset = self.out.count;
+        self.local_header(&name, FLAG_UTF8, METHOD_STORED, modified, false)?;
+        self.central_header(&ZipRecord {
+            name: &name,
+            flags: FLAG_UTF8,
+            method: METHOD_STORED,
+            modified,
+            crc: 0,
+            compressed: 0,
+            size: 0,
+            mode,
+            offset,
+            zip64: false,
+        })
+    }
+
+    /// Local header with CRC and sizes left as 0: they follow the data
+    ///
+    /// With `zip64` the sizes are marked as living in a (zeroed) Zip64 extra
+    /// field, which tells readers the data descriptor has 64-bit sizes.
+    fn local_header(
+        &mut self,
+        name: &str,
+        flags: u16,
+        method: u16,
+        modified: DateTime<Utc>,
+        zip64: bool,
+    ) -> Result<()> {
+        let (time, date) = dos_datetime(modified);
+        let version = if zip64 { VERSION_ZIP64 } else { VERSION_NEEDED };
+        let mut header = Vec::with_capacity(50 + name.len());
+        header.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        for field in [version, flags, method, time, date] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        header.extend_from_slice(&0u32.to_le_bytes());
+        let sizes = if zip64 { ZIP64_MARKER } else { 0 };
+        header.extend_from_slice(&sizes.to_le_bytes());
+        header.extend_from_slice(&sizes.to_le_bytes());
+        header.extend_from_slice(&name_len(name)?.to_le_bytes());
+        let extra_len: u16 = if zip64 { 20 } else { 0 };
+        header.extend_from_slice(&extra_len.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        if zip64 {
+            header.extend_from_slice(&ZIP64_EXTRA.to_le_bytes());
+            header.extend_from_slice(&16u16.to_le_bytes());
+            header.extend_from_slice(&[0; 16]);
+        }
+        self.out.write_all(&header)?;
+        Ok(())
+    }
+
+    fn central_header(&mut self, record: &ZipRecord) -> Result<()> {
+        let (time, date) = dos_datetime(record.modified);
+        let is_dir = record.name.ends_with('/');
+        // Unix mode in the high half; the low byte holds MS-DOS attributes
+        let attributes = (record.mode << 16) | if is_dir { 0x10 } else { 0 };
+
+        // Values too big for their field go to the Zip64 extra field, in
+        // this order, with the field itself set to the marker
+        let mut extra = Vec::new();
+        let mut field32 = |value: u64, force: bool| {
+            if force || value >= u64::from(ZIP64_MARKER) {
+                extra.extend_from_slice(&value.to_le_bytes());
+                ZIP64_MARKER
+            } else {
+                value as u32
+            }
+        };
+        let size = field32(record.size, record.zip64);
+        let compressed = field32(record.compressed, record.zip64);
+        let offset = field32(record.offset, false);
+        let version = if record.zip64 || !extra.is_empty() {
+            VERSION_ZIP64
+        } else {
+            VERSION_NEEDED
+        };
+        if !extra.is_empty() {
+            let len = extra.len() as u16;
+            extra.splice(
+                0..0,
+                [ZIP64_EXTRA.to_le_bytes(), len.to_le_bytes()].concat(),
+            );
+        }
+
+        let header = &mut self.central;
+        header.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        for field in [
+            VERSION_MADE_BY,
+            version,
+            record.flags,
+            record.method,
+            time,
+            date,
+        ] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        for field in [record.crc, compressed, size] {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        header.extend_from_slice(&name_len(record.name)?.to_le_bytes());
+        header.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+        // Comment, disk number and internal attributes
+        header.extend_from_slice(&[0; 6]);
+        header.extend_from_slice(&attributes.to_le_bytes());
+        header.extend_from_slice(&offset.to_le_bytes());
+        header.extend_from_slice(record.name.as_bytes());
+        header.extend_from_slice(&extra);
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Write the central directory; returns the underlying writer
+    ///
+    /// Past 65535 entries or 4 GiB, a Zip64 end record and its locator come
+    /// first, and the classic end record holds markers.
+    fn finish(mut self) -> Result<W> {
+        let entries = self.entries as u64;
+        let offset = self.out.count;
+        let size = self.central.len() as u64;
+        self.out.write_all(&self.central)?;
+
+        let marker = u64::from(ZIP64_MARKER);
+        if entries >= u64::from(u16::MAX) || offset >= marker || size >= marker {
+            let zip64_end = self.out.count;
+            let mut record = Vec::with_capacity(76);
+            record.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+            // Size of the rest of the record
+            record.extend_from_slice(&44u64.to_le_bytes());
+            record.extend_from_slice(&VERSION_MADE_BY.to_le_bytes());
+            record.extend_from_slice(&VERSION_ZIP64.to_le_bytes());
+            // This disk, and the one the central directory starts on
+            record.extend_from_slice(&[0; 8]);
+            for field in [entries, entries, size, offset] {
+                record.extend_from_slice(&field.to_le_bytes());
+            }
+            record.extend_from_slice(&ZIP64_END_LOCATOR.to_le_bytes());
+            record.extend_from_slice(&0u32.to_le_bytes());
+            record.extend_from_slice(&zip64_end.to_le_bytes());
+            record.extend_from_slice(&1u32.to_le_bytes());
+            self.out.write_all(&record)?;
+        }
+
+        let entries = entries.min(u64::from(u16::MAX)) as u16;
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        for field in [0, 0, entries, entries] {
+            end.extend_from_slice(&field.to_le_bytes());
+        }
+        end.extend_from_slice(&(size.min(marker) as u32).to_le_bytes());
+        end.extend_from_slice(&(offset.min(marker) as u32).to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes());
+        self.out.write_all(&end)?;
+        Ok(self.out.inner)
+    }
+}
+
+fn name_len(name: &str) -> Result<u16> {
+    u16::try_from(name.len()).map_err(|_| FsError::InvalidFormat {
+        format: format!("Name too long for a zip archive: {}", name),
+    })
+}
+
+/// MS-DOS time and date in local time, as unzip expects; 1980 at the earliest
+fn dos_datetime(time: DateTime<Utc>) -> (u16, u16) {
+    let local = time.with_timezone(&Local);
+    if local.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let time = (local.hour() << 11) | (local.minute() << 5) | (local.second() / 2);
+    let date = ((local.year() as u32 - 1980) << 9) | (local.month() << 5) | local.day();
+    (time as u16, date as u16)
+}
+
+/// Writer that counts the bytes written through it
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::traverse::{walk_no_filter, TraverseConfig};
+
+    #[test]
+    fn test_relative_name() {
+        let roots = [PathBuf::from("/data"), PathBuf::from("/data/photos")];
+        let name = |path: &str| relative_name(Path::new(path), &roots);
+        assert_eq!(name("/data/docs/a.txt"), "docs/a.txt");
+        assert_eq!(name("/data/photos/b.jpg"), "b.jpg");
+        assert_eq!(name("/data"), "data");
+        assert_eq!(name("/elsewhere/c.txt"), "elsewhere/c.txt");
+        assert_eq!(
+            relative_name(Path::new("notes.md"), &[PathBuf::from("notes.md")]),
+            "notes.md"
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        let format = |name: &str| ArchiveFormat::from_path(Path::new(name)).ok();
+        assert_eq!(format("backup.ZIP"), Some(ArchiveFormat::Zip));
+        assert_eq!(format("backup.tgz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format("backup.tar.zst"), Some(ArchiveFormat::TarZst));
+        assert_eq!(format("backup.rar"), None);
+    }
+
+    fn sample_tree() -> (tempfile::TempDir, Vec<Entry>) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("docs/empty")).unwrap();
+        fs::write(dir.path().join("docs/readme.txt"), "hello hello hello\n").unwrap();
+        fs::write(dir.path().join("data.csv"), "a,b\n1,2\n").unwrap();
+        let config = TraverseConfig::default();
+        let entries = walk_no_filter(dir.path(), &config).unwrap();
+        (dir, entries)
+    }
+
+    #[test]
+    fn test_create_tar_archive() {
+        let (dir, entries) = sample_tree();
+        let output = tempfile::tempdir().unwrap();
+        let archive = output.path().join("out.tar.gz");
+        let roots = [dir.path().to_path_buf()];
+
+        let summary = create_archive(
+            &entries,
+            &roots,
+            &archive,
+            ArchiveFormat::TarGz,
+            None,
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!((summary.files, summary.dirs), (2, 2));
+
+        let reader = flate2::read::GzDecoder::new(File::open(&archive).unwrap());
+        let mut names: Vec<String> = tar::Archive::new(reader)
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["data.csv", "docs", "docs/empty", "docs/readme.txt"]);
+
+        // Never overwrites
+        assert!(
+            create_archive(&entries, &roots, &archive, ArchiveFormat::Tar, None, |_| {}).is_err()
+        );
+    }
+
+    #[test]
+    fn test_create_zip_archive() {
+        let (dir, entries) = sample_tree();
+        let output = tempfile::tempdir().unwrap();
+        let archive = output.path().join("out.zip");
+        let roots = [dir.path().to_path_buf()];
+        let files: Vec<Entry> = entries
+            .into_iter()
+            .filter(|e| e.path.ends_with("readme.txt") || e.path.ends_with("docs"))
+            .collect();
+
+        let summary =
+            create_archive(&files, &roots, &archive, ArchiveFormat::Zip, None, |_| {}).unwrap();
+        assert_eq!(
+            (summary.files, summary.dirs, summary.total_size),
+            (1, 1, 18)
+        );
+
+        let bytes = fs::read(&archive).unwrap();
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+
+        // The end of central directory record counts both entries
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(end), END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u16_at(end + 10), 2);
+        let central = u32_at(end + 16) as usize;
+        assert_eq!(u32_at(central), CENTRAL_HEADER);
+
+        // Entries in walk order: the directory, then the deflated file
+        assert_eq!(u32_at(0), LOCAL_HEADER);
+        assert_eq!(&bytes[30..35], b"docs/");
+        let file = 35;
+        assert_eq!(u32_at(file), LOCAL_HEADER);
+        assert_eq!(u16_at(file + 8), METHOD_DEFLATED);
+        let name_end = file + 30 + u16_at(file + 26) as usize;
+        assert_eq!(&bytes[file + 30..name_end], b"docs/readme.txt");
+
+        let mut contents = String::new();
+        flate2::read::DeflateDecoder::new(&bytes[name_end..])
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "hello hello hello\n");
+    }
+
+    #[test]
+    fn test_zip64_entry_count() {
+        let mut zip = ZipWriter::new(Vec::new());
+        let now = Utc::now();
+        for i in 0..70_000 {
+            zip.add_dir(&format!("d{}", i), now, 0o040755).unwrap();
+        }
+        let bytes = zip.finish().unwrap();
+        let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+
+        // The classic record is maxed out and points readers to the Zip64 one
+        let end = bytes.len() - 22;
+        assert_eq!(u16_at(end + 10), u16::MAX);
+        let locator = end - 20;
+        assert_eq!(u32_at(locator), ZIP64_END_LOCATOR);
+        let zip64_end = u64_at(locator + 8) as usize;
+        assert_eq!(u32_at(zip64_end), ZIP64_END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u64_at(zip64_end + 32), 70_000);
+        assert_eq!(u32_at(u64_at(zip64_end + 48) as usize), CENTRAL_HEADER);
+    }
+
+    #[test]
+    fn test_zip64_sizes_and_offsets() {
+        let mut zip = ZipWriter::new(Vec::new());
+        // As if 5 GiB had been written already
+        zip.out.count = 5 << 30;
+        zip.add_file(
+            "big.bin",
+            &b"data"[..],
+            5 << 30,
+            Utc::now(),
+            0o100644,
+            flate2::Compression::default(),
+        )
+        .unwrap();
+        let central = zip.central.clone();
+        let bytes = zip.finish().unwrap();
+        let u16_at = |b: &[u8], at: usize| u16::from_le_bytes([b[at], b[at + 1]]);
+        let u32_at = |b: &[u8], at: usize| u32::from_le_bytes(b[at..at + 4].try_into().unwrap());
+        let u64_at = |b: &[u8], at: usize| u64::from_le_bytes(b[at..at + 8].try_into().unwrap());
+
+        // Local header: version 4.5, sizes deferred to a Zip64 extra field
+        assert_eq!(u16_at(&bytes, 4), VERSION_ZIP64);
+        assert_eq!(u32_at(&bytes, 18), ZIP64_MARKER);
+        assert_eq!(u16_at(&bytes, 28), 20);
+        assert_eq!(u16_at(&bytes, 30 + 7), ZIP64_EXTRA);
+
+        // Central header: sizes and the offset in its extra field
+        assert_eq!(u32_at(&central, 20), ZIP64_MARKER);
+        assert_eq!(u32_at(&central, 42), ZIP64_MARKER);
+        let extra = 46 + 7;
+        assert_eq!(u16_at(&central, extra), ZIP64_EXTRA);
+        assert_eq!(u16_at(&central, extra + 2), 24);
+        assert_eq!(u64_at(&central, extra + 4), 4);
+        assert_eq!(u64_at(&central, extra + 20), 5 << 30);
+
+        // The central directory starts past 4 GiB
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(&bytes, end + 16), ZIP64_MARKER);
+        assert_eq!(u32_at(&bytes, end - 20), ZIP64_END_LOCATOR);
+    }
+}
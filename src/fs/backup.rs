@@ -0,0 +1,126 @@
+use crate::fs::batch::{BatchChange, BatchReport};
+use std::path::{Path, PathBuf};
+
+/// Extended attribute Time Machine checks; its presence excludes the item
+/// (and, for directories, everything below) from backups
+pub const EXCLUDE_XATTR: &str = "com.apple.metadata:com_apple_backup_excludeItem";
+
+/// Whether the item carries the Time Machine exclusion attribute
+///
+/// Always false outside macOS.
+pub fn is_backup_excluded(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        matches!(xattr::get(path, EXCLUDE_XATTR), Ok(Some(_)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Add or remove the exclusion attribute, the way `tmutil addexclusion` does
+pub fn set_backup_excluded(path: &Path, excluded: bool) -> std::io::Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        if excluded {
+            xattr::set(path, EXCLUDE_XATTR, &exclusion_plist())
+        } else {
+            xattr::remove(path, EXCLUDE_XATTR)
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (path, excluded);
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Time Machine exclusions are only supported on macOS",
+        ))
+    }
+}
+
+/// Exclude (or include again) each path, reporting what changed
+pub fn set_backup_exclusion(paths: &[PathBuf], excluded: bool, dry_run: bool) -> BatchReport {
+    let mut report = BatchReport::default();
+    for path in paths {
+        if let Err(e) = path.symlink_metadata() {
+            report.errors.push((path.clone(), e.to_string()));
+            continue;
+        }
+        let current = is_backup_excluded(path);
+        if current == excluded {
+            report.unchanged += 1;
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = set_backup_excluded(path, excluded) {
+                report.errors.push((path.clone(), e.to_string()));
+                continue;
+            }
+        }
+        report.changed.push(exclusion_change(path, excluded));
+    }
+    report
+}
+
+fn exclusion_change(path: &Path, excluded: bool) -> BatchChange {
+    let label = |excluded: bool| if excluded { "excluded" } else { "backed up" };
+    BatchChange {
+        path: path.to_path_buf(),
+        from: label(!excluded).to_string(),
+        to: label(excluded).to_string(),
+    }
+}
+
+/// Attribute value written by `tmutil`: a binary plist holding the string
+/// `com.apple.backupd`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn exclusion_plist() -> Vec<u8> {
+    const VALUE: &[u8] = b"com.apple.backupd";
+
+    let mut plist = b"bplist00".to_vec();
+    // ASCII string object longer than 14 bytes: 0x5F, then its length as a 1-byte int
+    plist.extend_from_slice(&[0x5f, 0x10, VALUE.len() as u8]);
+    plist.extend_from_slice(VALUE);
+    let offset_table = plist.len() as u64;
+    // Offset table: the single object starts right after the header
+    plist.push(8);
+    // Trailer: 6 unused bytes, offset and reference sizes, object count,
+    // top object, offset table position
+    plist.extend_from_slice(&[0; 6]);
+    plist.extend_from_slice(&[1, 1]);
+    plist.extend_from_slice(&1u64.to_be_bytes());
+    plist.extend_from_slice(&0u64.to_be_bytes());
+    plist.extend_from_slice(&offset_table.to_be_bytes());
+    plist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusion_plist_matches_tmutil() {
+        let plist = exclusion_plist();
+        assert_eq!(plist.len(), 61);
+        assert!(plist.starts_with(b"bplist00\x5f\x10\x11com.apple.backupd\x08"));
+        assert_eq!(plist[plist.len() - 1], 28);
+    }
+
+    #[test]
+    fn test_plain_files_are_not_excluded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "x").unwrap();
+        assert!(!is_backup_excluded(&path));
+
+        let change = exclusion_change(&path, true);
+        assert_eq!(
+            (change.from.as_str(), change.to.as_str()),
+            ("backed up", "excluded")
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use crate::errors::{FsError, Result};
+use crate::models::Entry;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How an entry differs from the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    /// One-character marker for pretty output
+    pub fn marker(&self) -> char {
+        match self {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        }
+    }
+}
+
+/// An entry that was added, removed or changed since the baseline
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDiff {
+    pub change: ChangeKind,
+    /// The current entry (the baseline entry for removals)
+    #[serde(flatten)]
+    pub entry: Entry,
+    /// The baseline entry, for changed entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Entry>,
+}
+
+/// Load a previous `--format json` (or `ndjson`) export
+pub fn load_baseline(path: &Path) -> Result<Vec<Entry>> {
+    let content = std::fs::read_to_string(path).map_err(|e| FsError::PathAccess {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    parse_export(&content)
+}
+
+/// Load an export for `--from-results`, from stdin when `source` is `-`
+pub fn load_results(source: &Path) -> Result<Vec<Entry>> {
+    if source != Path::new("-") {
+        return load_baseline(source);
+    }
+    let content = std::io::read_to_string(std::io::stdin().lock())?;
+    parse_export(&content)
+}
+
+/// Entries of a JSON array or of one JSON object per line
+fn parse_export(content: &str) -> Result<Vec<Entry>> {
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(content)?);
+    }
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(FsError::from))
+        .collect()
+}
+
+/// Compare the current results against a baseline, matching entries by path
+///
+/// An entry counts as changed when its kind, size, mtime, permissions or owner
+/// differ. Results are sorted by path.
+pub fn diff_entries(baseline: &[Entry], current: &[Entry]) -> Vec<EntryDiff> {
+    let previous: HashMap<&Path, &Entry> = baseline.iter().map(|e| (e.path.as_path(), e)).collect();
+    let current_paths: HashMap<&Path, &Entry> =
+        current.iter().map(|e| (e.path.as_path(), e)).collect();
+
+    let mut diffs: Vec<EntryDiff> = current
+        .iter()
+        .filter_map(|entry| match previous.get(entry.path.as_path()) {
+            None => Some(EntryDiff {
+                change: ChangeKind::Added,
+                entry: entry.clone(),
+                previous: None,
+            }),
+            Some(old) if entry_changed(old, entry) => Some(EntryDiff {
+                change: ChangeKind::Changed,
+                entry: entry.clone(),
+                previous: Some((*old).clone()),
+            }),
+            Some(_) => None,
+        })
+        .collect();
+
+    diffs.extend(
+        baseline
+            .iter()
+            .filter(|e| !current_paths.contains_key(e.path.as_path()))
+            .map(|entry| EntryDiff {
+                change: ChangeKind::Removed,
+                entry: entry.clone(),
+                previous: None,
+            }),
+    );
+
+    diffs.sort_by(|a, b| a.entry.path.cmp(&b.entry.path));
+    diffs
+}
+
+fn entry_changed(old: &Entry, new: &Entry) -> bool {
+    old.kind != new.kind
+        || old.size != new.size
+        || old.mtime.timestamp() != new.mtime.timestamp()
+        || (old.perms.is_some() && new.perms.is_some() && old.perms != new.perms)
+        || (old.owner.is_some() && new.owner.is_some() && old.owner != new.owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn make_entry(path: &str, size: u64) -> Entry {
+        Entry {
+            size,
+            mtime: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let baseline = vec![
+            make_entry("./same.txt", 10),
+            make_entry("./grown.log", 10),
+            make_entry("./gone.tmp", 5),
+        ];
+        let current = vec![
+            make_entry("./same.txt", 10),
+            make_entry("./grown.log", 99),
+            make_entry("./new.rs", 1),
+        ];
+
+        let diffs = diff_entries(&baseline, &current);
+        let summary: Vec<(&str, ChangeKind)> = diffs
+            .iter()
+            .map(|d| (d.entry.name.as_str(), d.change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("gone.tmp", ChangeKind::Removed),
+                ("grown.log", ChangeKind::Changed),
+                ("new.rs", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(diffs[1].previous.as_ref().unwrap().size, 10);
+    }
+
+    #[test]
+    fn test_load_baseline_json_and_ndjson() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = vec![make_entry("./a.txt", 1), make_entry("./b.txt", 2)];
+
+        let json = dir.path().join("results.json");
+        std::fs::write(&json, serde_json::to_string_pretty(&entries).unwrap()).unwrap();
+        assert_eq!(load_baseline(&json).unwrap().len(), 2);
+
+        let ndjson = dir.path().join("results.ndjson");
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|e| serde_json::to_string(e).unwrap())
+            .collect();
+        std::fs::write(&ndjson, lines.join("\n")).unwrap();
+        assert_eq!(load_baseline(&ndjson).unwrap()[1].size, 2);
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+/// A single change applied (or previewed) by a batch command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchChange {
+    pub path: PathBuf,
+    pub from: String,
+    pub to: String,
+}
+
+/// Outcome of a batch command (chmod, chown, ...) across the selected entries
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Entries that were (or, in a dry run, would be) changed
+    pub changed: Vec<BatchChange>,
+    /// Entries already in the requested state
+    pub unchanged: usize,
+    /// Entries deliberately left alone, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Entries that failed, with the error
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl BatchReport {
+    /// One-line summary like "3 changed, 2 unchanged, 1 skipped, 0 errors"
+    pub fn summary(&self, dry_run: bool) -> String {
+        format!(
+            "{} {}, {} unchanged, {} skipped, {} errors",
+            self.changed.len(),
+            if dry_run { "would change" } else { "changed" },
+            self.unchanged,
+            self.skipped.len(),
+            self.errors.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let report = BatchReport {
+            changed: vec![BatchChange {
+                path: PathBuf::from("a"),
+                from: "0644".to_string(),
+                to: "0755".to_string(),
+            }],
+            unchanged: 2,
+            skipped: vec![(PathBuf::from("link"), "symlink".to_string())],
+            errors: Vec::new(),
+        };
+
+        assert_eq!(
+            report.summary(false),
+            "1 changed, 2 unchanged, 1 skipped, 0 errors"
+        );
+        assert!(report.summary(true).starts_with("1 would change"));
+    }
+}
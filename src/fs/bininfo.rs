@@ -0,0 +1,162 @@
+use crate::errors::{FsError, Result};
+use crate::util::format_size_human;
+use object::{Object, ObjectSection, ObjectSymbolTable};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Format, architecture and on-disk section sizes of an executable or object file
+#[derive(Debug, Clone, Serialize)]
+pub struct BinaryInfo {
+    #[serde(with = "crate::util::serde_path")]
+    pub path: PathBuf,
+    /// `ELF`, `Mach-O`, `PE`, `COFF`, ...
+    pub format: String,
+    /// `x86_64`, `aarch64`, ...
+    pub architecture: String,
+    pub bits: u8,
+    /// No symbol table left
+    pub stripped: bool,
+    /// DWARF or other debug info is embedded
+    pub debug_info: bool,
+    /// Size of the whole file
+    pub size: u64,
+    /// Sections stored in the file, largest first (`.bss` and the like take no space)
+    pub sections: Vec<SectionSize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionSize {
+    pub name: String,
+    pub size: u64,
+}
+
+impl BinaryInfo {
+    /// One-line summary for the `bin_sections` column
+    ///
+    /// `ELF x86_64, debug: .debug_info 3.1 MiB, .text 1.2 MiB, .rodata 301 KiB`
+    pub fn summary(&self) -> String {
+        let status = if self.stripped {
+            ", stripped"
+        } else if self.debug_info {
+            ", debug"
+        } else {
+            ""
+        };
+        let sections: Vec<String> = self
+            .sections
+            .iter()
+            .take(3)
+            .map(|s| format!("{} {}", s.name, format_size_human(s.size)))
+            .collect();
+        format!(
+            "{} {}{}: {}",
+            self.format,
+            self.architecture,
+            status,
+            sections.join(", ")
+        )
+    }
+}
+
+/// Whether the file starts like an ELF, Mach-O or PE binary
+///
+/// Cheap check so enrichment doesn't read every file in full.
+pub fn looks_like_binary(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    if file.read_exact(&mut magic).is_err() {
+        return false;
+    }
+    matches!(
+        magic,
+        [0x7f, b'E', b'L', b'F']
+            | [0xfe, 0xed, 0xfa, 0xce | 0xcf]
+            | [0xce | 0xcf, 0xfa, 0xed, 0xfe]
+            | [b'M', b'Z', _, _]
+    )
+}
+
+/// Parse an executable, shared library or object file
+pub fn analyze_binary(path: &Path) -> Result<BinaryInfo> {
+    let data = std::fs::read(path).map_err(|e| FsError::IoError {
+        context: format!("Failed to read {}", path.display()),
+        source: e,
+    })?;
+    let file = object::File::parse(&*data).map_err(|e| FsError::InvalidFormat {
+        format: format!("{}: not a supported executable ({})", path.display(), e),
+    })?;
+
+    let mut sections: Vec<SectionSize> = file
+        .sections()
+        .filter_map(|section| {
+            let (_, size) = section.file_range()?;
+            if size == 0 {
+                return None;
+            }
+            let name = section.name().ok()?;
+            let name = match section.segment_name() {
+                // Mach-O section names only make sense with their segment
+                Ok(Some(segment)) => format!("{},{}", segment, name),
+                _ => name.to_string(),
+            };
+            Some(SectionSize { name, size })
+        })
+        .collect();
+    sections.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(BinaryInfo {
+        path: path.to_path_buf(),
+        format: format_name(file.format()).to_string(),
+        architecture: format!("{:?}", file.architecture()).to_lowercase(),
+        bits: if file.is_64() { 64 } else { 32 },
+        stripped: file
+            .symbol_table()
+            .is_none_or(|symbols| symbols.symbols().next().is_none()),
+        debug_info: file.has_debug_symbols(),
+        size: data.len() as u64,
+        sections,
+    })
+}
+
+fn format_name(format: object::BinaryFormat) -> &'static str {
+    match format {
+        object::BinaryFormat::Elf => "ELF",
+        object::BinaryFormat::MachO => "Mach-O",
+        object::BinaryFormat::Pe => "PE",
+        object::BinaryFormat::Coff => "COFF",
+        object::BinaryFormat::Wasm => "Wasm",
+        object::BinaryFormat::Xcoff => "XCOFF",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_own_executable() {
+        let exe = std::env::current_exe().unwrap();
+        assert!(looks_like_binary(&exe));
+
+        let info = analyze_binary(&exe).unwrap();
+        assert!(!info.format.is_empty());
+        assert!(info.sections.iter().any(|s| s.name.contains("text")));
+        assert!(info.sections.windows(2).all(|w| w[0].size >= w[1].size));
+        assert!(info.summary().starts_with(&info.format));
+    }
+
+    #[test]
+    fn test_rejects_non_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "just text").unwrap();
+
+        assert!(!looks_like_binary(&path));
+        assert!(analyze_binary(&path).is_err());
+    }
+}
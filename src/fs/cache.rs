@@ -0,0 +1,401 @@
+//! Metadata cache for repeated walks (`find`, `size`)
+//!
+//! Walked entries are kept in a SQLite database in the user cache directory
+//! (`~/.cache/fexplorer/metadata.db` on Linux), keyed by absolute path along
+//! with their mtime and size. Every directory is still read, and the stored
+//! files of a directory whose mtime is unchanged are loaded in one query.
+//! Each file is still `lstat`ed: its cached entry is only used while its own
+//! mtime, size and change time match, so a file rewritten in place is read
+//! again. What the cache saves is the rest of the work, like looking up the
+//! owner. Walks with `--no-cache` read everything; `fexplorer cache clear`
+//! drops the database.
+
+use crate::errors::{FsError, Result};
+use crate::models::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::debug;
+
+/// An entry changed this recently could change again within the same mtime
+/// tick, so it's read in full again next time
+const RACY_NANOS: i64 = 2_000_000_000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        path TEXT PRIMARY KEY,
+        parent TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        mtime INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        atime INTEGER,
+        ctime INTEGER,
+        created INTEGER,
+        perms TEXT,
+        owner TEXT,
+        allocated INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS entries_parent ON entries (parent);";
+
+/// Cached entries, shared by the threads of one walk
+#[derive(Debug)]
+pub struct MetadataCache {
+    db: Mutex<Connection>,
+    /// Resolves the relative paths of a walk
+    cwd: PathBuf,
+    /// Files of unchanged directories, by the path the walk will find them
+    /// at, until it gets there
+    reusable: Mutex<HashMap<PathBuf, Entry>>,
+    /// Files and changed directories read from disk, by key, to store on
+    /// [`MetadataCache::save`]
+    fresh: Mutex<Vec<(String, Entry)>>,
+}
+
+impl MetadataCache {
+    /// The cache in the user cache directory
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// `fexplorer/metadata.db` in the user cache directory
+    pub fn default_path() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir().ok_or_else(|| FsError::InvalidFormat {
+            format: "Could not determine cache directory".to_string(),
+        })?;
+        Ok(cache_dir.join("fexplorer").join("metadata.db"))
+    }
+
+    /// Open (or create) the cache database at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| FsError::IoError {
+                context: format!("Failed to create {}", dir.display()),
+                source: e,
+            })?;
+        }
+        let db = Connection::open(path).map_err(|e| sqlite_error(path, e))?;
+        // Concurrent runs take turns writing
+        db.busy_timeout(Duration::from_secs(5))
+            .and_then(|()| {
+                db.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            })
+            .and_then(|()| db.execute_batch(SCHEMA))
+            .map_err(|e| sqlite_error(path, e))?;
+
+        Ok(Self {
+            db: Mutex::new(db),
+            cwd: std::env::current_dir()?,
+            reusable: Mutex::new(HashMap::new()),
+            fresh: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Delete the database at `path`; false if there was none
+    pub fn clear(path: &Path) -> Result<bool> {
+        for suffix in ["-wal", "-shm"] {
+            let mut side = path.as_os_str().to_os_string();
+            side.push(suffix);
+            let _ = std::fs::remove_file(side);
+        }
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(FsError::PathAccess {
+                path: path.to_path_buf(),
+                source: e,
+            }),
+        }
+    }
+
+    /// The cached entry for the file or symlink at `path`, if its directory
+    /// was found unchanged and `metadata` (from an `lstat`) still has the
+    /// stored mtime, size and change time
+    pub fn get(&self, path: &Path, depth: usize, metadata: &Metadata) -> Option<Entry> {
+        let mut entry = self.reusable.lock().ok()?.remove(path)?;
+        if !still_valid(&entry, metadata) {
+            return None;
+        }
+        entry.depth = depth;
+        entry.atime = metadata.accessed().ok().map(DateTime::from);
+        Some(entry)
+    }
+
+    /// Note an entry read from disk; the files of an unchanged directory are
+    /// made ready for [`MetadataCache::get`]
+    pub fn record(&self, entry: &Entry) {
+        if entry.error.is_some() {
+            return;
+        }
+        let Some(key) = self.key(&entry.path) else {
+            return;
+        };
+        if entry.kind == EntryKind::Dir {
+            match self.unchanged_files(&key, entry) {
+                Ok(Some(files)) => {
+                    if let Ok(mut reusable) = self.reusable.lock() {
+                        reusable.extend(files.into_iter().map(|file| (file.path.clone(), file)));
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    debug!(path = %entry.path.display(), error = %e, "Metadata cache lookup failed")
+                }
+            }
+        }
+        if let Ok(mut fresh) = self.fresh.lock() {
+            fresh.push((key, entry.clone()));
+        }
+    }
+
+    /// Stored files of the directory `key`, under the directory's walked
+    /// path, or `None` if it changed since
+    fn unchanged_files(&self, key: &str, dir: &Entry) -> rusqlite::Result<Option<Vec<Entry>>> {
+        let db = self.db.lock().map_err(|_| rusqlite::Error::InvalidQuery)?;
+        let stored: Option<i64> = db
+            .prepare_cached("SELECT mtime FROM entries WHERE path = ?1")?
+            .query_row([key], |row| row.get(0))
+            .optional()?;
+        if stored != Some(mtime_nanos(dir)) {
+            return Ok(None);
+        }
+
+        let mut files = db.prepare_cached(
+            "SELECT path, kind, mtime, size, atime, ctime, created, perms, owner, allocated \
+             FROM entries WHERE parent = ?1 AND kind != 'dir'",
+        )?;
+        let rows = files.query_map([key], |row| file_from_row(row, &dir.path))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map(Some)
+    }
+
+    /// Store the entries read from disk since the last save; returns how
+    /// many were written
+    pub fn save(&self) -> Result<usize> {
+        let fresh = match self.fresh.lock() {
+            Ok(mut fresh) => std::mem::take(&mut *fresh),
+            Err(_) => return Ok(0),
+        };
+        if fresh.is_empty() {
+            return Ok(0);
+        }
+        let racy = Utc::now().timestamp_nanos_opt().unwrap_or(i64::MAX) - RACY_NANOS;
+        let error = |e| FsError::InvalidFormat {
+            format: format!("Metadata cache update failed: {}", e),
+        };
+
+        let mut db = self.db.lock().map_err(|_| FsError::InvalidFormat {
+            format: "Metadata cache is unavailable".to_string(),
+        })?;
+        let tx = db.transaction().map_err(error)?;
+        {
+            // Files that are gone would otherwise stay in changed directories;
+            // subdirectories keep their rows since they're checked on their own
+            let mut clear = tx
+                .prepare("DELETE FROM entries WHERE parent = ?1 AND kind != 'dir'")
+                .map_err(error)?;
+            for (key, _) in fresh.iter().filter(|(_, e)| e.kind == EntryKind::Dir) {
+                clear.execute([key]).map_err(error)?;
+            }
+
+            let mut insert = tx
+                .prepare(
+                    "INSERT OR REPLACE INTO entries \
+                     (path, parent, kind, mtime, size, atime, ctime, created, perms, owner, \
+                     allocated) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                )
+                .map_err(error)?;
+            for (key, entry) in &fresh {
+                let parent = Path::new(key)
+                    .parent()
+                    .and_then(|p| p.to_str())
+                    .unwrap_or_default();
+                let mut mtime = mtime_nanos(entry);
+                if mtime > racy {
+                    mtime = -1;
+                }
+                let nanos =
+                    |time: Option<DateTime<Utc>>| time.and_then(|t| t.timestamp_nanos_opt());
+                insert
+                    .execute(params![
+                        key,
+                        parent,
+                        entry.kind.as_str(),
+                        mtime,
+                        entry.size as i64,
+                        nanos(entry.atime),
+                        nanos(entry.ctime),
+                        nanos(entry.created),
+                        entry.perms,
+                        entry.owner,
+                        entry.allocated.map(|a| a as i64),
+                    ])
+                    .map_err(error)?;
+            }
+        }
+        tx.commit().map_err(error)?;
+        debug!(entries = fresh.len(), "Metadata cache updated");
+        Ok(fresh.len())
+    }
+
+    /// Absolute path without `.` components; `None` if it isn't UTF-8
+    fn key(&self, path: &Path) -> Option<String> {
+        let absolute: PathBuf = self
+            .cwd
+            .join(path)
+            .components()
+            .filter(|c| *c != Component::CurDir)
+            .collect();
+        absolute.to_str().map(str::to_string)
+    }
+}
+
+/// A stored file, placed in the walked directory `dir`
+fn file_from_row(row: &Row, dir: &Path) -> rusqlite::Result<Entry> {
+    let key: String = row.get(0)?;
+    let name = Path::new(&key)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let kind = match row.get::<_, String>(1)?.as_str() {
+        "symlink" => EntryKind::Symlink,
+        _ => EntryKind::File,
+    };
+    let time = |i| -> rusqlite::Result<Option<DateTime<Utc>>> {
+        Ok(row
+            .get::<_, Option<i64>>(i)?
+            .map(DateTime::from_timestamp_nanos))
+    };
+
+    Ok(Entry {
+        path: dir.join(&name),
+        name,
+        size: row.get::<_, i64>(3)? as u64,
+        kind,
+        mtime: DateTime::from_timestamp_nanos(row.get(2)?),
+        atime: time(4)?,
+        ctime: time(5)?,
+        created: time(6)?,
+        perms: row.get(7)?,
+        owner: row.get(8)?,
+        allocated: row.get::<_, Option<i64>>(9)?.map(|a| a as u64),
+        last_activity: None,
+        error: None,
+        truncated: false,
+        enriched: Default::default(),
+        depth: 0,
+    })
+}
+
+/// Whether a cached file matches what's on disk now
+fn still_valid(entry: &Entry, metadata: &Metadata) -> bool {
+    let nanos = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()
+            .and_then(|t| DateTime::<Utc>::from(t).timestamp_nanos_opt())
+    };
+    metadata.len() == entry.size
+        && nanos(metadata.modified()) == Some(mtime_nanos(entry))
+        && ctime_nanos(metadata) == entry.ctime.and_then(|t| t.timestamp_nanos_opt())
+}
+
+#[cfg(unix)]
+fn ctime_nanos(metadata: &Metadata) -> Option<i64> {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata
+        .ctime()
+        .checked_mul(1_000_000_000)?
+        .checked_add(metadata.ctime_nsec())
+}
+
+#[cfg(not(unix))]
+fn ctime_nanos(_metadata: &Metadata) -> Option<i64> {
+    None
+}
+
+fn mtime_nanos(entry: &Entry) -> i64 {
+    entry.mtime.timestamp_nanos_opt().unwrap_or_default()
+}
+
+fn sqlite_error(path: &Path, e: rusqlite::Error) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("Failed to open metadata cache {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+    use filetime::FileTime;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_reuses_files_of_unchanged_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let db = temp.path().join("metadata.db");
+        let dir = temp.path().join("tree");
+        fs::create_dir(&dir).unwrap();
+        let file = dir.join("a.txt");
+        fs::write(&file, "one").unwrap();
+        // Old enough to be trusted
+        let old = |path: &Path, secs| {
+            filetime::set_file_mtime(path, FileTime::from_unix_time(secs, 0)).unwrap()
+        };
+        old(&file, 1_700_000_000);
+        old(&dir, 1_700_000_000);
+
+        // Entries of a walk, and whether the file came from the cache
+        let walk = || -> (Vec<Entry>, bool) {
+            let cache = MetadataCache::open(&db).unwrap();
+            let mut entries = vec![extract_entry(&dir, 0).unwrap()];
+            cache.record(&entries[0]);
+            let metadata = fs::symlink_metadata(&file).unwrap();
+            let cached = cache.get(&file, 1, &metadata);
+            let hit = cached.is_some();
+            entries.push(cached.unwrap_or_else(|| {
+                let entry = extract_entry(&file, 1).unwrap();
+                cache.record(&entry);
+                entry
+            }));
+            cache.save().unwrap();
+            (entries, hit)
+        };
+
+        let (entries, hit) = walk();
+        assert!(!hit);
+        assert_eq!(entries[1].size, 3);
+
+        let (entries, hit) = walk();
+        assert!(hit);
+        assert_eq!(entries[1].size, 3);
+        assert_eq!(entries[1].path, file);
+        assert_eq!(entries[1].depth, 1);
+
+        // Appended to in place: the directory doesn't change, the file does
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .unwrap()
+            .write_all(b" two")
+            .unwrap();
+        old(&file, 1_700_000_050);
+        old(&dir, 1_700_000_000);
+        let (entries, hit) = walk();
+        assert!(!hit);
+        assert_eq!(entries[1].size, 7);
+
+        // Rewritten with the same size and mtime, but its change time moved
+        fs::write(&file, "one two").unwrap();
+        old(&file, 1_700_000_050);
+        let (_, hit) = walk();
+        assert!(!hit);
+
+        assert!(MetadataCache::clear(&db).unwrap());
+        assert!(!MetadataCache::clear(&db).unwrap());
+    }
+}
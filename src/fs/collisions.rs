@@ -0,0 +1,313 @@
+use crate::models::Entry;
+use crate::timings;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Why two sibling names end up as the same file on some filesystems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionKind {
+    /// `README.md` / `readme.md`: same name on case-insensitive filesystems
+    Case,
+    /// `café` (NFC) / `café` (NFD): same name on normalizing filesystems (macOS)
+    Normalization,
+    /// Differs in both case and normalization form
+    Both,
+}
+
+impl std::fmt::Display for CollisionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollisionKind::Case => write!(f, "case"),
+            CollisionKind::Normalization => write!(f, "unicode normalization"),
+            CollisionKind::Both => write!(f, "case and unicode normalization"),
+        }
+    }
+}
+
+/// Entries in one directory whose names collide
+#[derive(Debug, Clone, Serialize)]
+pub struct CollisionGroup {
+    pub dir: PathBuf,
+    pub kind: CollisionKind,
+    pub entries: Vec<Entry>,
+}
+
+/// Group sibling entries whose names differ only by case or Unicode normalization
+///
+/// Such names coexist on Linux but collide on case-insensitive or normalizing
+/// filesystems, where a checkout or copy silently keeps only one of them.
+pub fn find_collisions(entries: &[Entry]) -> Vec<CollisionGroup> {
+    let _timer = timings::phase("collisions");
+
+    let mut by_key: HashMap<(&Path, String), Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        let dir = entry.path.parent().unwrap_or(Path::new(""));
+        by_key
+            .entry((dir, collision_key(&entry.name)))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut groups: Vec<CollisionGroup> = by_key
+        .into_iter()
+        .filter_map(|((dir, _), mut group)| {
+            group.sort_by(|a, b| a.name.cmp(&b.name));
+            group.dedup_by(|a, b| a.path == b.path);
+            if group.len() < 2 {
+                return None;
+            }
+
+            Some(CollisionGroup {
+                dir: dir.to_path_buf(),
+                kind: collision_kind(&group[0].name, &group[1].name),
+                entries: group.into_iter().cloned().collect(),
+            })
+        })
+        .collect();
+
+    groups.sort_by(|a, b| a.entries[0].path.cmp(&b.entries[0].path));
+    groups
+}
+
+/// Classify how two colliding names differ
+pub fn collision_kind(a: &str, b: &str) -> CollisionKind {
+    if decompose(a) == decompose(b) {
+        CollisionKind::Normalization
+    } else if a.to_lowercase() == b.to_lowercase() {
+        CollisionKind::Case
+    } else {
+        CollisionKind::Both
+    }
+}
+
+/// Key under which names that collide on case-insensitive, normalizing
+/// filesystems compare equal: canonical decomposition, then lowercase
+pub fn collision_key(name: &str) -> String {
+    decompose(name).to_lowercase()
+}
+
+/// Canonically decompose precomposed letters (`é` -> `e` + U+0301)
+///
+/// Covers Latin, Greek and Cyrillic letters, which is where NFC/NFD
+/// mismatches show up in practice; other characters pass through unchanged.
+fn decompose(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        match DECOMPOSITIONS.binary_search_by_key(&c, |(composed, _)| *composed) {
+            Ok(i) => out.push_str(DECOMPOSITIONS[i].1),
+            Err(_) => out.push(c),
+        }
+    }
+    out
+}
+
+/// Canonical (NFD) decompositions of precomposed letters, sorted by code point
+#[rustfmt::skip]
+const DECOMPOSITIONS: &[(char, &str)] = &[
+    ('À', "A\u{300}"), ('Á', "A\u{301}"), ('Â', "A\u{302}"), ('Ã', "A\u{303}"),
+    ('Ä', "A\u{308}"), ('Å', "A\u{30a}"), ('Ç', "C\u{327}"), ('È', "E\u{300}"),
+    ('É', "E\u{301}"), ('Ê', "E\u{302}"), ('Ë', "E\u{308}"), ('Ì', "I\u{300}"),
+    ('Í', "I\u{301}"), ('Î', "I\u{302}"), ('Ï', "I\u{308}"), ('Ñ', "N\u{303}"),
+    ('Ò', "O\u{300}"), ('Ó', "O\u{301}"), ('Ô', "O\u{302}"), ('Õ', "O\u{303}"),
+    ('Ö', "O\u{308}"), ('Ù', "U\u{300}"), ('Ú', "U\u{301}"), ('Û', "U\u{302}"),
+    ('Ü', "U\u{308}"), ('Ý', "Y\u{301}"), ('à', "a\u{300}"), ('á', "a\u{301}"),
+    ('â', "a\u{302}"), ('ã', "a\u{303}"), ('ä', "a\u{308}"), ('å', "a\u{30a}"),
+    ('ç', "c\u{327}"), ('è', "e\u{300}"), ('é', "e\u{301}"), ('ê', "e\u{302}"),
+    ('ë', "e\u{308}"), ('ì', "i\u{300}"), ('í', "i\u{301}"), ('î', "i\u{302}"),
+    ('ï', "i\u{308}"), ('ñ', "n\u{303}"), ('ò', "o\u{300}"), ('ó', "o\u{301}"),
+    ('ô', "o\u{302}"), ('õ', "o\u{303}"), ('ö', "o\u{308}"), ('ù', "u\u{300}"),
+    ('ú', "u\u{301}"), ('û', "u\u{302}"), ('ü', "u\u{308}"), ('ý', "y\u{301}"),
+    ('ÿ', "y\u{308}"), ('Ā', "A\u{304}"), ('ā', "a\u{304}"), ('Ă', "A\u{306}"),
+    ('ă', "a\u{306}"), ('Ą', "A\u{328}"), ('ą', "a\u{328}"), ('Ć', "C\u{301}"),
+    ('ć', "c\u{301}"), ('Ĉ', "C\u{302}"), ('ĉ', "c\u{302}"), ('Ċ', "C\u{307}"),
+    ('ċ', "c\u{307}"), ('Č', "C\u{30c}"), ('č', "c\u{30c}"), ('Ď', "D\u{30c}"),
+    ('ď', "d\u{30c}"), ('Ē', "E\u{304}"), ('ē', "e\u{304}"), ('Ĕ', "E\u{306}"),
+    ('ĕ', "e\u{306}"), ('Ė', "E\u{307}"), ('ė', "e\u{307}"), ('Ę', "E\u{328}"),
+    ('ę', "e\u{328}"), ('Ě', "E\u{30c}"), ('ě', "e\u{30c}"), ('Ĝ', "G\u{302}"),
+    ('ĝ', "g\u{302}"), ('Ğ', "G\u{306}"), ('ğ', "g\u{306}"), ('Ġ', "G\u{307}"),
+    ('ġ', "g\u{307}"), ('Ģ', "G\u{327}"), ('ģ', "g\u{327}"), ('Ĥ', "H\u{302}"),
+    ('ĥ', "h\u{302}"), ('Ĩ', "I\u{303}"), ('ĩ', "i\u{303}"), ('Ī', "I\u{304}"),
+    ('ī', "i\u{304}"), ('Ĭ', "I\u{306}"), ('ĭ', "i\u{306}"), ('Į', "I\u{328}"),
+    ('į', "i\u{328}"), ('İ', "I\u{307}"), ('Ĵ', "J\u{302}"), ('ĵ', "j\u{302}"),
+    ('Ķ', "K\u{327}"), ('ķ', "k\u{327}"), ('Ĺ', "L\u{301}"), ('ĺ', "l\u{301}"),
+    ('Ļ', "L\u{327}"), ('ļ', "l\u{327}"), ('Ľ', "L\u{30c}"), ('ľ', "l\u{30c}"),
+    ('Ń', "N\u{301}"), ('ń', "n\u{301}"), ('Ņ', "N\u{327}"), ('ņ', "n\u{327}"),
+    ('Ň', "N\u{30c}"), ('ň', "n\u{30c}"), ('Ō', "O\u{304}"), ('ō', "o\u{304}"),
+    ('Ŏ', "O\u{306}"), ('ŏ', "o\u{306}"), ('Ő', "O\u{30b}"), ('ő', "o\u{30b}"),
+    ('Ŕ', "R\u{301}"), ('ŕ', "r\u{301}"), ('Ŗ', "R\u{327}"), ('ŗ', "r\u{327}"),
+    ('Ř', "R\u{30c}"), ('ř', "r\u{30c}"), ('Ś', "S\u{301}"), ('ś', "s\u{301}"),
+    ('Ŝ', "S\u{302}"), ('ŝ', "s\u{302}"), ('Ş', "S\u{327}"), ('ş', "s\u{327}"),
+    ('Š', "S\u{30c}"), ('š', "s\u{30c}"), ('Ţ', "T\u{327}"), ('ţ', "t\u{327}"),
+    ('Ť', "T\u{30c}"), ('ť', "t\u{30c}"), ('Ũ', "U\u{303}"), ('ũ', "u\u{303}"),
+    ('Ū', "U\u{304}"), ('ū', "u\u{304}"), ('Ŭ', "U\u{306}"), ('ŭ', "u\u{306}"),
+    ('Ů', "U\u{30a}"), ('ů', "u\u{30a}"), ('Ű', "U\u{30b}"), ('ű', "u\u{30b}"),
+    ('Ų', "U\u{328}"), ('ų', "u\u{328}"), ('Ŵ', "W\u{302}"), ('ŵ', "w\u{302}"),
+    ('Ŷ', "Y\u{302}"), ('ŷ', "y\u{302}"), ('Ÿ', "Y\u{308}"), ('Ź', "Z\u{301}"),
+    ('ź', "z\u{301}"), ('Ż', "Z\u{307}"), ('ż', "z\u{307}"), ('Ž', "Z\u{30c}"),
+    ('ž', "z\u{30c}"), ('Ơ', "O\u{31b}"), ('ơ', "o\u{31b}"), ('Ư', "U\u{31b}"),
+    ('ư', "u\u{31b}"), ('Ǎ', "A\u{30c}"), ('ǎ', "a\u{30c}"), ('Ǐ', "I\u{30c}"),
+    ('ǐ', "i\u{30c}"), ('Ǒ', "O\u{30c}"), ('ǒ', "o\u{30c}"), ('Ǔ', "U\u{30c}"),
+    ('ǔ', "u\u{30c}"), ('Ǖ', "U\u{308}\u{304}"), ('ǖ', "u\u{308}\u{304}"),
+    ('Ǘ', "U\u{308}\u{301}"), ('ǘ', "u\u{308}\u{301}"), ('Ǚ', "U\u{308}\u{30c}"),
+    ('ǚ', "u\u{308}\u{30c}"), ('Ǜ', "U\u{308}\u{300}"), ('ǜ', "u\u{308}\u{300}"),
+    ('Ǟ', "A\u{308}\u{304}"), ('ǟ', "a\u{308}\u{304}"), ('Ǡ', "A\u{307}\u{304}"),
+    ('ǡ', "a\u{307}\u{304}"), ('Ǣ', "Æ\u{304}"), ('ǣ', "æ\u{304}"), ('Ǧ', "G\u{30c}"),
+    ('ǧ', "g\u{30c}"), ('Ǩ', "K\u{30c}"), ('ǩ', "k\u{30c}"), ('Ǫ', "O\u{328}"),
+    ('ǫ', "o\u{328}"), ('Ǭ', "O\u{328}\u{304}"), ('ǭ', "o\u{328}\u{304}"), ('Ǯ', "Ʒ\u{30c}"),
+    ('ǯ', "ʒ\u{30c}"), ('ǰ', "j\u{30c}"), ('Ǵ', "G\u{301}"), ('ǵ', "g\u{301}"),
+    ('Ǹ', "N\u{300}"), ('ǹ', "n\u{300}"), ('Ǻ', "A\u{30a}\u{301}"), ('ǻ', "a\u{30a}\u{301}"),
+    ('Ǽ', "Æ\u{301}"), ('ǽ', "æ\u{301}"), ('Ǿ', "Ø\u{301}"), ('ǿ', "ø\u{301}"),
+    ('Ȁ', "A\u{30f}"), ('ȁ', "a\u{30f}"), ('Ȃ', "A\u{311}"), ('ȃ', "a\u{311}"),
+    ('Ȅ', "E\u{30f}"), ('ȅ', "e\u{30f}"), ('Ȇ', "E\u{311}"), ('ȇ', "e\u{311}"),
+    ('Ȉ', "I\u{30f}"), ('ȉ', "i\u{30f}"), ('Ȋ', "I\u{311}"), ('ȋ', "i\u{311}"),
+    ('Ȍ', "O\u{30f}"), ('ȍ', "o\u{30f}"), ('Ȏ', "O\u{311}"), ('ȏ', "o\u{311}"),
+    ('Ȑ', "R\u{30f}"), ('ȑ', "r\u{30f}"), ('Ȓ', "R\u{311}"), ('ȓ', "r\u{311}"),
+    ('Ȕ', "U\u{30f}"), ('ȕ', "u\u{30f}"), ('Ȗ', "U\u{311}"), ('ȗ', "u\u{311}"),
+    ('Ș', "S\u{326}"), ('ș', "s\u{326}"), ('Ț', "T\u{326}"), ('ț', "t\u{326}"),
+    ('Ȟ', "H\u{30c}"), ('ȟ', "h\u{30c}"), ('Ȧ', "A\u{307}"), ('ȧ', "a\u{307}"),
+    ('Ȩ', "E\u{327}"), ('ȩ', "e\u{327}"), ('Ȫ', "O\u{308}\u{304}"), ('ȫ', "o\u{308}\u{304}"),
+    ('Ȭ', "O\u{303}\u{304}"), ('ȭ', "o\u{303}\u{304}"), ('Ȯ', "O\u{307}"), ('ȯ', "o\u{307}"),
+    ('Ȱ', "O\u{307}\u{304}"), ('ȱ', "o\u{307}\u{304}"), ('Ȳ', "Y\u{304}"), ('ȳ', "y\u{304}"),
+    ('ʹ', "ʹ"), ('Ά', "Α\u{301}"), ('Έ', "Ε\u{301}"), ('Ή', "Η\u{301}"), ('Ί', "Ι\u{301}"),
+    ('Ό', "Ο\u{301}"), ('Ύ', "Υ\u{301}"), ('Ώ', "Ω\u{301}"), ('ΐ', "ι\u{308}\u{301}"),
+    ('Ϊ', "Ι\u{308}"), ('Ϋ', "Υ\u{308}"), ('ά', "α\u{301}"), ('έ', "ε\u{301}"),
+    ('ή', "η\u{301}"), ('ί', "ι\u{301}"), ('ΰ', "υ\u{308}\u{301}"), ('ϊ', "ι\u{308}"),
+    ('ϋ', "υ\u{308}"), ('ό', "ο\u{301}"), ('ύ', "υ\u{301}"), ('ώ', "ω\u{301}"),
+    ('ϓ', "ϒ\u{301}"), ('ϔ', "ϒ\u{308}"), ('Ѐ', "Е\u{300}"), ('Ё', "Е\u{308}"),
+    ('Ѓ', "Г\u{301}"), ('Ї', "І\u{308}"), ('Ќ', "К\u{301}"), ('Ѝ', "И\u{300}"),
+    ('Ў', "У\u{306}"), ('Й', "И\u{306}"), ('й', "и\u{306}"), ('ѐ', "е\u{300}"),
+    ('ё', "е\u{308}"), ('ѓ', "г\u{301}"), ('ї', "і\u{308}"), ('ќ', "к\u{301}"),
+    ('ѝ', "и\u{300}"), ('ў', "у\u{306}"), ('Ѷ', "Ѵ\u{30f}"), ('ѷ', "ѵ\u{30f}"),
+    ('Ӂ', "Ж\u{306}"), ('ӂ', "ж\u{306}"), ('Ӑ', "А\u{306}"), ('ӑ', "а\u{306}"),
+    ('Ӓ', "А\u{308}"), ('ӓ', "а\u{308}"), ('Ӗ', "Е\u{306}"), ('ӗ', "е\u{306}"),
+    ('Ӛ', "Ә\u{308}"), ('ӛ', "ә\u{308}"), ('Ӝ', "Ж\u{308}"), ('ӝ', "ж\u{308}"),
+    ('Ӟ', "З\u{308}"), ('ӟ', "з\u{308}"), ('Ӣ', "И\u{304}"), ('ӣ', "и\u{304}"),
+    ('Ӥ', "И\u{308}"), ('ӥ', "и\u{308}"), ('Ӧ', "О\u{308}"), ('ӧ', "о\u{308}"),
+    ('Ӫ', "Ө\u{308}"), ('ӫ', "ө\u{308}"), ('Ӭ', "Э\u{308}"), ('ӭ', "э\u{308}"),
+    ('Ӯ', "У\u{304}"), ('ӯ', "у\u{304}"), ('Ӱ', "У\u{308}"), ('ӱ', "у\u{308}"),
+    ('Ӳ', "У\u{30b}"), ('ӳ', "у\u{30b}"), ('Ӵ', "Ч\u{308}"), ('ӵ', "ч\u{308}"),
+    ('Ӹ', "Ы\u{308}"), ('ӹ', "ы\u{308}"), ('Ḁ', "A\u{325}"), ('ḁ', "a\u{325}"),
+    ('Ḃ', "B\u{307}"), ('ḃ', "b\u{307}"), ('Ḅ', "B\u{323}"), ('ḅ', "b\u{323}"),
+    ('Ḇ', "B\u{331}"), ('ḇ', "b\u{331}"), ('Ḉ', "C\u{327}\u{301}"), ('ḉ', "c\u{327}\u{301}"),
+    ('Ḋ', "D\u{307}"), ('ḋ', "d\u{307}"), ('Ḍ', "D\u{323}"), ('ḍ', "d\u{323}"),
+    ('Ḏ', "D\u{331}"), ('ḏ', "d\u{331}"), ('Ḑ', "D\u{327}"), ('ḑ', "d\u{327}"),
+    ('Ḓ', "D\u{32d}"), ('ḓ', "d\u{32d}"), ('Ḕ', "E\u{304}\u{300}"), ('ḕ', "e\u{304}\u{300}"),
+    ('Ḗ', "E\u{304}\u{301}"), ('ḗ', "e\u{304}\u{301}"), ('Ḙ', "E\u{32d}"), ('ḙ', "e\u{32d}"),
+    ('Ḛ', "E\u{330}"), ('ḛ', "e\u{330}"), ('Ḝ', "E\u{327}\u{306}"), ('ḝ', "e\u{327}\u{306}"),
+    ('Ḟ', "F\u{307}"), ('ḟ', "f\u{307}"), ('Ḡ', "G\u{304}"), ('ḡ', "g\u{304}"),
+    ('Ḣ', "H\u{307}"), ('ḣ', "h\u{307}"), ('Ḥ', "H\u{323}"), ('ḥ', "h\u{323}"),
+    ('Ḧ', "H\u{308}"), ('ḧ', "h\u{308}"), ('Ḩ', "H\u{327}"), ('ḩ', "h\u{327}"),
+    ('Ḫ', "H\u{32e}"), ('ḫ', "h\u{32e}"), ('Ḭ', "I\u{330}"), ('ḭ', "i\u{330}"),
+    ('Ḯ', "I\u{308}\u{301}"), ('ḯ', "i\u{308}\u{301}"), ('Ḱ', "K\u{301}"), ('ḱ', "k\u{301}"),
+    ('Ḳ', "K\u{323}"), ('ḳ', "k\u{323}"), ('Ḵ', "K\u{331}"), ('ḵ', "k\u{331}"),
+    ('Ḷ', "L\u{323}"), ('ḷ', "l\u{323}"), ('Ḹ', "L\u{323}\u{304}"), ('ḹ', "l\u{323}\u{304}"),
+    ('Ḻ', "L\u{331}"), ('ḻ', "l\u{331}"), ('Ḽ', "L\u{32d}"), ('ḽ', "l\u{32d}"),
+    ('Ḿ', "M\u{301}"), ('ḿ', "m\u{301}"), ('Ṁ', "M\u{307}"), ('ṁ', "m\u{307}"),
+    ('Ṃ', "M\u{323}"), ('ṃ', "m\u{323}"), ('Ṅ', "N\u{307}"), ('ṅ', "n\u{307}"),
+    ('Ṇ', "N\u{323}"), ('ṇ', "n\u{323}"), ('Ṉ', "N\u{331}"), ('ṉ', "n\u{331}"),
+    ('Ṋ', "N\u{32d}"), ('ṋ', "n\u{32d}"), ('Ṍ', "O\u{303}\u{301}"), ('ṍ', "o\u{303}\u{301}"),
+    ('Ṏ', "O\u{303}\u{308}"), ('ṏ', "o\u{303}\u{308}"), ('Ṑ', "O\u{304}\u{300}"),
+    ('ṑ', "o\u{304}\u{300}"), ('Ṓ', "O\u{304}\u{301}"), ('ṓ', "o\u{304}\u{301}"),
+    ('Ṕ', "P\u{301}"), ('ṕ', "p\u{301}"), ('Ṗ', "P\u{307}"), ('ṗ', "p\u{307}"),
+    ('Ṙ', "R\u{307}"), ('ṙ', "r\u{307}"), ('Ṛ', "R\u{323}"), ('ṛ', "r\u{323}"),
+    ('Ṝ', "R\u{323}\u{304}"), ('ṝ', "r\u{323}\u{304}"), ('Ṟ', "R\u{331}"), ('ṟ', "r\u{331}"),
+    ('Ṡ', "S\u{307}"), ('ṡ', "s\u{307}"), ('Ṣ', "S\u{323}"), ('ṣ', "s\u{323}"),
+    ('Ṥ', "S\u{301}\u{307}"), ('ṥ', "s\u{301}\u{307}"), ('Ṧ', "S\u{30c}\u{307}"),
+    ('ṧ', "s\u{30c}\u{307}"), ('Ṩ', "S\u{323}\u{307}"), ('ṩ', "s\u{323}\u{307}"),
+    ('Ṫ', "T\u{307}"), ('ṫ', "t\u{307}"), ('Ṭ', "T\u{323}"), ('ṭ', "t\u{323}"),
+    ('Ṯ', "T\u{331}"), ('ṯ', "t\u{331}"), ('Ṱ', "T\u{32d}"), ('ṱ', "t\u{32d}"),
+    ('Ṳ', "U\u{324}"), ('ṳ', "u\u{324}"), ('Ṵ', "U\u{330}"), ('ṵ', "u\u{330}"),
+    ('Ṷ', "U\u{32d}"), ('ṷ', "u\u{32d}"), ('Ṹ', "U\u{303}\u{301}"), ('ṹ', "u\u{303}\u{301}"),
+    ('Ṻ', "U\u{304}\u{308}"), ('ṻ', "u\u{304}\u{308}"), ('Ṽ', "V\u{303}"), ('ṽ', "v\u{303}"),
+    ('Ṿ', "V\u{323}"), ('ṿ', "v\u{323}"), ('Ẁ', "W\u{300}"), ('ẁ', "w\u{300}"),
+    ('Ẃ', "W\u{301}"), ('ẃ', "w\u{301}"), ('Ẅ', "W\u{308}"), ('ẅ', "w\u{308}"),
+    ('Ẇ', "W\u{307}"), ('ẇ', "w\u{307}"), ('Ẉ', "W\u{323}"), ('ẉ', "w\u{323}"),
+    ('Ẋ', "X\u{307}"), ('ẋ', "x\u{307}"), ('Ẍ', "X\u{308}"), ('ẍ', "x\u{308}"),
+    ('Ẏ', "Y\u{307}"), ('ẏ', "y\u{307}"), ('Ẑ', "Z\u{302}"), ('ẑ', "z\u{302}"),
+    ('Ẓ', "Z\u{323}"), ('ẓ', "z\u{323}"), ('Ẕ', "Z\u{331}"), ('ẕ', "z\u{331}"),
+    ('ẖ', "h\u{331}"), ('ẗ', "t\u{308}"), ('ẘ', "w\u{30a}"), ('ẙ', "y\u{30a}"),
+    ('ẛ', "ſ\u{307}"), ('Ạ', "A\u{323}"), ('ạ', "a\u{323}"), ('Ả', "A\u{309}"),
+    ('ả', "a\u{309}"), ('Ấ', "A\u{302}\u{301}"), ('ấ', "a\u{302}\u{301}"),
+    ('Ầ', "A\u{302}\u{300}"), ('ầ', "a\u{302}\u{300}"), ('Ẩ', "A\u{302}\u{309}"),
+    ('ẩ', "a\u{302}\u{309}"), ('Ẫ', "A\u{302}\u{303}"), ('ẫ', "a\u{302}\u{303}"),
+    ('Ậ', "A\u{323}\u{302}"), ('ậ', "a\u{323}\u{302}"), ('Ắ', "A\u{306}\u{301}"),
+    ('ắ', "a\u{306}\u{301}"), ('Ằ', "A\u{306}\u{300}"), ('ằ', "a\u{306}\u{300}"),
+    ('Ẳ', "A\u{306}\u{309}"), ('ẳ', "a\u{306}\u{309}"), ('Ẵ', "A\u{306}\u{303}"),
+    ('ẵ', "a\u{306}\u{303}"), ('Ặ', "A\u{323}\u{306}"), ('ặ', "a\u{323}\u{306}"),
+    ('Ẹ', "E\u{323}"), ('ẹ', "e\u{323}"), ('Ẻ', "E\u{309}"), ('ẻ', "e\u{309}"),
+    ('Ẽ', "E\u{303}"), ('ẽ', "e\u{303}"), ('Ế', "E\u{302}\u{301}"), ('ế', "e\u{302}\u{301}"),
+    ('Ề', "E\u{302}\u{300}"), ('ề', "e\u{302}\u{300}"), ('Ể', "E\u{302}\u{309}"),
+    ('ể', "e\u{302}\u{309}"), ('Ễ', "E\u{302}\u{303}"), ('ễ', "e\u{302}\u{303}"),
+    ('Ệ', "E\u{323}\u{302}"), ('ệ', "e\u{323}\u{302}"), ('Ỉ', "I\u{309}"), ('ỉ', "i\u{309}"),
+    ('Ị', "I\u{323}"), ('ị', "i\u{323}"), ('Ọ', "O\u{323}"), ('ọ', "o\u{323}"),
+    ('Ỏ', "O\u{309}"), ('ỏ', "o\u{309}"), ('Ố', "O\u{302}\u{301}"), ('ố', "o\u{302}\u{301}"),
+    ('Ồ', "O\u{302}\u{300}"), ('ồ', "o\u{302}\u{300}"), ('Ổ', "O\u{302}\u{309}"),
+    ('ổ', "o\u{302}\u{309}"), ('Ỗ', "O\u{302}\u{303}"), ('ỗ', "o\u{302}\u{303}"),
+    ('Ộ', "O\u{323}\u{302}"), ('ộ', "o\u{323}\u{302}"), ('Ớ', "O\u{31b}\u{301}"),
+    ('ớ', "o\u{31b}\u{301}"), ('Ờ', "O\u{31b}\u{300}"), ('ờ', "o\u{31b}\u{300}"),
+    ('Ở', "O\u{31b}\u{309}"), ('ở', "o\u{31b}\u{309}"), ('Ỡ', "O\u{31b}\u{303}"),
+    ('ỡ', "o\u{31b}\u{303}"), ('Ợ', "O\u{31b}\u{323}"), ('ợ', "o\u{31b}\u{323}"),
+    ('Ụ', "U\u{323}"), ('ụ', "u\u{323}"), ('Ủ', "U\u{309}"), ('ủ', "u\u{309}"),
+    ('Ứ', "U\u{31b}\u{301}"), ('ứ', "u\u{31b}\u{301}"), ('Ừ', "U\u{31b}\u{300}"),
+    ('ừ', "u\u{31b}\u{300}"), ('Ử', "U\u{31b}\u{309}"), ('ử', "u\u{31b}\u{309}"),
+    ('Ữ', "U\u{31b}\u{303}"), ('ữ', "u\u{31b}\u{303}"), ('Ự', "U\u{31b}\u{323}"),
+    ('ự', "u\u{31b}\u{323}"), ('Ỳ', "Y\u{300}"), ('ỳ', "y\u{300}"), ('Ỵ', "Y\u{323}"),
+    ('ỵ', "y\u{323}"), ('Ỷ', "Y\u{309}"), ('ỷ', "y\u{309}"), ('Ỹ', "Y\u{303}"),
+    ('ỹ', "y\u{303}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(path: &str) -> Entry {
+        Entry {
+            size: 10,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_decomposition_table_sorted() {
+        assert!(DECOMPOSITIONS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_collision_key() {
+        assert_eq!(collision_key("README.md"), collision_key("readme.md"));
+        assert_eq!(
+            collision_key("caf\u{e9}.txt"),
+            collision_key("cafe\u{301}.txt")
+        );
+        assert_eq!(
+            collision_key("CAF\u{c9}.txt"),
+            collision_key("cafe\u{301}.txt")
+        );
+        assert_ne!(collision_key("cafe.txt"), collision_key("caf\u{e9}.txt"));
+    }
+
+    #[test]
+    fn test_find_collisions() {
+        let entries = vec![
+            make_entry("a/README.md"),
+            make_entry("a/readme.md"),
+            make_entry("b/readme.md"),
+            make_entry("a/caf\u{e9}.txt"),
+            make_entry("a/cafe\u{301}.txt"),
+            make_entry("a/other.md"),
+        ];
+
+        let groups = find_collisions(&entries);
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().all(|g| g.dir == Path::new("a")));
+        assert_eq!(groups[0].kind, CollisionKind::Case);
+        assert_eq!(groups[1].kind, CollisionKind::Normalization);
+        assert_eq!(
+            collision_kind("CAF\u{c9}", "cafe\u{301}"),
+            CollisionKind::Both
+        );
+    }
+}
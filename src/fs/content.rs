@@ -1,7 +1,13 @@
 #[cfg(feature = "grep")]
 use crate::errors::{FsError, Result};
 #[cfg(feature = "grep")]
-use crate::models::{ContentMatch, Entry};
+use crate::fs::gitignore::{is_hidden, GitignoreChecker};
+#[cfg(feature = "grep")]
+use crate::fs::preserve;
+#[cfg(feature = "grep")]
+use crate::models::{ContentMatch, Entry, EntryKind, MatchSpan};
+#[cfg(feature = "grep")]
+use crate::timings;
 #[cfg(feature = "grep")]
 use grep_matcher::Matcher;
 #[cfg(feature = "grep")]
@@ -9,15 +15,65 @@ use grep_regex::RegexMatcherBuilder;
 #[cfg(feature = "grep")]
 use grep_searcher::{sinks, BinaryDetection, SearcherBuilder};
 #[cfg(feature = "grep")]
-use std::fs::File;
+use std::fs::{self, File};
+#[cfg(feature = "grep")]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(feature = "grep")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "grep")]
+use tracing::{debug, info_span};
+
+/// How a grep pattern is interpreted
+#[cfg(feature = "grep")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatternOptions {
+    /// A regular expression rather than literal text
+    pub regex: bool,
+    pub case_insensitive: bool,
+    /// Only matches that start and end at a word boundary (`\b`)
+    pub word: bool,
+    /// Matches may span lines, and `.` matches newlines too
+    pub multiline: bool,
+}
+
+/// Files [`search_files`] and [`replace_files`] pass over without reading
+/// them
 #[cfg(feature = "grep")]
-use std::io::{BufRead, BufReader};
+#[derive(Default)]
+pub struct FileFilter {
+    /// Skip files larger than this many bytes (`--max-filesize`)
+    pub max_filesize: Option<u64>,
+    /// Skip hidden files (see [`is_hidden`]). Walks already leave them
+    /// out; this is for path lists and exported results
+    pub skip_hidden: bool,
+    /// Skip files excluded by `.gitignore`, like the walk does
+    pub gitignore: Option<GitignoreChecker>,
+}
+
 #[cfg(feature = "grep")]
-use std::path::Path;
+impl FileFilter {
+    /// Whether to search `entry`: a regular file the limits allow
+    pub fn allows(&self, entry: &Entry) -> bool {
+        entry.kind == EntryKind::File
+            && self.max_filesize.is_none_or(|max| entry.size <= max)
+            && !(self.skip_hidden && is_hidden(&entry.path))
+            && !self
+                .gitignore
+                .as_ref()
+                .is_some_and(|checker| checker.is_ignored(&entry.path, false))
+    }
+}
 
 #[cfg(feature = "grep")]
 pub struct ContentSearcher {
     matcher: grep_regex::RegexMatcher,
+    /// The same pattern for `--replace`, which needs capture expansion
+    regex: regex::Regex,
+    /// Literal patterns get literal replacements (no `$1` expansion)
+    literal: bool,
+    multiline: bool,
+    /// Search binary files too instead of skipping them at the first NUL
+    binary: bool,
     context_lines: usize,
     #[allow(dead_code)]
     line_numbers: bool,
@@ -33,26 +89,69 @@ impl ContentSearcher {
         context_lines: usize,
         line_numbers: bool,
     ) -> Result<Self> {
-        let pattern_to_use = if is_regex {
+        let options = PatternOptions {
+            regex: is_regex,
+            case_insensitive,
+            ..PatternOptions::default()
+        };
+        Self::with_options(pattern, options, context_lines, line_numbers)
+    }
+
+    /// Create a content searcher for a pattern with word or multiline matching
+    pub fn with_options(
+        pattern: &str,
+        options: PatternOptions,
+        context_lines: usize,
+        line_numbers: bool,
+    ) -> Result<Self> {
+        let mut pattern_to_use = if options.regex {
             pattern.to_string()
         } else {
             regex::escape(pattern)
         };
+        // The same boundaries for searching and --replace, so both count the
+        // same matches
+        if options.word {
+            pattern_to_use = format!(r"\b(?:{})\b", pattern_to_use);
+        }
+        let case_insensitive = options.case_insensitive;
 
         let matcher = RegexMatcherBuilder::new()
             .case_insensitive(case_insensitive)
+            .multi_line(options.multiline)
+            .dot_matches_new_line(options.multiline)
             .build(&pattern_to_use)
             .map_err(|e| FsError::InvalidFormat {
                 format: format!("Invalid regex pattern '{}': {}", pattern, e),
             })?;
+        let regex = regex::RegexBuilder::new(&pattern_to_use)
+            .case_insensitive(case_insensitive)
+            .multi_line(options.multiline)
+            .dot_matches_new_line(options.multiline)
+            .build()
+            .map_err(|e| FsError::InvalidRegex {
+                pattern: pattern.to_string(),
+                source: e,
+            })?;
 
         Ok(Self {
             matcher,
+            regex,
+            literal: !options.regex,
+            multiline: options.multiline,
+            binary: false,
             context_lines,
             line_numbers,
         })
     }
 
+    /// Search binary files as well (`--binary`), reading invalid UTF-8
+    /// lossily
+    pub fn with_binary(mut self) -> Self {
+        self.binary = true;
+        self
+    }
+
     /// Search a single file for matches
     pub fn search_file(&self, entry: &Entry) -> Result<Vec<ContentMatch>> {
         let path = &entry.path;
@@ -63,43 +162,61 @@ impl ContentSearcher {
         }
 
         let mut matches = Vec::new();
+        let binary_detection = if self.binary {
+            BinaryDetection::none()
+        } else {
+            BinaryDetection::quit(b'\x00')
+        };
         let mut searcher = SearcherBuilder::new()
-            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .binary_detection(binary_detection)
             .line_number(true)
+            .multi_line(self.multiline)
             .build();
 
-        let result = searcher.search_path(
-            &self.matcher,
-            path,
-            sinks::UTF8(|lnum, line| {
-                // Extract context if needed
-                let (context_before, context_after) = if self.context_lines > 0 {
-                    self.extract_context(path, lnum as usize, self.context_lines)
-                        .unwrap_or_else(|_| (Vec::new(), Vec::new()))
-                } else {
-                    (Vec::new(), Vec::new())
-                };
-
-                // Find the match column
-                let column = self
-                    .matcher
-                    .find(line.as_bytes())
-                    .ok()
-                    .and_then(|m| m.map(|m| m.start() + 1))
-                    .unwrap_or(1);
-
-                matches.push(ContentMatch {
-                    entry: entry.clone(),
-                    line_number: lnum as usize,
-                    column,
-                    matched_text: line.trim_end().to_string(),
-                    context_before,
-                    context_after,
-                });
-
-                Ok(true)
-            }),
-        );
+        let mut on_match = |lnum: u64, line: &str| -> std::io::Result<bool> {
+            // Every match on the line (or lines, for multiline matches),
+            // within the trimmed text
+            let text = line.trim_end();
+            let first_line = lnum as usize;
+            let last_line = first_line + text.matches('\n').count();
+
+            // Extract context if needed
+            let (context_before, context_after) = if self.context_lines > 0 {
+                self.extract_context(path, first_line, last_line, self.context_lines)
+                    .unwrap_or_else(|_| (Vec::new(), Vec::new()))
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+            let mut submatches = Vec::new();
+            let _ = self.matcher.find_iter(line.as_bytes(), |m| {
+                if m.start() < text.len() {
+                    submatches.push(MatchSpan {
+                        start: m.start(),
+                        end: m.end().min(text.len()),
+                    });
+                }
+                true
+            });
+            let column = submatches.first().map_or(1, |span| span.start + 1);
+
+            matches.push(ContentMatch {
+                entry: entry.clone(),
+                line_number: first_line,
+                column,
+                matched_text: text.to_string(),
+                submatches,
+                context_before,
+                context_after,
+            });
+
+            Ok(true)
+        };
+        let result = if self.binary {
+            searcher.search_path(&self.matcher, path, sinks::Lossy(&mut on_match))
+        } else {
+            searcher.search_path(&self.matcher, path, sinks::UTF8(&mut on_match))
+        };
 
         // Ignore binary file errors and permission denied
         match result {
@@ -114,11 +231,82 @@ impl ContentSearcher {
         }
     }
 
-    /// Extract context lines around a match
+    /// Replace every match in `entry` with `replacement`, line by line
+    ///
+    /// Regex patterns expand `$1`/`${name}` in the replacement. Returns `None`
+    /// when nothing matched. Unless `dry_run`, the new contents are written to
+    /// a temporary file next to the original, which then replaces it.
+    pub fn replace_in_file(
+        &self,
+        entry: &Entry,
+        replacement: &str,
+        dry_run: bool,
+    ) -> std::result::Result<Option<FileReplacement>, ReplaceSkip> {
+        let path = &entry.path;
+        let metadata = fs::symlink_metadata(path).map_err(ReplaceSkip::Error)?;
+        if !metadata.is_file() {
+            return Err(ReplaceSkip::Skipped("not a regular file".to_string()));
+        }
+        let bytes = fs::read(path).map_err(ReplaceSkip::Error)?;
+        // Only files the pattern matches are worth reporting as skipped
+        if !self.matcher.is_match(&bytes).unwrap_or(false) {
+            return Ok(None);
+        }
+        if bytes.contains(&0) {
+            return Err(ReplaceSkip::Skipped("binary file".to_string()));
+        }
+        let text = String::from_utf8(bytes)
+            .map_err(|_| ReplaceSkip::Skipped("not valid UTF-8".to_string()))?;
+
+        let mut result = FileReplacement {
+            path: path.clone(),
+            replacements: 0,
+            lines: Vec::new(),
+        };
+        let mut output = String::with_capacity(text.len());
+        for (idx, line) in text.split_inclusive('\n').enumerate() {
+            // Patterns see the line without its terminator, so `$` and `\s+`
+            // work as they do when searching
+            let body = line
+                .strip_suffix("\r\n")
+                .or_else(|| line.strip_suffix('\n'))
+                .unwrap_or(line);
+            let ending = &line[body.len()..];
+            let count = self.regex.find_iter(body).count();
+            if count == 0 {
+                output.push_str(line);
+                continue;
+            }
+            let replaced = if self.literal {
+                self.regex.replace_all(body, regex::NoExpand(replacement))
+            } else {
+                self.regex.replace_all(body, replacement)
+            };
+            output.push_str(&replaced);
+            output.push_str(ending);
+            result.replacements += count;
+            result.lines.push(LineReplacement {
+                line_number: idx + 1,
+                before: body.to_string(),
+                after: replaced.into_owned(),
+            });
+        }
+
+        if result.replacements == 0 {
+            return Ok(None);
+        }
+        if !dry_run && output != text {
+            write_atomic(path, output.as_bytes(), &metadata).map_err(ReplaceSkip::Error)?;
+        }
+        Ok(Some(result))
+    }
+
+    /// Extract context lines around a match on lines `first_line..=last_line`
     fn extract_context(
         &self,
         path: &Path,
-        match_line: usize,
+        first_line: usize,
+        last_line: usize,
         context: usize,
     ) -> Result<(Vec<String>, Vec<String>)> {
         let file = File::open(path)?;
@@ -126,20 +314,18 @@ impl ContentSearcher {
 
         let mut before = Vec::new();
         let mut after = Vec::new();
-        let mut current_line = 1;
 
-        for line in reader.lines() {
+        for (idx, line) in reader.lines().enumerate() {
             let line = line?;
+            let current_line = idx + 1;
 
-            if current_line < match_line && current_line >= match_line.saturating_sub(context) {
+            if current_line < first_line && current_line >= first_line.saturating_sub(context) {
                 before.push(line);
-            } else if current_line > match_line && current_line <= match_line + context {
+            } else if current_line > last_line && current_line <= last_line + context {
                 after.push(line);
-            } else if current_line > match_line + context {
+            } else if current_line > last_line + context {
                 break;
             }
-
-            current_line += 1;
         }
 
         Ok((before, after))
@@ -148,13 +334,26 @@ impl ContentSearcher {
 
 #[cfg(feature = "grep")]
 /// Search multiple files in parallel
-pub fn search_files(entries: &[Entry], searcher: &ContentSearcher) -> Result<Vec<ContentMatch>> {
+pub fn search_files(
+    entries: &[Entry],
+    searcher: &ContentSearcher,
+    filter: &FileFilter,
+) -> Result<Vec<ContentMatch>> {
+    let _span = info_span!("grep", files = entries.len()).entered();
+    let _timer = timings::phase("grep");
+    let search = |entry: &Entry| {
+        searcher.search_file(entry).inspect_err(
+            |e| debug!(path = %entry.path.display(), error = %e, "Failed to search file"),
+        )
+    };
+
     #[cfg(feature = "parallel")]
     {
         use rayon::prelude::*;
         let matches: Vec<ContentMatch> = entries
             .par_iter()
-            .filter_map(|entry| searcher.search_file(entry).ok())
+            .filter(|entry| filter.allows(entry))
+            .filter_map(|entry| search(entry).ok())
             .flatten()
             .collect();
         Ok(matches)
@@ -163,8 +362,8 @@ pub fn search_files(entries: &[Entry], searcher: &ContentSearcher) -> Result<Vec
     #[cfg(not(feature = "parallel"))]
     {
         let mut matches = Vec::new();
-        for entry in entries {
-            if let Ok(mut entry_matches) = searcher.search_file(entry) {
+        for entry in entries.iter().filter(|entry| filter.allows(entry)) {
+            if let Ok(mut entry_matches) = search(entry) {
                 matches.append(&mut entry_matches);
             }
         }
@@ -172,36 +371,151 @@ pub fn search_files(entries: &[Entry], searcher: &ContentSearcher) -> Result<Vec
     }
 }
 
+/// One line changed by `grep --replace`
+#[cfg(feature = "grep")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineReplacement {
+    /// 1-indexed
+    pub line_number: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Changes `grep --replace` made (or would make) to one file
+#[cfg(feature = "grep")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReplacement {
+    pub path: PathBuf,
+    /// Number of matches replaced
+    pub replacements: usize,
+    pub lines: Vec<LineReplacement>,
+}
+
+/// Why a file was left alone by `grep --replace`
+#[cfg(feature = "grep")]
+#[derive(Debug)]
+pub enum ReplaceSkip {
+    /// Deliberately, like binary files
+    Skipped(String),
+    Error(std::io::Error),
+}
+
+/// Outcome of `grep --replace` across the searched files
+#[cfg(feature = "grep")]
+#[derive(Debug, Default)]
+pub struct ReplaceReport {
+    /// Files with matches, sorted by path
+    pub files: Vec<FileReplacement>,
+    /// Files left alone, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Files that failed, with the error
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+#[cfg(feature = "grep")]
+impl ReplaceReport {
+    /// One-line summary like "Replaced 5 matches on 4 lines in 2 files"
+    pub fn summary(&self, dry_run: bool) -> String {
+        let matches: usize = self.files.iter().map(|f| f.replacements).sum();
+        let lines: usize = self.files.iter().map(|f| f.lines.len()).sum();
+        format!(
+            "{} {} matches on {} lines in {} files",
+            if dry_run { "Would replace" } else { "Replaced" },
+            matches,
+            lines,
+            self.files.len()
+        )
+    }
+}
+
+/// Replace matches in every file of `entries` (see [`ContentSearcher::replace_in_file`])
+///
+/// Binary, non-UTF-8 and non-regular files (symlinks, directories) are
+/// skipped, as are files `filter` leaves out.
+#[cfg(feature = "grep")]
+pub fn replace_files(
+    entries: &[Entry],
+    searcher: &ContentSearcher,
+    filter: &FileFilter,
+    replacement: &str,
+    dry_run: bool,
+) -> ReplaceReport {
+    let _span = info_span!("replace", files = entries.len()).entered();
+    let _timer = timings::phase("grep");
+    let replace = |entry: &Entry| {
+        (
+            entry.path.clone(),
+            searcher.replace_in_file(entry, replacement, dry_run),
+        )
+    };
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<_> = {
+        use rayon::prelude::*;
+        entries
+            .par_iter()
+            .filter(|entry| filter.allows(entry))
+            .map(replace)
+            .collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<_> = entries
+        .iter()
+        .filter(|entry| filter.allows(entry))
+        .map(replace)
+        .collect();
+
+    let mut report = ReplaceReport::default();
+    for (path, result) in results {
+        match result {
+            Ok(Some(file)) => report.files.push(file),
+            Ok(None) => {}
+            Err(ReplaceSkip::Skipped(reason)) => report.skipped.push((path, reason)),
+            Err(ReplaceSkip::Error(e)) => report.errors.push((path, e.to_string())),
+        }
+    }
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+    report
+}
+
+/// Replace `path` with `contents` via a temporary file and a rename, keeping
+/// its owner, permissions and extended attributes
+///
+/// Readers see either the old or the new file, never a partial write. The
+/// file gets a new inode, so other hard links keep the old contents. Files
+/// whose owner can't be kept are left alone.
+#[cfg(feature = "grep")]
+fn write_atomic(path: &Path, contents: &[u8], metadata: &fs::Metadata) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    // A unique name, so a file left behind by an interrupted run is no
+    // obstacle; it's removed again if anything below fails
+    let mut temp = tempfile::Builder::new()
+        .prefix(".fexplorer-replace")
+        .tempfile_in(dir)?;
+    temp.write_all(contents)?;
+    temp.as_file().sync_all()?;
+    preserve::copy_attributes(path, metadata, temp.path())?;
+    temp.persist(path).map(|_| ()).map_err(|e| e.error)
+}
+
 #[cfg(test)]
 #[cfg(feature = "grep")]
 mod tests {
     use super::*;
-    use crate::models::{Entry, EntryKind};
-    use chrono::Utc;
+    use crate::models::Entry;
     use std::fs;
-    use std::path::PathBuf;
     use tempfile::tempdir;
 
-    fn make_test_entry(path: PathBuf) -> Entry {
-        Entry {
-            path: path.clone(),
-            name: path.file_name().unwrap().to_string_lossy().to_string(),
-            size: 0,
-            kind: EntryKind::File,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
-        }
-    }
-
     #[test]
     fn test_literal_search() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("test.txt");
         fs::write(&file_path, "Hello World\nThis is a test\nHello again").unwrap();
 
-        let entry = make_test_entry(file_path);
+        let entry = Entry::test_file(file_path);
         let searcher = ContentSearcher::new("Hello", false, false, 0, false).unwrap();
         let matches = searcher.search_file(&entry).unwrap();
 
@@ -216,33 +530,246 @@ mod tests {
         let file_path = dir.path().join("test.txt");
         fs::write(&file_path, "test123\ntest456\nabc789").unwrap();
 
-        let entry = make_test_entry(file_path);
+        let entry = Entry::test_file(file_path);
         let searcher = ContentSearcher::new(r"test\d+", true, false, 0, false).unwrap();
         let matches = searcher.search_file(&entry).unwrap();
 
         assert_eq!(matches.len(), 2);
     }
 
+    #[test]
+    fn test_submatches() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "a foo and a foo  \n").unwrap();
+
+        let entry = Entry::test_file(file_path);
+        let searcher = ContentSearcher::new("foo", false, false, 0, false).unwrap();
+        let matches = searcher.search_file(&entry).unwrap();
+
+        assert_eq!(matches[0].matched_text, "a foo and a foo");
+        assert_eq!(matches[0].column, 3);
+        assert_eq!(
+            matches[0].submatches,
+            [
+                MatchSpan { start: 2, end: 5 },
+                MatchSpan { start: 12, end: 15 }
+            ]
+        );
+    }
+
     #[test]
     fn test_case_insensitive() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("test.txt");
         fs::write(&file_path, "Hello\nhello\nHELLO").unwrap();
 
-        let entry = make_test_entry(file_path);
+        let entry = Entry::test_file(file_path);
         let searcher = ContentSearcher::new("hello", false, true, 0, false).unwrap();
         let matches = searcher.search_file(&entry).unwrap();
 
         assert_eq!(matches.len(), 3);
     }
 
+    #[test]
+    fn test_word_regexp() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "cat\nconcat\ncat-like\ncats\n").unwrap();
+        let entry = Entry::test_file(file_path);
+
+        let options = PatternOptions {
+            word: true,
+            ..PatternOptions::default()
+        };
+        let searcher = ContentSearcher::with_options("cat", options, 0, false).unwrap();
+        let lines: Vec<usize> = searcher
+            .search_file(&entry)
+            .unwrap()
+            .iter()
+            .map(|m| m.line_number)
+            .collect();
+        assert_eq!(lines, [1, 3]);
+
+        // Regex alternatives are grouped before the boundaries apply
+        let options = PatternOptions {
+            regex: true,
+            word: true,
+            ..PatternOptions::default()
+        };
+        let searcher = ContentSearcher::with_options("cat|concat", options, 0, false).unwrap();
+        assert_eq!(searcher.search_file(&entry).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_multiline() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.rs");
+        fs::write(&file_path, "// head\nfn main(\n    x: u8,\n) {\n}\nend\n").unwrap();
+        let entry = Entry::test_file(file_path);
+
+        let options = PatternOptions {
+            regex: true,
+            multiline: true,
+            ..PatternOptions::default()
+        };
+        let searcher =
+            ContentSearcher::with_options(r"fn main\(.*?\) \{", options, 1, true).unwrap();
+        let matches = searcher.search_file(&entry).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].matched_text, "fn main(\n    x: u8,\n) {");
+        assert_eq!(matches[0].submatches, [MatchSpan { start: 0, end: 23 }]);
+        assert_eq!(matches[0].context_before, ["// head"]);
+        // Context continues after the last line of the match
+        assert_eq!(matches[0].context_after, ["}"]);
+
+        // Without --multiline the pattern can't cross a line break
+        let searcher = ContentSearcher::new(r"fn main\(.*?\) \{", true, false, 0, false).unwrap();
+        assert!(searcher.search_file(&entry).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_binary_files() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("app.bin");
+        fs::write(&file_path, b"\x7fELF\0\0needle\xff\n").unwrap();
+        let entry = Entry::test_file(file_path);
+
+        let searcher = ContentSearcher::new("needle", false, false, 0, false).unwrap();
+        assert!(searcher.search_file(&entry).unwrap().is_empty());
+
+        let matches = searcher.with_binary().search_file(&entry).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].matched_text.ends_with("needle\u{fffd}"));
+    }
+
+    #[test]
+    fn test_file_filter() {
+        let dir = tempdir().unwrap();
+        let small = dir.path().join("small.txt");
+        let big = dir.path().join("big.txt");
+        let hidden = dir.path().join(".env");
+        for path in [&small, &big, &hidden] {
+            fs::write(path, "needle\n").unwrap();
+        }
+        let entries: Vec<Entry> = [(&small, 7), (&big, 4096), (&hidden, 7)]
+            .into_iter()
+            .map(|(path, size)| Entry {
+                size,
+                ..Entry::test_file(path.clone())
+            })
+            .collect();
+
+        let searcher = ContentSearcher::new("needle", false, false, 0, false).unwrap();
+        let filter = FileFilter {
+            max_filesize: Some(1024),
+            skip_hidden: true,
+            gitignore: None,
+        };
+        let matches = search_files(&entries, &searcher, &filter).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry.path, small);
+
+        let all = search_files(&entries, &searcher, &FileFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn test_replace_in_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "foo bar foo\nbaz\r\nfoo.x\n").unwrap();
+        let entry = Entry::test_file(file_path.clone());
+
+        let searcher = ContentSearcher::new("foo", false, false, 0, false).unwrap();
+        let preview = searcher
+            .replace_in_file(&entry, "$1", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(preview.replacements, 3);
+        assert_eq!(preview.lines.len(), 2);
+        assert_eq!(preview.lines[0].after, "$1 bar $1");
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "foo bar foo\nbaz\r\nfoo.x\n"
+        );
+
+        let searcher = ContentSearcher::new(r"foo\.(\w)", true, false, 0, false).unwrap();
+        let done = searcher
+            .replace_in_file(&entry, "qux_$1", false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.lines[0].line_number, 3);
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "foo bar foo\nbaz\r\nqux_x\n"
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // Patterns don't see line terminators
+        fs::write(&file_path, "foo  \nbar foo\r\nfoo\n").unwrap();
+        let searcher = ContentSearcher::new(r"foo\s+|foo$", true, false, 0, false).unwrap();
+        let done = searcher
+            .replace_in_file(&entry, "x", false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.replacements, 3);
+        assert_eq!(done.lines[1].after, "bar x");
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "x\nbar x\r\nx\n");
+
+        // A temporary file left behind by an interrupted run is no obstacle
+        fs::write(dir.path().join(".test.txt.fexplorer-replace"), "").unwrap();
+        let searcher = ContentSearcher::new("bar", false, false, 0, false).unwrap();
+        assert!(searcher
+            .replace_in_file(&entry, "baz", false)
+            .unwrap()
+            .is_some());
+        assert!(fs::read_to_string(&file_path).unwrap().contains("baz x"));
+
+        let missing = ContentSearcher::new("nope", false, false, 0, false).unwrap();
+        assert!(missing
+            .replace_in_file(&entry, "x", false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_replace_files_skips_binary() {
+        let dir = tempdir().unwrap();
+        let text = dir.path().join("a.txt");
+        let binary = dir.path().join("b.bin");
+        fs::write(&text, "old\nold\n").unwrap();
+        fs::write(&binary, b"old\0old").unwrap();
+
+        let entries = [
+            Entry::test_file(text.clone()),
+            Entry::test_file(binary.clone()),
+        ];
+        let searcher = ContentSearcher::new("old", false, false, 0, false).unwrap();
+        let report = replace_files(&entries, &searcher, &FileFilter::default(), "new", false);
+
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(
+            report.skipped,
+            [(binary.clone(), "binary file".to_string())]
+        );
+        assert_eq!(
+            report.summary(false),
+            "Replaced 2 matches on 2 lines in 1 files"
+        );
+        assert_eq!(fs::read_to_string(&text).unwrap(), "new\nnew\n");
+        assert_eq!(fs::read(&binary).unwrap(), b"old\0old");
+    }
+
     #[test]
     fn test_context_lines() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("test.txt");
         fs::write(&file_path, "line1\nline2\nmatch\nline4\nline5").unwrap();
 
-        let entry = make_test_entry(file_path);
+        let entry = Entry::test_file(file_path);
         let searcher = ContentSearcher::new("match", false, false, 1, false).unwrap();
         let matches = searcher.search_file(&entry).unwrap();
 
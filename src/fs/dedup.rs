@@ -3,6 +3,8 @@ use crate::errors::Result;
 #[cfg(feature = "dedup")]
 use crate::models::{DuplicateGroup, Entry, EntryKind};
 #[cfg(feature = "dedup")]
+use crate::timings;
+#[cfg(feature = "dedup")]
 use blake3::Hasher;
 #[cfg(feature = "dedup")]
 use std::fs::File;
@@ -10,6 +12,8 @@ use std::fs::File;
 use std::io::{BufReader, Read};
 #[cfg(feature = "dedup")]
 use std::sync::Arc;
+#[cfg(feature = "dedup")]
+use tracing::{debug, info_span};
 
 #[cfg(feature = "dedup")]
 /// Find duplicate files by content hash
@@ -42,6 +46,9 @@ pub fn find_duplicates(entries: &[Entry], min_size: u64) -> Result<Vec<Duplicate
         return Ok(Vec::new());
     }
 
+    let _span = info_span!("hash", candidates = candidates.len()).entered();
+    let _timer = timings::phase("hash");
+
     // Hash files in parallel
     #[cfg(feature = "parallel")]
     {
@@ -51,7 +58,7 @@ pub fn find_duplicates(entries: &[Entry], min_size: u64) -> Result<Vec<Duplicate
             Arc::new(Mutex::new(std::collections::HashMap::new()));
 
         candidates.par_iter().for_each(|entry| {
-            if let Ok(hash) = hash_file(&entry.path) {
+            if let Ok(hash) = hash_file_logged(&entry.path) {
                 if let Ok(mut map) = hash_map.lock() {
                     map.entry(hash).or_default().push(entry.clone());
                 }
@@ -65,13 +72,10 @@ pub fn find_duplicates(entries: &[Entry], min_size: u64) -> Result<Vec<Duplicate
         let mut groups: Vec<DuplicateGroup> = hash_results
             .into_iter()
             .filter(|(_, entries)| entries.len() > 1)
-            .map(|(hash, entries)| {
-                let size = entries[0].size;
-                DuplicateGroup::new(hash, size, entries)
-            })
+            .map(|(hash, entries)| into_group(hash, entries))
             .collect();
 
-        groups.sort_by(|a, b| b.wasted_space.cmp(&a.wasted_space));
+        groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_space));
         Ok(groups)
     }
 
@@ -79,7 +83,7 @@ pub fn find_duplicates(entries: &[Entry], min_size: u64) -> Result<Vec<Duplicate
     {
         let mut hash_results = std::collections::HashMap::new();
         for entry in &candidates {
-            if let Ok(hash) = hash_file(&entry.path) {
+            if let Ok(hash) = hash_file_logged(&entry.path) {
                 hash_results
                     .entry(hash)
                     .or_default()
@@ -91,17 +95,47 @@ pub fn find_duplicates(entries: &[Entry], min_size: u64) -> Result<Vec<Duplicate
         let mut groups: Vec<DuplicateGroup> = hash_results
             .into_iter()
             .filter(|(_, entries)| entries.len() > 1)
-            .map(|(hash, entries)| {
-                let size = entries[0].size;
-                DuplicateGroup::new(hash, size, entries)
-            })
+            .map(|(hash, entries)| into_group(hash, entries))
             .collect();
 
-        groups.sort_by(|a, b| b.wasted_space.cmp(&a.wasted_space));
+        groups.sort_by_key(|g| std::cmp::Reverse(g.wasted_space));
         Ok(groups)
     }
 }
 
+#[cfg(feature = "dedup")]
+/// Group entries with the same hash, counting hard links to one file once
+fn into_group(hash: String, entries: Vec<Entry>) -> DuplicateGroup {
+    let size = entries[0].size;
+    let copies = count_copies(&entries);
+    DuplicateGroup::new(hash, size, entries).with_copies(copies)
+}
+
+#[cfg(feature = "dedup")]
+/// Number of distinct files (device and inode) among the entries
+///
+/// Entries whose identity can't be read count as separate files.
+fn count_copies(entries: &[Entry]) -> usize {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut seen = std::collections::HashSet::new();
+        entries
+            .iter()
+            .filter(|entry| match std::fs::metadata(&entry.path) {
+                Ok(meta) => seen.insert((meta.dev(), meta.ino())),
+                Err(_) => true,
+            })
+            .count()
+    }
+
+    #[cfg(not(unix))]
+    {
+        entries.len()
+    }
+}
+
 #[cfg(feature = "dedup")]
 /// Compute BLAKE3 hash of a file
 fn hash_file(path: &std::path::Path) -> Result<String> {
@@ -121,6 +155,13 @@ fn hash_file(path: &std::path::Path) -> Result<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+#[cfg(feature = "dedup")]
+/// Hash a file, logging (rather than propagating) failures
+pub(crate) fn hash_file_logged(path: &std::path::Path) -> Result<String> {
+    hash_file(path)
+        .inspect_err(|e| debug!(path = %path.display(), error = %e, "Failed to hash file"))
+}
+
 #[cfg(feature = "dedup")]
 /// Calculate total wasted space from duplicate groups
 pub fn calculate_wasted_space(groups: &[DuplicateGroup]) -> u64 {
@@ -165,21 +206,14 @@ impl DuplicateStats {
 mod tests {
     use super::*;
     use crate::models::Entry;
-    use chrono::Utc;
     use std::fs;
     use std::path::PathBuf;
     use tempfile::tempdir;
 
     fn make_test_entry(path: PathBuf, size: u64) -> Entry {
         Entry {
-            path: path.clone(),
-            name: path.file_name().unwrap().to_string_lossy().to_string(),
             size,
-            kind: EntryKind::File,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
+            ..Entry::test_file(path)
         }
     }
 
@@ -258,6 +292,36 @@ mod tests {
         assert_eq!(groups[0].wasted_space, 2048);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_are_not_wasted_space() {
+        let dir = tempdir().unwrap();
+
+        let original = dir.path().join("original.bin");
+        let link = dir.path().join("link.bin");
+        let copy = dir.path().join("copy.bin");
+        fs::write(&original, vec![1u8; 100]).unwrap();
+        fs::hard_link(&original, &link).unwrap();
+
+        let linked = vec![
+            make_test_entry(original.clone(), 100),
+            make_test_entry(link.clone(), 100),
+        ];
+        let groups = find_duplicates(&linked, 0).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].already_linked());
+        assert_eq!(groups[0].wasted_space, 0);
+
+        // A real copy next to the links wastes one file's worth
+        fs::write(&copy, vec![1u8; 100]).unwrap();
+        let mut mixed = linked;
+        mixed.push(make_test_entry(copy, 100));
+        let groups = find_duplicates(&mixed, 0).unwrap();
+        assert_eq!((groups[0].count, groups[0].copies), (3, 2));
+        assert!(!groups[0].already_linked());
+        assert_eq!(groups[0].wasted_space, 100);
+    }
+
     #[test]
     fn test_duplicate_stats() {
         let dir = tempdir().unwrap();
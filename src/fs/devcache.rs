@@ -0,0 +1,317 @@
+use crate::errors::{FsError, Result};
+use crate::fs::trash;
+use crate::fs::traverse::{walk_no_filter, TraverseConfig};
+use crate::models::EntryKind;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Kinds of developer caches `dev-caches` knows about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheKind {
+    CargoRegistry,
+    CargoGit,
+    CargoTarget,
+    Npm,
+    Pnpm,
+    Yarn,
+    Pip,
+    Docker,
+}
+
+impl CacheKind {
+    pub const ALL: &'static [CacheKind] = &[
+        CacheKind::CargoRegistry,
+        CacheKind::CargoGit,
+        CacheKind::CargoTarget,
+        CacheKind::Npm,
+        CacheKind::Pnpm,
+        CacheKind::Yarn,
+        CacheKind::Pip,
+        CacheKind::Docker,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            CacheKind::CargoRegistry => "cargo-registry",
+            CacheKind::CargoGit => "cargo-git",
+            CacheKind::CargoTarget => "cargo-target",
+            CacheKind::Npm => "npm",
+            CacheKind::Pnpm => "pnpm",
+            CacheKind::Yarn => "yarn",
+            CacheKind::Pip => "pip",
+            CacheKind::Docker => "docker",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.id() == s.trim().to_lowercase())
+            .ok_or_else(|| FsError::InvalidFormat {
+                format: format!(
+                    "Unknown cache type: {} (expected {} or all)",
+                    s,
+                    Self::ALL
+                        .iter()
+                        .map(|k| k.id())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+    }
+
+    /// What deleting the cache costs, and the tool's own cleanup command
+    pub fn guidance(self) -> &'static str {
+        match self {
+            CacheKind::CargoRegistry => {
+                "Safe: crates are downloaded again by the next build that needs them"
+            }
+            CacheKind::CargoGit => "Safe: git dependencies are cloned again on the next build",
+            CacheKind::CargoTarget => "Safe: build output; the next build starts from scratch",
+            CacheKind::Npm => "Safe: packages are downloaded again (`npm cache clean --force`)",
+            CacheKind::Pnpm => {
+                "Mostly safe: installed projects need `pnpm install` again; \
+                 `pnpm store prune` only drops unused packages"
+            }
+            CacheKind::Yarn => "Safe: packages are downloaded again (`yarn cache clean`)",
+            CacheKind::Pip => "Safe: wheels are downloaded or built again (`pip cache purge`)",
+            CacheKind::Docker => {
+                "Never delete by hand: use `docker system prune` or `docker builder prune`"
+            }
+        }
+    }
+
+    /// Whether `--clean` may delete it; Docker's storage is owned by the daemon
+    pub fn can_clean(self) -> bool {
+        self != CacheKind::Docker
+    }
+}
+
+/// Where the per-user caches live
+#[derive(Debug, Clone)]
+pub struct CacheRoots {
+    pub home: PathBuf,
+    pub cargo_home: PathBuf,
+    /// `~/.cache` on Linux, `~/Library/Caches` on macOS
+    pub cache_dir: PathBuf,
+    /// `~/.local/share` on Linux, `~/Library/Application Support` on macOS
+    pub data_dir: PathBuf,
+    /// Docker's storage on Linux, `/var/lib/docker`
+    pub docker_root: PathBuf,
+}
+
+impl CacheRoots {
+    pub fn detect() -> Result<Self> {
+        let home = dirs::home_dir().ok_or_else(|| FsError::InvalidFormat {
+            format: "Could not determine home directory".to_string(),
+        })?;
+        let cargo_home = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".cargo"));
+        Ok(Self {
+            cargo_home,
+            cache_dir: dirs::cache_dir().unwrap_or_else(|| home.join(".cache")),
+            data_dir: dirs::data_dir().unwrap_or_else(|| home.join(".local/share")),
+            docker_root: PathBuf::from("/var/lib/docker"),
+            home,
+        })
+    }
+
+    /// Known per-user cache locations, whether or not they exist
+    pub fn locations(&self) -> Vec<(CacheKind, PathBuf)> {
+        vec![
+            (CacheKind::CargoRegistry, self.cargo_home.join("registry")),
+            (CacheKind::CargoGit, self.cargo_home.join("git")),
+            (CacheKind::Npm, self.home.join(".npm/_cacache")),
+            (CacheKind::Pnpm, self.data_dir.join("pnpm/store")),
+            (CacheKind::Yarn, self.cache_dir.join("yarn")),
+            (CacheKind::Pip, self.cache_dir.join("pip")),
+            (CacheKind::Docker, self.docker_root.clone()),
+            (
+                CacheKind::Docker,
+                self.home
+                    .join("Library/Containers/com.docker.docker/Data/vms/0/data/Docker.raw"),
+            ),
+        ]
+    }
+}
+
+/// A cache found on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct DevCache {
+    pub kind: CacheKind,
+    #[serde(with = "crate::util::serde_path")]
+    pub path: PathBuf,
+    /// Apparent size of all files
+    pub size: u64,
+    pub files: u64,
+    pub guidance: &'static str,
+}
+
+/// Cargo `target/` directories under the project roots
+///
+/// Cargo marks them with a `CACHEDIR.TAG`; they aren't descended into, and
+/// neither are `.git` and `node_modules`.
+pub fn find_target_dirs(roots: &[PathBuf], config: &TraverseConfig) -> Vec<PathBuf> {
+    let mut targets = Vec::new();
+    for root in roots {
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .standard_filters(false)
+            .hidden(!config.include_hidden)
+            .follow_links(config.follow_symlinks)
+            .max_depth(config.max_depth)
+            .filter_entry(|entry| {
+                let name = entry.file_name();
+                name != ".git"
+                    && name != "node_modules"
+                    && !entry.path().parent().is_some_and(is_target_dir)
+            });
+
+        for entry in builder.build().flatten() {
+            if entry.file_type().is_some_and(|t| t.is_dir()) && is_target_dir(entry.path()) {
+                targets.push(entry.into_path());
+            }
+        }
+    }
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+fn is_target_dir(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "target") && path.join("CACHEDIR.TAG").is_file()
+}
+
+/// Measure every cache that exists: the per-user ones plus `targets`
+pub fn scan_caches(
+    roots: &CacheRoots,
+    targets: &[PathBuf],
+    config: &TraverseConfig,
+) -> Vec<DevCache> {
+    let locations = roots
+        .locations()
+        .into_iter()
+        .chain(targets.iter().map(|t| (CacheKind::CargoTarget, t.clone())));
+
+    let mut caches: Vec<DevCache> = locations
+        .filter(|(_, path)| path.exists())
+        .map(|(kind, path)| {
+            let (size, files) = measure(&path, config);
+            DevCache {
+                kind,
+                path,
+                size,
+                files,
+                guidance: kind.guidance(),
+            }
+        })
+        .collect();
+    caches.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    caches
+}
+
+/// Total size and file count; unreadable parts (e.g. root-owned Docker data) are skipped
+pub(crate) fn measure(path: &Path, config: &TraverseConfig) -> (u64, u64) {
+    let config = TraverseConfig {
+        include_hidden: true,
+        respect_gitignore: false,
+        max_depth: None,
+        quiet: true,
+        ..config.clone()
+    };
+    walk_no_filter(path, &config)
+        .unwrap_or_default()
+        .iter()
+        .filter(|e| e.kind == EntryKind::File)
+        .fold((0, 0), |(size, files), e| (size + e.size, files + 1))
+}
+
+/// Move a cache directory to the trash, or delete it when `permanent`
+pub fn clean_cache(cache: &DevCache, permanent: bool) -> Result<()> {
+    if !cache.kind.can_clean() {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "{} caches are not cleaned: {}",
+                cache.kind.id(),
+                cache.guidance
+            ),
+        });
+    }
+    trash::remove(&cache.path, permanent).map_err(|e| FsError::IoError {
+        context: format!("Failed to remove {}", cache.path.display()),
+        source: e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_scan_and_clean() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path().join("home");
+        let roots = CacheRoots {
+            cargo_home: home.join(".cargo"),
+            cache_dir: home.join(".cache"),
+            data_dir: home.join(".local/share"),
+            docker_root: temp.path().join("docker"),
+            home: home.clone(),
+        };
+        fs::create_dir_all(home.join(".cargo/registry/cache")).unwrap();
+        fs::write(
+            home.join(".cargo/registry/cache/serde.crate"),
+            vec![0u8; 300],
+        )
+        .unwrap();
+        fs::create_dir_all(home.join(".cache/pip/wheels")).unwrap();
+        fs::write(home.join(".cache/pip/wheels/a.whl"), vec![0u8; 100]).unwrap();
+        fs::create_dir_all(temp.path().join("docker/overlay2")).unwrap();
+        fs::write(temp.path().join("docker/overlay2/layer"), vec![0u8; 50]).unwrap();
+
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("target/debug")).unwrap();
+        fs::write(project.join("target/CACHEDIR.TAG"), "Signature").unwrap();
+        fs::write(project.join("target/debug/app"), vec![0u8; 200]).unwrap();
+        // Not a cargo target dir without the tag
+        fs::create_dir_all(project.join("docs/target")).unwrap();
+
+        let config = TraverseConfig::default();
+        let targets = find_target_dirs(std::slice::from_ref(&project), &config);
+        assert_eq!(targets, vec![project.join("target")]);
+
+        let caches = scan_caches(&roots, &targets, &config);
+        let kinds: Vec<CacheKind> = caches.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                CacheKind::CargoRegistry,
+                CacheKind::CargoTarget,
+                CacheKind::Pip,
+                CacheKind::Docker
+            ]
+        );
+        assert_eq!(caches[0].size, 300);
+        assert_eq!(caches[1].size, 209);
+        assert!(clean_cache(&caches[3], true).is_err());
+
+        clean_cache(&caches[2], true).unwrap();
+        assert!(!home.join(".cache/pip").exists());
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        assert_eq!(CacheKind::parse("npm").unwrap(), CacheKind::Npm);
+        assert_eq!(
+            CacheKind::parse("Cargo-Target").unwrap(),
+            CacheKind::CargoTarget
+        );
+        assert!(CacheKind::parse("maven").is_err());
+        assert!(!CacheKind::Docker.can_clean());
+    }
+}
@@ -0,0 +1,396 @@
+use crate::errors::{FsError, Result};
+use crate::fs::devcache::measure;
+use crate::fs::traverse::TraverseConfig;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// What a piece of container storage belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UsageKind {
+    Images,
+    Containers,
+    Volumes,
+    BuildCache,
+}
+
+impl UsageKind {
+    pub const ALL: &'static [UsageKind] = &[
+        UsageKind::Images,
+        UsageKind::Containers,
+        UsageKind::Volumes,
+        UsageKind::BuildCache,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UsageKind::Images => "Images",
+            UsageKind::Containers => "Containers",
+            UsageKind::Volumes => "Volumes",
+            UsageKind::BuildCache => "Build cache",
+        }
+    }
+}
+
+/// One image, container, volume or build cache record
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageItem {
+    pub kind: UsageKind,
+    pub name: String,
+    pub size: u64,
+    /// Used by a container (images, volumes) or running (containers)
+    pub in_use: bool,
+}
+
+/// Totals for one kind
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    pub kind: UsageKind,
+    pub count: usize,
+    pub size: u64,
+    /// Space freed by removing unused items; unknown when measured from disk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reclaimable: Option<u64>,
+}
+
+/// Disk usage of a Docker or Podman installation
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerUsage {
+    /// Daemon socket or storage directory the numbers come from
+    #[serde(with = "crate::util::serde_path")]
+    pub source: PathBuf,
+    pub summary: Vec<UsageSummary>,
+    /// Largest first
+    pub items: Vec<UsageItem>,
+}
+
+impl ContainerUsage {
+    pub fn total(&self) -> u64 {
+        self.summary.iter().map(|s| s.size).sum()
+    }
+
+    /// `layers_size` overrides the images total, whose items only count unshared layers
+    fn from_items(
+        source: PathBuf,
+        mut items: Vec<UsageItem>,
+        reclaimable: bool,
+        layers_size: Option<u64>,
+    ) -> Self {
+        items.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        let summary = UsageKind::ALL
+            .iter()
+            .map(|&kind| {
+                let of_kind = || items.iter().filter(move |i| i.kind == kind);
+                UsageSummary {
+                    kind,
+                    count: of_kind().count(),
+                    size: match (kind, layers_size) {
+                        (UsageKind::Images, Some(size)) => size,
+                        _ => of_kind().map(|i| i.size).sum(),
+                    },
+                    reclaimable: reclaimable
+                        .then(|| of_kind().filter(|i| !i.in_use).map(|i| i.size).sum()),
+                }
+            })
+            .collect();
+        Self {
+            source,
+            summary,
+            items,
+        }
+    }
+}
+
+/// Daemon API socket: `$DOCKER_HOST` (unix:// only), Docker's default, then Podman's
+pub fn find_socket() -> Option<PathBuf> {
+    let docker_host = std::env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| host.strip_prefix("unix://").map(PathBuf::from));
+    let podman_user = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("podman/podman.sock"));
+
+    docker_host
+        .into_iter()
+        .chain([PathBuf::from("/var/run/docker.sock")])
+        .chain(podman_user)
+        .chain([PathBuf::from("/run/podman/podman.sock")])
+        .find(|path| path.exists())
+}
+
+/// Storage directories of Docker, rootful Podman and rootless Podman that exist
+pub fn find_storage_dirs() -> Vec<PathBuf> {
+    let rootless = dirs::data_dir().map(|dir| dir.join("containers/storage"));
+    [
+        PathBuf::from("/var/lib/docker"),
+        PathBuf::from("/var/lib/containers/storage"),
+    ]
+    .into_iter()
+    .chain(rootless)
+    .filter(|path| path.is_dir())
+    .collect()
+}
+
+/// Ask the daemon for `docker system df` numbers
+pub fn usage_from_socket(socket: &Path) -> Result<ContainerUsage> {
+    let body = http_get(socket, "/system/df")?;
+    let df: Value = serde_json::from_slice(&body)?;
+    Ok(ContainerUsage::from_items(
+        socket.to_path_buf(),
+        parse_system_df(&df),
+        true,
+        df["LayersSize"].as_u64(),
+    ))
+}
+
+/// Measure a Docker or Podman storage directory
+///
+/// Without the daemon, images can't be told apart from their layers, so
+/// layer storage counts as images and nothing is known to be reclaimable.
+pub fn usage_from_storage(root: &Path, config: &TraverseConfig) -> Result<ContainerUsage> {
+    if !root.is_dir() {
+        return Err(FsError::InvalidFormat {
+            format: format!("{} is not a container storage directory", root.display()),
+        });
+    }
+
+    // Docker layout first, then Podman's (containers/storage)
+    let layout: &[(UsageKind, &str, bool)] = &[
+        (UsageKind::Images, "overlay2", false),
+        (UsageKind::Images, "image", false),
+        (UsageKind::Containers, "containers", true),
+        (UsageKind::Volumes, "volumes", true),
+        (UsageKind::BuildCache, "buildkit", false),
+        (UsageKind::Images, "overlay", false),
+        (UsageKind::Images, "overlay-images", false),
+        (UsageKind::Images, "overlay-layers", false),
+        (UsageKind::Containers, "overlay-containers", true),
+    ];
+
+    let mut items = Vec::new();
+    for &(kind, dir, per_child) in layout {
+        let path = root.join(dir);
+        if !path.is_dir() {
+            continue;
+        }
+        let paths: Vec<PathBuf> = if per_child {
+            std::fs::read_dir(&path)?
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|e| e.path())
+                .collect()
+        } else {
+            vec![path]
+        };
+        for path in paths {
+            let (size, _) = measure(&path, config);
+            items.push(UsageItem {
+                kind,
+                name: path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string(),
+                size,
+                in_use: false,
+            });
+        }
+    }
+
+    Ok(ContainerUsage::from_items(
+        root.to_path_buf(),
+        items,
+        false,
+        None,
+    ))
+}
+
+/// Items from a `GET /system/df` response (Docker and Podman's compat API)
+fn parse_system_df(df: &Value) -> Vec<UsageItem> {
+    let list = |key: &str| df[key].as_array().cloned().unwrap_or_default();
+    let size = |v: &Value| v.as_i64().unwrap_or(0).max(0) as u64;
+    let short_id = |v: &Value| {
+        let id = v.as_str().unwrap_or_default();
+        id.trim_start_matches("sha256:")
+            .chars()
+            .take(12)
+            .collect::<String>()
+    };
+
+    let mut items = Vec::new();
+    for image in list("Images") {
+        let name = image["RepoTags"]
+            .as_array()
+            .and_then(|tags| tags.first())
+            .and_then(Value::as_str)
+            .filter(|tag| *tag != "<none>:<none>")
+            .map(String::from)
+            .unwrap_or_else(|| short_id(&image["Id"]));
+        // Layers shared with other images only count toward the images total
+        items.push(UsageItem {
+            kind: UsageKind::Images,
+            name,
+            size: size(&image["Size"]).saturating_sub(size(&image["SharedSize"])),
+            in_use: image["Containers"].as_i64().unwrap_or(0) > 0,
+        });
+    }
+    for container in list("Containers") {
+        let name = container["Names"]
+            .as_array()
+            .and_then(|names| names.first())
+            .and_then(Value::as_str)
+            .map(|n| n.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| short_id(&container["Id"]));
+        items.push(UsageItem {
+            kind: UsageKind::Containers,
+            name,
+            size: size(&container["SizeRw"]),
+            in_use: container["State"].as_str() == Some("running"),
+        });
+    }
+    for volume in list("Volumes") {
+        items.push(UsageItem {
+            kind: UsageKind::Volumes,
+            name: volume["Name"].as_str().unwrap_or_default().to_string(),
+            size: size(&volume["UsageData"]["Size"]),
+            in_use: volume["UsageData"]["RefCount"].as_i64().unwrap_or(0) > 0,
+        });
+    }
+    for record in list("BuildCache") {
+        items.push(UsageItem {
+            kind: UsageKind::BuildCache,
+            name: record["Description"]
+                .as_str()
+                .filter(|d| !d.is_empty())
+                .map(String::from)
+                .unwrap_or_else(|| short_id(&record["ID"])),
+            size: size(&record["Size"]),
+            in_use: record["InUse"].as_bool().unwrap_or(false),
+        });
+    }
+    items
+}
+
+/// Minimal HTTP/1.0 GET over a unix socket; 1.0 keeps the body un-chunked
+fn http_get(socket: &Path, path: &str) -> Result<Vec<u8>> {
+    let io_error = |e: std::io::Error| FsError::IoError {
+        context: format!("Failed to query {}", socket.display()),
+        source: e,
+    };
+    let mut stream = UnixStream::connect(socket).map_err(io_error)?;
+    // system/df walks every layer, which takes a while on big installations
+    stream
+        .set_read_timeout(Some(Duration::from_secs(120)))
+        .map_err(io_error)?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path).map_err(io_error)?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(io_error)?;
+
+    let split = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| FsError::InvalidFormat {
+            format: format!("{}: malformed HTTP response", socket.display()),
+        })?;
+    let status_line = String::from_utf8_lossy(&response[..split]);
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "{}: GET {} returned {}",
+                socket.display(),
+                path,
+                status_line.lines().next().unwrap_or_default()
+            ),
+        });
+    }
+    Ok(response[split + 4..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    const SYSTEM_DF: &str = r#"{
+        "LayersSize": 1000,
+        "Images": [
+            {"Id": "sha256:aaaaaaaaaaaaaaaa", "RepoTags": ["postgres:16"], "Size": 600, "SharedSize": 100, "Containers": 1},
+            {"Id": "sha256:bbbbbbbbbbbbbbbb", "RepoTags": null, "Size": 300, "SharedSize": 0, "Containers": 0}
+        ],
+        "Containers": [
+            {"Id": "c1", "Names": ["/db"], "SizeRw": 50, "State": "running"},
+            {"Id": "c2", "Names": ["/old"], "SizeRw": 20, "State": "exited"}
+        ],
+        "Volumes": [
+            {"Name": "pgdata", "UsageData": {"Size": 400, "RefCount": 1}},
+            {"Name": "orphan", "UsageData": {"Size": 70, "RefCount": 0}}
+        ],
+        "BuildCache": [
+            {"ID": "x1", "Description": "mount / from exec", "Size": 30, "InUse": false}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_system_df() {
+        let df: Value = serde_json::from_str(SYSTEM_DF).unwrap();
+        let usage = ContainerUsage::from_items(
+            PathBuf::from("sock"),
+            parse_system_df(&df),
+            true,
+            Some(1000),
+        );
+
+        let images = &usage.summary[0];
+        assert_eq!((images.count, images.size), (2, 1000));
+        assert_eq!(images.reclaimable, Some(300));
+        assert_eq!(usage.summary[1].reclaimable, Some(20));
+        assert_eq!(usage.summary[2].reclaimable, Some(70));
+        assert_eq!(usage.total(), 1000 + 70 + 470 + 30);
+        assert_eq!(usage.items[0].name, "postgres:16");
+        assert!(usage.items.iter().any(|i| i.name == "bbbbbbbbbbbb"));
+    }
+
+    #[test]
+    fn test_usage_from_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("docker.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let len = stream.read(&mut request).unwrap();
+            assert!(request[..len].starts_with(b"GET /system/df HTTP/1.0\r\n"));
+            write!(
+                stream,
+                "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}",
+                SYSTEM_DF
+            )
+            .unwrap();
+        });
+
+        let usage = usage_from_socket(&path).unwrap();
+        server.join().unwrap();
+        assert_eq!(usage.items.len(), 7);
+    }
+
+    #[test]
+    fn test_usage_from_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("overlay2/l1")).unwrap();
+        std::fs::write(root.join("overlay2/l1/layer"), vec![0u8; 500]).unwrap();
+        std::fs::create_dir_all(root.join("volumes/cache/_data")).unwrap();
+        std::fs::write(root.join("volumes/cache/_data/f"), vec![0u8; 40]).unwrap();
+
+        let usage = usage_from_storage(root, &TraverseConfig::default()).unwrap();
+        assert_eq!(usage.summary[0].size, 500);
+        assert_eq!(usage.summary[2].count, 1);
+        assert_eq!(usage.summary[2].reclaimable, None);
+        assert_eq!(usage.items[1].name, "volumes/cache");
+    }
+}
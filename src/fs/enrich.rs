@@ -0,0 +1,481 @@
+#[cfg(not(all(
+    feature = "dedup",
+    feature = "git",
+    feature = "exif",
+    feature = "object"
+)))]
+use crate::errors::FsError;
+use crate::errors::Result;
+use crate::fs::size::{
+    compute_dir_sizes, compute_last_activity, update_entries_with_dir_sizes,
+    update_entries_with_last_activity,
+};
+use crate::models::{Column, Entry, EntryKind, FileCategory};
+use crate::timings;
+use std::path::Path;
+
+/// Adds metadata to entries after traversal
+///
+/// Enrichers run only when one of their columns is requested (see
+/// [`enrichers_for`]), so expensive ones such as hashing cost nothing
+/// otherwise. A new kind of metadata needs a field on
+/// [`Enrichment`](crate::models::Enrichment), a [`Column`] and an enricher
+/// registered in [`enrichers_for`].
+pub trait Enricher: Sync {
+    /// Short identifier, also used as the timing phase name
+    fn id(&self) -> &'static str;
+
+    /// Columns this enricher fills in
+    fn columns(&self) -> &'static [Column];
+
+    /// Fill in metadata; failures for individual entries leave them unset
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()>;
+}
+
+/// Enrichers needed for the requested columns, in a stable order; file types
+/// are told by `types`
+///
+/// Errors when a column needs a feature this build doesn't have.
+pub fn enrichers_for(columns: &[Column], types: TypeDetection) -> Result<Vec<Box<dyn Enricher>>> {
+    let mut enrichers: Vec<Box<dyn Enricher>> = Vec::new();
+    for column in columns {
+        let enricher: Box<dyn Enricher> = match column {
+            Column::LastActivity => Box::new(LastActivityEnricher),
+            Column::Category => Box::new(CategoryEnricher { types }),
+            Column::Mime => Box::new(MimeEnricher { types }),
+            Column::BackupExcluded => Box::new(BackupExclusionEnricher),
+            #[cfg(feature = "dedup")]
+            Column::Hash => Box::new(HashEnricher),
+            #[cfg(not(feature = "dedup"))]
+            Column::Hash => return Err(missing_feature("hash", "dedup")),
+            #[cfg(feature = "git")]
+            Column::GitStatus => Box::new(GitStatusEnricher),
+            #[cfg(not(feature = "git"))]
+            Column::GitStatus => return Err(missing_feature("git_status", "git")),
+            #[cfg(feature = "exif")]
+            Column::Taken => Box::new(ExifEnricher),
+            #[cfg(not(feature = "exif"))]
+            Column::Taken => return Err(missing_feature("taken", "exif")),
+            #[cfg(feature = "object")]
+            Column::BinSections => Box::new(BinaryEnricher),
+            #[cfg(not(feature = "object"))]
+            Column::BinSections => return Err(missing_feature("bin_sections", "object")),
+            _ => continue,
+        };
+        if !enrichers.iter().any(|e| e.id() == enricher.id()) {
+            enrichers.push(enricher);
+        }
+    }
+    Ok(enrichers)
+}
+
+/// Run enrichers over the entries in order
+pub fn enrich_entries(entries: &mut [Entry], enrichers: &[Box<dyn Enricher>]) -> Result<()> {
+    for enricher in enrichers {
+        let _timer = timings::phase(enricher.id());
+        enricher.enrich(entries)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(all(
+    feature = "dedup",
+    feature = "git",
+    feature = "exif",
+    feature = "object"
+)))]
+fn missing_feature(column: &str, feature: &str) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("column '{}' requires the {} feature", column, feature),
+    }
+}
+
+/// Apply `f` to every file entry, in parallel when available
+fn for_each_file<F>(entries: &mut [Entry], f: F)
+where
+    F: Fn(&mut Entry) + Sync + Send,
+{
+    let files = entries.iter_mut().filter(|e| e.kind == EntryKind::File);
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        files.collect::<Vec<_>>().into_par_iter().for_each(f);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    files.for_each(f);
+}
+
+/// Total size of everything under each directory (`size --aggregate`)
+pub struct DirSizeEnricher;
+
+impl Enricher for DirSizeEnricher {
+    fn id(&self) -> &'static str {
+        "aggregate"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        let dir_sizes = compute_dir_sizes(entries);
+        update_entries_with_dir_sizes(entries, &dir_sizes);
+        Ok(())
+    }
+}
+
+/// Newest mtime of any file under each directory
+pub struct LastActivityEnricher;
+
+impl Enricher for LastActivityEnricher {
+    fn id(&self) -> &'static str {
+        "last-activity"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::LastActivity]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        let activity = compute_last_activity(entries);
+        update_entries_with_last_activity(entries, &activity);
+        Ok(())
+    }
+}
+
+/// Category from the file extension or content (`source/rust`,
+/// `media/image`, ...)
+pub struct CategoryEnricher {
+    pub types: TypeDetection,
+}
+
+impl Enricher for CategoryEnricher {
+    fn id(&self) -> &'static str {
+        "category"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::Category]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        for_each_file(entries, |entry| {
+            entry.enriched.category = Some(self.types.category(&entry.path).label());
+        });
+        Ok(())
+    }
+}
+
+/// MIME type from the file extension, or its content with `--detect-type`
+pub struct MimeEnricher {
+    pub types: TypeDetection,
+}
+
+impl Enricher for MimeEnricher {
+    fn id(&self) -> &'static str {
+        "mime"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::Mime]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        for_each_file(entries, |entry| {
+            entry.enriched.mime = Some(self.types.mime(&entry.path).to_string());
+        });
+        Ok(())
+    }
+}
+
+/// Time Machine exclusion attribute (never set outside macOS)
+pub struct BackupExclusionEnricher;
+
+impl Enricher for BackupExclusionEnricher {
+    fn id(&self) -> &'static str {
+        "backup-excluded"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::BackupExcluded]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        for entry in entries.iter_mut() {
+            entry.enriched.backup_excluded =
+                Some(crate::fs::backup::is_backup_excluded(&entry.path));
+        }
+        Ok(())
+    }
+}
+
+/// BLAKE3 content hash
+#[cfg(feature = "dedup")]
+pub struct HashEnricher;
+
+#[cfg(feature = "dedup")]
+impl Enricher for HashEnricher {
+    fn id(&self) -> &'static str {
+        "hash"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::Hash]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        for_each_file(entries, |entry| {
+            entry.enriched.hash = crate::fs::dedup::hash_file_logged(&entry.path).ok();
+        });
+        Ok(())
+    }
+}
+
+/// `git status` of each entry inside a repository
+#[cfg(feature = "git")]
+pub struct GitStatusEnricher;
+
+#[cfg(feature = "git")]
+impl Enricher for GitStatusEnricher {
+    fn id(&self) -> &'static str {
+        "git-status"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::GitStatus]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        use crate::fs::git::{get_git_status, repo_toplevel, GitStatus};
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+
+        // Repositories are looked up once per directory, statuses once per repository
+        let mut toplevels: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+        let mut statuses: HashMap<PathBuf, HashMap<PathBuf, GitStatus>> = HashMap::new();
+
+        for entry in entries.iter_mut().filter(|e| e.kind == EntryKind::File) {
+            let Ok(path) = entry.path.canonicalize() else {
+                continue;
+            };
+            let Some(dir) = path.parent() else {
+                continue;
+            };
+            let toplevel = toplevels
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| repo_toplevel(dir).ok());
+            let Some(toplevel) = toplevel else {
+                continue;
+            };
+
+            if !statuses.contains_key(toplevel.as_path()) {
+                let status = get_git_status(toplevel).unwrap_or_default();
+                statuses.insert(toplevel.clone(), status);
+            }
+            let status = statuses[toplevel.as_path()]
+                .get(&path)
+                .copied()
+                .unwrap_or(GitStatus::Clean);
+            entry.enriched.git_status = Some(status.to_str().to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Photo capture time from EXIF data
+#[cfg(feature = "exif")]
+pub struct ExifEnricher;
+
+#[cfg(feature = "exif")]
+impl Enricher for ExifEnricher {
+    fn id(&self) -> &'static str {
+        "exif"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::Taken]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        use crate::models::MediaType;
+
+        for_each_file(entries, |entry| {
+            let category = FileCategory::from_extension(extension(&entry.path));
+            if matches!(
+                category,
+                FileCategory::Media {
+                    media_type: MediaType::Image
+                }
+            ) {
+                entry.enriched.taken = crate::fs::touch::exif_datetime(&entry.path).ok();
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Executable format and section sizes, for files with an ELF/Mach-O/PE header
+#[cfg(feature = "object")]
+pub struct BinaryEnricher;
+
+#[cfg(feature = "object")]
+impl Enricher for BinaryEnricher {
+    fn id(&self) -> &'static str {
+        "bin-sections"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &[Column::BinSections]
+    }
+
+    fn enrich(&self, entries: &mut [Entry]) -> Result<()> {
+        use crate::fs::bininfo::{analyze_binary, looks_like_binary};
+
+        for_each_file(entries, |entry| {
+            if looks_like_binary(&entry.path) {
+                entry.enriched.bin_sections =
+                    analyze_binary(&entry.path).ok().map(|info| info.summary());
+            }
+        });
+        Ok(())
+    }
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+/// Where file types (the `mime` and `category` columns, `--category`) come
+/// from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TypeDetection {
+    /// The file extension
+    #[default]
+    Extension,
+    /// The start of the file (`--detect-type`); by extension without the
+    /// `mime` feature
+    Content,
+}
+
+impl TypeDetection {
+    /// MIME type of the file at `path`
+    pub fn mime(self, path: &Path) -> &'static str {
+        #[cfg(feature = "mime")]
+        if self == TypeDetection::Content {
+            return crate::fs::mime::detect(path);
+        }
+        mime_from_extension(extension(path)).unwrap_or("application/octet-stream")
+    }
+
+    /// Category of the file at `path`
+    pub fn category(self, path: &Path) -> FileCategory {
+        #[cfg(feature = "mime")]
+        if self == TypeDetection::Content {
+            return crate::fs::mime::detect_category(path);
+        }
+        FileCategory::from_extension(extension(path))
+    }
+}
+
+/// MIME type for common file extensions
+pub fn mime_from_extension(ext: &str) -> Option<&'static str> {
+    let mime = match ext.to_lowercase().as_str() {
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "zst" => "application/zstd",
+        "7z" => "application/x-7z-compressed",
+        "wasm" => "application/wasm",
+        "sh" => "application/x-sh",
+        "rs" => "text/x-rust",
+        "py" => "text/x-python",
+        "c" | "h" => "text/x-c",
+        "cpp" | "cc" | "cxx" | "hpp" => "text/x-c++",
+        "go" => "text/x-go",
+        "java" => "text/x-java",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" => "image/vnd.microsoft.icon",
+        "tif" | "tiff" => "image/tiff",
+        "heic" => "image/heic",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "avi" => "video/x-msvideo",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(path: &str, kind: EntryKind) -> Entry {
+        Entry {
+            size: 10,
+            kind,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_enrichers_for_columns() {
+        let enrichers = enrichers_for(
+            &[Column::Path, Column::Mime, Column::Category, Column::Mime],
+            TypeDetection::Extension,
+        )
+        .unwrap();
+        let ids: Vec<&str> = enrichers.iter().map(|e| e.id()).collect();
+        assert_eq!(ids, vec!["mime", "category"]);
+        assert!(
+            enrichers_for(&[Column::Path, Column::Size], TypeDetection::Extension)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_category_and_mime() {
+        let mut entries = vec![
+            make_entry("src/main.rs", EntryKind::File),
+            make_entry("photo.JPG", EntryKind::File),
+            make_entry("src", EntryKind::Dir),
+        ];
+        let enrichers =
+            enrichers_for(&[Column::Category, Column::Mime], TypeDetection::Extension).unwrap();
+        enrich_entries(&mut entries, &enrichers).unwrap();
+
+        assert_eq!(entries[0].enriched.category.as_deref(), Some("source/rust"));
+        assert_eq!(entries[0].enriched.mime.as_deref(), Some("text/x-rust"));
+        assert_eq!(entries[1].enriched.category.as_deref(), Some("media/image"));
+        assert_eq!(entries[1].enriched.mime.as_deref(), Some("image/jpeg"));
+        // Directories are left alone
+        assert_eq!(entries[2].enriched, Default::default());
+    }
+}
@@ -0,0 +1,154 @@
+//! External command filters (`--filter-exec 'CMD {}'`)
+//!
+//! The command runs through the shell once per entry, with `{}` standing for
+//! the entry's path (appended when the template has no `{}`). Exit status 0
+//! keeps the entry, so validators like `file`, `exiftool` or a custom script
+//! can take part in filtering. The command's stdout is discarded.
+//!
+//! Spawning a process per entry is slow, so this filter runs after the walk
+//! and the other filters, on the survivors only, several commands at a time
+//! (see [`ExecFilter::retain`]).
+
+use crate::fs::filters::{Predicate, PredicateNode};
+use crate::models::Entry;
+use crate::util::shell_command;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tracing::warn;
+
+/// Keeps entries for which an external command succeeds
+pub struct ExecFilter {
+    template: String,
+    jobs: usize,
+    failed: AtomicBool,
+}
+
+impl ExecFilter {
+    /// Filter running `template`, at most `jobs` commands at a time
+    /// (`None`: one per CPU)
+    pub fn new(template: &str, jobs: Option<usize>) -> Self {
+        let template = if template.contains("{}") {
+            template.to_string()
+        } else {
+            format!("{} {{}}", template)
+        };
+        let jobs =
+            jobs.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        Self {
+            template,
+            jobs: jobs.max(1),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// The entries for which the command succeeds, in their original order
+    pub fn retain(&self, entries: Vec<Entry>) -> Vec<Entry> {
+        let next = AtomicUsize::new(0);
+        let keep: Vec<AtomicBool> = entries.iter().map(|_| AtomicBool::new(false)).collect();
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.jobs.min(entries.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = entries.get(i) else {
+                        break;
+                    };
+                    keep[i].store(self.test(entry), Ordering::Relaxed);
+                });
+            }
+        });
+
+        entries
+            .into_iter()
+            .zip(keep)
+            .filter(|(_, keep)| keep.load(Ordering::Relaxed))
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+
+    /// The shell command for one path
+    ///
+    /// On Unix the path is passed as `$1` rather than spliced into the
+    /// script, so no file name can break out of its quoting.
+    fn command(&self, path: &Path) -> Command {
+        if cfg!(unix) {
+            let mut command = shell_command(&self.template.replace("{}", "\"$1\""));
+            command.arg("fexplorer").arg(path);
+            command
+        } else {
+            let quoted = format!("\"{}\"", path.display());
+            shell_command(&self.template.replace("{}", &quoted))
+        }
+    }
+}
+
+impl Predicate for ExecFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        let status = self
+            .command(&entry.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status();
+        match status {
+            Ok(status) => status.success(),
+            Err(e) => {
+                if !self.failed.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        command = %self.template,
+                        error = %e,
+                        "Filter command failed to run, excluding the entries it fails on"
+                    );
+                }
+                false
+            }
+        }
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("exec", self.template.clone(), 0.5)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    #[test]
+    fn test_exec_filter_keeps_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let names = ["a.txt", "b.bin", "it's here.txt", "d.txt"];
+        for name in names {
+            let contents = if name.ends_with(".txt") { "text" } else { "\0" };
+            std::fs::write(dir.path().join(name), contents).unwrap();
+        }
+        let entries: Vec<Entry> = names
+            .iter()
+            .map(|name| extract_entry(&dir.path().join(name), 1).unwrap())
+            .collect();
+
+        let filter = ExecFilter::new("grep -q text {}", Some(2));
+        let kept: Vec<String> = filter
+            .retain(entries.clone())
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(kept, ["a.txt", "it's here.txt", "d.txt"]);
+
+        // Without `{}` the path is appended
+        let appended = ExecFilter::new("test -s", None);
+        assert_eq!(appended.retain(entries).len(), 4);
+    }
+
+    #[test]
+    fn test_exec_filter_failure_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        std::fs::write(&path, "data").unwrap();
+        let entry = extract_entry(&path, 1).unwrap();
+
+        assert!(!ExecFilter::new("exit 1 #", None).test(&entry));
+        assert!(!ExecFilter::new("no-such-command-fexplorer {}", None).test(&entry));
+    }
+}
@@ -0,0 +1,171 @@
+//! `--explain`: what a command would walk and how it would filter
+//!
+//! Built from the same traversal config and predicate the command would use,
+//! so a profile or `--where` expression can be checked without running it.
+
+use crate::fs::filters::{Predicate, PredicateNode};
+use crate::fs::traverse::TraverseConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Traversal settings of a plan
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraversalPlan {
+    pub max_depth: Option<usize>,
+    pub hidden: bool,
+    pub gitignore: bool,
+    pub follow_symlinks: bool,
+    pub skip_other_users: bool,
+    pub exclude: Vec<String>,
+    pub threads: usize,
+    /// Results left in the order the threads found them
+    pub unordered: bool,
+}
+
+impl From<&TraverseConfig> for TraversalPlan {
+    fn from(config: &TraverseConfig) -> Self {
+        Self {
+            max_depth: config.max_depth,
+            hidden: config.include_hidden,
+            gitignore: config.respect_gitignore,
+            follow_symlinks: config.follow_symlinks,
+            skip_other_users: config.skip_other_users,
+            exclude: config.exclude.clone(),
+            threads: config.threads,
+            unordered: config.unordered,
+        }
+    }
+}
+
+/// Everything `--explain` reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryPlan {
+    /// Roots to walk
+    pub roots: Vec<PathBuf>,
+    /// File the paths are read from instead (`--files-from`, `-` for `--stdin`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_list: Option<PathBuf>,
+    /// Export the entries come from instead (`--from-results`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<PathBuf>,
+    pub traversal: TraversalPlan,
+    /// Compiled filter tree; `None` keeps every entry
+    pub filter: Option<PredicateNode>,
+    /// Estimated share of walked entries kept
+    pub selectivity: f64,
+}
+
+impl QueryPlan {
+    pub fn new(
+        roots: &[PathBuf],
+        path_list: Option<PathBuf>,
+        config: &TraverseConfig,
+        predicate: Option<&dyn Predicate>,
+    ) -> Self {
+        let filter = predicate.map(|p| p.explain());
+        Self {
+            roots: if path_list.is_some() {
+                Vec::new()
+            } else {
+                roots.to_vec()
+            },
+            path_list,
+            results: None,
+            traversal: TraversalPlan::from(config),
+            selectivity: filter.as_ref().map_or(1.0, |f| f.selectivity),
+            filter,
+        }
+    }
+
+    /// Take the entries from an earlier export instead of walking
+    pub fn with_results(mut self, source: &Path) -> Self {
+        self.roots.clear();
+        self.path_list = None;
+        self.results = Some(source.to_path_buf());
+        self
+    }
+
+    /// Add a filter that runs after the walk (`--filter-exec`), ANDed last
+    pub fn with_post_filter(mut self, node: PredicateNode) -> Self {
+        let filter = match self.filter.take() {
+            Some(mut and) if and.filter == "and" => {
+                and.selectivity *= node.selectivity;
+                and.children.push(node);
+                and
+            }
+            Some(first) => PredicateNode {
+                filter: "and".to_string(),
+                detail: String::new(),
+                selectivity: first.selectivity * node.selectivity,
+                children: vec![first, node],
+            },
+            None => node,
+        };
+        self.selectivity = filter.selectivity;
+        self.filter = Some(filter);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::enrich::TypeDetection;
+    use crate::fs::filters::{AndPredicate, ExtensionFilter, SizeFilter};
+    use crate::fs::query::parse_where;
+
+    #[test]
+    fn test_plan_of_filters() {
+        let predicate = AndPredicate::new(vec![
+            Box::new(ExtensionFilter::new(&["rs".to_string()])),
+            Box::new(SizeFilter::new(Some("10KB"), None).unwrap()),
+            parse_where(
+                "name ~ 'test_*' or not kind = dir",
+                TypeDetection::Extension,
+            )
+            .unwrap(),
+        ]);
+        let config = TraverseConfig {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let plan = QueryPlan::new(&[PathBuf::from("src")], None, &config, Some(&predicate));
+
+        assert_eq!(plan.roots, [PathBuf::from("src")]);
+        assert_eq!(plan.traversal.max_depth, Some(2));
+        let filter = plan.filter.as_ref().unwrap();
+        assert_eq!(filter.filter, "and");
+        let names: Vec<&str> = filter.children.iter().map(|c| c.filter.as_str()).collect();
+        assert_eq!(names, ["extension", "size (files only)", "or"]);
+        assert_eq!(filter.children[2].children[0].detail, "~ test_*");
+        assert_eq!(filter.children[2].children[1].filter, "not");
+        assert!(plan.selectivity > 0.0 && plan.selectivity < 0.05);
+    }
+
+    #[test]
+    fn test_impossible_range_is_flagged() {
+        let size = SizeFilter::new(Some("2MB"), Some("1MB")).unwrap();
+        let plan = QueryPlan::new(&[], None, &TraverseConfig::default(), Some(&size));
+        assert_eq!(plan.selectivity, 0.0);
+
+        let plan = QueryPlan::new(&[], None, &TraverseConfig::default(), None);
+        assert!(plan.filter.is_none());
+        assert_eq!(plan.selectivity, 1.0);
+    }
+
+    #[test]
+    fn test_post_filter_runs_last() {
+        let exec = || PredicateNode::leaf("exec", "file {}".to_string(), 0.5);
+        let plan =
+            QueryPlan::new(&[], None, &TraverseConfig::default(), None).with_post_filter(exec());
+        assert_eq!(plan.filter.as_ref().unwrap().filter, "exec");
+
+        let ext = ExtensionFilter::new(&["jpg".to_string()]);
+        let plan = QueryPlan::new(&[], None, &TraverseConfig::default(), Some(&ext))
+            .with_post_filter(exec());
+        let filter = plan.filter.as_ref().unwrap();
+        let names: Vec<&str> = filter.children.iter().map(|c| c.filter.as_str()).collect();
+        assert_eq!(names, ["extension", "exec"]);
+        assert_eq!(plan.selectivity, filter.children[0].selectivity * 0.5);
+    }
+}
@@ -0,0 +1,203 @@
+//! Inventory export as a change feed
+//!
+//! `fexplorer export --since <time>` diffs the current tree against the newest
+//! snapshot of it taken at or before that time, so a periodic job only has to
+//! sync what was added, modified or removed. `--snapshot` saves the exported
+//! state as the baseline for the next run.
+
+use crate::fs::snapshot::{SnapshotEntry, SnapshotMeta};
+use crate::models::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// What happened to an entry since the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+/// One record of the change feed
+///
+/// Removed entries carry their last recorded size, kind and mtime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub change: ChangeKind,
+    #[serde(with = "crate::util::serde_path")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub kind: EntryKind,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub mtime: DateTime<Utc>,
+}
+
+impl ChangeRecord {
+    fn from_entry(change: ChangeKind, entry: &Entry) -> Self {
+        Self {
+            change,
+            path: entry.path.clone(),
+            size: entry.size,
+            kind: entry.kind,
+            mtime: entry.mtime,
+        }
+    }
+}
+
+/// The snapshot to diff against for `--since`: the newest one taken at or
+/// before `since` (`snapshots` oldest first, as listed by the store)
+pub fn baseline_for(snapshots: &[SnapshotMeta], since: DateTime<Utc>) -> Option<&SnapshotMeta> {
+    snapshots.iter().rev().find(|meta| meta.created <= since)
+}
+
+/// Changes from `before` (entries relative to `root`) to `after`, sorted by path
+///
+/// An entry counts as modified when its kind, size or mtime (to the second,
+/// as snapshots store it) differs.
+pub fn diff_against(root: &Path, before: Vec<SnapshotEntry>, after: &[Entry]) -> Vec<ChangeRecord> {
+    let mut before: HashMap<PathBuf, SnapshotEntry> = before
+        .into_iter()
+        .map(|e| (root.join(&e.path), e))
+        .collect();
+
+    let mut changes = Vec::new();
+    for entry in after {
+        let change = match before.remove(&entry.path) {
+            None => ChangeKind::Added,
+            Some(old)
+                if old.kind != entry.kind
+                    || old.size != entry.size
+                    || old.mtime.timestamp() != entry.mtime.timestamp() =>
+            {
+                ChangeKind::Modified
+            }
+            Some(_) => continue,
+        };
+        changes.push(ChangeRecord::from_entry(change, entry));
+    }
+    changes.extend(before.into_iter().map(|(path, old)| ChangeRecord {
+        change: ChangeKind::Removed,
+        path,
+        size: old.size,
+        kind: old.kind,
+        mtime: old.mtime,
+    }));
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// Changes without a baseline: entries modified since `since`, when given
+///
+/// Without a snapshot there's no telling new entries from modified ones, or
+/// seeing removals, so recent entries are all reported as modified. With no
+/// `since` the whole inventory is exported as added.
+pub fn changes_by_mtime(after: &[Entry], since: Option<DateTime<Utc>>) -> Vec<ChangeRecord> {
+    let mut changes: Vec<ChangeRecord> = match since {
+        Some(since) => after
+            .iter()
+            .filter(|entry| entry.mtime >= since)
+            .map(|entry| ChangeRecord::from_entry(ChangeKind::Modified, entry))
+            .collect(),
+        None => after
+            .iter()
+            .map(|entry| ChangeRecord::from_entry(ChangeKind::Added, entry))
+            .collect(),
+    };
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+    use std::fs;
+
+    fn snapshot_entry(path: &str, entry: &Entry) -> SnapshotEntry {
+        SnapshotEntry {
+            path: PathBuf::from(path),
+            size: entry.size,
+            kind: entry.kind,
+            mtime: entry.mtime,
+        }
+    }
+
+    #[test]
+    fn test_diff_against_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("same.txt"), "same").unwrap();
+        fs::write(root.join("grown.txt"), "grown").unwrap();
+        fs::write(root.join("new.txt"), "new").unwrap();
+
+        let same = extract_entry(&root.join("same.txt"), 1).unwrap();
+        let grown = extract_entry(&root.join("grown.txt"), 1).unwrap();
+        let new = extract_entry(&root.join("new.txt"), 1).unwrap();
+
+        let mut old_grown = snapshot_entry("grown.txt", &grown);
+        old_grown.size -= 1;
+        let mut gone = snapshot_entry("gone.txt", &same);
+        gone.size = 42;
+        let before = vec![snapshot_entry("same.txt", &same), old_grown, gone];
+
+        let changes = diff_against(root, before, &[same, grown, new]);
+        let summary: Vec<(ChangeKind, &str)> = changes
+            .iter()
+            .map(|c| (c.change, c.path.file_name().unwrap().to_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (ChangeKind::Removed, "gone.txt"),
+                (ChangeKind::Modified, "grown.txt"),
+                (ChangeKind::Added, "new.txt"),
+            ]
+        );
+        assert_eq!(changes[0].size, 42);
+        assert_eq!(changes[0].path, root.join("gone.txt"));
+    }
+
+    #[test]
+    fn test_changes_by_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "data").unwrap();
+        let entry = extract_entry(&path, 1).unwrap();
+        let entries = [entry.clone()];
+
+        let all = changes_by_mtime(&entries, None);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].change, ChangeKind::Added);
+
+        let recent = changes_by_mtime(&entries, Some(entry.mtime));
+        assert_eq!(recent[0].change, ChangeKind::Modified);
+        let later = entry.mtime + chrono::Duration::seconds(1);
+        assert!(changes_by_mtime(&entries, Some(later)).is_empty());
+    }
+
+    #[test]
+    fn test_baseline_for() {
+        let meta = |id: &str, secs| SnapshotMeta {
+            id: id.to_string(),
+            root: PathBuf::from("/data"),
+            created: DateTime::from_timestamp(secs, 0).unwrap(),
+            description: None,
+            exclude: Vec::new(),
+            total_size: 0,
+            files: 0,
+            dirs: 0,
+            categories: Default::default(),
+        };
+        let snapshots = [meta("a", 100), meta("b", 200), meta("c", 300)];
+        let at = |secs| DateTime::from_timestamp(secs, 0).unwrap();
+
+        assert!(baseline_for(&snapshots, at(50)).is_none());
+        assert_eq!(baseline_for(&snapshots, at(200)).unwrap().id, "b");
+        assert_eq!(baseline_for(&snapshots, at(299)).unwrap().id, "b");
+        assert_eq!(baseline_for(&snapshots, at(1000)).unwrap().id, "c");
+    }
+}
@@ -1,13 +1,65 @@
 use crate::errors::{FsError, Result};
+use crate::fs::enrich::TypeDetection;
+use crate::fs::size::DirSizeCache;
 use crate::models::{Entry, EntryKind, FileCategory};
-use crate::util::{parse_date, parse_size};
+use crate::util::{
+    format_size_human, parse_date, parse_duration, parse_size, parse_size_bound, SizeBound,
+};
 use chrono::{DateTime, Utc};
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use regex::Regex;
+use serde::Serialize;
+use std::sync::Arc;
 
 /// A predicate that can be applied to entries
 pub trait Predicate: Send + Sync {
     fn test(&self, entry: &Entry) -> bool;
+
+    /// Describe the predicate for `--explain`
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("custom", String::new(), 0.5)
+    }
+}
+
+/// One node of a compiled predicate tree, as printed by `--explain`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PredicateNode {
+    /// Filter or combinator (`and`, `glob`, `size`, ...)
+    pub filter: String,
+    /// The filter's settings, e.g. `*.rs, *.toml` or `>= 10.00 MiB`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    /// Rough share of entries expected to pass, from 0 to 1
+    ///
+    /// A rule-of-thumb guess from the filter's shape, not from the tree:
+    /// useful to spot a filter that can't match anything, not for planning.
+    pub selectivity: f64,
+    /// Operands of `and`/`or`/`not`, in evaluation order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PredicateNode>,
+}
+
+impl PredicateNode {
+    pub fn leaf(filter: &str, detail: String, selectivity: f64) -> Self {
+        Self {
+            filter: filter.to_string(),
+            detail,
+            selectivity: selectivity.clamp(0.0, 1.0),
+            children: Vec::new(),
+        }
+    }
+
+    fn branch(filter: &str, selectivity: f64, children: Vec<PredicateNode>) -> Self {
+        Self {
+            children,
+            ..Self::leaf(filter, String::new(), selectivity)
+        }
+    }
+}
+
+/// Share of entries expected to match any of `n` alternatives of share `each`
+fn any_of(n: usize, each: f64) -> f64 {
+    1.0 - (1.0 - each).powi(n as i32)
 }
 
 /// Combines multiple predicates with AND logic
@@ -25,11 +77,63 @@ impl Predicate for AndPredicate {
     fn test(&self, entry: &Entry) -> bool {
         self.predicates.iter().all(|p| p.test(entry))
     }
+
+    fn explain(&self) -> PredicateNode {
+        let children: Vec<PredicateNode> = self.predicates.iter().map(|p| p.explain()).collect();
+        let selectivity = children.iter().map(|c| c.selectivity).product();
+        PredicateNode::branch("and", selectivity, children)
+    }
+}
+
+/// Combines multiple predicates with OR logic
+pub struct OrPredicate {
+    predicates: Vec<Box<dyn Predicate>>,
+}
+
+impl OrPredicate {
+    pub fn new(predicates: Vec<Box<dyn Predicate>>) -> Self {
+        Self { predicates }
+    }
+}
+
+impl Predicate for OrPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        self.predicates.iter().any(|p| p.test(entry))
+    }
+
+    fn explain(&self) -> PredicateNode {
+        let children: Vec<PredicateNode> = self.predicates.iter().map(|p| p.explain()).collect();
+        let rejected: f64 = children.iter().map(|c| 1.0 - c.selectivity).product();
+        PredicateNode::branch("or", 1.0 - rejected, children)
+    }
+}
+
+/// Negates another predicate
+pub struct NotPredicate {
+    inner: Box<dyn Predicate>,
+}
+
+impl NotPredicate {
+    pub fn new(inner: Box<dyn Predicate>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Predicate for NotPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        !self.inner.test(entry)
+    }
+
+    fn explain(&self) -> PredicateNode {
+        let inner = self.inner.explain();
+        PredicateNode::branch("not", 1.0 - inner.selectivity, vec![inner])
+    }
 }
 
 /// Glob pattern filter
 pub struct GlobFilter {
     globset: GlobSet,
+    patterns: Vec<String>,
 }
 
 impl GlobFilter {
@@ -46,7 +150,33 @@ impl GlobFilter {
             pattern: "combined".to_string(),
             source: e,
         })?;
-        Ok(Self { globset })
+        Ok(Self {
+            globset,
+            patterns: patterns.to_vec(),
+        })
+    }
+
+    /// Build a glob filter that ignores case (like find's -iname)
+    pub fn case_insensitive(patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| FsError::InvalidGlob {
+                    pattern: pattern.clone(),
+                    source: e,
+                })?;
+            builder.add(glob);
+        }
+        let globset = builder.build().map_err(|e| FsError::InvalidGlob {
+            pattern: "combined".to_string(),
+            source: e,
+        })?;
+        Ok(Self {
+            globset,
+            patterns: patterns.to_vec(),
+        })
     }
 }
 
@@ -54,6 +184,14 @@ impl Predicate for GlobFilter {
     fn test(&self, entry: &Entry) -> bool {
         self.globset.is_match(&entry.name)
     }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf(
+            "name glob",
+            self.patterns.join(", "),
+            any_of(self.patterns.len(), 0.05),
+        )
+    }
 }
 
 /// Regex pattern filter
@@ -75,6 +213,10 @@ impl Predicate for RegexFilter {
     fn test(&self, entry: &Entry) -> bool {
         self.regex.is_match(&entry.name)
     }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("name regex", self.regex.as_str().to_string(), 0.1)
+    }
 }
 
 /// Extension filter
@@ -92,52 +234,144 @@ impl ExtensionFilter {
 
 impl Predicate for ExtensionFilter {
     fn test(&self, entry: &Entry) -> bool {
-        if let Some(ext) = entry.path.extension().and_then(|e| e.to_str()) {
-            self.extensions.contains(&ext.to_lowercase())
+        if let Some(ext) = entry.path.extension() {
+            self.extensions
+                .contains(&ext.to_string_lossy().to_lowercase())
         } else {
             false
         }
     }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf(
+            "extension",
+            self.extensions.join(", "),
+            any_of(self.extensions.len(), 0.05),
+        )
+    }
 }
 
 /// Size range filter
 pub struct SizeFilter {
     min: Option<u64>,
     max: Option<u64>,
+    /// Judge directories by their total size instead of letting them pass
+    dir_sizes: Option<Arc<DirSizeCache>>,
 }
 
 impl SizeFilter {
     pub fn new(min: Option<&str>, max: Option<&str>) -> Result<Self> {
         let min = min.map(parse_size).transpose()?;
         let max = max.map(parse_size).transpose()?;
-        Ok(Self { min, max })
+        Ok(Self {
+            min,
+            max,
+            dir_sizes: None,
+        })
+    }
+
+    /// Also apply the range to directories, by the total size of
+    /// everything under them
+    pub fn with_dir_sizes(mut self, cache: Arc<DirSizeCache>) -> Self {
+        self.dir_sizes = Some(cache);
+        self
+    }
+
+    fn in_range(&self, size: u64) -> bool {
+        self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max)
+    }
+
+    /// Narrow the range with `--size` bounds like "+10M", "-1G" or "512k"
+    pub fn with_bounds(mut self, bounds: &[String]) -> Result<Self> {
+        for bound in bounds {
+            match parse_size_bound(bound)? {
+                SizeBound::AtLeast(n) => self.min = Some(self.min.map_or(n, |m| m.max(n))),
+                SizeBound::AtMost(n) => self.max = Some(self.max.map_or(n, |m| m.min(n))),
+                SizeBound::Exactly(n) => {
+                    self.min = Some(self.min.map_or(n, |m| m.max(n)));
+                    self.max = Some(self.max.map_or(n, |m| m.min(n)));
+                }
+            }
+        }
+        Ok(self)
     }
 }
 
 impl Predicate for SizeFilter {
     fn test(&self, entry: &Entry) -> bool {
-        if entry.kind == EntryKind::Dir {
-            return true; // Skip dirs for size filtering
+        if entry.kind != EntryKind::Dir {
+            return self.in_range(entry.size);
         }
+        match &self.dir_sizes {
+            Some(cache) => self.in_range(cache.size_of(&entry.path)),
+            None => true, // Skip dirs for size filtering
+        }
+    }
 
+    fn explain(&self) -> PredicateNode {
+        let mut bounds = Vec::new();
         if let Some(min) = self.min {
-            if entry.size < min {
-                return false;
-            }
+            bounds.push(format!(">= {}", format_size_human(min)));
         }
-
         if let Some(max) = self.max {
-            if entry.size > max {
-                return false;
-            }
+            bounds.push(format!("<= {}", format_size_human(max)));
         }
+        let selectivity = match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => 0.0,
+            (Some(min), Some(max)) if min == max => 0.01,
+            (Some(_), Some(_)) => 0.15,
+            (Some(_), None) => 0.2,
+            (None, Some(_)) => 0.8,
+            (None, None) => 1.0,
+        };
+        let filter = match self.dir_sizes {
+            Some(_) => "size (directories by total)",
+            None => "size (files only)",
+        };
+        PredicateNode::leaf(filter, bounds.join(", "), selectivity)
+    }
+}
 
-        true
+/// Which timestamp of an entry a [`DateFilter`] compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeField {
+    #[default]
+    Modified,
+    Accessed,
+    /// Status change (Unix ctime)
+    Changed,
+    /// Birth time
+    Created,
+}
+
+impl TimeField {
+    /// Name used for `--<name>-after` options and in `--explain`
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeField::Modified => "modified",
+            TimeField::Accessed => "accessed",
+            TimeField::Changed => "changed",
+            TimeField::Created => "created",
+        }
+    }
+
+    /// The timestamp, if the platform recorded it
+    pub fn of(self, entry: &Entry) -> Option<DateTime<Utc>> {
+        match self {
+            TimeField::Modified => Some(entry.mtime),
+            TimeField::Accessed => entry.atime,
+            TimeField::Changed => entry.ctime,
+            TimeField::Created => entry.created,
+        }
     }
 }
 
-/// Date range filter
+/// Date range filter, on the modification time unless set otherwise
+///
+/// Entries without the timestamp (no birth time on the filesystem, no ctime
+/// off Unix) never match.
 pub struct DateFilter {
+    field: TimeField,
     after: Option<DateTime<Utc>>,
     before: Option<DateTime<Utc>>,
 }
@@ -146,26 +380,82 @@ impl DateFilter {
     pub fn new(after: Option<&str>, before: Option<&str>) -> Result<Self> {
         let after = after.map(parse_date).transpose()?;
         let before = before.map(parse_date).transpose()?;
-        Ok(Self { after, before })
+        Ok(Self {
+            field: TimeField::Modified,
+            after,
+            before,
+        })
+    }
+
+    /// Compare `field` instead of the modification time
+    pub fn on(mut self, field: TimeField) -> Self {
+        self.field = field;
+        self
+    }
+
+    /// Narrow the range with age limits like "30d" (older than) or "2h" (newer than)
+    pub fn with_ages(mut self, older_than: Option<&str>, newer_than: Option<&str>) -> Result<Self> {
+        let now = Utc::now();
+        // Ages reaching back past the earliest representable date are errors
+        let cutoff = |age: &str| {
+            now.checked_sub_signed(parse_duration(age)?)
+                .ok_or_else(|| FsError::InvalidDate {
+                    input: age.to_string(),
+                })
+        };
+
+        if let Some(age) = older_than {
+            let cutoff = cutoff(age)?;
+            self.before = Some(self.before.map_or(cutoff, |b| b.min(cutoff)));
+        }
+
+        if let Some(age) = newer_than {
+            let cutoff = cutoff(age)?;
+            self.after = Some(self.after.map_or(cutoff, |a| a.max(cutoff)));
+        }
+
+        Ok(self)
     }
 }
 
 impl Predicate for DateFilter {
     fn test(&self, entry: &Entry) -> bool {
+        let Some(time) = self.field.of(entry) else {
+            return false;
+        };
+
         if let Some(after) = &self.after {
-            if entry.mtime < *after {
+            if time < *after {
                 return false;
             }
         }
 
         if let Some(before) = &self.before {
-            if entry.mtime > *before {
+            if time > *before {
                 return false;
             }
         }
 
         true
     }
+
+    fn explain(&self) -> PredicateNode {
+        let mut bounds = Vec::new();
+        if let Some(after) = self.after {
+            bounds.push(format!("after {}", after.to_rfc3339()));
+        }
+        if let Some(before) = self.before {
+            bounds.push(format!("before {}", before.to_rfc3339()));
+        }
+        let selectivity = match (self.after, self.before) {
+            (Some(after), Some(before)) if after > before => 0.0,
+            (Some(_), Some(_)) => 0.15,
+            (Some(_), None) => 0.2,
+            (None, Some(_)) => 0.8,
+            (None, None) => 1.0,
+        };
+        PredicateNode::leaf(self.field.name(), bounds.join(", "), selectivity)
+    }
 }
 
 /// Kind filter
@@ -185,20 +475,42 @@ impl Predicate for KindFilter {
     fn test(&self, entry: &Entry) -> bool {
         self.kinds.contains(&entry.kind)
     }
+
+    fn explain(&self) -> PredicateNode {
+        let names: Vec<&str> = self.kinds.iter().map(|kind| kind.as_str()).collect();
+        let selectivity = self
+            .kinds
+            .iter()
+            .map(|kind| match kind {
+                EntryKind::File => 0.85,
+                EntryKind::Dir => 0.14,
+                EntryKind::Symlink => 0.01,
+            })
+            .sum();
+        PredicateNode::leaf("kind", names.join(", "), selectivity)
+    }
 }
 
 /// Category filter - matches files by smart categorization
 pub struct CategoryFilter {
     category: String,
+    types: TypeDetection,
 }
 
 impl CategoryFilter {
     pub fn new(category: &str) -> Self {
         Self {
             category: category.to_lowercase(),
+            types: TypeDetection::Extension,
         }
     }
 
+    /// Tell file categories by `types` instead of by extension
+    pub fn detected_by(mut self, types: TypeDetection) -> Self {
+        self.types = types;
+        self
+    }
+
     /// Check if a FileCategory matches the filter
     fn matches_category(&self, file_category: &FileCategory) -> bool {
         match self.category.as_str() {
@@ -240,33 +552,231 @@ impl Predicate for CategoryFilter {
             return false;
         }
 
-        // Get file extension
-        if let Some(ext) = entry.path.extension().and_then(|e| e.to_str()) {
-            let category = FileCategory::from_extension(ext);
-            self.matches_category(&category)
+        self.matches_category(&self.types.category(&entry.path))
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("category", self.category.clone(), 0.1)
+    }
+}
+
+/// MIME type filter, by content: `image/png`, `image/*` or `image`
+#[cfg(feature = "mime")]
+pub struct MimeFilter {
+    patterns: Vec<String>,
+}
+
+#[cfg(feature = "mime")]
+impl MimeFilter {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns.iter().map(|p| p.to_lowercase()).collect(),
+        }
+    }
+
+    fn matches(&self, mime: &str) -> bool {
+        let kind = mime.split('/').next().unwrap_or(mime);
+        self.patterns
+            .iter()
+            .any(|pattern| match pattern.strip_suffix("/*") {
+                Some(prefix) => prefix == kind,
+                None if !pattern.contains('/') => pattern == kind,
+                None => pattern == mime,
+            })
+    }
+}
+
+#[cfg(feature = "mime")]
+impl Predicate for MimeFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        entry.kind == EntryKind::File && self.matches(crate::fs::mime::detect(&entry.path))
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("mime", self.patterns.join(", "), 0.2)
+    }
+}
+
+/// Sparse filter - matches files with less space allocated than their size
+pub struct SparseFilter;
+
+impl Predicate for SparseFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        entry.kind == EntryKind::File && entry.is_sparse()
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("sparse", String::new(), 0.01)
+    }
+}
+
+/// How [`PermFilter`] compares mode bits, as find(1)'s `-perm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermMatch {
+    /// `MODE`: exactly these bits
+    Exact,
+    /// `-MODE`: at least these bits
+    All,
+    /// `/MODE`: any of these bits
+    Any,
+}
+
+/// Permission filter - matches entries by mode bits, or by what the current
+/// user may do with them
+///
+/// Symlinks never match: their own permissions mean nothing on most systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermFilter {
+    Mode {
+        bits: u32,
+        how: PermMatch,
+    },
+    /// Writable by the current user
+    Writable,
+    /// Executable (searchable, for directories) by the current user
+    Executable,
+}
+
+impl PermFilter {
+    /// Parse `755`, `u=rwx,go=rx`, `-022` (all of) or `/111` (any of)
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (how, mode) = if let Some(mode) = spec.strip_prefix('-') {
+            (PermMatch::All, mode)
+        } else if let Some(mode) = spec.strip_prefix('/') {
+            (PermMatch::Any, mode)
         } else {
-            false
+            (PermMatch::Exact, spec)
+        };
+        let bits = crate::fs::perms::ModeSpec::parse(mode)?.apply(0, false);
+        Ok(PermFilter::Mode { bits, how })
+    }
+
+    /// Writable by others: `-002`
+    pub fn world_writable() -> Self {
+        PermFilter::Mode {
+            bits: 0o002,
+            how: PermMatch::All,
+        }
+    }
+}
+
+impl Predicate for PermFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        if entry.kind == EntryKind::Symlink {
+            return false;
+        }
+        match *self {
+            PermFilter::Mode { bits, how } => {
+                let Some(mode) = mode_of(&entry.path) else {
+                    return false;
+                };
+                match how {
+                    PermMatch::Exact => mode == bits,
+                    PermMatch::All => mode & bits == bits,
+                    PermMatch::Any => bits == 0 || mode & bits != 0,
+                }
+            }
+            PermFilter::Writable => may_access(&entry.path, Access::Write),
+            PermFilter::Executable => may_access(&entry.path, Access::Execute),
+        }
+    }
+
+    fn explain(&self) -> PredicateNode {
+        match *self {
+            PermFilter::Mode { bits, how } => {
+                let (detail, selectivity) = match how {
+                    PermMatch::Exact => (format!("exactly {:04o}", bits), 0.1),
+                    PermMatch::All => (format!("all of {:04o}", bits), 0.05),
+                    PermMatch::Any => (format!("any of {:04o}", bits), 0.3),
+                };
+                PredicateNode::leaf("perm", detail, selectivity)
+            }
+            PermFilter::Writable => PredicateNode::leaf("writable", String::new(), 0.5),
+            PermFilter::Executable => PredicateNode::leaf("executable", String::new(), 0.1),
         }
     }
 }
 
+/// Permission bits, special ones included
+#[cfg(unix)]
+fn mode_of(path: &std::path::Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::symlink_metadata(path)
+        .ok()
+        .map(|m| m.mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode_of(_path: &std::path::Path) -> Option<u32> {
+    None
+}
+
+enum Access {
+    Write,
+    Execute,
+}
+
+/// Whether the current user may write or execute `path`, as access(2) says
+#[cfg(unix)]
+fn may_access(path: &std::path::Path, access: Access) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mode = match access {
+        Access::Write => libc::W_OK,
+        Access::Execute => libc::X_OK,
+    };
+    unsafe { libc::access(path.as_ptr(), mode) == 0 }
+}
+
+#[cfg(not(unix))]
+fn may_access(path: &std::path::Path, access: Access) -> bool {
+    match access {
+        Access::Write => std::fs::metadata(path).is_ok_and(|m| !m.permissions().readonly()),
+        Access::Execute => false,
+    }
+}
+
+/// Time Machine filter - matches items excluded from backups (macOS)
+///
+/// Only the excluded item carries the attribute, not the files below it.
+pub struct BackupExcludedFilter;
+
+impl Predicate for BackupExcludedFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        crate::fs::backup::is_backup_excluded(&entry.path)
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("backup excluded", String::new(), 0.01)
+    }
+}
+
+/// Error filter - matches entries that couldn't be read fully
+pub struct ErrorFilter;
+
+impl Predicate for ErrorFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        entry.error.is_some()
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("read error", String::new(), 0.01)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     fn make_test_entry(name: &str, size: u64, kind: EntryKind) -> Entry {
-        use chrono::Utc;
-
         Entry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
             size,
             kind,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
+            ..Entry::test_file(name)
         }
     }
 
@@ -277,6 +787,29 @@ mod tests {
         assert!(!filter.test(&make_test_entry("main.txt", 100, EntryKind::File)));
     }
 
+    #[test]
+    fn test_or_and_not_predicates() {
+        let either = OrPredicate::new(vec![
+            Box::new(ExtensionFilter::new(&["rs".to_string()])),
+            Box::new(ExtensionFilter::new(&["toml".to_string()])),
+        ]);
+        assert!(either.test(&make_test_entry("main.rs", 100, EntryKind::File)));
+        assert!(either.test(&make_test_entry("Cargo.toml", 100, EntryKind::File)));
+        assert!(!either.test(&make_test_entry("readme.md", 100, EntryKind::File)));
+
+        let not_dir = NotPredicate::new(Box::new(KindFilter::new(&[EntryKind::Dir])));
+        assert!(not_dir.test(&make_test_entry("file.txt", 100, EntryKind::File)));
+        assert!(!not_dir.test(&make_test_entry("dir", 0, EntryKind::Dir)));
+    }
+
+    #[test]
+    fn test_glob_filter_case_insensitive() {
+        let filter = GlobFilter::case_insensitive(&["*.LOG".to_string()]).unwrap();
+        assert!(filter.test(&make_test_entry("app.log", 100, EntryKind::File)));
+        assert!(filter.test(&make_test_entry("APP.Log", 100, EntryKind::File)));
+        assert!(!filter.test(&make_test_entry("app.txt", 100, EntryKind::File)));
+    }
+
     #[test]
     fn test_regex_filter() {
         let filter = RegexFilter::new(r"^test_.*\.rs$").unwrap();
@@ -300,6 +833,101 @@ mod tests {
         assert!(!filter.test(&make_test_entry("large.txt", 20000, EntryKind::File)));
     }
 
+    #[test]
+    fn test_size_filter_bounds() {
+        let filter = SizeFilter::new(None, None)
+            .unwrap()
+            .with_bounds(&["+1k".to_string(), "-10k".to_string()])
+            .unwrap();
+        assert!(!filter.test(&make_test_entry("small.txt", 500, EntryKind::File)));
+        assert!(filter.test(&make_test_entry("medium.txt", 5000, EntryKind::File)));
+        assert!(!filter.test(&make_test_entry("large.txt", 20000, EntryKind::File)));
+
+        // An exact size outside --min-size matches nothing
+        let filter = SizeFilter::new(Some("20KB"), None)
+            .unwrap()
+            .with_bounds(&["10KB".to_string()])
+            .unwrap();
+        let exact = parse_size("10KB").unwrap();
+        assert!(!filter.test(&make_test_entry("exact.txt", exact, EntryKind::File)));
+    }
+
+    #[test]
+    fn test_size_filter_dir_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = dir.path().join("big");
+        std::fs::create_dir_all(big.join("nested")).unwrap();
+        std::fs::write(big.join("a.bin"), vec![0u8; 3000]).unwrap();
+        std::fs::write(big.join("nested/b.bin"), vec![0u8; 3000]).unwrap();
+        let small = dir.path().join("small");
+        std::fs::create_dir(&small).unwrap();
+        std::fs::write(small.join("c.bin"), vec![0u8; 100]).unwrap();
+
+        let dir_entry = |path: &std::path::Path| {
+            let mut entry = make_test_entry("dir", 0, EntryKind::Dir);
+            entry.path = path.to_path_buf();
+            entry
+        };
+        let filter = SizeFilter::new(Some("5KB"), None).unwrap();
+        assert!(filter.test(&dir_entry(&small)));
+
+        let filter = filter.with_dir_sizes(Default::default());
+        assert!(filter.test(&dir_entry(&big)));
+        assert!(!filter.test(&dir_entry(&big.join("nested"))));
+        assert!(!filter.test(&dir_entry(&small)));
+        assert!(!filter.test(&make_test_entry("a.bin", 3000, EntryKind::File)));
+    }
+
+    #[test]
+    fn test_date_filter_ages() {
+        let mut old = make_test_entry("old.txt", 100, EntryKind::File);
+        old.mtime = Utc::now() - chrono::Duration::days(40);
+        let recent = make_test_entry("recent.txt", 100, EntryKind::File);
+
+        let filter = DateFilter::new(None, None)
+            .unwrap()
+            .with_ages(Some("30d"), None)
+            .unwrap();
+        assert!(filter.test(&old));
+        assert!(!filter.test(&recent));
+
+        let filter = DateFilter::new(None, None)
+            .unwrap()
+            .with_ages(None, Some("2h"))
+            .unwrap();
+        assert!(!filter.test(&old));
+        assert!(filter.test(&recent));
+        // Further back than dates go
+        assert!(DateFilter::new(None, None)
+            .unwrap()
+            .with_ages(Some("300000y"), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_date_filter_on_other_times() {
+        let mut entry = make_test_entry("photo.jpg", 100, EntryKind::File);
+        entry.atime = Some(Utc::now() - chrono::Duration::days(400));
+
+        let filter = DateFilter::new(None, Some("2024-01-01"))
+            .unwrap()
+            .on(TimeField::Accessed);
+        assert!(!filter.test(&entry));
+        let filter = DateFilter::new(None, None)
+            .unwrap()
+            .with_ages(Some("1y"), None)
+            .unwrap()
+            .on(TimeField::Accessed);
+        assert!(filter.test(&entry));
+        assert_eq!(filter.explain().filter, "accessed");
+
+        // No birth time recorded: never matches
+        let filter = DateFilter::new(Some("2000-01-01"), None)
+            .unwrap()
+            .on(TimeField::Created);
+        assert!(!filter.test(&entry));
+    }
+
     #[test]
     fn test_kind_filter() {
         let filter = KindFilter::new(&[EntryKind::File]);
@@ -330,4 +958,74 @@ mod tests {
         assert!(filter.test(&make_test_entry("config.yaml", 100, EntryKind::File)));
         assert!(!filter.test(&make_test_entry("main.rs", 100, EntryKind::File)));
     }
+
+    #[test]
+    fn test_sparse_filter() {
+        let mut entry = make_test_entry("disk.img", 20 << 30, EntryKind::File);
+        assert!(!SparseFilter.test(&entry));
+
+        entry.allocated = Some(1 << 20);
+        assert!(SparseFilter.test(&entry));
+
+        entry.allocated = Some(20 << 30);
+        assert!(!SparseFilter.test(&entry));
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn test_mime_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("download");
+        std::fs::write(&image, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let entry = |path: &std::path::Path| Entry {
+            path: path.to_path_buf(),
+            ..make_test_entry("", 16, EntryKind::File)
+        };
+
+        let matches = |patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            MimeFilter::new(&patterns).test(&entry(&image))
+        };
+        assert!(matches(&["image/png"]));
+        assert!(matches(&["IMAGE/*"]));
+        assert!(matches(&["text", "image"]));
+        assert!(!matches(&["image/jpeg"]));
+        assert!(!matches(&["imag"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_perm_filter() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let entry_with_mode = |name: &str, mode: u32| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, "").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+            Entry {
+                path,
+                ..make_test_entry(name, 0, EntryKind::File)
+            }
+        };
+        let script = entry_with_mode("run.sh", 0o755);
+        let shared = entry_with_mode("shared.txt", 0o666);
+        let private = entry_with_mode("key.pem", 0o600);
+
+        let exact = PermFilter::parse("755").unwrap();
+        assert!(exact.test(&script) && !exact.test(&shared));
+        assert_eq!(PermFilter::parse("u=rwx,go=rx").unwrap(), exact);
+
+        let group_or_other_writable = PermFilter::parse("/022").unwrap();
+        assert!(group_or_other_writable.test(&shared));
+        assert!(!group_or_other_writable.test(&script));
+
+        let world_writable = PermFilter::world_writable();
+        assert!(world_writable.test(&shared) && !world_writable.test(&private));
+        assert_eq!(world_writable, PermFilter::parse("-002").unwrap());
+
+        assert!(PermFilter::Executable.test(&script));
+        assert!(!PermFilter::Executable.test(&private));
+        assert!(PermFilter::parse("888").is_err());
+    }
 }
@@ -0,0 +1,488 @@
+//! Compatibility shim for classic find(1) expressions
+//!
+//! Parses arguments like `src -name '*.log' -size +10M -mtime -7 -delete`
+//! into the internal `Predicate` tree so existing cron jobs and scripts can
+//! be pointed at fexplorer with minimal changes.
+
+use crate::errors::{FsError, Result};
+use crate::fs::filters::{
+    AndPredicate, GlobFilter, KindFilter, NotPredicate, OrPredicate, Predicate,
+};
+use crate::models::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+
+/// What to do with each matching entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindAction {
+    /// Print the path followed by a newline (find's default)
+    Print,
+    /// Print the path followed by a NUL byte
+    Print0,
+    /// Delete the entry (directories must be empty, like find)
+    Delete,
+}
+
+/// A parsed find(1) command line
+pub struct FindExpression {
+    /// Starting points (defaults to ".")
+    pub paths: Vec<PathBuf>,
+    /// Compiled predicate tree
+    pub predicate: Box<dyn Predicate>,
+    /// -maxdepth
+    pub max_depth: Option<usize>,
+    /// -mindepth
+    pub min_depth: usize,
+    /// Action applied to matches
+    pub action: FindAction,
+}
+
+/// How a numeric find argument compares (`+N`, `-N` or `N`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumCmp {
+    Greater,
+    Less,
+    Exact,
+}
+
+impl NumCmp {
+    fn parse(arg: &str) -> (Self, &str) {
+        if let Some(rest) = arg.strip_prefix('+') {
+            (NumCmp::Greater, rest)
+        } else if let Some(rest) = arg.strip_prefix('-') {
+            (NumCmp::Less, rest)
+        } else {
+            (NumCmp::Exact, arg)
+        }
+    }
+
+    fn matches(&self, value: u64, n: u64) -> bool {
+        match self {
+            NumCmp::Greater => value > n,
+            NumCmp::Less => value < n,
+            NumCmp::Exact => value == n,
+        }
+    }
+}
+
+/// -size: compares the size rounded up to whole units, like find does
+struct FindSizePredicate {
+    cmp: NumCmp,
+    n: u64,
+    unit: u64,
+}
+
+impl Predicate for FindSizePredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        self.cmp.matches(entry.size.div_ceil(self.unit), self.n)
+    }
+}
+
+/// -mtime / -mmin: compares the age truncated to whole periods
+struct FindAgePredicate {
+    cmp: NumCmp,
+    n: u64,
+    period_secs: i64,
+    now: DateTime<Utc>,
+}
+
+impl Predicate for FindAgePredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        let age = (self.now - entry.mtime).num_seconds().max(0);
+        self.cmp.matches((age / self.period_secs) as u64, self.n)
+    }
+}
+
+/// -newer: modified more recently than a reference file
+struct NewerPredicate {
+    reference: DateTime<Utc>,
+}
+
+impl Predicate for NewerPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        entry.mtime > self.reference
+    }
+}
+
+/// -path / -wholename: glob matched against the full path
+struct PathGlobPredicate {
+    matcher: globset::GlobMatcher,
+}
+
+impl Predicate for PathGlobPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        self.matcher.is_match(&entry.path)
+    }
+}
+
+/// -empty: empty regular files and directories without children
+struct EmptyPredicate;
+
+impl Predicate for EmptyPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        match entry.kind {
+            EntryKind::File => entry.size == 0,
+            EntryKind::Dir => std::fs::read_dir(&entry.path)
+                .map(|mut it| it.next().is_none())
+                .unwrap_or(false),
+            EntryKind::Symlink => false,
+        }
+    }
+}
+
+/// Options and actions evaluate to true, like in find
+struct TruePredicate;
+
+impl Predicate for TruePredicate {
+    fn test(&self, _entry: &Entry) -> bool {
+        true
+    }
+}
+
+/// Parse a find(1) style argument list
+pub fn parse_find_args(args: &[String]) -> Result<FindExpression> {
+    // Leading arguments that don't look like expression tokens are starting points
+    let split = args
+        .iter()
+        .position(|a| a.starts_with('-') || a == "(" || a == "!")
+        .unwrap_or(args.len());
+
+    let mut paths: Vec<PathBuf> = args[..split].iter().map(PathBuf::from).collect();
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut parser = ExprParser {
+        tokens: &args[split..],
+        pos: 0,
+        max_depth: None,
+        min_depth: 0,
+        action: None,
+        now: Utc::now(),
+    };
+
+    let predicate: Box<dyn Predicate> = if parser.tokens.is_empty() {
+        Box::new(TruePredicate)
+    } else {
+        parser.parse_or()?
+    };
+
+    if let Some(token) = parser.peek() {
+        return Err(invalid(format!("unexpected '{}'", token)));
+    }
+
+    Ok(FindExpression {
+        paths,
+        predicate,
+        max_depth: parser.max_depth,
+        min_depth: parser.min_depth,
+        action: parser.action.unwrap_or(FindAction::Print),
+    })
+}
+
+/// Recursive descent parser over find's operator grammar
+struct ExprParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+    max_depth: Option<usize>,
+    min_depth: usize,
+    action: Option<FindAction>,
+    now: DateTime<Utc>,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.pos).map(String::as_str);
+        self.pos += 1;
+        token
+    }
+
+    fn value(&mut self, option: &str) -> Result<String> {
+        self.next()
+            .map(String::from)
+            .ok_or_else(|| invalid(format!("missing argument to '{}'", option)))
+    }
+
+    fn parse_or(&mut self) -> Result<Box<dyn Predicate>> {
+        let mut alternatives = vec![self.parse_and()?];
+
+        while matches!(self.peek(), Some("-o") | Some("-or")) {
+            self.pos += 1;
+            alternatives.push(self.parse_and()?);
+        }
+
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Box::new(OrPredicate::new(alternatives))
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Box<dyn Predicate>> {
+        let mut terms = vec![self.parse_unary()?];
+
+        loop {
+            match self.peek() {
+                Some("-a") | Some("-and") => {
+                    self.pos += 1;
+                    terms.push(self.parse_unary()?);
+                }
+                // Juxtaposition is an implicit -a
+                Some(token) if token != "-o" && token != "-or" && token != ")" => {
+                    terms.push(self.parse_unary()?);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Box::new(AndPredicate::new(terms))
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Box<dyn Predicate>> {
+        match self.peek() {
+            Some("!") | Some("-not") => {
+                self.pos += 1;
+                Ok(Box::new(NotPredicate::new(self.parse_unary()?)))
+            }
+            Some("(") => {
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                if self.next() != Some(")") {
+                    return Err(invalid("missing closing ')'".to_string()));
+                }
+                Ok(inner)
+            }
+            Some(_) => self.parse_primary(),
+            None => Err(invalid("expected an expression".to_string())),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Box<dyn Predicate>> {
+        let token = self.next().unwrap_or_default().to_string();
+
+        let predicate: Box<dyn Predicate> = match token.as_str() {
+            "-name" => Box::new(GlobFilter::new(&[self.value(&token)?])?),
+            "-iname" => Box::new(GlobFilter::case_insensitive(&[self.value(&token)?])?),
+            "-path" | "-wholename" => {
+                let pattern = self.value(&token)?;
+                let glob = globset::Glob::new(&pattern).map_err(|e| FsError::InvalidGlob {
+                    pattern: pattern.clone(),
+                    source: e,
+                })?;
+                Box::new(PathGlobPredicate {
+                    matcher: glob.compile_matcher(),
+                })
+            }
+            "-type" => {
+                let kind = match self.value(&token)?.as_str() {
+                    "f" => EntryKind::File,
+                    "d" => EntryKind::Dir,
+                    "l" => EntryKind::Symlink,
+                    other => return Err(invalid(format!("unsupported -type '{}'", other))),
+                };
+                Box::new(KindFilter::new(&[kind]))
+            }
+            "-size" => Box::new(parse_size_arg(&self.value(&token)?)?),
+            "-mtime" => Box::new(self.age_predicate(&token, 86_400)?),
+            "-mmin" => Box::new(self.age_predicate(&token, 60)?),
+            "-newer" => {
+                let reference = self.value(&token)?;
+                let mtime = std::fs::metadata(&reference)
+                    .and_then(|m| m.modified())
+                    .map_err(|e| FsError::PathAccess {
+                        path: PathBuf::from(&reference),
+                        source: e,
+                    })?;
+                Box::new(NewerPredicate {
+                    reference: mtime.into(),
+                })
+            }
+            "-empty" => Box::new(EmptyPredicate),
+            "-true" => Box::new(TruePredicate),
+            "-false" => Box::new(NotPredicate::new(Box::new(TruePredicate))),
+            "-maxdepth" => {
+                self.max_depth = Some(parse_number(&token, &self.value(&token)?)?);
+                Box::new(TruePredicate)
+            }
+            "-mindepth" => {
+                self.min_depth = parse_number(&token, &self.value(&token)?)?;
+                Box::new(TruePredicate)
+            }
+            "-print" => self.set_action(FindAction::Print),
+            "-print0" => self.set_action(FindAction::Print0),
+            "-delete" => self.set_action(FindAction::Delete),
+            other => return Err(invalid(format!("unknown predicate '{}'", other))),
+        };
+
+        Ok(predicate)
+    }
+
+    fn set_action(&mut self, action: FindAction) -> Box<dyn Predicate> {
+        self.action = Some(action);
+        Box::new(TruePredicate)
+    }
+
+    fn age_predicate(&mut self, option: &str, period_secs: i64) -> Result<FindAgePredicate> {
+        let arg = self.value(option)?;
+        let (cmp, number) = NumCmp::parse(&arg);
+        Ok(FindAgePredicate {
+            cmp,
+            n: parse_number(option, number)? as u64,
+            period_secs,
+            now: self.now,
+        })
+    }
+}
+
+/// Parse a -size argument: [+-]N[bckwMG], where the default unit is 512-byte blocks
+fn parse_size_arg(arg: &str) -> Result<FindSizePredicate> {
+    let (cmp, rest) = NumCmp::parse(arg);
+
+    let (number, unit) = match rest.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => {
+            let unit = match c {
+                'c' => 1,
+                'w' => 2,
+                'b' => 512,
+                'k' => 1024,
+                'M' => 1024 * 1024,
+                'G' => 1024 * 1024 * 1024,
+                _ => {
+                    return Err(FsError::InvalidSize {
+                        input: arg.to_string(),
+                    })
+                }
+            };
+            (&rest[..idx], unit)
+        }
+        _ => (rest, 512),
+    };
+
+    let n = number.parse::<u64>().map_err(|_| FsError::InvalidSize {
+        input: arg.to_string(),
+    })?;
+
+    Ok(FindSizePredicate { cmp, n, unit })
+}
+
+fn parse_number(option: &str, value: &str) -> Result<usize> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid number '{}' for '{}'", value, option)))
+}
+
+fn invalid(message: String) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("findx: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn make_entry(name: &str, size: u64, kind: EntryKind, age: Duration) -> Entry {
+        Entry {
+            size,
+            kind,
+            mtime: Utc::now() - age,
+            depth: 1,
+            ..Entry::test_file(name)
+        }
+    }
+
+    #[test]
+    fn test_paths_and_defaults() {
+        let expr = parse_find_args(&args("a b -name *.log")).unwrap();
+        assert_eq!(expr.paths, vec![PathBuf::from("a"), PathBuf::from("b")]);
+        assert_eq!(expr.action, FindAction::Print);
+
+        let expr = parse_find_args(&[]).unwrap();
+        assert_eq!(expr.paths, vec![PathBuf::from(".")]);
+    }
+
+    #[test]
+    fn test_name_size_mtime_delete() {
+        let expr = parse_find_args(&args(". -name *.log -size +10M -mtime -7 -delete")).unwrap();
+        assert_eq!(expr.action, FindAction::Delete);
+
+        let big_recent = make_entry(
+            "a.log",
+            20 * 1024 * 1024,
+            EntryKind::File,
+            Duration::days(1),
+        );
+        let small_recent = make_entry("b.log", 1024, EntryKind::File, Duration::days(1));
+        let big_old = make_entry(
+            "c.log",
+            20 * 1024 * 1024,
+            EntryKind::File,
+            Duration::days(30),
+        );
+        let wrong_name = make_entry(
+            "d.txt",
+            20 * 1024 * 1024,
+            EntryKind::File,
+            Duration::days(1),
+        );
+
+        assert!(expr.predicate.test(&big_recent));
+        assert!(!expr.predicate.test(&small_recent));
+        assert!(!expr.predicate.test(&big_old));
+        assert!(!expr.predicate.test(&wrong_name));
+    }
+
+    #[test]
+    fn test_or_not_and_parens() {
+        let expr = parse_find_args(&args("( -name *.rs -o -name *.toml ) ! -type d")).unwrap();
+
+        let rs = make_entry("main.rs", 10, EntryKind::File, Duration::zero());
+        let toml = make_entry("Cargo.toml", 10, EntryKind::File, Duration::zero());
+        let md = make_entry("README.md", 10, EntryKind::File, Duration::zero());
+        let dir = make_entry("src.rs", 0, EntryKind::Dir, Duration::zero());
+
+        assert!(expr.predicate.test(&rs));
+        assert!(expr.predicate.test(&toml));
+        assert!(!expr.predicate.test(&md));
+        assert!(!expr.predicate.test(&dir));
+    }
+
+    #[test]
+    fn test_size_rounds_up_like_find() {
+        // find -size -1M only matches empty files because sizes round up
+        let pred = parse_size_arg("-1M").unwrap();
+        assert!(pred.test(&make_entry("e", 0, EntryKind::File, Duration::zero())));
+        assert!(!pred.test(&make_entry("f", 1, EntryKind::File, Duration::zero())));
+
+        let pred = parse_size_arg("3c").unwrap();
+        assert!(pred.test(&make_entry("g", 3, EntryKind::File, Duration::zero())));
+    }
+
+    #[test]
+    fn test_depth_options() {
+        let expr = parse_find_args(&args(". -maxdepth 2 -mindepth 1 -type f")).unwrap();
+        assert_eq!(expr.max_depth, Some(2));
+        assert_eq!(expr.min_depth, 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse_find_args(&args(". -bogus")).is_err());
+        assert!(parse_find_args(&args(". -name")).is_err());
+        assert!(parse_find_args(&args(". ( -name x")).is_err());
+        assert!(parse_find_args(&args(". -size 10Q")).is_err());
+    }
+}
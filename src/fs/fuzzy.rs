@@ -0,0 +1,303 @@
+//! Near-duplicate detection with content-defined chunking (FastCDC)
+//!
+//! Files are cut into variable-size chunks at content-defined boundaries, so
+//! an insertion early in a file only changes the chunks around it. Two files
+//! sharing most of their chunks (successive VM images, database dumps) are
+//! near-duplicates even though their hashes differ.
+
+use crate::errors::{FsError, Result};
+use crate::models::{Entry, EntryKind};
+use crate::timings;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::{debug, info_span};
+
+/// Chunk size bounds; boundaries are picked so chunks average `avg` bytes
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkerConfig {
+    pub min: usize,
+    pub avg: usize,
+    pub max: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min: 16 * 1024,
+            avg: 64 * 1024,
+            max: 256 * 1024,
+        }
+    }
+}
+
+impl ChunkerConfig {
+    /// Masks for the normalized cut: stricter below `avg`, looser above
+    fn masks(&self) -> (u64, u64) {
+        let bits = self.avg.max(2).ilog2();
+        // Top bits of the gear hash depend on the most bytes
+        let mask = |ones: u32| (u64::MAX >> (64 - ones)) << (64 - ones);
+        (mask(bits + 1), mask(bits.saturating_sub(1).max(1)))
+    }
+}
+
+/// Gear hash table: one pseudo-random word per byte value (splitmix64)
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Length of the first chunk of `data`
+fn cut_point(data: &[u8], config: &ChunkerConfig, (mask_s, mask_l): (u64, u64)) -> usize {
+    if data.len() <= config.min {
+        return data.len();
+    }
+    let end = data.len().min(config.max);
+    let normal = end.min(config.avg);
+
+    let mut hash: u64 = 0;
+    for (i, &byte) in data.iter().enumerate().take(end).skip(config.min) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        let mask = if i < normal { mask_s } else { mask_l };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// A chunk's content hash (first 128 bits of BLAKE3) and length
+pub type Chunk = (u128, u64);
+
+/// Split a stream into content-defined chunks
+pub fn chunk_reader<R: Read>(mut reader: R, config: &ChunkerConfig) -> std::io::Result<Vec<Chunk>> {
+    let masks = config.masks();
+    let mut chunks = Vec::new();
+    let mut buffer: Vec<u8> = Vec::with_capacity(config.max * 2);
+    let mut eof = false;
+
+    loop {
+        // Keep at least one maximal chunk buffered so cut points don't depend on read sizes
+        while !eof && buffer.len() < config.max {
+            let start = buffer.len();
+            buffer.resize(start + config.max, 0);
+            let n = reader.read(&mut buffer[start..])?;
+            buffer.truncate(start + n);
+            eof = n == 0;
+        }
+        if buffer.is_empty() {
+            return Ok(chunks);
+        }
+
+        let len = cut_point(&buffer, config, masks);
+        let digest = blake3::hash(&buffer[..len]);
+        let mut key = [0u8; 16];
+        key.copy_from_slice(&digest.as_bytes()[..16]);
+        chunks.push((u128::from_le_bytes(key), len as u64));
+        buffer.drain(..len);
+    }
+}
+
+/// Chunk a file
+pub fn chunk_file(path: &Path, config: &ChunkerConfig) -> Result<Vec<Chunk>> {
+    let file = File::open(path).map_err(|e| FsError::IoError {
+        context: format!("Failed to read {}", path.display()),
+        source: e,
+    })?;
+    chunk_reader(std::io::BufReader::new(file), config).map_err(|e| FsError::IoError {
+        context: format!("Failed to read {}", path.display()),
+        source: e,
+    })
+}
+
+/// Two files sharing a large part of their content
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyPair {
+    pub first: Entry,
+    pub second: Entry,
+    /// Bytes of the chunks both files contain; roughly what block-level
+    /// dedup (or storing one as a delta) could save
+    pub shared_bytes: u64,
+    /// `shared_bytes` as a fraction of the larger file (0.0 - 1.0)
+    pub similarity: f64,
+}
+
+/// Pairs of files (at least `min_size` bytes) whose shared chunks make up at
+/// least `min_similarity` of the larger file, most dedupable bytes first
+pub fn find_fuzzy_duplicates(
+    entries: &[Entry],
+    min_size: u64,
+    min_similarity: f64,
+    config: &ChunkerConfig,
+) -> Result<Vec<FuzzyPair>> {
+    let files: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| e.kind == EntryKind::File && e.size >= min_size.max(1))
+        .collect();
+    if files.len() < 2 {
+        return Ok(Vec::new());
+    }
+
+    let _span = info_span!("chunk", files = files.len()).entered();
+    let _timer = timings::phase("chunk");
+
+    let chunk = |entry: &&Entry| {
+        chunk_file(&entry.path, config)
+            .inspect_err(
+                |e| debug!(path = %entry.path.display(), error = %e, "Failed to chunk file"),
+            )
+            .ok()
+    };
+    #[cfg(feature = "parallel")]
+    let chunked: Vec<Option<Vec<Chunk>>> = {
+        use rayon::prelude::*;
+        files.par_iter().map(chunk).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let chunked: Vec<Option<Vec<Chunk>>> = files.iter().map(chunk).collect();
+
+    // Chunk -> files holding it, so only pairs with something in common are compared
+    let mut holders: HashMap<u128, Vec<usize>> = HashMap::new();
+    let mut unique: Vec<HashMap<u128, u64>> = Vec::with_capacity(files.len());
+    for (index, chunks) in chunked.iter().enumerate() {
+        let mut set = HashMap::new();
+        for &(hash, len) in chunks.iter().flatten() {
+            if set.insert(hash, len).is_none() {
+                holders.entry(hash).or_default().push(index);
+            }
+        }
+        unique.push(set);
+    }
+
+    let mut shared: HashMap<(usize, usize), u64> = HashMap::new();
+    for (hash, indices) in &holders {
+        for (n, &a) in indices.iter().enumerate() {
+            for &b in &indices[n + 1..] {
+                *shared.entry((a, b)).or_default() += unique[a][hash];
+            }
+        }
+    }
+
+    let mut pairs: Vec<FuzzyPair> = shared
+        .into_iter()
+        .filter_map(|((a, b), shared_bytes)| {
+            let larger = files[a].size.max(files[b].size);
+            let similarity = shared_bytes as f64 / larger as f64;
+            (similarity >= min_similarity).then(|| FuzzyPair {
+                first: files[a].clone(),
+                second: files[b].clone(),
+                shared_bytes,
+                similarity,
+            })
+        })
+        .collect();
+
+    pairs.sort_by(|x, y| {
+        y.shared_bytes
+            .cmp(&x.shared_bytes)
+            .then_with(|| x.first.path.cmp(&y.first.path))
+            .then_with(|| x.second.path.cmp(&y.second.path))
+    });
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    /// Deterministic pseudo-random bytes
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn small_chunks() -> ChunkerConfig {
+        ChunkerConfig {
+            min: 256,
+            avg: 1024,
+            max: 4096,
+        }
+    }
+
+    #[test]
+    fn test_chunks_cover_input_within_bounds() {
+        let data = noise(1, 100_000);
+        let config = small_chunks();
+        let chunks = chunk_reader(&data[..], &config).unwrap();
+
+        assert_eq!(chunks.iter().map(|c| c.1).sum::<u64>(), data.len() as u64);
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(rest.iter().all(|c| c.1 >= 256 && c.1 <= 4096));
+        assert!(last.1 <= 4096);
+        // Boundaries don't depend on how the input is read
+        let again = chunk_reader(std::io::BufReader::with_capacity(7, &data[..]), &config).unwrap();
+        assert_eq!(chunks, again);
+    }
+
+    #[test]
+    fn test_insertion_keeps_most_chunks() {
+        let data = noise(2, 200_000);
+        let mut edited = data[..50_000].to_vec();
+        edited.extend_from_slice(b"a few inserted bytes");
+        edited.extend_from_slice(&data[50_000..]);
+
+        let config = small_chunks();
+        let a = chunk_reader(&data[..], &config).unwrap();
+        let b = chunk_reader(&edited[..], &config).unwrap();
+        let common = a.iter().filter(|c| b.contains(c)).count();
+        assert!(
+            common * 10 >= a.len() * 9,
+            "{} of {} chunks kept",
+            common,
+            a.len()
+        );
+    }
+
+    #[test]
+    fn test_find_fuzzy_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = noise(3, 300_000);
+        let mut dump2 = base.clone();
+        dump2.splice(100_000..100_000, noise(4, 5_000));
+
+        let paths = [
+            (dir.path().join("dump1.sql"), base),
+            (dir.path().join("dump2.sql"), dump2),
+            (dir.path().join("other.bin"), noise(5, 300_000)),
+        ];
+        for (path, data) in &paths {
+            std::fs::write(path, data).unwrap();
+        }
+        let entries: Vec<Entry> = paths
+            .iter()
+            .map(|(path, _)| extract_entry(path, 1).unwrap())
+            .collect();
+
+        let pairs = find_fuzzy_duplicates(&entries, 0, 0.5, &small_chunks()).unwrap();
+        assert_eq!(pairs.len(), 1);
+        let names = [pairs[0].first.name.as_str(), pairs[0].second.name.as_str()];
+        assert!(names.contains(&"dump1.sql") && names.contains(&"dump2.sql"));
+        assert!(pairs[0].similarity > 0.9);
+        assert!(pairs[0].shared_bytes > 280_000);
+    }
+}
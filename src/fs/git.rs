@@ -150,6 +150,127 @@ pub fn get_changed_since(repo_path: &Path, since_ref: &str) -> Result<Vec<PathBu
     Ok(paths)
 }
 
+#[cfg(feature = "git")]
+/// List the files tracked by git under a path, as canonical absolute paths
+pub fn tracked_files(repo_path: &Path) -> Result<std::collections::HashSet<PathBuf>> {
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--full-name", "--", "."])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| FsError::IoError {
+            context: "Failed to run git ls-files command".to_string(),
+            source: e,
+        })?;
+
+    if !output.status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "Git ls-files command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        });
+    }
+
+    let toplevel = repo_toplevel(repo_path)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|p| toplevel.join(p))
+        .collect())
+}
+
+#[cfg(feature = "git")]
+/// Canonical path of the repository containing `path`
+pub fn repo_toplevel(path: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .current_dir(path)
+        .output()
+        .map_err(|e| FsError::IoError {
+            context: "Failed to run git rev-parse command".to_string(),
+            source: e,
+        })?;
+
+    if !output.status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!("Not a git repository: {}", path.display()),
+        });
+    }
+
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(toplevel.canonicalize().unwrap_or(toplevel))
+}
+
+#[cfg(feature = "git")]
+/// Get the last commit time of every tracked file under a path
+///
+/// Keys are canonical absolute paths so they can be matched against walked
+/// entries regardless of how the root was spelled.
+pub fn last_commit_times(
+    repo_path: &Path,
+) -> Result<HashMap<PathBuf, chrono::DateTime<chrono::Utc>>> {
+    let toplevel = repo_toplevel(repo_path)?;
+
+    // Each commit starts with a NUL, then its timestamp and the touched files
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "core.quotepath=off",
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+            "--",
+            ".",
+        ])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| FsError::IoError {
+            context: "Failed to run git log command".to_string(),
+            source: e,
+        })?;
+
+    if !output.status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "Git log command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        });
+    }
+
+    Ok(parse_commit_times(
+        &String::from_utf8_lossy(&output.stdout),
+        &toplevel,
+    ))
+}
+
+#[cfg(feature = "git")]
+/// Parse `git log --format=%x00%ct --name-only` output (newest commit first)
+fn parse_commit_times(
+    log: &str,
+    toplevel: &Path,
+) -> HashMap<PathBuf, chrono::DateTime<chrono::Utc>> {
+    let mut times = HashMap::new();
+
+    for commit in log.split('\0').filter(|c| !c.trim().is_empty()) {
+        let mut lines = commit.lines();
+        let Some(time) = lines
+            .next()
+            .and_then(|t| t.trim().parse().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        else {
+            continue;
+        };
+
+        for file in lines.filter(|l| !l.is_empty()) {
+            times.entry(toplevel.join(file)).or_insert(time);
+        }
+    }
+
+    times
+}
+
 #[cfg(feature = "git")]
 /// Enrich entries with git status information
 pub fn enrich_with_git_status(entries: &[Entry], repo_path: &Path) -> Result<Vec<GitEntry>> {
@@ -195,4 +316,13 @@ mod tests {
         assert_eq!(GitStatus::Staged.to_str(), "staged");
         assert_eq!(GitStatus::Clean.to_str(), "clean");
     }
+
+    #[test]
+    fn test_parse_commit_times_keeps_latest() {
+        let log = "\x001700000200\n\nsrc/a.rs\n\x001700000100\n\nsrc/a.rs\nsrc/b.rs\n";
+        let times = parse_commit_times(log, Path::new("/repo"));
+
+        assert_eq!(times[Path::new("/repo/src/a.rs")].timestamp(), 1700000200);
+        assert_eq!(times[Path::new("/repo/src/b.rs")].timestamp(), 1700000100);
+    }
 }
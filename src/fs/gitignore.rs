@@ -0,0 +1,144 @@
+//! Gitignore rules for single paths
+//!
+//! The walker applies `.gitignore` as it descends, but paths read from a
+//! path list or exported results never pass through it. [`GitignoreChecker`]
+//! answers for any path by loading the `.gitignore` of its directory and of
+//! each parent up to the repository root (plus `.git/info/exclude`), once
+//! per directory. As with the walker, rules only apply inside a git
+//! repository; the global excludes file isn't read.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Rules of a directory and its parents, nearest first; `None` outside a
+/// repository
+type Chain = Option<Arc<Vec<Gitignore>>>;
+
+/// Checks paths against the `.gitignore` files above them
+#[derive(Default)]
+pub struct GitignoreChecker {
+    dirs: Mutex<HashMap<PathBuf, Chain>>,
+}
+
+impl GitignoreChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `path` is ignored; the nearest `.gitignore` with a matching
+    /// rule decides, so a `!pattern` in a subdirectory wins over its parent
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(path) = std::path::absolute(path) else {
+            return false;
+        };
+        let Some(chain) = path.parent().and_then(|dir| self.chain(dir)) else {
+            return false;
+        };
+        for rules in chain.iter() {
+            let matched = rules.matched_path_or_any_parents(&path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn chain(&self, dir: &Path) -> Chain {
+        if let Some(chain) = self
+            .dirs
+            .lock()
+            .ok()
+            .and_then(|dirs| dirs.get(dir).cloned())
+        {
+            return chain;
+        }
+
+        let git = dir.join(".git");
+        let mut builder = GitignoreBuilder::new(dir);
+        // Missing files are fine; broken lines are skipped
+        builder.add(dir.join(".gitignore"));
+        let chain = if git.exists() {
+            builder.add(git.join("info").join("exclude"));
+            Some(Arc::new(vec![build(&builder)]))
+        } else {
+            dir.parent()
+                .and_then(|parent| self.chain(parent))
+                .map(|parents| {
+                    let mut chain = vec![build(&builder)];
+                    chain.extend(parents.iter().cloned());
+                    Arc::new(chain)
+                })
+        };
+
+        if let Ok(mut dirs) = self.dirs.lock() {
+            dirs.insert(dir.to_path_buf(), chain.clone());
+        }
+        chain
+    }
+}
+
+fn build(builder: &GitignoreBuilder) -> Gitignore {
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Whether `path` is hidden (its name starts with `.`), or for a relative
+/// path, whether a directory on it is
+///
+/// The directories above an absolute path are where the user chose to look
+/// (`/home/me/.config/...`), much like a walk's root.
+pub fn is_hidden(path: &Path) -> bool {
+    let dot = |name: &std::ffi::OsStr| name.to_string_lossy().starts_with('.');
+    if path.is_absolute() {
+        return path.file_name().is_some_and(dot);
+    }
+    path.components().any(|component| match component {
+        Component::Normal(name) => dot(name),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(root.join("logs/.gitignore"), "!keep.log\n").unwrap();
+        fs::write(root.join(".git/info/exclude"), "scratch.txt\n").unwrap();
+
+        let checker = GitignoreChecker::new();
+        assert!(checker.is_ignored(&root.join("target/debug/app"), false));
+        assert!(checker.is_ignored(&root.join("build.log"), false));
+        assert!(checker.is_ignored(&root.join("scratch.txt"), false));
+        assert!(checker.is_ignored(&root.join("logs/other.log"), false));
+        assert!(!checker.is_ignored(&root.join("logs/keep.log"), false));
+        assert!(!checker.is_ignored(&root.join("src/main.rs"), false));
+
+        // Outside a repository .gitignore files don't count
+        let plain = tempfile::tempdir().unwrap();
+        fs::write(plain.path().join(".gitignore"), "*.log\n").unwrap();
+        assert!(!checker.is_ignored(&plain.path().join("a.log"), false));
+    }
+
+    #[test]
+    fn test_is_hidden() {
+        assert!(is_hidden(Path::new(".env")));
+        assert!(is_hidden(Path::new("src/.cache/a.rs")));
+        assert!(!is_hidden(Path::new("./src/main.rs")));
+        assert!(!is_hidden(Path::new("../src/main.rs")));
+        assert!(is_hidden(Path::new("/home/me/.env")));
+        assert!(!is_hidden(Path::new("/home/me/.config/app.toml")));
+    }
+}
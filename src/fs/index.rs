@@ -0,0 +1,311 @@
+use crate::errors::{FsError, Result};
+use crate::fs::gitignore::GitignoreChecker;
+use crate::fs::traverse::{exclude_matcher, TraverseConfig};
+use crate::timings;
+use ignore::overrides::Override;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+/// Where `find` gets candidate paths from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBackend {
+    /// Walk the tree (always accurate)
+    Walk,
+    /// Spotlight index (macOS)
+    Mdfind,
+    /// locate/plocate database (Linux)
+    Locate,
+}
+
+impl SearchBackend {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "walk" => Ok(SearchBackend::Walk),
+            "mdfind" | "spotlight" => Ok(SearchBackend::Mdfind),
+            "locate" | "plocate" => Ok(SearchBackend::Locate),
+            _ => Err(FsError::InvalidFormat {
+                format: format!("Unknown backend: {} (expected walk, mdfind or locate)", s),
+            }),
+        }
+    }
+}
+
+/// Paths under `roots` whose names may match `patterns`, from a system index
+///
+/// Results are candidates: the caller still applies its filters. Returns
+/// `None` when the walk should be used instead: the backend is `walk`, there
+/// are no name patterns, a pattern can't be expressed as an index query, or
+/// the index tool is missing or fails.
+pub fn search_index(
+    backend: SearchBackend,
+    roots: &[PathBuf],
+    patterns: &[String],
+    config: &TraverseConfig,
+) -> Option<Vec<PathBuf>> {
+    if backend == SearchBackend::Walk {
+        return None;
+    }
+    if patterns.is_empty() {
+        warn!("Index search needs --name patterns, walking instead");
+        return None;
+    }
+    // Brace alternatives aren't understood by either tool, and Spotlight
+    // only knows the `*` and `?` wildcards
+    let unsupported =
+        |p: &String| p.contains('{') || (backend == SearchBackend::Mdfind && p.contains('['));
+    if patterns.iter().any(unsupported) {
+        warn!("Name pattern not supported by the index, walking instead");
+        return None;
+    }
+    let _timer = timings::phase("index");
+    let gitignore = config.respect_gitignore.then(GitignoreChecker::new);
+
+    let mut found = Vec::new();
+    for root in roots {
+        let absolute = match root.canonicalize() {
+            Ok(path) => path,
+            Err(e) => {
+                warn!(path = %root.display(), error = %e, "Index lookup failed, walking instead");
+                return None;
+            }
+        };
+        // Bad patterns are left for the walk to report
+        let excludes = if config.exclude.is_empty() {
+            None
+        } else {
+            Some(exclude_matcher(&absolute, &config.exclude).ok()?)
+        };
+        let skip = WalkRules {
+            excludes,
+            gitignore: gitignore.as_ref(),
+        };
+        let output = match backend {
+            SearchBackend::Mdfind => run_index_tool(
+                "mdfind",
+                &[
+                    "-0".to_string(),
+                    "-onlyin".to_string(),
+                    absolute.display().to_string(),
+                    mdfind_query(patterns),
+                ],
+            ),
+            SearchBackend::Locate => {
+                let mut args = vec!["-0".to_string(), "-b".to_string(), "--".to_string()];
+                args.extend(patterns.iter().cloned());
+                run_index_tool("locate", &args)
+            }
+            SearchBackend::Walk => unreachable!(),
+        }?;
+
+        for path in output.split(|&b| b == 0).filter(|p| !p.is_empty()) {
+            let path = bytes_to_path(path);
+            if let Some(path) = within_root(&path, root, &absolute, config, &skip) {
+                found.push(path);
+            }
+        }
+    }
+    Some(found)
+}
+
+/// Rules beyond depth and hidden files that the walk would prune paths by
+#[derive(Default)]
+struct WalkRules<'a> {
+    /// `--exclude` globs, relative to the absolute root
+    excludes: Option<Override>,
+    /// `.gitignore` rules, unless `--no-gitignore`
+    gitignore: Option<&'a GitignoreChecker>,
+}
+
+/// Map an indexed absolute path back under `root`, applying the depth,
+/// hidden, exclude and gitignore rules of the walk
+///
+/// Stale index entries for deleted files are dropped.
+fn within_root(
+    path: &Path,
+    root: &Path,
+    absolute_root: &Path,
+    config: &TraverseConfig,
+    skip: &WalkRules,
+) -> Option<PathBuf> {
+    let relative = path.strip_prefix(absolute_root).ok()?;
+    let components: Vec<Component> = relative.components().collect();
+
+    if config.max_depth.is_some_and(|max| components.len() > max) {
+        return None;
+    }
+    let hidden = components
+        .iter()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if hidden && !config.include_hidden {
+        return None;
+    }
+
+    let joined = root.join(relative);
+    let metadata = joined.symlink_metadata().ok()?;
+    // An excluded directory hides everything below it
+    if let Some(excludes) = &skip.excludes {
+        let mut prefix = PathBuf::new();
+        for (i, component) in components.iter().enumerate() {
+            prefix.push(component);
+            let is_dir = i + 1 < components.len() || metadata.is_dir();
+            if excludes.matched(&prefix, is_dir).is_ignore() {
+                return None;
+            }
+        }
+    }
+    if skip
+        .gitignore
+        .is_some_and(|gitignore| gitignore.is_ignored(path, metadata.is_dir()))
+    {
+        return None;
+    }
+
+    Some(joined)
+}
+
+/// Spotlight query matching any of the name globs
+fn mdfind_query(patterns: &[String]) -> String {
+    let clauses: Vec<String> = patterns
+        .iter()
+        .map(|p| {
+            format!(
+                "kMDItemFSName == \"{}\"",
+                p.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect();
+    clauses.join(" || ")
+}
+
+fn run_index_tool(program: &str, args: &[String]) -> Option<Vec<u8>> {
+    match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => Some(output.stdout),
+        // locate exits 1 when nothing matches
+        Ok(output) if program == "locate" && output.status.code() == Some(1) => Some(output.stdout),
+        Ok(output) => {
+            warn!(
+                program,
+                stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                "Index lookup failed, walking instead"
+            );
+            None
+        }
+        Err(e) => {
+            warn!(program, error = %e, "Index tool unavailable, walking instead");
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(absolute.join("src/.cache")).unwrap();
+        std::fs::write(absolute.join("src/main.rs"), "").unwrap();
+        std::fs::write(absolute.join("src/.cache/x.rs"), "").unwrap();
+
+        let config = TraverseConfig::default();
+        let none = WalkRules::default();
+        let root = Path::new("proj");
+        assert_eq!(
+            within_root(
+                &absolute.join("src/main.rs"),
+                root,
+                &absolute,
+                &config,
+                &none
+            ),
+            None,
+            "relative roots are re-joined, so the path must exist under them"
+        );
+
+        let root = absolute.as_path();
+        let found = |path: &str, config: &TraverseConfig, skip: &WalkRules| {
+            within_root(&absolute.join(path), root, &absolute, config, skip).is_some()
+        };
+        assert!(found("src/main.rs", &config, &none));
+        assert!(!found("src/.cache/x.rs", &config, &none));
+        assert!(!found("src/gone.rs", &config, &none));
+        assert!(within_root(
+            Path::new("/elsewhere/main.rs"),
+            root,
+            &absolute,
+            &config,
+            &none
+        )
+        .is_none());
+
+        let shallow = TraverseConfig {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert!(!found("src/main.rs", &shallow, &none));
+    }
+
+    #[test]
+    fn test_within_root_skips_excluded_and_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(absolute.join(".git")).unwrap();
+        std::fs::create_dir_all(absolute.join("vendor/lib")).unwrap();
+        std::fs::create_dir_all(absolute.join("target")).unwrap();
+        std::fs::write(absolute.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(absolute.join("vendor/lib/a.rs"), "").unwrap();
+        std::fs::write(absolute.join("target/b.rs"), "").unwrap();
+        std::fs::write(absolute.join("main.rs"), "").unwrap();
+
+        let config = TraverseConfig::default();
+        let gitignore = GitignoreChecker::new();
+        let skip = WalkRules {
+            excludes: Some(exclude_matcher(&absolute, &["vendor".to_string()]).unwrap()),
+            gitignore: Some(&gitignore),
+        };
+        let found = |path: &str, skip: &WalkRules| {
+            within_root(&absolute.join(path), &absolute, &absolute, &config, skip).is_some()
+        };
+        assert!(found("main.rs", &skip));
+        assert!(!found("vendor/lib/a.rs", &skip));
+        assert!(!found("target/b.rs", &skip));
+        // --no-gitignore and no --exclude
+        assert!(found("vendor/lib/a.rs", &WalkRules::default()));
+        assert!(found("target/b.rs", &WalkRules::default()));
+    }
+
+    #[test]
+    fn test_mdfind_query() {
+        let patterns = vec!["*.rs".to_string(), "say \"hi\"".to_string()];
+        assert_eq!(
+            mdfind_query(&patterns),
+            r#"kMDItemFSName == "*.rs" || kMDItemFSName == "say \"hi\"""#
+        );
+    }
+
+    #[test]
+    fn test_walk_backend_and_missing_patterns_fall_back() {
+        let config = TraverseConfig::default();
+        let roots = vec![PathBuf::from(".")];
+        assert!(search_index(SearchBackend::Walk, &roots, &["*.rs".into()], &config).is_none());
+        assert!(search_index(SearchBackend::Locate, &roots, &[], &config).is_none());
+        assert!(
+            search_index(SearchBackend::Mdfind, &roots, &["[ab].rs".into()], &config).is_none()
+        );
+        assert!(SearchBackend::parse("everything").is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use crate::errors::{FsError, Result};
+use crate::fs::collisions::find_collisions;
+use crate::models::{Entry, EntryKind};
+use crate::timings;
+use serde::Serialize;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Files larger than this are not scanned by the content rules
+const MAX_CONTENT_SCAN: u64 = 16 * 1024 * 1024;
+
+/// Bytes inspected for a NUL byte when deciding whether a file is binary
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// Lock files and the manifest they are generated from
+const LOCK_FILES: &[(&str, &[&str])] = &[
+    ("Cargo.lock", &["Cargo.toml"]),
+    ("package-lock.json", &["package.json"]),
+    ("yarn.lock", &["package.json"]),
+    ("pnpm-lock.yaml", &["package.json"]),
+    ("poetry.lock", &["pyproject.toml"]),
+    ("Pipfile.lock", &["Pipfile"]),
+    ("Gemfile.lock", &["Gemfile", "gems.rb"]),
+    ("composer.lock", &["composer.json"]),
+    ("go.sum", &["go.mod"]),
+    ("mix.lock", &["mix.exs"]),
+];
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem reported by a lint rule
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Everything a rule gets to look at
+pub struct LintContext<'a> {
+    /// Root paths that were walked
+    pub roots: &'a [PathBuf],
+    /// All entries found under the roots
+    pub entries: &'a [Entry],
+}
+
+/// A project hygiene check
+///
+/// Rules are independent: each one inspects the walked entries and appends
+/// any findings. New rules only need to be added to [`default_rules`].
+pub trait LintRule {
+    /// Stable identifier used by `--rule` / `--skip-rule`
+    fn id(&self) -> &'static str;
+
+    /// One-line description for `--list-rules`
+    fn description(&self) -> &'static str;
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>);
+}
+
+/// Options shared by the built-in rules
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// Size above which a file tracked by git is reported
+    pub max_file_size: u64,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// All built-in rules, in reporting order
+pub fn default_rules(options: &LintOptions) -> Vec<Box<dyn LintRule>> {
+    #[cfg(not(feature = "git"))]
+    let _ = options;
+
+    vec![
+        #[cfg(feature = "git")]
+        Box::new(LargeGitFile {
+            max_size: options.max_file_size,
+        }),
+        Box::new(MixedLineEndings),
+        Box::new(TrailingWhitespace),
+        Box::new(OrphanedLockFile),
+        Box::new(CaseConflict),
+    ]
+}
+
+/// Narrow the rule set down to `only` (if non-empty) minus `skip`
+///
+/// Unknown rule ids are an error rather than silently matching nothing.
+pub fn select_rules(
+    rules: Vec<Box<dyn LintRule>>,
+    only: &[String],
+    skip: &[String],
+) -> Result<Vec<Box<dyn LintRule>>> {
+    for id in only.iter().chain(skip) {
+        if !rules.iter().any(|r| r.id() == id) {
+            let known: Vec<&str> = rules.iter().map(|r| r.id()).collect();
+            return Err(FsError::InvalidFormat {
+                format: format!("Unknown lint rule '{}' (known: {})", id, known.join(", ")),
+            });
+        }
+    }
+
+    Ok(rules
+        .into_iter()
+        .filter(|r| only.is_empty() || only.iter().any(|id| id == r.id()))
+        .filter(|r| !skip.iter().any(|id| id == r.id()))
+        .collect())
+}
+
+/// Run the rules and return their findings sorted by path and line
+pub fn run_rules(rules: &[Box<dyn LintRule>], ctx: &LintContext) -> Vec<Finding> {
+    let _timer = timings::phase("lint");
+
+    let mut findings = Vec::new();
+    for rule in rules {
+        rule.check(ctx, &mut findings);
+    }
+
+    findings.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| a.line.cmp(&b.line))
+            .then_with(|| a.rule.cmp(b.rule))
+    });
+    findings
+}
+
+/// Files above the size limit that are committed to git
+#[cfg(feature = "git")]
+pub struct LargeGitFile {
+    pub max_size: u64,
+}
+
+#[cfg(feature = "git")]
+impl LintRule for LargeGitFile {
+    fn id(&self) -> &'static str {
+        "large-git-file"
+    }
+
+    fn description(&self) -> &'static str {
+        "Large files tracked by git (consider Git LFS)"
+    }
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>) {
+        use crate::fs::git::{is_git_repo, tracked_files};
+        use crate::util::format_size_human;
+        use std::collections::HashSet;
+
+        let mut tracked: HashSet<PathBuf> = HashSet::new();
+        for root in ctx.roots.iter().filter(|r| r.is_dir() && is_git_repo(r)) {
+            match tracked_files(root) {
+                Ok(files) => tracked.extend(files),
+                Err(e) => tracing::warn!("Could not list git files in {}: {}", root.display(), e),
+            }
+        }
+        if tracked.is_empty() {
+            return;
+        }
+
+        let large = ctx
+            .entries
+            .iter()
+            .filter(|e| e.kind == EntryKind::File && e.size > self.max_size);
+        for entry in large {
+            let canonical = entry
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| entry.path.clone());
+            if tracked.contains(&canonical) {
+                findings.push(Finding {
+                    rule: self.id(),
+                    severity: Severity::Error,
+                    path: entry.path.clone(),
+                    line: None,
+                    message: format!(
+                        "{} file is committed to git (limit {})",
+                        format_size_human(entry.size),
+                        format_size_human(self.max_size)
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Text files that mix CRLF and LF line endings
+pub struct MixedLineEndings;
+
+impl LintRule for MixedLineEndings {
+    fn id(&self) -> &'static str {
+        "mixed-line-endings"
+    }
+
+    fn description(&self) -> &'static str {
+        "Text files mixing CRLF and LF line endings"
+    }
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>) {
+        for (entry, content) in text_files(ctx.entries) {
+            let (mut crlf, mut lf) = (0usize, 0usize);
+            let (mut first_crlf, mut first_lf) = (None, None);
+
+            for (i, line) in content.split_inclusive(|&b| b == b'\n').enumerate() {
+                if !line.ends_with(b"\n") {
+                    continue;
+                }
+                if line.ends_with(b"\r\n") {
+                    crlf += 1;
+                    first_crlf.get_or_insert(i + 1);
+                } else {
+                    lf += 1;
+                    first_lf.get_or_insert(i + 1);
+                }
+            }
+
+            if crlf > 0 && lf > 0 {
+                // Point at the first line using the less common style
+                let line = if crlf < lf { first_crlf } else { first_lf };
+                findings.push(Finding {
+                    rule: self.id(),
+                    severity: Severity::Warning,
+                    path: entry.path.clone(),
+                    line,
+                    message: format!("mixed line endings ({} CRLF, {} LF)", crlf, lf),
+                });
+            }
+        }
+    }
+}
+
+/// Text files with whitespace at the end of lines
+pub struct TrailingWhitespace;
+
+impl LintRule for TrailingWhitespace {
+    fn id(&self) -> &'static str {
+        "trailing-whitespace"
+    }
+
+    fn description(&self) -> &'static str {
+        "Text files with trailing spaces or tabs"
+    }
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>) {
+        for (entry, content) in text_files(ctx.entries) {
+            let mut count = 0usize;
+            let mut first = None;
+
+            for (i, line) in content.split(|&b| b == b'\n').enumerate() {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if matches!(line.last(), Some(b' ' | b'\t')) {
+                    count += 1;
+                    first.get_or_insert(i + 1);
+                }
+            }
+
+            if count > 0 {
+                findings.push(Finding {
+                    rule: self.id(),
+                    severity: Severity::Info,
+                    path: entry.path.clone(),
+                    line: first,
+                    message: format!(
+                        "trailing whitespace on {} line{}",
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Lock files whose manifest is missing from the same directory
+pub struct OrphanedLockFile;
+
+impl LintRule for OrphanedLockFile {
+    fn id(&self) -> &'static str {
+        "orphaned-lock-file"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lock files without the manifest they were generated from"
+    }
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>) {
+        for entry in ctx.entries.iter().filter(|e| e.kind == EntryKind::File) {
+            let Some((_, manifests)) = LOCK_FILES.iter().find(|(lock, _)| *lock == entry.name)
+            else {
+                continue;
+            };
+
+            let dir = entry.path.parent().unwrap_or(Path::new("."));
+            if !manifests.iter().any(|m| dir.join(m).exists()) {
+                findings.push(Finding {
+                    rule: self.id(),
+                    severity: Severity::Warning,
+                    path: entry.path.clone(),
+                    line: None,
+                    message: format!("lock file without {}", manifests.join(" or ")),
+                });
+            }
+        }
+    }
+}
+
+/// Sibling names that differ only in case or Unicode normalization
+///
+/// These collide on case-insensitive filesystems (macOS and Windows defaults),
+/// where only one of them survives a checkout.
+pub struct CaseConflict;
+
+impl LintRule for CaseConflict {
+    fn id(&self) -> &'static str {
+        "case-conflict"
+    }
+
+    fn description(&self) -> &'static str {
+        "Names in one directory that differ only in case or Unicode normalization"
+    }
+
+    fn check(&self, ctx: &LintContext, findings: &mut Vec<Finding>) {
+        for group in find_collisions(ctx.entries) {
+            let others = |skip: &Path| {
+                group
+                    .entries
+                    .iter()
+                    .filter(|e| e.path != skip)
+                    .map(|e| e.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            for entry in &group.entries {
+                findings.push(Finding {
+                    rule: self.id(),
+                    severity: Severity::Error,
+                    path: entry.path.clone(),
+                    line: None,
+                    message: format!(
+                        "name differs only in {} from {}",
+                        group.kind,
+                        others(&entry.path)
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Regular files small enough to scan that don't look binary, with their content
+fn text_files(entries: &[Entry]) -> impl Iterator<Item = (&Entry, Vec<u8>)> {
+    entries
+        .iter()
+        .filter(|e| e.kind == EntryKind::File && e.size > 0 && e.size <= MAX_CONTENT_SCAN)
+        .filter_map(|entry| {
+            let mut content = Vec::with_capacity(entry.size as usize);
+            File::open(&entry.path)
+                .and_then(|mut f| f.read_to_end(&mut content))
+                .ok()?;
+            let sniff = &content[..content.len().min(BINARY_SNIFF_LEN)];
+            (!sniff.contains(&0)).then_some((entry, content))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn make_entry(path: &Path) -> Entry {
+        Entry {
+            size: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    fn lint(rule: &dyn LintRule, entries: &[Entry]) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        rule.check(
+            &LintContext {
+                roots: &[],
+                entries,
+            },
+            &mut findings,
+        );
+        findings
+    }
+
+    #[test]
+    fn test_line_endings_and_whitespace() {
+        let temp = TempDir::new().unwrap();
+        let mixed = temp.path().join("mixed.txt");
+        let clean = temp.path().join("clean.txt");
+        let binary = temp.path().join("data.bin");
+        std::fs::write(&mixed, "one\r\ntwo \r\nthree\n").unwrap();
+        std::fs::write(&clean, "one\ntwo\n").unwrap();
+        std::fs::write(&binary, b"a \0\r\nb\n").unwrap();
+        let entries: Vec<Entry> = [&mixed, &clean, &binary]
+            .iter()
+            .map(|p| make_entry(p))
+            .collect();
+
+        let findings = lint(&MixedLineEndings, &entries);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].path, mixed);
+        assert_eq!(findings[0].line, Some(3));
+
+        let findings = lint(&TrailingWhitespace, &entries);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_orphaned_lock_file() {
+        let temp = TempDir::new().unwrap();
+        let app = temp.path().join("app");
+        std::fs::create_dir(&app).unwrap();
+        std::fs::write(app.join("Cargo.toml"), "").unwrap();
+        std::fs::write(app.join("Cargo.lock"), "").unwrap();
+        std::fs::write(temp.path().join("yarn.lock"), "").unwrap();
+
+        let entries = vec![
+            make_entry(&app.join("Cargo.lock")),
+            make_entry(&temp.path().join("yarn.lock")),
+        ];
+        let findings = lint(&OrphanedLockFile, &entries);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].path, temp.path().join("yarn.lock"));
+    }
+
+    #[test]
+    fn test_case_conflict() {
+        let entries: Vec<Entry> = ["a/README.md", "a/readme.md", "b/readme.md", "a/other.md"]
+            .iter()
+            .map(|p| make_entry(Path::new(p)))
+            .collect();
+
+        let findings = lint(&CaseConflict, &entries);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|f| f.path.starts_with("a")));
+        assert!(
+            findings[0].message.contains("readme.md") || findings[1].message.contains("readme.md")
+        );
+    }
+
+    #[test]
+    fn test_select_rules() {
+        let rules = select_rules(
+            default_rules(&LintOptions::default()),
+            &[],
+            &["trailing-whitespace".to_string()],
+        )
+        .unwrap();
+        assert!(rules.iter().all(|r| r.id() != "trailing-whitespace"));
+        assert!(rules.iter().any(|r| r.id() == "case-conflict"));
+
+        let rules = select_rules(
+            default_rules(&LintOptions::default()),
+            &["case-conflict".to_string()],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(rules.len(), 1);
+
+        assert!(select_rules(
+            default_rules(&LintOptions::default()),
+            &["no-such-rule".to_string()],
+            &[]
+        )
+        .is_err());
+    }
+}
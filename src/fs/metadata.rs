@@ -1,4 +1,5 @@
 use crate::errors::Result;
+use crate::fs::size::allocated_size;
 use crate::models::{Entry, EntryKind};
 use chrono::{DateTime, Utc};
 use std::fs;
@@ -21,12 +22,12 @@ pub fn extract_entry(path: &Path, depth: usize) -> Result<Entry> {
 
     let name = path
         .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("")
-        .to_string();
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
 
     let perms = extract_permissions(&metadata);
     let owner = extract_owner(path);
+    let allocated = (kind == EntryKind::File).then(|| allocated_size(&metadata));
 
     Ok(Entry {
         path: path.to_path_buf(),
@@ -34,8 +35,16 @@ pub fn extract_entry(path: &Path, depth: usize) -> Result<Entry> {
         size,
         kind,
         mtime,
+        atime: metadata.accessed().ok().map(DateTime::from),
+        ctime: extract_ctime(&metadata),
+        created: metadata.created().ok().map(DateTime::from),
         perms,
         owner,
+        allocated,
+        last_activity: None,
+        error: None,
+        truncated: false,
+        enriched: Default::default(),
         depth,
     })
 }
@@ -46,6 +55,19 @@ fn extract_mtime(metadata: &fs::Metadata) -> Result<DateTime<Utc>> {
     Ok(DateTime::from(mtime))
 }
 
+/// Extract status change time (Unix only)
+#[cfg(unix)]
+fn extract_ctime(metadata: &fs::Metadata) -> Option<DateTime<Utc>> {
+    use std::os::unix::fs::MetadataExt;
+
+    DateTime::from_timestamp(metadata.ctime(), metadata.ctime_nsec() as u32)
+}
+
+#[cfg(not(unix))]
+fn extract_ctime(_metadata: &fs::Metadata) -> Option<DateTime<Utc>> {
+    None
+}
+
 /// Extract permission string (Unix-style)
 #[cfg(unix)]
 fn extract_permissions(metadata: &fs::Metadata) -> Option<String> {
@@ -118,6 +140,21 @@ mod tests {
         assert_eq!(entry.kind, EntryKind::Dir);
     }
 
+    #[test]
+    fn test_extract_entry_times() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        File::create(&file_path).unwrap();
+        let accessed = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_atime(&file_path, accessed).unwrap();
+
+        let entry = extract_entry(&file_path, 0).unwrap();
+        assert_eq!(entry.atime.unwrap().timestamp(), 1_600_000_000);
+        // Setting the atime is itself a status change
+        #[cfg(unix)]
+        assert!(entry.ctime.unwrap() >= entry.mtime);
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_format_permissions() {
@@ -0,0 +1,239 @@
+//! File types from content (`--detect-type`, `--mime`)
+//!
+//! Extensions are missing on scripts and executables and sometimes wrong on
+//! downloads. Sniffing reads the first few KiB of a file and looks for a
+//! shebang line, then for magic bytes (via `infer`), then tells text from
+//! binary. Where the content alone is only "some text" or "some binary",
+//! the extension still decides, so `main.rs` stays `text/x-rust`.
+//!
+//! Sniffing costs a read per file, so the `mime` and `category` columns and
+//! `--category` only use it with
+//! [`TypeDetection::Content`](crate::fs::enrich::TypeDetection). Only regular
+//! files are read: opening a FIFO or a device would block or have effects.
+
+use crate::fs::enrich::mime_from_extension;
+use crate::models::{FileCategory, MediaType};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file
+const SNIFF_LEN: u64 = 8192;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Interpreters named on a shebang line, with the MIME type and extension of
+/// their scripts; version suffixes (`python3.12`) are ignored
+const INTERPRETERS: &[(&[&str], &str, &str)] = &[
+    (
+        &["sh", "bash", "zsh", "dash", "ksh", "fish"],
+        "text/x-shellscript",
+        "sh",
+    ),
+    (&["python"], "text/x-python", "py"),
+    (&["node", "nodejs", "deno", "bun"], "text/javascript", "js"),
+    (&["ruby"], "text/x-ruby", "rb"),
+    (&["perl"], "text/x-perl", "pl"),
+    (&["php"], "text/x-php", "php"),
+];
+
+/// MIME type of the file at `path`, by its extension when it isn't a regular
+/// file or can't be read
+pub fn detect(path: &Path) -> &'static str {
+    let ext = extension(path);
+    let by_extension = || mime_from_extension(ext).unwrap_or(OCTET_STREAM);
+    if !std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_file()) {
+        return by_extension();
+    }
+    let mut head = Vec::new();
+    match File::open(path).and_then(|file| file.take(SNIFF_LEN).read_to_end(&mut head)) {
+        Ok(_) => sniff(&head, ext),
+        Err(_) => by_extension(),
+    }
+}
+
+/// Category of the file at `path`, from its content
+pub fn detect_category(path: &Path) -> FileCategory {
+    category(detect(path), extension(path))
+}
+
+/// MIME type of a file starting with `head`, with extension `ext`
+pub fn sniff(head: &[u8], ext: &str) -> &'static str {
+    let by_extension = mime_from_extension(ext);
+    if head.is_empty() {
+        return by_extension.unwrap_or("application/x-empty");
+    }
+    if let Some(mime) = shebang(head) {
+        return mime;
+    }
+    if let Some(kind) = infer::get(head) {
+        // infer's text matchers are loose (a leading `<b ` is HTML); a
+        // known extension says more
+        if kind.matcher_type() != infer::MatcherType::Text || by_extension.is_none() {
+            return kind.mime_type();
+        }
+    }
+    if is_text(head) {
+        by_extension
+            .filter(|mime| is_textual(mime))
+            .unwrap_or("text/plain")
+    } else {
+        by_extension
+            .filter(|mime| !is_textual(mime))
+            .unwrap_or(OCTET_STREAM)
+    }
+}
+
+/// Category of a file of type `mime`; the extension decides for types
+/// that don't imply one, like `text/plain`
+pub fn category(mime: &str, ext: &str) -> FileCategory {
+    if let Some((_, _, script_ext)) = INTERPRETERS.iter().find(|(_, m, _)| *m == mime) {
+        return FileCategory::from_extension(script_ext);
+    }
+    let (kind, subtype) = mime.split_once('/').unwrap_or((mime, ""));
+    match (kind, subtype) {
+        ("image", _) => FileCategory::Media {
+            media_type: MediaType::Image,
+        },
+        ("video", _) => FileCategory::Media {
+            media_type: MediaType::Video,
+        },
+        ("audio", _) => FileCategory::Media {
+            media_type: MediaType::Audio,
+        },
+        ("application", "x-executable" | "x-mach-binary" | "vnd.microsoft.portable-executable") => {
+            FileCategory::Executable
+        }
+        (
+            "application",
+            "zip" | "gzip" | "x-tar" | "zstd" | "x-7z-compressed" | "x-bzip2" | "x-xz" | "vnd.rar",
+        ) => FileCategory::Archive,
+        _ => FileCategory::from_extension(ext),
+    }
+}
+
+/// Script type from a `#!` line, including `#!/usr/bin/env -S python3 -u`
+fn shebang(head: &[u8]) -> Option<&'static str> {
+    let line = head.strip_prefix(b"#!")?;
+    let line = &line[..line.iter().position(|&b| b == b'\n').unwrap_or(line.len())];
+    let line = String::from_utf8_lossy(line);
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let found = INTERPRETERS
+        .iter()
+        .find(|(names, _, _)| names.contains(&name));
+    Some(found.map_or("text/plain", |(_, mime, _)| mime))
+}
+
+/// No NUL bytes and valid UTF-8, but for a character cut off at the end
+fn is_text(head: &[u8]) -> bool {
+    !head.contains(&0)
+        && match std::str::from_utf8(head) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        }
+}
+
+/// Whether files of type `mime` are text
+fn is_textual(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/xml"
+                | "application/yaml"
+                | "application/toml"
+                | "application/x-sh"
+                | "image/svg+xml"
+        )
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension().and_then(|e| e.to_str()).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_sniff() {
+        let mut elf = b"\x7fELF\x02\x01\x01".to_vec();
+        elf.resize(64, 0);
+
+        // Content beats a missing or wrong extension
+        assert_eq!(sniff(PNG, ""), "image/png");
+        assert_eq!(sniff(PNG, "dat"), "image/png");
+        assert_eq!(sniff(&elf, ""), "application/x-executable");
+        assert_eq!(sniff(b"#!/bin/bash\necho hi\n", ""), "text/x-shellscript");
+        assert_eq!(
+            sniff(b"#!/usr/bin/env -S python3.12 -u\n", ""),
+            "text/x-python"
+        );
+        assert_eq!(sniff(b"#!/usr/bin/awk -f\n", ""), "text/plain");
+
+        // Text and binary without magic fall back to a fitting extension
+        assert_eq!(sniff(b"fn main() {}\n", "rs"), "text/x-rust");
+        assert_eq!(sniff(b"<b>not html</b>\n", "md"), "text/markdown");
+        assert_eq!(sniff("caf\u{e9}".as_bytes(), ""), "text/plain");
+        assert_eq!(sniff(b"plain", "png"), "text/plain");
+        assert_eq!(sniff(b"\0\x01\x02", "txt"), "application/octet-stream");
+        assert_eq!(sniff(b"", "txt"), "text/plain");
+        assert_eq!(sniff(b"", ""), "application/x-empty");
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(
+            category("application/x-executable", ""),
+            FileCategory::Executable
+        );
+        assert_eq!(category("text/x-shellscript", ""), FileCategory::Executable);
+        assert_eq!(
+            category("text/x-python", ""),
+            FileCategory::Source {
+                language: "python".to_string()
+            }
+        );
+        assert_eq!(
+            category("image/png", "dat"),
+            FileCategory::Media {
+                media_type: MediaType::Image
+            }
+        );
+        assert_eq!(category("application/gzip", ""), FileCategory::Archive);
+        assert_eq!(
+            category("text/x-rust", "rs"),
+            FileCategory::from_extension("rs")
+        );
+    }
+
+    #[test]
+    fn test_detect_reads_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("deploy");
+        std::fs::write(&script, "#!/bin/sh\nexit 0\n").unwrap();
+        assert_eq!(detect(&script), "text/x-shellscript");
+        assert_eq!(detect_category(&script), FileCategory::Executable);
+        // Unreadable files get the extension's type
+        assert_eq!(detect(&dir.path().join("missing.jpg")), "image/jpeg");
+
+        // A FIFO isn't opened, which would block until a writer shows up
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+
+            let fifo = dir.path().join("pipe.txt");
+            let name = std::ffi::CString::new(fifo.as_os_str().as_bytes()).unwrap();
+            // SAFETY: `name` is a valid NUL-terminated path
+            assert_eq!(unsafe { libc::mkfifo(name.as_ptr(), 0o600) }, 0);
+            assert_eq!(detect(&fifo), "text/plain");
+        }
+    }
+}
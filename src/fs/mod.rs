@@ -1,7 +1,43 @@
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod backup;
+pub mod baseline;
+pub mod batch;
+#[cfg(feature = "object")]
+pub mod bininfo;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod collisions;
+pub mod devcache;
+pub mod enrich;
+pub mod exec;
+pub mod explain;
 pub mod filters;
+pub mod find_compat;
+pub mod gitignore;
+pub mod index;
+pub mod lint;
 pub mod metadata;
+#[cfg(feature = "mime")]
+pub mod mime;
+#[cfg(feature = "archive")]
+pub mod pack;
+#[cfg(unix)]
+pub mod perms;
+pub mod portability;
+pub mod preserve;
+pub mod query;
+pub mod quota;
+pub mod reflink;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod similar;
 pub mod size;
+pub mod touch;
+pub mod trash;
 pub mod traverse;
+pub mod unique;
+pub mod users;
 pub mod watch;
 
 #[cfg(feature = "grep")]
@@ -10,5 +46,20 @@ pub mod content;
 #[cfg(feature = "dedup")]
 pub mod dedup;
 
+#[cfg(feature = "dedup")]
+pub mod fuzzy;
+
 #[cfg(feature = "git")]
 pub mod git;
+
+#[cfg(all(feature = "docker", unix))]
+pub mod docker;
+
+#[cfg(feature = "trends")]
+pub mod snapshot;
+
+#[cfg(feature = "trends")]
+pub mod trends;
+
+#[cfg(feature = "trends")]
+pub mod export;
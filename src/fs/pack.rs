@@ -0,0 +1,395 @@
+#[cfg(feature = "archive")]
+use crate::errors::{FsError, Result};
+#[cfg(feature = "archive")]
+use crate::fs::trash;
+#[cfg(feature = "archive")]
+use crate::models::{Entry, EntryKind};
+#[cfg(feature = "archive")]
+use crate::timings;
+#[cfg(feature = "archive")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "archive")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "archive")]
+use std::collections::HashMap;
+#[cfg(feature = "archive")]
+use std::fs::{self, File};
+#[cfg(feature = "archive")]
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(feature = "archive")]
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "archive")]
+use tracing::info_span;
+
+#[cfg(feature = "archive")]
+/// Archive compression, chosen from the archive's extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+#[cfg(feature = "archive")]
+impl Compression {
+    /// `.tar`, `.tar.gz`/`.tgz` or `.tar.zst`/`.tzst`
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Ok(Compression::Zstd)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(Compression::Gzip)
+        } else if name.ends_with(".tar") {
+            Ok(Compression::None)
+        } else {
+            Err(FsError::InvalidFormat {
+                format: format!(
+                    "Unsupported archive type: {} (use .tar, .tar.gz or .tar.zst)",
+                    path.display()
+                ),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+/// Manifest written next to the archive (`<archive>.manifest.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub created: DateTime<Utc>,
+    pub archive: PathBuf,
+    pub compression: Compression,
+    pub total_size: u64,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[cfg(feature = "archive")]
+/// One archived file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Name inside the archive
+    pub name: String,
+    /// Original location
+    pub source: PathBuf,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    /// BLAKE3 hash of the contents
+    pub blake3: String,
+}
+
+#[cfg(feature = "archive")]
+impl PackManifest {
+    /// Path of the manifest for an archive
+    pub fn path_for(archive: &Path) -> PathBuf {
+        let mut name = archive.as_os_str().to_owned();
+        name.push(".manifest.json");
+        PathBuf::from(name)
+    }
+
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = Self::path_for(&self.archive);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).map_err(|e| FsError::PathAccess {
+            path: path.clone(),
+            source: e,
+        })?;
+        Ok(path)
+    }
+}
+
+#[cfg(feature = "archive")]
+/// Name of a path inside the archive: its normal components, like tar does
+/// (leading `/`, `./` and `..` are dropped)
+pub fn archive_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(feature = "archive")]
+/// Write the regular files among `entries` into a new archive
+///
+/// Directories and symlinks are not archived. `on_file` is called with each
+/// file's size after it has been written, for progress reporting.
+pub fn pack_entries(
+    entries: &[Entry],
+    archive: &Path,
+    level: Option<i32>,
+    mut on_file: impl FnMut(u64),
+) -> Result<PackManifest> {
+    let _span = info_span!("pack", archive = %archive.display()).entered();
+    let _timer = timings::phase("pack");
+
+    if archive.exists() {
+        return Err(FsError::InvalidFormat {
+            format: format!("Archive already exists: {}", archive.display()),
+        });
+    }
+
+    let compression = Compression::from_path(archive)?;
+    let file = File::create(archive).map_err(|e| FsError::PathAccess {
+        path: archive.to_path_buf(),
+        source: e,
+    })?;
+
+    match write_archive(entries, file, compression, level, &mut on_file) {
+        Ok((files, total_size)) => Ok(PackManifest {
+            created: Utc::now(),
+            archive: archive.to_path_buf(),
+            compression,
+            total_size,
+            files,
+        }),
+        Err(e) => {
+            // Don't leave a truncated archive behind
+            let _ = fs::remove_file(archive);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+fn write_archive(
+    entries: &[Entry],
+    file: File,
+    compression: Compression,
+    level: Option<i32>,
+    on_file: &mut impl FnMut(u64),
+) -> Result<(Vec<ManifestEntry>, u64)> {
+    let writer: Box<dyn Write> = match compression {
+        Compression::None => Box::new(BufWriter::new(file)),
+        Compression::Gzip => Box::new(flate2::write::GzEncoder::new(
+            BufWriter::new(file),
+            flate2::Compression::new(level.unwrap_or(6).clamp(0, 9) as u32),
+        )),
+        Compression::Zstd => {
+            Box::new(zstd::Encoder::new(BufWriter::new(file), level.unwrap_or(3))?.auto_finish())
+        }
+    };
+
+    let mut builder = tar::Builder::new(writer);
+    let mut files = Vec::new();
+    let mut total_size = 0;
+
+    for entry in entries.iter().filter(|e| e.kind == EntryKind::File) {
+        let name = archive_name(&entry.path);
+        let source = File::open(&entry.path).map_err(|e| FsError::PathAccess {
+            path: entry.path.clone(),
+            source: e,
+        })?;
+        let metadata = source.metadata()?;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata_in_mode(&metadata, tar::HeaderMode::Complete);
+
+        let mut reader = HashingReader::new(BufReader::new(source));
+        builder.append_data(&mut header, &name, &mut reader)?;
+
+        total_size += metadata.len();
+        files.push(ManifestEntry {
+            name,
+            source: entry.path.clone(),
+            size: metadata.len(),
+            modified: entry.mtime,
+            blake3: reader.finish(),
+        });
+        on_file(metadata.len());
+    }
+
+    // Dropping the encoder writes the compression trailer
+    builder.into_inner()?.flush()?;
+
+    Ok((files, total_size))
+}
+
+#[cfg(feature = "archive")]
+/// Re-read the archive and check every file against the manifest
+pub fn verify_archive(manifest: &PackManifest) -> Result<()> {
+    let _timer = timings::phase("verify");
+
+    let file = File::open(&manifest.archive).map_err(|e| FsError::PathAccess {
+        path: manifest.archive.clone(),
+        source: e,
+    })?;
+    let reader: Box<dyn Read> = match manifest.compression {
+        Compression::None => Box::new(BufReader::new(file)),
+        Compression::Gzip => Box::new(flate2::read::GzDecoder::new(BufReader::new(file))),
+        Compression::Zstd => Box::new(zstd::Decoder::new(file)?),
+    };
+
+    let mut expected: HashMap<&str, &ManifestEntry> = manifest
+        .files
+        .iter()
+        .map(|f| (f.name.as_str(), f))
+        .collect();
+
+    let mut archive = tar::Archive::new(reader);
+    for item in archive.entries()? {
+        let mut item = item?;
+        let name = item.path()?.to_string_lossy().into_owned();
+
+        let mut hashing = HashingReader::new(&mut item);
+        let size = io::copy(&mut hashing, &mut io::sink())?;
+        let hash = hashing.finish();
+
+        match expected.remove(name.as_str()) {
+            Some(entry) if entry.size == size && entry.blake3 == hash => {}
+            Some(_) => return Err(verify_error(manifest, &format!("{} is corrupt", name))),
+            None => {
+                return Err(verify_error(
+                    manifest,
+                    &format!("unexpected entry {}", name),
+                ))
+            }
+        }
+    }
+
+    if let Some(name) = expected.keys().next() {
+        return Err(verify_error(manifest, &format!("{} is missing", name)));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "archive")]
+fn verify_error(manifest: &PackManifest, detail: &str) -> FsError {
+    FsError::InvalidFormat {
+        format: format!(
+            "Archive verification failed for {}: {}",
+            manifest.archive.display(),
+            detail
+        ),
+    }
+}
+
+#[cfg(feature = "archive")]
+/// Trash archived originals, or delete them when `permanent`; files changed
+/// since they were packed are kept
+///
+/// Returns the number of removed files and the paths left in place with why.
+pub fn remove_packed(manifest: &PackManifest, permanent: bool) -> (usize, Vec<(PathBuf, String)>) {
+    let mut removed = 0;
+    let mut kept = Vec::new();
+
+    for file in &manifest.files {
+        let unchanged = fs::metadata(&file.source).is_ok_and(|m| {
+            let modified = m.modified().ok().map(DateTime::<Utc>::from);
+            m.len() == file.size
+                && modified.is_some_and(|t| t.timestamp() == file.modified.timestamp())
+        });
+
+        if !unchanged {
+            kept.push((file.source.clone(), "changed since packing".to_string()));
+            continue;
+        }
+
+        match trash::remove(&file.source, permanent) {
+            Ok(()) => removed += 1,
+            Err(e) => kept.push((file.source.clone(), e.to_string())),
+        }
+    }
+
+    (removed, kept)
+}
+
+#[cfg(feature = "archive")]
+/// Reader that hashes everything read through it
+struct HashingReader<R> {
+    inner: R,
+    hasher: blake3::Hasher,
+}
+
+#[cfg(feature = "archive")]
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: blake3::Hasher::new(),
+        }
+    }
+
+    fn finish(&self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+}
+
+#[cfg(feature = "archive")]
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "archive")]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_compression_from_path() {
+        let from = |p: &str| Compression::from_path(Path::new(p));
+        assert_eq!(from("logs.tar.zst").unwrap(), Compression::Zstd);
+        assert_eq!(from("logs.TGZ").unwrap(), Compression::Gzip);
+        assert_eq!(from("logs.tar").unwrap(), Compression::None);
+        assert!(from("logs.zip").is_err());
+    }
+
+    #[test]
+    fn test_archive_name() {
+        assert_eq!(archive_name(Path::new("./logs/app.log")), "logs/app.log");
+        assert_eq!(archive_name(Path::new("/var/log/syslog")), "var/log/syslog");
+        assert_eq!(archive_name(Path::new("../up/x")), "up/x");
+    }
+
+    #[test]
+    fn test_pack_verify_and_remove() {
+        for ext in ["tar", "tar.gz", "tar.zst"] {
+            let dir = tempdir().unwrap();
+            let a = dir.path().join("a.log");
+            let b = dir.path().join("b.log");
+            fs::write(&a, "first log").unwrap();
+            fs::write(&b, "second log").unwrap();
+            let entries = vec![extract_entry(&a, 0).unwrap(), extract_entry(&b, 0).unwrap()];
+
+            let archive = dir.path().join(format!("logs.{}", ext));
+            let mut packed = 0;
+            let manifest = pack_entries(&entries, &archive, None, |_| packed += 1).unwrap();
+            assert_eq!(packed, 2);
+            assert_eq!(manifest.total_size, 19);
+            verify_archive(&manifest).unwrap();
+
+            let (removed, kept) = remove_packed(&manifest, true);
+            assert_eq!(removed, 2);
+            assert!(kept.is_empty());
+            assert!(!a.exists());
+
+            // Refuses to overwrite an existing archive
+            assert!(pack_entries(&entries, &archive, None, |_| {}).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_detects_mismatch() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.log");
+        fs::write(&a, "contents").unwrap();
+        let entries = vec![extract_entry(&a, 0).unwrap()];
+
+        let mut manifest = pack_entries(&entries, &dir.path().join("x.tar"), None, |_| {}).unwrap();
+        manifest.files[0].blake3 = "0".repeat(64);
+        assert!(verify_archive(&manifest).is_err());
+    }
+}
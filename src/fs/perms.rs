@@ -0,0 +1,331 @@
+use crate::errors::{FsError, Result};
+use crate::fs::batch::{BatchChange, BatchReport};
+use crate::fs::users::{group_id, group_name, user_id, user_name};
+use crate::models::{Entry, EntryKind};
+use std::fs;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+const SETUID: u32 = 0o4000;
+const SETGID: u32 = 0o2000;
+const STICKY: u32 = 0o1000;
+
+/// A chmod mode: absolute octal or a list of symbolic clauses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeSpec {
+    /// `644`, `0755`, `1777`
+    Absolute(u32),
+    /// `u+x`, `go-w`, `a=r,u+w`
+    Symbolic(Vec<ModeClause>),
+}
+
+/// One `who op perms` clause of a symbolic mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeClause {
+    /// Bits the clause may touch (from u/g/o/a)
+    who: u32,
+    /// Operations applied in order, e.g. `u+r-w` gives two
+    ops: Vec<(char, String)>,
+}
+
+impl ModeSpec {
+    /// Parse an octal or symbolic mode string
+    pub fn parse(input: &str) -> Result<Self> {
+        let invalid = || FsError::InvalidFormat {
+            format: format!("Invalid mode: {}", input),
+        };
+
+        if !input.is_empty() && input.chars().all(|c| c.is_digit(8)) {
+            let mode = u32::from_str_radix(input, 8).map_err(|_| invalid())?;
+            if mode > 0o7777 {
+                return Err(invalid());
+            }
+            return Ok(ModeSpec::Absolute(mode));
+        }
+
+        let mut clauses = Vec::new();
+        for part in input.split(',') {
+            let op_start = part.find(['+', '-', '=']).ok_or_else(invalid)?;
+            let (who_str, mut rest) = part.split_at(op_start);
+
+            let mut who = 0;
+            for c in who_str.chars() {
+                who |= match c {
+                    'u' => 0o700 | SETUID,
+                    'g' => 0o070 | SETGID,
+                    'o' => 0o007 | STICKY,
+                    'a' => 0o7777,
+                    _ => return Err(invalid()),
+                };
+            }
+            if who == 0 {
+                who = 0o7777;
+            }
+
+            let mut ops = Vec::new();
+            while let Some(op) = rest.chars().next() {
+                let perms_end = rest[1..]
+                    .find(['+', '-', '='])
+                    .map_or(rest.len(), |i| i + 1);
+                let perms = &rest[1..perms_end];
+                if !perms.chars().all(|c| "rwxXst".contains(c)) {
+                    return Err(invalid());
+                }
+                ops.push((op, perms.to_string()));
+                rest = &rest[perms_end..];
+            }
+
+            clauses.push(ModeClause { who, ops });
+        }
+
+        Ok(ModeSpec::Symbolic(clauses))
+    }
+
+    /// Compute the new permission bits for a file with `current` bits
+    pub fn apply(&self, current: u32, is_dir: bool) -> u32 {
+        let clauses = match self {
+            ModeSpec::Absolute(mode) => return *mode,
+            ModeSpec::Symbolic(clauses) => clauses,
+        };
+
+        let mut mode = current & 0o7777;
+        for clause in clauses {
+            for (op, perms) in &clause.ops {
+                let mut bits = 0;
+                for c in perms.chars() {
+                    bits |= match c {
+                        'r' => 0o444,
+                        'w' => 0o222,
+                        'x' => 0o111,
+                        // Execute only for directories or already-executable files
+                        'X' if is_dir || mode & 0o111 != 0 => 0o111,
+                        's' => SETUID | SETGID,
+                        't' => STICKY,
+                        _ => 0,
+                    };
+                }
+                let bits = bits & clause.who;
+
+                mode = match op {
+                    '+' => mode | bits,
+                    '-' => mode & !bits,
+                    _ => (mode & !clause.who) | bits,
+                };
+            }
+        }
+
+        mode
+    }
+}
+
+/// A chown target: `user`, `user:group`, `:group` (names or numeric ids)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnerSpec {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+impl OwnerSpec {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (user, group) = match input.split_once(':') {
+            Some((user, group)) => (user, Some(group)),
+            None => (input, None),
+        };
+
+        let uid = match user {
+            "" => None,
+            name => Some(resolve_id(name, user_id, "user")?),
+        };
+
+        let gid = match group {
+            None | Some("") => None,
+            Some(name) => Some(resolve_id(name, group_id, "group")?),
+        };
+
+        if uid.is_none() && gid.is_none() {
+            return Err(FsError::InvalidFormat {
+                format: format!("Invalid owner: {}", input),
+            });
+        }
+
+        Ok(Self { uid, gid })
+    }
+}
+
+/// Resolve a numeric id or a name via `lookup`
+fn resolve_id(name: &str, lookup: fn(&str) -> Option<u32>, kind: &str) -> Result<u32> {
+    name.parse()
+        .ok()
+        .or_else(|| lookup(name))
+        .ok_or_else(|| FsError::InvalidFormat {
+            format: format!("Unknown {}: {}", kind, name),
+        })
+}
+
+/// Apply a mode to each entry (symlinks are skipped, as chmod can't change them)
+pub fn chmod_entries(entries: &[Entry], spec: &ModeSpec, dry_run: bool) -> BatchReport {
+    let mut report = BatchReport::default();
+
+    for entry in entries {
+        if entry.kind == EntryKind::Symlink {
+            report
+                .skipped
+                .push((entry.path.clone(), "symlink".to_string()));
+            continue;
+        }
+
+        let metadata = match fs::symlink_metadata(&entry.path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                report.errors.push((entry.path.clone(), e.to_string()));
+                continue;
+            }
+        };
+
+        let current = metadata.mode() & 0o7777;
+        let new = spec.apply(current, metadata.is_dir());
+        if new == current {
+            report.unchanged += 1;
+            continue;
+        }
+
+        if !dry_run {
+            if let Err(e) = fs::set_permissions(&entry.path, fs::Permissions::from_mode(new)) {
+                report.errors.push((entry.path.clone(), e.to_string()));
+                continue;
+            }
+        }
+
+        report.changed.push(BatchChange {
+            path: entry.path.clone(),
+            from: format!("{:04o}", current),
+            to: format!("{:04o}", new),
+        });
+    }
+
+    report
+}
+
+/// Apply an owner/group to each entry (symlinks themselves, not their targets)
+pub fn chown_entries(entries: &[Entry], spec: &OwnerSpec, dry_run: bool) -> BatchReport {
+    let mut report = BatchReport::default();
+
+    for entry in entries {
+        let metadata = match fs::symlink_metadata(&entry.path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                report.errors.push((entry.path.clone(), e.to_string()));
+                continue;
+            }
+        };
+
+        let current = (metadata.uid(), metadata.gid());
+        let new = (spec.uid.unwrap_or(current.0), spec.gid.unwrap_or(current.1));
+        if new == current {
+            report.unchanged += 1;
+            continue;
+        }
+
+        if !dry_run {
+            if let Err(e) = std::os::unix::fs::lchown(&entry.path, spec.uid, spec.gid) {
+                report.errors.push((entry.path.clone(), e.to_string()));
+                continue;
+            }
+        }
+
+        report.changed.push(BatchChange {
+            path: entry.path.clone(),
+            from: format_owner(current.0, current.1),
+            to: format_owner(new.0, new.1),
+        });
+    }
+
+    report
+}
+
+/// Format `user:group`, falling back to numeric ids
+fn format_owner(uid: u32, gid: u32) -> String {
+    format!(
+        "{}:{}",
+        user_name(uid).unwrap_or_else(|| uid.to_string()),
+        group_name(gid).unwrap_or_else(|| gid.to_string())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_octal() {
+        assert_eq!(ModeSpec::parse("644").unwrap(), ModeSpec::Absolute(0o644));
+        assert_eq!(ModeSpec::parse("1777").unwrap(), ModeSpec::Absolute(0o1777));
+        assert!(ModeSpec::parse("17777").is_err());
+        assert!(ModeSpec::parse("u+q").is_err());
+        assert!(ModeSpec::parse("z+x").is_err());
+    }
+
+    #[test]
+    fn test_symbolic_apply() {
+        let apply = |spec: &str, mode: u32, is_dir: bool| {
+            ModeSpec::parse(spec).unwrap().apply(mode, is_dir)
+        };
+
+        assert_eq!(apply("u+x", 0o644, false), 0o744);
+        assert_eq!(apply("go-w", 0o666, false), 0o644);
+        assert_eq!(apply("+x", 0o644, false), 0o755);
+        assert_eq!(apply("a=r,u+w", 0o777, false), 0o644);
+        assert_eq!(apply("u+r-w", 0o200, false), 0o400);
+        assert_eq!(apply("o+t", 0o777, true), 0o1777);
+        assert_eq!(apply("g+s", 0o755, true), 0o2755);
+        // X only adds execute to directories or already-executable files
+        assert_eq!(apply("a+X", 0o644, false), 0o644);
+        assert_eq!(apply("a+X", 0o644, true), 0o755);
+        assert_eq!(apply("a+X", 0o744, false), 0o755);
+    }
+
+    #[test]
+    fn test_owner_parse() {
+        assert_eq!(
+            OwnerSpec::parse("0:0").unwrap(),
+            OwnerSpec {
+                uid: Some(0),
+                gid: Some(0)
+            }
+        );
+        assert_eq!(
+            OwnerSpec::parse(":12").unwrap(),
+            OwnerSpec {
+                uid: None,
+                gid: Some(12)
+            }
+        );
+        assert!(OwnerSpec::parse(":").is_err());
+        assert!(OwnerSpec::parse("no-such-user-xyz").is_err());
+    }
+
+    #[test]
+    fn test_chmod_entries_dry_run() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "#!/bin/sh").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let entries = vec![extract_entry(&path, 0).unwrap()];
+        let spec = ModeSpec::parse("u+x").unwrap();
+
+        let report = chmod_entries(&entries, &spec, true);
+        assert_eq!(report.changed[0].from, "0644");
+        assert_eq!(report.changed[0].to, "0744");
+        let mode = fs::metadata(&path).unwrap().mode() & 0o7777;
+        assert_eq!(mode, 0o644);
+
+        let report = chmod_entries(&entries, &spec, false);
+        assert_eq!(report.changed.len(), 1);
+        let mode = fs::metadata(&path).unwrap().mode() & 0o7777;
+        assert_eq!(mode, 0o744);
+
+        let report = chmod_entries(&entries, &spec, false);
+        assert_eq!(report.unchanged, 1);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::models::Entry;
+use crate::timings;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Windows MAX_PATH, the limit legacy APIs and many unzip tools still enforce
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters Windows does not allow in file names
+const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// What makes a path non-portable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueKind {
+    PathTooLong,
+    ReservedName,
+    TrailingSpaceOrDot,
+    InvalidCharacter,
+    NonUtf8,
+}
+
+impl IssueKind {
+    pub const ALL: [IssueKind; 5] = [
+        IssueKind::PathTooLong,
+        IssueKind::ReservedName,
+        IssueKind::TrailingSpaceOrDot,
+        IssueKind::InvalidCharacter,
+        IssueKind::NonUtf8,
+    ];
+
+    /// One-line description of the check
+    pub fn description(&self) -> &'static str {
+        match self {
+            IssueKind::PathTooLong => "Path longer than Windows MAX_PATH",
+            IssueKind::ReservedName => "Windows reserved device name (CON, NUL, COM1, ...)",
+            IssueKind::TrailingSpaceOrDot => "Name ending in a space or dot",
+            IssueKind::InvalidCharacter => "Character not allowed in Windows file names",
+            IssueKind::NonUtf8 => "Name that is not valid UTF-8",
+        }
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::PathTooLong => write!(f, "path-too-long"),
+            IssueKind::ReservedName => write!(f, "reserved-name"),
+            IssueKind::TrailingSpaceOrDot => write!(f, "trailing-space-or-dot"),
+            IssueKind::InvalidCharacter => write!(f, "invalid-character"),
+            IssueKind::NonUtf8 => write!(f, "non-utf8"),
+        }
+    }
+}
+
+/// A portability problem with one path, and how to fix it
+#[derive(Debug, Clone, Serialize)]
+pub struct PortabilityIssue {
+    pub kind: IssueKind,
+    pub path: PathBuf,
+    pub message: String,
+    pub suggestion: String,
+}
+
+/// Options for the portability audit
+#[derive(Debug, Clone)]
+pub struct PortabilityOptions {
+    /// Longest allowed path, counted relative to the audited root
+    pub max_path: usize,
+}
+
+impl Default for PortabilityOptions {
+    fn default() -> Self {
+        Self {
+            max_path: WINDOWS_MAX_PATH,
+        }
+    }
+}
+
+/// Check every entry for names and paths that won't survive a copy to Windows
+///
+/// Path lengths are measured from the root the entry was found under (what an
+/// archive of that root would contain), in UTF-16 units as Windows counts them.
+pub fn check_entries(
+    entries: &[Entry],
+    roots: &[PathBuf],
+    options: &PortabilityOptions,
+) -> Vec<PortabilityIssue> {
+    let _timer = timings::phase("portability");
+
+    let mut issues = Vec::new();
+    for entry in entries {
+        check_name(entry, &mut issues);
+
+        let relative = roots
+            .iter()
+            .filter_map(|root| entry.path.strip_prefix(root).ok())
+            .min_by_key(|rel| rel.as_os_str().len())
+            .unwrap_or(&entry.path);
+        let length = path_length(relative);
+        if length > options.max_path {
+            issues.push(PortabilityIssue {
+                kind: IssueKind::PathTooLong,
+                path: entry.path.clone(),
+                message: format!("path is {} characters (limit {})", length, options.max_path),
+                suggestion: format!(
+                    "shorten the path by at least {} characters",
+                    length - options.max_path
+                ),
+            });
+        }
+    }
+    issues
+}
+
+fn check_name(entry: &Entry, issues: &mut Vec<PortabilityIssue>) {
+    let Some(file_name) = entry.path.file_name() else {
+        return;
+    };
+
+    let mut push = |kind, message: String| {
+        issues.push(PortabilityIssue {
+            kind,
+            path: entry.path.clone(),
+            message,
+            suggestion: format!(
+                "rename to '{}'",
+                portable_name(&file_name.to_string_lossy())
+            ),
+        })
+    };
+
+    let Some(name) = file_name.to_str() else {
+        push(IssueKind::NonUtf8, "name is not valid UTF-8".to_string());
+        return;
+    };
+
+    if is_reserved_name(name) {
+        push(
+            IssueKind::ReservedName,
+            format!("'{}' is a reserved device name on Windows", name),
+        );
+    }
+    if name.ends_with(' ') || name.ends_with('.') {
+        push(
+            IssueKind::TrailingSpaceOrDot,
+            "name ends with a space or dot, which Windows strips".to_string(),
+        );
+    }
+    let invalid: String = name
+        .chars()
+        .filter(|c| is_invalid_char(*c))
+        .map(|c| format!("{:?}", c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !invalid.is_empty() {
+        push(
+            IssueKind::InvalidCharacter,
+            format!("name contains characters invalid on Windows: {}", invalid),
+        );
+    }
+}
+
+/// Whether a name is a Windows device name (`NUL`, `com1.txt`, `Con.tar.gz`)
+pub fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem))
+}
+
+fn is_invalid_char(c: char) -> bool {
+    INVALID_CHARS.contains(&c) || c.is_control()
+}
+
+/// A version of `name` that is valid on Windows
+///
+/// Invalid characters (and the replacement characters left by a lossy UTF-8
+/// conversion) become `_`, trailing spaces and dots are dropped and reserved
+/// device names get a `_` prefix.
+pub fn portable_name(name: &str) -> String {
+    let mut fixed: String = name
+        .chars()
+        .map(|c| {
+            if is_invalid_char(c) || c == char::REPLACEMENT_CHARACTER {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let trimmed_len = fixed.trim_end_matches([' ', '.']).len();
+    fixed.truncate(trimmed_len);
+    if fixed.is_empty() {
+        fixed.push('_');
+    }
+    if is_reserved_name(&fixed) {
+        fixed.insert(0, '_');
+    }
+    fixed
+}
+
+/// Path length as Windows counts it (UTF-16 units, `\` separators)
+fn path_length(path: &Path) -> usize {
+    path.to_string_lossy().encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(path: PathBuf) -> Entry {
+        Entry {
+            size: 10,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    fn kinds(path: &str) -> Vec<IssueKind> {
+        let entry = make_entry(PathBuf::from("root").join(path));
+        check_entries(&[entry], &[PathBuf::from("root")], &Default::default())
+            .into_iter()
+            .map(|i| i.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_reserved_names() {
+        assert!(is_reserved_name("CON"));
+        assert!(is_reserved_name("nul.txt"));
+        assert!(is_reserved_name("Com1.tar.gz"));
+        assert!(!is_reserved_name("CONSOLE.md"));
+        assert!(!is_reserved_name("com10"));
+    }
+
+    #[test]
+    fn test_portable_name() {
+        assert_eq!(portable_name("what?.txt"), "what_.txt");
+        assert_eq!(portable_name("notes. "), "notes");
+        assert_eq!(portable_name("aux.c"), "_aux.c");
+        assert_eq!(portable_name("a:b|c"), "a_b_c");
+        assert_eq!(portable_name("..."), "_");
+    }
+
+    #[test]
+    fn test_check_entries() {
+        assert_eq!(kinds("fine.txt"), vec![]);
+        assert_eq!(kinds("con.txt"), vec![IssueKind::ReservedName]);
+        assert_eq!(kinds("draft "), vec![IssueKind::TrailingSpaceOrDot]);
+        assert_eq!(kinds("a<b>.txt"), vec![IssueKind::InvalidCharacter]);
+        assert_eq!(kinds(&"x".repeat(300)), vec![IssueKind::PathTooLong]);
+        // The root prefix doesn't count toward the limit
+        assert_eq!(kinds(&"x".repeat(260)), vec![]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let entry = make_entry(PathBuf::from(OsStr::from_bytes(b"bad\xffname")));
+        let issues = check_entries(&[entry], &[], &Default::default());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, IssueKind::NonUtf8);
+        assert_eq!(issues[0].suggestion, "rename to 'bad_name'");
+    }
+}
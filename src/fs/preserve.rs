@@ -0,0 +1,94 @@
+//! Keeping a file's owner and attributes when it's replaced
+//!
+//! `grep --replace` and `duplicates --reflink` write a new file next to the
+//! old one and rename it over it. The new file belongs to whoever runs
+//! fexplorer and has none of the old one's extended attributes, so
+//! [`copy_attributes`] carries the owner, group, mode and xattrs over. When
+//! they can't be kept (say, someone else's file replaced without root) it
+//! fails and the caller leaves the original alone.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Give `target` the owner, group, mode and extended attributes of `source`,
+/// whose metadata is `metadata`
+#[cfg(unix)]
+pub fn copy_attributes(source: &Path, metadata: &fs::Metadata, target: &Path) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let current = fs::symlink_metadata(target)?;
+    if (current.uid(), current.gid()) != (metadata.uid(), metadata.gid()) {
+        std::os::unix::fs::lchown(target, Some(metadata.uid()), Some(metadata.gid())).map_err(
+            |e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "can't keep owner {}:{} ({})",
+                        metadata.uid(),
+                        metadata.gid(),
+                        e
+                    ),
+                )
+            },
+        )?;
+    }
+    // After chown, which clears the setuid and setgid bits
+    fs::set_permissions(target, metadata.permissions())?;
+
+    let names = match xattr::list(source) {
+        Ok(names) => names,
+        Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for name in names {
+        if let Some(value) = xattr::get(source, &name)? {
+            xattr::set(target, &name, &value).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "can't keep extended attribute {} ({})",
+                        name.to_string_lossy(),
+                        e
+                    ),
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Give `target` the permissions of `source`, whose metadata is `metadata`
+#[cfg(not(unix))]
+pub fn copy_attributes(_source: &Path, metadata: &fs::Metadata, target: &Path) -> io::Result<()> {
+    fs::set_permissions(target, metadata.permissions())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_copy_attributes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        let target = dir.path().join("target");
+        fs::write(&source, "a").unwrap();
+        fs::write(&target, "b").unwrap();
+        fs::set_permissions(&source, fs::Permissions::from_mode(0o640)).unwrap();
+        // Not every filesystem (tmpfs without user xattrs) takes them
+        let tagged = xattr::set(&source, "user.fexplorer.test", b"yes").is_ok();
+
+        let metadata = fs::metadata(&source).unwrap();
+        copy_attributes(&source, &metadata, &target).unwrap();
+        let copied = fs::metadata(&target).unwrap();
+        assert_eq!(copied.permissions().mode() & 0o7777, 0o640);
+        if tagged {
+            assert_eq!(
+                xattr::get(&target, "user.fexplorer.test").unwrap(),
+                Some(b"yes".to_vec())
+            );
+        }
+    }
+}
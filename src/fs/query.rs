@@ -0,0 +1,721 @@
+//! Filter expressions for `--where`
+//!
+//! A small query language that compiles into the same `Predicate` tree the
+//! individual filter flags build, so a complex condition fits in one string:
+//!
+//! ```text
+//! size > 10MB and mtime < "2024-01-01" and (ext = log or ext = tmp)
+//! ```
+//!
+//! Comparisons are `field op value`. Terms combine with `and`, `or`, `not`
+//! and parentheses; `and` binds tighter than `or`. Values are bare words or
+//! quoted strings (needed for values with spaces, like `"7 days ago"`).
+//!
+//! | Field | Operators | Value |
+//! |-------|-----------|-------|
+//! | `size` | `= != < <= > >=` | size (`10MB`, `2GiB`) |
+//! | `mtime` | `= != < <= > >=` | date (`2024-01-01`, `yesterday`) |
+//! | `age` | `= != < <= > >=` | duration since mtime (`30d`, `2h`) |
+//! | `depth` | `= != < <= > >=` | number |
+//! | `name`, `path`, `ext`, `owner` | `= !=`, `~ !~` (glob) | text |
+//! | `kind` | `= !=` | `file`, `dir`, `symlink` |
+//! | `category` | `= !=` | category name (`source`, `media`, ...) |
+//!
+//! Saved `find` profiles store their filters as arguments instead (`ext`,
+//! `min_size`, `where`, ...); [`profile_predicates`] turns those into the
+//! same predicates.
+
+use crate::errors::{FsError, Result};
+use crate::fs::enrich::TypeDetection;
+use crate::fs::filters::{
+    AndPredicate, CategoryFilter, DateFilter, ExtensionFilter, GlobFilter, KindFilter,
+    NotPredicate, OrPredicate, Predicate, PredicateNode, SizeFilter, TimeField,
+};
+use crate::models::{Entry, EntryKind};
+use crate::util::{format_size_human, parse_date, parse_duration, parse_size};
+use chrono::{DateTime, Utc};
+use globset::{GlobBuilder, GlobMatcher};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+impl CmpOp {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "=" | "==" => CmpOp::Eq,
+            "!=" => CmpOp::Ne,
+            "<" => CmpOp::Lt,
+            "<=" => CmpOp::Le,
+            ">" => CmpOp::Gt,
+            ">=" => CmpOp::Ge,
+            "~" => CmpOp::Match,
+            "!~" => CmpOp::NotMatch,
+            _ => return None,
+        })
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Match => "~",
+            CmpOp::NotMatch => "!~",
+        }
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(self, CmpOp::Lt | CmpOp::Le | CmpOp::Gt | CmpOp::Ge)
+    }
+
+    /// The operator with its operands swapped (`a < b` is `b > a`)
+    fn reversed(self) -> Self {
+        match self {
+            CmpOp::Lt => CmpOp::Gt,
+            CmpOp::Le => CmpOp::Ge,
+            CmpOp::Gt => CmpOp::Lt,
+            CmpOp::Ge => CmpOp::Le,
+            other => other,
+        }
+    }
+
+    /// Rough share of values passing a comparison against a typical value
+    fn selectivity(&self) -> f64 {
+        match self {
+            CmpOp::Eq => 0.05,
+            CmpOp::Ne => 0.95,
+            CmpOp::Gt | CmpOp::Ge => 0.2,
+            CmpOp::Lt | CmpOp::Le => 0.8,
+            CmpOp::Match => 0.1,
+            CmpOp::NotMatch => 0.9,
+        }
+    }
+
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            CmpOp::Eq => ordering == Ordering::Equal,
+            CmpOp::Ne => ordering != Ordering::Equal,
+            CmpOp::Lt => ordering == Ordering::Less,
+            CmpOp::Le => ordering != Ordering::Greater,
+            CmpOp::Gt => ordering == Ordering::Greater,
+            CmpOp::Ge => ordering != Ordering::Less,
+            CmpOp::Match | CmpOp::NotMatch => false,
+        }
+    }
+}
+
+/// Numeric fields
+#[derive(Debug, Clone, Copy)]
+enum NumberField {
+    Size,
+    Depth,
+}
+
+struct NumberPredicate {
+    field: NumberField,
+    op: CmpOp,
+    value: u64,
+}
+
+impl Predicate for NumberPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        let actual = match self.field {
+            NumberField::Size => entry.size,
+            NumberField::Depth => entry.depth as u64,
+        };
+        self.op.holds(actual.cmp(&self.value))
+    }
+
+    fn explain(&self) -> PredicateNode {
+        let (filter, value) = match self.field {
+            NumberField::Size => ("size", format_size_human(self.value)),
+            NumberField::Depth => ("depth", self.value.to_string()),
+        };
+        PredicateNode::leaf(
+            filter,
+            format!("{} {}", self.op.symbol(), value),
+            self.op.selectivity(),
+        )
+    }
+}
+
+/// `mtime` comparisons (`age` compiles to these too)
+struct MtimePredicate {
+    op: CmpOp,
+    value: DateTime<Utc>,
+}
+
+impl Predicate for MtimePredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        self.op.holds(entry.mtime.cmp(&self.value))
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf(
+            "mtime",
+            format!("{} {}", self.op.symbol(), self.value.to_rfc3339()),
+            self.op.selectivity(),
+        )
+    }
+}
+
+/// Text fields
+#[derive(Debug, Clone, Copy)]
+enum TextField {
+    Name,
+    Path,
+    Ext,
+    Owner,
+}
+
+impl TextField {
+    fn name(&self) -> &'static str {
+        match self {
+            TextField::Name => "name",
+            TextField::Path => "path",
+            TextField::Ext => "ext",
+            TextField::Owner => "owner",
+        }
+    }
+
+    fn value(&self, entry: &Entry) -> Option<String> {
+        match self {
+            TextField::Name => Some(entry.name.clone()),
+            TextField::Path => Some(entry.path.to_string_lossy().into_owned()),
+            TextField::Ext => entry
+                .path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase()),
+            TextField::Owner => entry.owner.clone(),
+        }
+    }
+}
+
+enum TextMatch {
+    Exact(String),
+    Glob(GlobMatcher),
+}
+
+struct TextPredicate {
+    field: TextField,
+    negate: bool,
+    matcher: TextMatch,
+}
+
+impl Predicate for TextPredicate {
+    fn test(&self, entry: &Entry) -> bool {
+        let matched = self
+            .field
+            .value(entry)
+            .is_some_and(|value| match &self.matcher {
+                TextMatch::Exact(expected) => value == *expected,
+                TextMatch::Glob(glob) => glob.is_match(&value),
+            });
+        matched != self.negate
+    }
+
+    fn explain(&self) -> PredicateNode {
+        let (op, value) = match (&self.matcher, self.negate) {
+            (TextMatch::Exact(value), false) => (CmpOp::Eq, value.as_str()),
+            (TextMatch::Exact(value), true) => (CmpOp::Ne, value.as_str()),
+            (TextMatch::Glob(glob), false) => (CmpOp::Match, glob.glob().glob()),
+            (TextMatch::Glob(glob), true) => (CmpOp::NotMatch, glob.glob().glob()),
+        };
+        PredicateNode::leaf(
+            self.field.name(),
+            format!("{} {}", op.symbol(), value),
+            op.selectivity(),
+        )
+    }
+}
+
+/// Parse a `--where` expression into a predicate tree; `category` compares
+/// file categories told by `types`
+pub fn parse_where(expr: &str, types: TypeDetection) -> Result<Box<dyn Predicate>> {
+    let tokens = tokenize(expr)?;
+    let mut parser = WhereParser {
+        tokens: &tokens,
+        pos: 0,
+        now: Utc::now(),
+        types,
+    };
+
+    let predicate = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(invalid(format!("unexpected {}", token)));
+    }
+    Ok(predicate)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Bare word: field name, keyword or unquoted value
+    Word(String),
+    /// Quoted string
+    Quoted(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+            Token::Op(op) => write!(f, "'{}'", op.symbol()),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+fn is_operator_char(c: char) -> bool {
+    matches!(c, '=' | '!' | '<' | '>' | '~')
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::LParen);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::RParen);
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => text.extend(chars.next()),
+                    Some(ch) if ch == c => break,
+                    Some(ch) => text.push(ch),
+                    None => return Err(invalid("unterminated string".to_string())),
+                }
+            }
+            tokens.push(Token::Quoted(text));
+        } else if is_operator_char(c) {
+            let mut op = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| is_operator_char(**ch)) {
+                op.push(ch);
+                chars.next();
+            }
+            let parsed =
+                CmpOp::parse(&op).ok_or_else(|| invalid(format!("unknown operator '{}'", op)))?;
+            tokens.push(Token::Op(parsed));
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| {
+                !ch.is_whitespace() && !matches!(ch, '(' | ')') && !is_operator_char(**ch)
+            }) {
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser: `or` of `and`s of (optionally negated) terms
+struct WhereParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    now: DateTime<Utc>,
+    types: TypeDetection,
+}
+
+impl WhereParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Box<dyn Predicate>> {
+        let mut alternatives = vec![self.parse_and()?];
+
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            alternatives.push(self.parse_and()?);
+        }
+
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Box::new(OrPredicate::new(alternatives))
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Box<dyn Predicate>> {
+        let mut terms = vec![self.parse_unary()?];
+
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            terms.push(self.parse_unary()?);
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Box::new(AndPredicate::new(terms))
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Box<dyn Predicate>> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Box::new(NotPredicate::new(self.parse_unary()?)));
+        }
+
+        match self.next() {
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                if self.next() != Some(&Token::RParen) {
+                    return Err(invalid("missing closing ')'".to_string()));
+                }
+                Ok(inner)
+            }
+            Some(Token::Word(field)) => {
+                let field = field.clone();
+                let op = match self.next() {
+                    Some(Token::Op(op)) => *op,
+                    _ => return Err(invalid(format!("expected an operator after '{}'", field))),
+                };
+                let value = match self.next() {
+                    Some(Token::Word(value)) | Some(Token::Quoted(value)) => value.clone(),
+                    _ => {
+                        return Err(invalid(format!(
+                            "expected a value after '{} {}'",
+                            field,
+                            op.symbol()
+                        )))
+                    }
+                };
+                self.comparison(&field, op, &value)
+            }
+            Some(token) => Err(invalid(format!("expected a field name, found {}", token))),
+            None => Err(invalid("expected an expression".to_string())),
+        }
+    }
+
+    fn comparison(&self, field: &str, op: CmpOp, value: &str) -> Result<Box<dyn Predicate>> {
+        let field = field.to_lowercase();
+        let unsupported = || {
+            invalid(format!(
+                "'{}' is not supported for '{}'",
+                op.symbol(),
+                field
+            ))
+        };
+
+        match field.as_str() {
+            "size" | "depth" => {
+                if matches!(op, CmpOp::Match | CmpOp::NotMatch) {
+                    return Err(unsupported());
+                }
+                let (field, value) = if field == "size" {
+                    (NumberField::Size, parse_size(value)?)
+                } else {
+                    let depth = value
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid depth '{}'", value)))?;
+                    (NumberField::Depth, depth)
+                };
+                Ok(Box::new(NumberPredicate { field, op, value }))
+            }
+            "mtime" | "modified" => {
+                if matches!(op, CmpOp::Match | CmpOp::NotMatch) {
+                    return Err(unsupported());
+                }
+                Ok(Box::new(MtimePredicate {
+                    op,
+                    value: parse_date(value)?,
+                }))
+            }
+            "age" => {
+                if matches!(op, CmpOp::Match | CmpOp::NotMatch) {
+                    return Err(unsupported());
+                }
+                // Older means an earlier mtime, so the comparison flips
+                let value = self
+                    .now
+                    .checked_sub_signed(parse_duration(value)?)
+                    .ok_or_else(|| FsError::InvalidDate {
+                        input: value.to_string(),
+                    })?;
+                Ok(Box::new(MtimePredicate {
+                    op: op.reversed(),
+                    value,
+                }))
+            }
+            "name" | "path" | "ext" | "owner" => {
+                if op.is_ordering() {
+                    return Err(unsupported());
+                }
+                let field = match field.as_str() {
+                    "name" => TextField::Name,
+                    "path" => TextField::Path,
+                    "ext" => TextField::Ext,
+                    _ => TextField::Owner,
+                };
+                let value = match field {
+                    TextField::Ext => value.trim_start_matches('.').to_lowercase(),
+                    _ => value.to_string(),
+                };
+                let matcher = if matches!(op, CmpOp::Match | CmpOp::NotMatch) {
+                    let glob = GlobBuilder::new(&value)
+                        .case_insensitive(matches!(field, TextField::Ext))
+                        .build()
+                        .map_err(|e| FsError::InvalidGlob {
+                            pattern: value.clone(),
+                            source: e,
+                        })?;
+                    TextMatch::Glob(glob.compile_matcher())
+                } else {
+                    TextMatch::Exact(value)
+                };
+                Ok(Box::new(TextPredicate {
+                    field,
+                    negate: matches!(op, CmpOp::Ne | CmpOp::NotMatch),
+                    matcher,
+                }))
+            }
+            "kind" | "type" => {
+                let kind = match value.to_lowercase().as_str() {
+                    "file" | "f" => EntryKind::File,
+                    "dir" | "directory" | "d" => EntryKind::Dir,
+                    "symlink" | "link" | "l" => EntryKind::Symlink,
+                    other => return Err(invalid(format!("unknown kind '{}'", other))),
+                };
+                equality(op, Box::new(KindFilter::new(&[kind]))).ok_or_else(unsupported)
+            }
+            "category" => {
+                let filter = CategoryFilter::new(value).detected_by(self.types);
+                equality(op, Box::new(filter)).ok_or_else(unsupported)
+            }
+            other => Err(invalid(format!("unknown field '{}'", other))),
+        }
+    }
+}
+
+/// `=` keeps the predicate, `!=` negates it; other operators don't apply
+fn equality(op: CmpOp, predicate: Box<dyn Predicate>) -> Option<Box<dyn Predicate>> {
+    match op {
+        CmpOp::Eq => Some(predicate),
+        CmpOp::Ne => Some(Box::new(NotPredicate::new(predicate))),
+        _ => None,
+    }
+}
+
+fn invalid(message: String) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("--where: {}", message),
+    }
+}
+
+/// Filter predicates from the arguments of a `find` profile (or pipeline
+/// stage), with file categories told by `types`
+pub fn profile_predicates(
+    args: &HashMap<String, Value>,
+    types: TypeDetection,
+) -> Result<Vec<Box<dyn Predicate>>> {
+    let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
+
+    let names = profile_list(args, "names");
+    if !names.is_empty() {
+        predicates.push(Box::new(GlobFilter::new(&names)?));
+    }
+
+    let extensions = profile_list(args, "ext");
+    if !extensions.is_empty() {
+        predicates.push(Box::new(ExtensionFilter::new(&extensions)));
+    }
+
+    let min = args.get("min_size").and_then(|v| v.as_str());
+    let max = args.get("max_size").and_then(|v| v.as_str());
+    let size: Vec<String> = match args.get("size") {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        Some(Value::String(value)) => vec![value.clone()],
+        _ => Vec::new(),
+    };
+    if min.is_some() || max.is_some() || !size.is_empty() {
+        let mut filter = SizeFilter::new(min, max)?.with_bounds(&size)?;
+        if args.get("dir_size").and_then(|v| v.as_bool()) == Some(true) {
+            filter = filter.with_dir_sizes(Default::default());
+        }
+        predicates.push(Box::new(filter));
+    }
+
+    let after = args.get("after").and_then(|v| v.as_str());
+    let before = args.get("before").and_then(|v| v.as_str());
+    let older_than = args.get("older_than").and_then(|v| v.as_str());
+    let newer_than = args.get("newer_than").and_then(|v| v.as_str());
+    if after.is_some() || before.is_some() || older_than.is_some() || newer_than.is_some() {
+        predicates.push(Box::new(
+            DateFilter::new(after, before)?.with_ages(older_than, newer_than)?,
+        ));
+    }
+
+    for field in [TimeField::Accessed, TimeField::Changed, TimeField::Created] {
+        let after = args
+            .get(&format!("{}_after", field.name()))
+            .and_then(|v| v.as_str());
+        let before = args
+            .get(&format!("{}_before", field.name()))
+            .and_then(|v| v.as_str());
+        if after.is_some() || before.is_some() {
+            predicates.push(Box::new(DateFilter::new(after, before)?.on(field)));
+        }
+    }
+
+    if let Some(category) = args.get("category").and_then(|v| v.as_str()) {
+        predicates.push(Box::new(CategoryFilter::new(category).detected_by(types)));
+    }
+
+    #[cfg(feature = "mime")]
+    {
+        let mime = profile_list(args, "mime");
+        if !mime.is_empty() {
+            predicates.push(Box::new(crate::fs::filters::MimeFilter::new(&mime)));
+        }
+    }
+
+    if let Some(expr) = args.get("where").and_then(|v| v.as_str()) {
+        predicates.push(parse_where(expr, types)?);
+    }
+
+    Ok(predicates)
+}
+
+/// A list argument of a profile: an array, or one comma-separated string
+/// (as a `{{param}}` placeholder fills it)
+pub fn profile_list(args: &HashMap<String, Value>, key: &str) -> Vec<String> {
+    match args.get(key) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        Some(Value::String(value)) => value
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn parse(expr: &str) -> Result<Box<dyn Predicate>> {
+        parse_where(expr, TypeDetection::Extension)
+    }
+
+    fn make_entry(path: &str, size: u64, kind: EntryKind, age: Duration) -> Entry {
+        Entry {
+            size,
+            kind,
+            mtime: Utc::now() - age,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_combined_expression() {
+        let predicate =
+            parse(r#"size > 10MB and mtime < "2024-01-01" and (ext = log or ext = tmp)"#).unwrap();
+        let old = Duration::days(365 * 5);
+
+        assert!(predicate.test(&make_entry("var/app.log", 20 << 20, EntryKind::File, old)));
+        assert!(predicate.test(&make_entry("x.TMP", 20 << 20, EntryKind::File, old)));
+        assert!(!predicate.test(&make_entry("app.log", 1 << 20, EntryKind::File, old)));
+        assert!(!predicate.test(&make_entry("app.txt", 20 << 20, EntryKind::File, old)));
+        assert!(!predicate.test(&make_entry(
+            "app.log",
+            20 << 20,
+            EntryKind::File,
+            Duration::zero()
+        )));
+    }
+
+    #[test]
+    fn test_precedence_and_not() {
+        // and binds tighter than or
+        let predicate = parse("name = a.txt or kind = dir and size > 1k").unwrap();
+        assert!(predicate.test(&make_entry("a.txt", 0, EntryKind::File, Duration::zero())));
+        assert!(!predicate.test(&make_entry("src", 0, EntryKind::Dir, Duration::zero())));
+
+        let predicate = parse("not (name ~ '*.rs' or category = docs)").unwrap();
+        assert!(!predicate.test(&make_entry("main.rs", 0, EntryKind::File, Duration::zero())));
+        assert!(!predicate.test(&make_entry(
+            "README.md",
+            0,
+            EntryKind::File,
+            Duration::zero()
+        )));
+        assert!(predicate.test(&make_entry(
+            "logo.png",
+            0,
+            EntryKind::File,
+            Duration::zero()
+        )));
+    }
+
+    #[test]
+    fn test_age_and_depth() {
+        let predicate = parse("age > 30d AND depth <= 1").unwrap();
+        assert!(predicate.test(&make_entry("old", 0, EntryKind::File, Duration::days(40))));
+        assert!(!predicate.test(&make_entry("new", 0, EntryKind::File, Duration::days(2))));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("size >").is_err());
+        assert!(parse("size ~ 10MB").is_err());
+        assert!(parse("name < a").is_err());
+        assert!(parse("colour = red").is_err());
+        assert!(parse("(size > 1k").is_err());
+        assert!(parse("name = 'open").is_err());
+        assert!(parse("size > 1k size < 2k").is_err());
+        assert!(parse("size => 1k").is_err());
+        assert!(parse("age > 300000y").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::models::{Entry, EntryKind};
+use crate::util::format_size_human;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Limits a directory tree must stay within
+#[derive(Debug, Clone, Default)]
+pub struct QuotaLimits {
+    /// Maximum total size of all files, in bytes
+    pub max_size: Option<u64>,
+    /// Maximum number of files
+    pub max_files: Option<u64>,
+}
+
+/// Which limit was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaLimit {
+    Size,
+    Files,
+}
+
+/// A limit that the tree exceeds
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaViolation {
+    pub limit: QuotaLimit,
+    pub actual: u64,
+    pub max: u64,
+}
+
+impl fmt::Display for QuotaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.limit {
+            QuotaLimit::Size => write!(
+                f,
+                "size {} exceeds limit {}",
+                format_size_human(self.actual),
+                format_size_human(self.max)
+            ),
+            QuotaLimit::Files => write!(f, "file count {} exceeds limit {}", self.actual, self.max),
+        }
+    }
+}
+
+/// Usage of one root compared against the limits
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaReport {
+    pub path: PathBuf,
+    pub size: u64,
+    pub files: u64,
+    pub violations: Vec<QuotaViolation>,
+    /// Largest direct children by total size, to show where the space goes
+    pub top_children: Vec<(PathBuf, u64)>,
+}
+
+impl QuotaReport {
+    pub fn exceeded(&self) -> bool {
+        !self.violations.is_empty()
+    }
+}
+
+/// Measure the entries walked under `root` and compare them with the limits
+///
+/// Sizes are apparent file sizes. When a limit is exceeded, the report lists
+/// the `top` largest direct children of the root.
+pub fn check_quota(
+    root: &Path,
+    entries: &[Entry],
+    limits: &QuotaLimits,
+    top: usize,
+) -> QuotaReport {
+    let mut size = 0u64;
+    let mut files = 0u64;
+    let mut children: HashMap<PathBuf, u64> = HashMap::new();
+
+    for entry in entries.iter().filter(|e| e.kind == EntryKind::File) {
+        size += entry.size;
+        files += 1;
+
+        // Attribute the file to the root's direct child containing it
+        let first = entry
+            .path
+            .strip_prefix(root)
+            .ok()
+            .and_then(|relative| relative.components().next());
+        if let Some(first) = first {
+            *children.entry(root.join(first)).or_default() += entry.size;
+        }
+    }
+
+    let mut violations = Vec::new();
+    if let Some(max) = limits.max_size.filter(|&max| size > max) {
+        violations.push(QuotaViolation {
+            limit: QuotaLimit::Size,
+            actual: size,
+            max,
+        });
+    }
+    if let Some(max) = limits.max_files.filter(|&max| files > max) {
+        violations.push(QuotaViolation {
+            limit: QuotaLimit::Files,
+            actual: files,
+            max,
+        });
+    }
+
+    let mut top_children: Vec<(PathBuf, u64)> = Vec::new();
+    if !violations.is_empty() {
+        top_children = children.into_iter().collect();
+        top_children.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_children.truncate(top);
+    }
+
+    QuotaReport {
+        path: root.to_path_buf(),
+        size,
+        files,
+        violations,
+        top_children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(path: &str, size: u64) -> Entry {
+        Entry {
+            size,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_check_quota() {
+        let entries = vec![
+            make_entry("scratch/a/big.bin", 700),
+            make_entry("scratch/a/small.bin", 50),
+            make_entry("scratch/b/data.csv", 200),
+            make_entry("scratch/top.txt", 100),
+        ];
+        let root = Path::new("scratch");
+
+        let limits = QuotaLimits {
+            max_size: Some(1000),
+            max_files: Some(10),
+        };
+        let report = check_quota(root, &entries, &limits, 2);
+        assert_eq!(report.size, 1050);
+        assert_eq!(report.files, 4);
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].limit, QuotaLimit::Size);
+        assert_eq!(
+            report.top_children,
+            vec![
+                (PathBuf::from("scratch/a"), 750),
+                (PathBuf::from("scratch/b"), 200)
+            ]
+        );
+
+        let limits = QuotaLimits {
+            max_size: None,
+            max_files: Some(4),
+        };
+        let report = check_quota(root, &entries, &limits, 2);
+        assert!(!report.exceeded());
+        assert!(report.top_children.is_empty());
+    }
+}
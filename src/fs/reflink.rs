@@ -0,0 +1,355 @@
+//! Copy-on-write file clones (reflinks)
+//!
+//! A clone shares its data blocks with the source until either file is
+//! modified, so it costs no extra space. Linux supports it through the
+//! `FICLONE` ioctl (btrfs, XFS, bcachefs, ...) and macOS through
+//! `clonefile` (APFS). Other filesystems report "not supported", and
+//! [`copy_file`] falls back to a regular copy.
+
+use crate::fs::batch::{BatchChange, BatchReport};
+use crate::models::DuplicateGroup;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// How [`copy_file`] copied a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    /// Copy-on-write clone sharing the source's blocks
+    Clone,
+    /// Regular byte copy
+    Copy,
+}
+
+/// Clone `src` to the new file `dst`, which must not exist yet
+///
+/// Nothing is left at `dst` when cloning fails.
+pub fn clone_file(src: &Path, dst: &Path) -> io::Result<()> {
+    clone_file_impl(src, dst)
+}
+
+#[cfg(target_os = "linux")]
+fn clone_file_impl(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let source = File::open(src)?;
+    let target = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dst)?;
+
+    // SAFETY: both descriptors stay open for the duration of the call
+    let ret = unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    if ret == -1 {
+        let err = io::Error::last_os_error();
+        drop(target);
+        let _ = fs::remove_file(dst);
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn clone_file_impl(src: &Path, dst: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let src = CString::new(src.as_os_str().as_bytes())?;
+    let dst = CString::new(dst.as_os_str().as_bytes())?;
+    // SAFETY: both are valid NUL-terminated paths
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), 0) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn clone_file_impl(_src: &Path, _dst: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reflinks are not supported on this platform",
+    ))
+}
+
+/// Whether a clone failed because the filesystem (or the pair of paths) can't
+/// do it, rather than for a reason a regular copy would hit as well
+pub fn is_unsupported(err: &io::Error) -> bool {
+    if matches!(
+        err.kind(),
+        io::ErrorKind::Unsupported | io::ErrorKind::CrossesDevices
+    ) {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        // EINVAL: unaligned or special files; ENOTTY: no ioctl on this filesystem
+        let codes = [
+            libc::EOPNOTSUPP,
+            libc::ENOTSUP,
+            libc::EXDEV,
+            libc::EINVAL,
+            libc::ENOTTY,
+            libc::ENOSYS,
+        ];
+        err.raw_os_error().is_some_and(|code| codes.contains(&code))
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+/// Copy `src` to `dst`, cloning when the filesystem supports it
+pub fn copy_file(src: &Path, dst: &Path) -> io::Result<CopyMethod> {
+    match clone_file(src, dst) {
+        Ok(()) => Ok(CopyMethod::Clone),
+        Err(e) if is_unsupported(&e) => fs::copy(src, dst).map(|_| CopyMethod::Copy),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether files in `dir` can be cloned, by cloning a small probe file
+pub fn supports_reflink(dir: &Path) -> bool {
+    let probe = dir.join(format!(".fexplorer-reflink-probe-{}", std::process::id()));
+    let clone = probe.with_extension("clone");
+    let supported = fs::write(&probe, b"probe").is_ok() && clone_file(&probe, &clone).is_ok();
+    let _ = fs::remove_file(&clone);
+    let _ = fs::remove_file(&probe);
+    supported
+}
+
+/// Replace `duplicate` with a clone of `original`, freeing its blocks
+///
+/// The clone is made next to the duplicate, checked byte for byte against
+/// it, given the duplicate's owner, mode, extended attributes and mtime, and
+/// only then renamed over it. If anything fails (e.g. the duplicate belongs
+/// to another user) the duplicate is left untouched.
+pub fn replace_with_clone(original: &Path, duplicate: &Path) -> io::Result<()> {
+    let name = duplicate
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(".fexplorer-clone");
+    let temp = duplicate.with_file_name(temp_name);
+
+    clone_file(original, &temp)?;
+    let result = (|| {
+        if !same_contents(&temp, duplicate)? {
+            return Err(io::Error::other(
+                "contents changed since the duplicate scan",
+            ));
+        }
+        let metadata = fs::metadata(duplicate)?;
+        crate::fs::preserve::copy_attributes(duplicate, &metadata, &temp)?;
+        filetime::set_file_mtime(
+            &temp,
+            filetime::FileTime::from_last_modification_time(&metadata),
+        )?;
+        fs::rename(&temp, duplicate)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (
+        BufReader::new(File::open(a)?),
+        BufReader::new(File::open(b)?),
+    );
+    let (mut buf_a, mut buf_b) = ([0u8; 64 * 1024], [0u8; 64 * 1024]);
+    loop {
+        let n = a.read(&mut buf_a)?;
+        if n == 0 {
+            return Ok(b.read(&mut buf_b[..1])? == 0);
+        }
+        if b.read_exact(&mut buf_b[..n]).is_err() || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Replace every file of each group with a clone of the group's first path
+///
+/// Files that are hard links to the kept file, have other hard links (the
+/// space wouldn't be freed), live on another filesystem, or sit on a
+/// filesystem without reflink support are skipped with the reason.
+pub fn clone_duplicates(groups: &[DuplicateGroup], dry_run: bool) -> BatchReport {
+    let mut report = BatchReport::default();
+    let mut support: HashMap<PathBuf, bool> = HashMap::new();
+
+    for group in groups {
+        let mut paths: Vec<&Path> = group.entries.iter().map(|e| e.path.as_path()).collect();
+        paths.sort();
+        let Some((&keep, rest)) = paths.split_first() else {
+            continue;
+        };
+        let kept = match FileId::of(keep) {
+            Ok(id) => id,
+            Err(e) => {
+                report.errors.push((keep.to_path_buf(), e.to_string()));
+                continue;
+            }
+        };
+
+        for &path in rest {
+            let id = match FileId::of(path) {
+                Ok(id) => id,
+                Err(e) => {
+                    report.errors.push((path.to_path_buf(), e.to_string()));
+                    continue;
+                }
+            };
+            let skip = if id.same_file(&kept) {
+                Some(format!("hard link to {}", keep.display()))
+            } else if id.dev != kept.dev {
+                Some(format!("on another filesystem than {}", keep.display()))
+            } else if id.links > 1 {
+                Some("has other hard links, cloning would not free space".to_string())
+            } else {
+                let dir = path.parent().unwrap_or(Path::new("."));
+                let supported = *support
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| supports_reflink(dir));
+                (!supported).then(|| "filesystem does not support reflinks".to_string())
+            };
+            if let Some(reason) = skip {
+                report.skipped.push((path.to_path_buf(), reason));
+                continue;
+            }
+
+            if !dry_run {
+                if let Err(e) = replace_with_clone(keep, path) {
+                    report.errors.push((path.to_path_buf(), e.to_string()));
+                    continue;
+                }
+            }
+            report.changed.push(BatchChange {
+                path: path.to_path_buf(),
+                from: "copy".to_string(),
+                to: format!("clone of {}", keep.display()),
+            });
+        }
+    }
+
+    report
+}
+
+/// Identity of a file on disk
+struct FileId {
+    dev: u64,
+    ino: u64,
+    links: u64,
+}
+
+impl FileId {
+    #[cfg(unix)]
+    fn of(path: &Path) -> io::Result<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = fs::symlink_metadata(path)?;
+        Ok(Self {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+            links: metadata.nlink(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn of(path: &Path) -> io::Result<Self> {
+        fs::symlink_metadata(path)?;
+        Ok(Self {
+            dev: 0,
+            ino: 0,
+            links: 1,
+        })
+    }
+
+    fn same_file(&self, other: &Self) -> bool {
+        self.ino != 0 && self.dev == other.dev && self.ino == other.ino
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    #[test]
+    fn test_copy_file_falls_back_to_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.bin");
+        let dst = dir.path().join("dst.bin");
+        fs::write(&src, vec![7u8; 100_000]).unwrap();
+
+        let method = copy_file(&src, &dst).unwrap();
+        assert_eq!(fs::read(&dst).unwrap(), fs::read(&src).unwrap());
+        assert_eq!(method == CopyMethod::Clone, supports_reflink(dir.path()));
+        // The probe cleans up after itself
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_same_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, data: &[u8]| {
+            let path = dir.path().join(name);
+            fs::write(&path, data).unwrap();
+            path
+        };
+        let a = write("a", b"same data");
+        let b = write("b", b"same data");
+        let c = write("c", b"same datb");
+        let d = write("d", b"same data and more");
+
+        assert!(same_contents(&a, &b).unwrap());
+        assert!(!same_contents(&a, &c).unwrap());
+        assert!(!same_contents(&a, &d).unwrap());
+        assert!(!same_contents(&d, &a).unwrap());
+    }
+
+    #[test]
+    fn test_clone_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.bin");
+        let b = dir.path().join("b.bin");
+        let link = dir.path().join("c.bin");
+        fs::write(&a, vec![1u8; 10_000]).unwrap();
+        fs::write(&b, vec![1u8; 10_000]).unwrap();
+        fs::hard_link(&a, &link).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&b, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let entries = [&a, &b, &link]
+            .iter()
+            .map(|p| extract_entry(p, 1).unwrap())
+            .collect();
+        let group = DuplicateGroup::new("hash".to_string(), 10_000, entries);
+        let report = clone_duplicates(&[group], false);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert!(report
+            .skipped
+            .iter()
+            .any(|(path, reason)| path == &link && reason.starts_with("hard link")));
+        if supports_reflink(dir.path()) {
+            assert_eq!(report.changed.len(), 1);
+            assert_eq!(report.changed[0].path, b);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(&b).unwrap().permissions().mode();
+                assert_eq!(mode & 0o7777, 0o640);
+            }
+        } else {
+            assert!(report.skipped.iter().any(|(path, _)| path == &b));
+        }
+        assert_eq!(fs::read(&b).unwrap(), vec![1u8; 10_000]);
+    }
+}
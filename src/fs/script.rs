@@ -0,0 +1,153 @@
+//! Scripted filters (`--filter-script FILE.rhai`)
+//!
+//! The script runs once per entry with `entry` in scope: an object map with
+//! the fields `--format json` prints (`path`, `name`, `size`, `kind`, `mtime`
+//! in Unix seconds, ...) plus `ext`, the lowercased extension. Fields an
+//! entry doesn't have read as `()`. The script's value must be a boolean;
+//! `true` keeps the entry:
+//!
+//! ```text
+//! // release-1.4.2.tar.gz and newer
+//! let v = entry.name.split('-')[1].split('.');
+//! v.len() > 2 && (parse_int(v[0]) > 1 || parse_int(v[1]) >= 4)
+//! ```
+//!
+//! Scripts can't touch the filesystem, and each run is capped at
+//! [`MAX_OPERATIONS`] so a looping script can't hang a walk.
+
+use crate::errors::{FsError, Result};
+use crate::fs::filters::{Predicate, PredicateNode};
+use crate::models::Entry;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+/// Operations (roughly, evaluation steps) a single run may take
+pub const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A Rhai script as a filter predicate
+///
+/// Entries the script fails on (a runtime error, a non-boolean result,
+/// running out of operations) are excluded; the first failure is logged.
+pub struct ScriptFilter {
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    failed: AtomicBool,
+}
+
+impl ScriptFilter {
+    /// Compile the script at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| FsError::PathAccess {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Self::compile(path, &source)
+    }
+
+    /// Compile `source`; `path` only names the script in messages
+    pub fn compile(path: &Path, source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| FsError::InvalidFormat {
+            format: format!("Filter script {}: {}", path.display(), e),
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            engine,
+            ast,
+            failed: AtomicBool::new(false),
+        })
+    }
+
+    fn eval(&self, entry: &Entry) -> std::result::Result<bool, String> {
+        let mut map: Map = rhai::serde::to_dynamic(entry)
+            .map_err(|e| e.to_string())?
+            .try_cast()
+            .ok_or("entry is not an object")?;
+        let ext = entry
+            .path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        map.insert("ext".into(), ext.into());
+
+        let mut scope = Scope::new();
+        scope.push_constant("entry", map);
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| e.to_string())?;
+        value
+            .as_bool()
+            .map_err(|type_name| format!("script returned {}, not a boolean", type_name))
+    }
+}
+
+impl Predicate for ScriptFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        self.eval(entry).unwrap_or_else(|e| {
+            if !self.failed.swap(true, Ordering::Relaxed) {
+                warn!(
+                    script = %self.path.display(),
+                    path = %entry.path.display(),
+                    error = %e,
+                    "Filter script failed, excluding the entries it fails on"
+                );
+            }
+            false
+        })
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("script", self.path.display().to_string(), 0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    fn filter(source: &str) -> ScriptFilter {
+        ScriptFilter::compile(Path::new("test.rhai"), source).unwrap()
+    }
+
+    #[test]
+    fn test_script_filters_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["release-1.2.0.tar.gz", "release-1.10.3.TGZ", "notes.md"] {
+            std::fs::write(dir.path().join(name), "data").unwrap();
+        }
+        let entry = |name: &str| extract_entry(&dir.path().join(name), 1).unwrap();
+
+        let newer = filter(
+            r#"
+            fn minor(name) { parse_int(name.split('-')[1].split('.')[1]) }
+            entry.name.starts_with("release-") && minor(entry.name) >= 10
+            "#,
+        );
+        assert!(newer.test(&entry("release-1.10.3.TGZ")));
+        assert!(!newer.test(&entry("release-1.2.0.tar.gz")));
+        assert!(!newer.test(&entry("notes.md")));
+
+        let by_ext = filter(r#"entry.ext == "tgz" && entry.size == 4 && entry.kind == "file""#);
+        assert!(by_ext.test(&entry("release-1.10.3.TGZ")));
+        assert!(!by_ext.test(&entry("notes.md")));
+    }
+
+    #[test]
+    fn test_failing_script_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, "data").unwrap();
+        let entry = extract_entry(&path, 1).unwrap();
+
+        assert!(!filter("entry.size").test(&entry));
+        assert!(!filter("loop {}").test(&entry));
+        assert!(!filter("entry.name.no_such_method()").test(&entry));
+        assert!(ScriptFilter::compile(Path::new("bad.rhai"), "let = ;").is_err());
+    }
+}
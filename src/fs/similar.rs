@@ -0,0 +1,327 @@
+use crate::models::{Entry, EntryKind, SimilarNameGroup};
+use crate::timings;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Options for near-duplicate name detection
+#[derive(Debug, Clone)]
+pub struct SimilarOptions {
+    /// Maximum edit distance between normalized names
+    pub max_distance: usize,
+    /// Compare files across directories, not just siblings
+    pub across_dirs: bool,
+}
+
+impl Default for SimilarOptions {
+    fn default() -> Self {
+        Self {
+            max_distance: 2,
+            across_dirs: false,
+        }
+    }
+}
+
+/// Files sharing a directory (unless comparing across directories) and extension
+type Bucket<'a> = (Option<&'a Path>, String);
+
+/// Backup extensions that are stripped before comparing (`notes.txt.bak`)
+const BACKUP_EXTENSIONS: &[&str] = &["bak", "orig", "old", "backup"];
+
+/// Normalizes file names by stripping copy markers and version tags
+pub struct NameNormalizer {
+    copy_prefix: Regex,
+    suffix: Regex,
+}
+
+impl NameNormalizer {
+    pub fn new() -> Self {
+        Self {
+            copy_prefix: Regex::new(r"^copy of\s+").expect("valid regex"),
+            suffix: Regex::new(
+                r"(?x)
+                (
+                    [\s_-]+copy(\s*\d+)?           # ' copy', '_copy', ' - Copy 2'
+                  | \s*\(\d+\) | \s*\[\d+\]        # ' (1)', '[2]'
+                  | [\s_-]+v\d+                    # '_v2', ' v3'
+                  | [\s_-]+(final|old|new|backup|bak|orig|original|draft|edited|updated|latest)
+                )$",
+            )
+            .expect("valid regex"),
+        }
+    }
+
+    /// Split a file name into its normalized stem and lowercase extension
+    ///
+    /// `Report (1).DOCX`, `report_final_v2.docx` and `Copy of report.docx.bak`
+    /// all normalize to `("report", "docx")`.
+    pub fn key(&self, name: &str) -> (String, String) {
+        let mut name = name.trim_end_matches('~').to_lowercase();
+
+        loop {
+            match name.rsplit_once('.') {
+                Some((inner, ext)) if BACKUP_EXTENSIONS.contains(&ext) && !inner.is_empty() => {
+                    name = inner.to_string();
+                }
+                _ => break,
+            }
+        }
+
+        let (stem, ext) = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), ext.to_string()),
+            _ => (name.clone(), String::new()),
+        };
+
+        let mut stem = self.copy_prefix.replace(&stem, "").into_owned();
+        loop {
+            let stripped = self.suffix.replace(&stem, "").into_owned();
+            if stripped == stem || stripped.is_empty() {
+                break;
+            }
+            stem = stripped;
+        }
+
+        (stem.trim().to_string(), ext)
+    }
+}
+
+impl Default for NameNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cluster files whose names are near-identical
+///
+/// Names are normalized first; files then join a group when their normalized
+/// stems are equal or within `max_distance` edits. Only groups with at least
+/// two distinct file names are reported, so same-named files in different
+/// directories (`mod.rs`, `README.md`) don't count on their own.
+pub fn find_similar_names(entries: &[Entry], options: &SimilarOptions) -> Vec<SimilarNameGroup> {
+    let _timer = timings::phase("similar");
+    let normalizer = NameNormalizer::new();
+
+    // Only names in the same bucket (directory and extension) are compared
+    let mut buckets: HashMap<Bucket, Vec<(String, &Entry)>> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.kind == EntryKind::File) {
+        let (stem, ext) = normalizer.key(&entry.name);
+        let dir = if options.across_dirs {
+            None
+        } else {
+            entry.path.parent()
+        };
+        buckets.entry((dir, ext)).or_default().push((stem, entry));
+    }
+
+    let mut groups = Vec::new();
+    for ((_, ext), items) in buckets {
+        let mut sets = DisjointSet::new(items.len());
+
+        // Identical stems are merged first; the pairwise edit-distance pass
+        // then only has to look at one representative per stem
+        let mut by_stem: HashMap<&str, usize> = HashMap::new();
+        for (i, (stem, _)) in items.iter().enumerate() {
+            match by_stem.get(stem.as_str()) {
+                Some(&first) => sets.union(first, i),
+                None => {
+                    by_stem.insert(stem, i);
+                }
+            }
+        }
+
+        let mut stems: Vec<(&str, usize)> = by_stem.into_iter().collect();
+        stems.sort_by_key(|(stem, _)| stem.chars().count());
+        for (i, (a, ai)) in stems.iter().enumerate() {
+            let a_len = a.chars().count();
+            for (b, bi) in &stems[i + 1..] {
+                if b.chars().count() - a_len > options.max_distance {
+                    break;
+                }
+                if names_similar(a, b, options.max_distance) {
+                    sets.union(*ai, *bi);
+                }
+            }
+        }
+
+        let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..items.len() {
+            clusters.entry(sets.find(i)).or_default().push(i);
+        }
+
+        for members in clusters.into_values() {
+            let mut group: Vec<Entry> = members.iter().map(|&i| items[i].1.clone()).collect();
+            let first_name = &group[0].name;
+            if group.iter().all(|e| &e.name == first_name) {
+                continue;
+            }
+
+            group.sort_by(|a, b| a.path.cmp(&b.path));
+            // The shortest normalized stem names the group
+            let stem = members
+                .iter()
+                .map(|&i| items[i].0.as_str())
+                .min_by_key(|s| (s.len(), *s))
+                .unwrap_or_default();
+            let key = if ext.is_empty() {
+                stem.to_string()
+            } else {
+                format!("{}.{}", stem, ext)
+            };
+
+            groups.push(SimilarNameGroup {
+                key,
+                entries: group,
+            });
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.entries
+            .len()
+            .cmp(&a.entries.len())
+            .then_with(|| a.entries[0].path.cmp(&b.entries[0].path))
+    });
+    groups
+}
+
+/// Whether two distinct normalized stems are close enough to be the same name
+///
+/// Short names need proportionally closer matches, and names that differ only
+/// in their digits (`chapter1`/`chapter2`, `IMG_0001`/`IMG_0002`) are treated
+/// as a numbered series rather than copies.
+fn names_similar(a: &str, b: &str, max_distance: usize) -> bool {
+    let without_digits = |s: &str| {
+        s.chars()
+            .filter(|c| !c.is_ascii_digit())
+            .collect::<String>()
+    };
+    if without_digits(a) == without_digits(b) {
+        return false;
+    }
+
+    let shortest = a.chars().count().min(b.chars().count());
+    let allowed = max_distance.min(shortest / 3);
+    allowed > 0 && edit_distance(a, b) <= allowed
+}
+
+/// Levenshtein distance between two strings (by chars)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    prev[b.len()]
+}
+
+/// Minimal union-find for clustering
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Path compression
+        let mut current = i;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[rb] = ra;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_entry(path: &str) -> Entry {
+        Entry {
+            size: 10,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_name_key() {
+        let normalizer = NameNormalizer::new();
+        let key = |name: &str| normalizer.key(name);
+
+        let expected = ("report".to_string(), "docx".to_string());
+        assert_eq!(key("Report (1).DOCX"), expected);
+        assert_eq!(key("report_final_v2.docx"), expected);
+        assert_eq!(key("Copy of report.docx.bak"), expected);
+        assert_eq!(key("report - Copy.docx"), expected);
+        assert_eq!(key("report copy 2.docx"), expected);
+        assert_eq!(key("report.docx~"), expected);
+        assert_eq!(key("Makefile"), ("makefile".to_string(), String::new()));
+        assert_eq!(key(".bashrc"), (".bashrc".to_string(), String::new()));
+        // A name made only of a marker is left alone
+        assert_eq!(key("final.txt"), ("final".to_string(), "txt".to_string()));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("budget", "budget"), 0);
+        assert_eq!(edit_distance("budget", "budegt"), 2);
+    }
+
+    #[test]
+    fn test_find_similar_names() {
+        let entries = vec![
+            make_entry("docs/report.docx"),
+            make_entry("docs/report (1).docx"),
+            make_entry("docs/report_final_v2.docx"),
+            make_entry("docs/reprot.docx"),
+            make_entry("docs/chapter1.md"),
+            make_entry("docs/chapter2.md"),
+            make_entry("a/mod.rs"),
+            make_entry("b/mod.rs"),
+            make_entry("other/report.docx"),
+        ];
+
+        let groups = find_similar_names(&entries, &SimilarOptions::default());
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].key, "report.docx");
+        assert_eq!(groups[0].entries.len(), 4);
+
+        // Across directories the sibling-only `other/report.docx` joins in,
+        // while identical names alone still don't form a group
+        let options = SimilarOptions {
+            across_dirs: true,
+            ..Default::default()
+        };
+        let groups = find_similar_names(&entries, &options);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].entries.len(), 5);
+    }
+}
@@ -1,14 +1,20 @@
 use crate::models::{Entry, EntryKind};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::timings;
+use crate::util::top_k_by;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
 
-/// Compute directory sizes by aggregating file sizes
+/// Compute directory sizes by aggregating file (and symlink) sizes
 pub fn compute_dir_sizes(entries: &[Entry]) -> HashMap<PathBuf, u64> {
+    let _timer = timings::phase("aggregate");
     let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
 
     // First, collect all file sizes
     for entry in entries {
-        if entry.kind == EntryKind::File {
+        if entry.kind != EntryKind::Dir {
             // Add size to file's own path
             sizes.insert(entry.path.clone(), entry.size);
 
@@ -24,10 +30,53 @@ pub fn compute_dir_sizes(entries: &[Entry]) -> HashMap<PathBuf, u64> {
     sizes
 }
 
+/// Total sizes of directories, read from disk on demand
+///
+/// For filters that judge a directory by its contents while the walk is
+/// still going. Each directory below one that was measured is remembered,
+/// so asking for a parent and then its children reads every file once.
+/// Counts files and symlinks as [`compute_dir_sizes`] does, but hidden and
+/// ignored ones too; symlinks aren't followed and unreadable directories
+/// count as empty.
+#[derive(Debug, Default)]
+pub struct DirSizeCache {
+    sizes: Mutex<HashMap<PathBuf, u64>>,
+}
+
+impl DirSizeCache {
+    /// Total size of everything under `dir`
+    pub fn size_of(&self, dir: &Path) -> u64 {
+        let cached = self
+            .sizes
+            .lock()
+            .ok()
+            .and_then(|sizes| sizes.get(dir).copied());
+        if let Some(size) = cached {
+            return size;
+        }
+
+        let mut total = 0;
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => total += self.size_of(&entry.path()),
+                Ok(_) => total += entry.metadata().map(|m| m.len()).unwrap_or(0),
+                Err(_) => {}
+            }
+        }
+        if let Ok(mut sizes) = self.sizes.lock() {
+            sizes.insert(dir.to_path_buf(), total);
+        }
+        total
+    }
+}
+
 /// Update entries with computed directory sizes
+///
+/// Symlinks are updated too: a followed link to a directory gets the total of
+/// everything walked beneath it.
 pub fn update_entries_with_dir_sizes(entries: &mut [Entry], dir_sizes: &HashMap<PathBuf, u64>) {
     for entry in entries.iter_mut() {
-        if entry.kind == EntryKind::Dir {
+        if entry.kind != EntryKind::File {
             if let Some(&size) = dir_sizes.get(&entry.path) {
                 entry.size = size;
             }
@@ -35,11 +84,193 @@ pub fn update_entries_with_dir_sizes(entries: &mut [Entry], dir_sizes: &HashMap<
     }
 }
 
-/// Get top N entries by size
+/// Attribute each symlink's target size to the link itself
+///
+/// Links to files take the target's size. Every file is counted once: a target
+/// already reached through a regular entry or an earlier link (or a hard link
+/// to it) contributes 0, so link farms and followed directory links don't
+/// inflate totals. Dangling and looping links keep their own size. Links to
+/// directories are left for [`compute_dir_sizes`], which totals whatever was
+/// walked beneath them.
+pub fn resolve_link_targets(entries: &mut [Entry]) {
+    let _timer = timings::phase("resolve links");
+    let mut seen: HashSet<FileId> = HashSet::new();
+
+    // Regular files claim their identity first, so links into the tree add nothing
+    for entry in entries.iter_mut().filter(|e| e.kind == EntryKind::File) {
+        if let Ok(metadata) = std::fs::metadata(&entry.path) {
+            if !seen.insert(file_id(&entry.path, &metadata)) {
+                entry.size = 0;
+            }
+        }
+    }
+
+    for entry in entries.iter_mut().filter(|e| e.kind == EntryKind::Symlink) {
+        match std::fs::metadata(&entry.path) {
+            Ok(target) if target.is_file() => {
+                entry.size = if seen.insert(file_id(&entry.path, &target)) {
+                    target.len()
+                } else {
+                    0
+                };
+            }
+            Ok(_) => {}
+            Err(e) => debug!(path = %entry.path.display(), error = %e, "Unresolvable symlink"),
+        }
+    }
+}
+
+/// Identity of a file independent of the path used to reach it
+#[cfg(unix)]
+type FileId = (u64, u64);
+
+#[cfg(not(unix))]
+type FileId = PathBuf;
+
+#[cfg(unix)]
+fn file_id(_path: &std::path::Path, metadata: &std::fs::Metadata) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(path: &std::path::Path, _metadata: &std::fs::Metadata) -> FileId {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Compute the newest mtime of any file below each directory
+pub fn compute_last_activity(entries: &[Entry]) -> HashMap<PathBuf, DateTime<Utc>> {
+    let _timer = timings::phase("aggregate");
+    let mut activity: HashMap<PathBuf, DateTime<Utc>> = HashMap::new();
+
+    for entry in entries.iter().filter(|e| e.kind != EntryKind::Dir) {
+        let mut current = entry.path.parent();
+        while let Some(parent) = current {
+            match activity.get_mut(parent) {
+                // Ancestors were already raised at least this far
+                Some(newest) if *newest >= entry.mtime => break,
+                Some(newest) => *newest = entry.mtime,
+                None => {
+                    activity.insert(parent.to_path_buf(), entry.mtime);
+                }
+            }
+            current = parent.parent();
+        }
+    }
+
+    activity
+}
+
+/// Set `last_activity` on directories (empty ones fall back to their own mtime)
+pub fn update_entries_with_last_activity(
+    entries: &mut [Entry],
+    activity: &HashMap<PathBuf, DateTime<Utc>>,
+) {
+    for entry in entries.iter_mut() {
+        if entry.kind == EntryKind::Dir {
+            entry.last_activity = Some(activity.get(&entry.path).copied().unwrap_or(entry.mtime));
+        }
+    }
+}
+
+/// Compute `du`-style totals for every entry path
+///
+/// Each entry contributes its own usage (allocated blocks, or the byte length
+/// when `apparent` is set) to itself and all of its ancestors. Hard-linked
+/// files are only counted once, matching `du`.
+pub fn compute_du_sizes(entries: &[Entry], apparent: bool) -> HashMap<PathBuf, u64> {
+    let _timer = timings::phase("aggregate");
+    let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
+    let mut seen_inodes = std::collections::HashSet::new();
+
+    for entry in entries {
+        let Ok(metadata) = std::fs::symlink_metadata(&entry.path) else {
+            continue;
+        };
+
+        if !record_inode(&metadata, &mut seen_inodes) {
+            continue;
+        }
+
+        let usage = if apparent {
+            metadata.len()
+        } else {
+            allocated_size(&metadata)
+        };
+
+        *sizes.entry(entry.path.clone()).or_insert(0) += usage;
+
+        let mut current = entry.path.parent();
+        while let Some(parent) = current {
+            *sizes.entry(parent.to_path_buf()).or_insert(0) += usage;
+            current = parent.parent();
+        }
+    }
+
+    sizes
+}
+
+/// Bytes actually allocated on disk for a file
+#[cfg(unix)]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+pub fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Remember a file's inode, returning false if it was already counted
+#[cfg(unix)]
+fn record_inode(
+    metadata: &std::fs::Metadata,
+    seen: &mut std::collections::HashSet<(u64, u64)>,
+) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.is_dir() || metadata.nlink() <= 1 {
+        return true;
+    }
+    seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn record_inode(
+    _metadata: &std::fs::Metadata,
+    _seen: &mut std::collections::HashSet<(u64, u64)>,
+) -> bool {
+    true
+}
+
+/// Order entries like `du` prints them: children before their parent directory
+pub fn sort_du_order(entries: &mut [Entry]) {
+    let _timer = timings::phase("sort");
+    entries.sort_by(|a, b| {
+        let mut a_parts = a.path.components();
+        let mut b_parts = b.path.components();
+        loop {
+            match (a_parts.next(), b_parts.next()) {
+                (Some(x), Some(y)) if x == y => continue,
+                (Some(x), Some(y)) => return x.cmp(&y),
+                // A descendant is printed before its ancestor
+                (Some(_), None) => return std::cmp::Ordering::Less,
+                (None, Some(_)) => return std::cmp::Ordering::Greater,
+                (None, None) => return std::cmp::Ordering::Equal,
+            }
+        }
+    });
+}
+
+/// Get top N entries by size, largest first
+///
+/// Uses a bounded heap instead of sorting, and only clones the N results.
 pub fn get_top_by_size(entries: &[Entry], n: usize) -> Vec<Entry> {
-    let mut sorted = entries.to_vec();
-    sorted.sort_by(|a, b| b.size.cmp(&a.size));
-    sorted.into_iter().take(n).collect()
+    top_k_by(entries, n, |a, b| b.size.cmp(&a.size))
+        .into_iter()
+        .cloned()
+        .collect()
 }
 
 /// Compute total size of all entries
@@ -50,27 +281,12 @@ pub fn compute_total_size(entries: &[Entry]) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     fn make_entry(path: &str, size: u64, kind: EntryKind) -> Entry {
-        use chrono::Utc;
-
-        let path_buf = PathBuf::from(path);
-        let name = path_buf
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
-
         Entry {
-            path: path_buf,
-            name,
             size,
             kind,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
+            ..Entry::test_file(path)
         }
     }
 
@@ -106,6 +322,37 @@ mod tests {
         assert_eq!(entries[1].size, 100); // File size unchanged
     }
 
+    #[test]
+    fn test_last_activity() {
+        use chrono::Duration;
+
+        let old = Utc::now() - Duration::days(400);
+        let recent = Utc::now() - Duration::days(2);
+        let with_mtime = |path: &str, kind: EntryKind, mtime: DateTime<Utc>| Entry {
+            mtime,
+            ..make_entry(path, 0, kind)
+        };
+
+        let mut entries = vec![
+            with_mtime("/root", EntryKind::Dir, recent),
+            with_mtime("/root/dead", EntryKind::Dir, recent),
+            with_mtime("/root/dead/a.txt", EntryKind::File, old),
+            with_mtime("/root/live", EntryKind::Dir, old),
+            with_mtime("/root/live/old.txt", EntryKind::File, old),
+            with_mtime("/root/live/src/new.rs", EntryKind::File, recent),
+            with_mtime("/root/empty", EntryKind::Dir, old),
+        ];
+
+        let activity = compute_last_activity(&entries);
+        update_entries_with_last_activity(&mut entries, &activity);
+
+        assert_eq!(entries[0].last_activity, Some(recent));
+        assert_eq!(entries[1].last_activity, Some(old));
+        assert_eq!(entries[3].last_activity, Some(recent));
+        assert_eq!(entries[6].last_activity, Some(old));
+        assert_eq!(entries[2].last_activity, None);
+    }
+
     #[test]
     fn test_get_top_by_size() {
         let entries = vec![
@@ -120,6 +367,69 @@ mod tests {
         assert_eq!(top[1].size, 100);
     }
 
+    #[test]
+    fn test_sort_du_order() {
+        let mut entries = vec![
+            make_entry("/root", 0, EntryKind::Dir),
+            make_entry("/root/a", 0, EntryKind::Dir),
+            make_entry("/root/a/x", 0, EntryKind::Dir),
+            make_entry("/root/b", 0, EntryKind::Dir),
+        ];
+
+        sort_du_order(&mut entries);
+
+        let paths: Vec<_> = entries
+            .iter()
+            .map(|e| e.path.display().to_string())
+            .collect();
+        assert_eq!(paths, vec!["/root/a/x", "/root/a", "/root/b", "/root"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_link_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(&file, vec![0u8; 3000]).unwrap();
+        std::fs::write(outside.path(), vec![0u8; 500]).unwrap();
+
+        let inner_link = dir.path().join("inner");
+        let outer_link = dir.path().join("outer");
+        let dangling = dir.path().join("dangling");
+        std::os::unix::fs::symlink(&file, &inner_link).unwrap();
+        std::os::unix::fs::symlink(outside.path(), &outer_link).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("missing"), &dangling).unwrap();
+
+        let mut entries = vec![
+            make_entry(inner_link.to_str().unwrap(), 10, EntryKind::Symlink),
+            make_entry(file.to_str().unwrap(), 3000, EntryKind::File),
+            make_entry(outer_link.to_str().unwrap(), 10, EntryKind::Symlink),
+            make_entry(dangling.to_str().unwrap(), 10, EntryKind::Symlink),
+        ];
+        resolve_link_targets(&mut entries);
+
+        // The in-tree target is already counted by its regular entry
+        let sizes: Vec<u64> = entries.iter().map(|e| e.size).collect();
+        assert_eq!(sizes, vec![0, 3000, 500, 10]);
+
+        let dir_sizes = compute_dir_sizes(&entries);
+        assert_eq!(dir_sizes.get(dir.path()), Some(&3510));
+    }
+
+    #[test]
+    fn test_compute_du_sizes_apparent() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        std::fs::write(&file, vec![0u8; 3000]).unwrap();
+
+        let entries = vec![make_entry(file.to_str().unwrap(), 3000, EntryKind::File)];
+
+        let sizes = compute_du_sizes(&entries, true);
+        assert_eq!(sizes.get(&file), Some(&3000));
+        assert_eq!(sizes.get(dir.path()), Some(&3000));
+    }
+
     #[test]
     fn test_compute_total_size() {
         let entries = vec![
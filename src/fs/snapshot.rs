@@ -0,0 +1,531 @@
+//! Saved filesystem snapshots for trend analysis
+//!
+//! Each snapshot is stored in `~/.local/share/fexplorer/snapshots/` as two
+//! files: `<id>.json` with the summary (root, time, description, totals and
+//! per-category counts) so listing stays cheap, and `<id>.entries.zst` with
+//! one zstd-compressed NDJSON record per entry.
+//!
+//! `watch --update-snapshot` keeps the newest snapshot of a tree current as a
+//! [`LiveSnapshot`], re-reading only the paths file events name, and saves it
+//! as a new snapshot from time to time instead of walking the whole tree.
+
+use crate::errors::{FsError, Result};
+use crate::fs::metadata::extract_entry;
+use crate::fs::traverse::{exclude_matcher, walk_no_filter, TraverseConfig};
+use crate::models::{Entry, EntryKind, FileCategory};
+use chrono::{DateTime, Utc};
+use ignore::overrides::Override;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// File count and total size of one category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryTotals {
+    pub files: u64,
+    pub size: u64,
+}
+
+/// Summary of a snapshot, without its entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMeta {
+    /// `YYYYMMDD-HHMMSS` (UTC) of creation, with a `-N` suffix if taken twice a second
+    pub id: String,
+    #[serde(with = "crate::util::serde_path")]
+    pub root: PathBuf,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `--exclude` globs the tree was walked with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    pub total_size: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// Per top-level category (`source`, `media`, ...) of files
+    #[serde(default)]
+    pub categories: BTreeMap<String, CategoryTotals>,
+}
+
+/// One entry of a snapshot, with its path relative to the root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    #[serde(with = "crate::util::serde_path")]
+    pub path: PathBuf,
+    pub size: u64,
+    pub kind: EntryKind,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub mtime: DateTime<Utc>,
+}
+
+/// Where snapshots are kept
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    /// The default store (`~/.local/share/fexplorer/snapshots`)
+    pub fn open_default() -> Result<Self> {
+        let data_dir = dirs::data_dir().ok_or_else(|| FsError::InvalidFormat {
+            format: "Could not determine data directory".to_string(),
+        })?;
+        Ok(Self::new(data_dir.join("fexplorer").join("snapshots")))
+    }
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save a snapshot of `entries`, walked from `root` leaving out `exclude`
+    pub fn create(
+        &self,
+        root: &Path,
+        entries: &[Entry],
+        exclude: &[String],
+        description: Option<String>,
+    ) -> Result<SnapshotMeta> {
+        let records: Vec<SnapshotEntry> = entries
+            .iter()
+            .map(|entry| SnapshotEntry::new(root, entry))
+            .collect();
+        self.save(root, &records, exclude, description)
+    }
+
+    /// Save a snapshot of `root` made of `records`
+    pub fn save<'a>(
+        &self,
+        root: &Path,
+        records: impl IntoIterator<Item = &'a SnapshotEntry>,
+        exclude: &[String],
+        description: Option<String>,
+    ) -> Result<SnapshotMeta> {
+        fs::create_dir_all(&self.dir).map_err(|e| io_error("create", &self.dir, e))?;
+
+        let created = Utc::now();
+        let base = created.format("%Y%m%d-%H%M%S").to_string();
+        let id = (1..)
+            .map(|n| {
+                if n == 1 {
+                    base.clone()
+                } else {
+                    format!("{}-{}", base, n)
+                }
+            })
+            .find(|id| !self.meta_path(id).exists())
+            .expect("unbounded range");
+
+        let mut meta = SnapshotMeta {
+            id,
+            root: root.to_path_buf(),
+            created,
+            description,
+            exclude: exclude.to_vec(),
+            total_size: 0,
+            files: 0,
+            dirs: 0,
+            categories: BTreeMap::new(),
+        };
+
+        let entries_path = self.entries_path(&meta.id);
+        let file =
+            fs::File::create(&entries_path).map_err(|e| io_error("create", &entries_path, e))?;
+        let mut encoder = zstd::Encoder::new(BufWriter::new(file), 3)
+            .map_err(|e| io_error("compress", &entries_path, e))?;
+
+        for record in records {
+            match record.kind {
+                EntryKind::File => {
+                    meta.files += 1;
+                    meta.total_size += record.size;
+                    let totals = meta
+                        .categories
+                        .entry(category_of(&record.path))
+                        .or_default();
+                    totals.files += 1;
+                    totals.size += record.size;
+                }
+                EntryKind::Dir => meta.dirs += 1,
+                EntryKind::Symlink => {}
+            }
+
+            serde_json::to_writer(&mut encoder, record)?;
+            encoder
+                .write_all(b"\n")
+                .map_err(|e| io_error("write", &entries_path, e))?;
+        }
+        encoder
+            .finish()
+            .and_then(|mut writer| writer.flush())
+            .map_err(|e| io_error("write", &entries_path, e))?;
+
+        // The summary is written last, so a listed snapshot is always complete
+        let meta_path = self.meta_path(&meta.id);
+        fs::write(&meta_path, serde_json::to_string_pretty(&meta)?)
+            .map_err(|e| io_error("write", &meta_path, e))?;
+        Ok(meta)
+    }
+
+    /// All snapshots, oldest first; `root` limits them to one snapshotted path
+    pub fn list(&self, root: Option<&Path>) -> Result<Vec<SnapshotMeta>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error("read", &self.dir, e)),
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let data = fs::read_to_string(&path).map_err(|e| io_error("read", &path, e))?;
+            let meta: SnapshotMeta = serde_json::from_str(&data)?;
+            if root.is_none_or(|root| meta.root == root) {
+                snapshots.push(meta);
+            }
+        }
+        snapshots.sort_by(|a, b| a.created.cmp(&b.created).then_with(|| a.id.cmp(&b.id)));
+        Ok(snapshots)
+    }
+
+    /// Summary of one snapshot
+    pub fn get(&self, id: &str) -> Result<SnapshotMeta> {
+        check_id(id)?;
+        let path = self.meta_path(id);
+        let data = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => unknown_snapshot(id),
+            _ => io_error("read", &path, e),
+        })?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Entries of a snapshot, paths relative to its root
+    pub fn entries(&self, id: &str) -> Result<Vec<SnapshotEntry>> {
+        check_id(id)?;
+        let path = self.entries_path(id);
+        let file = fs::File::open(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => unknown_snapshot(id),
+            _ => io_error("read", &path, e),
+        })?;
+        let decoder = zstd::Decoder::new(file).map_err(|e| io_error("decompress", &path, e))?;
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(decoder).lines() {
+            let line = line.map_err(|e| io_error("decompress", &path, e))?;
+            if !line.is_empty() {
+                entries.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Remove a snapshot
+    pub fn delete(&self, id: &str) -> Result<()> {
+        check_id(id)?;
+        let meta_path = self.meta_path(id);
+        if !meta_path.is_file() {
+            return Err(unknown_snapshot(id));
+        }
+        fs::remove_file(&meta_path).map_err(|e| io_error("remove", &meta_path, e))?;
+
+        let entries_path = self.entries_path(id);
+        match fs::remove_file(&entries_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(io_error("remove", &entries_path, e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn meta_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn entries_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.entries.zst", id))
+    }
+}
+
+impl SnapshotEntry {
+    /// Record of `entry`, walked from `root`
+    fn new(root: &Path, entry: &Entry) -> Self {
+        Self {
+            path: entry
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&entry.path)
+                .to_path_buf(),
+            size: entry.size,
+            kind: entry.kind,
+            mtime: entry.mtime,
+        }
+    }
+}
+
+/// A snapshot kept current from file events (`watch --update-snapshot`)
+#[derive(Debug, Clone)]
+pub struct LiveSnapshot {
+    root: PathBuf,
+    /// By path relative to the root; a directory's contents follow it
+    entries: BTreeMap<PathBuf, SnapshotEntry>,
+    /// The snapshot's `--exclude` globs, kept out of it as it changes
+    exclude: Vec<String>,
+    excludes: Option<Override>,
+}
+
+impl LiveSnapshot {
+    /// The snapshot `meta` as stored, to be updated from there
+    pub fn load(store: &SnapshotStore, meta: &SnapshotMeta) -> Result<Self> {
+        let entries = store
+            .entries(&meta.id)?
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+        let excludes = if meta.exclude.is_empty() {
+            None
+        } else {
+            Some(exclude_matcher(&meta.root, &meta.exclude)?)
+        };
+        Ok(Self {
+            root: meta.root.clone(),
+            entries,
+            exclude: meta.exclude.clone(),
+            excludes,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `--exclude` globs the snapshot was taken with
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
+    }
+
+    /// Entries in path order, paths relative to the root
+    pub fn entries(&self) -> impl Iterator<Item = &SnapshotEntry> {
+        self.entries.values()
+    }
+
+    /// Re-read `path` after an event: gone paths are dropped along with what
+    /// was under them, directories new to the snapshot are walked with
+    /// `config`, anything else is stat'ed again. Excluded paths are ignored.
+    pub fn refresh(&mut self, path: &Path, config: &TraverseConfig) {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return;
+        };
+        if relative.as_os_str().is_empty() || self.is_excluded(relative, path.is_dir()) {
+            return;
+        }
+        let Ok(entry) = extract_entry(path, 0) else {
+            let gone: Vec<PathBuf> = self
+                .entries
+                .range(relative.to_path_buf()..)
+                .take_while(|(p, _)| p.starts_with(relative))
+                .map(|(p, _)| p.clone())
+                .collect();
+            for p in gone {
+                self.entries.remove(&p);
+            }
+            return;
+        };
+
+        let known = self.entries.contains_key(relative);
+        if entry.kind == EntryKind::Dir && !known {
+            // Moved in or created with contents the events may not name
+            if let Ok(walked) = walk_no_filter(path, config) {
+                for entry in &walked {
+                    let record = SnapshotEntry::new(&self.root, entry);
+                    // The walk only knows the globs relative to `path`
+                    if !self.is_excluded(&record.path, entry.kind == EntryKind::Dir) {
+                        self.entries.insert(record.path.clone(), record);
+                    }
+                }
+            }
+        }
+        let record = SnapshotEntry::new(&self.root, &entry);
+        self.entries.insert(record.path.clone(), record);
+    }
+
+    /// Whether `relative`, or a directory above it, matches an exclude glob
+    fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        let Some(excludes) = &self.excludes else {
+            return false;
+        };
+        let mut prefix = PathBuf::new();
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            prefix.push(component);
+            let prefix_is_dir = components.peek().is_some() || is_dir;
+            if excludes.matched(&prefix, prefix_is_dir).is_ignore() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Top-level category of a file (`source`, `media`, `unknown`, ...)
+fn category_of(path: &Path) -> String {
+    let category = path
+        .extension()
+        .map(|ext| FileCategory::from_extension(&ext.to_string_lossy()))
+        .unwrap_or(FileCategory::Unknown);
+    let label = category.label();
+    label.split('/').next().unwrap_or("unknown").to_string()
+}
+
+/// Ids name files in the store, so one must be a plain file name: no
+/// separators, `.` or `..` that would reach outside it
+fn check_id(id: &str) -> Result<()> {
+    let mut components = Path::new(id).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => Err(FsError::InvalidFormat {
+            format: format!("Invalid snapshot id: {}", id),
+        }),
+    }
+}
+
+fn unknown_snapshot(id: &str) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("No snapshot with id {} (see `fexplorer snapshot list`)", id),
+    }
+}
+
+fn io_error(action: &str, path: &Path, source: std::io::Error) -> FsError {
+    FsError::IoError {
+        context: format!("Failed to {} {}", action, path.display()),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::traverse::{walk_no_filter, TraverseConfig};
+
+    #[test]
+    fn test_create_list_read_delete() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("logo.png"), vec![0u8; 100]).unwrap();
+
+        let store = SnapshotStore::new(temp.path().join("snapshots"));
+        assert!(store.list(None).unwrap().is_empty());
+
+        let entries = walk_no_filter(&root, &TraverseConfig::default()).unwrap();
+        let first = store
+            .create(&root, &entries, &[], Some("before cleanup".to_string()))
+            .unwrap();
+        let second = store.create(&root, &entries, &[], None).unwrap();
+        assert_ne!(first.id, second.id);
+
+        assert_eq!((first.files, first.dirs, first.total_size), (2, 2, 112));
+        assert_eq!(
+            first.categories["media"],
+            CategoryTotals {
+                files: 1,
+                size: 100
+            }
+        );
+        assert_eq!(first.categories["source"].size, 12);
+
+        let listed = store.list(Some(&root)).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].description.as_deref(), Some("before cleanup"));
+        assert!(store
+            .list(Some(Path::new("/elsewhere")))
+            .unwrap()
+            .is_empty());
+
+        let saved = store.entries(&first.id).unwrap();
+        assert_eq!(saved.len(), entries.len());
+        assert!(saved
+            .iter()
+            .any(|e| e.path == Path::new("src/main.rs") && e.size == 12));
+
+        store.delete(&first.id).unwrap();
+        assert_eq!(store.list(None).unwrap().len(), 1);
+        assert!(store.get(&first.id).is_err());
+        assert!(store.delete(&first.id).is_err());
+
+        // Ids can't reach outside the store
+        fs::write(temp.path().join("outside.json"), "{}").unwrap();
+        for id in ["../outside", "..", ".", "", "a/b"] {
+            assert!(store.delete(id).is_err(), "{}", id);
+            assert!(store.entries(id).is_err(), "{}", id);
+        }
+        assert!(temp.path().join("outside.json").exists());
+    }
+
+    #[test]
+    fn test_live_snapshot() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("notes.txt"), "todo").unwrap();
+
+        let store = SnapshotStore::new(temp.path().join("snapshots"));
+        let exclude = vec!["node_modules".to_string(), "/lib/gen".to_string()];
+        let config = TraverseConfig {
+            exclude: exclude.clone(),
+            ..Default::default()
+        };
+        let entries = walk_no_filter(&root, &config).unwrap();
+        let meta = store.create(&root, &entries, &exclude, None).unwrap();
+        assert_eq!(store.get(&meta.id).unwrap().exclude, exclude);
+        let mut live = LiveSnapshot::load(&store, &meta).unwrap();
+
+        // A file grows, a directory disappears, one with contents appears
+        fs::write(root.join("notes.txt"), "todo: more").unwrap();
+        fs::remove_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("lib/deep")).unwrap();
+        fs::write(root.join("lib/deep/a.rs"), "pub fn a() {}").unwrap();
+        // Excluded trees stay out, whether named by an event or walked into
+        fs::create_dir_all(root.join("lib/gen")).unwrap();
+        fs::write(root.join("lib/gen/b.rs"), "pub fn b() {}").unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+        for path in [
+            "notes.txt",
+            "src",
+            "src/main.rs",
+            "lib",
+            "node_modules",
+            "node_modules/pkg/index.js",
+        ] {
+            live.refresh(&root.join(path), &config);
+        }
+
+        let paths: Vec<&Path> = live.entries().map(|e| e.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new(""),
+                Path::new("lib"),
+                Path::new("lib/deep"),
+                Path::new("lib/deep/a.rs"),
+                Path::new("notes.txt"),
+            ]
+        );
+        let updated = store
+            .save(live.root(), live.entries(), live.exclude(), None)
+            .unwrap();
+        assert_eq!((updated.files, updated.total_size), (2, 23));
+        assert_ne!(updated.id, meta.id);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::errors::{FsError, Result};
+use crate::fs::batch::{BatchChange, BatchReport};
+use crate::models::{Entry, EntryKind};
+use chrono::{DateTime, Local, Utc};
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of an applied `touch` run, used by `touch --undo`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchJournal {
+    pub created: DateTime<Utc>,
+    pub entries: Vec<JournalEntry>,
+}
+
+/// A single mtime change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub path: PathBuf,
+    pub old_mtime: DateTime<Utc>,
+    pub new_mtime: DateTime<Utc>,
+}
+
+impl TouchJournal {
+    pub fn new() -> Self {
+        Self {
+            created: Utc::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Write the journal to the journal directory and return its path
+    pub fn save(&self) -> Result<PathBuf> {
+        let dir = Self::journal_dir()?;
+        fs::create_dir_all(&dir).map_err(|e| FsError::PathAccess {
+            path: dir.clone(),
+            source: e,
+        })?;
+
+        let name = format!("touch-{}.json", self.created.format("%Y%m%d-%H%M%S%.3f"));
+        let path = dir.join(name);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).map_err(|e| FsError::PathAccess {
+            path: path.clone(),
+            source: e,
+        })?;
+
+        Ok(path)
+    }
+
+    /// Load a journal file
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path).map_err(|e| FsError::PathAccess {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+
+        serde_json::from_str(&data).map_err(|e| FsError::InvalidFormat {
+            format: format!("Invalid touch journal {}: {}", path.display(), e),
+        })
+    }
+
+    /// Most recently written journal, if any
+    pub fn latest() -> Result<Option<PathBuf>> {
+        let dir = Self::journal_dir()?;
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            return Ok(None);
+        };
+
+        // Names embed the timestamp, so the lexicographically last is the newest
+        Ok(read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("touch-") && n.ends_with(".json"))
+            })
+            .max())
+    }
+
+    /// Journal directory (~/.cache/fexplorer/journal)
+    pub fn journal_dir() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir().ok_or_else(|| FsError::InvalidFormat {
+            format: "Could not determine cache directory".to_string(),
+        })?;
+
+        Ok(cache_dir.join("fexplorer").join("journal"))
+    }
+}
+
+impl Default for TouchJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-entry mtime chooser: the new time, or a reason to skip the entry
+pub type MtimeSource = Box<dyn FnMut(&Entry) -> std::result::Result<DateTime<Utc>, String>>;
+
+/// Set each entry's mtime to the time chosen by `target`
+///
+/// `target` returns the desired mtime, or a reason to skip the entry (e.g. no
+/// EXIF data). Symlinks are always skipped. The journal lists applied changes.
+pub fn touch_entries<F>(
+    entries: &[Entry],
+    mut target: F,
+    dry_run: bool,
+) -> (BatchReport, TouchJournal)
+where
+    F: FnMut(&Entry) -> std::result::Result<DateTime<Utc>, String>,
+{
+    let mut report = BatchReport::default();
+    let mut journal = TouchJournal::new();
+
+    for entry in entries {
+        if entry.kind == EntryKind::Symlink {
+            report
+                .skipped
+                .push((entry.path.clone(), "symlink".to_string()));
+            continue;
+        }
+
+        let new_mtime = match target(entry) {
+            Ok(time) => time,
+            Err(reason) => {
+                report.skipped.push((entry.path.clone(), reason));
+                continue;
+            }
+        };
+
+        match set_mtime(&entry.path, new_mtime, dry_run) {
+            Ok(None) => report.unchanged += 1,
+            Ok(Some(old_mtime)) => {
+                report
+                    .changed
+                    .push(mtime_change(&entry.path, old_mtime, new_mtime));
+                journal.entries.push(JournalEntry {
+                    path: entry.path.clone(),
+                    old_mtime,
+                    new_mtime,
+                });
+            }
+            Err(e) => report.errors.push((entry.path.clone(), e.to_string())),
+        }
+    }
+
+    (report, journal)
+}
+
+/// Restore the mtimes recorded in a journal
+///
+/// Files whose mtime changed again after the journaled run are left alone.
+pub fn undo_touch(journal: &TouchJournal, dry_run: bool) -> BatchReport {
+    let mut report = BatchReport::default();
+
+    for item in &journal.entries {
+        match current_mtime(&item.path) {
+            Ok(current) if current != item.new_mtime => {
+                report
+                    .skipped
+                    .push((item.path.clone(), "modified since touch".to_string()));
+            }
+            Ok(_) => match set_mtime(&item.path, item.old_mtime, dry_run) {
+                Ok(None) => report.unchanged += 1,
+                Ok(Some(current)) => {
+                    report
+                        .changed
+                        .push(mtime_change(&item.path, current, item.old_mtime));
+                }
+                Err(e) => report.errors.push((item.path.clone(), e.to_string())),
+            },
+            Err(e) => report.errors.push((item.path.clone(), e.to_string())),
+        }
+    }
+
+    report
+}
+
+/// Set a file's mtime (keeping atime); returns the previous mtime if it changed
+fn set_mtime(path: &Path, mtime: DateTime<Utc>, dry_run: bool) -> Result<Option<DateTime<Utc>>> {
+    let old = current_mtime(path)?;
+    if old == mtime {
+        return Ok(None);
+    }
+
+    if !dry_run {
+        let time = FileTime::from_unix_time(mtime.timestamp(), mtime.timestamp_subsec_nanos());
+        filetime::set_file_mtime(path, time).map_err(|e| FsError::PathAccess {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    }
+
+    Ok(Some(old))
+}
+
+fn current_mtime(path: &Path) -> Result<DateTime<Utc>> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| FsError::PathAccess {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let mtime = FileTime::from_last_modification_time(&metadata);
+
+    DateTime::from_timestamp(mtime.unix_seconds(), mtime.nanoseconds()).ok_or_else(|| {
+        FsError::InvalidDate {
+            input: format!("{:?}", mtime),
+        }
+    })
+}
+
+fn mtime_change(path: &Path, from: DateTime<Utc>, to: DateTime<Utc>) -> BatchChange {
+    let format = |t: DateTime<Utc>| {
+        t.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+    BatchChange {
+        path: path.to_path_buf(),
+        from: format(from),
+        to: format(to),
+    }
+}
+
+/// Capture time from a photo's EXIF data (DateTimeOriginal, falling back to DateTime)
+///
+/// EXIF times are local wall-clock times; an OffsetTimeOriginal tag is used when
+/// present, otherwise the local timezone is assumed.
+#[cfg(feature = "exif")]
+pub fn exif_datetime(path: &Path) -> std::result::Result<DateTime<Utc>, String> {
+    use chrono::{FixedOffset, NaiveDateTime, TimeZone};
+    use exif::{In, Tag, Value};
+
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut std::io::BufReader::new(file))
+        .map_err(|_| "no EXIF data".to_string())?;
+
+    let ascii = |tag: Tag| {
+        exif.get_field(tag, In::PRIMARY)
+            .and_then(|field| match &field.value {
+                Value::Ascii(values) => values
+                    .first()
+                    .map(|v| String::from_utf8_lossy(v).into_owned()),
+                _ => None,
+            })
+    };
+
+    let raw = ascii(Tag::DateTimeOriginal)
+        .or_else(|| ascii(Tag::DateTime))
+        .ok_or_else(|| "no EXIF capture time".to_string())?;
+    let naive = NaiveDateTime::parse_from_str(raw.trim(), "%Y:%m:%d %H:%M:%S")
+        .map_err(|_| format!("invalid EXIF time '{}'", raw))?;
+
+    let offset = ascii(Tag::OffsetTimeOriginal).and_then(|o| {
+        DateTime::parse_from_rfc3339(&format!("2000-01-01T00:00:00{}", o.trim()))
+            .ok()
+            .map(|dt| *dt.offset())
+    });
+
+    let time = match offset {
+        Some(offset) => FixedOffset::from_offset(&offset)
+            .from_local_datetime(&naive)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    };
+
+    time.ok_or_else(|| format!("invalid EXIF time '{}'", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_touch_and_undo() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        fs::write(&path, "data").unwrap();
+        let original = current_mtime(&path).unwrap();
+        let entries = vec![extract_entry(&path, 0).unwrap()];
+        let target = Utc.with_ymd_and_hms(2020, 5, 17, 10, 30, 0).unwrap();
+
+        let (report, _) = touch_entries(&entries, |_| Ok(target), true);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(current_mtime(&path).unwrap(), original);
+
+        let (report, journal) = touch_entries(&entries, |_| Ok(target), false);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(journal.entries[0].old_mtime, original);
+        assert_eq!(current_mtime(&path).unwrap(), target);
+
+        let (report, _) = touch_entries(&entries, |_| Ok(target), false);
+        assert_eq!(report.unchanged, 1);
+
+        let report = undo_touch(&journal, false);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(current_mtime(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_touch_skips_with_reason() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "data").unwrap();
+        let entries = vec![extract_entry(&path, 0).unwrap()];
+
+        let (report, journal) = touch_entries(&entries, |_| Err("no EXIF data".to_string()), false);
+        assert_eq!(report.skipped[0].1, "no EXIF data");
+        assert!(journal.entries.is_empty());
+    }
+
+    #[test]
+    fn test_undo_skips_modified_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "data").unwrap();
+
+        let journal = TouchJournal {
+            created: Utc::now(),
+            entries: vec![JournalEntry {
+                path: path.clone(),
+                old_mtime: Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap(),
+                new_mtime: Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap(),
+            }],
+        };
+
+        let report = undo_touch(&journal, false);
+        assert_eq!(report.skipped.len(), 1);
+    }
+}
@@ -0,0 +1,396 @@
+//! Moving files to the platform trash
+//!
+//! Commands that delete (`findx -delete`, `pack --remove`, `dev-caches
+//! --clean`, the TUI) trash by default and only unlink with `--permanent`.
+//! On Linux and the BSDs this follows the Freedesktop trash spec: the home
+//! trash under `$XDG_DATA_HOME/Trash`, or `$topdir/.Trash-$uid` for files on
+//! another filesystem. macOS uses `~/.Trash` and `/Volumes/<name>/.Trashes`;
+//! Windows hands the path to the Recycle Bin through PowerShell.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A path that was moved to the trash
+#[derive(Debug, Clone)]
+pub struct Trashed {
+    /// Where the path was
+    pub original: PathBuf,
+    /// Where it is now, when the platform tells (not on Windows)
+    pub location: Option<PathBuf>,
+    /// Freedesktop `.trashinfo` file
+    info: Option<PathBuf>,
+}
+
+impl Trashed {
+    /// Move the path back where it came from
+    pub fn restore(&self) -> io::Result<()> {
+        let location = self.location.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "restore it from the Recycle Bin",
+            )
+        })?;
+        if self.original.symlink_metadata().is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", self.original.display()),
+            ));
+        }
+        fs::rename(location, &self.original)?;
+        if let Some(info) = &self.info {
+            let _ = fs::remove_file(info);
+        }
+        Ok(())
+    }
+}
+
+/// Remove `path`: to the trash, or for good when `permanent`
+///
+/// Directories are removed with their contents either way.
+pub fn remove(path: &Path, permanent: bool) -> io::Result<()> {
+    if !permanent {
+        return trash(path).map(|_| ());
+    }
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Where trashed paths and their info files go
+struct TrashDir {
+    files: PathBuf,
+    /// Freedesktop info directory; macOS keeps no record
+    info: Option<PathBuf>,
+    /// Mount point that info paths are relative to, for a per-volume trash
+    topdir: Option<PathBuf>,
+}
+
+/// Move `path` to the trash
+#[cfg(unix)]
+pub fn trash(path: &Path) -> io::Result<Trashed> {
+    let path = std::path::absolute(path)?;
+    path.symlink_metadata()?;
+    let dir = trash_dir(&path)?;
+    trash_into(&path, &dir)
+}
+
+/// Move `path` to the Recycle Bin
+#[cfg(windows)]
+pub fn trash(path: &Path) -> io::Result<Trashed> {
+    let path = std::path::absolute(path)?;
+    let method = if path.symlink_metadata()?.is_dir() {
+        "DeleteDirectory"
+    } else {
+        "DeleteFile"
+    };
+    let script = format!(
+        "Add-Type -AssemblyName Microsoft.VisualBasic; \
+         [Microsoft.VisualBasic.FileIO.FileSystem]::{}('{}', 'OnlyErrorDialogs', 'SendToRecycleBin')",
+        method,
+        path.display().to_string().replace('\'', "''")
+    );
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "powershell exited with {}",
+            status
+        )));
+    }
+    Ok(Trashed {
+        original: path,
+        location: None,
+        info: None,
+    })
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn trash(_path: &Path) -> io::Result<Trashed> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no trash on this platform; use --permanent",
+    ))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_dir(path: &Path) -> io::Result<TrashDir> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let home = dirs::data_dir()
+        .ok_or_else(|| io::Error::other("no data directory for the trash"))?
+        .join("Trash");
+    let device = device_of(path)?;
+    if device_of(&home)? == device {
+        return Ok(TrashDir {
+            files: home.join("files"),
+            info: Some(home.join("info")),
+            topdir: None,
+        });
+    }
+
+    // A file on another filesystem goes to that filesystem's trash: the
+    // shared `$topdir/.Trash/$uid` when an admin set it up, else `.Trash-$uid`
+    let topdir = mount_point(path, device);
+    let uid = unsafe { libc::getuid() };
+    let shared = topdir.join(".Trash");
+    let sticky = shared
+        .symlink_metadata()
+        .is_ok_and(|m| m.is_dir() && m.permissions().mode() & 0o1000 != 0 && m.dev() == device);
+    let trash = if sticky {
+        shared.join(uid.to_string())
+    } else {
+        topdir.join(format!(".Trash-{}", uid))
+    };
+    Ok(TrashDir {
+        files: trash.join("files"),
+        info: Some(trash.join("info")),
+        topdir: Some(topdir),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn trash_dir(path: &Path) -> io::Result<TrashDir> {
+    let home =
+        dirs::home_dir().ok_or_else(|| io::Error::other("no home directory for the trash"))?;
+    let device = device_of(path)?;
+    let files = if device_of(&home)? == device {
+        home.join(".Trash")
+    } else {
+        let uid = unsafe { libc::getuid() };
+        mount_point(path, device)
+            .join(".Trashes")
+            .join(uid.to_string())
+    };
+    Ok(TrashDir {
+        files,
+        info: None,
+        topdir: None,
+    })
+}
+
+/// Device of `path`, or of its nearest existing ancestor
+#[cfg(unix)]
+fn device_of(path: &Path) -> io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    // The entry itself may be a symlink to elsewhere; its directory decides
+    let mut dir = path.parent().unwrap_or(path);
+    loop {
+        match fs::metadata(dir) {
+            Ok(metadata) => return Ok(metadata.dev()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => match dir.parent() {
+                Some(parent) => dir = parent,
+                None => return Err(e),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Topmost ancestor of `path` on `device`
+#[cfg(unix)]
+fn mount_point(path: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| fs::metadata(dir).is_ok_and(|m| m.dev() == device))
+        .last()
+        .unwrap_or(Path::new("/"))
+        .to_path_buf()
+}
+
+/// Move `source` (absolute) into `dir` under a free name
+///
+/// A name taken by an earlier trashing gets a `.1`, `.2`, ... suffix.
+fn trash_into(source: &Path, dir: &TrashDir) -> io::Result<Trashed> {
+    if let Some(info) = &dir.info {
+        // A Freedesktop trash belongs to one user, and others must not read it
+        create_private_dir(dir.files.parent().unwrap_or(&dir.files))?;
+        fs::create_dir_all(info)?;
+    }
+    fs::create_dir_all(&dir.files)?;
+
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+    // Claim a free name by creating its info file first
+    let (location, info_file) = (0..)
+        .map(|n| {
+            let mut candidate = name.to_os_string();
+            if n > 0 {
+                candidate.push(format!(".{}", n));
+            }
+            candidate
+        })
+        .find_map(|candidate| {
+            let location = dir.files.join(&candidate);
+            let Some(info) = &dir.info else {
+                return (location.symlink_metadata().is_err()).then_some(Ok((location, None)));
+            };
+            let mut info_name = candidate;
+            info_name.push(".trashinfo");
+            let info_file = info.join(info_name);
+            let created = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_file);
+            match created {
+                Ok(_) if location.symlink_metadata().is_err() => {
+                    Some(Ok((location, Some(info_file))))
+                }
+                Ok(_) => {
+                    let _ = fs::remove_file(&info_file);
+                    None
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => None,
+                Err(e) => Some(Err(e)),
+            }
+        })
+        .expect("unbounded candidate names")?;
+
+    let result = match &info_file {
+        Some(info_file) => {
+            let original = match &dir.topdir {
+                Some(topdir) => source.strip_prefix(topdir).unwrap_or(source),
+                None => source,
+            };
+            fs::write(info_file, trash_info(original))
+        }
+        None => Ok(()),
+    }
+    .and_then(|()| fs::rename(source, &location));
+    if let Err(e) = result {
+        if let Some(info_file) = &info_file {
+            let _ = fs::remove_file(info_file);
+        }
+        return Err(e);
+    }
+
+    Ok(Trashed {
+        original: source.to_path_buf(),
+        location: Some(location),
+        info: info_file,
+    })
+}
+
+/// Create `dir`, and any missing parents, with only its owner allowed in
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    match builder.create(dir) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists && dir.is_dir() => Ok(()),
+        result => result,
+    }
+}
+
+/// Contents of a `.trashinfo` file, with the path's raw bytes percent-encoded
+fn trash_info(original: &Path) -> String {
+    let mut path = String::new();
+    for &byte in original.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                path.push(byte as char)
+            }
+            _ => path.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        path,
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir {
+            files: dir.path().join("Trash/files"),
+            info: Some(dir.path().join("Trash/info")),
+            topdir: None,
+        };
+        let file = dir.path().join("my notes.txt");
+
+        let mut trashed = Vec::new();
+        for _ in 0..2 {
+            fs::write(&file, "notes").unwrap();
+            trashed.push(trash_into(&file, &trash).unwrap());
+            assert!(!file.exists());
+        }
+        let location = |t: &Trashed| t.location.clone().unwrap();
+        assert_eq!(location(&trashed[0]), trash.files.join("my notes.txt"));
+        assert_eq!(location(&trashed[1]), trash.files.join("my notes.txt.1"));
+
+        let info_file = trashed[1].info.clone().unwrap();
+        let info = fs::read_to_string(&info_file).unwrap();
+        assert!(info.starts_with("[Trash Info]\nPath=/"), "{}", info);
+        assert!(info.contains("/my%20notes.txt\nDeletionDate="), "{}", info);
+
+        trashed[1].restore().unwrap();
+        assert!(file.exists());
+        assert!(!info_file.exists());
+        // The first one can't go back over it
+        assert!(trashed[0].restore().is_err());
+    }
+
+    #[test]
+    fn test_trash_relative_to_topdir() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = TrashDir {
+            files: dir.path().join(".Trash-1000/files"),
+            info: None,
+            topdir: Some(dir.path().to_path_buf()),
+        };
+        fs::create_dir(dir.path().join("cache")).unwrap();
+        fs::write(dir.path().join("cache/blob"), "x").unwrap();
+
+        let trashed = trash_into(&dir.path().join("cache"), &trash).unwrap();
+        assert!(trash.files.join("cache/blob").exists());
+        assert!(trashed.info.is_none());
+        assert!(remove(&dir.path().join("nothing"), true).is_err());
+
+        let info = trash_info(Path::new("cache"));
+        assert!(info.contains("\nPath=cache\n"), "{}", info);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join(".Trash-1000");
+        let trash = TrashDir {
+            files: root.join("files"),
+            info: Some(root.join("info")),
+            topdir: Some(dir.path().to_path_buf()),
+        };
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        fs::write(dir.path().join(name), "x").unwrap();
+
+        let trashed = trash_into(&dir.path().join(name), &trash).unwrap();
+        assert_eq!(trashed.location, Some(trash.files.join(name)));
+        let info = fs::read_to_string(trashed.info.unwrap()).unwrap();
+        assert!(info.contains("\nPath=caf%E9.txt\n"), "{}", info);
+        let mode = fs::metadata(&root).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
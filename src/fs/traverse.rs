@@ -1,9 +1,20 @@
-use crate::errors::Result;
+use crate::errors::{FsError, Result};
+#[cfg(feature = "cache")]
+use crate::fs::cache::MetadataCache;
+use crate::fs::enrich::TypeDetection;
 use crate::fs::filters::Predicate;
 use crate::fs::metadata::extract_entry;
-use crate::models::Entry;
+use crate::i18n::{tr, trf, Msg};
+use crate::models::{Entry, EntryKind};
+use crate::timings;
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "cache")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info_span, warn};
 
 /// Configuration for filesystem traversal
 #[derive(Debug, Clone)]
@@ -13,7 +24,19 @@ pub struct TraverseConfig {
     pub include_hidden: bool,
     pub respect_gitignore: bool,
     pub threads: usize,
+    /// With more than one thread, keep entries in the order the threads
+    /// found them instead of sorting them by path
+    pub unordered: bool,
     pub quiet: bool,
+    /// Skip home directories (under `/home` or `/Users`) owned by other users
+    pub skip_other_users: bool,
+    /// Gitignore-style globs pruned during the walk (`node_modules`, `*.log`)
+    pub exclude: Vec<String>,
+    /// Where file types come from, for type filters and columns
+    pub type_detection: TypeDetection,
+    /// Reuse the metadata of files in directories unchanged since last time
+    #[cfg(feature = "cache")]
+    pub cache: Option<Arc<MetadataCache>>,
 }
 
 impl Default for TraverseConfig {
@@ -24,7 +47,13 @@ impl Default for TraverseConfig {
             include_hidden: false,
             respect_gitignore: true,
             threads: 1,
+            unordered: false,
             quiet: false,
+            skip_other_users: false,
+            exclude: Vec::new(),
+            type_detection: TypeDetection::Extension,
+            #[cfg(feature = "cache")]
+            cache: None,
         }
     }
 }
@@ -34,58 +63,91 @@ pub fn walk<P>(root: &Path, config: &TraverseConfig, predicate: Option<&P>) -> R
 where
     P: Predicate + ?Sized,
 {
-    let mut builder = WalkBuilder::new(root);
-
-    builder
-        .follow_links(config.follow_symlinks)
-        .hidden(!config.include_hidden)
-        .git_ignore(config.respect_gitignore)
-        .git_exclude(config.respect_gitignore);
-
-    if let Some(depth) = config.max_depth {
-        builder.max_depth(Some(depth));
+    #[cfg(feature = "parallel")]
+    if config.threads > 1 {
+        return walk_parallel(root, config, predicate);
     }
 
+    let _span = info_span!("walk", root = %root.display()).entered();
+    let start = Instant::now();
+    let mut filter_time = Duration::ZERO;
     let mut entries = Vec::new();
 
-    for result in builder.build() {
+    for result in walk_builder(root, config)?.build() {
         match result {
             Ok(dir_entry) => {
-                let path = dir_entry.path();
-                let depth = dir_entry.depth();
-
-                match extract_entry(path, depth) {
-                    Ok(entry) => {
-                        // Apply predicate filter if provided
-                        if let Some(pred) = predicate {
-                            if pred.test(&entry) {
-                                entries.push(entry);
-                            }
-                        } else {
-                            entries.push(entry);
-                        }
-                    }
-                    Err(e) => {
-                        // Log error but continue traversal
-                        if !config.quiet {
-                            eprintln!("Warning: Failed to extract entry for {:?}: {}", path, e);
-                        }
+                let entry = walked_entry(&dir_entry, config);
+
+                // Apply predicate filter if provided
+                if let Some(pred) = predicate {
+                    if timings::measure(&mut filter_time, || pred.test(&entry)) {
+                        entries.push(entry);
                     }
+                } else {
+                    entries.push(entry);
                 }
             }
-            Err(e) => {
-                if !config.quiet {
-                    eprintln!("Warning: Error during traversal: {}", e);
-                }
-            }
+            Err(e) => record_walk_error(&mut entries, &e, predicate, config),
         }
     }
 
+    #[cfg(feature = "cache")]
+    save_cache(config);
+    timings::record("walk", start.elapsed().saturating_sub(filter_time));
+    if predicate.is_some() {
+        timings::record("filter", filter_time);
+    }
+
+    debug!(entries = entries.len(), "Traversal complete");
     Ok(entries)
 }
 
 /// Walk a directory tree without filtering (convenience function)
 pub fn walk_no_filter(root: &Path, config: &TraverseConfig) -> Result<Vec<Entry>> {
+    #[cfg(feature = "parallel")]
+    if config.threads > 1 {
+        return walk_parallel::<dyn Predicate>(root, config, None);
+    }
+
+    let _span = info_span!("walk", root = %root.display()).entered();
+    let _timer = timings::phase("walk");
+    let mut entries = Vec::new();
+
+    for result in walk_builder(root, config)?.build() {
+        match result {
+            Ok(dir_entry) => entries.push(walked_entry(&dir_entry, config)),
+            Err(e) => record_walk_error::<dyn Predicate>(&mut entries, &e, None, config),
+        }
+    }
+    #[cfg(feature = "cache")]
+    save_cache(config);
+
+    debug!(entries = entries.len(), "Traversal complete");
+    Ok(entries)
+}
+
+/// Matcher for the `--exclude` globs under `root`; excluded paths match as
+/// ignored
+pub(crate) fn exclude_matcher(root: &Path, patterns: &[String]) -> Result<Override> {
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in patterns {
+        globset::Glob::new(pattern).map_err(|source| FsError::InvalidGlob {
+            pattern: pattern.clone(),
+            source,
+        })?;
+        overrides
+            .add(&format!("!{}", pattern))
+            .map_err(|e| FsError::InvalidFormat {
+                format: format!("Invalid exclude pattern {}: {}", pattern, e),
+            })?;
+    }
+    overrides.build().map_err(|e| FsError::InvalidFormat {
+        format: format!("Invalid exclude patterns: {}", e),
+    })
+}
+
+/// Walker for `root` with the traversal options applied
+fn walk_builder(root: &Path, config: &TraverseConfig) -> Result<WalkBuilder> {
     let mut builder = WalkBuilder::new(root);
 
     builder
@@ -98,29 +160,365 @@ pub fn walk_no_filter(root: &Path, config: &TraverseConfig) -> Result<Vec<Entry>
         builder.max_depth(Some(depth));
     }
 
+    // Excluded directories are never descended into
+    if !config.exclude.is_empty() {
+        builder.overrides(exclude_matcher(root, &config.exclude)?);
+    }
+
+    #[cfg(unix)]
+    if config.skip_other_users {
+        let homes = home_parents(root);
+        let uid = crate::fs::users::invoking_uid();
+        if !homes.is_empty() {
+            builder.filter_entry(move |entry| {
+                use std::os::unix::fs::MetadataExt;
+
+                let in_homes = entry
+                    .path()
+                    .parent()
+                    .is_some_and(|parent| homes.iter().any(|h| h == parent));
+                let other_owner = || entry.metadata().is_ok_and(|m| m.is_dir() && m.uid() != uid);
+                !(in_homes && other_owner())
+            });
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Where `/home` and `/Users` appear in a walk of `root`, spelled the way the
+/// walk will report them
+///
+/// Empty when neither is at or below `root`.
+fn home_parents(root: &Path) -> Vec<PathBuf> {
+    let Ok(canonical) = root.canonicalize() else {
+        return Vec::new();
+    };
+    ["/home", "/Users"]
+        .iter()
+        .filter_map(|base| {
+            let base = Path::new(base).canonicalize().ok()?;
+            let relative = base.strip_prefix(&canonical).ok()?;
+            Some(if relative.as_os_str().is_empty() {
+                root.to_path_buf()
+            } else {
+                root.join(relative)
+            })
+        })
+        .collect()
+}
+
+/// Entry for a path the walker yielded
+fn walked_entry(dir_entry: &ignore::DirEntry, config: &TraverseConfig) -> Entry {
+    let path = dir_entry.path();
+    let depth = dir_entry.depth();
+
+    #[cfg(feature = "cache")]
+    if let Some(cache) = &config.cache {
+        // Directories are always read, to see whether they changed
+        let is_dir = dir_entry.file_type().is_some_and(|t| t.is_dir());
+        if !is_dir {
+            let cached = std::fs::symlink_metadata(path)
+                .ok()
+                .and_then(|metadata| cache.get(path, depth, &metadata));
+            if let Some(entry) = cached {
+                return entry;
+            }
+        }
+    }
+
+    let entry = extract_entry(path, depth).unwrap_or_else(|e| {
+        // Log error but continue traversal
+        if !config.quiet {
+            warn!(path = %path.display(), error = %e, "Failed to extract entry");
+        }
+        unreadable_entry(path, dir_entry.file_type(), depth, &e)
+    });
+    #[cfg(feature = "cache")]
+    if let Some(cache) = &config.cache {
+        cache.record(&entry);
+    }
+    entry
+}
+
+/// Store what a walk read in the metadata cache, if it has one
+#[cfg(feature = "cache")]
+fn save_cache(config: &TraverseConfig) {
+    if let Some(cache) = &config.cache {
+        if let Err(e) = cache.save() {
+            if !config.quiet {
+                warn!(error = %e, "Failed to update the metadata cache");
+            }
+        }
+    }
+}
+
+/// Stub entry for a path whose metadata couldn't be read, so it shows up with
+/// an `error` instead of vanishing from the results
+fn unreadable_entry(
+    path: &Path,
+    file_type: Option<std::fs::FileType>,
+    depth: usize,
+    error: &FsError,
+) -> Entry {
+    let kind = file_type.map_or(EntryKind::File, EntryKind::from_file_type);
+    let reason = match error {
+        FsError::Io(e) => e.to_string(),
+        other => other.to_string(),
+    };
+    Entry::unreadable(path, kind, depth, reason)
+}
+
+/// Directories walks in this process couldn't list for lack of permission
+static DENIED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Directories skipped so far because listing them was denied, in walk order
+pub fn denied_dirs() -> Vec<PathBuf> {
+    DENIED.lock().map(|d| d.clone()).unwrap_or_default()
+}
+
+/// End-of-run note about directories that couldn't be read, with a few examples
+pub fn denied_hint(denied: &[PathBuf]) -> Option<String> {
+    const EXAMPLES: usize = 3;
+
+    if denied.is_empty() {
+        return None;
+    }
+    let count = if denied.len() == 1 {
+        Msg::DeniedOne
+    } else {
+        Msg::DeniedMany
+    };
+    let mut hint = trf(count, &[&denied.len()]);
+    for path in denied.iter().take(EXAMPLES) {
+        hint.push_str(&format!("\n  {}", path.display()));
+    }
+    if denied.len() > EXAMPLES {
+        let more = trf(Msg::DeniedMore, &[&(denied.len() - EXAMPLES)]);
+        hint.push_str(&format!("\n  {}", more));
+    }
+    hint.push('\n');
+    hint.push_str(tr(Msg::DeniedRerun));
+    Some(hint)
+}
+
+/// Attach a traversal error to the entry it belongs to
+///
+/// A directory that can't be listed was already yielded (its own metadata
+/// was readable), so the error goes on that entry and it's marked
+/// `truncated`; otherwise the path is added with the error, subject to the
+/// predicate like any other entry. Permission errors are collected for
+/// [`denied_hint`] instead of being logged one by one.
+fn record_walk_error<P>(
+    entries: &mut Vec<Entry>,
+    error: &ignore::Error,
+    predicate: Option<&P>,
+    config: &TraverseConfig,
+) where
+    P: Predicate + ?Sized,
+{
+    let denied = error
+        .io_error()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied);
+    if denied {
+        debug!(error = %error, "Permission denied during traversal");
+    } else if !config.quiet {
+        warn!(error = %error, "Error during traversal");
+    }
+
+    let Some((path, depth)) = error_location(error) else {
+        return;
+    };
+    if denied {
+        if let Ok(mut list) = DENIED.lock() {
+            list.push(path.to_path_buf());
+        }
+    }
+    let reason = error_reason(error);
+    // Symlink loops are left out on purpose; I/O errors hide real contents
+    let listing_failed = error.io_error().is_some();
+
+    if let Some(last) = entries.last_mut().filter(|e| e.path == path) {
+        last.truncated = listing_failed && last.kind == EntryKind::Dir;
+        last.error = Some(reason);
+        return;
+    }
+
+    let entry = match extract_entry(path, depth) {
+        Ok(mut entry) => {
+            entry.truncated = listing_failed && entry.kind == EntryKind::Dir;
+            entry.error = Some(reason);
+            entry
+        }
+        Err(_) => Entry::unreadable(path, EntryKind::File, depth, reason),
+    };
+    if predicate.is_none_or(|pred| pred.test(&entry)) {
+        entries.push(entry);
+    }
+}
+
+/// Path and depth a walk error refers to
+///
+/// Only I/O failures and symlink loops count; problems in ignore files don't
+/// make any entry unreadable.
+fn error_location(error: &ignore::Error) -> Option<(&Path, usize)> {
+    let mut path = None;
+    let mut depth = None;
+    let mut error = error;
+    loop {
+        match error {
+            ignore::Error::WithDepth { depth: d, err } => {
+                depth.get_or_insert(*d);
+                error = err;
+            }
+            ignore::Error::WithPath { path: p, err } => {
+                path.get_or_insert(p.as_path());
+                error = err;
+            }
+            ignore::Error::Loop { child, .. } => {
+                return Some((path.unwrap_or(child), depth.unwrap_or(0)));
+            }
+            ignore::Error::Io(_) => return path.map(|p| (p, depth.unwrap_or(0))),
+            _ => return None,
+        }
+    }
+}
+
+/// The underlying OS error ("Permission denied (os error 13)") without the
+/// path prefixes the walker adds
+fn error_reason(error: &ignore::Error) -> String {
+    match error.io_error() {
+        Some(io) => io
+            .get_ref()
+            .and_then(|inner| inner.source())
+            .map_or_else(|| io.to_string(), |source| source.to_string()),
+        None => error.to_string(),
+    }
+}
+
+/// Walk several roots in order, merging the results
+///
+/// Roots nested inside another root (or repeated) are skipped so overlapping
+/// arguments like `~/src ~/src/app` don't produce duplicate entries.
+pub fn walk_paths<P>(
+    roots: &[PathBuf],
+    config: &TraverseConfig,
+    predicate: Option<&P>,
+) -> Result<Vec<Entry>>
+where
+    P: Predicate + ?Sized,
+{
     let mut entries = Vec::new();
 
-    for result in builder.build() {
-        match result {
-            Ok(dir_entry) => {
-                let path = dir_entry.path();
-                let depth = dir_entry.depth();
+    for root in dedup_roots(roots) {
+        let mut root_entries = match predicate {
+            Some(pred) => walk(&root, config, Some(pred))?,
+            None => walk_no_filter(&root, config)?,
+        };
+        entries.append(&mut root_entries);
+    }
 
-                match extract_entry(path, depth) {
-                    Ok(entry) => {
-                        entries.push(entry);
-                    }
-                    Err(e) => {
-                        // Log error but continue traversal
-                        if !config.quiet {
-                            eprintln!("Warning: Failed to extract entry for {:?}: {}", path, e);
-                        }
-                    }
+    Ok(entries)
+}
+
+/// Drop roots that repeat or are contained in an earlier/later root
+///
+/// Containment is checked on canonical paths; the original spelling of each
+/// kept root is preserved so output paths match what the user typed.
+pub fn dedup_roots(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let canonical: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| r.canonicalize().unwrap_or_else(|_| r.clone()))
+        .collect();
+
+    roots
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            let path = &canonical[*i];
+            !canonical.iter().enumerate().any(|(j, other)| {
+                // Nested in another root, or a repeat of an earlier one
+                (j != *i && path != other && path.starts_with(other)) || (j < *i && path == other)
+            })
+        })
+        .map(|(_, root)| root.clone())
+        .collect()
+}
+
+/// Read a path list from a file, or from stdin when `source` is `-`
+pub fn read_path_list(source: &Path) -> Result<Vec<PathBuf>> {
+    use std::io::Read;
+
+    let mut data = Vec::new();
+    if source == Path::new("-") {
+        std::io::stdin().lock().read_to_end(&mut data)?;
+    } else {
+        data = std::fs::read(source).map_err(|e| FsError::PathAccess {
+            path: source.to_path_buf(),
+            source: e,
+        })?;
+    }
+
+    Ok(parse_path_list(&data))
+}
+
+/// Split a path list on NUL bytes (if any are present, as from `find -print0`)
+/// or otherwise on newlines, skipping empty records
+pub fn parse_path_list(data: &[u8]) -> Vec<PathBuf> {
+    let separator = if data.contains(&0) { b'\0' } else { b'\n' };
+
+    data.split(|b| *b == separator)
+        .map(|record| {
+            if separator == b'\n' {
+                record.strip_suffix(b"\r").unwrap_or(record)
+            } else {
+                record
+            }
+        })
+        .filter(|record| !record.is_empty())
+        .map(bytes_to_path)
+        .collect()
+}
+
+#[cfg(unix)]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Build entries for an explicit path list instead of walking
+///
+/// Each path becomes a depth-0 entry; repeated paths are only listed once.
+pub fn entries_from_paths<P>(
+    paths: &[PathBuf],
+    config: &TraverseConfig,
+    predicate: Option<&P>,
+) -> Result<Vec<Entry>>
+where
+    P: Predicate + ?Sized,
+{
+    let mut seen = std::collections::HashSet::new();
+    let mut entries = Vec::new();
+
+    for path in paths {
+        if !seen.insert(path) {
+            continue;
+        }
+
+        match extract_entry(path, 0) {
+            Ok(entry) => {
+                if predicate.is_none_or(|pred| pred.test(&entry)) {
+                    entries.push(entry);
                 }
             }
             Err(e) => {
                 if !config.quiet {
-                    eprintln!("Warning: Error during traversal: {}", e);
+                    warn!(path = %path.display(), error = %e, "Failed to extract entry");
                 }
             }
         }
@@ -129,7 +527,13 @@ pub fn walk_no_filter(root: &Path, config: &TraverseConfig) -> Result<Vec<Entry>
     Ok(entries)
 }
 
-/// Parallel walk implementation (requires "parallel" feature)
+/// Walk a directory tree on `config.threads` threads (requires "parallel"
+/// feature)
+///
+/// [`walk`] and [`walk_no_filter`] hand off to this when there's more than
+/// one thread. Walker settings, filtering and unreadable entries are the
+/// same; entries are sorted by path afterwards, which keeps every directory
+/// ahead of its contents, unless `config.unordered` is set.
 #[cfg(feature = "parallel")]
 pub fn walk_parallel<P>(
     root: &Path,
@@ -137,47 +541,105 @@ pub fn walk_parallel<P>(
     predicate: Option<&P>,
 ) -> Result<Vec<Entry>>
 where
-    P: Predicate + Sync,
+    P: Predicate + ?Sized,
 {
-    use jwalk::WalkDir;
-    use rayon::prelude::*;
+    use rayon::slice::ParallelSliceMut;
+
+    let _span = info_span!("walk_parallel", root = %root.display()).entered();
+    let _timer = timings::phase("walk");
+
+    let found = Mutex::new(Vec::new());
+    walk_builder(root, config)?
+        .threads(config.threads)
+        .build_parallel()
+        .visit(&mut CollectorBuilder {
+            found: &found,
+            predicate,
+            config,
+        });
+
+    #[cfg(feature = "cache")]
+    save_cache(config);
+    let mut entries = found.into_inner().unwrap_or_else(|e| e.into_inner());
+    if !config.unordered {
+        entries.par_sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    }
 
-    let mut builder = WalkDir::new(root);
+    debug!(entries = entries.len(), "Traversal complete");
+    Ok(entries)
+}
 
-    builder = builder
-        .follow_links(config.follow_symlinks)
-        .skip_hidden(!config.include_hidden);
+/// Hands each walker thread a [`Collector`]
+#[cfg(feature = "parallel")]
+struct CollectorBuilder<'a, P: ?Sized> {
+    found: &'a Mutex<Vec<Entry>>,
+    predicate: Option<&'a P>,
+    config: &'a TraverseConfig,
+}
 
-    if let Some(depth) = config.max_depth {
-        builder = builder.max_depth(depth);
-    }
-
-    let entries: Vec<Entry> = builder
-        .into_iter()
-        .par_bridge()
-        .filter_map(|result| result.ok())
-        .filter_map(|dir_entry| {
-            let path = dir_entry.path();
-            let depth = dir_entry.depth;
-
-            match extract_entry(&path, depth) {
-                Ok(entry) => {
-                    if let Some(pred) = predicate {
-                        if pred.test(&entry) {
-                            Some(entry)
-                        } else {
-                            None
-                        }
-                    } else {
-                        Some(entry)
-                    }
+#[cfg(feature = "parallel")]
+impl<'a, P> ignore::ParallelVisitorBuilder<'a> for CollectorBuilder<'a, P>
+where
+    P: Predicate + ?Sized,
+{
+    fn build(&mut self) -> Box<dyn ignore::ParallelVisitor + 'a> {
+        Box::new(Collector {
+            entries: Vec::new(),
+            found: self.found,
+            predicate: self.predicate,
+            config: self.config,
+        })
+    }
+}
+
+/// Entries one walker thread found, added to the shared list when the thread
+/// is done
+///
+/// A thread reports a directory's listing error right after the directory,
+/// so [`record_walk_error`] finds it as the last entry here too.
+#[cfg(feature = "parallel")]
+struct Collector<'a, P: ?Sized> {
+    entries: Vec<Entry>,
+    found: &'a Mutex<Vec<Entry>>,
+    predicate: Option<&'a P>,
+    config: &'a TraverseConfig,
+}
+
+#[cfg(feature = "parallel")]
+impl<P> ignore::ParallelVisitor for Collector<'_, P>
+where
+    P: Predicate + ?Sized,
+{
+    fn visit(
+        &mut self,
+        result: std::result::Result<ignore::DirEntry, ignore::Error>,
+    ) -> ignore::WalkState {
+        match result {
+            // A symlink loop is reported before the directory it leads to
+            Ok(dir_entry)
+                if self
+                    .entries
+                    .last()
+                    .is_some_and(|e| e.path == dir_entry.path()) => {}
+            Ok(dir_entry) => {
+                let entry = walked_entry(&dir_entry, self.config);
+                if self.predicate.is_none_or(|pred| pred.test(&entry)) {
+                    self.entries.push(entry);
                 }
-                Err(_) => None,
             }
-        })
-        .collect();
+            Err(e) => record_walk_error(&mut self.entries, &e, self.predicate, self.config),
+        }
+        ignore::WalkState::Continue
+    }
+}
 
-    Ok(entries)
+#[cfg(feature = "parallel")]
+impl<P: ?Sized> Drop for Collector<'_, P> {
+    fn drop(&mut self) {
+        if let Ok(mut found) = self.found.lock() {
+            found.append(&mut self.entries);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -201,6 +663,157 @@ mod tests {
         assert!(entries.len() >= 3);
     }
 
+    #[test]
+    fn test_dedup_roots() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a");
+        let nested = a.join("nested");
+        let b = dir.path().join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir(&b).unwrap();
+
+        let roots = vec![nested.clone(), a.clone(), b.clone(), a.clone()];
+        assert_eq!(dedup_roots(&roots), vec![a, b]);
+    }
+
+    #[test]
+    fn test_walk_paths_merges_roots() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        fs::create_dir(&a).unwrap();
+        fs::create_dir(&b).unwrap();
+        fs::write(a.join("one.txt"), "1").unwrap();
+        fs::write(b.join("two.txt"), "2").unwrap();
+
+        let config = TraverseConfig::default();
+        let entries =
+            walk_paths::<dyn Predicate>(&[a.clone(), b, a.join("one.txt")], &config, None).unwrap();
+
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names.iter().filter(|n| **n == "one.txt").count(), 1);
+        assert!(names.contains(&"two.txt"));
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        assert_eq!(
+            parse_path_list(b"a.txt\r\nsub dir/b.rs\n\n"),
+            vec![PathBuf::from("a.txt"), PathBuf::from("sub dir/b.rs")]
+        );
+        assert_eq!(
+            parse_path_list(b"with\nnewline\0other\0"),
+            vec![PathBuf::from("with\nnewline"), PathBuf::from("other")]
+        );
+    }
+
+    #[test]
+    fn test_entries_from_paths() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "test").unwrap();
+
+        let paths = vec![file.clone(), dir.path().join("missing"), file];
+        let config = TraverseConfig {
+            quiet: true,
+            ..Default::default()
+        };
+        let entries = entries_from_paths::<dyn Predicate>(&paths, &config, None).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "file.txt");
+        assert_eq!(entries[0].depth, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_keeps_unreadable_entries() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("file.txt"), "test").unwrap();
+        std::os::unix::fs::symlink("..", sub.join("up")).unwrap();
+
+        let config = TraverseConfig {
+            follow_symlinks: true,
+            quiet: true,
+            ..Default::default()
+        };
+        let entries = walk_no_filter(dir.path(), &config).unwrap();
+
+        let looped: Vec<_> = entries.iter().filter(|e| e.error.is_some()).collect();
+        assert_eq!(looped.len(), 1);
+        assert_eq!(looped[0].path, sub.join("up"));
+        assert_eq!(looped[0].depth, 2);
+        assert!(looped[0].error.as_ref().unwrap().contains("loop"));
+        assert!(!looped[0].truncated);
+        assert!(entries
+            .iter()
+            .any(|e| e.name == "file.txt" && e.error.is_none()));
+    }
+
+    #[test]
+    fn test_denied_hint() {
+        assert_eq!(denied_hint(&[]), None);
+
+        let one = denied_hint(&[PathBuf::from("/root")]).unwrap();
+        assert!(one.starts_with("1 directory could not be read"));
+        assert!(one.contains("\n  /root\n"));
+
+        let many: Vec<PathBuf> = (0..5)
+            .map(|i| PathBuf::from(format!("/home/u{}", i)))
+            .collect();
+        let hint = denied_hint(&many).unwrap();
+        assert!(hint.starts_with("5 directories"));
+        assert!(hint.contains("/home/u2"));
+        assert!(!hint.contains("/home/u3"));
+        assert!(hint.contains("... and 2 more"));
+    }
+
+    #[test]
+    fn test_walk_exclude() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules/pkg")).unwrap();
+        fs::write(dir.path().join("web/node_modules/pkg/index.js"), "x").unwrap();
+        fs::write(dir.path().join("web/app.js"), "x").unwrap();
+        fs::write(dir.path().join("debug.log"), "x").unwrap();
+
+        let config = TraverseConfig {
+            exclude: vec!["node_modules".to_string(), "*.log".to_string()],
+            ..Default::default()
+        };
+        let entries = walk_no_filter(dir.path(), &config).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+
+        assert!(names.contains(&"app.js"));
+        assert!(!names.contains(&"node_modules"));
+        assert!(!names.contains(&"index.js"));
+        assert!(!names.contains(&"debug.log"));
+
+        let invalid = TraverseConfig {
+            exclude: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            walk_no_filter(dir.path(), &invalid),
+            Err(FsError::InvalidGlob { .. })
+        ));
+    }
+
+    #[test]
+    fn test_home_parents() {
+        let dir = tempdir().unwrap();
+        assert!(home_parents(dir.path()).is_empty());
+
+        if Path::new("/home").is_dir() {
+            assert!(home_parents(Path::new("/")).contains(&PathBuf::from("/home")));
+            assert_eq!(
+                home_parents(Path::new("/home/")),
+                vec![PathBuf::from("/home/")]
+            );
+        }
+    }
+
     #[test]
     fn test_walk_max_depth() {
         let dir = tempdir().unwrap();
@@ -238,4 +851,66 @@ mod tests {
         let entries = walk_no_filter(dir.path(), &config).unwrap();
         assert!(entries.iter().any(|e| e.name == ".hidden"));
     }
+
+    // The symlink loop needs Unix symlinks
+    #[cfg(all(feature = "parallel", unix))]
+    #[test]
+    fn test_walk_parallel_matches_sequential() {
+        use crate::fs::filters::ExtensionFilter;
+
+        let dir = tempdir().unwrap();
+        for sub in ["a/deep/er", "b", "node_modules/pkg"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        for file in [
+            "a/one.rs",
+            "a/deep/two.rs",
+            "a/deep/er/three.txt",
+            "b/four.rs",
+        ] {
+            fs::write(dir.path().join(file), "x").unwrap();
+        }
+        fs::write(dir.path().join("node_modules/pkg/index.rs"), "x").unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("b/up")).unwrap();
+
+        let sequential = TraverseConfig {
+            follow_symlinks: true,
+            quiet: true,
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let parallel = TraverseConfig {
+            threads: 4,
+            ..sequential.clone()
+        };
+        let paths =
+            |entries: Vec<Entry>| -> Vec<PathBuf> { entries.into_iter().map(|e| e.path).collect() };
+
+        let mut expected = paths(walk_no_filter(dir.path(), &sequential).unwrap());
+        expected.sort();
+        let entries = walk_no_filter(dir.path(), &parallel).unwrap();
+        assert!(entries
+            .iter()
+            .any(|e| e.path == dir.path().join("b/up") && e.error.is_some()));
+        // Sorted by path, so every directory comes before its contents
+        assert_eq!(paths(entries), expected);
+
+        let rust = ExtensionFilter::new(&["rs".to_string()]);
+        let mut found = paths(
+            walk(
+                dir.path(),
+                &TraverseConfig {
+                    unordered: true,
+                    ..parallel
+                },
+                Some(&rust),
+            )
+            .unwrap(),
+        );
+        found.sort();
+        assert_eq!(
+            found,
+            ["a/deep/two.rs", "a/one.rs", "b/four.rs"].map(|f| dir.path().join(f))
+        );
+    }
 }
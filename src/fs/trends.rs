@@ -0,0 +1,380 @@
+//! Growth of a path over time, from saved snapshots
+//!
+//! The first and last of the compared snapshots are diffed entry by entry
+//! (new and deleted files); every snapshot in between contributes a point
+//! to the size series. `--alert` thresholds ([`TrendAlert`]) are checked
+//! against the report, so a scheduled `trends` run can notify when a path
+//! grows too fast.
+
+use crate::errors::{FsError, Result};
+use crate::fs::snapshot::{SnapshotEntry, SnapshotMeta, SnapshotStore};
+use crate::models::EntryKind;
+use crate::util::{format_size_human, parse_count, parse_size};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+/// Totals of one snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub id: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created: DateTime<Utc>,
+    pub total_size: u64,
+    pub files: u64,
+}
+
+/// Change of one category between the first and last snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryChange {
+    pub category: String,
+    pub size_delta: i64,
+    pub files_delta: i64,
+}
+
+/// Comparison of two or more snapshots of the same path
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendReport {
+    #[serde(with = "crate::util::serde_path")]
+    pub root: PathBuf,
+    /// One point per snapshot, oldest first
+    pub series: Vec<TrendPoint>,
+    pub size_delta: i64,
+    pub files_delta: i64,
+    /// Categories whose size or count changed, largest change first
+    pub categories: Vec<CategoryChange>,
+    #[serde(flatten)]
+    pub files: FileChanges,
+}
+
+/// Files that appeared or disappeared between two snapshots
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileChanges {
+    pub added: u64,
+    pub added_size: u64,
+    pub deleted: u64,
+    pub deleted_size: u64,
+    /// Largest added files, at most `top`
+    pub largest_new: Vec<SnapshotEntry>,
+    /// Largest deleted files (with their last recorded size), at most `top`
+    pub largest_deleted: Vec<SnapshotEntry>,
+}
+
+/// Compare `snapshots` (oldest first, all of one root), listing up to `top` files
+pub fn analyze(
+    store: &SnapshotStore,
+    snapshots: &[SnapshotMeta],
+    top: usize,
+) -> Result<TrendReport> {
+    let [first, .., last] = snapshots else {
+        return Err(FsError::InvalidFormat {
+            format: "Trends need at least two snapshots of the path (see `fexplorer snapshot`)"
+                .to_string(),
+        });
+    };
+
+    let files = diff_entries(store.entries(&first.id)?, store.entries(&last.id)?, top);
+
+    let categories: BTreeSet<&String> = first
+        .categories
+        .keys()
+        .chain(last.categories.keys())
+        .collect();
+    let mut categories: Vec<CategoryChange> = categories
+        .into_iter()
+        .map(|category| {
+            let before = first.categories.get(category).copied().unwrap_or_default();
+            let after = last.categories.get(category).copied().unwrap_or_default();
+            CategoryChange {
+                category: category.clone(),
+                size_delta: delta(before.size, after.size),
+                files_delta: delta(before.files, after.files),
+            }
+        })
+        .filter(|c| c.size_delta != 0 || c.files_delta != 0)
+        .collect();
+    categories.sort_by(|a, b| {
+        b.size_delta
+            .unsigned_abs()
+            .cmp(&a.size_delta.unsigned_abs())
+            .then_with(|| a.category.cmp(&b.category))
+    });
+
+    Ok(TrendReport {
+        root: last.root.clone(),
+        series: snapshots
+            .iter()
+            .map(|meta| TrendPoint {
+                id: meta.id.clone(),
+                created: meta.created,
+                total_size: meta.total_size,
+                files: meta.files,
+            })
+            .collect(),
+        size_delta: delta(first.total_size, last.total_size),
+        files_delta: delta(first.files, last.files),
+        categories,
+        files,
+    })
+}
+
+/// Files present in only one of two snapshots, matched by relative path
+pub fn diff_entries(
+    before: Vec<SnapshotEntry>,
+    after: Vec<SnapshotEntry>,
+    top: usize,
+) -> FileChanges {
+    let is_file = |e: &SnapshotEntry| e.kind == EntryKind::File;
+    let mut before: HashMap<PathBuf, SnapshotEntry> = before
+        .into_iter()
+        .filter(is_file)
+        .map(|e| (e.path.clone(), e))
+        .collect();
+
+    let mut changes = FileChanges::default();
+    let mut added = Vec::new();
+    for entry in after.into_iter().filter(is_file) {
+        if before.remove(&entry.path).is_none() {
+            changes.added += 1;
+            changes.added_size += entry.size;
+            added.push(entry);
+        }
+    }
+    let mut deleted: Vec<SnapshotEntry> = before.into_values().collect();
+    changes.deleted = deleted.len() as u64;
+    changes.deleted_size = deleted.iter().map(|e| e.size).sum();
+
+    changes.largest_new = largest(&mut added, top);
+    changes.largest_deleted = largest(&mut deleted, top);
+    changes
+}
+
+fn largest(entries: &mut [SnapshotEntry], top: usize) -> Vec<SnapshotEntry> {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    entries.iter().take(top).cloned().collect()
+}
+
+/// One-line chart of `values`, scaled between their minimum and maximum
+pub fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    values
+        .iter()
+        .map(|&v| {
+            let level = match max - min {
+                0 => BARS.len() / 2,
+                range => ((v - min) as u128 * (BARS.len() - 1) as u128 / range as u128) as usize,
+            };
+            BARS[level]
+        })
+        .collect()
+}
+
+/// A `trends --alert` threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrendAlert {
+    /// Total size grew by more than this many bytes (`growth=10GB`)
+    Growth(u64),
+    /// Total size grew by more than this percentage (`growth=25%`)
+    GrowthPercent(f64),
+    /// Latest total size is above this (`size=500GB`)
+    Size(u64),
+    /// Latest file count is above this (`files=1M`)
+    Files(u64),
+}
+
+impl TrendAlert {
+    /// Parse `growth=<size>`, `growth=<percent>%`, `size=<size>` or
+    /// `files=<count>`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = || FsError::InvalidFormat {
+            format: format!(
+                "Invalid alert '{}' (expected growth=<size>, growth=<percent>%, size=<size> \
+                 or files=<count>)",
+                spec
+            ),
+        };
+        let (metric, value) = spec.split_once('=').ok_or_else(invalid)?;
+        match metric.trim() {
+            "growth" => match value.trim().strip_suffix('%') {
+                Some(percent) => percent
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|p: &f64| p.is_finite() && *p >= 0.0)
+                    .map(TrendAlert::GrowthPercent)
+                    .ok_or_else(invalid),
+                None => parse_size(value).map(TrendAlert::Growth),
+            },
+            "size" => parse_size(value).map(TrendAlert::Size),
+            "files" => parse_count(value).map(TrendAlert::Files),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// How `report` crosses this threshold, or `None` if it doesn't
+    pub fn check(&self, report: &TrendReport) -> Option<String> {
+        let first = report.series.first()?;
+        let last = report.series.last()?;
+        let growth = report.size_delta;
+        match *self {
+            TrendAlert::Growth(max) if growth > 0 && growth as u64 > max => Some(format!(
+                "grew by {}, more than {}",
+                format_size_human(growth as u64),
+                format_size_human(max)
+            )),
+            TrendAlert::GrowthPercent(max) if growth > 0 => {
+                let percent = match first.total_size {
+                    0 => f64::INFINITY,
+                    before => growth as f64 * 100.0 / before as f64,
+                };
+                (percent > max).then(|| {
+                    format!(
+                        "grew by {:.1}% ({}), more than {}%",
+                        percent,
+                        format_size_human(growth as u64),
+                        max
+                    )
+                })
+            }
+            TrendAlert::Size(max) if last.total_size > max => Some(format!(
+                "size {} is above {}",
+                format_size_human(last.total_size),
+                format_size_human(max)
+            )),
+            TrendAlert::Files(max) if last.files > max => {
+                Some(format!("{} files, more than {}", last.files, max))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn delta(before: u64, after: u64) -> i64 {
+    after as i64 - before as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> SnapshotEntry {
+        SnapshotEntry {
+            path: PathBuf::from(path),
+            size,
+            kind: EntryKind::File,
+            mtime: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let before = vec![
+            file("a.txt", 10),
+            file("old.log", 500),
+            file("gone.bin", 70),
+        ];
+        let after = vec![
+            file("a.txt", 20),
+            file("video.mp4", 900),
+            file("notes.md", 5),
+            file("data.csv", 300),
+        ];
+
+        let changes = diff_entries(before, after, 2);
+        assert_eq!((changes.added, changes.added_size), (3, 1205));
+        assert_eq!((changes.deleted, changes.deleted_size), (2, 570));
+        let names = |entries: &[SnapshotEntry]| -> Vec<PathBuf> {
+            entries.iter().map(|e| e.path.clone()).collect()
+        };
+        assert_eq!(
+            names(&changes.largest_new),
+            vec![PathBuf::from("video.mp4"), PathBuf::from("data.csv")]
+        );
+        assert_eq!(
+            names(&changes.largest_deleted),
+            vec![PathBuf::from("old.log"), PathBuf::from("gone.bin")]
+        );
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+        assert_eq!(sparkline(&[5, 5]), "▅▅");
+        assert_eq!(sparkline(&[u64::MAX, 0]), "█▁");
+    }
+
+    #[test]
+    fn test_analyze_needs_two_snapshots() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("data");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("a.rs"), "fn a() {}").unwrap();
+        let store = SnapshotStore::new(temp.path().join("snapshots"));
+        let walk = || crate::fs::traverse::walk_no_filter(&root, &Default::default()).unwrap();
+
+        let first = store.create(&root, &walk(), &[], None).unwrap();
+        assert!(analyze(&store, std::slice::from_ref(&first), 10).is_err());
+
+        std::fs::write(root.join("big.png"), vec![0u8; 1000]).unwrap();
+        std::fs::remove_file(root.join("a.rs")).unwrap();
+        let second = store.create(&root, &walk(), &[], None).unwrap();
+
+        let report = analyze(&store, &[first, second], 10).unwrap();
+        assert_eq!(report.series.len(), 2);
+        assert_eq!((report.size_delta, report.files_delta), (991, 0));
+        assert_eq!(report.files.largest_new[0].path, PathBuf::from("big.png"));
+        assert_eq!(report.files.largest_deleted[0].path, PathBuf::from("a.rs"));
+        assert_eq!(
+            report.categories,
+            vec![
+                CategoryChange {
+                    category: "media".to_string(),
+                    size_delta: 1000,
+                    files_delta: 1
+                },
+                CategoryChange {
+                    category: "source".to_string(),
+                    size_delta: -9,
+                    files_delta: -1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alerts() {
+        let point = |total_size, files| TrendPoint {
+            id: String::new(),
+            created: Utc::now(),
+            total_size,
+            files,
+        };
+        let report = TrendReport {
+            root: PathBuf::from("/data"),
+            series: vec![point(1000, 10), point(1500, 12)],
+            size_delta: 500,
+            files_delta: 2,
+            categories: Vec::new(),
+            files: FileChanges::default(),
+        };
+        let fires = |spec| TrendAlert::parse(spec).unwrap().check(&report).is_some();
+
+        assert!(fires("growth=400"));
+        assert!(!fires("growth=1KB"));
+        assert!(fires("growth=25%"));
+        assert!(!fires("growth=50%"));
+        assert!(fires("size=1KB"));
+        assert!(!fires("size=2KB"));
+        assert!(fires("files=11"));
+        assert!(!fires("files=12"));
+
+        assert!(TrendAlert::parse("growth").is_err());
+        assert!(TrendAlert::parse("shrink=10%").is_err());
+        assert!(TrendAlert::parse("growth=-5%").is_err());
+    }
+}
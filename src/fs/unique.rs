@@ -0,0 +1,115 @@
+use crate::errors::{FsError, Result};
+use crate::models::{Entry, EntryKind};
+use std::collections::HashMap;
+
+/// Key that `--unique-by` collapses entries on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniqueKey {
+    Name,
+    Size,
+    /// Content hash; needs the `hash` column to be enriched first
+    Hash,
+}
+
+impl UniqueKey {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "name" => Ok(UniqueKey::Name),
+            "size" => Ok(UniqueKey::Size),
+            "hash" | "blake3" => Ok(UniqueKey::Hash),
+            _ => Err(FsError::InvalidFormat {
+                format: format!(
+                    "--unique-by: unknown key '{}' (expected name, size or hash)",
+                    s
+                ),
+            }),
+        }
+    }
+
+    /// Key of an entry, or `None` if it never collapses
+    ///
+    /// Sizes and hashes only compare files, so directories stay distinct.
+    fn key_of(self, entry: &Entry) -> Option<String> {
+        match self {
+            UniqueKey::Name => Some(entry.name.clone()),
+            UniqueKey::Size if entry.kind == EntryKind::File => Some(entry.size.to_string()),
+            UniqueKey::Hash => entry.enriched.hash.clone(),
+            UniqueKey::Size => None,
+        }
+    }
+}
+
+/// Keep the first entry for each key, recording in its `count` how many shared it
+///
+/// Entries keep their current order, so "first" follows any sort applied before.
+pub fn collapse_unique(entries: Vec<Entry>, key: UniqueKey) -> Vec<Entry> {
+    let mut kept: Vec<Entry> = Vec::with_capacity(entries.len());
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for mut entry in entries {
+        match key.key_of(&entry) {
+            Some(k) => match index.get(&k) {
+                Some(&i) => {
+                    *kept[i].enriched.count.get_or_insert(1) += 1;
+                }
+                None => {
+                    index.insert(k, kept.len());
+                    entry.enriched.count = Some(1);
+                    kept.push(entry);
+                }
+            },
+            None => {
+                entry.enriched.count = Some(1);
+                kept.push(entry);
+            }
+        }
+    }
+
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, size: u64, kind: EntryKind) -> Entry {
+        Entry {
+            size,
+            kind,
+            depth: 1,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_collapse_by_name_keeps_first() {
+        let entries = vec![
+            entry("/a/vendor/lodash.js", 10, EntryKind::File),
+            entry("/a/lib/util.rs", 20, EntryKind::File),
+            entry("/b/vendor/lodash.js", 30, EntryKind::File),
+            entry("/c/lodash.js", 40, EntryKind::File),
+        ];
+        let unique = collapse_unique(entries, UniqueKey::Name);
+
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].path, PathBuf::from("/a/vendor/lodash.js"));
+        assert_eq!(unique[0].enriched.count, Some(3));
+        assert_eq!(unique[1].enriched.count, Some(1));
+    }
+
+    #[test]
+    fn test_collapse_by_size_skips_dirs() {
+        let entries = vec![
+            entry("/a", 0, EntryKind::Dir),
+            entry("/b", 0, EntryKind::Dir),
+            entry("/a/x", 5, EntryKind::File),
+            entry("/b/y", 5, EntryKind::File),
+        ];
+        let unique = collapse_unique(entries, UniqueKey::Size);
+
+        assert_eq!(unique.len(), 3);
+        assert_eq!(unique[2].enriched.count, Some(2));
+        assert!(UniqueKey::parse("mtime").is_err());
+    }
+}
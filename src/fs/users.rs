@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+/// Cached uid -> user name table parsed from /etc/passwd
+static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+/// Cached gid -> group name table parsed from /etc/group
+static GROUPS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+/// Look up the user name for a uid (best effort, falls back to None)
+pub fn user_name(uid: u32) -> Option<String> {
+    USERS
+        .get_or_init(|| load_id_table("/etc/passwd"))
+        .get(&uid)
+        .cloned()
+}
+
+/// Look up the group name for a gid (best effort, falls back to None)
+pub fn group_name(gid: u32) -> Option<String> {
+    GROUPS
+        .get_or_init(|| load_id_table("/etc/group"))
+        .get(&gid)
+        .cloned()
+}
+
+/// Look up the uid for a user name
+pub fn user_id(name: &str) -> Option<u32> {
+    find_id(USERS.get_or_init(|| load_id_table("/etc/passwd")), name)
+}
+
+/// Look up the gid for a group name
+pub fn group_id(name: &str) -> Option<u32> {
+    find_id(GROUPS.get_or_init(|| load_id_table("/etc/group")), name)
+}
+
+fn find_id(table: &HashMap<u32, String>, name: &str) -> Option<u32> {
+    table
+        .iter()
+        .filter(|(_, n)| n.as_str() == name)
+        .map(|(id, _)| *id)
+        .min()
+}
+
+/// The user a scan runs for: the effective uid, or under `sudo` the user who
+/// ran it (`SUDO_UID`)
+#[cfg(unix)]
+pub fn invoking_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let euid = unsafe { libc::geteuid() };
+    if euid != 0 {
+        return euid;
+    }
+    std::env::var("SUDO_UID")
+        .ok()
+        .and_then(|uid| uid.parse().ok())
+        .unwrap_or(euid)
+}
+
+/// Load a colon-separated id table (passwd/group format)
+fn load_id_table(path: &str) -> HashMap<u32, String> {
+    fs::read_to_string(path)
+        .map(|content| parse_id_table(&content))
+        .unwrap_or_default()
+}
+
+/// Parse `name:x:id:...` lines into an id -> name map
+fn parse_id_table(content: &str) -> HashMap<u32, String> {
+    let mut table = HashMap::new();
+
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(':');
+        let name = fields.next();
+        let id = fields.nth(1).and_then(|id| id.parse::<u32>().ok());
+
+        if let (Some(name), Some(id)) = (name, id) {
+            // Keep the first name for an id, like getpwuid does
+            table.entry(id).or_insert_with(|| name.to_string());
+        }
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_id_table() {
+        let content = "# comment\nroot:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\nbad line\n";
+        let table = parse_id_table(content);
+
+        assert_eq!(table.get(&0), Some(&"root".to_string()));
+        assert_eq!(table.get(&1000), Some(&"alice".to_string()));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_find_id() {
+        let table = parse_id_table("root:x:0:0::/root:/bin/sh\nalice:x:1000:1000::/:/bin/sh\n");
+        assert_eq!(find_id(&table, "alice"), Some(1000));
+        assert_eq!(find_id(&table, "bob"), None);
+    }
+
+    #[test]
+    fn test_parse_id_table_keeps_first() {
+        let table = parse_id_table("wheel:x:10:\nadmin:x:10:\n");
+        assert_eq!(table.get(&10), Some(&"wheel".to_string()));
+    }
+}
@@ -5,7 +5,7 @@ use crate::models::WatchEvent;
 #[cfg(feature = "watch")]
 use notify::{Event, EventKind, RecursiveMode, Watcher};
 #[cfg(feature = "watch")]
-use std::path::Path;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "watch")]
 use std::sync::mpsc::channel;
 #[cfg(feature = "watch")]
@@ -59,6 +59,49 @@ impl FileWatcher {
         Ok(())
     }
 
+    /// Watch several paths, handing events over in batches
+    ///
+    /// A batch is delivered once `quiet` passes without further events, so a
+    /// burst of changes (a checkout, a build) is handled once.
+    pub fn watch_batched<F>(
+        &self,
+        paths: &[PathBuf],
+        quiet: Duration,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(Vec<WatchEvent>) -> Result<()>,
+    {
+        let (tx, rx) = channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        })
+        .map_err(|e| FsError::Watch(e.to_string()))?;
+
+        for path in paths {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(|e| FsError::Watch(format!("{}: {}", path.display(), e)))?;
+        }
+
+        let mut batch = Vec::new();
+        loop {
+            match rx.recv_timeout(quiet) {
+                Ok(event) => batch.extend(self.process_event(event)),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) if !batch.is_empty() => {
+                    callback(std::mem::take(&mut batch))?;
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(_) => break,
+            }
+        }
+
+        Ok(())
+    }
+
     fn process_event(&self, event: Event) -> Option<WatchEvent> {
         let event_type = match event.kind {
             EventKind::Create(_) => "create",
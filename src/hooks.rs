@@ -0,0 +1,154 @@
+//! Shell hooks around commands
+//!
+//! The `[hooks]` table of the config maps `pre_<command>` and
+//! `post_<command>` to a shell command, e.g.
+//! `post_duplicates = "notify-send 'fexplorer' 'duplicate scan done'"`.
+//! A hook gets a JSON summary of the command on stdin. A failing pre hook
+//! stops the command; a failing post hook is only reported, as the command
+//! has already run. Hook output goes to stderr, so it never mixes with the
+//! command's results.
+
+use crate::errors::{FsError, Result};
+use crate::util::shell_command;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// When a hook runs relative to its command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookStage {
+    Pre,
+    Post,
+}
+
+/// The summary a hook receives on stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct HookEvent {
+    /// Config key of the hook, e.g. `post_duplicates`
+    pub hook: String,
+    pub stage: HookStage,
+    /// Subcommand name, e.g. `duplicates`
+    pub command: String,
+    /// The full command line
+    pub args: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+    /// Whether the command succeeded (post hooks only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// The command's error, if it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HookEvent {
+    /// Event for the hook run before `command`
+    pub fn pre(command: &str, args: &[String]) -> Self {
+        Self::new(HookStage::Pre, command, args)
+    }
+
+    /// Event for the hook run after `command`, with its outcome
+    pub fn post(command: &str, args: &[String], duration: Duration, error: Option<String>) -> Self {
+        Self {
+            success: Some(error.is_none()),
+            duration_ms: Some(duration.as_millis() as u64),
+            error,
+            ..Self::new(HookStage::Post, command, args)
+        }
+    }
+
+    fn new(stage: HookStage, command: &str, args: &[String]) -> Self {
+        Self {
+            hook: hook_name(stage, command),
+            stage,
+            command: command.to_string(),
+            args: args.to_vec(),
+            timestamp: Utc::now(),
+            success: None,
+            duration_ms: None,
+            error: None,
+        }
+    }
+}
+
+/// Config key of a command's hook (`-` in command names becomes `_`)
+pub fn hook_name(stage: HookStage, command: &str) -> String {
+    let stage = match stage {
+        HookStage::Pre => "pre",
+        HookStage::Post => "post",
+    };
+    format!("{}_{}", stage, command.replace('-', "_"))
+}
+
+/// Run the hook configured for `event`, if any
+///
+/// Returns whether a hook ran. Errors when it can't be started or exits
+/// unsuccessfully.
+pub fn run_hook(hooks: &HashMap<String, String>, event: &HookEvent) -> Result<bool> {
+    let Some(script) = hooks.get(&event.hook) else {
+        return Ok(false);
+    };
+    let failed =
+        |message: String| FsError::Hook(format!("{} ({}): {}", event.hook, script, message));
+
+    let mut child = shell_command(script)
+        .stdin(Stdio::piped())
+        .stdout(std::io::stderr())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes the pipe early; that's fine
+        let mut payload = serde_json::to_vec(event)?;
+        payload.push(b'\n');
+        let _ = stdin.write_all(&payload);
+    }
+
+    let status = child.wait().map_err(|e| failed(e.to_string()))?;
+    if !status.success() {
+        return Err(failed(format!("exited with {}", status)));
+    }
+    Ok(true)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_receives_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("summary.json");
+        let hooks = HashMap::from([(
+            "post_find".to_string(),
+            format!("cat > '{}'", out.display()),
+        )]);
+        let args = vec!["fexplorer".to_string(), "find".to_string()];
+
+        assert!(!run_hook(&hooks, &HookEvent::pre("find", &args)).unwrap());
+        let event = HookEvent::post("find", &args, Duration::from_millis(1500), None);
+        assert!(run_hook(&hooks, &event).unwrap());
+
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(summary["hook"], "post_find");
+        assert_eq!(summary["stage"], "post");
+        assert_eq!(summary["success"], true);
+        assert_eq!(summary["duration_ms"], 1500);
+        assert!(summary.get("error").is_none());
+    }
+
+    #[test]
+    fn test_failing_hook() {
+        let hooks = HashMap::from([("pre_self_update".to_string(), "exit 3".to_string())]);
+        let event = HookEvent::pre("self-update", &[]);
+        assert_eq!(event.hook, "pre_self_update");
+
+        let err = run_hook(&hooks, &event).unwrap_err().to_string();
+        assert!(err.contains("pre_self_update"), "{}", err);
+    }
+}
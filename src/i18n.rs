@@ -0,0 +1,273 @@
+//! Translated user-facing messages (`--lang`)
+//!
+//! Summaries, notes and TUI footer text come from the [`Msg`] catalog in
+//! the language picked by [`set`], by default English. `main` picks it from
+//! `--lang`, else from the locale: the first of `LC_ALL`, `LC_MESSAGES` and
+//! `LANG` that is set, so `LANG=es_ES.UTF-8` gives Spanish. Log messages,
+//! errors and help text stay in English.
+//!
+//! Messages take arguments as `{0}`, `{1}`, ..., so a translation can put
+//! them in a different order. Adding a language means a [`Lang`] variant, a
+//! column in [`Msg::texts`] and a code in [`Lang::parse`].
+
+use crate::errors::{FsError, Result};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// A language messages are available in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Es,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::En, Lang::Es];
+
+    /// Language of a code or locale name: `es`, `es-MX`, `es_ES.UTF-8`;
+    /// `C` and `POSIX` are English
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.split(['.', '@']).next().unwrap_or_default();
+        let primary = code.split(['_', '-']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+        }
+    }
+}
+
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+/// Print messages in `lang` for the rest of this process
+pub fn set(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// The language messages are printed in
+pub fn current() -> Lang {
+    let index = LANG.load(Ordering::Relaxed) as usize;
+    Lang::ALL.get(index).copied().unwrap_or(Lang::En)
+}
+
+/// Language from `--lang`, else from the locale environment
+///
+/// An unknown `--lang` is an error; an unknown locale falls back to English.
+pub fn detect(flag: Option<&str>) -> Result<Lang> {
+    if let Some(code) = flag {
+        return Lang::parse(code).ok_or_else(|| FsError::InvalidFormat {
+            format: format!(
+                "Unsupported language '{}' (available: {})",
+                code,
+                Lang::ALL.map(Lang::code).join(", ")
+            ),
+        });
+    }
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+    Ok(locale.as_deref().and_then(Lang::parse).unwrap_or(Lang::En))
+}
+
+/// A translatable message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    /// Prefix of the end-of-run permission note
+    Note,
+    DeniedOne,
+    DeniedMany,
+    DeniedMore,
+    DeniedRerun,
+    NoMatches,
+    FoundMatches,
+    NoDuplicates,
+    NoNearDuplicates,
+    NoSimilarNames,
+    CacheCleared,
+    CacheEmpty,
+    TuiStatus,
+    TuiNoFilter,
+    TuiOn,
+    TuiOff,
+    TuiCopyTo,
+    TuiMoveTo,
+    TuiRenameTo,
+    TuiGoTo,
+}
+
+impl Msg {
+    pub const ALL: [Msg; 20] = [
+        Msg::Note,
+        Msg::DeniedOne,
+        Msg::DeniedMany,
+        Msg::DeniedMore,
+        Msg::DeniedRerun,
+        Msg::NoMatches,
+        Msg::FoundMatches,
+        Msg::NoDuplicates,
+        Msg::NoNearDuplicates,
+        Msg::NoSimilarNames,
+        Msg::CacheCleared,
+        Msg::CacheEmpty,
+        Msg::TuiStatus,
+        Msg::TuiNoFilter,
+        Msg::TuiOn,
+        Msg::TuiOff,
+        Msg::TuiCopyTo,
+        Msg::TuiMoveTo,
+        Msg::TuiRenameTo,
+        Msg::TuiGoTo,
+    ];
+
+    /// English and Spanish text
+    fn texts(self) -> [&'static str; 2] {
+        match self {
+            Msg::Note => ["Note: {0}", "Nota: {0}"],
+            Msg::DeniedOne => [
+                "{0} directory could not be read (permission denied), so results are incomplete:",
+                "No se pudo leer {0} directorio (permiso denegado), así que los resultados están incompletos:",
+            ],
+            Msg::DeniedMany => [
+                "{0} directories could not be read (permission denied), so results are incomplete:",
+                "No se pudieron leer {0} directorios (permiso denegado), así que los resultados están incompletos:",
+            ],
+            Msg::DeniedMore => ["... and {0} more", "... y {0} más"],
+            Msg::DeniedRerun => [
+                "Rerun with sudo (or as the owning user) to include them.",
+                "Vuelva a ejecutar con sudo (o como el usuario propietario) para incluirlos.",
+            ],
+            Msg::NoMatches => ["No matches found", "No se encontraron coincidencias"],
+            Msg::FoundMatches => [
+                "Found {0} matches in {1} files",
+                "Se encontraron {0} coincidencias en {1} archivos",
+            ],
+            Msg::NoDuplicates => [
+                "No duplicate files found",
+                "No se encontraron archivos duplicados",
+            ],
+            Msg::NoNearDuplicates => [
+                "No near-duplicate files found",
+                "No se encontraron archivos casi duplicados",
+            ],
+            Msg::NoSimilarNames => [
+                "No similar file names found",
+                "No se encontraron nombres de archivo parecidos",
+            ],
+            Msg::CacheCleared => [
+                "Metadata cache cleared ({0}).",
+                "Caché de metadatos vaciada ({0}).",
+            ],
+            Msg::CacheEmpty => [
+                "Metadata cache is already empty.",
+                "La caché de metadatos ya está vacía.",
+            ],
+            Msg::TuiStatus => [
+                "{0} entries | Filter: {1} | Hidden: {2} | q:quit /:filter ::commands ?:help",
+                "{0} entradas | Filtro: {1} | Ocultos: {2} | q:salir /:filtrar ::comandos ?:ayuda",
+            ],
+            Msg::TuiNoFilter => ["<none>", "<ninguno>"],
+            Msg::TuiOn => ["on", "sí"],
+            Msg::TuiOff => ["off", "no"],
+            Msg::TuiCopyTo => ["Copy to", "Copiar a"],
+            Msg::TuiMoveTo => ["Move to", "Mover a"],
+            Msg::TuiRenameTo => ["Rename to", "Renombrar a"],
+            Msg::TuiGoTo => ["Go to", "Ir a"],
+        }
+    }
+
+    /// The message in `lang`, placeholders left in
+    pub fn text_in(self, lang: Lang) -> &'static str {
+        self.texts()[lang as usize]
+    }
+}
+
+/// The message in the current language
+pub fn tr(msg: Msg) -> &'static str {
+    msg.text_in(current())
+}
+
+/// The message in the current language with `{0}`, `{1}`, ... filled in
+pub fn trf(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(tr(msg), args)
+}
+
+/// `template` with each `{n}` replaced by `args[n]`, in one pass so that
+/// arguments containing braces stay as they are
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let arg = after.split_once('}').and_then(|(index, tail)| {
+            let arg = args.get(index.parse::<usize>().ok()?)?;
+            Some((arg, tail))
+        });
+        match arg {
+            Some((arg, tail)) => {
+                out.push_str(&arg.to_string());
+                rest = tail;
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Lang::parse("es"), Some(Lang::Es));
+        assert_eq!(Lang::parse("es_ES.UTF-8"), Some(Lang::Es));
+        assert_eq!(Lang::parse("ES-mx"), Some(Lang::Es));
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse("C.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse("POSIX"), Some(Lang::En));
+        assert_eq!(Lang::parse("fr_FR"), None);
+        assert!(detect(Some("de")).is_err());
+        assert_eq!(detect(Some("es")).unwrap(), Lang::Es);
+    }
+
+    #[test]
+    fn test_catalog_placeholders_match() {
+        let placeholders = |text: &str| -> Vec<usize> {
+            (0..4)
+                .filter(|i| text.contains(&format!("{{{}}}", i)))
+                .collect()
+        };
+        for msg in Msg::ALL {
+            let english = placeholders(msg.text_in(Lang::En));
+            for lang in Lang::ALL {
+                let text = msg.text_in(lang);
+                assert!(!text.is_empty(), "{:?} in {:?}", msg, lang);
+                assert_eq!(placeholders(text), english, "{:?} in {:?}", msg, lang);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(Msg::FoundMatches.text_in(Lang::Es), &[&3, &2]),
+            "Se encontraron 3 coincidencias en 2 archivos"
+        );
+        assert_eq!(fill("{1} before {0}", &[&"a", &"b"]), "b before a");
+        assert_eq!(fill("{0} and {1}", &[&"{1}", &"x"]), "{1} and x");
+        assert_eq!(fill("{0} {x} {9}", &[&1]), "1 {x} {9}");
+    }
+}
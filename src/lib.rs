@@ -5,10 +5,20 @@ pub mod cli;
 pub mod config;
 pub mod errors;
 pub mod fs;
+pub mod hooks;
+pub mod i18n;
+pub mod logging;
 pub mod models;
+pub mod notify;
 pub mod output;
+pub mod plain;
+pub mod stats;
+pub mod timings;
 pub mod util;
 
+#[cfg(feature = "plugins")]
+pub mod plugins;
+
 #[cfg(feature = "tui")]
 pub mod tui;
 
@@ -16,4 +26,4 @@ pub mod tui;
 pub mod px;
 
 pub use errors::{FsError, Result};
-pub use models::{Column, Entry, EntryKind, OutputFormat, SortKey, SortOrder};
+pub use models::{Column, Entry, EntryKind, OutputFormat, SortField, SortKey, SortOrder};
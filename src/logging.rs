@@ -0,0 +1,99 @@
+//! Structured logging setup
+//!
+//! Warnings and diagnostics are emitted through `tracing`. By default only
+//! warnings reach stderr; `-v` raises the level to `info`, which also reports
+//! how long each traversal, hashing and grep phase took when its span closes.
+
+use crate::errors::{FsError, Result};
+use crate::output::color;
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Logging options collected from the command line
+#[derive(Debug, Clone, Default)]
+pub struct LogConfig {
+    /// Explicit level or filter directive (overrides verbose/quiet)
+    pub level: Option<String>,
+    /// Append logs to this file instead of stderr
+    pub file: Option<PathBuf>,
+    pub verbose: bool,
+    pub quiet: bool,
+    pub no_color: bool,
+}
+
+impl LogConfig {
+    /// Effective filter directive
+    pub fn directive(&self) -> String {
+        match &self.level {
+            Some(level) => level.clone(),
+            None if self.quiet => "error".to_string(),
+            None if self.verbose => "info".to_string(),
+            None => "warn".to_string(),
+        }
+    }
+}
+
+/// Install the global tracing subscriber
+pub fn init(config: &LogConfig) -> Result<()> {
+    let directive = config.directive();
+    let filter = EnvFilter::try_new(&directive).map_err(|e| FsError::InvalidFormat {
+        format: format!("Invalid log level '{}': {}", directive, e),
+    })?;
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_target(false);
+
+    let result = match &config.file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| FsError::PathAccess {
+                    path: path.clone(),
+                    source: e,
+                })?;
+
+            builder
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .try_init()
+        }
+        // An empty timer (rather than `without_time`) keeps span close timings
+        None => builder
+            .with_timer(())
+            .with_ansi(!config.no_color && color::use_color(std::io::stderr().is_terminal()))
+            .with_writer(std::io::stderr)
+            .try_init(),
+    };
+
+    result.map_err(|e| FsError::InvalidFormat {
+        format: format!("Failed to initialize logging: {}", e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directive_precedence() {
+        let mut config = LogConfig::default();
+        assert_eq!(config.directive(), "warn");
+
+        config.verbose = true;
+        assert_eq!(config.directive(), "info");
+
+        config.quiet = true;
+        assert_eq!(config.directive(), "error");
+
+        config.level = Some("debug".to_string());
+        assert_eq!(config.directive(), "debug");
+    }
+}
@@ -1,312 +1,1595 @@
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use rust_filesearch::{
     cli::{
-        self, parse_entry_kinds, parse_sort_key, parse_sort_order, Cli, Commands, ProfileCommand,
+        self, parse_entry_kinds, parse_sort_fields, parse_sort_order, BackupCommand, Cli, Commands,
+        ProfileCommand, QuotaCommand, StatsCommand,
     },
-    config::Config,
+    config::{Config, PipelineStage},
     errors::{FsError, Result},
     fs::{
+        baseline::load_results,
+        batch::BatchReport,
+        enrich::{
+            enrich_entries, enrichers_for, DirSizeEnricher, Enricher, LastActivityEnricher,
+            TypeDetection,
+        },
+        exec::ExecFilter,
         filters::{
-            AndPredicate, CategoryFilter, DateFilter, ExtensionFilter, GlobFilter, KindFilter,
-            Predicate, RegexFilter, SizeFilter,
+            AndPredicate, BackupExcludedFilter, CategoryFilter, DateFilter, ErrorFilter,
+            ExtensionFilter, GlobFilter, KindFilter, PermFilter, Predicate, RegexFilter,
+            SizeFilter, SparseFilter, TimeField,
+        },
+        query::{parse_where, profile_list, profile_predicates},
+        size::{
+            compute_du_sizes, get_top_by_size, resolve_link_targets, sort_du_order, DirSizeCache,
         },
-        size::{compute_dir_sizes, get_top_by_size, update_entries_with_dir_sizes},
-        traverse::{walk, walk_no_filter, TraverseConfig},
+        traverse::{
+            denied_dirs, denied_hint, entries_from_paths, read_path_list, walk, walk_no_filter,
+            walk_paths, TraverseConfig,
+        },
+        unique::collapse_unique,
     },
-    models::{Entry, EntryKind, OutputFormat, SortKey, SortOrder},
+    models::{Entry, EntryKind, OutputFormat, SortField, SortOrder},
     output::{
         csvw::CsvFormatter,
+        destination::Destination,
+        du::DuFormatter,
         format::OutputSink,
+        gha::Annotation,
         json::{JsonFormatter, NdjsonFormatter},
+        ls::LsFormatter,
         pretty::{PrettyFormatter, TreeFormatter},
+        sarif::{sarif_log, SarifResult, SarifRule},
+        select::SelectFormatter,
     },
 };
+use rust_filesearch::hooks::{run_hook, HookEvent};
+use rust_filesearch::i18n::{self, tr, trf, Msg};
+use rust_filesearch::logging::{self, LogConfig};
+use rust_filesearch::notify::{Notification, Notifier};
+use rust_filesearch::output::color;
+use rust_filesearch::plain;
+use rust_filesearch::stats::{record_run, UsageStats};
+use rust_filesearch::timings;
+use rust_filesearch::util::top_k_by;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{info_span, warn};
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let mut matches = Cli::command().get_matches_from(&args);
+
+    // Per-command config defaults apply to options not given on the command line
+    let config = Config::load();
+    if let Ok(config) = &config {
+        if let Some(args) = cli::apply_command_defaults(&args, &matches, &config.defaults)? {
+            matches = Cli::command().get_matches_from(args);
+        }
+    }
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // Plain output has no color anywhere
+    cli.no_color |= cli.plain;
+    color::set(color::detect(cli.color, cli.no_color));
+
+    logging::init(&LogConfig {
+        level: cli.log_level.clone(),
+        file: cli.log_file.clone(),
+        verbose: cli.verbose,
+        quiet: cli.quiet,
+        no_color: cli.no_color,
+    })?;
+
+    // Usage stats key: profiles are tracked individually, stats itself is not tracked
+    let stats_key = match &cli.command {
+        Commands::Run { profile, .. } => Some(format!("run:{}", profile)),
+        Commands::Stats { .. } => None,
+        _ => matches.subcommand_name().map(String::from),
+    };
+    let hooks = config.as_ref().map(|c| c.hooks.clone()).unwrap_or_default();
+    let record_stats =
+        stats_key.is_some() && config.map(|c| c.preferences.record_stats).unwrap_or(false);
+
+    i18n::set(i18n::detect(cli.lang.as_deref())?);
+    if cli.timings {
+        timings::enable();
+    }
+    if cli.plain {
+        plain::enable();
+    }
+
+    let start = Instant::now();
+    let command_name = matches.subcommand_name().unwrap_or_default();
+    let quiet = cli.quiet;
+    let hook_args: Vec<String> = args
+        .iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    run_hook(&hooks, &HookEvent::pre(command_name, &hook_args))?;
+    let result = info_span!("command", name = command_name).in_scope(|| run(cli));
+
+    let error = result.as_ref().err().map(|e| e.to_string());
+    let event = HookEvent::post(command_name, &hook_args, start.elapsed(), error);
+    if let Err(e) = run_hook(&hooks, &event) {
+        warn!("{}", e);
+    }
+
+    // Permission errors are summarized once instead of warned about per directory
+    if let Some(hint) = denied_hint(&denied_dirs()).filter(|_| !quiet) {
+        eprintln!("\n{}", trf(Msg::Note, &[&hint]));
+    }
+
+    if timings::is_enabled() {
+        let mut phases = timings::snapshot();
+        phases.push(("total", start.elapsed()));
+        eprintln!("Timings: {}", timings::format_report(&phases));
+    }
+
+    if let Some(key) = stats_key.filter(|_| record_stats) {
+        record_run(&key, start.elapsed(), result.is_ok());
+    }
+
+    result
+}
 
+fn run(cli: Cli) -> Result<()> {
+    let types = cli.type_detection();
     match cli.command {
         Commands::List {
-            path,
+            paths,
             sort,
             order,
-            dirs_first,
-            common,
+            limit,
+            ls,
+            mut common,
         } => {
-            let config = build_traverse_config(&common, cli.quiet);
+            common.reject_from_results("list")?;
+            if ls {
+                // ls -la shows dotfiles, ignores .gitignore and lists one level
+                common.hidden = true;
+                common.no_gitignore = true;
+                common.max_depth.get_or_insert(1);
+            }
+
+            let config = build_traverse_config(&common, cli.quiet, types);
             let predicate = build_predicate_from_common(&common)?;
 
-            let mut entries = if let Some(pred) = &predicate {
-                walk(&path, &config, Some(pred.as_ref()))?
+            let mut entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+
+            // Sort if requested (ls mode defaults to sorting by name)
+            let sort = if ls {
+                sort.or_else(|| Some("name".to_string()))
             } else {
-                walk_no_filter(&path, &config)?
+                sort
             };
-
-            // Sort if requested
-            if let Some(sort_key_str) = sort {
-                let sort_key = parse_sort_key(&sort_key_str)?;
-                let sort_order = parse_sort_order(&order)?;
-                sort_entries(&mut entries, sort_key, sort_order, dirs_first);
+            let fields = match sort {
+                Some(sort) => parse_sort_fields(&sort, parse_sort_order(&order)?)?,
+                None => Vec::new(),
+            };
+            match limit {
+                Some(n) if !fields.is_empty() && !thins_entries(&common) => {
+                    // Select the first N without sorting everything
+                    let _timer = timings::phase("sort");
+                    entries = top_k_by(entries, n, entry_order(fields, common.first_kind()));
+                }
+                _ => {
+                    sort_entries(&mut entries, fields, common.first_kind());
+                    thin_entries(&mut entries, &common)?;
+                    if let Some(n) = limit {
+                        entries.truncate(n);
+                    }
+                }
             }
 
-            output_entries(&entries, &common, cli.no_color)?;
+            if ls {
+                let _timer = timings::phase("output");
+                let out = Destination::open(common.output.as_deref())?;
+                let no_color = cli.no_color || out.is_file();
+                let mut formatter = LsFormatter::new(Box::new(out.clone()), no_color);
+                for entry in &entries {
+                    formatter.write(entry)?;
+                }
+                formatter.finish()?;
+                out.finish()?;
+            } else {
+                output_entries(&entries, &common, cli.no_color)?;
+            }
         }
 
-        Commands::Tree {
-            path,
-            dirs_first,
-            common,
-        } => {
-            let config = build_traverse_config(&common, cli.quiet);
+        Commands::Tree { path, common } => {
+            common.reject_from_results("tree")?;
+            common.reject_reproducible("tree")?;
+            let config = build_traverse_config(&common, cli.quiet, types);
             let entries = walk_no_filter(&path, &config)?;
 
             // For tree view, use TreeFormatter
-            let stdout = io::stdout();
-            let stdout_lock = stdout.lock();
-            let mut formatter = TreeFormatter::new(Box::new(stdout_lock), cli.no_color, dirs_first);
+            let _timer = timings::phase("output");
+            let out = Destination::open(common.output.as_deref())?;
+            let no_color = cli.no_color || out.is_file();
+            let mut formatter =
+                TreeFormatter::new(Box::new(out.clone()), no_color, common.first_kind());
             formatter.write_tree(&entries)?;
+            out.finish()?;
         }
 
         Commands::Find {
-            path,
-            names,
-            regex,
-            ext,
-            min_size,
-            max_size,
-            after,
-            before,
-            kind,
-            category,
+            paths,
+            case_collisions,
+            sort,
+            baseline,
+            backend,
+            #[cfg(feature = "cache")]
+            no_cache,
+            filters,
             common,
         } => {
-            let config = build_traverse_config(&common, cli.quiet);
+            use rust_filesearch::fs::index::{search_index, SearchBackend};
+
+            let config = build_traverse_config(&common, cli.quiet, types);
+            // Shared by the size filter and the size column
+            let dir_sizes = std::sync::Arc::new(DirSizeCache::default());
+            let predicate = build_filter_predicate_sharing(&filters, types, &dir_sizes)?;
+            if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                return Ok(());
+            }
+            #[cfg(feature = "cache")]
+            let config = with_cache(config, no_cache);
+            let backend = SearchBackend::parse(&backend)?;
+
+            // The index only supplies candidates; filters still run on each one
+            let indexed = match common.path_list_source().or(common.from_results.clone()) {
+                Some(_) => None,
+                None => search_index(backend, &paths, &filters.names, &config),
+            };
+            let mut entries = match indexed {
+                Some(found) => {
+                    let mut entries = entries_from_paths(&found, &config, predicate.as_deref())?;
+                    enrich_entries(
+                        &mut entries,
+                        &enrichers_for(&common.enrich_columns()?, config.type_detection)?,
+                    )?;
+                    entries
+                }
+                None => collect_entries(&paths, &common, &config, predicate.as_deref())?,
+            };
+            entries = filter_exec(entries, &filters);
+            // Show (and sort by) the totals directories were judged by
+            if filters.dir_size {
+                for entry in entries.iter_mut().filter(|e| e.kind == EntryKind::Dir) {
+                    entry.size = dir_sizes.size_of(&entry.path);
+                }
+            }
+            let fields = match sort {
+                Some(sort) => parse_sort_fields(&sort, SortOrder::Asc)?,
+                None => Vec::new(),
+            };
+            sort_entries(&mut entries, fields, common.first_kind());
+            thin_entries(&mut entries, &common)?;
 
-            // Build combined predicate
-            let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
+            if case_collisions {
+                use rust_filesearch::fs::collisions::find_collisions;
 
-            if !names.is_empty() {
-                predicates.push(Box::new(GlobFilter::new(&names)?));
+                // Colliding names are listed next to each other
+                let groups = find_collisions(&entries);
+                if !cli.quiet {
+                    for group in &groups {
+                        let names: Vec<&str> =
+                            group.entries.iter().map(|e| e.name.as_str()).collect();
+                        eprintln!(
+                            "{}: {} differ only in {}",
+                            group.dir.display(),
+                            names.join(", "),
+                            group.kind
+                        );
+                    }
+                }
+                entries = groups.into_iter().flat_map(|g| g.entries).collect();
             }
 
-            if let Some(ref pattern) = regex {
-                predicates.push(Box::new(RegexFilter::new(pattern)?));
+            if let Some(baseline) = baseline {
+                use rust_filesearch::fs::baseline::{diff_entries, load_baseline, ChangeKind};
+                use rust_filesearch::util::format_size_human;
+                use std::io::Write;
+
+                let diffs = diff_entries(&load_baseline(&baseline)?, &entries);
+                let format = common.output_format()?;
+                let mut out = Destination::open(common.output.as_deref())?;
+                match format {
+                    OutputFormat::Json => {
+                        writeln!(out, "{}", serde_json::to_string_pretty(&diffs)?)?
+                    }
+                    OutputFormat::Ndjson => {
+                        for diff in &diffs {
+                            writeln!(out, "{}", serde_json::to_string(diff)?)?;
+                        }
+                    }
+                    OutputFormat::Pretty => {
+                        for diff in &diffs {
+                            let entry = &diff.entry;
+                            let size = match &diff.previous {
+                                Some(old) if old.size != entry.size => format!(
+                                    "{} (was {})",
+                                    format_size_human(entry.size),
+                                    format_size_human(old.size)
+                                ),
+                                _ => format_size_human(entry.size),
+                            };
+                            writeln!(
+                                out,
+                                "{} {}  {}  {}",
+                                diff.change.marker(),
+                                entry.path.display(),
+                                size,
+                                entry.mtime.format("%Y-%m-%d %H:%M:%S")
+                            )?;
+                        }
+
+                        if !cli.quiet {
+                            let count =
+                                |kind| diffs.iter().filter(|d| d.change == kind).count();
+                            writeln!(
+                                out,
+                                "\n{} added, {} removed, {} changed",
+                                count(ChangeKind::Added),
+                                count(ChangeKind::Removed),
+                                count(ChangeKind::Changed)
+                            )?;
+                        }
+                    }
+                    _ => {
+                        return Err(FsError::InvalidFormat {
+                            format: "--baseline supports pretty, json and ndjson output".to_string(),
+                        });
+                    }
+                }
+                return Ok(out.finish()?);
             }
 
-            if !ext.is_empty() {
-                predicates.push(Box::new(ExtensionFilter::new(&ext)));
+            output_entries(&entries, &common, cli.no_color)?;
+        }
+
+        Commands::Findx { permanent, args } => {
+            use rust_filesearch::fs::find_compat::{parse_find_args, FindAction};
+            use rust_filesearch::fs::trash;
+            use std::io::Write;
+
+            let expr = parse_find_args(&args)?;
+
+            // find(1) never skips hidden or gitignored files
+            let config = TraverseConfig {
+                max_depth: expr.max_depth,
+                include_hidden: true,
+                respect_gitignore: false,
+                quiet: cli.quiet,
+                ..Default::default()
+            };
+
+            let mut matches = Vec::new();
+            for root in &expr.paths {
+                let entries = walk(root, &config, Some(expr.predicate.as_ref()))?;
+                matches.extend(entries.into_iter().filter(|e| e.depth >= expr.min_depth));
             }
 
-            if min_size.is_some() || max_size.is_some() {
-                predicates.push(Box::new(SizeFilter::new(
-                    min_size.as_deref(),
-                    max_size.as_deref(),
-                )?));
+            match expr.action {
+                FindAction::Print | FindAction::Print0 => {
+                    let terminator = if expr.action == FindAction::Print0 {
+                        '\0'
+                    } else {
+                        '\n'
+                    };
+                    let stdout = io::stdout();
+                    let mut out = stdout.lock();
+                    for entry in &matches {
+                        write!(out, "{}{}", entry.path.display(), terminator)?;
+                    }
+                    out.flush()?;
+                }
+                FindAction::Delete => {
+                    // Like find -delete, remove children before their parents
+                    matches.sort_by_key(|e| std::cmp::Reverse(e.depth));
+
+                    let mut failed = 0;
+                    for entry in &matches {
+                        let result = if entry.kind != EntryKind::Dir {
+                            trash::remove(&entry.path, permanent)
+                        } else if permanent {
+                            std::fs::remove_dir(&entry.path)
+                        } else {
+                            // Only empty directories, as remove_dir would
+                            std::fs::read_dir(&entry.path).and_then(|mut children| {
+                                match children.next() {
+                                    Some(_) => Err(std::io::Error::new(
+                                        std::io::ErrorKind::DirectoryNotEmpty,
+                                        "Directory not empty",
+                                    )),
+                                    None => trash::remove(&entry.path, false),
+                                }
+                            })
+                        };
+
+                        if let Err(e) = result {
+                            failed += 1;
+                            warn!(path = %entry.path.display(), error = %e, "Failed to delete");
+                        }
+                    }
+
+                    if failed > 0 {
+                        return Err(FsError::InvalidFormat {
+                            format: format!("findx: failed to delete {} entries", failed),
+                        });
+                    }
+                }
             }
+        }
+
+        Commands::Chmod {
+            mode,
+            paths,
+            dry_run,
+            filters,
+            common,
+        } => {
+            common.reject_output("chmod")?;
+            common.reject_from_results("chmod")?;
+            common.reject_reproducible("chmod")?;
+            #[cfg(unix)]
+            {
+                use rust_filesearch::fs::perms::{chmod_entries, ModeSpec};
+
+                let spec = ModeSpec::parse(&mode)?;
+                let config = build_traverse_config(&common, cli.quiet, types);
+                let predicate = build_filter_predicate(&filters, types)?;
+                if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                    return Ok(());
+                }
+                let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+                let entries = filter_exec(entries, &filters);
 
-            if after.is_some() || before.is_some() {
-                predicates.push(Box::new(DateFilter::new(
-                    after.as_deref(),
-                    before.as_deref(),
-                )?));
+                let report = chmod_entries(&entries, &spec, dry_run);
+                print_batch_report(&report, dry_run, cli.quiet)?;
             }
 
-            if !kind.is_empty() {
-                let kinds = parse_entry_kinds(&kind)?;
-                predicates.push(Box::new(KindFilter::new(&kinds)));
+            #[cfg(not(unix))]
+            {
+                let _ = (mode, paths, dry_run, filters, common);
+                return Err(FsError::InvalidFormat {
+                    format: "chmod is only supported on Unix".to_string(),
+                });
             }
+        }
+
+        Commands::Chown {
+            owner,
+            paths,
+            dry_run,
+            filters,
+            common,
+        } => {
+            common.reject_output("chown")?;
+            common.reject_from_results("chown")?;
+            common.reject_reproducible("chown")?;
+            #[cfg(unix)]
+            {
+                use rust_filesearch::fs::perms::{chown_entries, OwnerSpec};
+
+                let spec = OwnerSpec::parse(&owner)?;
+                let config = build_traverse_config(&common, cli.quiet, types);
+                let predicate = build_filter_predicate(&filters, types)?;
+                if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                    return Ok(());
+                }
+                let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+                let entries = filter_exec(entries, &filters);
 
-            if let Some(cat) = category {
-                predicates.push(Box::new(CategoryFilter::new(&cat)));
+                let report = chown_entries(&entries, &spec, dry_run);
+                print_batch_report(&report, dry_run, cli.quiet)?;
             }
 
-            let entries = if !predicates.is_empty() {
-                let combined = AndPredicate::new(predicates);
-                walk(&path, &config, Some(&combined))?
-            } else {
-                walk_no_filter(&path, &config)?
-            };
-            output_entries(&entries, &common, cli.no_color)?;
+            #[cfg(not(unix))]
+            {
+                let _ = (owner, paths, dry_run, filters, common);
+                return Err(FsError::InvalidFormat {
+                    format: "chown is only supported on Unix".to_string(),
+                });
+            }
         }
 
-        Commands::Size {
-            path,
-            top,
-            aggregate,
-            du,
+        Commands::Touch {
+            paths,
+            set_mtime,
+            #[cfg(feature = "exif")]
+            from_exif,
+            #[cfg(feature = "git")]
+            from_git,
+            undo,
+            dry_run,
+            filters,
             common,
         } => {
-            let config = build_traverse_config(&common, cli.quiet);
-            let mut entries = walk_no_filter(&path, &config)?;
+            use rust_filesearch::fs::touch::{
+                touch_entries, undo_touch, MtimeSource, TouchJournal,
+            };
 
-            if aggregate || du {
-                // Compute directory sizes
-                let dir_sizes = compute_dir_sizes(&entries);
-                update_entries_with_dir_sizes(&mut entries, &dir_sizes);
+            common.reject_output("touch")?;
+            common.reject_from_results("touch")?;
+            common.reject_reproducible("touch")?;
+            if let Some(journal_path) = undo {
+                let journal_path = match journal_path {
+                    Some(path) => path,
+                    None => TouchJournal::latest()?.ok_or_else(|| FsError::InvalidFormat {
+                        format: "No touch journal found to undo".to_string(),
+                    })?,
+                };
+
+                let journal = TouchJournal::load(&journal_path)?;
+                let report = undo_touch(&journal, dry_run);
+                if !dry_run && report.errors.is_empty() {
+                    // A journal is only undone once
+                    let _ = std::fs::remove_file(&journal_path);
+                }
+                return print_batch_report(&report, dry_run, cli.quiet);
             }
 
-            // Filter to top N if requested
-            if let Some(n) = top {
-                entries = get_top_by_size(&entries, n);
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let predicate = build_filter_predicate(&filters, types)?;
+            if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                return Ok(());
             }
+            let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+            let entries = filter_exec(entries, &filters);
 
-            // Sort by size descending for size command
-            entries.sort_by(|a, b| b.size.cmp(&a.size));
+            let mut target: Option<MtimeSource> = None;
 
-            output_entries(&entries, &common, cli.no_color)?;
+            if let Some(date) = &set_mtime {
+                let time = rust_filesearch::util::parse_date(date)?;
+                target = Some(Box::new(move |_| Ok(time)));
+            }
+
+            #[cfg(feature = "exif")]
+            if from_exif {
+                use rust_filesearch::fs::touch::exif_datetime;
+                target = Some(Box::new(|entry| exif_datetime(&entry.path)));
+            }
+
+            #[cfg(feature = "git")]
+            if from_git {
+                use rust_filesearch::fs::git::last_commit_times;
+
+                let mut times = std::collections::HashMap::new();
+                for path in &paths {
+                    let dir = match path.parent() {
+                        _ if path.is_dir() => path.as_path(),
+                        Some(parent) if !parent.as_os_str().is_empty() => parent,
+                        _ => Path::new("."),
+                    };
+                    times.extend(last_commit_times(dir)?);
+                }
+
+                target = Some(Box::new(move |entry| {
+                    let path = entry.path.canonicalize().map_err(|e| e.to_string())?;
+                    times
+                        .get(&path)
+                        .copied()
+                        .ok_or_else(|| "not tracked in git".to_string())
+                }));
+            }
+
+            let target = target.ok_or_else(|| FsError::InvalidFormat {
+                format: "touch needs a time source".to_string(),
+            })?;
+
+            let (report, journal) = touch_entries(&entries, target, dry_run);
+            if !dry_run && !journal.entries.is_empty() {
+                let journal_path = journal.save()?;
+                if !cli.quiet {
+                    eprintln!(
+                        "Journal written to {} (undo with `fexplorer touch --undo`)",
+                        journal_path.display()
+                    );
+                }
+            }
+            print_batch_report(&report, dry_run, cli.quiet)?;
         }
 
-        #[cfg(feature = "grep")]
-        Commands::Grep {
-            path,
-            pattern,
-            regex,
-            case_insensitive,
-            ext,
-            context,
-            line_numbers,
+        #[cfg(feature = "archive")]
+        Commands::Archive {
+            paths,
+            level,
+            filters,
             common,
         } => {
-            use rust_filesearch::fs::content::{search_files, ContentSearcher};
+            use rust_filesearch::fs::archive::{create_archive, ArchiveFormat};
+
+            common.reject_from_results("archive")?;
+            common.reject_reproducible("archive")?;
+            let output = common
+                .output
+                .clone()
+                .ok_or_else(|| FsError::InvalidFormat {
+                    format: "archive needs --output <ARCHIVE>".to_string(),
+                })?;
+            // --format is shared with the listing commands, where it defaults to pretty
+            let name = Some(common.format.as_str()).filter(|name| *name != "pretty");
+            let format = ArchiveFormat::for_output(name, &output)?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let predicate = build_filter_predicate(&filters, types)?;
+            if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                return Ok(());
+            }
+            let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+            let entries = filter_exec(entries, &filters);
 
-            let config = build_traverse_config(&common, cli.quiet);
+            let summary = create_archive(&entries, &paths, &output, format, level, |_| {})?;
+            print_archive_summary(&summary, &output)?;
+        }
 
-            // Build extension filter if provided
-            let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
-            if !ext.is_empty() {
-                predicates.push(Box::new(ExtensionFilter::new(&ext)));
+        #[cfg(feature = "archive")]
+        Commands::Pack {
+            paths,
+            into,
+            remove,
+            permanent,
+            level,
+            dry_run,
+            filters,
+            common,
+        } => {
+            use rust_filesearch::fs::pack::{
+                pack_entries, remove_packed, verify_archive, Compression,
+            };
+            use rust_filesearch::util::format_size_human;
+
+            common.reject_output("pack")?;
+            common.reject_from_results("pack")?;
+            common.reject_reproducible("pack")?;
+            Compression::from_path(&into)?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let predicate = build_filter_predicate(&filters, types)?;
+            if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                return Ok(());
+            }
+            let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+            let entries = filter_exec(entries, &filters);
+            let files: Vec<Entry> = entries
+                .into_iter()
+                .filter(|e| e.kind == EntryKind::File)
+                .collect();
+
+            if files.is_empty() {
+                return Err(FsError::NoEntriesFound);
             }
 
-            // Get files to search
-            let entries = if !predicates.is_empty() {
-                let combined = AndPredicate::new(predicates);
-                walk(&path, &config, Some(&combined))?
-            } else {
-                walk_no_filter(&path, &config)?
+            if dry_run {
+                for file in &files {
+                    println!("would pack {}", file.path.display());
+                }
+                let total: u64 = files.iter().map(|f| f.size).sum();
+                println!();
+                println!(
+                    "{} files ({}) would be packed into {}",
+                    files.len(),
+                    format_size_human(total),
+                    into.display()
+                );
+                return Ok(());
+            }
+
+            #[cfg(feature = "progress")]
+            let progress = {
+                use std::io::IsTerminal;
+
+                let total: u64 = files.iter().map(|f| f.size).sum();
+                let bar = if cli.quiet || !io::stderr().is_terminal() {
+                    indicatif::ProgressBar::hidden()
+                } else {
+                    indicatif::ProgressBar::new(total)
+                };
+                bar.set_style(
+                    indicatif::ProgressStyle::with_template(
+                        "{bar:40} {bytes}/{total_bytes} ({eta})",
+                    )
+                    .unwrap_or_else(|_| indicatif::ProgressStyle::default_bar()),
+                );
+                bar
             };
 
-            // Create searcher
-            let searcher =
-                ContentSearcher::new(&pattern, regex, case_insensitive, context, line_numbers)?;
+            let manifest = pack_entries(&files, &into, level, |_size| {
+                #[cfg(feature = "progress")]
+                progress.inc(_size);
+            })?;
 
-            // Search files
-            let matches = search_files(&entries, &searcher)?;
+            #[cfg(feature = "progress")]
+            progress.finish_and_clear();
+
+            verify_archive(&manifest)?;
+            let manifest_path = manifest.save()?;
+
+            let archive_size = std::fs::metadata(&into).map(|m| m.len()).unwrap_or(0);
+            println!(
+                "Packed {} files ({}) into {} ({}), verified",
+                manifest.files.len(),
+                format_size_human(manifest.total_size),
+                into.display(),
+                format_size_human(archive_size)
+            );
+            println!("Manifest: {}", manifest_path.display());
+
+            if remove {
+                let (removed, kept) = remove_packed(&manifest, permanent);
+                for (path, reason) in &kept {
+                    warn!(path = %path.display(), reason = %reason, "Not removed");
+                }
+                if permanent {
+                    println!("Removed {} original files", removed);
+                } else {
+                    println!("Moved {} original files to the trash", removed);
+                }
+            }
+        }
 
-            // Output matches
-            if matches.is_empty() {
-                if !cli.quiet {
-                    println!("No matches found");
+        #[cfg(feature = "object")]
+        Commands::BinInfo { files, format } => {
+            use rust_filesearch::fs::bininfo::analyze_binary;
+            use rust_filesearch::util::format_size_human;
+
+            let infos = files
+                .iter()
+                .map(|file| analyze_binary(file))
+                .collect::<Result<Vec<_>>>()?;
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&infos)?);
+                return Ok(());
+            }
+
+            for (i, info) in infos.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                let yes_no = |b: bool| if b { "yes" } else { "no" };
+                println!("{}", info.path.display());
+                println!(
+                    "  Format:        {} ({}-bit, {})",
+                    info.format,
+                    info.bits,
+                    format_size_human(info.size)
+                );
+                println!("  Architecture:  {}", info.architecture);
+                println!("  Stripped:      {}", yes_no(info.stripped));
+                println!("  Debug info:    {}", yes_no(info.debug_info));
+                println!("  Sections:");
+                for section in &info.sections {
+                    println!(
+                        "    {:<24} {:>10}  {:>5.1}%",
+                        section.name,
+                        format_size_human(section.size),
+                        section.size as f64 * 100.0 / info.size.max(1) as f64
+                    );
                 }
+            }
+        }
+
+        Commands::DevCaches {
+            paths,
+            clean,
+            dry_run,
+            permanent,
+            common,
+        } => {
+            use rust_filesearch::fs::devcache::{
+                clean_cache, find_target_dirs, scan_caches, CacheKind, CacheRoots,
+            };
+            use rust_filesearch::util::format_size_human;
+
+            common.reject_output("dev-caches")?;
+            common.reject_from_results("dev-caches")?;
+            common.reject_reproducible("dev-caches")?;
+            let clean_kinds: Vec<CacheKind> = if clean.iter().any(|c| c == "all") {
+                CacheKind::ALL
+                    .iter()
+                    .copied()
+                    .filter(|k| k.can_clean())
+                    .collect()
             } else {
-                for m in &matches {
-                    if line_numbers {
+                clean
+                    .iter()
+                    .map(|c| CacheKind::parse(c))
+                    .collect::<Result<_>>()?
+            };
+
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let targets = find_target_dirs(&paths, &config);
+            let caches = scan_caches(&CacheRoots::detect()?, &targets, &config);
+
+            if clean_kinds.is_empty() {
+                match common.output_format()? {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&caches)?),
+                    OutputFormat::Ndjson => {
+                        for cache in &caches {
+                            println!("{}", serde_json::to_string(cache)?);
+                        }
+                    }
+                    OutputFormat::Pretty if caches.is_empty() => {
+                        println!("No developer caches found.");
+                    }
+                    OutputFormat::Pretty => {
+                        for cache in &caches {
+                            println!(
+                                "{:<15} {:>10}  {}",
+                                cache.kind.id(),
+                                format_size_human(cache.size),
+                                cache.path.display()
+                            );
+                            println!("  {}", cache.guidance);
+                        }
+                        let total: u64 = caches.iter().map(|c| c.size).sum();
+                        println!();
                         println!(
-                            "{}:{}:{}: {}",
-                            m.entry.path.display(),
-                            m.line_number,
-                            m.column,
-                            m.matched_text
+                            "Total: {} in {} caches",
+                            format_size_human(total),
+                            caches.len()
                         );
-                    } else {
-                        println!("{}: {}", m.entry.path.display(), m.matched_text);
+                        println!("Clean up with --clean <TYPE>[,<TYPE>...] (--dry-run to preview)");
                     }
+                    _ => {
+                        return Err(FsError::InvalidFormat {
+                            format: "dev-caches supports pretty, json and ndjson output"
+                                .to_string(),
+                        });
+                    }
+                }
+                return Ok(());
+            }
 
-                    // Print context if requested
-                    if !m.context_before.is_empty() {
-                        for (i, line) in m.context_before.iter().enumerate() {
-                            let line_num = m.line_number - m.context_before.len() + i;
-                            println!("  {}-  {}", line_num, line);
+            let mut freed = 0u64;
+            let mut errors = 0usize;
+            for cache in caches.iter().filter(|c| clean_kinds.contains(&c.kind)) {
+                if !cache.kind.can_clean() {
+                    eprintln!("skipped {}: {}", cache.path.display(), cache.guidance);
+                } else if dry_run {
+                    println!(
+                        "would remove {} ({})",
+                        cache.path.display(),
+                        format_size_human(cache.size)
+                    );
+                    freed += cache.size;
+                } else {
+                    match clean_cache(cache, permanent) {
+                        Ok(()) => {
+                            println!(
+                                "{} {} ({})",
+                                if permanent { "removed" } else { "trashed" },
+                                cache.path.display(),
+                                format_size_human(cache.size)
+                            );
+                            freed += cache.size;
                         }
-                    }
-                    if !m.context_after.is_empty() {
-                        for (i, line) in m.context_after.iter().enumerate() {
-                            let line_num = m.line_number + i + 1;
-                            println!("  {}+  {}", line_num, line);
+                        Err(e) => {
+                            eprintln!("error {}: {}", cache.path.display(), e);
+                            errors += 1;
                         }
                     }
                 }
+            }
 
-                println!(
-                    "\nFound {} matches in {} files",
-                    matches.len(),
-                    matches
-                        .iter()
-                        .map(|m| &m.entry.path)
-                        .collect::<std::collections::HashSet<_>>()
-                        .len()
-                );
+            println!();
+            let verb = if dry_run {
+                "Would free"
+            } else if permanent {
+                "Freed"
+            } else {
+                "Trashed"
+            };
+            println!("{} {}", verb, format_size_human(freed));
+            if errors > 0 {
+                return Err(FsError::InvalidFormat {
+                    format: format!("{} caches could not be removed", errors),
+                });
             }
         }
 
-        #[cfg(feature = "dedup")]
-        Commands::Duplicates {
-            path,
-            min_size,
-            summary,
-            common,
+        #[cfg(all(feature = "docker", unix))]
+        Commands::DockerUsage {
+            socket,
+            storage,
+            items,
+            format,
         } => {
-            use rust_filesearch::fs::dedup::{find_duplicates, DuplicateStats};
-            use rust_filesearch::util::parse_size;
-
-            let config = build_traverse_config(&common, cli.quiet);
-            let entries = walk_no_filter(&path, &config)?;
+            use rust_filesearch::fs::docker::{
+                find_socket, find_storage_dirs, usage_from_socket, usage_from_storage,
+            };
+            use rust_filesearch::util::format_size_human;
 
-            // Parse min size
-            let min_size_bytes = parse_size(&min_size)?;
+            // The daemon knows what is in use; the storage directory is the fallback
+            let config = TraverseConfig {
+                quiet: cli.quiet,
+                ..Default::default()
+            };
+            let usages = match (storage, socket.or_else(find_socket)) {
+                (Some(storage), _) => vec![usage_from_storage(&storage, &config)?],
+                (None, Some(socket)) => vec![usage_from_socket(&socket)?],
+                (None, None) => {
+                    let dirs = find_storage_dirs();
+                    if dirs.is_empty() {
+                        return Err(FsError::InvalidFormat {
+                            format: "No Docker or Podman socket or storage directory found"
+                                .to_string(),
+                        });
+                    }
+                    dirs.iter()
+                        .map(|dir| usage_from_storage(dir, &config))
+                        .collect::<Result<Vec<_>>>()?
+                }
+            };
 
-            // Find duplicates
-            let groups = find_duplicates(&entries, min_size_bytes)?;
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&usages)?);
+                return Ok(());
+            }
 
-            if groups.is_empty() {
-                if !cli.quiet {
-                    println!("No duplicate files found");
+            for (i, usage) in usages.iter().enumerate() {
+                if i > 0 {
+                    println!();
                 }
-            } else if summary {
-                // Show summary statistics
-                let stats = DuplicateStats::from_groups(&groups);
-                println!("Duplicate Files Summary:");
-                println!("  Total duplicate groups: {}", stats.total_groups);
-                println!("  Total duplicate files: {}", stats.total_files);
-                println!(
-                    "  Total wasted space: {}",
-                    humansize::format_size(stats.total_wasted_space, humansize::BINARY)
-                );
+                println!("{}", usage.source.display());
                 println!(
-                    "  Largest group wasted space: {}",
-                    humansize::format_size(stats.largest_group_size, humansize::BINARY)
+                    "  {:<12} {:>6} {:>12} {:>12}",
+                    "TYPE", "COUNT", "SIZE", "RECLAIMABLE"
                 );
-                println!("  Largest group file count: {}", stats.largest_group_count);
-            } else {
-                // Show detailed groups
-                for (i, group) in groups.iter().enumerate() {
-                    println!(
-                        "\nDuplicate Group #{} (hash: {}...)",
-                        i + 1,
-                        &group.hash[..8]
-                    );
+                for summary in &usage.summary {
                     println!(
-                        "  File size: {}",
-                        humansize::format_size(group.size, humansize::BINARY)
-                    );
-                    println!("  Count: {} files", group.count);
-                    println!(
-                        "  Wasted space: {}",
-                        humansize::format_size(group.wasted_space, humansize::BINARY)
+                        "  {:<12} {:>6} {:>12} {:>12}",
+                        summary.kind.label(),
+                        summary.count,
+                        format_size_human(summary.size),
+                        summary
+                            .reclaimable
+                            .map_or("-".to_string(), format_size_human)
                     );
-                    println!("  Files:");
-                    for entry in &group.entries {
-                        println!("    - {}", entry.path.display());
-                    }
                 }
-
-                let stats = DuplicateStats::from_groups(&groups);
                 println!(
+                    "  {:<12} {:>6} {:>12}",
+                    "Total",
+                    "",
+                    format_size_human(usage.total())
+                );
+
+                if items {
+                    // Only the daemon knows what is in use
+                    let known = usage.summary.iter().any(|s| s.reclaimable.is_some());
+                    println!();
+                    for item in &usage.items {
+                        println!(
+                            "  {:>10}  {:<12} {}{}",
+                            format_size_human(item.size),
+                            item.kind.label(),
+                            item.name,
+                            if known && !item.in_use {
+                                "  (unused)"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                }
+            }
+        }
+
+        Commands::Size {
+            paths,
+            top,
+            aggregate,
+            du,
+            human_readable,
+            apparent_size,
+            all,
+            newest_child,
+            link_targets,
+            #[cfg(feature = "cache")]
+            no_cache,
+            mut common,
+        } => {
+            if du {
+                common.reject_reproducible("size --du")?;
+                // Like du, count everything and only limit what gets printed
+                let print_depth = common.max_depth.take();
+                common.hidden = true;
+                common.no_gitignore = true;
+                let config = build_traverse_config(&common, cli.quiet, types);
+                #[cfg(feature = "cache")]
+                let config = with_cache(config, no_cache);
+                let mut entries = collect_entries::<dyn Predicate>(&paths, &common, &config, None)?;
+
+                let du_sizes = compute_du_sizes(&entries, apparent_size);
+                for entry in entries.iter_mut() {
+                    entry.size = du_sizes.get(&entry.path).copied().unwrap_or(0);
+                }
+
+                entries.retain(|e| {
+                    (all || e.kind == EntryKind::Dir || e.depth == 0)
+                        && print_depth.is_none_or(|d| e.depth <= d)
+                });
+
+                if let Some(n) = top {
+                    entries = get_top_by_size(&entries, n);
+                } else {
+                    // Each root starts a new run at depth 0; keep roots in argument order
+                    for root_entries in entries.chunk_by_mut(|_, b| b.depth != 0) {
+                        sort_du_order(root_entries);
+                    }
+                }
+
+                let _timer = timings::phase("output");
+                let out = Destination::open(common.output.as_deref())?;
+                let mut formatter = DuFormatter::new(Box::new(out.clone()), human_readable);
+                for entry in &entries {
+                    formatter.write(entry)?;
+                }
+                formatter.finish()?;
+                out.finish()?;
+            } else {
+                let config = build_traverse_config(&common, cli.quiet, types);
+                #[cfg(feature = "cache")]
+                let config = with_cache(config, no_cache);
+                let mut entries = collect_entries::<dyn Predicate>(&paths, &common, &config, None)?;
+
+                if link_targets {
+                    resolve_link_targets(&mut entries);
+                }
+
+                let mut enrichers: Vec<Box<dyn Enricher>> = Vec::new();
+                if aggregate || newest_child {
+                    enrichers.push(Box::new(DirSizeEnricher));
+                }
+                if newest_child {
+                    enrichers.push(Box::new(LastActivityEnricher));
+                }
+                enrich_entries(&mut entries, &enrichers)?;
+
+                if newest_child {
+                    entries.retain(|e| e.kind == EntryKind::Dir);
+
+                    if common.columns.is_empty() {
+                        common.columns = vec![
+                            "path".to_string(),
+                            "size".to_string(),
+                            "last_activity".to_string(),
+                        ];
+                    }
+                }
+
+                if thins_entries(&common) {
+                    // Keep the largest entries of each directory or key
+                    entries.sort_by_key(|e| std::cmp::Reverse(e.size));
+                    thin_entries(&mut entries, &common)?;
+                }
+
+                // Filter to top N if requested
+                if let Some(n) = top {
+                    entries = get_top_by_size(&entries, n);
+                }
+
+                {
+                    let _timer = timings::phase("sort");
+                    if newest_child {
+                        // Least recently active directories first
+                        entries.sort_by_key(|e| e.last_activity);
+                    } else {
+                        // Sort by size descending for size command
+                        entries.sort_by_key(|e| std::cmp::Reverse(e.size));
+                    }
+                }
+                sort_entries(&mut entries, Vec::new(), common.first_kind());
+
+                output_entries(&entries, &common, cli.no_color)?;
+            }
+        }
+
+        #[cfg(feature = "grep")]
+        Commands::Grep {
+            paths,
+            pattern,
+            regex,
+            case_insensitive,
+            fixed_strings: _,
+            word_regexp,
+            multiline,
+            ext,
+            context,
+            line_numbers,
+            max_filesize,
+            binary,
+            replace,
+            dry_run,
+            common,
+        } => {
+            use rust_filesearch::fs::content::{
+                replace_files, search_files, ContentSearcher, FileFilter, PatternOptions,
+            };
+            use rust_filesearch::fs::gitignore::GitignoreChecker;
+            use rust_filesearch::models::ContentMatch;
+            use rust_filesearch::output::grep::{
+                MatchCsvFormatter, MatchJsonFormatter, MatchNdjsonFormatter, MatchPrettyFormatter,
+            };
+            use rust_filesearch::util::parse_size;
+            use std::io::Write;
+
+            common.reject_reproducible("grep")?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+
+            // Build extension filter if provided
+            let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
+            if !ext.is_empty() {
+                predicates.push(Box::new(ExtensionFilter::new(&ext)));
+            }
+
+            // Get files to search
+            let entries = if !predicates.is_empty() {
+                let combined = AndPredicate::new(predicates);
+                collect_entries(&paths, &common, &config, Some(&combined))?
+            } else {
+                collect_entries::<dyn Predicate>(&paths, &common, &config, None)?
+            };
+
+            // Create searcher
+            let options = PatternOptions {
+                regex,
+                case_insensitive,
+                word: word_regexp,
+                multiline,
+            };
+            let mut searcher =
+                ContentSearcher::with_options(&pattern, options, context, line_numbers)?;
+            if binary {
+                searcher = searcher.with_binary();
+            }
+
+            // Walks already skip hidden and ignored files; path lists and
+            // exported results are checked file by file
+            let walked = common.from_results.is_none()
+                && common.path_list_source().is_none()
+                && paths != [Path::new("-")];
+            let filter = FileFilter {
+                max_filesize: max_filesize.as_deref().map(parse_size).transpose()?,
+                skip_hidden: !walked && !config.include_hidden,
+                gitignore: (!walked && config.respect_gitignore).then(GitignoreChecker::new),
+            };
+
+            if let Some(replacement) = replace {
+                let report = replace_files(&entries, &searcher, &filter, &replacement, dry_run);
+                return print_replace_report(&report, dry_run, cli.quiet);
+            }
+
+            // Search files
+            let matches = search_files(&entries, &searcher, &filter)?;
+            // An unknown format mustn't truncate an existing --output file
+            let format = common.output_format()?;
+            let destination = Destination::open(common.output.as_deref())?;
+            let writer: Box<dyn io::Write> = Box::new(destination.clone());
+            // Files only get colors with --color always
+            let no_color = cli.no_color || destination.is_file();
+
+            let mut sink: Box<dyn OutputSink<ContentMatch>> = match format {
+                OutputFormat::Pretty => {
+                    Box::new(MatchPrettyFormatter::new(writer, no_color, line_numbers))
+                }
+                OutputFormat::Json => Box::new(MatchJsonFormatter::new(writer)),
+                OutputFormat::Ndjson => Box::new(MatchNdjsonFormatter::new(writer)),
+                OutputFormat::Csv => Box::new(MatchCsvFormatter::new(writer)?),
+                _ => {
+                    return Err(FsError::InvalidFormat {
+                        format: format!(
+                            "grep supports pretty, json, ndjson and csv output, not {}",
+                            common.format
+                        ),
+                    });
+                }
+            };
+
+            if matches.is_empty() && format == OutputFormat::Pretty && !cli.quiet {
+                let mut out = destination.clone();
+                writeln!(out, "{}", tr(Msg::NoMatches))?;
+            }
+            for m in &matches {
+                sink.write(m)?;
+            }
+            sink.finish()?;
+            destination.finish()?;
+        }
+
+        #[cfg(feature = "dedup")]
+        Commands::Duplicates {
+            path,
+            min_size,
+            summary,
+            skip_linked,
+            fuzzy,
+            similarity,
+            reflink,
+            dry_run,
+            common,
+        } => {
+            use rust_filesearch::fs::dedup::{find_duplicates, DuplicateStats};
+            use rust_filesearch::util::parse_size;
+            use std::io::Write;
+
+            common.reject_reproducible("duplicates")?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let entries = collect_entries::<dyn Predicate>(
+                std::slice::from_ref(&path),
+                &common,
+                &config,
+                None,
+            )?;
+
+            // Parse min size
+            let min_size_bytes = parse_size(&min_size)?;
+
+            if fuzzy {
+                use rust_filesearch::fs::fuzzy::{find_fuzzy_duplicates, ChunkerConfig};
+
+                if !(0.0..=100.0).contains(&similarity) {
+                    return Err(FsError::InvalidFormat {
+                        format: format!("--similarity must be 0-100, got {}", similarity),
+                    });
+                }
+                let pairs = find_fuzzy_duplicates(
+                    &entries,
+                    min_size_bytes,
+                    similarity / 100.0,
+                    &ChunkerConfig::default(),
+                )?;
+                let mut out = Destination::open(common.output.as_deref())?;
+
+                if pairs.is_empty() {
+                    if !cli.quiet {
+                        writeln!(out, "{}", tr(Msg::NoNearDuplicates))?;
+                    }
+                } else if !summary {
+                    for (i, pair) in pairs.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "\nNear-duplicate pair #{} ({:.0}% shared)",
+                            i + 1,
+                            pair.similarity * 100.0
+                        )?;
+                        for entry in [&pair.first, &pair.second] {
+                            writeln!(
+                                out,
+                                "    - {} ({})",
+                                entry.path.display(),
+                                humansize::format_size(entry.size, humansize::BINARY)
+                            )?;
+                        }
+                        writeln!(
+                            out,
+                            "  Dedupable: {}",
+                            humansize::format_size(pair.shared_bytes, humansize::BINARY)
+                        )?;
+                    }
+                }
+                if !pairs.is_empty() {
+                    let total: u64 = pairs.iter().map(|p| p.shared_bytes).sum();
+                    writeln!(
+                        out,
+                        "\nTotal: {} pairs, {} dedupable (pairs may overlap)",
+                        pairs.len(),
+                        humansize::format_size(total, humansize::BINARY)
+                    )?;
+                }
+                return Ok(out.finish()?);
+            }
+
+            // Find duplicates
+            let mut groups = find_duplicates(&entries, min_size_bytes)?;
+            if skip_linked {
+                groups.retain(|group| !group.already_linked());
+            }
+
+            if reflink {
+                use rust_filesearch::fs::reflink::clone_duplicates;
+
+                let report = clone_duplicates(&groups, dry_run);
+                return print_batch_report(&report, dry_run, cli.quiet);
+            }
+
+            let mut out = Destination::open(common.output.as_deref())?;
+            if groups.is_empty() {
+                if !cli.quiet {
+                    writeln!(out, "{}", tr(Msg::NoDuplicates))?;
+                }
+            } else if summary {
+                // Show summary statistics
+                let stats = DuplicateStats::from_groups(&groups);
+                writeln!(out, "Duplicate Files Summary:")?;
+                writeln!(out, "  Total duplicate groups: {}", stats.total_groups)?;
+                writeln!(out, "  Total duplicate files: {}", stats.total_files)?;
+                writeln!(
+                    out,
+                    "  Total wasted space: {}",
+                    humansize::format_size(stats.total_wasted_space, humansize::BINARY)
+                )?;
+                writeln!(
+                    out,
+                    "  Largest group wasted space: {}",
+                    humansize::format_size(stats.largest_group_size, humansize::BINARY)
+                )?;
+                writeln!(
+                    out,
+                    "  Largest group file count: {}",
+                    stats.largest_group_count
+                )?;
+            } else {
+                // Show detailed groups
+                for (i, group) in groups.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "\nDuplicate Group #{} (hash: {}...)",
+                        i + 1,
+                        &group.hash[..8]
+                    )?;
+                    writeln!(
+                        out,
+                        "  File size: {}",
+                        humansize::format_size(group.size, humansize::BINARY)
+                    )?;
+                    if group.copies < group.count {
+                        writeln!(
+                            out,
+                            "  Count: {} files, {} on disk (the rest are hard links)",
+                            group.count, group.copies
+                        )?;
+                    } else {
+                        writeln!(out, "  Count: {} files", group.count)?;
+                    }
+                    writeln!(
+                        out,
+                        "  Wasted space: {}{}",
+                        humansize::format_size(group.wasted_space, humansize::BINARY),
+                        if group.already_linked() {
+                            " (already linked)"
+                        } else {
+                            ""
+                        }
+                    )?;
+                    writeln!(out, "  Files:")?;
+                    for entry in &group.entries {
+                        writeln!(out, "    - {}", entry.path.display())?;
+                    }
+                }
+
+                let stats = DuplicateStats::from_groups(&groups);
+                writeln!(
+                    out,
                     "\nTotal: {} groups, {} files, {} wasted",
                     stats.total_groups,
                     stats.total_files,
                     humansize::format_size(stats.total_wasted_space, humansize::BINARY)
-                );
+                )?;
+            }
+            out.finish()?;
+        }
+
+        Commands::Similar {
+            paths,
+            max_distance,
+            across_dirs,
+            filters,
+            common,
+        } => {
+            use rust_filesearch::fs::similar::{find_similar_names, SimilarOptions};
+            use rust_filesearch::util::format_size_human;
+            use std::io::Write;
+
+            common.reject_from_results("similar")?;
+            common.reject_reproducible("similar")?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let predicate = build_filter_predicate(&filters, types)?;
+            if explain_plan(&paths, &filters, &common, &config, predicate.as_deref())? {
+                return Ok(());
+            }
+            let entries = collect_entries(&paths, &common, &config, predicate.as_deref())?;
+            let entries = filter_exec(entries, &filters);
+
+            let options = SimilarOptions {
+                max_distance,
+                across_dirs,
+            };
+            let groups = find_similar_names(&entries, &options);
+
+            let format = common.output_format()?;
+            let mut out = Destination::open(common.output.as_deref())?;
+            if matches!(format, OutputFormat::Json) {
+                writeln!(out, "{}", serde_json::to_string_pretty(&groups)?)?;
+            } else if groups.is_empty() {
+                if !cli.quiet {
+                    writeln!(out, "{}", tr(Msg::NoSimilarNames))?;
+                }
+            } else {
+                for (i, group) in groups.iter().enumerate() {
+                    writeln!(out, "\nSimilar Group #{} ({})", i + 1, group.key)?;
+                    for entry in &group.entries {
+                        writeln!(
+                            out,
+                            "    - {}  {}  {}",
+                            entry.path.display(),
+                            format_size_human(entry.size),
+                            entry.mtime.format("%Y-%m-%d %H:%M")
+                        )?;
+                    }
+                }
+
+                let files: usize = groups.iter().map(|g| g.entries.len()).sum();
+                writeln!(out, "\nTotal: {} groups, {} files", groups.len(), files)?;
+            }
+            out.finish()?;
+        }
+
+        Commands::Lint {
+            paths,
+            rules,
+            skip_rules,
+            list_rules,
+            max_file_size,
+            common,
+        } => {
+            use rust_filesearch::fs::lint::{
+                default_rules, run_rules, select_rules, LintContext, LintOptions, Severity,
+            };
+            use rust_filesearch::util::parse_size;
+            use std::io::Write;
+
+            common.reject_from_results("lint")?;
+            common.reject_reproducible("lint")?;
+            let options = LintOptions {
+                max_file_size: parse_size(&max_file_size)?,
+            };
+            let rules = select_rules(default_rules(&options), &rules, &skip_rules)?;
+
+            if list_rules {
+                for rule in &rules {
+                    println!("{:<22} {}", rule.id(), rule.description());
+                }
+                return Ok(());
+            }
+
+            let format = common.output_format()?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let entries = collect_entries::<dyn Predicate>(&paths, &common, &config, None)?;
+            let findings = run_rules(
+                &rules,
+                &LintContext {
+                    roots: &paths,
+                    entries: &entries,
+                },
+            );
+
+            let mut out = Destination::open(common.output.as_deref())?;
+            match format {
+                OutputFormat::Json => {
+                    writeln!(out, "{}", serde_json::to_string_pretty(&findings)?)?
+                }
+                OutputFormat::Ndjson => {
+                    for finding in &findings {
+                        writeln!(out, "{}", serde_json::to_string(finding)?)?;
+                    }
+                }
+                OutputFormat::Gha => {
+                    for finding in &findings {
+                        writeln!(out, "{}", Annotation::from(finding))?;
+                    }
+                }
+                OutputFormat::Sarif => {
+                    let rules: Vec<SarifRule> =
+                        rules.iter().map(|rule| rule.as_ref().into()).collect();
+                    let results: Vec<SarifResult> = findings.iter().map(Into::into).collect();
+                    writeln!(
+                        out,
+                        "{}",
+                        serde_json::to_string_pretty(&sarif_log(&rules, &results))?
+                    )?;
+                }
+                _ => {
+                    for finding in &findings {
+                        let location = match finding.line {
+                            Some(line) => format!("{}:{}", finding.path.display(), line),
+                            None => finding.path.display().to_string(),
+                        };
+                        writeln!(
+                            out,
+                            "{}: {} [{}] {}",
+                            location, finding.severity, finding.rule, finding.message
+                        )?;
+                    }
+                    if !cli.quiet {
+                        if findings.is_empty() {
+                            writeln!(out, "No problems found")?;
+                        } else {
+                            writeln!(out, "\n{} problems found", findings.len())?;
+                        }
+                    }
+                }
+            }
+
+            out.finish()?;
+
+            let errors = findings
+                .iter()
+                .filter(|f| f.severity == Severity::Error)
+                .count();
+            if errors > 0 {
+                return Err(FsError::InvalidFormat {
+                    format: format!("{} lint errors", errors),
+                });
+            }
+        }
+
+        Commands::Portability {
+            paths,
+            max_path,
+            common,
+        } => {
+            use rust_filesearch::fs::portability::{check_entries, IssueKind, PortabilityOptions};
+            use std::io::Write;
+
+            common.reject_from_results("portability")?;
+            common.reject_reproducible("portability")?;
+            let config = build_traverse_config(&common, cli.quiet, types);
+            let entries = collect_entries::<dyn Predicate>(&paths, &common, &config, None)?;
+            let issues = check_entries(&entries, &paths, &PortabilityOptions { max_path });
+
+            let format = common.output_format()?;
+            let mut out = Destination::open(common.output.as_deref())?;
+            match format {
+                OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&issues)?)?,
+                OutputFormat::Ndjson => {
+                    for issue in &issues {
+                        writeln!(out, "{}", serde_json::to_string(issue)?)?;
+                    }
+                }
+                OutputFormat::Gha => {
+                    for issue in &issues {
+                        writeln!(out, "{}", Annotation::from(issue))?;
+                    }
+                }
+                OutputFormat::Sarif => {
+                    let rules: Vec<SarifRule> =
+                        IssueKind::ALL.into_iter().map(Into::into).collect();
+                    let results: Vec<SarifResult> = issues.iter().map(Into::into).collect();
+                    writeln!(
+                        out,
+                        "{}",
+                        serde_json::to_string_pretty(&sarif_log(&rules, &results))?
+                    )?;
+                }
+                _ => {
+                    for issue in &issues {
+                        writeln!(
+                            out,
+                            "{}: [{}] {}\n    fix: {}",
+                            issue.path.display(),
+                            issue.kind,
+                            issue.message,
+                            issue.suggestion
+                        )?;
+                    }
+                    if !cli.quiet {
+                        if issues.is_empty() {
+                            writeln!(out, "No portability issues found")?;
+                        } else {
+                            writeln!(out, "\n{} portability issues found", issues.len())?;
+                        }
+                    }
+                }
+            }
+
+            out.finish()?;
+
+            if !issues.is_empty() {
+                return Err(FsError::InvalidFormat {
+                    format: format!("{} portability issues", issues.len()),
+                });
             }
         }
 
@@ -321,6 +1604,7 @@ fn main() -> Result<()> {
                 enrich_with_git_status, get_changed_since, is_git_repo, GitStatus,
             };
 
+            common.reject_from_results("git")?;
             // Check if path is in a git repository
             if !is_git_repo(&path) {
                 return Err(FsError::InvalidFormat {
@@ -328,7 +1612,7 @@ fn main() -> Result<()> {
                 });
             }
 
-            let config = build_traverse_config(&common, cli.quiet);
+            let config = build_traverse_config(&common, cli.quiet, types);
             let mut entries = walk_no_filter(&path, &config)?;
 
             // If "since" is specified, filter to only changed files
@@ -393,7 +1677,8 @@ fn main() -> Result<()> {
         Commands::Interactive { path } => {
             use rust_filesearch::tui::{ui, App};
 
-            let mut app = App::new(path)?;
+            let config = Config::load()?;
+            let mut app = App::new(path, &config.tui)?;
             ui::run(&mut app).map_err(|e| FsError::IoError {
                 context: "TUI error".to_string(),
                 source: e,
@@ -402,39 +1687,240 @@ fn main() -> Result<()> {
 
         #[cfg(feature = "trends")]
         Commands::Snapshot {
-            path: _,
-            description: _,
+            command,
+            path,
+            description,
+            exclude,
         } => {
-            println!("🚧 Snapshot command - Implementation coming in Phase 4!");
-            println!("This will save filesystem state for trend analysis.");
+            use rust_filesearch::fs::snapshot::SnapshotStore;
+            use rust_filesearch::util::format_size_human;
+
+            let store = SnapshotStore::open_default()?;
+            match command {
+                None => {
+                    let root = path.canonicalize().map_err(|e| FsError::PathAccess {
+                        path: path.clone(),
+                        source: e,
+                    })?;
+                    // Snapshots record everything that takes up space
+                    let config = TraverseConfig {
+                        include_hidden: true,
+                        respect_gitignore: false,
+                        quiet: cli.quiet,
+                        exclude,
+                        ..Default::default()
+                    };
+                    let entries = walk_no_filter(&root, &config)?;
+                    let meta = store.create(&root, &entries, &config.exclude, description)?;
+                    println!(
+                        "Saved snapshot {} of {}: {} files, {} directories, {}",
+                        meta.id,
+                        meta.root.display(),
+                        meta.files,
+                        meta.dirs,
+                        format_size_human(meta.total_size)
+                    );
+                }
+
+                Some(cli::SnapshotCommand::List { path, format }) => {
+                    let root = path
+                        .map(|p| {
+                            p.canonicalize()
+                                .map_err(|e| FsError::PathAccess { path: p, source: e })
+                        })
+                        .transpose()?;
+                    let snapshots = store.list(root.as_deref())?;
+
+                    if format == "json" {
+                        println!("{}", serde_json::to_string_pretty(&snapshots)?);
+                    } else if snapshots.is_empty() {
+                        println!(
+                            "No snapshots saved yet (stored in {}).",
+                            store.dir().display()
+                        );
+                    } else {
+                        println!(
+                            "{:<20} {:<16} {:>8} {:>10}  Path",
+                            "Id", "Created", "Files", "Size"
+                        );
+                        if !plain::is_enabled() {
+                            println!("{}", "─".repeat(78));
+                        }
+                        for meta in &snapshots {
+                            println!(
+                                "{:<20} {:<16} {:>8} {:>10}  {}{}",
+                                meta.id,
+                                meta.created
+                                    .with_timezone(&chrono::Local)
+                                    .format("%Y-%m-%d %H:%M"),
+                                meta.files,
+                                format_size_human(meta.total_size),
+                                meta.root.display(),
+                                meta.description
+                                    .as_ref()
+                                    .map(|d| format!("  ({})", d))
+                                    .unwrap_or_default()
+                            );
+                        }
+                    }
+                }
+
+                Some(cli::SnapshotCommand::Delete { ids }) => {
+                    for id in &ids {
+                        store.delete(id)?;
+                        println!("Deleted snapshot {}", id);
+                    }
+                }
+            }
         }
 
         #[cfg(feature = "trends")]
         Commands::Trends {
-            path: _,
-            since: _,
-            chart: _,
+            path,
+            since,
+            chart,
+            top,
+            format,
+            alert,
+            notify,
         } => {
-            println!("🚧 Trends command - Implementation coming in Phase 4!");
-            println!("This will analyze filesystem growth over time.");
-        }
+            use rust_filesearch::fs::snapshot::SnapshotStore;
+            use rust_filesearch::fs::trends::{analyze, TrendAlert};
+            use rust_filesearch::output::trends::write_trends_report;
+            use rust_filesearch::util::parse_date;
+
+            let alerts = alert
+                .iter()
+                .map(|spec| TrendAlert::parse(spec))
+                .collect::<Result<Vec<_>>>()?;
+            let notifier = Notifier::from_specs(&notify)?;
+            let root = path.canonicalize().map_err(|e| FsError::PathAccess {
+                path: path.clone(),
+                source: e,
+            })?;
+            let since = since.as_deref().map(parse_date).transpose()?;
 
-        Commands::Completions { shell } => {
-            use clap::CommandFactory;
-            use clap_complete::{generate, Shell as CompShell};
+            let store = SnapshotStore::open_default()?;
+            let mut snapshots = store.list(Some(&root))?;
+            snapshots.retain(|meta| since.is_none_or(|since| meta.created >= since));
+            let report = analyze(&store, &snapshots, top)?;
 
-            let mut cmd = Cli::command();
-            let shell_type = match shell {
-                cli::Shell::Bash => CompShell::Bash,
-                cli::Shell::Zsh => CompShell::Zsh,
-                cli::Shell::Fish => CompShell::Fish,
-                cli::Shell::Powershell => CompShell::PowerShell,
-                cli::Shell::Elvish => CompShell::Elvish,
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                write_trends_report(&mut io::stdout().lock(), &report, chart)?;
+            }
+
+            let fired: Vec<String> = alerts.iter().filter_map(|a| a.check(&report)).collect();
+            if !fired.is_empty() {
+                let notification = Notification::new(
+                    "trends.alert",
+                    format!("Trend alert: {}", report.root.display()),
+                    fired.join(", "),
+                    serde_json::to_value(&report)?,
+                );
+                notifier.send(&notification)?;
+                return Err(FsError::InvalidFormat {
+                    format: format!("{}: {}", report.root.display(), fired.join(", ")),
+                });
+            }
+        }
+
+        #[cfg(feature = "trends")]
+        Commands::Export {
+            path,
+            since,
+            snapshot,
+            exclude,
+            format,
+        } => {
+            use rust_filesearch::fs::export::{baseline_for, changes_by_mtime, diff_against};
+            use rust_filesearch::fs::snapshot::SnapshotStore;
+            use rust_filesearch::util::parse_date;
+            use std::io::Write;
+
+            if format != "ndjson" && format != "json" {
+                return Err(FsError::InvalidFormat {
+                    format: format!("export supports ndjson and json output, not {}", format),
+                });
+            }
+            let root = path.canonicalize().map_err(|e| FsError::PathAccess {
+                path: path.clone(),
+                source: e,
+            })?;
+            let since = since.as_deref().map(parse_date).transpose()?;
+
+            // Same walk as `snapshot`, so exports and their baselines line up
+            let config = TraverseConfig {
+                include_hidden: true,
+                respect_gitignore: false,
+                quiet: cli.quiet,
+                exclude,
+                ..Default::default()
+            };
+            let entries = walk_no_filter(&root, &config)?;
+
+            let store = SnapshotStore::open_default()?;
+            let changes = match since {
+                Some(since) => {
+                    let snapshots = store.list(Some(&root))?;
+                    match baseline_for(&snapshots, since) {
+                        Some(meta) => diff_against(&root, store.entries(&meta.id)?, &entries),
+                        None => {
+                            warn!(
+                                root = %root.display(),
+                                "No snapshot taken by then, exporting entries modified \
+                                 since (removals are not detected)"
+                            );
+                            changes_by_mtime(&entries, Some(since))
+                        }
+                    }
+                }
+                None => changes_by_mtime(&entries, None),
+            };
+
+            let mut out = io::BufWriter::new(io::stdout().lock());
+            if format == "json" {
+                serde_json::to_writer_pretty(&mut out, &changes)?;
+                writeln!(out)?;
+            } else {
+                for change in &changes {
+                    serde_json::to_writer(&mut out, change)?;
+                    writeln!(out)?;
+                }
+            }
+            out.flush()?;
+
+            if snapshot {
+                store.create(&root, &entries, &config.exclude, Some("export".to_string()))?;
+            }
+        }
+
+        Commands::Projects { command } => {
+            rust_filesearch::px::cli::run(command, "fexplorer projects")?;
+        }
+
+        Commands::Completions { shell } => {
+            use clap::CommandFactory;
+            use clap_complete::{generate, Shell as CompShell};
+
+            let mut cmd = Cli::command();
+            let shell_type = match shell {
+                cli::Shell::Bash => CompShell::Bash,
+                cli::Shell::Zsh => CompShell::Zsh,
+                cli::Shell::Fish => CompShell::Fish,
+                cli::Shell::Powershell => CompShell::PowerShell,
+                cli::Shell::Elvish => CompShell::Elvish,
             };
 
             generate(shell_type, &mut cmd, "fexplorer", &mut io::stdout());
         }
 
+        Commands::Schema => {
+            let schema = rust_filesearch::output::json::entry_schema();
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+        }
+
         Commands::Profiles { command } => match command {
             ProfileCommand::List => {
                 let config = Config::load()?;
@@ -469,6 +1955,14 @@ fn main() -> Result<()> {
                     for (key, value) in &profile.args {
                         println!("  {} = {}", key, value);
                     }
+                    if !profile.params.is_empty() {
+                        let mut params: Vec<_> = profile.params.iter().collect();
+                        params.sort_by(|a, b| a.0.cmp(b.0));
+                        println!("Parameters (--set NAME=VALUE):");
+                        for (name, default) in params {
+                            println!("  {} (default {})", name, default);
+                        }
+                    }
                 } else {
                     eprintln!("Profile '{}' not found", name);
                 }
@@ -485,6 +1979,7 @@ fn main() -> Result<()> {
         Commands::Run {
             profile,
             path,
+            set,
             args,
         } => {
             let config = Config::load()?;
@@ -514,8 +2009,15 @@ fn main() -> Result<()> {
                 }
             }
 
-            // Merge profile args with overrides
-            let mut merged_args = profile_def.args.clone();
+            if profile_def.command == "pipeline" && !override_args.is_empty() {
+                return Err(FsError::InvalidFormat {
+                    format: "Pipeline profiles take parameters with --set, not overrides"
+                        .to_string(),
+                });
+            }
+
+            // Merge profile args, with their parameters filled in, with overrides
+            let mut merged_args = profile_def.resolve_args(&set)?;
             for (key, value) in override_args {
                 merged_args.insert(key, value);
             }
@@ -531,43 +2033,9 @@ fn main() -> Result<()> {
             // Execute the command based on profile
             match profile_def.command.as_str() {
                 "find" => {
-                    let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
-                    let config = build_traverse_config(&cli::CommonArgs::default(), cli.quiet);
-
-                    // Build predicates from merged args
-                    if let Some(names) = merged_args.get("names").and_then(|v| v.as_array()) {
-                        let names: Vec<String> = names
-                            .iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect();
-                        if !names.is_empty() {
-                            predicates.push(Box::new(GlobFilter::new(&names)?));
-                        }
-                    }
-
-                    if let Some(ext) = merged_args.get("ext").and_then(|v| v.as_array()) {
-                        let extensions: Vec<String> = ext
-                            .iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect();
-                        if !extensions.is_empty() {
-                            predicates.push(Box::new(ExtensionFilter::new(&extensions)));
-                        }
-                    }
-
-                    if let Some(min) = merged_args.get("min_size").and_then(|v| v.as_str()) {
-                        let max = merged_args.get("max_size").and_then(|v| v.as_str());
-                        predicates.push(Box::new(SizeFilter::new(Some(min), max)?));
-                    }
-
-                    if let Some(after) = merged_args.get("after").and_then(|v| v.as_str()) {
-                        let before = merged_args.get("before").and_then(|v| v.as_str());
-                        predicates.push(Box::new(DateFilter::new(Some(after), before)?));
-                    }
-
-                    if let Some(category) = merged_args.get("category").and_then(|v| v.as_str()) {
-                        predicates.push(Box::new(CategoryFilter::new(category)));
-                    }
+                    let config =
+                        build_traverse_config(&cli::CommonArgs::default(), cli.quiet, types);
+                    let predicates = profile_predicates(&merged_args, types)?;
 
                     let entries = if !predicates.is_empty() {
                         let combined = AndPredicate::new(predicates);
@@ -580,22 +2048,24 @@ fn main() -> Result<()> {
                     output_entries(&entries, &common, cli.no_color)?;
                 }
                 "list" => {
-                    let config = build_traverse_config(&cli::CommonArgs::default(), cli.quiet);
+                    let config =
+                        build_traverse_config(&cli::CommonArgs::default(), cli.quiet, types);
                     let entries = walk_no_filter(&target_path, &config)?;
                     let common = cli::CommonArgs::default();
                     output_entries(&entries, &common, cli.no_color)?;
                 }
                 "size" => {
-                    let config = build_traverse_config(&cli::CommonArgs::default(), cli.quiet);
+                    let config =
+                        build_traverse_config(&cli::CommonArgs::default(), cli.quiet, types);
                     let mut entries = walk_no_filter(&target_path, &config)?;
 
-                    let dir_sizes = compute_dir_sizes(&entries);
-                    update_entries_with_dir_sizes(&mut entries, &dir_sizes);
-                    entries.sort_by(|a, b| b.size.cmp(&a.size));
+                    let enrichers: [Box<dyn Enricher>; 1] = [Box::new(DirSizeEnricher)];
+                    enrich_entries(&mut entries, &enrichers)?;
+                    entries.sort_by_key(|e| std::cmp::Reverse(e.size));
 
                     if let Some(top) = merged_args
                         .get("top")
-                        .and_then(|v| v.as_u64())
+                        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
                         .map(|v| v as usize)
                     {
                         entries = get_top_by_size(&entries, top);
@@ -604,6 +2074,38 @@ fn main() -> Result<()> {
                     let common = cli::CommonArgs::default();
                     output_entries(&entries, &common, cli.no_color)?;
                 }
+                #[cfg(feature = "archive")]
+                "archive" => {
+                    use rust_filesearch::fs::archive::{create_archive, ArchiveFormat};
+
+                    let output = merged_args
+                        .get("output")
+                        .and_then(|v| v.as_str())
+                        .map(PathBuf::from)
+                        .ok_or_else(|| FsError::InvalidFormat {
+                            format: "Archive profiles need an `output` arg".to_string(),
+                        })?;
+                    let name = merged_args.get("format").and_then(|v| v.as_str());
+                    let format = ArchiveFormat::for_output(name, &output)?;
+
+                    let config =
+                        build_traverse_config(&cli::CommonArgs::default(), cli.quiet, types);
+                    let predicates = profile_predicates(&merged_args, types)?;
+                    let entries = if !predicates.is_empty() {
+                        let combined = AndPredicate::new(predicates);
+                        walk(&target_path, &config, Some(&combined))?
+                    } else {
+                        walk_no_filter(&target_path, &config)?
+                    };
+
+                    let roots = [target_path.clone()];
+                    let summary = create_archive(&entries, &roots, &output, format, None, |_| {})?;
+                    print_archive_summary(&summary, &output)?;
+                }
+                "pipeline" => {
+                    let stages = profile_def.resolve_stages(&set)?;
+                    run_pipeline(&stages, &target_path, cli.quiet, cli.no_color, types)?;
+                }
                 cmd => {
                     return Err(FsError::InvalidFormat {
                         format: format!("Unsupported profile command: {}", cmd),
@@ -612,15 +2114,270 @@ fn main() -> Result<()> {
             }
         }
 
+        Commands::Backup { command } => {
+            let (paths, excluded, dry_run) = match command {
+                BackupCommand::Exclude { paths, dry_run } => (paths, true, dry_run),
+                BackupCommand::Include { paths, dry_run } => (paths, false, dry_run),
+            };
+
+            #[cfg(target_os = "macos")]
+            {
+                use rust_filesearch::fs::backup::set_backup_exclusion;
+
+                let report = set_backup_exclusion(&paths, excluded, dry_run);
+                print_batch_report(&report, dry_run, cli.quiet)?;
+            }
+
+            #[cfg(not(target_os = "macos"))]
+            {
+                let _ = (paths, excluded, dry_run);
+                return Err(FsError::InvalidFormat {
+                    format: "Time Machine exclusions are only supported on macOS".to_string(),
+                });
+            }
+        }
+
+        Commands::Quota { command } => match command {
+            QuotaCommand::Check {
+                paths,
+                max_size,
+                max_files,
+                top,
+                notify,
+                mut common,
+            } => {
+                use rust_filesearch::fs::quota::{check_quota, QuotaLimit, QuotaLimits};
+                use rust_filesearch::util::{format_size_human, parse_count, parse_size};
+
+                common.reject_output("quota check")?;
+                common.reject_from_results("quota check")?;
+                common.reject_reproducible("quota check")?;
+                let notifier = Notifier::from_specs(&notify)?;
+                let limits = QuotaLimits {
+                    max_size: max_size.as_deref().map(parse_size).transpose()?,
+                    max_files: max_files.as_deref().map(parse_count).transpose()?,
+                };
+
+                // Everything counts toward a quota, like du
+                common.hidden = true;
+                common.no_gitignore = true;
+                let config = build_traverse_config(&common, cli.quiet, types);
+
+                let mut reports = Vec::new();
+                for path in &paths {
+                    let entries = walk::<dyn Predicate>(path, &config, None)?;
+                    reports.push(check_quota(path, &entries, &limits, top));
+                }
+
+                match common.output_format()? {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+                    OutputFormat::Ndjson => {
+                        for report in &reports {
+                            println!("{}", serde_json::to_string(report)?);
+                        }
+                    }
+                    OutputFormat::Gha => {
+                        for annotation in reports.iter().flat_map(Annotation::from_quota) {
+                            println!("{}", annotation);
+                        }
+                    }
+                    OutputFormat::Sarif => {
+                        return Err(FsError::InvalidFormat {
+                            format: "quota check supports pretty, json, ndjson and gha output"
+                                .to_string(),
+                        });
+                    }
+                    _ => {
+                        for report in &reports {
+                            let status = if report.exceeded() { "FAIL" } else { "OK" };
+                            println!("{:<4} {}", status, report.path.display());
+
+                            let mark = |limit| {
+                                if report.violations.iter().any(|v| v.limit == limit) {
+                                    "  EXCEEDED"
+                                } else {
+                                    ""
+                                }
+                            };
+                            if let Some(max) = limits.max_size {
+                                println!(
+                                    "     size   {} (limit {}){}",
+                                    format_size_human(report.size),
+                                    format_size_human(max),
+                                    mark(QuotaLimit::Size)
+                                );
+                            }
+                            if let Some(max) = limits.max_files {
+                                println!(
+                                    "     files  {} (limit {}){}",
+                                    report.files,
+                                    max,
+                                    mark(QuotaLimit::Files)
+                                );
+                            }
+                            if !report.top_children.is_empty() {
+                                println!("     largest:");
+                                for (child, size) in &report.top_children {
+                                    println!(
+                                        "       {:>10}  {}",
+                                        format_size_human(*size),
+                                        child.display()
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+
+                for report in reports.iter().filter(|r| r.exceeded()) {
+                    let violations: Vec<String> =
+                        report.violations.iter().map(|v| v.to_string()).collect();
+                    let notification = Notification::new(
+                        "quota.exceeded",
+                        format!("Quota exceeded: {}", report.path.display()),
+                        violations.join(", "),
+                        serde_json::to_value(report)?,
+                    );
+                    notifier.send(&notification)?;
+                }
+
+                let failed = reports.iter().filter(|r| r.exceeded()).count();
+                if failed > 0 {
+                    return Err(FsError::InvalidFormat {
+                        format: format!("{} of {} paths exceed their quota", failed, reports.len()),
+                    });
+                }
+            }
+        },
+
+        Commands::Stats { command } => match command {
+            StatsCommand::Show { format } => {
+                let stats = UsageStats::load()?;
+
+                if format == "json" {
+                    println!("{}", serde_json::to_string_pretty(&stats)?);
+                } else if stats.commands.is_empty() {
+                    println!("No usage statistics recorded yet.");
+                    if !Config::load()?.preferences.record_stats {
+                        println!("Enable with `record_stats = true` under [preferences] in:");
+                        println!("{}", Config::config_file_path()?.display());
+                    }
+                } else {
+                    println!(
+                        "Usage since {} (stored locally in {})",
+                        stats.since.format("%Y-%m-%d"),
+                        UsageStats::stats_path()?.display()
+                    );
+                    println!();
+                    println!(
+                        "{:<24} {:>6} {:>6} {:>10} {:>10}  Last run",
+                        "Command", "Runs", "Fails", "Avg", "Max"
+                    );
+                    if !plain::is_enabled() {
+                        println!("{}", "─".repeat(78));
+                    }
+
+                    for (name, cmd) in stats.by_usage() {
+                        println!(
+                            "{:<24} {:>6} {:>6} {:>10} {:>10}  {}",
+                            name,
+                            cmd.runs,
+                            cmd.failures,
+                            format_millis(cmd.avg_ms()),
+                            format_millis(cmd.max_ms),
+                            cmd.last_run
+                                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                                .unwrap_or_default()
+                        );
+                    }
+                }
+            }
+
+            StatsCommand::Reset => {
+                UsageStats::reset()?;
+                println!("Usage statistics cleared.");
+            }
+        },
+
+        #[cfg(feature = "cache")]
+        Commands::Cache { command } => match command {
+            cli::CacheCommand::Clear => {
+                use rust_filesearch::fs::cache::MetadataCache;
+
+                let path = MetadataCache::default_path()?;
+                if MetadataCache::clear(&path)? {
+                    println!("{}", trf(Msg::CacheCleared, &[&path.display()]));
+                } else {
+                    println!("{}", tr(Msg::CacheEmpty));
+                }
+            }
+        },
+
         #[cfg(feature = "watch")]
         Commands::Watch {
             path,
             events,
             format,
+            output,
+            notify,
+            #[cfg(feature = "trends")]
+            update_snapshot,
+            #[cfg(feature = "trends")]
+            snapshot_interval,
         } => {
             use rust_filesearch::fs::watch::FileWatcher;
 
+            #[cfg(feature = "trends")]
+            if update_snapshot {
+                return watch_snapshot(&path, &snapshot_interval, cli.quiet);
+            }
+
             let watcher = FileWatcher::new(events);
+            let notifier = Notifier::from_specs(&notify)?;
+            let send = |event: &rust_filesearch::models::WatchEvent| {
+                if notifier.is_empty() {
+                    return;
+                }
+                let notification = Notification::new(
+                    &format!("watch.{}", event.event),
+                    format!("File {}", event.event),
+                    event.path.display().to_string(),
+                    serde_json::to_value(event).unwrap_or_default(),
+                );
+                // A failed delivery shouldn't stop the watch
+                if let Err(e) = notifier.send(&notification) {
+                    warn!("{}", e);
+                }
+            };
+
+            match output.as_str() {
+                "stdout" => {}
+                #[cfg(all(feature = "syslog", unix))]
+                "syslog" => {
+                    use rust_filesearch::output::syslog::SyslogWriter;
+
+                    let writer = SyslogWriter::connect()?;
+                    watcher.watch(&path, |event| {
+                        if let Err(e) = writer.write_event(&event) {
+                            warn!("{}", e);
+                        }
+                        send(&event);
+                    })?;
+                    return Ok(());
+                }
+                #[cfg(not(all(feature = "syslog", unix)))]
+                "syslog" => {
+                    return Err(FsError::InvalidFormat {
+                        format: "--output syslog requires the syslog feature (Unix only)"
+                            .to_string(),
+                    });
+                }
+                other => {
+                    return Err(FsError::InvalidFormat {
+                        format: format!("unknown watch output '{}' (stdout, syslog)", other),
+                    });
+                }
+            }
 
             // For watch, we output events as they come
             match format.as_str() {
@@ -629,27 +2386,63 @@ fn main() -> Result<()> {
                         if let Ok(json) = serde_json::to_string(&event) {
                             println!("{}", json);
                         }
+                        send(&event);
                     })?;
                 }
                 _ => {
                     watcher.watch(&path, |event| {
                         println!("{:?}", event);
+                        send(&event);
                     })?;
                 }
             }
         }
 
         #[cfg(feature = "plugins")]
-        Commands::Plugins { command: _ } => {
-            println!("🚧 Plugins command - Implementation coming in Phase 4!");
-            println!("This will manage loadable filter plugins.");
+        Commands::Plugins { command } => {
+            use rust_filesearch::plugins::PluginRegistry;
+
+            let mut registry = PluginRegistry::load()?;
+            match command {
+                cli::PluginCommand::List => {
+                    if registry.plugins.is_empty() {
+                        println!(
+                            "No plugins installed (install with `fexplorer plugins install <PATH>`)."
+                        );
+                    }
+                    for (name, info) in &registry.plugins {
+                        println!(
+                            "{:<24} {:<9} {}",
+                            name,
+                            if info.enabled { "enabled" } else { "disabled" },
+                            registry.module_path(name).display()
+                        );
+                    }
+                }
+                cli::PluginCommand::Install { path, name } => {
+                    let name = registry.install(&path, name.as_deref())?;
+                    println!("Installed plugin {} (enabled)", name);
+                }
+                cli::PluginCommand::Enable { name } => {
+                    registry.set_enabled(&name, true)?;
+                    println!("Enabled plugin {}", name);
+                }
+                cli::PluginCommand::Disable { name } => {
+                    registry.set_enabled(&name, false)?;
+                    println!("Disabled plugin {}", name);
+                }
+            }
         }
     }
 
     Ok(())
 }
 
-fn build_traverse_config(common: &cli::CommonArgs, quiet: bool) -> TraverseConfig {
+fn build_traverse_config(
+    common: &cli::CommonArgs,
+    quiet: bool,
+    type_detection: TypeDetection,
+) -> TraverseConfig {
     TraverseConfig {
         max_depth: common.max_depth,
         follow_symlinks: common.follow_symlinks,
@@ -659,42 +2452,695 @@ fn build_traverse_config(common: &cli::CommonArgs, quiet: bool) -> TraverseConfi
         threads: common.threads,
         #[cfg(not(feature = "parallel"))]
         threads: 1,
+        #[cfg(feature = "parallel")]
+        unordered: common.unordered,
+        #[cfg(not(feature = "parallel"))]
+        unordered: false,
         quiet,
+        skip_other_users: common.skip_other_users,
+        exclude: common.exclude.clone(),
+        type_detection,
+        #[cfg(feature = "cache")]
+        cache: None,
     }
 }
 
-fn build_predicate_from_common(_common: &cli::CommonArgs) -> Result<Option<Box<dyn Predicate>>> {
-    // For basic list, we don't apply additional predicates
-    // They're applied in specific subcommands
-    Ok(None)
+/// `watch --update-snapshot`: keep the newest snapshot of `path` current
+/// from file events, saving it as a new snapshot at most every `interval`
+///
+/// Changes are saved with the first batch of events once `interval` has
+/// passed. Changes made while nothing watched aren't seen, and those since
+/// the last save are lost on Ctrl+C.
+#[cfg(all(feature = "watch", feature = "trends"))]
+fn watch_snapshot(path: &Path, interval: &str, quiet: bool) -> Result<()> {
+    use rust_filesearch::fs::snapshot::{LiveSnapshot, SnapshotStore};
+    use rust_filesearch::fs::watch::FileWatcher;
+    use rust_filesearch::util::{format_size_human, parse_duration};
+
+    let interval = parse_duration(interval)?
+        .to_std()
+        .map_err(|_| FsError::InvalidFormat {
+            format: "--snapshot-interval must be positive".to_string(),
+        })?;
+    let root = path.canonicalize().map_err(|e| FsError::PathAccess {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let store = SnapshotStore::open_default()?;
+    let latest = store
+        .list(Some(&root))?
+        .pop()
+        .ok_or_else(|| FsError::InvalidFormat {
+            format: format!(
+                "No snapshot of {} to update; take one with `fexplorer snapshot {}` first",
+                root.display(),
+                path.display()
+            ),
+        })?;
+    let mut live = LiveSnapshot::load(&store, &latest)?;
+    // As `snapshot` walks: everything that takes up space but what it excluded
+    let config = TraverseConfig {
+        include_hidden: true,
+        respect_gitignore: false,
+        quiet: true,
+        exclude: latest.exclude.clone(),
+        ..Default::default()
+    };
+    if !quiet {
+        eprintln!(
+            "Updating snapshot {} of {} from file events (Ctrl+C to stop)",
+            latest.id,
+            root.display()
+        );
+    }
+
+    let mut last_saved = Instant::now();
+    FileWatcher::new(Vec::new()).watch_batched(
+        std::slice::from_ref(&root),
+        std::time::Duration::from_secs(2),
+        |events| {
+            for event in &events {
+                live.refresh(&event.path, &config);
+            }
+            if last_saved.elapsed() < interval {
+                return Ok(());
+            }
+            let meta = store.save(
+                live.root(),
+                live.entries(),
+                live.exclude(),
+                Some("updated by watch".to_string()),
+            )?;
+            last_saved = Instant::now();
+            println!(
+                "Saved snapshot {}: {} files, {} directories, {}",
+                meta.id,
+                meta.files,
+                meta.dirs,
+                format_size_human(meta.total_size)
+            );
+            Ok(())
+        },
+    )
+}
+
+/// Let a `find` or `size` walk reuse cached metadata, unless --no-cache
+///
+/// Without a usable cache the walk simply reads everything.
+#[cfg(feature = "cache")]
+fn with_cache(mut config: TraverseConfig, no_cache: bool) -> TraverseConfig {
+    use rust_filesearch::fs::cache::MetadataCache;
+
+    if !no_cache {
+        match MetadataCache::open_default() {
+            Ok(cache) => config.cache = Some(std::sync::Arc::new(cache)),
+            Err(e) if !config.quiet => warn!("{}", e),
+            Err(_) => {}
+        }
+    }
+    config
+}
+
+/// Collect entries from the root paths, or from --files-from/--stdin or
+/// --from-results when given
+///
+/// A lone `-` root is shorthand for `--stdin`. Entries from --from-results
+/// keep their exported metadata and only go through the predicate.
+fn collect_entries<P>(
+    paths: &[PathBuf],
+    common: &cli::CommonArgs,
+    config: &TraverseConfig,
+    predicate: Option<&P>,
+) -> Result<Vec<Entry>>
+where
+    P: Predicate + ?Sized,
+{
+    let stdin_root = (paths.len() == 1 && paths[0] == Path::new("-")).then(|| PathBuf::from("-"));
+
+    let mut entries = if let Some(results) = &common.from_results {
+        let mut entries = load_results(results)?;
+        entries.retain(|entry| predicate.is_none_or(|pred| pred.test(entry)));
+        entries
+    } else {
+        match common.path_list_source().or(stdin_root) {
+            Some(source) => entries_from_paths(&read_path_list(&source)?, config, predicate)?,
+            None => walk_paths(paths, config, predicate)?,
+        }
+    };
+
+    // Fill in metadata for the requested columns (git status, hash, ...)
+    enrich_entries(
+        &mut entries,
+        &enrichers_for(&common.enrich_columns()?, config.type_detection)?,
+    )?;
+    Ok(entries)
+}
+
+/// Print what `archive` (or an archive profile) wrote
+#[cfg(feature = "archive")]
+fn print_archive_summary(
+    summary: &rust_filesearch::fs::archive::ArchiveSummary,
+    output: &Path,
+) -> Result<()> {
+    use rust_filesearch::util::format_size_human;
+
+    if summary.files + summary.dirs == 0 {
+        let _ = std::fs::remove_file(output);
+        return Err(FsError::NoEntriesFound);
+    }
+    let archive_size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "Archived {} files ({}) and {} directories into {} ({})",
+        summary.files,
+        format_size_human(summary.total_size),
+        summary.dirs,
+        output.display(),
+        format_size_human(archive_size)
+    );
+    Ok(())
+}
+
+/// Print the changes made by a batch command, then skipped/failed paths and a summary
+///
+/// Returns an error if any entry failed so scripts can detect partial failures.
+fn print_batch_report(report: &BatchReport, dry_run: bool, quiet: bool) -> Result<()> {
+    let prefix = if dry_run { "would change" } else { "changed" };
+    for change in &report.changed {
+        println!(
+            "{} {}: {} -> {}",
+            prefix,
+            change.path.display(),
+            change.from,
+            change.to
+        );
+    }
+
+    if !quiet {
+        for (path, reason) in &report.skipped {
+            eprintln!("skipped {}: {}", path.display(), reason);
+        }
+    }
+    for (path, error) in &report.errors {
+        eprintln!("error {}: {}", path.display(), error);
+    }
+
+    println!();
+    println!("{}", report.summary(dry_run));
+
+    if report.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(FsError::InvalidFormat {
+            format: format!("{} entries could not be changed", report.errors.len()),
+        })
+    }
 }
 
-fn sort_entries(entries: &mut [Entry], key: SortKey, order: SortOrder, dirs_first: bool) {
-    entries.sort_by(|a, b| {
-        // Apply dirs_first if requested
-        if dirs_first {
-            match (a.kind, b.kind) {
-                (EntryKind::Dir, EntryKind::File) => return std::cmp::Ordering::Less,
-                (EntryKind::File, EntryKind::Dir) => return std::cmp::Ordering::Greater,
-                _ => {}
+/// Print `grep --replace` results: a diff per file in a dry run, otherwise
+/// the changed files, then skipped/failed files and a summary
+///
+/// Returns an error if any file failed so scripts can detect partial failures.
+#[cfg(feature = "grep")]
+fn print_replace_report(
+    report: &rust_filesearch::fs::content::ReplaceReport,
+    dry_run: bool,
+    quiet: bool,
+) -> Result<()> {
+    for file in &report.files {
+        if dry_run {
+            println!("--- {}", file.path.display());
+            println!("+++ {}", file.path.display());
+            for line in &file.lines {
+                println!("@@ -{} +{} @@", line.line_number, line.line_number);
+                println!("-{}", line.before);
+                println!("+{}", line.after);
             }
+        } else {
+            println!(
+                "{}: {} matches on {} lines",
+                file.path.display(),
+                file.replacements,
+                file.lines.len()
+            );
+        }
+    }
+
+    if !quiet {
+        for (path, reason) in &report.skipped {
+            eprintln!("skipped {}: {}", path.display(), reason);
+        }
+    }
+    for (path, error) in &report.errors {
+        eprintln!("error {}: {}", path.display(), error);
+    }
+
+    println!();
+    println!("{}", report.summary(dry_run));
+
+    if report.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(FsError::InvalidFormat {
+            format: format!("{} files could not be changed", report.errors.len()),
+        })
+    }
+}
+
+/// Format a duration in milliseconds for the stats table
+fn format_millis(ms: u64) -> String {
+    if ms >= 1000 {
+        format!("{:.2}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+/// `--explain`: print what would be walked and how it would be filtered
+///
+/// Returns whether the plan was printed, in which case the command stops there.
+fn explain_plan(
+    paths: &[PathBuf],
+    filters: &cli::FilterArgs,
+    common: &cli::CommonArgs,
+    config: &TraverseConfig,
+    predicate: Option<&dyn Predicate>,
+) -> Result<bool> {
+    use rust_filesearch::fs::explain::QueryPlan;
+    use rust_filesearch::output::explain::write_plan;
+
+    if !filters.explain {
+        return Ok(false);
+    }
+    let mut plan = QueryPlan::new(paths, common.path_list_source(), config, predicate);
+    if let Some(results) = &common.from_results {
+        plan = plan.with_results(results);
+    }
+    if let Some(exec) = &filters.filter_exec {
+        plan = plan.with_post_filter(ExecFilter::new(exec, filters.exec_jobs).explain());
+    }
+    if common.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        write_plan(&mut io::stdout().lock(), &plan)?;
+    }
+    Ok(true)
+}
+
+/// Run the stages of a `pipeline` profile in order
+///
+/// Entries are handed from stage to stage in memory: `find` and `list` add
+/// entries, `filter`, `hash`, `enrich`, `sort` and `limit` work on them, and
+/// `export` writes them out. Without an `export` stage the final entries are
+/// printed.
+fn run_pipeline(
+    stages: &[PipelineStage],
+    root: &std::path::Path,
+    quiet: bool,
+    no_color: bool,
+    types: TypeDetection,
+) -> Result<()> {
+    use rust_filesearch::models::Column;
+
+    match stages.first().map(|stage| stage.command.as_str()) {
+        Some("find" | "list") => {}
+        Some(_) => {
+            return Err(FsError::InvalidFormat {
+                format: "A pipeline must start with a find or list stage".to_string(),
+            });
+        }
+        None => {
+            return Err(FsError::InvalidFormat {
+                format: "Pipeline profile has no stages".to_string(),
+            });
         }
+    }
 
-        let cmp = match key {
-            SortKey::Name => a.name.cmp(&b.name),
-            SortKey::Size => a.size.cmp(&b.size),
-            SortKey::Mtime => a.mtime.cmp(&b.mtime),
-            SortKey::Kind => format!("{:?}", a.kind).cmp(&format!("{:?}", b.kind)),
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut exported = false;
+    for (i, stage) in stages.iter().enumerate() {
+        let args = &stage.args;
+        let invalid = |message: &str| FsError::InvalidFormat {
+            format: format!("Pipeline stage {} ({}): {}", i + 1, stage.command, message),
         };
 
-        match order {
-            SortOrder::Asc => cmp,
-            SortOrder::Desc => cmp.reverse(),
+        match stage.command.as_str() {
+            "find" | "list" => {
+                let path = args
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| root.to_path_buf());
+                let config = build_traverse_config(&cli::CommonArgs::default(), quiet, types);
+                let predicates = match stage.command.as_str() {
+                    "find" => profile_predicates(args, config.type_detection)?,
+                    _ => Vec::new(),
+                };
+                if predicates.is_empty() {
+                    entries.extend(walk_no_filter(&path, &config)?);
+                } else {
+                    let combined = AndPredicate::new(predicates);
+                    entries.extend(walk(&path, &config, Some(&combined))?);
+                }
+            }
+            "filter" => {
+                let predicate = AndPredicate::new(profile_predicates(args, types)?);
+                entries.retain(|entry| predicate.test(entry));
+            }
+            "hash" => enrich_entries(&mut entries, &enrichers_for(&[Column::Hash], types)?)?,
+            "enrich" => {
+                let columns = profile_list(args, "columns")
+                    .iter()
+                    .map(|name| {
+                        Column::from_str(name)
+                            .ok_or_else(|| invalid(&format!("invalid column '{}'", name)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                enrich_entries(&mut entries, &enrichers_for(&columns, types)?)?;
+            }
+            "sort" => {
+                let by = args
+                    .get("by")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| invalid("needs `by`, e.g. by = \"size:desc\""))?;
+                sort_entries(&mut entries, parse_sort_fields(by, SortOrder::Asc)?, None);
+            }
+            "limit" => {
+                let n = args
+                    .get("n")
+                    .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+                    .ok_or_else(|| invalid("needs a number `n`"))?;
+                entries.truncate(n as usize);
+            }
+            "export" => {
+                export_stage(args, &entries, quiet, no_color)?;
+                exported = true;
+            }
+            cmd => {
+                return Err(invalid(&format!(
+                    "unknown stage '{}' (use find, list, filter, hash, enrich, sort, limit \
+                     or export)",
+                    cmd
+                )));
+            }
+        }
+
+        if !quiet {
+            eprintln!(
+                "[{}/{}] {}: {} entries",
+                i + 1,
+                stages.len(),
+                stage.command,
+                entries.len()
+            );
+        }
+    }
+
+    if !exported {
+        output_entries(&entries, &cli::CommonArgs::default(), no_color)?;
+    }
+    Ok(())
+}
+
+/// The `export` stage of a pipeline: any output format, or a SQLite table
+fn export_stage(
+    args: &std::collections::HashMap<String, serde_json::Value>,
+    entries: &[Entry],
+    quiet: bool,
+    no_color: bool,
+) -> Result<()> {
+    let format = args
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("ndjson");
+    let output = args.get("out").and_then(|v| v.as_str()).map(PathBuf::from);
+
+    if format == "sqlite" {
+        #[cfg(feature = "sqlite")]
+        {
+            let out = output.ok_or_else(|| FsError::InvalidFormat {
+                format: "sqlite export needs `out`, the database file".to_string(),
+            })?;
+            let table = args
+                .get("table")
+                .and_then(|v| v.as_str())
+                .unwrap_or("entries");
+            let rows = rust_filesearch::output::sqlite::write_sqlite(&out, table, entries)?;
+            if !quiet {
+                eprintln!("Wrote {} rows to {} ({})", rows, out.display(), table);
+            }
+            return Ok(());
+        }
+        #[cfg(not(feature = "sqlite"))]
+        {
+            return Err(FsError::InvalidFormat {
+                format: "sqlite export requires the sqlite feature".to_string(),
+            });
+        }
+    }
+
+    let common = cli::CommonArgs {
+        format: format.to_string(),
+        output,
+        columns: profile_list(args, "columns"),
+        ..Default::default()
+    };
+    output_entries(entries, &common, no_color)?;
+    if let (Some(out), false) = (&common.output, quiet) {
+        eprintln!("Wrote {} entries to {}", entries.len(), out.display());
+    }
+    Ok(())
+}
+
+/// Combine the standard filter flags into a single predicate (None if no filters are set)
+fn build_filter_predicate(
+    filters: &cli::FilterArgs,
+    types: TypeDetection,
+) -> Result<Option<Box<dyn Predicate>>> {
+    build_filter_predicate_sharing(filters, types, &Default::default())
+}
+
+/// [`build_filter_predicate`], keeping `--dir-size` totals in `dir_sizes`
+fn build_filter_predicate_sharing(
+    filters: &cli::FilterArgs,
+    types: TypeDetection,
+    dir_sizes: &std::sync::Arc<DirSizeCache>,
+) -> Result<Option<Box<dyn Predicate>>> {
+    let mut predicates: Vec<Box<dyn Predicate>> = Vec::new();
+
+    if !filters.names.is_empty() {
+        predicates.push(Box::new(GlobFilter::new(&filters.names)?));
+    }
+
+    if let Some(ref pattern) = filters.regex {
+        predicates.push(Box::new(RegexFilter::new(pattern)?));
+    }
+
+    if !filters.ext.is_empty() {
+        predicates.push(Box::new(ExtensionFilter::new(&filters.ext)));
+    }
+
+    if filters.min_size.is_some() || filters.max_size.is_some() || !filters.size.is_empty() {
+        let mut filter = SizeFilter::new(filters.min_size.as_deref(), filters.max_size.as_deref())?
+            .with_bounds(&filters.size)?;
+        if filters.dir_size {
+            filter = filter.with_dir_sizes(dir_sizes.clone());
         }
+        predicates.push(Box::new(filter));
+    }
+
+    if filters.after.is_some()
+        || filters.before.is_some()
+        || filters.older_than.is_some()
+        || filters.newer_than.is_some()
+    {
+        predicates.push(Box::new(
+            DateFilter::new(filters.after.as_deref(), filters.before.as_deref())?
+                .with_ages(filters.older_than.as_deref(), filters.newer_than.as_deref())?,
+        ));
+    }
+
+    for (field, after, before) in [
+        (
+            TimeField::Accessed,
+            &filters.accessed_after,
+            &filters.accessed_before,
+        ),
+        (
+            TimeField::Changed,
+            &filters.changed_after,
+            &filters.changed_before,
+        ),
+        (
+            TimeField::Created,
+            &filters.created_after,
+            &filters.created_before,
+        ),
+    ] {
+        if after.is_some() || before.is_some() {
+            predicates.push(Box::new(
+                DateFilter::new(after.as_deref(), before.as_deref())?.on(field),
+            ));
+        }
+    }
+
+    if !filters.kind.is_empty() {
+        let kinds = parse_entry_kinds(&filters.kind)?;
+        predicates.push(Box::new(KindFilter::new(&kinds)));
+    }
+
+    if let Some(ref cat) = filters.category {
+        predicates.push(Box::new(CategoryFilter::new(cat).detected_by(types)));
+    }
+
+    #[cfg(feature = "mime")]
+    if !filters.mime.is_empty() {
+        use rust_filesearch::fs::filters::MimeFilter;
+        predicates.push(Box::new(MimeFilter::new(&filters.mime)));
+    }
+
+    if filters.sparse {
+        predicates.push(Box::new(SparseFilter));
+    }
+
+    if filters.backup_excluded {
+        predicates.push(Box::new(BackupExcludedFilter));
+    }
+
+    if let Some(ref perm) = filters.perm {
+        predicates.push(Box::new(PermFilter::parse(perm)?));
+    }
+
+    if filters.writable {
+        predicates.push(Box::new(PermFilter::Writable));
+    }
+
+    if filters.executable {
+        predicates.push(Box::new(PermFilter::Executable));
+    }
+
+    if filters.world_writable {
+        predicates.push(Box::new(PermFilter::world_writable()));
+    }
+
+    if filters.errors {
+        predicates.push(Box::new(ErrorFilter));
+    }
+
+    if let Some(ref expr) = filters.where_expr {
+        predicates.push(parse_where(expr, types)?);
+    }
+
+    #[cfg(feature = "scripting")]
+    if let Some(ref script) = filters.filter_script {
+        use rust_filesearch::fs::script::ScriptFilter;
+        predicates.push(Box::new(ScriptFilter::load(script)?));
+    }
+
+    #[cfg(feature = "plugins-wasm")]
+    if !filters.no_plugins {
+        use rust_filesearch::plugins::{wasm::enabled_filters, PluginRegistry};
+        predicates.extend(enabled_filters(&PluginRegistry::load()?)?);
+    }
+
+    if predicates.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(Box::new(AndPredicate::new(predicates))))
+    }
+}
+
+/// `--filter-exec`: keep the entries for which the command succeeds
+fn filter_exec(entries: Vec<Entry>, filters: &cli::FilterArgs) -> Vec<Entry> {
+    match &filters.filter_exec {
+        Some(exec) => ExecFilter::new(exec, filters.exec_jobs).retain(entries),
+        None => entries,
+    }
+}
+
+fn build_predicate_from_common(_common: &cli::CommonArgs) -> Result<Option<Box<dyn Predicate>>> {
+    // For basic list, we don't apply additional predicates
+    // They're applied in specific subcommands
+    Ok(None)
+}
+
+/// Composite comparator for `--sort` and `--dirs-first`/`--files-first`
+///
+/// Each sort key breaks ties of the previous one.
+fn entry_order(
+    fields: Vec<SortField>,
+    first: Option<EntryKind>,
+) -> impl Fn(&Entry, &Entry) -> std::cmp::Ordering {
+    move |a, b| {
+        let grouped = first.map_or(std::cmp::Ordering::Equal, |kind| {
+            (b.kind == kind).cmp(&(a.kind == kind))
+        });
+
+        fields
+            .iter()
+            .map(|field| field.compare(a, b))
+            .fold(grouped, std::cmp::Ordering::then)
+    }
+}
+
+/// `--per-dir-limit`: keep the first N entries of each directory, in current order
+fn limit_per_dir(entries: &mut Vec<Entry>, limit: Option<usize>) {
+    let Some(limit) = limit else {
+        return;
+    };
+    let mut counts = std::collections::HashMap::new();
+    entries.retain(|entry| {
+        let dir = entry.path.parent().unwrap_or(Path::new(""));
+        let count = counts.entry(dir.to_path_buf()).or_insert(0);
+        *count += 1;
+        *count <= limit
     });
 }
 
+/// Whether `--unique-by` or `--per-dir-limit` drop entries after sorting
+fn thins_entries(common: &cli::CommonArgs) -> bool {
+    common.unique_by.is_some() || common.per_dir_limit.is_some()
+}
+
+/// Apply `--unique-by`, then `--per-dir-limit`, to entries in output order
+fn thin_entries(entries: &mut Vec<Entry>, common: &cli::CommonArgs) -> Result<()> {
+    if let Some(key) = common.unique_key()? {
+        *entries = collapse_unique(std::mem::take(entries), key);
+    }
+    limit_per_dir(entries, common.per_dir_limit);
+    Ok(())
+}
+
+/// Shared sorting stage; stable, so entries equal on every key keep their order
+fn sort_entries(entries: &mut [Entry], fields: Vec<SortField>, first: Option<EntryKind>) {
+    if fields.is_empty() && first.is_none() {
+        return;
+    }
+    let _timer = timings::phase("sort");
+    entries.sort_by(entry_order(fields, first));
+}
+
 fn output_entries(entries: &[Entry], common: &cli::CommonArgs, no_color: bool) -> Result<()> {
+    let _timer = timings::phase("output");
+
+    // `--reproducible`: the same tree gives the same bytes on any machine
+    let normalized: Vec<Entry>;
+    let entries = if common.reproducible {
+        let mut copy = entries.to_vec();
+        copy.iter_mut().for_each(Entry::make_reproducible);
+        copy.sort_by(|a, b| a.path.cmp(&b.path));
+        normalized = copy;
+        &normalized[..]
+    } else {
+        entries
+    };
+
+    // An unknown format mustn't truncate an existing --output file
+    let format = common.output_format()?;
+    let destination = Destination::open(common.output.as_deref())?;
+    let mut writer: Box<dyn io::Write> = Box::new(destination.clone());
+    // Files only get colors with --color always
+    let no_color = no_color || destination.is_file();
+
     // Check if template export is requested
     #[cfg(feature = "templates")]
     if let Some(template_name) = &common.template {
@@ -706,33 +3152,94 @@ fn output_entries(entries: &[Entry], common: &cli::CommonArgs, no_color: bool) -
             }
         })?;
 
-        let stdout = io::stdout();
-        let mut stdout_lock = stdout.lock();
-
-        return export_with_template(&mut stdout_lock, entries, &format, None);
+        export_with_template(&mut writer, entries, &format, None)?;
+        return Ok(destination.finish()?);
     }
 
-    let format = common.output_format()?;
-    let columns = common.columns()?;
-
-    let stdout = io::stdout();
-    let stdout_lock = stdout.lock();
-
-    let mut sink: Box<dyn OutputSink> = match format {
-        OutputFormat::Pretty => Box::new(PrettyFormatter::new(
-            Box::new(stdout_lock),
-            columns,
-            no_color,
-        )),
-        OutputFormat::Json => Box::new(JsonFormatter::new(Box::new(stdout_lock))),
-        OutputFormat::Ndjson => Box::new(NdjsonFormatter::new(Box::new(stdout_lock))),
-        OutputFormat::Csv => Box::new(CsvFormatter::new(Box::new(stdout_lock), columns)?),
+    #[cfg(feature = "ndjson-zst")]
+    let compressed = match format {
+        OutputFormat::NdjsonZst => {
+            use rust_filesearch::output::compress::ZstdOutput;
+
+            if !destination.is_file() && rust_filesearch::util::is_tty() {
+                return Err(FsError::InvalidFormat {
+                    format: "ndjson.zst output is binary: write it to --output FILE or pipe it"
+                        .to_string(),
+                });
+            }
+            let output = ZstdOutput::new(writer)?;
+            writer = Box::new(output.clone());
+            Some(output)
+        }
+        _ => None,
     };
 
-    for entry in entries {
-        sink.write(entry)?;
+    // Grouped summaries replace the per-entry rows
+    if let Some(spec) = common.group_spec()? {
+        let rows = spec.apply(entries);
+        spec.write(writer, &rows, format, no_color)?;
+    } else {
+        let columns = common.columns()?;
+        let ndjson = |writer| {
+            let ndjson = NdjsonFormatter::new(writer);
+            if common.reproducible {
+                ndjson.with_iso_times()
+            } else {
+                ndjson
+            }
+        };
+
+        let mut sink: Box<dyn OutputSink> = match common.selection()? {
+            Some(selection) => Box::new(SelectFormatter::new(writer, selection, format)?),
+            None => match format {
+                OutputFormat::Pretty => {
+                    let pretty = PrettyFormatter::new(writer, columns, no_color);
+                    Box::new(if common.reproducible {
+                        pretty.with_iso_times()
+                    } else {
+                        pretty
+                    })
+                }
+                OutputFormat::Json => {
+                    let json = JsonFormatter::new(writer);
+                    Box::new(if common.reproducible {
+                        json.with_iso_times()
+                    } else {
+                        json
+                    })
+                }
+                OutputFormat::Ndjson => Box::new(ndjson(writer)),
+                #[cfg(feature = "ndjson-zst")]
+                OutputFormat::NdjsonZst => Box::new(ndjson(writer)),
+                OutputFormat::Csv => {
+                    let csv = CsvFormatter::new(writer, columns)?;
+                    Box::new(if common.reproducible {
+                        csv.with_iso_times()
+                    } else {
+                        csv
+                    })
+                }
+                OutputFormat::Gha | OutputFormat::Sarif => {
+                    return Err(FsError::InvalidFormat {
+                        format: format!(
+                            "{} output is only supported by commands that report findings",
+                            common.format
+                        ),
+                    });
+                }
+            },
+        };
+
+        for entry in entries {
+            sink.write(entry)?;
+        }
+
+        sink.finish()?;
     }
 
-    sink.finish()?;
-    Ok(())
+    #[cfg(feature = "ndjson-zst")]
+    if let Some(output) = compressed {
+        output.finish()?;
+    }
+    Ok(destination.finish()?)
 }
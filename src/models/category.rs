@@ -34,6 +34,23 @@ pub enum MediaType {
 }
 
 impl FileCategory {
+    /// Short label such as `source/rust`, `media/image` or `archive`
+    pub fn label(&self) -> String {
+        match self {
+            FileCategory::Source { language } => format!("source/{}", language),
+            FileCategory::Build => "build".to_string(),
+            FileCategory::Config { format } => format!("config/{}", format),
+            FileCategory::Documentation => "docs".to_string(),
+            FileCategory::Media { media_type } => {
+                format!("media/{}", format!("{:?}", media_type).to_lowercase())
+            }
+            FileCategory::Data { format } => format!("data/{}", format),
+            FileCategory::Archive => "archive".to_string(),
+            FileCategory::Executable => "executable".to_string(),
+            FileCategory::Unknown => "unknown".to_string(),
+        }
+    }
+
     /// Categorize a file based on its extension
     pub fn from_extension(ext: &str) -> Self {
         match ext.to_lowercase().as_str() {
@@ -10,9 +10,12 @@ pub struct DuplicateGroup {
     pub size: u64,
     /// Number of duplicates in this group
     pub count: usize,
+    /// Distinct files on disk; entries hard-linked to each other count once
+    #[serde(default)]
+    pub copies: usize,
     /// List of duplicate entries
     pub entries: Vec<Entry>,
-    /// Total wasted space (size * (count - 1))
+    /// Total wasted space (size * (copies - 1))
     pub wasted_space: u64,
 }
 
@@ -29,8 +32,30 @@ impl DuplicateGroup {
             hash,
             size,
             count,
+            copies: count,
             entries,
             wasted_space,
         }
     }
+
+    /// Set how many distinct files the entries are, recomputing the wasted space
+    pub fn with_copies(mut self, copies: usize) -> Self {
+        self.copies = copies;
+        self.wasted_space = self.size * (copies.max(1) as u64 - 1);
+        self
+    }
+
+    /// All entries are hard links to one file, so nothing can be reclaimed
+    pub fn already_linked(&self) -> bool {
+        self.count > 1 && self.copies == 1
+    }
+}
+
+/// Files whose names differ only by copy markers, version tags or a typo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarNameGroup {
+    /// Normalized name shared by the group (e.g. `report.docx`)
+    pub key: String,
+    /// Files in the group, sorted by path
+    pub entries: Vec<Entry>,
 }
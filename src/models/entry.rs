@@ -1,25 +1,201 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SubsecRound, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Represents a filesystem entry with metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Entry {
+    /// Serialized with non-UTF-8 bytes escaped as `\xNN`
+    #[serde(with = "crate::util::serde_path")]
+    #[schemars(with = "String")]
     pub path: PathBuf,
+    /// File name for display and matching (lossy if not valid UTF-8)
     pub name: String,
     pub size: u64,
     pub kind: EntryKind,
+    /// Last modification, in seconds since the Unix epoch like all times
     #[serde(with = "chrono::serde::ts_seconds")]
+    #[schemars(with = "i64")]
     pub mtime: DateTime<Utc>,
+    /// Last access; often stale, since most filesystems are mounted
+    /// `relatime` or `noatime`
+    #[serde(
+        default,
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<i64>")]
+    pub atime: Option<DateTime<Utc>>,
+    /// Last status change (Unix only): contents, permissions, owner or links
+    #[serde(
+        default,
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<i64>")]
+    pub ctime: Option<DateTime<Utc>>,
+    /// Creation (birth) time, where the platform and filesystem record it
+    #[serde(
+        default,
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<i64>")]
+    pub created: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub perms: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<String>,
+    /// Bytes allocated on disk (files only); below `size` for sparse files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocated: Option<u64>,
+    /// Newest mtime of any file under a directory (set by size aggregation)
+    #[serde(
+        default,
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<i64>")]
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Why the entry couldn't be read fully (metadata or directory contents)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// A directory whose contents couldn't be listed, so nothing below it
+    /// is in the results
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Metadata added after traversal for the requested columns
+    #[serde(default, flatten)]
+    pub enriched: Enrichment,
     pub depth: usize,
 }
 
+/// Metadata filled in by enrichers (see `fs::enrich`), only when requested
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Enrichment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<String>,
+    /// BLAKE3 hash of the file contents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Capture time from photo EXIF data
+    #[serde(
+        default,
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<i64>")]
+    pub taken: Option<DateTime<Utc>>,
+    /// Entries sharing this one's `--unique-by` key, itself included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// Executable format and largest sections (see `fs::bininfo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_sections: Option<String>,
+    /// Excluded from Time Machine backups (macOS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_excluded: Option<bool>,
+}
+
+impl Entry {
+    /// Placeholder for a path whose metadata couldn't be read
+    ///
+    /// Size and mtime are unknown (0 and the Unix epoch), the other times
+    /// unset; `error` says why.
+    pub fn unreadable(path: &Path, kind: EntryKind, depth: usize, error: String) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size: 0,
+            kind,
+            mtime: DateTime::UNIX_EPOCH,
+            atime: None,
+            ctime: None,
+            created: None,
+            perms: None,
+            owner: None,
+            allocated: None,
+            last_activity: None,
+            error: Some(error),
+            truncated: false,
+            enriched: Default::default(),
+            depth,
+        }
+    }
+
+    /// Whether fewer bytes are allocated on disk than the file's apparent size
+    ///
+    /// True for sparse files such as VM images, and also for files on
+    /// compressing filesystems.
+    pub fn is_sparse(&self) -> bool {
+        self.allocated
+            .is_some_and(|allocated| allocated < self.size)
+    }
+
+    /// Drop what differs between machines or runs over the same tree
+    /// (`--reproducible`)
+    ///
+    /// The owner, access/change/birth times and allocated size are cleared,
+    /// the remaining times cut to whole seconds, and path separators become
+    /// `/` on every platform.
+    pub fn make_reproducible(&mut self) {
+        self.owner = None;
+        self.atime = None;
+        self.ctime = None;
+        self.created = None;
+        self.allocated = None;
+        self.mtime = self.mtime.trunc_subsecs(0);
+        self.last_activity = self.last_activity.map(|t| t.trunc_subsecs(0));
+        self.enriched.taken = self.enriched.taken.map(|t| t.trunc_subsecs(0));
+        if std::path::MAIN_SEPARATOR != '/' {
+            let path = self
+                .path
+                .to_string_lossy()
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            self.path = PathBuf::from(path);
+        }
+    }
+}
+
+#[cfg(test)]
+impl Entry {
+    /// Empty file at `path`, named after it, modified now and at depth 0
+    pub(crate) fn test_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path,
+            size: 0,
+            kind: EntryKind::File,
+            mtime: Utc::now(),
+            atime: None,
+            ctime: None,
+            created: None,
+            perms: None,
+            owner: None,
+            allocated: None,
+            last_activity: None,
+            error: None,
+            truncated: false,
+            enriched: Default::default(),
+            depth: 0,
+        }
+    }
+}
+
 /// File system entry types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum EntryKind {
     File,
@@ -29,7 +205,10 @@ pub enum EntryKind {
 
 impl EntryKind {
     pub fn from_metadata(metadata: &std::fs::Metadata) -> Self {
-        let file_type = metadata.file_type();
+        Self::from_file_type(metadata.file_type())
+    }
+
+    pub fn from_file_type(file_type: std::fs::FileType) -> Self {
         if file_type.is_symlink() {
             EntryKind::Symlink
         } else if file_type.is_dir() {
@@ -38,6 +217,15 @@ impl EntryKind {
             EntryKind::File
         }
     }
+
+    /// Lowercase name, as serialized (`file`, `dir`, `symlink`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::File => "file",
+            EntryKind::Dir => "dir",
+            EntryKind::Symlink => "symlink",
+        }
+    }
 }
 
 /// Watch events
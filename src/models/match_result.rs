@@ -12,6 +12,9 @@ pub struct ContentMatch {
     pub column: usize,
     /// The matched text/line
     pub matched_text: String,
+    /// Byte ranges of every match within `matched_text`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub submatches: Vec<MatchSpan>,
     /// Lines before the match for context
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub context_before: Vec<String>,
@@ -19,3 +22,10 @@ pub struct ContentMatch {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub context_after: Vec<String>,
 }
+
+/// Where a pattern matched within a line, as byte offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchSpan {
+    pub start: usize,
+    pub end: usize,
+}
@@ -1,17 +1,17 @@
 // Core entry types
 mod entry;
-pub use entry::{Entry, EntryKind};
+pub use entry::{Enrichment, Entry, EntryKind};
 
 #[cfg(feature = "watch")]
 pub use entry::WatchEvent;
 
 // Content search (grep feature)
 mod match_result;
-pub use match_result::ContentMatch;
+pub use match_result::{ContentMatch, MatchSpan};
 
 // Duplicate detection
 mod duplicate;
-pub use duplicate::DuplicateGroup;
+pub use duplicate::{DuplicateGroup, SimilarNameGroup};
 
 // Git integration
 #[cfg(feature = "git")]
@@ -44,6 +44,38 @@ pub enum SortOrder {
     Desc,
 }
 
+/// One key of a multi-key sort, e.g. `size:desc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortField {
+    pub key: SortKey,
+    pub order: SortOrder,
+}
+
+impl SortField {
+    pub fn compare(&self, a: &Entry, b: &Entry) -> std::cmp::Ordering {
+        let cmp = match self.key {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Mtime => a.mtime.cmp(&b.mtime),
+            // Directories, then files, then symlinks
+            SortKey::Kind => kind_rank(a.kind).cmp(&kind_rank(b.kind)),
+        };
+
+        match self.order {
+            SortOrder::Asc => cmp,
+            SortOrder::Desc => cmp.reverse(),
+        }
+    }
+}
+
+fn kind_rank(kind: EntryKind) -> u8 {
+    match kind {
+        EntryKind::Dir => 0,
+        EntryKind::File => 1,
+        EntryKind::Symlink => 2,
+    }
+}
+
 /// Output columns to display
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Column {
@@ -51,9 +83,23 @@ pub enum Column {
     Name,
     Size,
     Mtime,
+    Atime,
+    Ctime,
+    Created,
     Kind,
     Perms,
     Owner,
+    Allocated,
+    LastActivity,
+    GitStatus,
+    Hash,
+    Mime,
+    Category,
+    Taken,
+    Count,
+    BinSections,
+    BackupExcluded,
+    Error,
 }
 
 impl Column {
@@ -64,9 +110,23 @@ impl Column {
             "name" => Some(Column::Name),
             "size" => Some(Column::Size),
             "mtime" => Some(Column::Mtime),
+            "atime" | "accessed" => Some(Column::Atime),
+            "ctime" | "changed" => Some(Column::Ctime),
+            "created" | "birthtime" | "btime" => Some(Column::Created),
             "kind" => Some(Column::Kind),
             "perms" => Some(Column::Perms),
             "owner" => Some(Column::Owner),
+            "allocated" | "alloc" => Some(Column::Allocated),
+            "last_activity" | "activity" => Some(Column::LastActivity),
+            "git_status" | "git" => Some(Column::GitStatus),
+            "hash" | "blake3" => Some(Column::Hash),
+            "mime" => Some(Column::Mime),
+            "category" => Some(Column::Category),
+            "taken" => Some(Column::Taken),
+            "count" => Some(Column::Count),
+            "bin_sections" | "bin" => Some(Column::BinSections),
+            "backup_excluded" | "tm_excluded" => Some(Column::BackupExcluded),
+            "error" | "errors" => Some(Column::Error),
             _ => None,
         }
     }
@@ -78,7 +138,14 @@ pub enum OutputFormat {
     Pretty,
     Json,
     Ndjson,
+    /// Zstandard-compressed NDJSON
+    #[cfg(feature = "ndjson-zst")]
+    NdjsonZst,
     Csv,
+    /// GitHub Actions annotations, for commands that report findings
+    Gha,
+    /// SARIF 2.1.0, for rule-based commands
+    Sarif,
 }
 
 impl OutputFormat {
@@ -88,7 +155,11 @@ impl OutputFormat {
             "pretty" => Some(OutputFormat::Pretty),
             "json" => Some(OutputFormat::Json),
             "ndjson" => Some(OutputFormat::Ndjson),
+            #[cfg(feature = "ndjson-zst")]
+            "ndjson.zst" | "ndjson-zst" => Some(OutputFormat::NdjsonZst),
             "csv" => Some(OutputFormat::Csv),
+            "gha" => Some(OutputFormat::Gha),
+            "sarif" => Some(OutputFormat::Sarif),
             _ => None,
         }
     }
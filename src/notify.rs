@@ -0,0 +1,206 @@
+//! Notifications for watch and threshold events
+//!
+//! Commands that detect something worth acting on (a watched file changed, a
+//! directory went over quota, a trend crossed an `--alert` threshold) accept
+//! `--notify <target>`. A target is either
+//! `webhook=<url>`, which POSTs the event as JSON, or `desktop`, which raises
+//! a notification through `notify-send` (Linux) or `osascript` (macOS).
+
+use crate::errors::{FsError, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::process::Command;
+
+/// Where a notification is delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyTarget {
+    Webhook(String),
+    Desktop,
+}
+
+impl NotifyTarget {
+    /// Parse `webhook=<url>` or `desktop`
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once('=') {
+            Some(("webhook", url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(NotifyTarget::Webhook(url.to_string()))
+            }
+            Some(("webhook", url)) => Err(FsError::Notify(format!(
+                "webhook URL must start with http:// or https://: {}",
+                url
+            ))),
+            None if spec == "desktop" => Ok(NotifyTarget::Desktop),
+            _ => Err(FsError::Notify(format!(
+                "unknown target '{}' (expected webhook=<url> or desktop)",
+                spec
+            ))),
+        }
+    }
+}
+
+/// An event sent to every configured target
+///
+/// Webhooks receive this as the JSON body. `text` holds the human-readable
+/// summary, which is what Slack and Mattermost incoming webhooks display.
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    /// Event name, e.g. `watch.modify` or `quota.exceeded`
+    pub event: String,
+    pub title: String,
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+    /// Structured details (the watch event or quota report)
+    pub data: serde_json::Value,
+}
+
+impl Notification {
+    pub fn new(event: &str, title: String, text: String, data: serde_json::Value) -> Self {
+        Self {
+            event: event.to_string(),
+            title,
+            text,
+            timestamp: Utc::now(),
+            data,
+        }
+    }
+}
+
+/// Delivers notifications to the targets given with `--notify`
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    targets: Vec<NotifyTarget>,
+}
+
+impl Notifier {
+    pub fn from_specs(specs: &[String]) -> Result<Self> {
+        let targets = specs
+            .iter()
+            .map(|spec| NotifyTarget::parse(spec))
+            .collect::<Result<Vec<_>>>()?;
+
+        #[cfg(not(feature = "webhooks"))]
+        if targets
+            .iter()
+            .any(|t| matches!(t, NotifyTarget::Webhook(_)))
+        {
+            return Err(FsError::Notify(
+                "webhook notifications require the 'webhooks' feature".to_string(),
+            ));
+        }
+
+        Ok(Self { targets })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Send to every target
+    ///
+    /// All targets are attempted even if one fails; the first failure is
+    /// returned.
+    pub fn send(&self, notification: &Notification) -> Result<()> {
+        let mut first_error = None;
+        for target in &self.targets {
+            let result = match target {
+                NotifyTarget::Webhook(url) => post_webhook(url, notification),
+                NotifyTarget::Desktop => show_desktop(notification),
+            };
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(feature = "webhooks")]
+fn post_webhook(url: &str, notification: &Notification) -> Result<()> {
+    ureq::post(url)
+        .timeout(std::time::Duration::from_secs(10))
+        .send_json(notification)
+        .map_err(|e| FsError::Notify(format!("webhook {}: {}", url, e)))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "webhooks"))]
+fn post_webhook(_url: &str, _notification: &Notification) -> Result<()> {
+    Err(FsError::Notify(
+        "webhook notifications require the 'webhooks' feature".to_string(),
+    ))
+}
+
+fn show_desktop(notification: &Notification) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(&notification.text),
+            applescript_string(&notification.title)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command
+            .arg("--app-name=fexplorer")
+            .arg(&notification.title)
+            .arg(&notification.text);
+        command
+    } else {
+        return Err(FsError::Notify(
+            "desktop notifications are not supported on this platform".to_string(),
+        ));
+    };
+
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .status()
+        .map_err(|e| FsError::Notify(format!("failed to run {}: {}", program, e)))?;
+    if !status.success() {
+        return Err(FsError::Notify(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+    Ok(())
+}
+
+/// Quote a string for an AppleScript literal
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            NotifyTarget::parse("desktop").unwrap(),
+            NotifyTarget::Desktop
+        );
+        assert_eq!(
+            NotifyTarget::parse("webhook=https://hooks.example.com/T0/B0").unwrap(),
+            NotifyTarget::Webhook("https://hooks.example.com/T0/B0".to_string())
+        );
+        assert!(NotifyTarget::parse("webhook=ftp://example.com").is_err());
+        assert!(NotifyTarget::parse("email=me@example.com").is_err());
+        assert!(NotifyTarget::parse("desktop=yes").is_err());
+    }
+
+    #[test]
+    fn test_notification_payload() {
+        let notification = Notification::new(
+            "quota.exceeded",
+            "Quota exceeded".to_string(),
+            "/scratch: size 61 GiB exceeds limit 50 GiB".to_string(),
+            serde_json::json!({ "path": "/scratch" }),
+        );
+        let payload = serde_json::to_value(&notification).unwrap();
+        assert_eq!(payload["event"], "quota.exceeded");
+        assert_eq!(payload["data"]["path"], "/scratch");
+        assert!(payload["text"].as_str().unwrap().contains("50 GiB"));
+    }
+}
@@ -0,0 +1,506 @@
+//! Grouped summaries for `--group-by` / `--agg`
+//!
+//! Instead of one row per entry, entries are grouped by one or more key
+//! expressions (the `--select` expression language) and each group gets one
+//! row of aggregates:
+//!
+//! ```text
+//! --group-by 'top, ext' --agg 'count, sum(size), max(mtime)'
+//! ```
+//!
+//! Aggregates are `count`, `sum(x)`, `avg(x)`, `min(x)` and `max(x)`, each
+//! optionally renamed with `as`. Without `--group-by`, all entries form a
+//! single group.
+
+use crate::errors::{FsError, Result};
+use crate::models::{Column, Entry, OutputFormat};
+use crate::output::color;
+use crate::output::select::{FieldValue, Row, Selection};
+use crate::timings;
+use crate::util::{format_size_human, is_tty};
+use csv::Writer;
+use nu_ansi_term::Style;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AggFunc {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// One aggregate column
+#[derive(Debug, Clone)]
+struct Aggregate {
+    name: String,
+    func: AggFunc,
+    /// The aggregated expression (`None` for `count`)
+    expr: Option<Selection>,
+    /// Render as a size in pretty output (aggregates of `size`/`allocated`)
+    is_size: bool,
+}
+
+/// Running state of one aggregate within a group
+#[derive(Debug, Clone)]
+enum Accumulator {
+    Count(u64),
+    Sum(Option<f64>),
+    Avg(f64, u64),
+    Extreme(Option<FieldValue>),
+}
+
+impl Accumulator {
+    fn new(func: AggFunc) -> Self {
+        match func {
+            AggFunc::Count => Accumulator::Count(0),
+            AggFunc::Sum => Accumulator::Sum(None),
+            AggFunc::Avg => Accumulator::Avg(0.0, 0),
+            AggFunc::Min | AggFunc::Max => Accumulator::Extreme(None),
+        }
+    }
+
+    fn add(&mut self, func: AggFunc, value: FieldValue) {
+        match (self, value) {
+            (Accumulator::Count(n), _) => *n += 1,
+            (Accumulator::Sum(total), FieldValue::Number(x)) => {
+                *total = Some(total.unwrap_or(0.0) + x)
+            }
+            (Accumulator::Avg(total, n), FieldValue::Number(x)) => {
+                *total += x;
+                *n += 1;
+            }
+            (Accumulator::Extreme(current), value) if value != FieldValue::Null => {
+                let wanted = if func == AggFunc::Min {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+                let replace = match current {
+                    None => true,
+                    Some(existing) => compare(&value, existing) == Some(wanted),
+                };
+                if replace {
+                    *current = Some(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> FieldValue {
+        match self {
+            Accumulator::Count(n) => FieldValue::Number(n as f64),
+            Accumulator::Sum(total) => total.map_or(FieldValue::Null, FieldValue::Number),
+            Accumulator::Avg(_, 0) => FieldValue::Null,
+            Accumulator::Avg(total, n) => FieldValue::Number(total / n as f64),
+            Accumulator::Extreme(value) => value.unwrap_or(FieldValue::Null),
+        }
+    }
+}
+
+/// Order two values of the same kind; `None` for mismatched kinds
+fn compare(a: &FieldValue, b: &FieldValue) -> Option<Ordering> {
+    match (a, b) {
+        (FieldValue::Number(x), FieldValue::Number(y)) => x.partial_cmp(y),
+        (FieldValue::Text(x), FieldValue::Text(y)) => Some(x.cmp(y)),
+        (FieldValue::Time(x), FieldValue::Time(y)) => Some(x.cmp(y)),
+        (FieldValue::Null, FieldValue::Null) => Some(Ordering::Equal),
+        (FieldValue::Null, _) => Some(Ordering::Less),
+        (_, FieldValue::Null) => Some(Ordering::Greater),
+        _ => None,
+    }
+}
+
+/// A parsed `--group-by` / `--agg` pair
+#[derive(Debug, Clone)]
+pub struct GroupSpec {
+    keys: Option<Selection>,
+    aggregates: Vec<Aggregate>,
+}
+
+impl GroupSpec {
+    pub fn parse(group_by: Option<&str>, agg: &str) -> Result<Self> {
+        let keys = group_by.map(Selection::parse).transpose()?;
+
+        let pattern = Regex::new(r"(?is)^(\w+)\s*(?:\((.*)\))?(?:\s+as\s+(\S+))?$")
+            .expect("aggregate pattern is valid");
+        let mut aggregates = Vec::new();
+        for item in split_top_level(agg) {
+            let item = item.trim();
+            let caps = pattern
+                .captures(item)
+                .ok_or_else(|| invalid(format!("invalid aggregate '{}'", item)))?;
+
+            let func = match caps[1].to_lowercase().as_str() {
+                "count" => AggFunc::Count,
+                "sum" => AggFunc::Sum,
+                "avg" | "mean" => AggFunc::Avg,
+                "min" => AggFunc::Min,
+                "max" => AggFunc::Max,
+                other => return Err(invalid(format!("unknown aggregate '{}'", other))),
+            };
+            let arg = caps.get(2).map(|m| m.as_str().trim()).unwrap_or_default();
+
+            let expr = match (func, arg) {
+                (AggFunc::Count, "" | "*") => None,
+                (AggFunc::Count, _) => {
+                    return Err(invalid("count takes no argument".to_string()));
+                }
+                (_, "") => return Err(invalid(format!("{} needs an argument", &caps[1]))),
+                (_, arg) => {
+                    let selection = Selection::parse(arg)?;
+                    if selection.fields.len() != 1 {
+                        return Err(invalid(format!("{} takes one argument", &caps[1])));
+                    }
+                    if matches!(func, AggFunc::Sum | AggFunc::Avg)
+                        && !selection.fields[0].is_numeric()
+                    {
+                        return Err(invalid(format!(
+                            "{} needs a numeric argument, not '{}'",
+                            &caps[1], arg
+                        )));
+                    }
+                    Some(selection)
+                }
+            };
+
+            let name = match caps.get(3) {
+                Some(alias) => alias.as_str().to_string(),
+                None if expr.is_none() => "count".to_string(),
+                None => item.to_string(),
+            };
+            aggregates.push(Aggregate {
+                name,
+                func,
+                is_size: matches!(arg.to_lowercase().as_str(), "size" | "allocated"),
+                expr,
+            });
+        }
+
+        if aggregates.is_empty() {
+            return Err(invalid("no aggregates given".to_string()));
+        }
+        Ok(Self { keys, aggregates })
+    }
+
+    /// Output column names: group keys, then aggregates
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.keys.as_ref().map(Selection::names).unwrap_or_default();
+        names.extend(self.aggregates.iter().map(|a| a.name.clone()));
+        names
+    }
+
+    /// Columns referenced by keys and aggregates, so their enrichers can run
+    pub fn columns(&self) -> Vec<Column> {
+        let mut columns = self
+            .keys
+            .as_ref()
+            .map(Selection::columns)
+            .unwrap_or_default();
+        for selection in self.aggregates.iter().filter_map(|a| a.expr.as_ref()) {
+            for column in selection.columns() {
+                if !columns.contains(&column) {
+                    columns.push(column);
+                }
+            }
+        }
+        columns
+    }
+
+    /// Group the entries and compute one row per group, sorted by key
+    pub fn apply(&self, entries: &[Entry]) -> Vec<Vec<FieldValue>> {
+        let _timer = timings::phase("group");
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(Vec<FieldValue>, Vec<Accumulator>)> = Vec::new();
+
+        for entry in entries {
+            let key = self
+                .keys
+                .as_ref()
+                .map(|keys| keys.eval(entry))
+                .unwrap_or_default();
+            let lookup = key
+                .iter()
+                .map(|value| format!("{:?}", value))
+                .collect::<Vec<_>>()
+                .join("\0");
+
+            let slot = *index.entry(lookup).or_insert_with(|| {
+                let accumulators = self
+                    .aggregates
+                    .iter()
+                    .map(|a| Accumulator::new(a.func))
+                    .collect();
+                groups.push((key, accumulators));
+                groups.len() - 1
+            });
+
+            for (aggregate, accumulator) in self.aggregates.iter().zip(&mut groups[slot].1) {
+                let value = aggregate
+                    .expr
+                    .as_ref()
+                    .map_or(FieldValue::Null, |expr| expr.eval(entry).remove(0));
+                accumulator.add(aggregate.func, value);
+            }
+        }
+
+        groups.sort_by(|(a, _), (b, _)| {
+            a.iter()
+                .zip(b)
+                .map(|(x, y)| compare(x, y).unwrap_or_else(|| x.to_string().cmp(&y.to_string())))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        groups
+            .into_iter()
+            .map(|(mut key, accumulators)| {
+                key.extend(accumulators.into_iter().map(Accumulator::finish));
+                key
+            })
+            .collect()
+    }
+
+    /// Write grouped rows in the requested format
+    pub fn write(
+        &self,
+        mut writer: Box<dyn Write>,
+        rows: &[Vec<FieldValue>],
+        format: OutputFormat,
+        no_color: bool,
+    ) -> Result<()> {
+        let names = self.names();
+        match format {
+            OutputFormat::Pretty => self.write_table(&mut writer, &names, rows, no_color)?,
+            OutputFormat::Csv => {
+                let mut csv = Writer::from_writer(writer);
+                csv.write_record(&names)?;
+                for row in rows {
+                    csv.write_record(row.iter().map(|v| v.to_string()))?;
+                }
+                csv.flush()?;
+                return Ok(());
+            }
+            OutputFormat::Json => {
+                let rows: Vec<Row> = rows
+                    .iter()
+                    .map(|values| Row {
+                        names: &names,
+                        values,
+                    })
+                    .collect();
+                writeln!(writer, "{}", serde_json::to_string_pretty(&rows)?)?;
+            }
+            #[cfg(feature = "ndjson-zst")]
+            OutputFormat::NdjsonZst => {
+                return self.write(writer, rows, OutputFormat::Ndjson, no_color);
+            }
+            OutputFormat::Ndjson => {
+                for values in rows {
+                    let row = Row {
+                        names: &names,
+                        values,
+                    };
+                    writeln!(writer, "{}", serde_json::to_string(&row)?)?;
+                }
+            }
+            OutputFormat::Gha | OutputFormat::Sarif => {
+                return Err(invalid(
+                    "grouped output needs --format pretty, csv, json or ndjson".to_string(),
+                ));
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Aligned table; numbers right-aligned, sizes human-readable
+    fn write_table(
+        &self,
+        writer: &mut dyn Write,
+        names: &[String],
+        rows: &[Vec<FieldValue>],
+        no_color: bool,
+    ) -> Result<()> {
+        let key_count = names.len() - self.aggregates.len();
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let is_size = i >= key_count && self.aggregates[i - key_count].is_size;
+                        match value {
+                            FieldValue::Number(n) if is_size => format_size_human(*n as u64),
+                            FieldValue::Number(n) if n.fract() != 0.0 => format!("{:.2}", n),
+                            FieldValue::Time(t) => t.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            other => other.to_string(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..names.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([names[i].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let numeric: Vec<bool> = (0..names.len())
+            .map(|i| {
+                rows.iter()
+                    .all(|row| matches!(row[i], FieldValue::Number(_) | FieldValue::Null))
+            })
+            .collect();
+
+        let render = |row: &[String]| {
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if numeric[i] {
+                        format!("{:>width$}", cell, width = widths[i])
+                    } else {
+                        format!("{:<width$}", cell, width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        let header = render(names);
+        if color::use_color(is_tty() && !no_color) {
+            writeln!(writer, "{}", Style::new().bold().paint(header))?;
+        } else {
+            writeln!(writer, "{}", header)?;
+        }
+        for row in &cells {
+            writeln!(writer, "{}", render(row))?;
+        }
+        Ok(())
+    }
+}
+
+/// Split on commas that aren't inside parentheses or quotes
+fn split_top_level(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts.into_iter().filter(|p| !p.trim().is_empty()).collect()
+}
+
+fn invalid(message: String) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("--agg: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn make_test_entry(path: &str, size: u64, depth: usize, mtime_secs: i64) -> Entry {
+        Entry {
+            size,
+            mtime: Utc.timestamp_opt(mtime_secs, 0).unwrap(),
+            depth,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_group_by_top_and_ext() {
+        let entries = vec![
+            make_test_entry("root/src/main.rs", 100, 2, 10),
+            make_test_entry("root/src/lib.rs", 50, 2, 30),
+            make_test_entry("root/docs/a/guide.md", 7, 3, 20),
+            make_test_entry("root/src/notes.md", 5, 2, 40),
+        ];
+        let spec =
+            GroupSpec::parse(Some("top, ext"), "count, sum(size) as bytes, max(mtime)").unwrap();
+        assert_eq!(spec.names(), ["top", "ext", "count", "bytes", "max(mtime)"]);
+
+        let rows = spec.apply(&entries);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0][0], FieldValue::Text("root/docs".to_string()));
+        assert_eq!(
+            rows[2],
+            vec![
+                FieldValue::Text("root/src".to_string()),
+                FieldValue::Text("rs".to_string()),
+                FieldValue::Number(2.0),
+                FieldValue::Number(150.0),
+                FieldValue::Time(Utc.timestamp_opt(30, 0).unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_without_keys() {
+        let entries = vec![
+            make_test_entry("a.txt", 10, 1, 0),
+            make_test_entry("b.txt", 30, 1, 0),
+        ];
+        let spec = GroupSpec::parse(None, "count(*), avg(size), min(name)").unwrap();
+        let rows = spec.apply(&entries);
+        assert_eq!(
+            rows,
+            vec![vec![
+                FieldValue::Number(2.0),
+                FieldValue::Number(20.0),
+                FieldValue::Text("a.txt".to_string()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(GroupSpec::parse(None, "").is_err());
+        assert!(GroupSpec::parse(None, "median(size)").is_err());
+        assert!(GroupSpec::parse(None, "sum").is_err());
+        assert!(GroupSpec::parse(None, "count(size)").is_err());
+        assert!(GroupSpec::parse(None, "sum(size, depth)").is_err());
+        assert!(GroupSpec::parse(None, "avg(name)").is_err());
+        assert!(GroupSpec::parse(None, "sum(mtime)").is_err());
+        assert!(GroupSpec::parse(None, "sum(size / 1024), avg(len(name))").is_ok());
+        assert!(GroupSpec::parse(Some("colour"), "count").is_err());
+    }
+
+    #[test]
+    fn test_split_top_level() {
+        assert_eq!(
+            split_top_level("count, sum(round(size, 2)), max(concat(name, ','))"),
+            ["count", " sum(round(size, 2))", " max(concat(name, ','))"]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! When output is colored (`--color`)
+//!
+//! `main` settles it once with [`set`]: `--no-color` (and `--plain`) turn
+//! color off, then `--color` decides, then the environment: a
+//! `CLICOLOR_FORCE` other than `0` turns color on even into pipes, else a
+//! non-empty `NO_COLOR` turns it off. In auto mode, the default, output is
+//! colored only where it goes to a terminal. Formatters work out whether
+//! that's the case and ask [`use_color`].
+
+use clap::ValueEnum;
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// When to color output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only on a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    const ALL: [ColorChoice; 3] = [ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never];
+}
+
+static CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Color output by `choice` for the rest of this process
+pub fn set(choice: ColorChoice) {
+    CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// The color choice in effect
+pub fn current() -> ColorChoice {
+    let index = CHOICE.load(Ordering::Relaxed) as usize;
+    ColorChoice::ALL
+        .get(index)
+        .copied()
+        .unwrap_or(ColorChoice::Auto)
+}
+
+/// Whether to color output that auto mode would color if `is_terminal`
+pub fn use_color(is_terminal: bool) -> bool {
+    match current() {
+        ColorChoice::Auto => is_terminal,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// Color choice from `--no-color` and `--color`, else from the environment
+pub fn detect(flag: Option<ColorChoice>, no_color: bool) -> ColorChoice {
+    if no_color {
+        return ColorChoice::Never;
+    }
+    flag.unwrap_or_else(|| from_env(|name| std::env::var_os(name)))
+}
+
+/// `CLICOLOR_FORCE` and `NO_COLOR`, looked up with `var`
+fn from_env(var: impl Fn(&str) -> Option<OsString>) -> ColorChoice {
+    if var("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+        ColorChoice::Always
+    } else if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            from_env(move |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            })
+        };
+        assert_eq!(env(&[]), ColorChoice::Auto);
+        assert_eq!(env(&[("NO_COLOR", "1")]), ColorChoice::Never);
+        assert_eq!(env(&[("NO_COLOR", "")]), ColorChoice::Auto);
+        assert_eq!(env(&[("CLICOLOR_FORCE", "1")]), ColorChoice::Always);
+        assert_eq!(env(&[("CLICOLOR_FORCE", "0")]), ColorChoice::Auto);
+        assert_eq!(
+            env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]),
+            ColorChoice::Always
+        );
+
+        // Flags beat the environment, --no-color beats --color
+        assert_eq!(
+            detect(Some(ColorChoice::Always), false),
+            ColorChoice::Always
+        );
+        assert_eq!(detect(Some(ColorChoice::Always), true), ColorChoice::Never);
+    }
+}
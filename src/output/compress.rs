@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+type Encoder = zstd::Encoder<'static, Box<dyn Write>>;
+
+/// Zstandard-compressing writer, for `--format ndjson.zst`
+///
+/// Formatters own their writer and only flush it, so the writer is shared:
+/// formatters get a clone, and the caller completes the frame with
+/// [`ZstdOutput::finish`] once the formatter is done.
+#[derive(Clone)]
+pub struct ZstdOutput {
+    encoder: Rc<RefCell<Option<Encoder>>>,
+}
+
+impl ZstdOutput {
+    /// Level 3 is zstd's default: fast, and JSON still shrinks about tenfold
+    pub const LEVEL: i32 = 3;
+
+    pub fn new(writer: Box<dyn Write>) -> io::Result<Self> {
+        let encoder = Encoder::new(writer, Self::LEVEL)?;
+        Ok(Self {
+            encoder: Rc::new(RefCell::new(Some(encoder))),
+        })
+    }
+
+    /// Write the end of the frame and flush the underlying writer
+    pub fn finish(&self) -> io::Result<()> {
+        match self.encoder.borrow_mut().take() {
+            Some(encoder) => encoder.finish()?.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn with_encoder<T>(&self, f: impl FnOnce(&mut Encoder) -> io::Result<T>) -> io::Result<T> {
+        match self.encoder.borrow_mut().as_mut() {
+            Some(encoder) => f(encoder),
+            None => Err(io::Error::other("compressed output already finished")),
+        }
+    }
+}
+
+impl Write for ZstdOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_encoder(|encoder| encoder.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_encoder(|encoder| encoder.flush())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writer whose contents stay readable after it's boxed away
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sink = Shared::default();
+        let output = ZstdOutput::new(Box::new(sink.clone())).unwrap();
+
+        let mut writer: Box<dyn Write> = Box::new(output.clone());
+        for i in 0..1000 {
+            writeln!(writer, "{{\"n\":{}}}", i).unwrap();
+        }
+        writer.flush().unwrap();
+        output.finish().unwrap();
+        assert!(writer.write_all(b"late").is_err());
+
+        let compressed = sink.0.borrow().clone();
+        let text = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        assert_eq!(text.lines().count(), 1000);
+        assert!(compressed.len() < text.len() / 4);
+    }
+}
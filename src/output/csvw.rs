@@ -1,12 +1,16 @@
 use crate::errors::Result;
 use crate::models::{Column, Entry};
 use crate::output::format::OutputSink;
+use crate::util::escape_path;
+use chrono::{DateTime, SecondsFormat, Utc};
 use csv::Writer;
 use std::io::Write;
 
 pub struct CsvFormatter {
     writer: Writer<Box<dyn Write>>,
     columns: Vec<Column>,
+    /// `Z` instead of `+00:00`, at whole seconds (`--reproducible`)
+    iso_times: bool,
 }
 
 impl CsvFormatter {
@@ -20,7 +24,26 @@ impl CsvFormatter {
             .collect();
         writer.write_record(&headers)?;
 
-        Ok(Self { writer, columns })
+        Ok(Self {
+            writer,
+            columns,
+            iso_times: false,
+        })
+    }
+
+    /// Print times as `2025-01-31T09:30:00Z` instead of
+    /// `2025-01-31T09:30:00.123+00:00`
+    pub fn with_iso_times(mut self) -> Self {
+        self.iso_times = true;
+        self
+    }
+
+    fn format_time(&self, time: DateTime<Utc>) -> String {
+        if self.iso_times {
+            time.to_rfc3339_opts(SecondsFormat::Secs, true)
+        } else {
+            time.to_rfc3339()
+        }
     }
 }
 
@@ -30,13 +53,45 @@ impl OutputSink for CsvFormatter {
             .columns
             .iter()
             .map(|column| match column {
-                Column::Path => entry.path.display().to_string(),
+                Column::Path => escape_path(&entry.path).into_owned(),
                 Column::Name => entry.name.clone(),
                 Column::Size => entry.size.to_string(),
-                Column::Mtime => entry.mtime.to_rfc3339(),
+                Column::Mtime => self.format_time(entry.mtime),
+                Column::Atime => entry.atime.map(|t| self.format_time(t)).unwrap_or_default(),
+                Column::Ctime => entry.ctime.map(|t| self.format_time(t)).unwrap_or_default(),
+                Column::Created => entry
+                    .created
+                    .map(|t| self.format_time(t))
+                    .unwrap_or_default(),
                 Column::Kind => format!("{:?}", entry.kind).to_lowercase(),
                 Column::Perms => entry.perms.clone().unwrap_or_default(),
                 Column::Owner => entry.owner.clone().unwrap_or_default(),
+                Column::Allocated => entry.allocated.map(|a| a.to_string()).unwrap_or_default(),
+                Column::LastActivity => entry
+                    .last_activity
+                    .map(|t| self.format_time(t))
+                    .unwrap_or_default(),
+                Column::GitStatus => entry.enriched.git_status.clone().unwrap_or_default(),
+                Column::Hash => entry.enriched.hash.clone().unwrap_or_default(),
+                Column::Mime => entry.enriched.mime.clone().unwrap_or_default(),
+                Column::Category => entry.enriched.category.clone().unwrap_or_default(),
+                Column::Taken => entry
+                    .enriched
+                    .taken
+                    .map(|t| self.format_time(t))
+                    .unwrap_or_default(),
+                Column::Count => entry
+                    .enriched
+                    .count
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                Column::BinSections => entry.enriched.bin_sections.clone().unwrap_or_default(),
+                Column::BackupExcluded => entry
+                    .enriched
+                    .backup_excluded
+                    .map(|b| b.to_string())
+                    .unwrap_or_default(),
+                Column::Error => entry.error.clone().unwrap_or_default(),
             })
             .collect();
 
@@ -53,21 +108,13 @@ impl OutputSink for CsvFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::EntryKind;
-    use std::path::PathBuf;
 
     fn make_test_entry(name: &str) -> Entry {
-        use chrono::Utc;
-
         Entry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
             size: 1024,
-            kind: EntryKind::File,
-            mtime: Utc::now(),
             perms: Some("rw-r--r--".to_string()),
             owner: Some("1000".to_string()),
-            depth: 0,
+            ..Entry::test_file(name)
         }
     }
 
@@ -83,4 +130,15 @@ mod tests {
         // Can't easily extract output from boxed writer in this test
         // In real usage, output goes to stdout which is fine
     }
+
+    #[test]
+    fn test_iso_times() {
+        let time = "2025-01-31T09:30:00Z".parse().unwrap();
+        let formatter = CsvFormatter::new(Box::new(Vec::new()), vec![Column::Mtime]).unwrap();
+        assert_eq!(formatter.format_time(time), "2025-01-31T09:30:00+00:00");
+        assert_eq!(
+            formatter.with_iso_times().format_time(time),
+            "2025-01-31T09:30:00Z"
+        );
+    }
 }
@@ -0,0 +1,134 @@
+use crate::errors::{FsError, Result};
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+enum Writer {
+    Stdout(io::StdoutLock<'static>),
+    File(BufWriter<File>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+}
+
+/// Where a command's results go: stdout, or the `--output` file
+///
+/// A path ending in `.gz` is gzip-compressed. Formatters own their writer,
+/// so as for `ndjson.zst` output the writer is shared: formatters get a
+/// clone, and the caller completes the file with [`Destination::finish`].
+#[derive(Clone)]
+pub struct Destination {
+    writer: Rc<RefCell<Option<Writer>>>,
+    is_file: bool,
+}
+
+impl Destination {
+    /// Open `path`, or stdout when there is none
+    pub fn open(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::new(Writer::Stdout(io::stdout().lock())));
+        };
+        let gzip = path.extension().is_some_and(|ext| ext == "gz");
+        #[cfg(not(feature = "gzip"))]
+        if gzip {
+            return Err(FsError::InvalidFormat {
+                format: "gzip output (a .gz --output path) requires the gzip feature".to_string(),
+            });
+        }
+
+        let file = File::create(path).map_err(|e| FsError::PathAccess {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let file = BufWriter::new(file);
+        #[cfg(feature = "gzip")]
+        if gzip {
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            return Ok(Self::new(Writer::Gzip(encoder)));
+        }
+        Ok(Self::new(Writer::File(file)))
+    }
+
+    fn new(writer: Writer) -> Self {
+        Self {
+            is_file: !matches!(writer, Writer::Stdout(_)),
+            writer: Rc::new(RefCell::new(Some(writer))),
+        }
+    }
+
+    /// Whether results go to a file rather than stdout
+    pub fn is_file(&self) -> bool {
+        self.is_file
+    }
+
+    /// Complete the output: write the gzip trailer, if any, and flush
+    pub fn finish(&self) -> io::Result<()> {
+        match self.writer.borrow_mut().take() {
+            Some(Writer::Stdout(mut out)) => out.flush(),
+            Some(Writer::File(mut file)) => file.flush(),
+            #[cfg(feature = "gzip")]
+            Some(Writer::Gzip(encoder)) => encoder.finish()?.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn with_writer<T>(&self, f: impl FnOnce(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        match self.writer.borrow_mut().as_mut() {
+            Some(Writer::Stdout(out)) => f(out),
+            Some(Writer::File(file)) => f(file),
+            #[cfg(feature = "gzip")]
+            Some(Writer::Gzip(encoder)) => f(encoder),
+            None => Err(io::Error::other("output already finished")),
+        }
+    }
+}
+
+impl Write for Destination {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_writer(|writer| writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_writer(|writer| writer.flush())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.txt");
+        let output = Destination::open(Some(&path)).unwrap();
+        assert!(output.is_file());
+
+        let mut writer: Box<dyn Write> = Box::new(output.clone());
+        writeln!(writer, "one").unwrap();
+        output.finish().unwrap();
+        assert!(writer.write_all(b"late").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\n");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_file() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.ndjson.gz");
+        let mut output = Destination::open(Some(&path)).unwrap();
+        for i in 0..100 {
+            writeln!(output, "{{\"n\":{}}}", i).unwrap();
+        }
+        output.finish().unwrap();
+
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text.lines().count(), 100);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::errors::Result;
+use crate::models::Entry;
+use crate::output::format::OutputSink;
+use crate::util::format_size_du;
+use std::io::Write;
+
+/// `du` compatible formatter: one `size<TAB>path` line per entry
+///
+/// Entry sizes are expected to already hold du totals. Without `human`,
+/// sizes are printed in 1K blocks like plain `du`.
+pub struct DuFormatter {
+    writer: Box<dyn Write>,
+    human: bool,
+}
+
+impl DuFormatter {
+    pub fn new(writer: Box<dyn Write>, human: bool) -> Self {
+        Self { writer, human }
+    }
+
+    fn format_size(&self, size: u64) -> String {
+        if self.human {
+            format_size_du(size)
+        } else {
+            size.div_ceil(1024).to_string()
+        }
+    }
+}
+
+impl OutputSink for DuFormatter {
+    fn write(&mut self, entry: &Entry) -> Result<()> {
+        writeln!(
+            self.writer,
+            "{}\t{}",
+            self.format_size(entry.size),
+            entry.path.display()
+        )?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_du_block_sizes() {
+        let formatter = DuFormatter::new(Box::new(Vec::new()), false);
+        assert_eq!(formatter.format_size(0), "0");
+        assert_eq!(formatter.format_size(1), "1");
+        assert_eq!(formatter.format_size(4096), "4");
+        assert_eq!(formatter.format_size(4097), "5");
+
+        let formatter = DuFormatter::new(Box::new(Vec::new()), true);
+        assert_eq!(formatter.format_size(4096), "4.0K");
+    }
+}
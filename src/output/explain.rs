@@ -0,0 +1,176 @@
+use crate::errors::Result;
+use crate::fs::explain::QueryPlan;
+use crate::fs::filters::PredicateNode;
+use crate::plain;
+use std::io::Write;
+
+/// Human-readable `--explain` output
+pub fn write_plan(writer: &mut dyn Write, plan: &QueryPlan) -> Result<()> {
+    match (&plan.results, &plan.path_list) {
+        (Some(source), _) if source.as_os_str() == "-" => {
+            writeln!(writer, "Entries: exported results read from stdin")?
+        }
+        (Some(source), _) => writeln!(
+            writer,
+            "Entries: exported results read from {}",
+            source.display()
+        )?,
+        (None, Some(source)) if source.as_os_str() == "-" => {
+            writeln!(writer, "Paths: read from stdin")?
+        }
+        (None, Some(source)) => writeln!(writer, "Paths: read from {}", source.display())?,
+        (None, None) => {
+            writeln!(writer, "Roots:")?;
+            for root in &plan.roots {
+                writeln!(writer, "  {}", root.display())?;
+            }
+        }
+    }
+
+    let traversal = &plan.traversal;
+    let setting = |on: bool, yes: &'static str, no: &'static str| if on { yes } else { no };
+    writeln!(writer, "\nTraversal:")?;
+    writeln!(
+        writer,
+        "  max depth:        {}",
+        traversal
+            .max_depth
+            .map_or("unlimited".to_string(), |depth| depth.to_string())
+    )?;
+    writeln!(
+        writer,
+        "  hidden entries:   {}",
+        setting(traversal.hidden, "included", "skipped")
+    )?;
+    writeln!(
+        writer,
+        "  .gitignore:       {}",
+        setting(traversal.gitignore, "respected", "ignored")
+    )?;
+    writeln!(
+        writer,
+        "  symlinks:         {}",
+        setting(traversal.follow_symlinks, "followed", "not followed")
+    )?;
+    writeln!(
+        writer,
+        "  other users:      {}",
+        setting(traversal.skip_other_users, "skipped", "walked")
+    )?;
+    if !traversal.exclude.is_empty() {
+        writeln!(
+            writer,
+            "  exclude:          {}",
+            traversal.exclude.join(", ")
+        )?;
+    }
+    let order = match (traversal.threads, traversal.unordered) {
+        (1, _) => "",
+        (_, false) => " (sorted by path)",
+        (_, true) => " (unordered)",
+    };
+    writeln!(writer, "  threads:          {}{}", traversal.threads, order)?;
+
+    match &plan.filter {
+        Some(filter) => {
+            writeln!(
+                writer,
+                "\nFilters (in evaluation order, stopping at the first that decides):"
+            )?;
+            write_node(writer, filter, "  ", "")?;
+            writeln!(
+                writer,
+                "\nEstimated to keep {} of the walked entries",
+                percent(plan.selectivity)
+            )?;
+        }
+        None => writeln!(writer, "\nFilters: none, every walked entry is kept")?,
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// One node and its children, drawn as a tree (indented with `--plain`)
+fn write_node(
+    writer: &mut dyn Write,
+    node: &PredicateNode,
+    prefix: &str,
+    branch: &str,
+) -> Result<()> {
+    let detail = if node.detail.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", node.detail)
+    };
+    writeln!(
+        writer,
+        "{}{}{}{}  (~{})",
+        prefix,
+        branch,
+        node.filter,
+        detail,
+        percent(node.selectivity)
+    )?;
+
+    let child_prefix = match branch {
+        "├─ " => format!("{}│  ", prefix),
+        "└─ " => format!("{}   ", prefix),
+        "  " => format!("{}  ", prefix),
+        _ => prefix.to_string(),
+    };
+    for (i, child) in node.children.iter().enumerate() {
+        let branch = if plain::is_enabled() {
+            "  "
+        } else if i + 1 == node.children.len() {
+            "└─ "
+        } else {
+            "├─ "
+        };
+        write_node(writer, child, &child_prefix, branch)?;
+    }
+    Ok(())
+}
+
+/// Share as a percentage, keeping small non-zero shares visible
+fn percent(share: f64) -> String {
+    let percent = share * 100.0;
+    if percent > 0.0 && percent < 1.0 {
+        "<1%".to_string()
+    } else {
+        format!("{:.0}%", percent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::filters::{ExtensionFilter, NotPredicate};
+    use crate::fs::traverse::TraverseConfig;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_plan() {
+        let predicate = NotPredicate::new(Box::new(ExtensionFilter::new(&[
+            "log".to_string(),
+            "tmp".to_string(),
+        ])));
+        let config = TraverseConfig {
+            exclude: vec!["target".to_string()],
+            ..Default::default()
+        };
+        let plan = QueryPlan::new(&[PathBuf::from(".")], None, &config, Some(&predicate));
+
+        let mut out = Vec::new();
+        write_plan(&mut out, &plan).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("max depth:        unlimited"), "{}", out);
+        assert!(out.contains("exclude:          target"));
+        assert!(
+            out.contains("  not  (~90%)\n  └─ extension log, tmp  (~10%)"),
+            "{}",
+            out
+        );
+        assert!(out.contains("Estimated to keep 90%"));
+    }
+}
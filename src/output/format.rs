@@ -1,10 +1,10 @@
 use crate::errors::Result;
 use crate::models::Entry;
 
-/// Trait for output formatters
-pub trait OutputSink {
+/// Trait for output formatters, of entries unless `T` says otherwise
+pub trait OutputSink<T = Entry> {
     /// Write a single entry
-    fn write(&mut self, entry: &Entry) -> Result<()>;
+    fn write(&mut self, entry: &T) -> Result<()>;
 
     /// Finish writing and flush any buffered data
     fn finish(&mut self) -> Result<()>;
@@ -0,0 +1,148 @@
+use crate::fs::lint::{Finding, Severity};
+use crate::fs::portability::PortabilityIssue;
+use crate::fs::quota::QuotaReport;
+use crate::util::workspace_path;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Annotation level, as understood by GitHub Actions workflow commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Notice,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Notice => write!(f, "notice"),
+            Level::Warning => write!(f, "warning"),
+            Level::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A GitHub Actions annotation (`::error file=src/a.rs,line=3,title=...::message`)
+///
+/// Printed to stdout from a workflow step, annotations show up in the run
+/// summary and inline on the pull request diff.
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub level: Level,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            properties.push(format!("file={}", escape_property(&workspace_path(file))));
+        }
+        if let Some(line) = self.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(title) = &self.title {
+            properties.push(format!("title={}", escape_property(title)));
+        }
+
+        write!(f, "::{}", self.level)?;
+        if !properties.is_empty() {
+            write!(f, " {}", properties.join(","))?;
+        }
+        write!(f, "::{}", escape_data(&self.message))
+    }
+}
+
+impl From<&Finding> for Annotation {
+    fn from(finding: &Finding) -> Self {
+        Self {
+            level: match finding.severity {
+                Severity::Info => Level::Notice,
+                Severity::Warning => Level::Warning,
+                Severity::Error => Level::Error,
+            },
+            file: Some(finding.path.clone()),
+            line: finding.line,
+            title: Some(format!("fexplorer lint: {}", finding.rule)),
+            message: finding.message.clone(),
+        }
+    }
+}
+
+impl From<&PortabilityIssue> for Annotation {
+    fn from(issue: &PortabilityIssue) -> Self {
+        Self {
+            level: Level::Error,
+            file: Some(issue.path.clone()),
+            line: None,
+            title: Some(format!("fexplorer portability: {}", issue.kind)),
+            message: format!("{} (fix: {})", issue.message, issue.suggestion),
+        }
+    }
+}
+
+impl Annotation {
+    /// One annotation per exceeded limit of a quota report
+    ///
+    /// Quotas apply to directories, which GitHub can't attach to a diff line,
+    /// so these carry no `file` and appear in the run summary only.
+    pub fn from_quota(report: &QuotaReport) -> Vec<Self> {
+        report
+            .violations
+            .iter()
+            .map(|violation| Self {
+                level: Level::Error,
+                file: None,
+                line: None,
+                title: Some("fexplorer quota exceeded".to_string()),
+                message: format!("{}: {}", report.path.display(), violation),
+            })
+            .collect()
+    }
+}
+
+/// Escape a message the way the runner's command parser expects
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value, which additionally can't contain `:` or `,`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_format() {
+        let annotation = Annotation {
+            level: Level::Warning,
+            file: Some(PathBuf::from("./src/a,b.rs")),
+            line: Some(7),
+            title: Some("lint: trailing".to_string()),
+            message: "100% bad\nreally".to_string(),
+        };
+        assert_eq!(
+            annotation.to_string(),
+            "::warning file=src/a%2Cb.rs,line=7,title=lint%3A trailing::100%25 bad%0Areally"
+        );
+
+        let bare = Annotation {
+            level: Level::Error,
+            file: None,
+            line: None,
+            title: None,
+            message: "over quota".to_string(),
+        };
+        assert_eq!(bare.to_string(), "::error::over quota");
+    }
+}
@@ -0,0 +1,310 @@
+//! Grep matches (`fexplorer grep`) in each output format
+//!
+//! Pretty output lists the matches of a file under its path, with the
+//! matched text highlighted on a terminal. JSON, NDJSON and CSV have one
+//! record per matching line; JSON and NDJSON include the file's entry,
+//! the context lines and the byte range of every match (`submatches`).
+
+use crate::errors::Result;
+use crate::i18n::{trf, Msg};
+use crate::models::ContentMatch;
+use crate::output::color;
+use crate::output::format::OutputSink;
+use crate::util::{escape_path, is_tty};
+use csv::Writer;
+use nu_ansi_term::{Color, Style};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Matches grouped by file, followed by a count
+pub struct MatchPrettyFormatter {
+    writer: Box<dyn Write>,
+    use_color: bool,
+    line_numbers: bool,
+    /// File of the previous match, whose heading is already out
+    current: Option<PathBuf>,
+    matches: usize,
+    files: usize,
+}
+
+impl MatchPrettyFormatter {
+    pub fn new(writer: Box<dyn Write>, no_color: bool, line_numbers: bool) -> Self {
+        Self {
+            writer,
+            use_color: color::use_color(is_tty() && !no_color),
+            line_numbers,
+            current: None,
+            matches: 0,
+            files: 0,
+        }
+    }
+
+    fn paint(&self, style: Style, text: &str) -> String {
+        if self.use_color {
+            style.paint(text).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl OutputSink<ContentMatch> for MatchPrettyFormatter {
+    fn write(&mut self, m: &ContentMatch) -> Result<()> {
+        if self.current.as_ref() != Some(&m.entry.path) {
+            if self.current.is_some() {
+                writeln!(self.writer)?;
+            }
+            let heading = self.paint(Color::Purple.bold(), &escape_path(&m.entry.path));
+            writeln!(self.writer, "{}", heading)?;
+            self.current = Some(m.entry.path.clone());
+            self.files += 1;
+        }
+        self.matches += 1;
+
+        let first_before = m.line_number - m.context_before.len();
+        for (i, line) in m.context_before.iter().enumerate() {
+            writeln!(self.writer, "  {}-  {}", first_before + i, line)?;
+        }
+        let text = if self.use_color {
+            highlight(m, Color::Red.bold())
+        } else {
+            m.matched_text.clone()
+        };
+        // A multiline match prints each of its lines, numbered
+        let mut last_line = m.line_number;
+        for (i, line) in text.split('\n').enumerate() {
+            last_line = m.line_number + i;
+            if self.line_numbers {
+                let location = if i == 0 {
+                    format!("{}:{}", last_line, m.column)
+                } else {
+                    last_line.to_string()
+                };
+                let location = self.paint(Color::Green.normal(), &location);
+                writeln!(self.writer, "  {}: {}", location, line)?;
+            } else {
+                writeln!(self.writer, "  {}", line)?;
+            }
+        }
+        for (i, line) in m.context_after.iter().enumerate() {
+            writeln!(self.writer, "  {}+  {}", last_line + i + 1, line)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.matches > 0 {
+            let summary = trf(Msg::FoundMatches, &[&self.matches, &self.files]);
+            writeln!(self.writer, "\n{}", summary)?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The matched line with every match painted in `style`, line by line for
+/// matches spanning lines
+fn highlight(m: &ContentMatch, style: Style) -> String {
+    let text = &m.matched_text;
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for span in &m.submatches {
+        let (Some(before), Some(matched)) =
+            (text.get(last..span.start), text.get(span.start..span.end))
+        else {
+            continue;
+        };
+        out.push_str(before);
+        let painted: Vec<String> = matched
+            .split('\n')
+            .map(|part| {
+                if part.is_empty() {
+                    String::new()
+                } else {
+                    style.paint(part).to_string()
+                }
+            })
+            .collect();
+        out.push_str(&painted.join("\n"));
+        last = span.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// JSON array of matches (buffers all matches)
+pub struct MatchJsonFormatter {
+    writer: Box<dyn Write>,
+    matches: Vec<ContentMatch>,
+}
+
+impl MatchJsonFormatter {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            matches: Vec::new(),
+        }
+    }
+}
+
+impl OutputSink<ContentMatch> for MatchJsonFormatter {
+    fn write(&mut self, m: &ContentMatch) -> Result<()> {
+        self.matches.push(m.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.matches)?;
+        writeln!(self.writer, "{}", json)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// One JSON object per match and line (streaming)
+pub struct MatchNdjsonFormatter {
+    writer: Box<dyn Write>,
+}
+
+impl MatchNdjsonFormatter {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self { writer }
+    }
+}
+
+impl OutputSink<ContentMatch> for MatchNdjsonFormatter {
+    fn write(&mut self, m: &ContentMatch) -> Result<()> {
+        writeln!(self.writer, "{}", serde_json::to_string(m)?)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// `path,line,column,text` rows; context lines are left out
+pub struct MatchCsvFormatter {
+    writer: Writer<Box<dyn Write>>,
+}
+
+impl MatchCsvFormatter {
+    pub fn new(output: Box<dyn Write>) -> Result<Self> {
+        let mut writer = Writer::from_writer(output);
+        writer.write_record(["path", "line", "column", "text"])?;
+        Ok(Self { writer })
+    }
+}
+
+impl OutputSink<ContentMatch> for MatchCsvFormatter {
+    fn write(&mut self, m: &ContentMatch) -> Result<()> {
+        self.writer.write_record([
+            escape_path(&m.entry.path).as_ref(),
+            &m.line_number.to_string(),
+            &m.column.to_string(),
+            &m.matched_text,
+        ])?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Entry, EntryKind, MatchSpan};
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::rc::Rc;
+
+    /// Writer whose contents stay readable after it's boxed away
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn make_match(
+        path: &str,
+        line_number: usize,
+        text: &str,
+        spans: &[(usize, usize)],
+    ) -> ContentMatch {
+        let mut entry = Entry::unreadable(Path::new(path), EntryKind::File, 1, String::new());
+        entry.error = None;
+        ContentMatch {
+            entry,
+            line_number,
+            column: spans.first().map_or(1, |(start, _)| start + 1),
+            matched_text: text.to_string(),
+            submatches: spans
+                .iter()
+                .map(|&(start, end)| MatchSpan { start, end })
+                .collect(),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pretty_groups_by_file() {
+        let sink = Shared::default();
+        let mut formatter = MatchPrettyFormatter::new(Box::new(sink.clone()), true, true);
+        formatter
+            .write(&make_match("a.rs", 3, "// TODO one", &[(3, 7)]))
+            .unwrap();
+        formatter
+            .write(&make_match("a.rs", 9, "// TODO two", &[(3, 7)]))
+            .unwrap();
+        formatter
+            .write(&make_match("b.rs", 1, "TODO", &[(0, 4)]))
+            .unwrap();
+        formatter.finish().unwrap();
+
+        let text = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        assert_eq!(
+            text,
+            "a.rs\n  3:4: // TODO one\n  9:4: // TODO two\n\nb.rs\n  1:1: TODO\n\nFound 3 matches in 2 files\n"
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let m = make_match("a.txt", 1, "foo bar foo", &[(0, 3), (8, 11)]);
+        let style = Color::Red.bold();
+        let expected = format!("{} bar {}", style.paint("foo"), style.paint("foo"));
+        assert_eq!(highlight(&m, style), expected);
+
+        let m = make_match("a.txt", 1, "fn a(\n) {", &[(0, 7)]);
+        let expected = format!("{}\n{} {{", style.paint("fn a("), style.paint(")"));
+        assert_eq!(highlight(&m, style), expected);
+    }
+
+    #[test]
+    fn test_pretty_multiline_match() {
+        let sink = Shared::default();
+        let mut formatter = MatchPrettyFormatter::new(Box::new(sink.clone()), true, true);
+        let mut m = make_match("a.rs", 4, "fn main(\n    x: u8,\n) {", &[(0, 23)]);
+        m.context_after = vec!["}".to_string()];
+        formatter.write(&m).unwrap();
+        formatter.finish().unwrap();
+
+        let text = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        assert_eq!(
+            text,
+            "a.rs\n  4:1: fn main(\n  5:     x: u8,\n  6: ) {\n  7+  }\n\nFound 1 matches in 1 files\n"
+        );
+    }
+}
@@ -1,12 +1,79 @@
+//! Entries as JSON (`--format json`) and NDJSON (`--format ndjson`)
+//!
+//! Every entry carries `schema_version`. New fields may appear without a
+//! version bump, so consumers should ignore fields they don't know; the
+//! version goes up when a field is renamed, removed or changes meaning.
+//! [`entry_schema`] describes one entry and is published at [`SCHEMA_URL`].
+//!
+//! Times are seconds since the Unix epoch, or RFC 3339 strings in UTC
+//! (`2025-01-31T09:30:00Z`) with `--reproducible`.
+
 use crate::errors::Result;
 use crate::models::Entry;
 use crate::output::format::OutputSink;
+use chrono::SecondsFormat;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
 use std::io::Write;
 
+/// Version of the entry layout in JSON and NDJSON output
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Where the JSON Schema of [`SCHEMA_VERSION`] is published
+pub const SCHEMA_URL: &str =
+    "https://raw.githubusercontent.com/jbdgw/rust_fileexplorer/main/docs/schema/entry.schema.json";
+
+/// An entry as written to JSON and NDJSON output
+#[derive(Serialize, JsonSchema)]
+#[schemars(title = "fexplorer entry")]
+struct VersionedEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a Entry,
+    /// Bumped when a field is renamed, removed or changes meaning
+    schema_version: u32,
+}
+
+impl<'a> VersionedEntry<'a> {
+    fn new(entry: &'a Entry) -> Self {
+        Self {
+            entry,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+}
+
+/// `entry` as a JSON value, its times as RFC 3339 strings
+fn with_iso_times(entry: &Entry) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(VersionedEntry::new(entry))?;
+    let times = [
+        ("mtime", Some(entry.mtime)),
+        ("atime", entry.atime),
+        ("ctime", entry.ctime),
+        ("created", entry.created),
+        ("last_activity", entry.last_activity),
+        ("taken", entry.enriched.taken),
+    ];
+    for (key, time) in times {
+        if let Some(time) = time {
+            value[key] = time.to_rfc3339_opts(SecondsFormat::Secs, true).into();
+        }
+    }
+    Ok(value)
+}
+
+/// JSON Schema of one entry in JSON (array item) and NDJSON (line) output
+pub fn entry_schema() -> serde_json::Value {
+    let mut schema = schemars::schema_for!(VersionedEntry<'static>);
+    schema.insert("$id".to_string(), SCHEMA_URL.into());
+    schema.to_value()
+}
+
 /// JSON array formatter (buffers all entries)
 pub struct JsonFormatter {
     writer: Box<dyn Write>,
     entries: Vec<Entry>,
+    iso_times: bool,
 }
 
 impl JsonFormatter {
@@ -14,8 +81,15 @@ impl JsonFormatter {
         Self {
             writer,
             entries: Vec::new(),
+            iso_times: false,
         }
     }
+
+    /// Write times as RFC 3339 strings in UTC instead of epoch seconds
+    pub fn with_iso_times(mut self) -> Self {
+        self.iso_times = true;
+        self
+    }
 }
 
 impl OutputSink for JsonFormatter {
@@ -25,7 +99,18 @@ impl OutputSink for JsonFormatter {
     }
 
     fn finish(&mut self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.entries)?;
+        let json = if self.iso_times {
+            let entries = self
+                .entries
+                .iter()
+                .map(with_iso_times)
+                .collect::<serde_json::Result<Vec<Value>>>()?;
+            serde_json::to_string_pretty(&entries)?
+        } else {
+            let entries: Vec<VersionedEntry> =
+                self.entries.iter().map(VersionedEntry::new).collect();
+            serde_json::to_string_pretty(&entries)?
+        };
         writeln!(self.writer, "{}", json)?;
         self.writer.flush()?;
         Ok(())
@@ -35,17 +120,31 @@ impl OutputSink for JsonFormatter {
 /// NDJSON (newline-delimited JSON) formatter (streaming)
 pub struct NdjsonFormatter {
     writer: Box<dyn Write>,
+    iso_times: bool,
 }
 
 impl NdjsonFormatter {
     pub fn new(writer: Box<dyn Write>) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            iso_times: false,
+        }
+    }
+
+    /// Write times as RFC 3339 strings in UTC instead of epoch seconds
+    pub fn with_iso_times(mut self) -> Self {
+        self.iso_times = true;
+        self
     }
 }
 
 impl OutputSink for NdjsonFormatter {
     fn write(&mut self, entry: &Entry) -> Result<()> {
-        let json = serde_json::to_string(entry)?;
+        let json = if self.iso_times {
+            serde_json::to_string(&with_iso_times(entry)?)?
+        } else {
+            serde_json::to_string(&VersionedEntry::new(entry))?
+        };
         writeln!(self.writer, "{}", json)?;
         Ok(())
     }
@@ -59,21 +158,11 @@ impl OutputSink for NdjsonFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::EntryKind;
-    use std::path::PathBuf;
 
     fn make_test_entry(name: &str) -> Entry {
-        use chrono::Utc;
-
         Entry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
             size: 1024,
-            kind: EntryKind::File,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
+            ..Entry::test_file(name)
         }
     }
 
@@ -100,4 +189,42 @@ mod tests {
         formatter.write(&make_test_entry("test2.txt")).unwrap();
         formatter.finish().unwrap();
     }
+
+    #[test]
+    fn test_versioned_entry() {
+        let json = serde_json::to_value(VersionedEntry::new(&make_test_entry("a.txt"))).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["name"], "a.txt");
+        assert_eq!(json["kind"], "file");
+    }
+
+    #[test]
+    fn test_iso_times() {
+        let entry = Entry {
+            mtime: "2025-01-31T09:30:00Z".parse().unwrap(),
+            ..make_test_entry("a.txt")
+        };
+        let json = with_iso_times(&entry).unwrap();
+        assert_eq!(json["mtime"], "2025-01-31T09:30:00Z");
+        assert!(json.get("atime").is_none());
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_published_schema_is_current() {
+        let published: serde_json::Value =
+            serde_json::from_str(include_str!("../../docs/schema/entry.schema.json")).unwrap();
+        assert_eq!(
+            published,
+            entry_schema(),
+            "regenerate with: fexplorer schema > docs/schema/entry.schema.json"
+        );
+        assert_eq!(published["$id"], SCHEMA_URL);
+        // Enrichment fields are flattened into the entry
+        assert!(published["properties"]["mime"].is_object());
+        assert!(published["required"]
+            .as_array()
+            .unwrap()
+            .contains(&"schema_version".into()));
+    }
 }
@@ -0,0 +1,286 @@
+use crate::errors::Result;
+use crate::models::{Entry, EntryKind};
+use crate::output::color;
+use crate::output::format::OutputSink;
+use crate::util::is_tty;
+use chrono::{DateTime, Duration, Local, Utc};
+use nu_ansi_term::Color;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `ls -la` compatible long listing formatter (buffers entries to align columns)
+pub struct LsFormatter {
+    writer: Box<dyn Write>,
+    entries: Vec<Entry>,
+    use_color: bool,
+}
+
+/// A fully rendered `ls -l` row before column alignment
+struct LsRow {
+    mode: String,
+    nlink: u64,
+    owner: String,
+    group: String,
+    size: u64,
+    date: String,
+    name: String,
+    blocks: u64,
+}
+
+impl LsFormatter {
+    pub fn new(writer: Box<dyn Write>, no_color: bool) -> Self {
+        let use_color = color::use_color(is_tty() && !no_color);
+        Self {
+            writer,
+            entries: Vec::new(),
+            use_color,
+        }
+    }
+
+    /// Build a row for a path, displaying it under `display_name`
+    fn build_row(&self, path: &Path, display_name: &str, kind: EntryKind) -> Option<LsRow> {
+        let metadata = fs::symlink_metadata(path).ok()?;
+        let stat = LsStat::from_metadata(&metadata);
+        let mtime: DateTime<Utc> = metadata.modified().ok()?.into();
+
+        let mut name = self.colorize_name(display_name, kind, stat.mode);
+        match kind {
+            EntryKind::Symlink => {
+                if let Ok(target) = fs::read_link(path) {
+                    name.push_str(&format!(" -> {}", target.display()));
+                }
+            }
+            _ => name.push_str(classify_indicator(kind, stat.mode)),
+        }
+
+        Some(LsRow {
+            mode: mode_string(stat.mode, kind),
+            nlink: stat.nlink,
+            owner: stat.owner,
+            group: stat.group,
+            size: metadata.len(),
+            date: format_ls_date(mtime, Utc::now()),
+            name,
+            blocks: stat.blocks,
+        })
+    }
+
+    fn colorize_name(&self, name: &str, kind: EntryKind, mode: u32) -> String {
+        if !self.use_color {
+            return name.to_string();
+        }
+
+        match kind {
+            EntryKind::Dir => Color::Blue.bold().paint(name).to_string(),
+            EntryKind::Symlink => Color::Cyan.bold().paint(name).to_string(),
+            EntryKind::File if mode & 0o111 != 0 => Color::Green.bold().paint(name).to_string(),
+            EntryKind::File => name.to_string(),
+        }
+    }
+
+    fn collect_rows(&self) -> Vec<LsRow> {
+        // The listed root is shown first (as "." and ".." for directories), like ls -la
+        let (roots, children): (Vec<&Entry>, Vec<&Entry>) =
+            self.entries.iter().partition(|e| e.depth == 0);
+
+        let mut rows = Vec::new();
+
+        for root in roots {
+            if root.kind == EntryKind::Dir {
+                rows.extend(self.build_row(&root.path, ".", root.kind));
+                let parent: PathBuf = root.path.join("..");
+                rows.extend(self.build_row(&parent, "..", EntryKind::Dir));
+            } else {
+                let name = root.path.display().to_string();
+                rows.extend(self.build_row(&root.path, &name, root.kind));
+            }
+        }
+
+        for entry in children {
+            rows.extend(self.build_row(&entry.path, &entry.name, entry.kind));
+        }
+
+        rows
+    }
+}
+
+impl OutputSink for LsFormatter {
+    fn write(&mut self, entry: &Entry) -> Result<()> {
+        self.entries.push(entry.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let rows = self.collect_rows();
+
+        let nlink_width = rows.iter().map(|r| r.nlink.to_string().len()).max();
+        let owner_width = rows.iter().map(|r| r.owner.len()).max();
+        let group_width = rows.iter().map(|r| r.group.len()).max();
+        let size_width = rows.iter().map(|r| r.size.to_string().len()).max();
+
+        // ls reports totals in 1K blocks; st_blocks counts 512-byte units
+        let total_blocks: u64 = rows.iter().map(|r| r.blocks).sum::<u64>() / 2;
+        writeln!(self.writer, "total {}", total_blocks)?;
+
+        for row in &rows {
+            writeln!(
+                self.writer,
+                "{} {:>nw$} {:<ow$} {:<gw$} {:>sw$} {} {}",
+                row.mode,
+                row.nlink,
+                row.owner,
+                row.group,
+                row.size,
+                row.date,
+                row.name,
+                nw = nlink_width.unwrap_or(0),
+                ow = owner_width.unwrap_or(0),
+                gw = group_width.unwrap_or(0),
+                sw = size_width.unwrap_or(0),
+            )?;
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Platform stat fields needed for a long listing
+struct LsStat {
+    mode: u32,
+    nlink: u64,
+    owner: String,
+    group: String,
+    blocks: u64,
+}
+
+impl LsStat {
+    #[cfg(unix)]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        use crate::fs::users::{group_name, user_name};
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            mode: metadata.mode(),
+            nlink: metadata.nlink(),
+            owner: user_name(metadata.uid()).unwrap_or_else(|| metadata.uid().to_string()),
+            group: group_name(metadata.gid()).unwrap_or_else(|| metadata.gid().to_string()),
+            blocks: metadata.blocks(),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        let mode = if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        };
+
+        Self {
+            mode,
+            nlink: 1,
+            owner: "-".to_string(),
+            group: "-".to_string(),
+            blocks: metadata.len().div_ceil(512),
+        }
+    }
+}
+
+/// Render a mode as the 10-character `ls -l` permission string
+pub fn mode_string(mode: u32, kind: EntryKind) -> String {
+    let type_char = match mode & 0o170000 {
+        0o140000 => 's',
+        0o120000 => 'l',
+        0o060000 => 'b',
+        0o040000 => 'd',
+        0o020000 => 'c',
+        0o010000 => 'p',
+        0o100000 => '-',
+        _ => match kind {
+            EntryKind::Dir => 'd',
+            EntryKind::Symlink => 'l',
+            EntryKind::File => '-',
+        },
+    };
+
+    let mut s = String::with_capacity(10);
+    s.push(type_char);
+    s.push_str(&perm_triplet(mode >> 6, mode & 0o4000 != 0, 's'));
+    s.push_str(&perm_triplet(mode >> 3, mode & 0o2000 != 0, 's'));
+    s.push_str(&perm_triplet(mode, mode & 0o1000 != 0, 't'));
+    s
+}
+
+/// Render one rwx triplet, folding setuid/setgid/sticky into the execute slot
+fn perm_triplet(bits: u32, special: bool, special_char: char) -> String {
+    let r = if bits & 0o4 != 0 { 'r' } else { '-' };
+    let w = if bits & 0o2 != 0 { 'w' } else { '-' };
+    let x = match (bits & 0o1 != 0, special) {
+        (true, true) => special_char,
+        (false, true) => special_char.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+    format!("{}{}{}", r, w, x)
+}
+
+/// `ls -F` style indicator for non-symlink entries
+fn classify_indicator(kind: EntryKind, mode: u32) -> &'static str {
+    match mode & 0o170000 {
+        0o010000 => "|",
+        0o140000 => "=",
+        _ => match kind {
+            EntryKind::Dir => "/",
+            EntryKind::File if mode & 0o111 != 0 => "*",
+            _ => "",
+        },
+    }
+}
+
+/// Format a timestamp the way ls does: time for recent files, year for old ones
+fn format_ls_date(mtime: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let local = mtime.with_timezone(&Local);
+    let six_months = Duration::days(182);
+
+    if mtime > now - six_months && mtime <= now + Duration::hours(1) {
+        local.format("%b %e %H:%M").to_string()
+    } else {
+        local.format("%b %e  %Y").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mode_string() {
+        assert_eq!(mode_string(0o100644, EntryKind::File), "-rw-r--r--");
+        assert_eq!(mode_string(0o040755, EntryKind::Dir), "drwxr-xr-x");
+        assert_eq!(mode_string(0o120777, EntryKind::Symlink), "lrwxrwxrwx");
+        assert_eq!(mode_string(0o104755, EntryKind::File), "-rwsr-xr-x");
+        assert_eq!(mode_string(0o041777, EntryKind::Dir), "drwxrwxrwt");
+        assert_eq!(mode_string(0o102644, EntryKind::File), "-rw-r-Sr--");
+    }
+
+    #[test]
+    fn test_classify_indicator() {
+        assert_eq!(classify_indicator(EntryKind::Dir, 0o040755), "/");
+        assert_eq!(classify_indicator(EntryKind::File, 0o100755), "*");
+        assert_eq!(classify_indicator(EntryKind::File, 0o100644), "");
+        assert_eq!(classify_indicator(EntryKind::File, 0o010644), "|");
+    }
+
+    #[test]
+    fn test_format_ls_date_old_file_shows_year() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let old = Utc.with_ymd_and_hms(2022, 6, 15, 12, 0, 0).unwrap();
+        assert!(format_ls_date(old, now).ends_with("2022"));
+
+        let recent = now - Duration::days(3);
+        assert!(format_ls_date(recent, now).contains(':'));
+    }
+}
@@ -1,7 +1,28 @@
+pub mod aggregate;
+pub mod color;
+#[cfg(feature = "ndjson-zst")]
+pub mod compress;
 pub mod csvw;
+pub mod destination;
+pub mod du;
+pub mod explain;
 pub mod format;
+pub mod gha;
+pub mod grep;
 pub mod json;
+pub mod ls;
 pub mod pretty;
+pub mod sarif;
+pub mod select;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(all(feature = "syslog", unix))]
+pub mod syslog;
 
 #[cfg(feature = "templates")]
 pub mod templates;
+
+#[cfg(feature = "trends")]
+pub mod trends;
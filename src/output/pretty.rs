@@ -1,7 +1,10 @@
 use crate::errors::Result;
 use crate::models::{Column, Entry, EntryKind};
+use crate::output::color;
 use crate::output::format::OutputSink;
-use crate::util::{format_size_human, is_tty};
+use crate::plain;
+use crate::util::{escape_path, format_allocated, format_size_human, is_tty};
+use chrono::{DateTime, SecondsFormat, Utc};
 use nu_ansi_term::Color;
 use std::io::Write;
 
@@ -9,37 +12,99 @@ pub struct PrettyFormatter {
     writer: Box<dyn Write>,
     columns: Vec<Column>,
     use_color: bool,
+    iso_times: bool,
+    /// Start each line with the entry's kind (`--plain`)
+    plain: bool,
 }
 
 impl PrettyFormatter {
     pub fn new(writer: Box<dyn Write>, columns: Vec<Column>, no_color: bool) -> Self {
-        let use_color = is_tty() && !no_color;
+        let use_color = color::use_color(is_tty() && !no_color);
         Self {
             writer,
             columns,
             use_color,
+            iso_times: false,
+            plain: plain::is_enabled(),
         }
     }
 
+    /// Print times as ISO-8601 (`2025-01-31T09:30:00Z`) instead of
+    /// `2025-01-31 09:30:00`, both in UTC
+    pub fn with_iso_times(mut self) -> Self {
+        self.iso_times = true;
+        self
+    }
+
     fn format_entry(&self, entry: &Entry) -> String {
         let mut parts = Vec::new();
+        if self.plain {
+            parts.push(plain::kind_marker(entry.kind).to_string());
+        }
 
         for column in &self.columns {
             let value = match column {
-                Column::Path => self.colorize_path(&entry.path.display().to_string(), entry.kind),
+                Column::Path => self.colorize_path(&escape_path(&entry.path), entry.kind),
                 Column::Name => self.colorize_path(&entry.name, entry.kind),
                 Column::Size => format_size_human(entry.size),
-                Column::Mtime => entry.mtime.format("%Y-%m-%d %H:%M:%S").to_string(),
+                Column::Mtime => self.format_time(Some(entry.mtime)),
+                Column::Atime => self.format_time(entry.atime),
+                Column::Ctime => self.format_time(entry.ctime),
+                Column::Created => self.format_time(entry.created),
                 Column::Kind => format!("{:?}", entry.kind).to_lowercase(),
                 Column::Perms => entry.perms.clone().unwrap_or_default(),
                 Column::Owner => entry.owner.clone().unwrap_or_default(),
+                Column::Allocated => entry
+                    .allocated
+                    .map(|a| format_allocated(a, entry.size))
+                    .unwrap_or_default(),
+                Column::LastActivity => self.format_time(entry.last_activity),
+                Column::GitStatus => entry.enriched.git_status.clone().unwrap_or_default(),
+                Column::Hash => entry.enriched.hash.clone().unwrap_or_default(),
+                Column::Mime => entry.enriched.mime.clone().unwrap_or_default(),
+                Column::Category => entry.enriched.category.clone().unwrap_or_default(),
+                Column::Taken => self.format_time(entry.enriched.taken),
+                Column::Count => entry
+                    .enriched
+                    .count
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                Column::BinSections => entry.enriched.bin_sections.clone().unwrap_or_default(),
+                Column::BackupExcluded => match entry.enriched.backup_excluded {
+                    Some(true) => "excluded".to_string(),
+                    _ => String::new(),
+                },
+                Column::Error => entry.error.clone().unwrap_or_default(),
             };
             parts.push(value);
         }
 
+        // Unreadable entries say why even when the column wasn't requested
+        if let Some(error) = entry
+            .error
+            .as_ref()
+            .filter(|_| !self.columns.contains(&Column::Error))
+        {
+            let error = format!("error: {}", error);
+            parts.push(if self.use_color {
+                Color::Red.paint(error).to_string()
+            } else {
+                error
+            });
+        }
+
         parts.join("  ")
     }
 
+    /// A timestamp column, blank when unknown
+    fn format_time(&self, time: Option<DateTime<Utc>>) -> String {
+        match time {
+            Some(t) if self.iso_times => t.to_rfc3339_opts(SecondsFormat::Secs, true),
+            Some(t) => t.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => String::new(),
+        }
+    }
+
     fn colorize_path(&self, path: &str, kind: EntryKind) -> String {
         if !self.use_color {
             return path.to_string();
@@ -76,30 +141,32 @@ impl OutputSink for PrettyFormatter {
 pub struct TreeFormatter {
     writer: Box<dyn Write>,
     use_color: bool,
-    dirs_first: bool,
+    first: Option<EntryKind>,
+    /// Kind words and indentation instead of branches (`--plain`)
+    plain: bool,
 }
 
 impl TreeFormatter {
-    pub fn new(writer: Box<dyn Write>, no_color: bool, dirs_first: bool) -> Self {
-        let use_color = is_tty() && !no_color;
+    /// `first` lists entries of that kind before the others (`--dirs-first`/`--files-first`)
+    pub fn new(writer: Box<dyn Write>, no_color: bool, first: Option<EntryKind>) -> Self {
+        let use_color = color::use_color(is_tty() && !no_color);
         Self {
             writer,
             use_color,
-            dirs_first,
+            first,
+            plain: plain::is_enabled(),
         }
     }
 
     pub fn write_tree(&mut self, entries: &[Entry]) -> Result<()> {
-        // Sort entries if dirs_first is enabled
+        // Sort entries if dirs_first/files_first is enabled
         let mut sorted_entries = entries.to_vec();
-        if self.dirs_first {
+        if let Some(first) = self.first {
             sorted_entries.sort_by(|a, b| {
-                // First by kind (dirs before files), then by name
-                match (a.kind, b.kind) {
-                    (EntryKind::Dir, EntryKind::File) => std::cmp::Ordering::Less,
-                    (EntryKind::File, EntryKind::Dir) => std::cmp::Ordering::Greater,
-                    _ => a.name.cmp(&b.name),
-                }
+                // First by kind, then by name
+                (b.kind == first)
+                    .cmp(&(a.kind == first))
+                    .then_with(|| a.name.cmp(&b.name))
             });
         }
 
@@ -113,7 +180,13 @@ impl TreeFormatter {
 
     fn write_tree_entry(&mut self, entry: &Entry) -> Result<()> {
         let indent = "  ".repeat(entry.depth);
-        let prefix = if entry.depth > 0 { "├── " } else { "" };
+        let prefix = if self.plain {
+            format!("{} ", plain::kind_marker(entry.kind).trim_end())
+        } else if entry.depth > 0 {
+            "├── ".to_string()
+        } else {
+            String::new()
+        };
 
         let name = self.colorize_name(&entry.name, entry.kind);
         writeln!(self.writer, "{}{}{}", indent, prefix, name)?;
@@ -136,20 +209,30 @@ impl TreeFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
-    fn make_test_entry(name: &str, kind: EntryKind) -> Entry {
-        use chrono::Utc;
+    /// Writer whose contents stay readable after it's boxed away
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
+    fn make_test_entry(name: &str, kind: EntryKind) -> Entry {
         Entry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
             size: 1024,
             kind,
-            mtime: Utc::now(),
             perms: Some("rw-r--r--".to_string()),
             owner: Some("1000".to_string()),
-            depth: 0,
+            ..Entry::test_file(name)
         }
     }
 
@@ -171,7 +254,7 @@ mod tests {
         use std::io::Cursor;
 
         let output = Cursor::new(Vec::new());
-        let mut formatter = TreeFormatter::new(Box::new(output), true, false);
+        let mut formatter = TreeFormatter::new(Box::new(output), true, None);
 
         let entries = vec![
             make_test_entry("root", EntryKind::Dir),
@@ -183,4 +266,41 @@ mod tests {
 
         formatter.write_tree(&entries).unwrap();
     }
+
+    #[test]
+    fn test_plain_output() {
+        let sink = Shared::default();
+        let mut formatter = PrettyFormatter::new(Box::new(sink.clone()), vec![Column::Name], true);
+        formatter.plain = true;
+        formatter
+            .write(&make_test_entry("src", EntryKind::Dir))
+            .unwrap();
+        formatter
+            .write(&make_test_entry("a.txt", EntryKind::File))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(sink.0.borrow().clone()).unwrap(),
+            "DIR   src\nFILE  a.txt\n"
+        );
+
+        let sink = Shared::default();
+        let mut formatter = TreeFormatter::new(Box::new(sink.clone()), true, None);
+        formatter.plain = true;
+        let entries = vec![
+            make_test_entry("root", EntryKind::Dir),
+            Entry {
+                depth: 1,
+                ..make_test_entry("file.txt", EntryKind::File)
+            },
+            Entry {
+                depth: 1,
+                ..make_test_entry("latest", EntryKind::Symlink)
+            },
+        ];
+        formatter.write_tree(&entries).unwrap();
+        assert_eq!(
+            String::from_utf8(sink.0.borrow().clone()).unwrap(),
+            "DIR root\n  FILE file.txt\n  LINK latest\n"
+        );
+    }
 }
@@ -0,0 +1,249 @@
+use crate::fs::lint::{Finding, LintRule, Severity};
+use crate::fs::portability::{IssueKind, PortabilityIssue};
+use crate::util::workspace_relative;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A rule as listed in the tool driver
+#[derive(Debug, Clone)]
+pub struct SarifRule {
+    pub id: String,
+    pub description: String,
+}
+
+/// A single SARIF result
+#[derive(Debug, Clone)]
+pub struct SarifResult {
+    pub rule_id: String,
+    /// `note`, `warning` or `error`
+    pub level: &'static str,
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl From<&dyn LintRule> for SarifRule {
+    fn from(rule: &dyn LintRule) -> Self {
+        Self {
+            id: rule.id().to_string(),
+            description: rule.description().to_string(),
+        }
+    }
+}
+
+impl From<IssueKind> for SarifRule {
+    fn from(kind: IssueKind) -> Self {
+        Self {
+            id: kind.to_string(),
+            description: kind.description().to_string(),
+        }
+    }
+}
+
+impl From<&Finding> for SarifResult {
+    fn from(finding: &Finding) -> Self {
+        Self {
+            rule_id: finding.rule.to_string(),
+            level: match finding.severity {
+                Severity::Info => "note",
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            },
+            path: finding.path.clone(),
+            line: finding.line,
+            message: finding.message.clone(),
+        }
+    }
+}
+
+impl From<&PortabilityIssue> for SarifResult {
+    fn from(issue: &PortabilityIssue) -> Self {
+        Self {
+            rule_id: issue.kind.to_string(),
+            level: "error",
+            path: issue.path.clone(),
+            line: None,
+            message: format!("{} (fix: {})", issue.message, issue.suggestion),
+        }
+    }
+}
+
+/// Build a SARIF 2.1.0 log with a single run
+///
+/// The output can be uploaded to GitHub code scanning
+/// (`github/codeql-action/upload-sarif`) or read by any SARIF viewer.
+/// Relative locations are resolved against `%SRCROOT%`, the checkout root.
+pub fn sarif_log(rules: &[SarifRule], results: &[SarifResult]) -> Value {
+    let rule_index = |id: &str| rules.iter().position(|r| r.id == id);
+
+    let results: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": artifact_location(&result.path),
+                }
+            });
+            if let Some(line) = result.line {
+                location["physicalLocation"]["region"] = json!({ "startLine": line });
+            }
+
+            let mut value = json!({
+                "ruleId": result.rule_id,
+                "level": result.level,
+                "message": { "text": result.message },
+                "locations": [location],
+            });
+            if let Some(index) = rule_index(&result.rule_id) {
+                value["ruleIndex"] = json!(index);
+            }
+            value
+        })
+        .collect();
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fexplorer",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn artifact_location(path: &Path) -> Value {
+    let relative = workspace_relative(path);
+    let uri = uri_path(relative);
+    if relative.has_root() {
+        json!({ "uri": format!("file://{}", uri) })
+    } else {
+        json!({ "uri": uri, "uriBaseId": "%SRCROOT%" })
+    }
+}
+
+/// `path` as a URI path: its components percent-encoded and joined with `/`
+///
+/// Each component is encoded from its raw bytes, so backslashes in Unix
+/// file names and non-UTF-8 names survive; only Windows separators become `/`.
+fn uri_path(path: &Path) -> String {
+    let segments: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            // A drive such as `C:`, kept readable
+            Component::Prefix(prefix) => {
+                Some(prefix.as_os_str().to_string_lossy().replace('\\', "/"))
+            }
+            Component::RootDir | Component::CurDir => None,
+            Component::ParentDir => Some("..".to_string()),
+            Component::Normal(name) => Some(encode_uri(&os_bytes(name))),
+        })
+        .collect();
+    let uri = segments.join("/");
+    if path.has_root() {
+        format!("/{}", uri)
+    } else {
+        uri
+    }
+}
+
+#[cfg(unix)]
+fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(name.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn os_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    match name.to_string_lossy() {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+    }
+}
+
+/// Percent-encode everything but unreserved characters
+fn encode_uri(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_log() {
+        let rules = vec![SarifRule {
+            id: "trailing-whitespace".to_string(),
+            description: "Lines ending in spaces or tabs".to_string(),
+        }];
+        let results = vec![SarifResult {
+            rule_id: "trailing-whitespace".to_string(),
+            level: "note",
+            path: PathBuf::from("./docs/my notes.md"),
+            line: Some(3),
+            message: "trailing whitespace on 1 line".to_string(),
+        }];
+
+        let log = sarif_log(&rules, &results);
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["id"],
+            "trailing-whitespace"
+        );
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["level"], "note");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "docs/my%20notes.md");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(location["region"]["startLine"], 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_uri_path_encodes_raw_bytes() {
+        use std::os::unix::ffi::OsStrExt;
+
+        assert_eq!(uri_path(Path::new("a\\b.txt")), "a%5Cb.txt");
+        assert_eq!(
+            uri_path(Path::new("/srv/my notes.md")),
+            "/srv/my%20notes.md"
+        );
+        let name = OsStr::from_bytes(b"caf\xe9.txt");
+        assert_eq!(uri_path(&Path::new("docs").join(name)), "docs/caf%E9.txt");
+        assert_eq!(
+            artifact_location(Path::new("/srv/x"))["uri"],
+            "file:///srv/x"
+        );
+    }
+}
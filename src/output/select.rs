@@ -0,0 +1,769 @@
+//! Computed output fields for `--select`
+//!
+//! `--select 'path, size/1024 as kb, upper(ext)'` replaces the entry columns
+//! with a list of expressions, evaluated per entry and written as CSV or JSON.
+//! Expressions support fields (any column name plus `ext`, `depth` and `top`,
+//! the top-level directory under the walk root),
+//! numbers (size suffixes allowed: `size / 1MiB`), quoted strings,
+//! `+ - * / %`, parentheses and the functions below.
+//!
+//! | Function | Result |
+//! |----------|--------|
+//! | `upper(s)`, `lower(s)` | case-converted text |
+//! | `len(s)` | length in characters |
+//! | `round(x[, digits])` | rounded number |
+//! | `human(n)` | size like `1.5 MB` |
+//! | `date(t[, format])` | timestamp formatted with strftime (default `%Y-%m-%d`) |
+//! | `stem(path)`, `parent(path)` | file name without extension, parent directory |
+//! | `concat(a, b, ...)` | joined text |
+//! | `coalesce(a, b, ...)` | first non-empty value |
+
+use crate::errors::{FsError, Result};
+use crate::models::{Column, Entry, OutputFormat};
+use crate::output::format::OutputSink;
+use crate::util::{escape_path, format_size_human, parse_size};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+
+/// A value computed for one entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Null,
+    Number(f64),
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl FieldValue {
+    fn number(&self) -> Option<f64> {
+        match self {
+            FieldValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// JSON representation; timestamps are Unix seconds like in entry JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            FieldValue::Null => serde_json::Value::Null,
+            FieldValue::Number(n) if is_integral(*n) => serde_json::json!(*n as i64),
+            FieldValue::Number(n) => serde_json::json!(n),
+            FieldValue::Text(s) => serde_json::json!(s),
+            FieldValue::Time(t) => serde_json::json!(t.timestamp()),
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Null => Ok(()),
+            FieldValue::Number(n) if is_integral(*n) => write!(f, "{}", *n as i64),
+            FieldValue::Number(n) => write!(f, "{}", n),
+            FieldValue::Text(s) => write!(f, "{}", s),
+            FieldValue::Time(t) => write!(f, "{}", t.to_rfc3339()),
+        }
+    }
+}
+
+fn is_integral(n: f64) -> bool {
+    n.fract() == 0.0 && n.abs() < 9.0e15
+}
+
+/// An entry field an expression can refer to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Column(Column),
+    Ext,
+    Depth,
+    /// Top-level directory (or file) under the walk root
+    Top,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ext" | "extension" => Some(Field::Ext),
+            "depth" => Some(Field::Depth),
+            "top" => Some(Field::Top),
+            other => Column::from_str(other).map(Field::Column),
+        }
+    }
+
+    fn value(&self, entry: &Entry) -> FieldValue {
+        let text = |s: &Option<String>| s.clone().map_or(FieldValue::Null, FieldValue::Text);
+        let time = |t: Option<DateTime<Utc>>| t.map_or(FieldValue::Null, FieldValue::Time);
+
+        match self {
+            Field::Ext => entry.path.extension().map_or(FieldValue::Null, |e| {
+                FieldValue::Text(e.to_string_lossy().into_owned())
+            }),
+            Field::Depth => FieldValue::Number(entry.depth as f64),
+            Field::Top => entry
+                .path
+                .ancestors()
+                .nth(entry.depth.saturating_sub(1))
+                .map_or(FieldValue::Null, |top| {
+                    FieldValue::Text(escape_path(top).into_owned())
+                }),
+            Field::Column(column) => match column {
+                Column::Path => FieldValue::Text(escape_path(&entry.path).into_owned()),
+                Column::Name => FieldValue::Text(entry.name.clone()),
+                Column::Size => FieldValue::Number(entry.size as f64),
+                Column::Mtime => FieldValue::Time(entry.mtime),
+                Column::Atime => time(entry.atime),
+                Column::Ctime => time(entry.ctime),
+                Column::Created => time(entry.created),
+                Column::Kind => FieldValue::Text(format!("{:?}", entry.kind).to_lowercase()),
+                Column::Perms => text(&entry.perms),
+                Column::Owner => text(&entry.owner),
+                Column::Allocated => entry
+                    .allocated
+                    .map_or(FieldValue::Null, |a| FieldValue::Number(a as f64)),
+                Column::LastActivity => time(entry.last_activity),
+                Column::GitStatus => text(&entry.enriched.git_status),
+                Column::Hash => text(&entry.enriched.hash),
+                Column::Mime => text(&entry.enriched.mime),
+                Column::Category => text(&entry.enriched.category),
+                Column::Taken => time(entry.enriched.taken),
+                Column::Count => entry
+                    .enriched
+                    .count
+                    .map_or(FieldValue::Null, |c| FieldValue::Number(c as f64)),
+                Column::BinSections => text(&entry.enriched.bin_sections),
+                Column::BackupExcluded => entry
+                    .enriched
+                    .backup_excluded
+                    .map_or(FieldValue::Null, |b| FieldValue::Text(b.to_string())),
+                Column::Error => text(&entry.error),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(FieldValue),
+    Field(Field),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Upper,
+    Lower,
+    Len,
+    Round,
+    Human,
+    Date,
+    Stem,
+    Parent,
+    Concat,
+    Coalesce,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "upper" => Function::Upper,
+            "lower" => Function::Lower,
+            "len" | "length" => Function::Len,
+            "round" => Function::Round,
+            "human" => Function::Human,
+            "date" => Function::Date,
+            "stem" => Function::Stem,
+            "parent" => Function::Parent,
+            "concat" => Function::Concat,
+            "coalesce" => Function::Coalesce,
+            _ => return None,
+        })
+    }
+
+    /// Accepted argument counts (min, max)
+    fn arity(&self) -> (usize, usize) {
+        match self {
+            Function::Round | Function::Date => (1, 2),
+            Function::Concat | Function::Coalesce => (1, usize::MAX),
+            _ => (1, 1),
+        }
+    }
+
+    fn call(&self, args: &[FieldValue]) -> FieldValue {
+        let text = |value: &FieldValue| match value {
+            FieldValue::Null => None,
+            other => Some(other.to_string()),
+        };
+
+        match self {
+            Function::Upper => {
+                text(&args[0]).map_or(FieldValue::Null, |s| FieldValue::Text(s.to_uppercase()))
+            }
+            Function::Lower => {
+                text(&args[0]).map_or(FieldValue::Null, |s| FieldValue::Text(s.to_lowercase()))
+            }
+            Function::Len => text(&args[0]).map_or(FieldValue::Null, |s| {
+                FieldValue::Number(s.chars().count() as f64)
+            }),
+            Function::Round => {
+                let digits = args.get(1).and_then(FieldValue::number).unwrap_or(0.0);
+                let scale = 10f64.powi(digits as i32);
+                args[0].number().map_or(FieldValue::Null, |n| {
+                    FieldValue::Number((n * scale).round() / scale)
+                })
+            }
+            Function::Human => args[0].number().map_or(FieldValue::Null, |n| {
+                FieldValue::Text(format_size_human(n.max(0.0) as u64))
+            }),
+            Function::Date => match &args[0] {
+                FieldValue::Time(t) => {
+                    let format = args.get(1).and_then(text);
+                    let items: Vec<Item> =
+                        StrftimeItems::new(format.as_deref().unwrap_or("%Y-%m-%d")).collect();
+                    if items.contains(&Item::Error) {
+                        return FieldValue::Null;
+                    }
+                    FieldValue::Text(
+                        t.with_timezone(&chrono::Local)
+                            .format_with_items(items.into_iter())
+                            .to_string(),
+                    )
+                }
+                _ => FieldValue::Null,
+            },
+            Function::Stem => text(&args[0])
+                .and_then(|s| {
+                    Path::new(&s)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                })
+                .map_or(FieldValue::Null, FieldValue::Text),
+            Function::Parent => text(&args[0])
+                .and_then(|s| {
+                    Path::new(&s)
+                        .parent()
+                        .map(|parent| parent.to_string_lossy().into_owned())
+                })
+                .map_or(FieldValue::Null, FieldValue::Text),
+            Function::Concat => {
+                FieldValue::Text(args.iter().filter_map(text).collect::<Vec<_>>().concat())
+            }
+            Function::Coalesce => args
+                .iter()
+                .find(|v| text(v).is_some_and(|s| !s.is_empty()))
+                .cloned()
+                .unwrap_or(FieldValue::Null),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, entry: &Entry) -> FieldValue {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Field(field) => field.value(entry),
+            Expr::Neg(inner) => inner
+                .eval(entry)
+                .number()
+                .map_or(FieldValue::Null, |n| FieldValue::Number(-n)),
+            Expr::Binary(op, lhs, rhs) => {
+                let (Some(a), Some(b)) = (lhs.eval(entry).number(), rhs.eval(entry).number())
+                else {
+                    return FieldValue::Null;
+                };
+                let result = match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div | BinaryOp::Rem if b == 0.0 => return FieldValue::Null,
+                    BinaryOp::Div => a / b,
+                    BinaryOp::Rem => a % b,
+                };
+                FieldValue::Number(result)
+            }
+            Expr::Call(function, args) => {
+                let args: Vec<FieldValue> = args.iter().map(|arg| arg.eval(entry)).collect();
+                function.call(&args)
+            }
+        }
+    }
+
+    /// Whether the expression always evaluates to a number (or null)
+    fn is_numeric(&self) -> bool {
+        match self {
+            Expr::Literal(value) => matches!(value, FieldValue::Number(_)),
+            Expr::Field(Field::Depth) => true,
+            Expr::Field(Field::Column(column)) => {
+                matches!(column, Column::Size | Column::Allocated | Column::Count)
+            }
+            Expr::Field(_) => false,
+            Expr::Neg(_) | Expr::Binary(..) => true,
+            Expr::Call(Function::Len | Function::Round, _) => true,
+            Expr::Call(Function::Coalesce, args) => args.iter().all(Expr::is_numeric),
+            Expr::Call(..) => false,
+        }
+    }
+
+    fn collect_columns(&self, columns: &mut Vec<Column>) {
+        match self {
+            Expr::Field(Field::Column(column)) if !columns.contains(column) => {
+                columns.push(*column)
+            }
+            Expr::Neg(inner) => inner.collect_columns(columns),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_columns(columns);
+                rhs.collect_columns(columns);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.collect_columns(columns)),
+            _ => {}
+        }
+    }
+}
+
+/// One output field: an expression and the name it is written under
+#[derive(Debug, Clone)]
+pub struct SelectField {
+    pub name: String,
+    expr: Expr,
+}
+
+impl SelectField {
+    /// Whether the field is a number, as `sum` and `avg` need
+    pub fn is_numeric(&self) -> bool {
+        self.expr.is_numeric()
+    }
+}
+
+/// A parsed `--select` list
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub fields: Vec<SelectField>,
+}
+
+impl Selection {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = SelectParser {
+            input,
+            tokens: &tokens,
+            pos: 0,
+        };
+
+        let mut fields = Vec::new();
+        loop {
+            fields.push(parser.parse_field()?);
+            match parser.next() {
+                Some(Token::Comma) => continue,
+                None => break,
+                Some(token) => return Err(invalid(format!("unexpected {}", token))),
+            }
+        }
+        Ok(Self { fields })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.fields.iter().map(|field| field.name.clone()).collect()
+    }
+
+    /// Columns referenced by the expressions, so their enrichers can run
+    pub fn columns(&self) -> Vec<Column> {
+        let mut columns = Vec::new();
+        for field in &self.fields {
+            field.expr.collect_columns(&mut columns);
+        }
+        columns
+    }
+
+    pub fn eval(&self, entry: &Entry) -> Vec<FieldValue> {
+        self.fields
+            .iter()
+            .map(|field| field.expr.eval(entry))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Op(BinaryOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Number(n) => write!(f, "'{}'", n),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Op(op) => write!(f, "'{:?}'", op),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, Range<usize>)>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | ',' | '+' | '-' | '*' | '/' | '%' => {
+                chars.next();
+                match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    '+' => Token::Op(BinaryOp::Add),
+                    '-' => Token::Op(BinaryOp::Sub),
+                    '*' => Token::Op(BinaryOp::Mul),
+                    '/' => Token::Op(BinaryOp::Div),
+                    _ => Token::Op(BinaryOp::Rem),
+                }
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => text.extend(chars.next().map(|(_, ch)| ch)),
+                        Some((_, ch)) if ch == c => break,
+                        Some((_, ch)) => text.push(ch),
+                        None => return Err(invalid("unterminated string".to_string())),
+                    }
+                }
+                Token::Str(text)
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if !(ch.is_alphanumeric() || ch == '_' || ch == '.') {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                if word.starts_with(|ch: char| ch.is_ascii_digit() || ch == '.') {
+                    // Plain numbers, or sizes like 1MiB
+                    match word.parse::<f64>() {
+                        Ok(n) => Token::Number(n),
+                        Err(_) => Token::Number(parse_size(&word)? as f64),
+                    }
+                } else {
+                    Token::Ident(word)
+                }
+            }
+            other => return Err(invalid(format!("unexpected character '{}'", other))),
+        };
+        let end = chars.peek().map_or(input.len(), |&(i, _)| i);
+        tokens.push((token, start..end));
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser for select lists
+struct SelectParser<'a> {
+    input: &'a str,
+    tokens: &'a [(Token, Range<usize>)],
+    pos: usize,
+}
+
+impl SelectParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _)| token);
+        self.pos += 1;
+        token
+    }
+
+    fn parse_field(&mut self) -> Result<SelectField> {
+        let start = self.pos;
+        let expr = self.parse_sum()?;
+        let end = self.pos;
+
+        let name = if matches!(self.peek(), Some(Token::Ident(w)) if w.eq_ignore_ascii_case("as")) {
+            self.pos += 1;
+            match self.next() {
+                Some(Token::Ident(alias)) | Some(Token::Str(alias)) => alias.clone(),
+                _ => return Err(invalid("expected a name after 'as'".to_string())),
+            }
+        } else {
+            // Unaliased fields are named after their source text
+            let span = self.tokens[start].1.start..self.tokens[end - 1].1.end;
+            self.input[span].to_string()
+        };
+
+        Ok(SelectField { name, expr })
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_product()?;
+        while let Some(Token::Op(op @ (BinaryOp::Add | BinaryOp::Sub))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_product()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_product(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while let Some(Token::Op(op @ (BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem))) =
+            self.peek()
+        {
+            let op = *op;
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Op(BinaryOp::Sub)) {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+
+        match self.next().cloned() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(FieldValue::Number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(FieldValue::Text(s))),
+            Some(Token::LParen) => {
+                let inner = self.parse_sum()?;
+                if self.next() != Some(&Token::RParen) {
+                    return Err(invalid("missing closing ')'".to_string()));
+                }
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| invalid(format!("unknown function '{}'", name)))?;
+
+                let mut args = vec![self.parse_sum()?];
+                loop {
+                    match self.next() {
+                        Some(Token::Comma) => args.push(self.parse_sum()?),
+                        Some(Token::RParen) => break,
+                        _ => return Err(invalid(format!("missing ')' after {}(...", name))),
+                    }
+                }
+
+                let (min, max) = function.arity();
+                if args.len() < min || args.len() > max {
+                    return Err(invalid(format!("wrong number of arguments to {}()", name)));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Ident(name)) => Field::from_name(&name)
+                .map(Expr::Field)
+                .ok_or_else(|| invalid(format!("unknown field '{}'", name))),
+            Some(token) => Err(invalid(format!("expected a value, found {}", token))),
+            None => Err(invalid("expected a value".to_string())),
+        }
+    }
+}
+
+fn invalid(message: String) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("--select: {}", message),
+    }
+}
+
+/// Writes the selected fields of each entry as CSV, JSON or NDJSON
+pub struct SelectFormatter {
+    selection: Selection,
+    names: Vec<String>,
+    output: SelectOutput,
+}
+
+enum SelectOutput {
+    Csv(Box<Writer<Box<dyn Write>>>),
+    Json(Box<dyn Write>, Vec<Vec<FieldValue>>),
+    Ndjson(Box<dyn Write>),
+}
+
+impl SelectFormatter {
+    pub fn new(writer: Box<dyn Write>, selection: Selection, format: OutputFormat) -> Result<Self> {
+        let output = match format {
+            OutputFormat::Csv => {
+                let mut writer = Writer::from_writer(writer);
+                writer.write_record(selection.names())?;
+                SelectOutput::Csv(Box::new(writer))
+            }
+            OutputFormat::Json => SelectOutput::Json(writer, Vec::new()),
+            OutputFormat::Ndjson => SelectOutput::Ndjson(writer),
+            #[cfg(feature = "ndjson-zst")]
+            OutputFormat::NdjsonZst => SelectOutput::Ndjson(writer),
+            _ => {
+                return Err(invalid(
+                    "computed fields need --format csv, json or ndjson".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            names: selection.names(),
+            selection,
+            output,
+        })
+    }
+}
+
+/// One output row, serialized as an object with keys in field order
+pub(crate) struct Row<'a> {
+    pub names: &'a [String],
+    pub values: &'a [FieldValue],
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.names.len()))?;
+        for (name, value) in self.names.iter().zip(self.values) {
+            map.serialize_entry(name, &value.to_json())?;
+        }
+        map.end()
+    }
+}
+
+impl OutputSink for SelectFormatter {
+    fn write(&mut self, entry: &Entry) -> Result<()> {
+        let values = self.selection.eval(entry);
+        match &mut self.output {
+            SelectOutput::Csv(writer) => {
+                writer.write_record(values.iter().map(|v| v.to_string()))?;
+            }
+            SelectOutput::Json(_, rows) => rows.push(values),
+            SelectOutput::Ndjson(writer) => {
+                let row = Row {
+                    names: &self.names,
+                    values: &values,
+                };
+                writeln!(writer, "{}", serde_json::to_string(&row)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match &mut self.output {
+            SelectOutput::Csv(writer) => writer.flush()?,
+            SelectOutput::Json(writer, rows) => {
+                let rows: Vec<Row> = rows
+                    .iter()
+                    .map(|values| Row {
+                        names: &self.names,
+                        values,
+                    })
+                    .collect();
+                writeln!(writer, "{}", serde_json::to_string_pretty(&rows)?)?;
+                writer.flush()?;
+            }
+            SelectOutput::Ndjson(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_test_entry(path: &str, size: u64) -> Entry {
+        Entry {
+            size,
+            depth: 2,
+            ..Entry::test_file(path)
+        }
+    }
+
+    #[test]
+    fn test_parse_and_eval() {
+        let selection = Selection::parse("path, size/1024 as kb, upper(ext)").unwrap();
+        let names: Vec<&str> = selection.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["path", "kb", "upper(ext)"]);
+
+        let values = selection.eval(&make_test_entry("src/main.rs", 3072));
+        assert_eq!(values[0], FieldValue::Text("src/main.rs".to_string()));
+        assert_eq!(values[1], FieldValue::Number(3.0));
+        assert_eq!(values[2], FieldValue::Text("RS".to_string()));
+    }
+
+    #[test]
+    fn test_arithmetic_and_functions() {
+        let selection = Selection::parse(
+            "round(size / 1MiB, 2) as mib, -depth + 2 * 3, stem(path), \
+             concat(parent(path), '/', lower(name)), coalesce(owner, 'nobody'), size / 0",
+        )
+        .unwrap();
+        let values = selection.eval(&make_test_entry("docs/README.md", 1_572_864));
+
+        assert_eq!(values[0], FieldValue::Number(1.5));
+        assert_eq!(values[1], FieldValue::Number(4.0));
+        assert_eq!(values[2], FieldValue::Text("README".to_string()));
+        assert_eq!(values[3], FieldValue::Text("docs/readme.md".to_string()));
+        assert_eq!(values[4], FieldValue::Text("nobody".to_string()));
+        assert_eq!(values[5], FieldValue::Null);
+        assert_eq!(values[1].to_json(), serde_json::json!(4));
+        assert_eq!(values[0].to_string(), "1.5");
+
+        let selection =
+            Selection::parse("date(mtime, '%Y'), date(mtime, '%Q'), date(name)").unwrap();
+        let values = selection.eval(&make_test_entry("a.txt", 0));
+        assert_eq!(
+            values[0],
+            FieldValue::Text(chrono::Local::now().format("%Y").to_string())
+        );
+        assert_eq!(values[1], FieldValue::Null);
+        assert_eq!(values[2], FieldValue::Null);
+    }
+
+    #[test]
+    fn test_referenced_columns() {
+        let selection = Selection::parse("name, upper(category), len(mime), category").unwrap();
+        assert_eq!(
+            selection.columns(),
+            vec![Column::Name, Column::Category, Column::Mime]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Selection::parse("").is_err());
+        assert!(Selection::parse("colour").is_err());
+        assert!(Selection::parse("shout(name)").is_err());
+        assert!(Selection::parse("round()").is_err());
+        assert!(Selection::parse("upper(name, ext)").is_err());
+        assert!(Selection::parse("size as").is_err());
+        assert!(Selection::parse("(size + 1").is_err());
+        assert!(Selection::parse("name ext").is_err());
+        assert!(Selection::parse("'open").is_err());
+    }
+}
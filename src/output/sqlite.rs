@@ -0,0 +1,101 @@
+use crate::errors::{FsError, Result};
+use crate::models::Entry;
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// Write entries into `table` of the SQLite database at `path`
+///
+/// The table is created if needed, keyed by path, so re-running an export
+/// updates rows in place instead of duplicating them. Enrichment columns
+/// (`hash`, `mime`, `category`, `git_status`) are NULL unless an earlier
+/// stage filled them in. Returns the number of rows written.
+pub fn write_sqlite(path: &Path, table: &str, entries: &[Entry]) -> Result<usize> {
+    if table.is_empty() || !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "Invalid SQLite table name '{}' (use letters, digits and '_')",
+                table
+            ),
+        });
+    }
+    let sqlite_error = |e: rusqlite::Error| FsError::InvalidFormat {
+        format: format!("SQLite export to {} failed: {}", path.display(), e),
+    };
+
+    let mut conn = Connection::open(path).map_err(sqlite_error)?;
+    let tx = conn.transaction().map_err(sqlite_error)?;
+    tx.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            path TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            mtime INTEGER NOT NULL,
+            owner TEXT,
+            hash TEXT,
+            mime TEXT,
+            category TEXT,
+            git_status TEXT
+        )",
+        table
+    ))
+    .map_err(sqlite_error)?;
+
+    {
+        let mut insert = tx
+            .prepare(&format!(
+                "INSERT OR REPLACE INTO {} \
+                 (path, name, kind, size, mtime, owner, hash, mime, category, git_status) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                table
+            ))
+            .map_err(sqlite_error)?;
+        for entry in entries {
+            insert
+                .execute(params![
+                    entry.path.to_string_lossy(),
+                    entry.name,
+                    entry.kind.as_str(),
+                    entry.size as i64,
+                    entry.mtime.timestamp(),
+                    entry.owner,
+                    entry.enriched.hash,
+                    entry.enriched.mime,
+                    entry.enriched.category,
+                    entry.enriched.git_status,
+                ])
+                .map_err(sqlite_error)?;
+        }
+    }
+    tx.commit().map_err(sqlite_error)?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    #[test]
+    fn test_write_sqlite_upserts() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "hello").unwrap();
+        let mut entry = extract_entry(&file, 1).unwrap();
+        let db = dir.path().join("inventory.db");
+
+        assert_eq!(write_sqlite(&db, "files", &[entry.clone()]).unwrap(), 1);
+        entry.enriched.hash = Some("abc".to_string());
+        write_sqlite(&db, "files", &[entry]).unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        let (count, hash): (i64, String) = conn
+            .query_row("SELECT COUNT(*), MAX(hash) FROM files", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((count, hash.as_str()), (1, "abc"));
+
+        assert!(write_sqlite(&db, "files; DROP", &[]).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::errors::{FsError, Result};
+use crate::models::WatchEvent;
+use crate::util::escape_path;
+use chrono::Local;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+
+/// journald's native protocol socket, which keeps fields structured
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Local syslog sockets (Linux, macOS, BSD)
+const SYSLOG_SOCKETS: &[&str] = &["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+const IDENTIFIER: &str = "fexplorer";
+
+/// `user` facility, `info` severity
+const FACILITY_USER: u8 = 1;
+const SEVERITY_INFO: u8 = 6;
+
+/// Wire format of the socket we are connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyslogProtocol {
+    /// journald native protocol (`KEY=value` fields)
+    Journal,
+    /// BSD syslog (RFC 3164) lines, understood by rsyslog, syslog-ng and journald
+    Syslog,
+}
+
+/// Writes watch events to journald or the local syslog daemon
+pub struct SyslogWriter {
+    socket: UnixDatagram,
+    path: PathBuf,
+    protocol: SyslogProtocol,
+}
+
+impl SyslogWriter {
+    /// Connect to journald if it is running, otherwise to the syslog socket
+    pub fn connect() -> Result<Self> {
+        if Path::new(JOURNAL_SOCKET).exists() {
+            return Self::connect_to(Path::new(JOURNAL_SOCKET), SyslogProtocol::Journal);
+        }
+        let path = SYSLOG_SOCKETS
+            .iter()
+            .map(Path::new)
+            .find(|p| p.exists())
+            .ok_or_else(|| FsError::Watch("no syslog socket found".to_string()))?;
+        Self::connect_to(path, SyslogProtocol::Syslog)
+    }
+
+    pub fn connect_to(path: &Path, protocol: SyslogProtocol) -> Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        Ok(Self {
+            socket,
+            path: path.to_path_buf(),
+            protocol,
+        })
+    }
+
+    pub fn write_event(&self, event: &WatchEvent) -> Result<()> {
+        let fields = event_fields(event);
+        let message = match self.protocol {
+            SyslogProtocol::Journal => journal_message(&fields),
+            SyslogProtocol::Syslog => syslog_line(&fields, std::process::id()).into_bytes(),
+        };
+        self.socket
+            .send_to(&message, &self.path)
+            .map_err(|e| FsError::IoError {
+                context: format!("Failed to write to {}", self.path.display()),
+                source: e,
+            })?;
+        Ok(())
+    }
+}
+
+/// Structured fields of a watch event, in output order
+fn event_fields(event: &WatchEvent) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("event", event.event.clone()),
+        ("path", escape_path(&event.path).into_owned()),
+    ];
+    if let Some(size) = event.size {
+        fields.push(("size", size.to_string()));
+    }
+    if let Some(mtime) = event.mtime {
+        fields.push(("mtime", mtime.to_rfc3339()));
+    }
+    fields
+}
+
+/// Human-readable `key=value` summary, quoting values with spaces
+fn logfmt(fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| {
+            if value.is_empty() || value.contains([' ', '"', '=']) {
+                format!(
+                    "{}=\"{}\"",
+                    key,
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// RFC 3164 line: `<14>Oct 16 10:17:59 fexplorer[42]: event=create path=...`
+fn syslog_line(fields: &[(&str, String)], pid: u32) -> String {
+    format!(
+        "<{}>{} {}[{}]: {}",
+        FACILITY_USER * 8 + SEVERITY_INFO,
+        Local::now().format("%b %e %H:%M:%S"),
+        IDENTIFIER,
+        pid,
+        logfmt(fields)
+    )
+}
+
+/// journald datagram; every event field becomes a `FEXPLORER_*` journal field
+///
+/// Values containing newlines use the protocol's length-prefixed binary form.
+fn journal_message(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut message = Vec::new();
+    let mut push = |key: &str, value: &str| {
+        if value.contains('\n') {
+            message.extend_from_slice(key.as_bytes());
+            message.push(b'\n');
+            message.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            message.extend_from_slice(value.as_bytes());
+            message.push(b'\n');
+        } else {
+            message.extend_from_slice(format!("{}={}\n", key, value).as_bytes());
+        }
+    };
+
+    push("MESSAGE", &logfmt(fields));
+    push("PRIORITY", &SEVERITY_INFO.to_string());
+    push("SYSLOG_FACILITY", &FACILITY_USER.to_string());
+    push("SYSLOG_IDENTIFIER", IDENTIFIER);
+    for (key, value) in fields {
+        push(&format!("FEXPLORER_{}", key.to_uppercase()), value);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_event() -> WatchEvent {
+        WatchEvent {
+            event: "create".to_string(),
+            path: PathBuf::from("/srv/share/new report.pdf"),
+            mtime: None,
+            size: Some(2048),
+        }
+    }
+
+    #[test]
+    fn test_syslog_line() {
+        let line = syslog_line(&event_fields(&sample_event()), 42);
+        assert!(line.starts_with("<14>"));
+        assert!(line
+            .ends_with("fexplorer[42]: event=create path=\"/srv/share/new report.pdf\" size=2048"));
+    }
+
+    #[test]
+    fn test_journal_message() {
+        let mut fields = event_fields(&sample_event());
+        fields.push(("note", "two\nlines".to_string()));
+        let message = journal_message(&fields);
+
+        let text = String::from_utf8_lossy(&message);
+        assert!(text.contains("SYSLOG_IDENTIFIER=fexplorer\n"));
+        assert!(text.contains("FEXPLORER_PATH=/srv/share/new report.pdf\n"));
+        assert!(text.contains("FEXPLORER_SIZE=2048\n"));
+
+        let mut binary = b"FEXPLORER_NOTE\n".to_vec();
+        binary.extend_from_slice(&9u64.to_le_bytes());
+        binary.extend_from_slice(b"two\nlines\n");
+        assert!(message.ends_with(&binary));
+    }
+
+    #[test]
+    fn test_write_event_to_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+
+        let writer = SyslogWriter::connect_to(&path, SyslogProtocol::Syslog).unwrap();
+        writer.write_event(&sample_event()).unwrap();
+
+        let mut buf = [0u8; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        let received = String::from_utf8_lossy(&buf[..len]);
+        assert!(received.contains("event=create"));
+    }
+}
@@ -3,6 +3,8 @@ use crate::errors::Result;
 #[cfg(feature = "templates")]
 use crate::models::Entry;
 #[cfg(feature = "templates")]
+use crate::util::escape_path;
+#[cfg(feature = "templates")]
 use std::io::Write;
 
 #[cfg(feature = "templates")]
@@ -83,7 +85,7 @@ fn export_markdown<W: Write>(writer: &mut W, entries: &[Entry], title: Option<&s
         writeln!(
             writer,
             "| {} | {} | {} | {} |",
-            entry.path.display(),
+            escape_path(&entry.path),
             size_str,
             mtime_str,
             kind_str
@@ -198,7 +200,7 @@ fn export_html<W: Write>(writer: &mut W, entries: &[Entry], title: Option<&str>)
             writer,
             "                    <td class=\"file-path {}\">{}</td>",
             kind_class,
-            entry.path.display()
+            escape_path(&entry.path)
         )?;
         writeln!(writer, "                    <td>{}</td>", size_str)?;
         writeln!(writer, "                    <td>{}</td>", mtime_str)?;
@@ -220,19 +222,12 @@ fn export_html<W: Write>(writer: &mut W, entries: &[Entry], title: Option<&str>)
 mod tests {
     use super::*;
     use crate::models::EntryKind;
-    use chrono::Utc;
-    use std::path::PathBuf;
 
     fn make_test_entry(name: &str, size: u64, kind: EntryKind) -> Entry {
         Entry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
             size,
             kind,
-            mtime: Utc::now(),
-            perms: None,
-            owner: None,
-            depth: 0,
+            ..Entry::test_file(name)
         }
     }
 
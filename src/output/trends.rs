@@ -0,0 +1,151 @@
+use crate::errors::Result;
+use crate::fs::snapshot::SnapshotEntry;
+use crate::fs::trends::{sparkline, TrendPoint, TrendReport};
+use crate::plain;
+use crate::util::format_size_human;
+use chrono::Local;
+use std::io::Write;
+
+/// Human-readable `fexplorer trends` report, with an optional size sparkline
+/// (left out with `--plain`)
+pub fn write_trends_report(
+    writer: &mut dyn Write,
+    report: &TrendReport,
+    chart: bool,
+) -> Result<()> {
+    let (Some(first), Some(last)) = (report.series.first(), report.series.last()) else {
+        return Ok(());
+    };
+    let date = |point: &TrendPoint| {
+        point
+            .created
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+    let arrow = plain::pick("→", "to");
+
+    writeln!(
+        writer,
+        "Trends for {} ({} snapshots, {} {} {})",
+        report.root.display(),
+        report.series.len(),
+        date(first),
+        arrow,
+        date(last)
+    )?;
+    writeln!(writer)?;
+    writeln!(
+        writer,
+        "  Size:   {} {} {} ({})",
+        format_size_human(first.total_size),
+        arrow,
+        format_size_human(last.total_size),
+        signed_size(report.size_delta)
+    )?;
+    writeln!(
+        writer,
+        "  Files:  {} {} {} ({:+})",
+        first.files, arrow, last.files, report.files_delta
+    )?;
+
+    if chart && !plain::is_enabled() {
+        let sizes: Vec<u64> = report.series.iter().map(|p| p.total_size).collect();
+        writeln!(
+            writer,
+            "  Chart:  {}  ({} – {})",
+            sparkline(&sizes),
+            format_size_human(sizes.iter().copied().min().unwrap_or(0)),
+            format_size_human(sizes.iter().copied().max().unwrap_or(0))
+        )?;
+    }
+
+    if !report.categories.is_empty() {
+        writeln!(writer, "\nBy category:")?;
+        for change in &report.categories {
+            writeln!(
+                writer,
+                "  {:<16} {:>12} {:>+8} files",
+                change.category,
+                signed_size(change.size_delta),
+                change.files_delta
+            )?;
+        }
+    }
+
+    let files = &report.files;
+    write_files(
+        writer,
+        &format!(
+            "New files: {} ({})",
+            files.added,
+            format_size_human(files.added_size)
+        ),
+        &files.largest_new,
+    )?;
+    write_files(
+        writer,
+        &format!(
+            "Deleted files: {} ({})",
+            files.deleted,
+            format_size_human(files.deleted_size)
+        ),
+        &files.largest_deleted,
+    )?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_files(writer: &mut dyn Write, heading: &str, entries: &[SnapshotEntry]) -> Result<()> {
+    writeln!(writer, "\n{}", heading)?;
+    for entry in entries {
+        writeln!(
+            writer,
+            "  {:>10}  {}",
+            format_size_human(entry.size),
+            entry.path.display()
+        )?;
+    }
+    Ok(())
+}
+
+/// Size change with an explicit sign, e.g. `+1.50 MiB`
+fn signed_size(delta: i64) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_size_human(delta.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::trends::FileChanges;
+    use chrono::DateTime;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_trends_report() {
+        let point = |id: &str, total_size| TrendPoint {
+            id: id.to_string(),
+            created: DateTime::UNIX_EPOCH,
+            total_size,
+            files: 2,
+        };
+        let report = TrendReport {
+            root: PathBuf::from("/data"),
+            series: vec![point("a", 1024), point("b", 0)],
+            size_delta: -1024,
+            files_delta: 0,
+            categories: Vec::new(),
+            files: FileChanges::default(),
+        };
+
+        let mut out = Vec::new();
+        write_trends_report(&mut out, &report, true).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Trends for /data (2 snapshots"));
+        assert!(out.contains("(-1 KiB)"), "{}", out);
+        assert!(out.contains("Chart:  █▁"));
+        assert!(out.contains("New files: 0"));
+    }
+}
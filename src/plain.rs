@@ -0,0 +1,40 @@
+//! Screen-reader friendly output (`--plain`)
+//!
+//! Plain output has no color, box drawing, emoji or arrows: entry kinds are
+//! spelled out as `DIR`, `FILE` and `LINK`, status symbols become words or
+//! are left out, and separator rules are skipped. It's off unless [`enable`]
+//! was called; the formatters, the TUI and px check [`is_enabled`] where
+//! they would draw a symbol.
+
+use crate::models::EntryKind;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Print plain output for the rest of this process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether output is plain
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// `fancy` normally, `plain` in plain mode
+pub fn pick<'a>(fancy: &'a str, plain: &'a str) -> &'a str {
+    if is_enabled() {
+        plain
+    } else {
+        fancy
+    }
+}
+
+/// Word for an entry kind, padded to the same width for all kinds
+pub fn kind_marker(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::Dir => "DIR ",
+        EntryKind::File => "FILE",
+        EntryKind::Symlink => "LINK",
+    }
+}
@@ -0,0 +1,207 @@
+//! Installed filter plugins
+//!
+//! Plugins live in `~/.config/fexplorer/plugins/`: one `<name>.wasm` module
+//! per plugin, plus `plugins.toml` recording which of them are enabled.
+//! Enabled plugins join the filter chain of every command taking filter
+//! flags (see [`wasm`] for the module interface).
+
+use crate::config::Config;
+use crate::errors::{FsError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "plugins-wasm")]
+pub mod wasm;
+
+/// Registry entry of one installed plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Where the module was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// The installed plugins and their state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginRegistry {
+    #[serde(skip)]
+    dir: PathBuf,
+    #[serde(default)]
+    pub plugins: BTreeMap<String, PluginInfo>,
+}
+
+impl PluginRegistry {
+    /// Load the registry from the default directory
+    pub fn load() -> Result<Self> {
+        Self::load_from(Self::plugins_dir()?)
+    }
+
+    /// Load the registry kept in `dir` (empty if there is none yet)
+    pub fn load_from(dir: PathBuf) -> Result<Self> {
+        let path = dir.join("plugins.toml");
+        let mut registry: Self = match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).map_err(|e| FsError::InvalidFormat {
+                format: format!("Invalid plugin registry {}: {}", path.display(), e),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(FsError::PathAccess { path, source: e }),
+        };
+        registry.dir = dir;
+        Ok(registry)
+    }
+
+    /// Write the registry back to its directory
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| FsError::PathAccess {
+            path: self.dir.clone(),
+            source: e,
+        })?;
+        let content = toml::to_string_pretty(self).map_err(|e| FsError::InvalidFormat {
+            format: format!("Failed to serialize plugin registry: {}", e),
+        })?;
+        let path = self.dir.join("plugins.toml");
+        fs::write(&path, content).map_err(|e| FsError::PathAccess { path, source: e })
+    }
+
+    /// Plugin directory (~/.config/fexplorer/plugins)
+    pub fn plugins_dir() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("plugins"))
+    }
+
+    /// Path of a plugin's module
+    pub fn module_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.wasm", name))
+    }
+
+    /// Copy the module at `source` into the plugin directory, enabled
+    ///
+    /// The name defaults to the file stem. Installing over an existing
+    /// plugin replaces its module.
+    pub fn install(&mut self, source: &Path, name: Option<&str>) -> Result<String> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => source
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(FsError::InvalidFormat {
+                format: format!(
+                    "Invalid plugin name '{}' (use letters, digits, '-' and '_')",
+                    name
+                ),
+            });
+        }
+
+        let module = fs::read(source).map_err(|e| FsError::PathAccess {
+            path: source.to_path_buf(),
+            source: e,
+        })?;
+        #[cfg(feature = "plugins-wasm")]
+        wasm::validate(&module).map_err(|e| FsError::InvalidFormat {
+            format: format!("{}: {}", source.display(), e),
+        })?;
+        #[cfg(not(feature = "plugins-wasm"))]
+        if !module.starts_with(b"\0asm") {
+            return Err(FsError::InvalidFormat {
+                format: format!("{} is not a WebAssembly module", source.display()),
+            });
+        }
+
+        fs::create_dir_all(&self.dir).map_err(|e| FsError::PathAccess {
+            path: self.dir.clone(),
+            source: e,
+        })?;
+        let target = self.module_path(&name);
+        fs::write(&target, module).map_err(|e| FsError::PathAccess {
+            path: target,
+            source: e,
+        })?;
+
+        self.plugins.insert(
+            name.clone(),
+            PluginInfo {
+                enabled: true,
+                source: source.canonicalize().ok(),
+            },
+        );
+        self.save()?;
+        Ok(name)
+    }
+
+    /// Enable or disable an installed plugin
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let info = self
+            .plugins
+            .get_mut(name)
+            .ok_or_else(|| FsError::InvalidFormat {
+                format: format!(
+                    "Plugin '{}' is not installed (see `fexplorer plugins list`)",
+                    name
+                ),
+            })?;
+        info.enabled = enabled;
+        self.save()
+    }
+
+    /// Names of the enabled plugins, sorted
+    pub fn enabled(&self) -> impl Iterator<Item = &str> {
+        self.plugins
+            .iter()
+            .filter(|(_, info)| info.enabled)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_enable_disable() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("only-rust.wasm");
+        #[cfg(feature = "plugins-wasm")]
+        fs::write(
+            &source,
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "test") (param i32 i32) (result i32) (i32.const 1)))"#,
+        )
+        .unwrap();
+        #[cfg(not(feature = "plugins-wasm"))]
+        fs::write(&source, b"\0asm\x01\0\0\0").unwrap();
+        let junk = dir.path().join("junk.wasm");
+        fs::write(&junk, "not wasm").unwrap();
+
+        let plugins_dir = dir.path().join("plugins");
+        let mut registry = PluginRegistry::load_from(plugins_dir.clone()).unwrap();
+        assert!(registry.install(&junk, None).is_err());
+        assert!(registry.install(&source, Some("bad name")).is_err());
+
+        let name = registry.install(&source, None).unwrap();
+        assert_eq!(name, "only-rust");
+        assert!(plugins_dir.join("only-rust.wasm").is_file());
+
+        registry.set_enabled("only-rust", false).unwrap();
+        assert!(registry.set_enabled("missing", true).is_err());
+
+        let reloaded = PluginRegistry::load_from(plugins_dir).unwrap();
+        assert_eq!(reloaded.plugins.len(), 1);
+        assert_eq!(reloaded.enabled().count(), 0);
+    }
+}
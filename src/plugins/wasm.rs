@@ -0,0 +1,224 @@
+//! WebAssembly filter plugins
+//!
+//! A plugin is a WebAssembly module (binary or text format) exporting:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32`: address of a `len`-byte buffer for the host
+//! - `test(ptr: i32, len: i32) -> i32`: called with the entry as JSON (the
+//!   object `--format json` prints) in that buffer; non-zero keeps the entry
+//! - optionally `dealloc(ptr: i32, len: i32)`, called after `test`
+//!
+//! Modules get no imports, so they can't touch the filesystem or network,
+//! and each call runs on a fuel budget so a looping plugin can't hang a walk.
+
+use crate::errors::{FsError, Result};
+use crate::fs::filters::{Predicate, PredicateNode};
+use crate::models::Entry;
+use crate::plugins::PluginRegistry;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::warn;
+use wasmtime::{Engine, Linker, Memory, Module, Store, TypedFunc};
+
+/// Instructions (roughly) a single `test` call may execute
+const FUEL_PER_CALL: u64 = 50_000_000;
+
+/// An instantiated plugin module
+struct Instance {
+    store: Store<()>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    test: TypedFunc<(i32, i32), i32>,
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+impl Instance {
+    fn new(engine: &Engine, module: &Module) -> wasmtime::Result<Self> {
+        let mut store = Store::new(engine, ());
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = Linker::new(engine).instantiate(&mut store, module)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| wasmtime::Error::msg("module does not export `memory`"))?;
+        let alloc = instance.get_typed_func(&mut store, "alloc")?;
+        let test = instance.get_typed_func(&mut store, "test")?;
+        let dealloc = instance.get_typed_func(&mut store, "dealloc").ok();
+        Ok(Self {
+            store,
+            memory,
+            alloc,
+            test,
+            dealloc,
+        })
+    }
+
+    fn call(&mut self, json: &[u8]) -> wasmtime::Result<bool> {
+        let len = i32::try_from(json.len())?;
+        self.store.set_fuel(FUEL_PER_CALL)?;
+        let ptr = self.alloc.call(&mut self.store, len)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, json)?;
+        let keep = self.test.call(&mut self.store, (ptr, len))? != 0;
+        if let Some(dealloc) = &self.dealloc {
+            dealloc.call(&mut self.store, (ptr, len))?;
+        }
+        Ok(keep)
+    }
+}
+
+fn engine() -> wasmtime::Result<Engine> {
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config)
+}
+
+/// Check that `module` compiles and has the plugin exports
+pub fn validate(module: &[u8]) -> std::result::Result<(), String> {
+    let engine = engine().map_err(|e| e.to_string())?;
+    let module = Module::new(&engine, module).map_err(|e| format!("{:#}", e))?;
+    Instance::new(&engine, &module)
+        .map(|_| ())
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// A plugin as a filter predicate
+///
+/// Entries the plugin fails on (a trap, running out of fuel) are excluded;
+/// the first failure is logged.
+pub struct WasmFilter {
+    name: String,
+    instance: Mutex<Instance>,
+    failed: AtomicBool,
+}
+
+impl WasmFilter {
+    /// Load and instantiate an installed plugin
+    pub fn load(registry: &PluginRegistry, name: &str) -> Result<Self> {
+        let path = registry.module_path(name);
+        let invalid = |e: wasmtime::Error| FsError::InvalidFormat {
+            format: format!("Plugin '{}' ({}): {:#}", name, path.display(), e),
+        };
+
+        let engine = engine().map_err(invalid)?;
+        let module = Module::from_file(&engine, &path).map_err(invalid)?;
+        let instance = Instance::new(&engine, &module).map_err(invalid)?;
+        Ok(Self {
+            name: name.to_string(),
+            instance: Mutex::new(instance),
+            failed: AtomicBool::new(false),
+        })
+    }
+}
+
+impl Predicate for WasmFilter {
+    fn test(&self, entry: &Entry) -> bool {
+        let result = serde_json::to_vec(entry)
+            .map_err(wasmtime::Error::from)
+            .and_then(|json| match self.instance.lock() {
+                Ok(mut instance) => instance.call(&json),
+                Err(_) => Err(wasmtime::Error::msg("plugin poisoned by an earlier panic")),
+            });
+
+        result.unwrap_or_else(|e| {
+            if !self.failed.swap(true, Ordering::Relaxed) {
+                warn!(
+                    plugin = %self.name,
+                    path = %entry.path.display(),
+                    error = %format!("{:#}", e),
+                    "Plugin failed, excluding the entries it fails on"
+                );
+            }
+            false
+        })
+    }
+
+    fn explain(&self) -> PredicateNode {
+        PredicateNode::leaf("plugin", self.name.clone(), 0.5)
+    }
+}
+
+/// Filters for all enabled plugins
+pub fn enabled_filters(registry: &PluginRegistry) -> Result<Vec<Box<dyn Predicate>>> {
+    registry
+        .enabled()
+        .map(|name| Ok(Box::new(WasmFilter::load(registry, name)?) as Box<dyn Predicate>))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    /// Keeps entries whose JSON contains `.rs"`, i.e. names ending in `.rs`
+    const KEEP_RUST_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          ;; One entry is passed at a time, so a fixed buffer will do
+          (func (export "alloc") (param $len i32) (result i32) (i32.const 1024))
+          (func (export "test") (param $ptr i32) (param $len i32) (result i32)
+            (local $i i32) (local $end i32)
+            (local.set $i (local.get $ptr))
+            (local.set $end
+              (i32.sub (i32.add (local.get $ptr) (local.get $len)) (i32.const 4)))
+            (block $done
+              (loop $scan
+                (br_if $done (i32.gt_s (local.get $i) (local.get $end)))
+                (if (i32.eq (i32.load (local.get $i)) (i32.const 0x2273722e))
+                  (then (return (i32.const 1))))
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $scan)))
+            (i32.const 0)))
+    "#;
+
+    const LOOPING_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "test") (param i32 i32) (result i32)
+            (loop $forever (br $forever))
+            (i32.const 1)))
+    "#;
+
+    fn install(dir: &std::path::Path, name: &str, wat: &str) -> PluginRegistry {
+        let source = dir.join(format!("{}.wat", name));
+        std::fs::write(&source, wat).unwrap();
+        let mut registry = PluginRegistry::load_from(dir.join("plugins")).unwrap();
+        registry.install(&source, None).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_plugin_filters_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = install(dir.path(), "rust-only", KEEP_RUST_WAT);
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "# notes").unwrap();
+
+        let filters = enabled_filters(&registry).unwrap();
+        assert_eq!(filters.len(), 1);
+        let keep = |name: &str| filters[0].test(&extract_entry(&dir.path().join(name), 1).unwrap());
+        assert!(keep("main.rs"));
+        assert!(!keep("notes.md"));
+    }
+
+    #[test]
+    fn test_runaway_plugin_is_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = install(dir.path(), "forever", LOOPING_WAT);
+        let filter = WasmFilter::load(&registry, "forever").unwrap();
+
+        let entry = extract_entry(&registry.module_path("forever"), 1).unwrap();
+        assert!(!filter.test(&entry));
+        assert!(!filter.test(&entry));
+    }
+
+    #[test]
+    fn test_validate_requires_exports() {
+        assert!(validate(KEEP_RUST_WAT.as_bytes()).is_ok());
+        let err = validate(br#"(module (memory (export "memory") 1))"#).unwrap_err();
+        assert!(err.contains("alloc"), "{}", err);
+        assert!(validate(b"\0asm garbage").is_err());
+    }
+}
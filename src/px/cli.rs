@@ -0,0 +1,280 @@
+//! px's subcommands, shared by the `px` binary and `fexplorer projects`
+//!
+//! `program` is how the user invokes them, so the shell integration calls
+//! back into the right binary.
+
+use crate::config::PxConfig;
+use crate::errors::{FsError, Result};
+use crate::px::shell::{init_script, Shell};
+use crate::px::{commands, index::ProjectIndex};
+use clap::Subcommand;
+use std::path::PathBuf;
+
+/// A px subcommand
+#[derive(Subcommand, Debug)]
+pub enum PxCommand {
+    /// List all projects
+    List {
+        /// Filter projects (has-changes, inactive-30d, inactive-90d)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Output format (json, path, pretty)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Open project in editor
+    Open {
+        /// Project name/path query (fuzzy matched); omit to pick interactively
+        query: Option<String>,
+
+        /// Editor to use (code, cursor, vim, etc.)
+        #[arg(long)]
+        editor: Option<String>,
+
+        /// Output format (pretty, json); json reports the project, the steps
+        /// taken and any errors
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Pick a project from a live-filtered list and open it
+    Pick {
+        /// Editor to use (code, cursor, vim, etc.)
+        #[arg(long)]
+        editor: Option<String>,
+    },
+
+    /// Print a project's path; with `px shell-init`, change to it
+    Cd {
+        /// Project name/path query (fuzzy matched)
+        query: String,
+    },
+
+    /// Create or attach to a tmux session for a project, in its directory
+    ///
+    /// Commands under `[startup]` in the config run when the session is created.
+    Tmux {
+        /// Project name/path query (fuzzy matched)
+        query: String,
+    },
+
+    /// Open a project's `origin` remote (GitHub, GitLab, Bitbucket) in the browser
+    Web {
+        /// Project name/path query (fuzzy matched)
+        query: String,
+
+        /// Copy the URL to the clipboard instead
+        #[arg(long)]
+        copy: bool,
+    },
+
+    /// Print a `px` wrapper function that makes `px cd` change directory
+    ///
+    /// Add `eval "$(px shell-init bash)"` to ~/.bashrc (or zsh, ~/.zshrc),
+    /// or `px shell-init fish | source` to ~/.config/fish/config.fish.
+    ShellInit {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Create a project from a template, put it under git and index it
+    ///
+    /// Templates are named under `[templates]` in the config (a directory or
+    /// a git URL) or are directories in ~/.config/px/templates.
+    New {
+        /// Directory name of the new project
+        name: String,
+
+        /// Template name, directory or git URL; omit for an empty project
+        #[arg(short, long)]
+        template: Option<String>,
+
+        /// Create it here instead of in the first scan directory
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// List projects with uncommitted changes or unpushed commits, untouched for a while
+    ///
+    /// Prints nothing when all work is committed and pushed, so it suits a
+    /// shell prompt hook or a cron job.
+    Remind {
+        /// Only work whose last commit is at least this many days old
+        #[arg(long, default_value_t = 7, value_name = "DAYS")]
+        days: u32,
+
+        /// Re-read every project's git status instead of the cached one
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Show project information
+    Info {
+        /// Project name/path query (fuzzy matched)
+        query: String,
+
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+
+    /// Re-index projects by scanning configured directories
+    Sync {
+        /// Keep running and refresh projects as their files change
+        #[arg(long)]
+        watch: bool,
+
+        /// Output format (pretty, json); json reports the projects added and removed
+        #[arg(long, default_value = "pretty", conflicts_with = "watch")]
+        format: String,
+    },
+
+    /// Summarize the index: languages, disk usage, most and least active projects
+    Stats {
+        /// Output format (pretty, json)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+
+        /// Skip measuring disk usage, which walks every project
+        #[arg(long)]
+        no_size: bool,
+    },
+
+    /// Initialize px configuration
+    Init,
+
+    /// Write the index, including access history, as JSON
+    Export {
+        /// File to write [default: stdout]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Merge access history from a `px export` file (`-` for stdin)
+    ///
+    /// Counts are summed and the latest access kept, so importing the same
+    /// file twice counts its accesses twice.
+    Import {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+impl PxCommand {
+    /// Whether results and errors are printed in the JSON envelope
+    fn is_json(&self) -> bool {
+        match self {
+            PxCommand::Open { format, .. }
+            | PxCommand::Info { format, .. }
+            | PxCommand::Sync { format, .. } => format == "json",
+            _ => false,
+        }
+    }
+}
+
+/// Run `command` against the saved index and config
+///
+/// With `--format json`, an error is also printed to stdout as
+/// `{"ok": false, "error": {...}}`, for scripts reading the output.
+pub fn run(command: PxCommand, program: &str) -> Result<()> {
+    let json = command.is_json();
+    let result = execute(command, program);
+    if let (true, Err(e)) = (json, &result) {
+        commands::print_json_error(e);
+    }
+    result
+}
+
+fn execute(command: PxCommand, program: &str) -> Result<()> {
+    let config = PxConfig::load()?;
+    let mut index = ProjectIndex::load()?;
+
+    match command {
+        PxCommand::List { filter, format: _ } => {
+            index.refresh_stale()?;
+            commands::cmd_list(&index, filter)?;
+        }
+        PxCommand::Open {
+            query: Some(query),
+            editor,
+            format,
+        } => {
+            let editor = editor.unwrap_or_else(|| config.default_editor.clone());
+            commands::cmd_open(&mut index, &query, &editor, &config, &format)?;
+        }
+        PxCommand::Open {
+            query: None,
+            format,
+            ..
+        } if format != "pretty" => {
+            return Err(FsError::InvalidFormat {
+                format: "px open --format json needs a query".to_string(),
+            });
+        }
+        PxCommand::Open {
+            query: None,
+            editor,
+            ..
+        }
+        | PxCommand::Pick { editor } => {
+            let editor = editor.unwrap_or_else(|| config.default_editor.clone());
+            commands::cmd_pick(&mut index, &editor, &config)?;
+        }
+        PxCommand::Tmux { query } => {
+            commands::cmd_tmux(&mut index, &query, &config)?;
+        }
+        PxCommand::Cd { query } => {
+            commands::cmd_cd(&mut index, &query)?;
+        }
+        PxCommand::Web { query, copy } => {
+            commands::cmd_web(&index, &query, copy)?;
+        }
+        PxCommand::ShellInit { shell } => {
+            print!("{}", init_script(shell, program));
+        }
+        PxCommand::New {
+            name,
+            template,
+            dir,
+        } => {
+            commands::cmd_new(
+                &mut index,
+                &name,
+                template.as_deref(),
+                dir.as_deref(),
+                &config,
+            )?;
+        }
+        PxCommand::Remind { days, refresh } => {
+            commands::cmd_remind(&mut index, days, refresh)?;
+        }
+        PxCommand::Info { query, format } => {
+            commands::cmd_info(&mut index, &query, &format)?;
+        }
+        PxCommand::Sync {
+            watch: false,
+            format,
+        } => {
+            commands::cmd_sync(&mut index, &config.scan_dirs, &format)?;
+        }
+        PxCommand::Sync { watch: true, .. } => {
+            commands::cmd_sync_watch(&mut index, &config.scan_dirs)?;
+        }
+        PxCommand::Stats { format, no_size } => {
+            commands::cmd_stats(&index, &format, !no_size)?;
+        }
+        PxCommand::Init => {
+            commands::cmd_init()?;
+        }
+        PxCommand::Export { output } => {
+            commands::cmd_export(&index, output.as_deref())?;
+        }
+        PxCommand::Import { file } => {
+            commands::cmd_import(&mut index, &file)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,24 +1,140 @@
 //! CLI command implementations
 //!
-//! Implements the core px commands: list, open, info, sync, init
+//! Implements the core px commands: list, open, pick, cd, tmux, web, new,
+//! remind, info, stats, sync, init, export and import
 
-use crate::config::PxConfig;
+use crate::config::{PxConfig, Terminal};
 use crate::errors::{FsError, Result};
-use crate::px::index::ProjectIndex;
+use crate::plain;
+use crate::px::index::{MergeSummary, ProjectIndex};
+use crate::px::project::Project;
+use crate::px::remote;
 use crate::px::search::ProjectSearcher;
+use crate::px::stats;
+use crate::px::template::{self, Template};
+use crate::px::tmux;
+use crate::util::format_size_human;
 use chrono::Duration;
-use std::path::PathBuf;
-use std::process::Command;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// `✓ ` before a finished action; nothing with `--plain`
+fn done() -> &'static str {
+    plain::pick("✓ ", "")
+}
+
+/// `⚠️  ` before a warning; `Warning: ` with `--plain`
+fn warning() -> &'static str {
+    plain::pick("⚠️  ", "Warning: ")
+}
+
+/// Whether `format` asks for JSON rather than text: `json` or `pretty`
+pub fn is_json(format: &str) -> Result<bool> {
+    match format {
+        "json" => Ok(true),
+        "pretty" => Ok(false),
+        other => Err(FsError::InvalidFormat {
+            format: format!("{} (expected pretty or json)", other),
+        }),
+    }
+}
+
+/// Print `result` in the JSON envelope: `{"ok": true, "result": ...}`
+fn print_json<T: Serialize>(result: &T) -> Result<()> {
+    let envelope = serde_json::json!({ "ok": true, "result": result });
+    println!("{}", serde_json::to_string_pretty(&envelope)?);
+    Ok(())
+}
+
+/// Print `error` in the JSON envelope: `{"ok": false, "error": {...}}`, with
+/// the messages of its underlying errors as `causes`
+pub fn print_json_error(error: &FsError) {
+    let causes: Vec<String> = std::iter::successors(error.source(), |&e| e.source())
+        .map(|e| e.to_string())
+        .collect();
+    // px reports most failures as InvalidFormat; its prefix would only mislead
+    let message = match error {
+        FsError::InvalidFormat { format } => format.clone(),
+        other => other.to_string(),
+    };
+    let envelope = serde_json::json!({
+        "ok": false,
+        "error": { "message": message, "causes": causes },
+    });
+    println!("{:#}", envelope);
+}
+
+fn no_match(query: &str) -> FsError {
+    FsError::InvalidFormat {
+        format: format!("No projects found matching '{}'", query),
+    }
+}
 
 /// Initialize px configuration
 pub fn cmd_init() -> Result<()> {
     PxConfig::init()
 }
 
+/// What `px sync` did
+#[derive(Debug, Serialize)]
+pub struct SyncReport {
+    pub scan_dirs: Vec<PathBuf>,
+    /// Scan directories that don't exist and were skipped
+    pub missing_dirs: Vec<PathBuf>,
+    /// Number of projects in the index afterwards
+    pub projects: usize,
+    /// Paths of projects new to the index
+    pub added: Vec<String>,
+    /// Paths of projects that are gone from the index
+    pub removed: Vec<String>,
+    pub elapsed_secs: f64,
+}
+
 /// Rebuild the project index by scanning configured directories
-pub fn cmd_sync(index: &mut ProjectIndex, scan_dirs: &[PathBuf]) -> Result<()> {
+pub fn cmd_sync(index: &mut ProjectIndex, scan_dirs: &[PathBuf], format: &str) -> Result<()> {
+    if is_json(format)? {
+        if scan_dirs.is_empty() {
+            return Err(FsError::InvalidFormat {
+                format: format!(
+                    "No scan directories configured in {}",
+                    PxConfig::config_file_path()?.display()
+                ),
+            });
+        }
+        let before: HashSet<String> = index.projects.keys().cloned().collect();
+        let start = std::time::Instant::now();
+        let count = index.sync(scan_dirs)?;
+        let elapsed = start.elapsed();
+
+        let mut added: Vec<String> = index
+            .projects
+            .keys()
+            .filter(|p| !before.contains(*p))
+            .cloned()
+            .collect();
+        let mut removed: Vec<String> = before
+            .into_iter()
+            .filter(|p| !index.projects.contains_key(p))
+            .collect();
+        added.sort();
+        removed.sort();
+        return print_json(&SyncReport {
+            scan_dirs: scan_dirs.to_vec(),
+            missing_dirs: scan_dirs.iter().filter(|d| !d.exists()).cloned().collect(),
+            projects: count,
+            added,
+            removed,
+            elapsed_secs: elapsed.as_secs_f64(),
+        });
+    }
+
     if scan_dirs.is_empty() {
-        println!("⚠️  No scan directories configured!");
+        println!("{}No scan directories configured!", warning());
         println!("Run `px init` to create a config file, then edit:");
         println!("  {}", PxConfig::config_file_path()?.display());
         return Ok(());
@@ -26,7 +142,7 @@ pub fn cmd_sync(index: &mut ProjectIndex, scan_dirs: &[PathBuf]) -> Result<()> {
 
     println!("Scanning {} directories...", scan_dirs.len());
     for dir in scan_dirs {
-        println!("  • {}", dir.display());
+        println!("  {}{}", plain::pick("• ", ""), dir.display());
     }
     println!();
 
@@ -34,11 +150,67 @@ pub fn cmd_sync(index: &mut ProjectIndex, scan_dirs: &[PathBuf]) -> Result<()> {
     let count = index.sync(scan_dirs)?;
     let elapsed = start.elapsed();
 
-    println!("✓ Indexed {} projects in {:.2}s", count, elapsed.as_secs_f64());
+    println!(
+        "{}Indexed {} projects in {:.2}s",
+        done(),
+        count,
+        elapsed.as_secs_f64()
+    );
 
     Ok(())
 }
 
+/// Sync, then keep the index fresh as files under the scan directories change
+///
+/// Changed projects get their git status re-read; a repository appearing or
+/// moving triggers a full sync.
+#[cfg(feature = "watch")]
+pub fn cmd_sync_watch(index: &mut ProjectIndex, scan_dirs: &[PathBuf]) -> Result<()> {
+    use crate::fs::watch::FileWatcher;
+
+    cmd_sync(index, scan_dirs, "pretty")?;
+    let watched: Vec<PathBuf> = scan_dirs.iter().filter(|d| d.exists()).cloned().collect();
+    if watched.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    println!("Watching for changes... (Ctrl+C to stop)");
+    let quiet = std::time::Duration::from_secs(2);
+    FileWatcher::new(Vec::new()).watch_batched(&watched, quiet, |events| {
+        // Other px commands may have recorded accesses meanwhile
+        *index = ProjectIndex::load()?;
+        let paths: Vec<PathBuf> = events.into_iter().map(|e| e.path).collect();
+
+        match index.affected_by(&paths) {
+            None => {
+                let count = index.sync(scan_dirs)?;
+                println!("{}Re-indexed {} projects", done(), count);
+            }
+            Some(projects) if projects.is_empty() => {}
+            Some(projects) => {
+                for path in &projects {
+                    index.refresh_project(path);
+                }
+                index.save()?;
+                let names: Vec<&str> = projects
+                    .iter()
+                    .map(|path| path.rsplit(std::path::is_separator).next().unwrap_or(path))
+                    .collect();
+                println!("{}Refreshed {}", done(), names.join(", "));
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(feature = "watch"))]
+pub fn cmd_sync_watch(_index: &mut ProjectIndex, _scan_dirs: &[PathBuf]) -> Result<()> {
+    Err(FsError::InvalidFormat {
+        format: "px sync --watch requires the watch feature".to_string(),
+    })
+}
+
 /// List all projects with optional filtering
 pub fn cmd_list(index: &ProjectIndex, filter: Option<String>) -> Result<()> {
     let mut projects: Vec<_> = index.sorted_projects();
@@ -49,11 +221,11 @@ pub fn cmd_list(index: &ProjectIndex, filter: Option<String>) -> Result<()> {
             "has-changes" => p.git_status.has_uncommitted,
             "inactive-30d" => {
                 let cutoff = chrono::Utc::now() - Duration::days(30);
-                p.last_accessed.map_or(true, |t| t < cutoff)
+                p.last_accessed.is_none_or(|t| t < cutoff)
             }
             "inactive-90d" => {
                 let cutoff = chrono::Utc::now() - Duration::days(90);
-                p.last_accessed.map_or(true, |t| t < cutoff)
+                p.last_accessed.is_none_or(|t| t < cutoff)
             }
             _ => true,
         });
@@ -70,18 +242,20 @@ pub fn cmd_list(index: &ProjectIndex, filter: Option<String>) -> Result<()> {
 
     // Print header
     println!("{:<30} {:<15} {:<8}", "Project", "Branch", "Status");
-    println!("{}", "─".repeat(60));
+    if !plain::is_enabled() {
+        println!("{}", "─".repeat(60));
+    }
 
     // Print projects
     for project in &projects {
         let status = if project.git_status.has_uncommitted {
-            "⚠ changes"
+            plain::pick("⚠ changes", "changes")
         } else if project.git_status.ahead > 0 {
-            "↑ ahead"
+            plain::pick("↑ ahead", "ahead")
         } else if project.git_status.behind > 0 {
-            "↓ behind"
+            plain::pick("↓ behind", "behind")
         } else {
-            "✓ clean"
+            plain::pick("✓ clean", "clean")
         };
 
         println!(
@@ -98,38 +272,160 @@ pub fn cmd_list(index: &ProjectIndex, filter: Option<String>) -> Result<()> {
     Ok(())
 }
 
-/// Open a project in an editor and iTerm2
-pub fn cmd_open(index: &mut ProjectIndex, query: &str, editor: &str) -> Result<()> {
+/// What `px open` did
+#[derive(Debug, Serialize)]
+pub struct OpenReport {
+    /// The project, with the access recorded
+    pub project: Project,
+    pub editor: String,
+    /// Steps carried out, in order: `editor`, `record_access`, then `tmux`
+    /// or `iterm` as the config's `terminal` says
+    pub actions: Vec<&'static str>,
+    /// Steps that failed without stopping the others
+    pub errors: Vec<String>,
+}
+
+/// Open a project in an editor and the configured terminal
+pub fn cmd_open(
+    index: &mut ProjectIndex,
+    query: &str,
+    editor: &str,
+    config: &PxConfig,
+    format: &str,
+) -> Result<()> {
+    let json = is_json(format)?;
     let searcher = ProjectSearcher::new();
     let projects: Vec<_> = index.projects.values().cloned().collect();
     let results = searcher.search(&projects, query);
 
-    if results.is_empty() {
+    let Some(project) = results.first() else {
+        if json {
+            return Err(no_match(query));
+        }
         println!("No projects found matching '{}'", query);
         return Ok(());
+    };
+
+    let report = open_project(index, (*project).clone(), editor, config, json)?;
+    if json {
+        print_json(&report)?;
     }
+    Ok(())
+}
 
-    let project = results[0];
-    let project_path = project.path.clone();
-    let project_name = project.name.clone();
+/// Choose a project interactively, then open it like `px open`
+#[cfg(feature = "tui")]
+pub fn cmd_pick(index: &mut ProjectIndex, editor: &str, config: &PxConfig) -> Result<()> {
+    index.refresh_stale()?;
+    let projects: Vec<_> = index.projects.values().cloned().collect();
+    if projects.is_empty() {
+        println!("No projects indexed yet. Run `px sync` to scan for projects.");
+        return Ok(());
+    }
 
-    println!("Opening {} in {} + iTerm2...", project_name, editor);
-    println!("  Path: {}", project_path.display());
+    let picked = crate::px::pick::pick(&projects).map_err(|e| FsError::IoError {
+        context: "Failed to run the project picker".to_string(),
+        source: e,
+    })?;
+    if let Some(project) = picked {
+        open_project(index, project, editor, config, false)?;
+    }
+    Ok(())
+}
 
-    // Spawn editor
-    let editor_status = Command::new(editor)
-        .arg(&project_path)
-        .status()
-        .map_err(|e| FsError::IoError {
-            context: format!("Failed to spawn editor '{}'", editor),
-            source: e,
-        })?;
+#[cfg(not(feature = "tui"))]
+pub fn cmd_pick(_index: &mut ProjectIndex, _editor: &str, _config: &PxConfig) -> Result<()> {
+    Err(FsError::InvalidFormat {
+        format: "px pick requires the tui feature".to_string(),
+    })
+}
+
+/// Open `project` in `editor` and the configured terminal, recording the access
+///
+/// Progress is printed unless `json`; failures after the editor has started
+/// are only reported.
+fn open_project(
+    index: &mut ProjectIndex,
+    project: Project,
+    editor: &str,
+    config: &PxConfig,
+    json: bool,
+) -> Result<OpenReport> {
+    let (project_path, project_name) = (project.path.clone(), project.name.clone());
+    if !json {
+        let terminal = match config.terminal {
+            Terminal::Tmux => " + tmux",
+            Terminal::Iterm => " + iTerm2",
+            Terminal::None => "",
+        };
+        println!("Opening {} in {}{}...", project_name, editor, terminal);
+        println!("  Path: {}", project_path.display());
+    }
+    let mut actions = Vec::new();
+    let mut errors = Vec::new();
+    let mut report_error = |message: String| {
+        if !json {
+            warn!("{}", message);
+        }
+        errors.push(message);
+    };
 
-    if !editor_status.success() {
-        eprintln!("⚠️  Editor '{}' exited with error", editor);
+    // Spawn editor; JSON output on stdout must stay the only thing there
+    let mut editor_command = Command::new(editor);
+    editor_command.arg(&project_path);
+    if json {
+        editor_command.stdout(Stdio::null());
+    }
+    let editor_status = editor_command.status().map_err(|e| FsError::IoError {
+        context: format!("Failed to spawn editor '{}'", editor),
+        source: e,
+    })?;
+
+    if editor_status.success() {
+        actions.push("editor");
+    } else {
+        report_error(format!("Editor '{}' exited with error", editor));
+    }
+
+    // Record access for frecency tracking, before tmux takes over the terminal
+    let key = project_path.to_string_lossy().to_string();
+    index.record_access(&key)?;
+    actions.push("record_access");
+
+    let opened = match config.terminal {
+        Terminal::Tmux => {
+            let startup = config
+                .startup
+                .get(&project_name)
+                .map_or(&[][..], Vec::as_slice);
+            let session = tmux::session_name(&project_name);
+            tmux::open_session(&session, &project_path, startup, !json)
+                .map(|()| Some("tmux"))
+                .map_err(|e| e.to_string())
+        }
+        Terminal::Iterm => open_iterm(&project_path).map(|()| {
+            if !json {
+                println!("{}Opened iTerm2 window at project directory", done());
+            }
+            Some("iterm")
+        }),
+        Terminal::None => Ok(None),
+    };
+    match opened {
+        Ok(action) => actions.extend(action),
+        Err(message) => report_error(message),
     }
 
-    // Open iTerm2 window at project directory
+    Ok(OpenReport {
+        project: index.projects.get(&key).cloned().unwrap_or(project),
+        editor: editor.to_string(),
+        actions,
+        errors,
+    })
+}
+
+/// Open an iTerm2 window at the project directory (macOS)
+fn open_iterm(project_path: &Path) -> std::result::Result<(), String> {
     let applescript = format!(
         r#"
         tell application "iTerm"
@@ -149,40 +445,282 @@ pub fn cmd_open(index: &mut ProjectIndex, query: &str, editor: &str) -> Result<(
         .status();
 
     match iterm_result {
-        Ok(status) if status.success() => {
-            println!("✓ Opened iTerm2 window at project directory");
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err("Failed to open iTerm2 window (check if iTerm2 is installed)".to_string()),
+        Err(e) => Err(format!("Could not execute osascript: {}", e)),
+    }
+}
+
+/// Create or attach to the best match's tmux session, in its directory
+pub fn cmd_tmux(index: &mut ProjectIndex, query: &str, config: &PxConfig) -> Result<()> {
+    let searcher = ProjectSearcher::new();
+    let projects: Vec<_> = index.projects.values().cloned().collect();
+    let results = searcher.search(&projects, query);
+
+    let Some(project) = results.first() else {
+        println!("No projects found matching '{}'", query);
+        return Ok(());
+    };
+    let (project_path, project_name) = (project.path.clone(), project.name.clone());
+
+    index.record_access(&project_path.to_string_lossy())?;
+    let startup = config.startup.get(&project_name).map_or(&[][..], Vec::as_slice);
+    tmux::open_session(
+        &tmux::session_name(&project_name),
+        &project_path,
+        startup,
+        true,
+    )
+}
+
+/// Print the path of the best match, for the shell wrapper to `cd` into
+pub fn cmd_cd(index: &mut ProjectIndex, query: &str) -> Result<()> {
+    let searcher = ProjectSearcher::new();
+    let projects: Vec<_> = index.projects.values().cloned().collect();
+    let results = searcher.search(&projects, query);
+
+    // Nothing on stdout, so the wrapper doesn't cd anywhere
+    let project = results.first().ok_or_else(|| no_match(query))?;
+    let project_path = project.path.clone();
+    println!("{}", project_path.display());
+
+    index.record_access(&project_path.to_string_lossy())?;
+
+    Ok(())
+}
+
+/// Open the best match's `origin` remote in the browser, or copy its URL
+pub fn cmd_web(index: &ProjectIndex, query: &str, copy: bool) -> Result<()> {
+    let searcher = ProjectSearcher::new();
+    let projects: Vec<_> = index.projects.values().cloned().collect();
+    let results = searcher.search(&projects, query);
+
+    let Some(project) = results.first() else {
+        println!("No projects found matching '{}'", query);
+        return Ok(());
+    };
+
+    let remote = project.origin_url()?.ok_or_else(|| FsError::InvalidFormat {
+        format: format!("{} has no origin remote", project.name),
+    })?;
+    let url = remote::web_url(&remote).ok_or_else(|| FsError::InvalidFormat {
+        format: format!("No web page for {}'s origin remote '{}'", project.name, remote),
+    })?;
+
+    if copy {
+        copy_to_clipboard(&url)?;
+        println!("{}Copied {}", done(), url);
+    } else {
+        println!("Opening {}", url);
+        open_in_browser(&url)?;
+    }
+    Ok(())
+}
+
+/// Hand `url` to `$BROWSER` or the platform's default handler
+fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = match std::env::var("BROWSER") {
+        Ok(browser) if !browser.is_empty() => Command::new(browser),
+        _ if cfg!(target_os = "macos") => Command::new("open"),
+        _ if cfg!(windows) => {
+            let mut command = Command::new("rundll32");
+            command.arg("url.dll,FileProtocolHandler");
+            command
         }
-        Ok(_) => {
-            eprintln!("⚠️  Failed to open iTerm2 window (check if iTerm2 is installed)");
+        _ => Command::new("xdg-open"),
+    };
+    let status = command.arg(url).status().map_err(|e| FsError::IoError {
+        context: "Failed to open the browser".to_string(),
+        source: e,
+    })?;
+    if !status.success() {
+        warn!(%status, "Browser exited with an error");
+    }
+    Ok(())
+}
+
+/// Put `text` on the clipboard with the first clipboard tool that works
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    use std::io::Write;
+
+    let tools: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(windows) {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    };
+    for tool in tools {
+        let Ok(mut child) = Command::new(tool[0])
+            .args(&tool[1..])
+            .stdin(Stdio::piped())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
         }
-        Err(e) => {
-            eprintln!("⚠️  Could not execute osascript: {}", e);
+        if child.wait()?.success() {
+            return Ok(());
         }
     }
 
-    // Record access for frecency tracking
-    index.record_access(&project_path.to_string_lossy())?;
+    let names: Vec<&str> = tools.iter().map(|tool| tool[0]).collect();
+    Err(FsError::InvalidFormat {
+        format: format!("No clipboard tool worked (tried {})", names.join(", ")),
+    })
+}
 
+/// Create a project in `dir` (the first scan directory by default), from a
+/// template if given, put it under git and index it
+pub fn cmd_new(
+    index: &mut ProjectIndex,
+    name: &str,
+    template: Option<&str>,
+    dir: Option<&Path>,
+    config: &PxConfig,
+) -> Result<()> {
+    if name.is_empty() || name.contains(std::path::is_separator) || name.starts_with('.') {
+        return Err(FsError::InvalidFormat {
+            format: format!("Invalid project name '{}'", name),
+        });
+    }
+    let parent = match dir.or(config.scan_dirs.first().map(PathBuf::as_path)) {
+        Some(parent) => parent,
+        None => {
+            return Err(FsError::InvalidFormat {
+                format: "No scan directories configured; pass --dir".to_string(),
+            })
+        }
+    };
+    let access_error = |e| FsError::PathAccess {
+        path: parent.to_path_buf(),
+        source: e,
+    };
+    // Indexed by absolute path, like everything `px sync` finds
+    fs::create_dir_all(parent).map_err(access_error)?;
+    let dest = parent.canonicalize().map_err(access_error)?.join(name);
+    if dest.exists() {
+        return Err(FsError::InvalidFormat {
+            format: format!("{} already exists", dest.display()),
+        });
+    }
+
+    match template {
+        Some(template) => {
+            let templates_dir = PxConfig::templates_dir()?;
+            Template::resolve(template, &config.templates, &templates_dir)?
+                .scaffold(&dest, name)?;
+        }
+        None => fs::create_dir(&dest).map_err(|e| FsError::PathAccess {
+            path: dest.clone(),
+            source: e,
+        })?,
+    }
+    template::init_repository(&dest)?;
+
+    let project = Project::from_git_repo(dest.clone())?;
+    index.projects.insert(dest.to_string_lossy().to_string(), project);
+    index.save()?;
+
+    match template {
+        Some(template) => println!("{}Created {} from {} at {}", done(), name, template, dest.display()),
+        None => println!("{}Created {} at {}", done(), name, dest.display()),
+    }
+    let in_scan_dir = config
+        .scan_dirs
+        .iter()
+        .any(|scan| scan.canonicalize().is_ok_and(|scan| dest.starts_with(scan)));
+    if !in_scan_dir {
+        println!("  Not under a scan directory, so `px sync` will drop it");
+    }
+    println!("Open it with `px open {}`", name);
+    Ok(())
+}
+
+/// Print projects with work left uncommitted or unpushed for `days`
+///
+/// Prints nothing when there's nothing to remind of, so it can run from a
+/// shell prompt hook. The git status check is the cached one `px list` uses,
+/// unless `refresh` re-reads every project.
+pub fn cmd_remind(index: &mut ProjectIndex, days: u32, refresh: bool) -> Result<()> {
+    if refresh {
+        let paths: Vec<String> = index.projects.keys().cloned().collect();
+        for path in &paths {
+            index.refresh_project(path);
+        }
+        index.save()?;
+    } else {
+        index.refresh_stale()?;
+    }
+
+    let now = chrono::Utc::now();
+    for project in index.reminders(Duration::days(days.into())) {
+        let git = &project.git_status;
+        let mut work = Vec::new();
+        if git.has_uncommitted {
+            work.push("uncommitted changes".to_string());
+        }
+        if git.ahead > 0 {
+            let plural = if git.ahead == 1 { "" } else { "s" };
+            work.push(format!("{} unpushed commit{}", git.ahead, plural));
+        }
+        println!(
+            "{}{} ({}): {}, last commit {} days ago",
+            plain::pick("⚠ ", ""),
+            project.name,
+            git.current_branch,
+            work.join(" and "),
+            (now - project.last_modified).num_days()
+        );
+    }
     Ok(())
 }
 
 /// Show detailed project information
-pub fn cmd_info(index: &ProjectIndex, query: &str) -> Result<()> {
+pub fn cmd_info(index: &mut ProjectIndex, query: &str, format: &str) -> Result<()> {
+    let json = is_json(format)?;
     let searcher = ProjectSearcher::new();
     let projects: Vec<_> = index.projects.values().cloned().collect();
     let results = searcher.search(&projects, query);
 
     if results.is_empty() {
+        if json {
+            return Err(no_match(query));
+        }
         println!("No projects found matching '{}'", query);
         return Ok(());
     }
 
-    let project = results[0];
+    // Never show a branch that has since been switched away from
+    let key = results[0].path.to_string_lossy().to_string();
+    if results[0].is_stale() {
+        index.refresh_project(&key);
+        index.save()?;
+    }
+    let Some(project) = index.projects.get(&key) else {
+        let message = format!("{} no longer exists; run `px sync`", results[0].path.display());
+        if json {
+            return Err(FsError::InvalidFormat { format: message });
+        }
+        println!("{}", message);
+        return Ok(());
+    };
+    if json {
+        return print_json(project);
+    }
 
     // Project header
     println!();
-    println!("📁 {}", project.name);
-    println!("{}", "=".repeat(60));
+    println!("{}{}", plain::pick("📁 ", "Project: "), project.name);
+    if !plain::is_enabled() {
+        println!("{}", "=".repeat(60));
+    }
 
     // Basic info
     println!("Path:     {}", project.path.display());
@@ -190,17 +728,20 @@ pub fn cmd_info(index: &ProjectIndex, query: &str) -> Result<()> {
 
     // Git status
     let status = if project.git_status.has_uncommitted {
-        "⚠️  Uncommitted changes"
+        plain::pick("⚠️  Uncommitted changes", "Uncommitted changes")
     } else {
-        "✓ Clean"
+        plain::pick("✓ Clean", "Clean")
     };
     println!("Status:   {}", status);
 
     // Ahead/behind
     if project.git_status.ahead > 0 || project.git_status.behind > 0 {
         println!(
-            "Sync:     ↑ {} ahead, ↓ {} behind",
-            project.git_status.ahead, project.git_status.behind
+            "Sync:     {}{} ahead, {}{} behind",
+            plain::pick("↑ ", ""),
+            project.git_status.ahead,
+            plain::pick("↓ ", ""),
+            project.git_status.behind
         );
     }
 
@@ -242,6 +783,168 @@ pub fn cmd_info(index: &ProjectIndex, query: &str) -> Result<()> {
     Ok(())
 }
 
+/// Summarize the index: languages, disk usage, activity and access recency
+///
+/// `with_sizes` walks every project; without it, languages only come from
+/// build files.
+pub fn cmd_stats(index: &ProjectIndex, format: &str, with_sizes: bool) -> Result<()> {
+    let json = is_json(format)?;
+    let stats = stats::collect(&index.sorted_projects(), with_sizes);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    if stats.projects.is_empty() {
+        println!("No projects indexed yet. Run `px sync` to scan for projects.");
+        return Ok(());
+    }
+
+    println!();
+    match stats.total_size {
+        Some(total) => println!(
+            "{}{} projects, {}",
+            plain::pick("📊 ", ""),
+            stats.projects.len(),
+            format_size_human(total)
+        ),
+        None => println!(
+            "{}{} projects",
+            plain::pick("📊 ", ""),
+            stats.projects.len()
+        ),
+    }
+    if !plain::is_enabled() {
+        println!("{}", "=".repeat(60));
+    }
+
+    println!();
+    println!("Languages:");
+    let unknown =
+        stats.projects.len() - stats.languages.iter().map(|l| l.projects).sum::<usize>();
+    for language in &stats.languages {
+        println!("  {:<16} {:>5}", language.language, language.projects);
+    }
+    if unknown > 0 {
+        println!("  {:<16} {:>5}", "(unknown)", unknown);
+    }
+
+    if stats.total_size.is_some() {
+        let mut by_size: Vec<_> = stats.projects.iter().collect();
+        by_size.sort_by_key(|p| std::cmp::Reverse(p.size));
+        println!();
+        println!("Largest:");
+        for project in by_size.iter().take(5) {
+            println!(
+                "  {:<30} {:>10}",
+                truncate(&project.name, 28),
+                format_size_human(project.size.unwrap_or(0))
+            );
+        }
+    }
+
+    for (title, paths) in [
+        ("Most active:", &stats.most_active),
+        ("Least active:", &stats.least_active),
+    ] {
+        if paths.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", title);
+        for project in paths.iter().filter_map(|path| stats.project(path)) {
+            let activity = match project.last_accessed {
+                Some(last) => format!(
+                    "{} opens, last {}",
+                    project.access_count,
+                    last.format("%Y-%m-%d")
+                ),
+                None => "never opened".to_string(),
+            };
+            println!("  {:<30} {}", truncate(&project.name, 28), activity);
+        }
+    }
+
+    let access = &stats.access;
+    println!();
+    println!("Last opened:");
+    for (label, count) in [
+        ("within a week", access.last_week),
+        ("within a month", access.last_month),
+        ("within 90 days", access.last_quarter),
+        ("longer ago", access.older),
+        ("never", access.never),
+    ] {
+        println!("  {:<16} {:>5}", label, count);
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Write the index, access history included, as JSON to a file or stdout
+pub fn cmd_export(index: &ProjectIndex, output: Option<&Path>) -> Result<()> {
+    let json = serde_json::to_string_pretty(&index.for_export())?;
+    match output {
+        Some(path) => {
+            fs::write(path, json + "\n").map_err(|e| FsError::IoError {
+                context: format!("Failed to write {}", path.display()),
+                source: e,
+            })?;
+            eprintln!(
+                "{}Exported {} projects to {}",
+                done(),
+                index.projects.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Merge an exported index (`-` for stdin) into this one
+pub fn cmd_import(index: &mut ProjectIndex, input: &Path) -> Result<()> {
+    let data = if input == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        fs::read_to_string(input).map_err(|e| FsError::IoError {
+            context: format!("Failed to read {}", input.display()),
+            source: e,
+        })?
+    };
+    let imported: ProjectIndex =
+        serde_json::from_str(&data).map_err(|e| FsError::InvalidFormat {
+            format: format!("Invalid px export {}: {}", input.display(), e),
+        })?;
+
+    let MergeSummary {
+        merged,
+        added,
+        skipped,
+        already_imported,
+    } = index.merge(imported);
+    if already_imported {
+        println!("Already imported {}; nothing changed", input.display());
+        return Ok(());
+    }
+    index.save()?;
+
+    println!(
+        "{}Merged history of {} projects, added {} new",
+        done(),
+        merged,
+        added
+    );
+    if !skipped.is_empty() {
+        println!("Skipped {} projects not found on this machine:", skipped.len());
+        for path in &skipped {
+            println!("  {}{}", plain::pick("• ", ""), path.display());
+        }
+    }
+    Ok(())
+}
+
 /// Helper to truncate strings with ellipsis
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
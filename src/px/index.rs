@@ -7,11 +7,39 @@ use crate::errors::{FsError, Result};
 use crate::fs::traverse::{walk_no_filter, TraverseConfig};
 use crate::models::EntryKind;
 use crate::px::project::Project;
-use chrono::{DateTime, Utc};
+use crate::px::remote;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Outcome of merging an exported index into this one
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Projects already indexed here whose access history was combined
+    pub merged: usize,
+    /// Projects new to this index, present on disk
+    pub added: usize,
+    /// Projects neither indexed here nor found on disk
+    pub skipped: Vec<PathBuf>,
+    /// The export was merged before, so nothing was
+    pub already_imported: bool,
+}
+
+/// A [`ProjectIndex`] as exported, with its export id
+#[derive(Debug, Serialize)]
+pub struct ExportedIndex<'a> {
+    #[serde(flatten)]
+    pub index: &'a ProjectIndex,
+    pub export_id: String,
+}
+
+/// The same remote over SSH or HTTPS compares equal
+fn repository_key(remote: &str) -> String {
+    remote::web_url(remote).unwrap_or_else(|| remote.trim().to_string())
+}
 
 /// Project index with caching and sync capabilities
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +53,15 @@ pub struct ProjectIndex {
 
     /// Schema version for future migrations
     pub version: u32,
+
+    /// Identifies an export, so importing it twice doesn't count its
+    /// history twice
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub export_id: Option<String>,
+
+    /// Ids of the exports merged into this index
+    #[serde(skip_serializing_if = "BTreeSet::is_empty", default)]
+    pub imported: BTreeSet<String>,
 }
 
 impl ProjectIndex {
@@ -34,6 +71,8 @@ impl ProjectIndex {
             projects: HashMap::new(),
             last_sync: Utc::now(),
             version: 1,
+            export_id: None,
+            imported: BTreeSet::new(),
         }
     }
 
@@ -53,11 +92,10 @@ impl ProjectIndex {
             source: e,
         })?;
 
-        let index: ProjectIndex = serde_json::from_str(&data).map_err(|e| {
-            FsError::InvalidFormat {
+        let index: ProjectIndex =
+            serde_json::from_str(&data).map_err(|e| FsError::InvalidFormat {
                 format: format!("Invalid cache JSON: {}", e),
-            }
-        })?;
+            })?;
 
         Ok(index)
     }
@@ -104,10 +142,7 @@ impl ProjectIndex {
         // Traverse each scan directory
         for scan_dir in scan_dirs {
             if !scan_dir.exists() {
-                eprintln!(
-                    "Warning: Scan directory does not exist: {}",
-                    scan_dir.display()
-                );
+                warn!(path = %scan_dir.display(), "Scan directory does not exist");
                 continue;
             }
 
@@ -117,8 +152,14 @@ impl ProjectIndex {
                 follow_symlinks: false,
                 include_hidden: false,
                 respect_gitignore: true,
-                threads: 4, // Parallel scan (feature enabled by default)
+                threads: 4,  // Parallel scan (feature enabled by default)
+                unordered: true,
                 quiet: true, // Suppress permission errors
+                skip_other_users: false,
+                exclude: Vec::new(),
+                type_detection: Default::default(),
+                #[cfg(feature = "cache")]
+                cache: None,
             };
 
             // Use existing fexplorer traverse infrastructure
@@ -143,10 +184,10 @@ impl ProjectIndex {
                         }
                         Err(e) => {
                             // Log error but continue indexing
-                            eprintln!(
-                                "Warning: Failed to index {}: {}",
-                                entry.path.display(),
-                                e
+                            warn!(
+                                path = %entry.path.display(),
+                                error = %e,
+                                "Failed to index project"
                             );
                         }
                     }
@@ -164,6 +205,70 @@ impl ProjectIndex {
         Ok(count)
     }
 
+    /// Merge the access history of an exported index
+    ///
+    /// Projects are matched by path, or by `origin` remote when the path
+    /// differs (a new home directory) and only one project here has that
+    /// remote. Access counts add up and the latest access wins. Unmatched
+    /// projects are added when their path exists, with git status read on
+    /// next access. An export that was merged before is left out.
+    pub fn merge(&mut self, other: ProjectIndex) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        if let Some(id) = other.export_id {
+            if !self.imported.insert(id) {
+                summary.already_imported = true;
+                return summary;
+            }
+        }
+
+        for (key, theirs) in other.projects {
+            let target = if self.projects.contains_key(&key) {
+                Some(key.clone())
+            } else {
+                let origin = theirs.origin.as_deref().map(repository_key);
+                let mut same_origin = self.projects.iter().filter(|(_, ours)| {
+                    origin.is_some() && ours.origin.as_deref().map(repository_key) == origin
+                });
+                match (same_origin.next(), same_origin.next()) {
+                    (Some((path, _)), None) => Some(path.clone()),
+                    _ => None,
+                }
+            };
+
+            match target.and_then(|path| self.projects.get_mut(&path)) {
+                Some(ours) => {
+                    ours.access_count += theirs.access_count;
+                    ours.last_accessed = ours.last_accessed.max(theirs.last_accessed);
+                    ours.update_frecency_score();
+                    summary.merged += 1;
+                }
+                None if theirs.path.is_dir() => {
+                    let mut project = theirs;
+                    project.status_checked = None;
+                    project.update_frecency_score();
+                    self.projects.insert(key, project);
+                    summary.added += 1;
+                }
+                None => summary.skipped.push(theirs.path),
+            }
+        }
+        summary.skipped.sort();
+        summary
+    }
+
+    /// A copy to export, marked with a new id
+    pub fn for_export(&self) -> ExportedIndex<'_> {
+        let now = Utc::now();
+        ExportedIndex {
+            index: self,
+            export_id: format!(
+                "{:x}-{:x}",
+                now.timestamp_nanos_opt().unwrap_or_else(|| now.timestamp()),
+                std::process::id()
+            ),
+        }
+    }
+
     /// Record project access for frecency tracking
     ///
     /// Updates access_count, last_accessed, and recalculates frecency_score.
@@ -181,13 +286,104 @@ impl ProjectIndex {
         Ok(())
     }
 
+    /// Re-read git status of the projects whose repositories changed since
+    ///
+    /// Keeps listings current without a full sync. Saves when anything was
+    /// refreshed and returns how many projects were.
+    pub fn refresh_stale(&mut self) -> Result<usize> {
+        let stale: Vec<String> = self
+            .projects
+            .iter()
+            .filter(|(_, project)| project.is_stale())
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &stale {
+            self.refresh_project(path);
+        }
+        if !stale.is_empty() {
+            self.save()?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Re-read one project's git status, dropping it if it's gone
+    ///
+    /// Doesn't save. Returns whether the project is still indexed.
+    pub fn refresh_project(&mut self, project_path: &str) -> bool {
+        let Some(project) = self.projects.get_mut(project_path) else {
+            return false;
+        };
+        if !project.path.is_dir() {
+            self.projects.remove(project_path);
+            return false;
+        }
+        if let Err(e) = project.refresh_git_status() {
+            warn!(path = project_path, error = %e, "Failed to refresh project");
+        }
+        true
+    }
+
+    /// Projects with uncommitted changes or unpushed commits and no commit
+    /// for `idle`, longest forgotten first
+    pub fn reminders(&self, idle: Duration) -> Vec<&Project> {
+        let cutoff = Utc::now() - idle;
+        let mut projects: Vec<&Project> = self
+            .projects
+            .values()
+            .filter(|p| p.git_status.has_uncommitted || p.git_status.ahead > 0)
+            .filter(|p| p.last_modified < cutoff)
+            .collect();
+        projects.sort_by_key(|p| p.last_modified);
+        projects
+    }
+
+    /// Projects to refresh after changes to `paths`
+    ///
+    /// Returns `None` when the set of projects itself may have changed (a
+    /// repository appeared or moved), which takes a full sync. Changes inside
+    /// `.git` only count when they can move a branch: the index and object
+    /// store churn on every `git status`.
+    pub fn affected_by(&self, paths: &[PathBuf]) -> Option<BTreeSet<String>> {
+        let mut affected = BTreeSet::new();
+        for path in paths {
+            let project = self
+                .projects
+                .iter()
+                .filter(|(_, project)| path.starts_with(&project.path))
+                .max_by_key(|(_, project)| project.path.as_os_str().len());
+
+            let Some((key, project)) = project else {
+                // A new clone, or a repository moved in
+                let is_repo = path.file_name().is_some_and(|name| name == ".git")
+                    || path.join(".git").exists();
+                if is_repo {
+                    return None;
+                }
+                continue;
+            };
+
+            let relative = path.strip_prefix(&project.path).unwrap_or(Path::new(""));
+            let counts = match relative.strip_prefix(".git") {
+                // The repository itself went away or appeared
+                Ok(rest) if rest.as_os_str().is_empty() => return None,
+                Ok(rest) => {
+                    rest.starts_with("refs")
+                        || ["HEAD", "FETCH_HEAD", "packed-refs", "logs/HEAD"]
+                            .iter()
+                            .any(|name| rest == Path::new(name))
+                }
+                Err(_) => true,
+            };
+            if counts {
+                affected.insert(key.clone());
+            }
+        }
+        Some(affected)
+    }
+
     /// Get the cache file path (~/.cache/px/projects.json)
     fn cache_path() -> Result<PathBuf> {
-        let cache_dir = dirs::cache_dir().ok_or_else(|| FsError::InvalidFormat {
-            format: "Could not determine cache directory".to_string(),
-        })?;
-
-        Ok(cache_dir.join("px").join("projects.json"))
+        Ok(crate::px::paths::cache_dir()?.join("projects.json"))
     }
 
     /// Get projects as a sorted vector (by frecency)
@@ -234,26 +430,16 @@ mod tests {
         let test_project = Project::from_git_repo(PathBuf::from(".")).unwrap_or_else(|_| {
             // Fallback if current dir is not a git repo
             Project {
-                path: PathBuf::from("/test/path"),
                 name: "test-project".to_string(),
-                last_modified: Utc::now(),
-                git_status: crate::px::project::ProjectGitStatus {
-                    current_branch: "main".to_string(),
-                    has_uncommitted: false,
-                    ahead: 0,
-                    behind: 0,
-                    last_commit: None,
-                },
-                frecency_score: 0.0,
-                last_accessed: None,
-                access_count: 0,
                 readme_excerpt: Some("Test project".to_string()),
+                ..Project::test_at("/test/path")
             }
         });
 
-        index
-            .projects
-            .insert(test_project.path.to_string_lossy().to_string(), test_project);
+        index.projects.insert(
+            test_project.path.to_string_lossy().to_string(),
+            test_project,
+        );
 
         // Save
         let json = serde_json::to_string_pretty(&index).unwrap();
@@ -273,22 +459,7 @@ mod tests {
 
         // Create a test project
         let test_path = "/test/path";
-        let mut project = Project {
-            path: PathBuf::from(test_path),
-            name: "test".to_string(),
-            last_modified: Utc::now(),
-            git_status: crate::px::project::ProjectGitStatus {
-                current_branch: "main".to_string(),
-                has_uncommitted: false,
-                ahead: 0,
-                behind: 0,
-                last_commit: None,
-            },
-            frecency_score: 0.0,
-            last_accessed: None,
-            access_count: 0,
-            readme_excerpt: None,
-        };
+        let project = Project::test_at(test_path);
 
         index.projects.insert(test_path.to_string(), project);
 
@@ -302,5 +473,137 @@ mod tests {
         assert!(project.last_accessed.is_some());
         assert!(project.frecency_score > 0.0);
     }
-}
 
+    #[test]
+    fn test_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = |path: &Path, count: u32, days_ago: i64| {
+            let mut project = Project {
+                last_accessed: Some(Utc::now() - chrono::Duration::days(days_ago)),
+                access_count: count,
+                ..Project::test_at(path)
+            };
+            project.update_frecency_score();
+            (path.to_string_lossy().to_string(), project)
+        };
+
+        let here = temp_dir.path().join("home");
+        let with_origin = |(key, project): (String, Project), origin: &str| {
+            let project = Project {
+                origin: Some(origin.to_string()),
+                ..project
+            };
+            (key, project)
+        };
+        let mut ours = ProjectIndex::new();
+        ours.projects.extend([
+            project(&here.join("api"), 3, 10),
+            with_origin(
+                project(&here.join("web"), 1, 1),
+                "git@github.com:ana/web.git",
+            ),
+            with_origin(
+                project(&here.join("docs"), 1, 1),
+                "git@github.com:ana/docs.git",
+            ),
+        ]);
+        fs::create_dir_all(here.join("cli")).unwrap();
+
+        let old_home = PathBuf::from("/Users/old");
+        let mut theirs = ProjectIndex::new();
+        theirs.projects.extend([
+            project(&here.join("api"), 4, 2),
+            with_origin(
+                project(&old_home.join("web"), 5, 30),
+                "https://github.com/ana/web",
+            ),
+            project(&here.join("cli"), 2, 5),
+            project(&old_home.join("gone"), 9, 1),
+            // Same name, another repository
+            with_origin(
+                project(&old_home.join("docs"), 2, 1),
+                "git@github.com:bo/docs.git",
+            ),
+        ]);
+        theirs.export_id = Some("export-1".to_string());
+        let again = ProjectIndex {
+            projects: theirs.projects.clone(),
+            export_id: theirs.export_id.clone(),
+            ..ProjectIndex::new()
+        };
+
+        let summary = ours.merge(theirs);
+        assert_eq!(summary.merged, 2);
+        assert_eq!(summary.added, 1);
+        assert_eq!(
+            summary.skipped,
+            [old_home.join("docs"), old_home.join("gone")]
+        );
+
+        let api = &ours.projects[&here.join("api").to_string_lossy().to_string()];
+        assert_eq!(api.access_count, 7);
+        assert!(api.last_accessed.unwrap() > Utc::now() - chrono::Duration::days(3));
+        // Matched by remote, keeping its own more recent access
+        let web = &ours.projects[&here.join("web").to_string_lossy().to_string()];
+        assert_eq!(web.access_count, 6);
+        assert!(web.last_accessed.unwrap() > Utc::now() - chrono::Duration::days(2));
+        assert_eq!(ours.projects.len(), 4);
+
+        // Importing the same export again changes nothing
+        let summary = ours.merge(again);
+        assert!(summary.already_imported);
+        assert_eq!(summary.merged, 0);
+        let api = &ours.projects[&here.join("api").to_string_lossy().to_string()];
+        assert_eq!(api.access_count, 7);
+    }
+
+    #[test]
+    fn test_reminders() {
+        let project = |name: &str, uncommitted: bool, ahead: usize, days_idle: i64| {
+            let mut project = Project {
+                last_modified: Utc::now() - Duration::days(days_idle),
+                ..Project::test_at(PathBuf::from("/src").join(name))
+            };
+            project.git_status.has_uncommitted = uncommitted;
+            project.git_status.ahead = ahead;
+            (project.path.to_string_lossy().to_string(), project)
+        };
+
+        let mut index = ProjectIndex::new();
+        index.projects.extend([
+            project("dirty", true, 0, 10),
+            project("unpushed", false, 2, 30),
+            project("clean", false, 0, 60),
+            project("busy", true, 1, 1),
+        ]);
+
+        let names: Vec<&str> = index
+            .reminders(Duration::days(7))
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["unpushed", "dirty"]);
+        assert_eq!(index.reminders(Duration::zero()).len(), 3);
+    }
+
+    #[test]
+    fn test_affected_by() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        let mut index = ProjectIndex::new();
+        index.projects.insert(
+            repo.to_string_lossy().to_string(),
+            Project::test_at(repo.clone()),
+        );
+
+        let affected = |paths: &[PathBuf]| index.affected_by(paths).map(|set| set.len());
+        assert_eq!(affected(&[repo.join("src/main.rs")]), Some(1));
+        assert_eq!(affected(&[repo.join(".git/HEAD")]), Some(1));
+        assert_eq!(affected(&[repo.join(".git/refs/heads/main")]), Some(1));
+        assert_eq!(affected(&[repo.join(".git/index")]), Some(0));
+        assert_eq!(affected(&[repo.join(".git/objects/ab/cdef")]), Some(0));
+        assert_eq!(affected(&[temp_dir.path().join("notes.txt")]), Some(0));
+        assert_eq!(affected(&[temp_dir.path().join("clone/.git")]), None);
+        assert_eq!(affected(&[repo.join(".git")]), None);
+    }
+}
@@ -3,11 +3,20 @@
 //! This module provides fast project management and switching capabilities
 //! with fuzzy search, frecency tracking, and editor integration.
 
+pub mod cli;
 pub mod commands;
 pub mod frecency;
 pub mod index;
+pub mod paths;
+#[cfg(feature = "tui")]
+pub mod pick;
 pub mod project;
+pub mod remote;
 pub mod search;
+pub mod shell;
+pub mod stats;
+pub mod template;
+pub mod tmux;
 
 // Re-export main types for convenience
 pub use index::ProjectIndex;
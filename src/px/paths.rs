@@ -0,0 +1,121 @@
+//! Where px keeps its files
+//!
+//! `PX_CONFIG_DIR` and `PX_CACHE_DIR` name the directories outright, for
+//! setups that carry px's state around. Otherwise px follows the XDG base
+//! directory spec on every platform, macOS and Windows included:
+//! `$XDG_CONFIG_HOME/px` and `$XDG_CACHE_HOME/px`, by default `~/.config/px`
+//! and `~/.cache/px`.
+//!
+//! Earlier versions used the platform's own directories (`~/Library/...` on
+//! macOS); those are still read as long as nothing exists at the new place.
+
+use crate::errors::{FsError, Result};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Directory of `config.toml` and `templates/`
+pub fn config_dir() -> Result<PathBuf> {
+    locate(
+        "PX_CONFIG_DIR",
+        "XDG_CONFIG_HOME",
+        ".config",
+        dirs::config_dir(),
+    )
+}
+
+/// Directory of the project index
+pub fn cache_dir() -> Result<PathBuf> {
+    locate(
+        "PX_CACHE_DIR",
+        "XDG_CACHE_HOME",
+        ".cache",
+        dirs::cache_dir(),
+    )
+}
+
+fn locate(
+    override_var: &str,
+    xdg_var: &str,
+    default: &str,
+    platform_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| FsError::InvalidFormat {
+        format: "Could not determine home directory".to_string(),
+    })?;
+    Ok(resolve(
+        std::env::var_os(override_var),
+        std::env::var_os(xdg_var),
+        &home.join(default),
+        platform_dir.map(|dir| dir.join("px")),
+    ))
+}
+
+/// The override if set, else `<xdg or fallback>/px`, unless only the legacy
+/// platform directory exists
+fn resolve(
+    override_dir: Option<OsString>,
+    xdg_dir: Option<OsString>,
+    fallback: &Path,
+    legacy: Option<PathBuf>,
+) -> PathBuf {
+    if let Some(dir) = override_dir.filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    // The spec says to ignore relative paths
+    let base = xdg_dir
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| fallback.to_path_buf());
+    let dir = base.join("px");
+    match legacy {
+        Some(legacy) if !dir.exists() && legacy.exists() => legacy,
+        _ => dir,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let temp = tempfile::tempdir().unwrap();
+        let home_cache = temp.path().join(".cache");
+        let legacy = temp.path().join("Library/Caches/px");
+        let os = |path: &Path| Some(path.as_os_str().to_os_string());
+
+        assert_eq!(
+            resolve(None, None, &home_cache, None),
+            home_cache.join("px")
+        );
+        assert_eq!(
+            resolve(
+                os(Path::new("/portable/px")),
+                os(temp.path()),
+                &home_cache,
+                None
+            ),
+            PathBuf::from("/portable/px")
+        );
+        assert_eq!(
+            resolve(Some(OsString::new()), os(temp.path()), &home_cache, None),
+            temp.path().join("px")
+        );
+        assert_eq!(
+            resolve(None, os(Path::new("relative")), &home_cache, None),
+            home_cache.join("px")
+        );
+
+        // The old platform directory is used until the new one exists
+        std::fs::create_dir_all(&legacy).unwrap();
+        assert_eq!(
+            resolve(None, None, &home_cache, Some(legacy.clone())),
+            legacy
+        );
+        std::fs::create_dir_all(home_cache.join("px")).unwrap();
+        assert_eq!(
+            resolve(None, None, &home_cache, Some(legacy)),
+            home_cache.join("px")
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Interactive project picker for `px pick`
+//!
+//! Typing filters the projects live with [`ProjectSearcher`]; an empty query
+//! lists them by frecency. The selected project's git status and README are
+//! previewed beside the list.
+
+use crate::plain;
+use crate::px::project::Project;
+use crate::px::search::ProjectSearcher;
+use crate::tui::theme::Theme;
+use crate::tui::ui::{with_terminal, Backend};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::io;
+
+/// Query and matches of the picker
+pub struct Picker<'a> {
+    projects: &'a [Project],
+    searcher: ProjectSearcher,
+    query: String,
+    matches: Vec<&'a Project>,
+    selected: usize,
+}
+
+impl<'a> Picker<'a> {
+    pub fn new(projects: &'a [Project]) -> Self {
+        let searcher = ProjectSearcher::new();
+        let matches = searcher.search(projects, "");
+        Self {
+            projects,
+            searcher,
+            query: String::new(),
+            matches,
+            selected: 0,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Projects matching the query, best first
+    pub fn matches(&self) -> &[&'a Project] {
+        &self.matches
+    }
+
+    pub fn selected(&self) -> Option<&'a Project> {
+        self.matches.get(self.selected).copied()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.update();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.update();
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.update();
+    }
+
+    /// Move the selection by `delta` rows, stopping at either end
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Re-run the search; the selection goes back to the best match
+    fn update(&mut self) {
+        self.matches = self.searcher.search(self.projects, &self.query);
+        self.selected = 0;
+    }
+}
+
+/// Let the user pick one of `projects`; `None` when they cancel
+pub fn pick(projects: &[Project]) -> io::Result<Option<Project>> {
+    let mut picker = Picker::new(projects);
+    let theme = Theme::default();
+    with_terminal(|terminal| pick_loop(&mut picker, &theme, terminal))
+}
+
+fn pick_loop(
+    picker: &mut Picker,
+    theme: &Theme,
+    terminal: &mut Terminal<Backend>,
+) -> io::Result<Option<Project>> {
+    let mut list_state = ListState::default();
+    loop {
+        list_state.select(picker.selected().map(|_| picker.selected));
+        terminal.draw(|frame| draw(frame, picker, theme, &mut list_state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match handle_key(picker, key) {
+            Some(Outcome::Picked) => return Ok(picker.selected().cloned()),
+            Some(Outcome::Cancelled) => return Ok(None),
+            None => {}
+        }
+    }
+}
+
+enum Outcome {
+    Picked,
+    Cancelled,
+}
+
+fn handle_key(picker: &mut Picker, key: KeyEvent) -> Option<Outcome> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => return Some(Outcome::Cancelled),
+        KeyCode::Char('c') if ctrl => return Some(Outcome::Cancelled),
+        KeyCode::Enter if picker.selected().is_some() => return Some(Outcome::Picked),
+        KeyCode::Up => picker.move_selection(-1),
+        KeyCode::Down => picker.move_selection(1),
+        KeyCode::Char('p' | 'k') if ctrl => picker.move_selection(-1),
+        KeyCode::Char('n' | 'j') if ctrl => picker.move_selection(1),
+        KeyCode::PageUp => picker.move_selection(-10),
+        KeyCode::PageDown => picker.move_selection(10),
+        KeyCode::Char('u') if ctrl => picker.clear(),
+        KeyCode::Backspace => picker.pop(),
+        KeyCode::Char(c) if !ctrl => picker.push(c),
+        _ => {}
+    }
+    None
+}
+
+fn draw(frame: &mut Frame, picker: &Picker, theme: &Theme, list_state: &mut ListState) {
+    let [prompt, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let count = format!("  {}/{}", picker.matches().len(), picker.projects.len());
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", theme.accent),
+            Span::raw(picker.query()),
+            Span::styled(count, theme.dim),
+        ])),
+        prompt,
+    );
+    frame.set_cursor_position((
+        prompt.x + 2 + picker.query().chars().count() as u16,
+        prompt.y,
+    ));
+
+    let [list, preview] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(body);
+    let items: Vec<ListItem> = picker
+        .matches()
+        .iter()
+        .map(|project| {
+            ListItem::new(Line::from(vec![
+                Span::styled(project.name.as_str(), theme.dir),
+                Span::styled(
+                    format!("  {}", project.git_status.current_branch),
+                    theme.dim,
+                ),
+            ]))
+        })
+        .collect();
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Projects "))
+            .highlight_style(theme.selection),
+        list,
+        list_state,
+    );
+    draw_preview(frame, picker.selected(), theme, preview);
+
+    frame.render_widget(
+        Paragraph::new(plain::pick(
+            " Enter open  ↑/↓ move  Ctrl+U clear  Esc cancel",
+            " Enter open  Up/Down move  Ctrl+U clear  Esc cancel",
+        ))
+        .style(theme.footer),
+        footer,
+    );
+}
+
+fn draw_preview(frame: &mut Frame, project: Option<&Project>, theme: &Theme, area: Rect) {
+    let Some(project) = project else {
+        frame.render_widget(Block::default().borders(Borders::ALL), area);
+        return;
+    };
+
+    let git = &project.git_status;
+    let mut status = if git.has_uncommitted {
+        let text = plain::pick("⚠ uncommitted changes", "uncommitted changes");
+        vec![Span::styled(text, theme.mark)]
+    } else {
+        vec![Span::raw(plain::pick("✓ clean", "clean"))]
+    };
+    if plain::is_enabled() {
+        if git.ahead > 0 {
+            status.push(Span::raw(format!("  {} ahead", git.ahead)));
+        }
+        if git.behind > 0 {
+            status.push(Span::raw(format!("  {} behind", git.behind)));
+        }
+    } else {
+        if git.ahead > 0 {
+            status.push(Span::raw(format!("  ↑{}", git.ahead)));
+        }
+        if git.behind > 0 {
+            status.push(Span::raw(format!("  ↓{}", git.behind)));
+        }
+    }
+
+    let mut lines = vec![
+        Line::styled(project.path.display().to_string(), theme.dim),
+        Line::raw(""),
+        Line::from(vec![
+            Span::styled("Branch  ", theme.dim),
+            Span::raw(git.current_branch.as_str()),
+        ]),
+        Line::from([vec![Span::styled("Status  ", theme.dim)], status].concat()),
+    ];
+    if let Some(commit) = &git.last_commit {
+        lines.push(Line::raw(""));
+        lines.push(Line::from(vec![
+            Span::styled(commit.hash.as_str(), theme.key),
+            Span::raw(format!(" {}", commit.message)),
+        ]));
+        lines.push(Line::styled(
+            format!(
+                "by {} at {}",
+                commit.author,
+                commit.timestamp.format("%Y-%m-%d %H:%M")
+            ),
+            theme.dim,
+        ));
+    }
+    if let Some(readme) = &project.readme_excerpt {
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(readme.as_str()));
+    }
+    if let Some(last) = project.last_accessed {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!(
+                "Opened {} times, last {}",
+                project.access_count,
+                last.format("%Y-%m-%d %H:%M")
+            ),
+            theme.dim,
+        ));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", project.name)),
+        ),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(name: &str, frecency_score: f64) -> Project {
+        Project {
+            frecency_score,
+            ..Project::test_at(PathBuf::from("/home/ana/src").join(name))
+        }
+    }
+
+    #[test]
+    fn test_picker_filters_and_selects() {
+        let projects = [
+            project("website", 1.0),
+            project("api-server", 50.0),
+            project("cli-tools", 10.0),
+        ];
+        let mut picker = Picker::new(&projects);
+        let names = |picker: &Picker| -> Vec<String> {
+            picker.matches().iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(names(&picker), ["api-server", "cli-tools", "website"]);
+
+        picker.move_selection(5);
+        assert_eq!(picker.selected().unwrap().name, "website");
+        picker.move_selection(-1);
+        assert_eq!(picker.selected().unwrap().name, "cli-tools");
+
+        for c in "web".chars() {
+            picker.push(c);
+        }
+        assert_eq!(names(&picker), ["website"]);
+        assert_eq!(picker.selected().unwrap().name, "website");
+
+        picker.push('z');
+        assert!(picker.selected().is_none());
+        picker.clear();
+        assert_eq!(picker.matches().len(), 3);
+    }
+}
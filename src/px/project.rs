@@ -43,6 +43,19 @@ pub struct Project {
     /// First line of README (if exists)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub readme_excerpt: Option<String>,
+
+    /// When git_status was read; newer repository state makes it stale
+    #[serde(
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub status_checked: Option<DateTime<Utc>>,
+
+    /// URL of the `origin` remote, which identifies the project on other
+    /// machines
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub origin: Option<String>,
 }
 
 /// Git repository status information
@@ -105,9 +118,8 @@ impl Project {
         // Extract project name from directory name
         let name = path
             .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
 
         // Get git status information
         let git_status = Self::get_git_status(&path)?;
@@ -118,7 +130,7 @@ impl Project {
         // Extract README excerpt
         let readme_excerpt = Self::extract_readme_excerpt(&path);
 
-        Ok(Project {
+        let mut project = Project {
             path,
             name,
             last_modified,
@@ -127,7 +139,93 @@ impl Project {
             last_accessed: None,
             access_count: 0,
             readme_excerpt,
-        })
+            status_checked: Some(Utc::now()),
+            origin: None,
+        };
+        project.origin = project.origin_url().ok().flatten();
+        Ok(project)
+    }
+
+    /// Re-read git status, last modified time and README excerpt
+    pub fn refresh_git_status(&mut self) -> Result<()> {
+        self.git_status = Self::get_git_status(&self.path)?;
+        self.last_modified = Self::get_last_modified_time(&self.path, &self.git_status)?;
+        self.readme_excerpt = Self::extract_readme_excerpt(&self.path);
+        self.origin = self.origin_url().ok().flatten();
+        self.status_checked = Some(Utc::now());
+        Ok(())
+    }
+
+    /// URL of the `origin` remote, if there is one
+    pub fn origin_url(&self) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .current_dir(&self.path)
+            .output()
+            .map_err(|e| FsError::IoError {
+                context: "Failed to get git remote".to_string(),
+                source: e,
+            })?;
+
+        let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((output.status.success() && !url.is_empty()).then_some(url))
+    }
+
+    /// Whether the repository changed since git status was read
+    ///
+    /// Checkouts, commits and fetches move `HEAD` or a ref, and edits leave a
+    /// newer file or directory in the working tree; both are found with stats,
+    /// without running git. The working tree walk skips ignored files and
+    /// stops at the first change.
+    pub fn is_stale(&self) -> bool {
+        let Some(checked) = self.status_checked else {
+            return true;
+        };
+        let Some(git_dir) = Self::git_dir(&self.path) else {
+            return false;
+        };
+        // `status_checked` is stored in whole seconds, so a change in the
+        // second of the check counts as newer
+        let newer = |metadata: fs::Metadata| {
+            metadata.modified().is_ok_and(|modified| {
+                DateTime::<Utc>::from(modified).timestamp() >= checked.timestamp()
+            })
+        };
+
+        // Linked worktrees keep their refs in the main repository
+        let common_dir = fs::read_to_string(git_dir.join("commondir"))
+            .map_or_else(|_| git_dir.clone(), |dir| git_dir.join(dir.trim()));
+        let refs_moved = ["HEAD", "FETCH_HEAD", "ORIG_HEAD"]
+            .iter()
+            .map(|name| git_dir.join(name))
+            .chain([common_dir.join("packed-refs")])
+            .filter_map(|path| fs::symlink_metadata(path).ok())
+            .any(newer)
+            || ignore::WalkBuilder::new(common_dir.join("refs"))
+                .standard_filters(false)
+                .build()
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .any(newer);
+
+        refs_moved
+            || ignore::WalkBuilder::new(&self.path)
+                .hidden(false)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build()
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .any(newer)
+    }
+
+    /// The repository's git directory: `.git`, or where a `.git` file points
+    fn git_dir(repo_path: &Path) -> Option<PathBuf> {
+        let dot_git = repo_path.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        // Worktrees and submodules have `gitdir: <path>` in a file
+        let content = fs::read_to_string(&dot_git).ok()?;
+        let dir = content.strip_prefix("gitdir:")?.trim();
+        Some(repo_path.join(dir))
     }
 
     /// Get comprehensive git status for a repository
@@ -201,7 +299,7 @@ impl Project {
         match output {
             Ok(output) if output.status.success() => {
                 let counts = String::from_utf8_lossy(&output.stdout);
-                let parts: Vec<&str> = counts.trim().split_whitespace().collect();
+                let parts: Vec<&str> = counts.split_whitespace().collect();
 
                 if parts.len() == 2 {
                     let ahead = parts[0].parse().unwrap_or(0);
@@ -316,6 +414,35 @@ impl Project {
     }
 }
 
+#[cfg(test)]
+impl Project {
+    /// Project at `path`, named after its directory: clean, on `main` and
+    /// never opened
+    pub(crate) fn test_at(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: path
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().to_string()),
+            path,
+            last_modified: Utc::now(),
+            git_status: ProjectGitStatus {
+                current_branch: "main".to_string(),
+                has_uncommitted: false,
+                ahead: 0,
+                behind: 0,
+                last_commit: None,
+            },
+            frecency_score: 0.0,
+            last_accessed: None,
+            access_count: 0,
+            readme_excerpt: None,
+            status_checked: None,
+            origin: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -343,4 +470,49 @@ mod tests {
         let excerpt = Project::extract_readme_excerpt(temp_dir.path());
         assert_eq!(excerpt, Some("Title".to_string()));
     }
+
+    #[test]
+    fn test_is_stale() {
+        use filetime::{set_file_mtime, FileTime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        for sub in [".git/refs/heads", "src", "target"] {
+            fs::create_dir_all(repo.join(sub)).unwrap();
+        }
+        let files = [
+            (".git/HEAD", "ref: refs/heads/main\n"),
+            (".git/refs/heads/main", ""),
+            (".gitignore", "target/\n"),
+            ("src/lib.rs", ""),
+            ("target/out", ""),
+        ];
+        for (file, content) in files {
+            fs::write(repo.join(file), content).unwrap();
+        }
+        let age = |path: &str, secs: i64| {
+            set_file_mtime(repo.join(path), FileTime::from_unix_time(secs, 0)).unwrap()
+        };
+        let dirs = [".", ".git", ".git/refs", ".git/refs/heads", "src", "target"];
+        for path in dirs.into_iter().chain(files.map(|(file, _)| file)) {
+            age(path, 1_600_000_000);
+        }
+
+        let mut project = Project::test_at(repo);
+        assert!(project.is_stale(), "never checked");
+        project.status_checked = DateTime::from_timestamp(1_700_000_000, 0);
+        assert!(!project.is_stale());
+
+        // Ignored build output doesn't count, an edit does
+        age("target/out", 1_800_000_000);
+        age("target", 1_800_000_000);
+        assert!(!project.is_stale());
+        age("src/lib.rs", 1_800_000_000);
+        assert!(project.is_stale());
+        age("src/lib.rs", 1_600_000_000);
+
+        // A commit moves the branch
+        age(".git/refs/heads/main", 1_800_000_000);
+        assert!(project.is_stale());
+    }
 }
@@ -0,0 +1,119 @@
+//! Web URLs for git remotes
+//!
+//! Turns the SSH and HTTPS forms of a remote (`git@github.com:owner/repo.git`,
+//! `ssh://git@gitlab.com/group/sub/repo`, `https://user@bitbucket.org/team/repo.git`)
+//! into the repository's page, `https://<host>/<path>`. GitHub, GitLab and
+//! Bitbucket, hosted or self-hosted, all serve it there.
+
+/// Hosts that take SSH on port 443 under a different name than the website
+const SSH_ALIASES: &[(&str, &str)] = &[
+    ("ssh.github.com", "github.com"),
+    ("altssh.gitlab.com", "gitlab.com"),
+    ("altssh.bitbucket.org", "bitbucket.org"),
+];
+
+/// The browsable page of the repository at `remote`, if it's on a server
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+    let (scheme, host, path) = match remote.split_once("://") {
+        Some((scheme, rest)) => {
+            // The port of an SSH or git daemon remote isn't the website's;
+            // an HTTP one is
+            let (scheme, web_port) = match scheme {
+                "http" => ("http", true),
+                "https" => ("https", true),
+                "ssh" | "git" | "git+ssh" | "ssh+git" => ("https", false),
+                _ => return None,
+            };
+            let (authority, path) = rest.split_once('/')?;
+            // Drop `user@`, and `:port` unless it's the website's
+            let host = authority.rsplit('@').next()?;
+            let host = match host.rsplit_once(':') {
+                Some((name, port)) if !web_port && port.bytes().all(|b| b.is_ascii_digit()) => name,
+                _ => host,
+            };
+            (scheme, host, path)
+        }
+        // scp-like syntax, `[user@]host:path`; a slash first means a local path
+        None => {
+            let (authority, path) = remote.split_once(':')?;
+            if authority.contains(['/', '\\']) {
+                return None;
+            }
+            ("https", authority.rsplit('@').next()?, path)
+        }
+    };
+
+    // A single letter is a Windows drive, not a host
+    if host.len() < 2 {
+        return None;
+    }
+    let host = SSH_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(host))
+        .map_or(host, |(_, web)| web);
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if path.is_empty() {
+        return None;
+    }
+    Some(format!("{}://{}/{}", scheme, host, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_url() {
+        let cases = [
+            (
+                "git@github.com:owner/repo.git",
+                "https://github.com/owner/repo",
+            ),
+            (
+                "https://github.com/owner/repo.git",
+                "https://github.com/owner/repo",
+            ),
+            (
+                "ssh://git@ssh.github.com:443/owner/repo.git",
+                "https://github.com/owner/repo",
+            ),
+            (
+                "git@gitlab.com:group/sub/repo.git",
+                "https://gitlab.com/group/sub/repo",
+            ),
+            (
+                "ssh://git@gitlab.example.com:2222/group/repo.git",
+                "https://gitlab.example.com/group/repo",
+            ),
+            (
+                "https://ana@bitbucket.org/team/repo.git",
+                "https://bitbucket.org/team/repo",
+            ),
+            (
+                "http://git.internal/tools/repo/",
+                "http://git.internal/tools/repo",
+            ),
+            ("git://example.org/repo", "https://example.org/repo"),
+            ("git://example.org:9418/repo", "https://example.org/repo"),
+            (
+                "https://git.example.com:8443/team/repo.git",
+                "https://git.example.com:8443/team/repo",
+            ),
+        ];
+        for (remote, url) in cases {
+            assert_eq!(web_url(remote).as_deref(), Some(url), "{}", remote);
+        }
+
+        for remote in [
+            "/srv/git/repo.git",
+            "./repo:1",
+            "file:///srv/repo",
+            r"C:\repos\app",
+            "git@github.com:",
+        ] {
+            assert_eq!(web_url(remote), None, "{}", remote);
+        }
+    }
+}
@@ -64,7 +64,7 @@ impl ProjectSearcher {
             .collect();
 
         // Sort by combined score (highest first)
-        matches.sort_by(|a, b| b.1.cmp(&a.1));
+        matches.sort_by_key(|m| std::cmp::Reverse(m.1));
 
         matches.into_iter().map(|(project, _)| project).collect()
     }
@@ -116,26 +116,12 @@ impl Default for ProjectSearcher {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::px::project::{Project, ProjectGitStatus};
-    use chrono::Utc;
-    use std::path::PathBuf;
+    use crate::px::project::Project;
 
     fn create_test_project(name: &str, frecency: f64) -> Project {
         Project {
-            path: PathBuf::from(format!("/test/{}", name)),
-            name: name.to_string(),
-            last_modified: Utc::now(),
-            git_status: ProjectGitStatus {
-                current_branch: "main".to_string(),
-                has_uncommitted: false,
-                ahead: 0,
-                behind: 0,
-                last_commit: None,
-            },
             frecency_score: frecency,
-            last_accessed: None,
-            access_count: 0,
-            readme_excerpt: None,
+            ..Project::test_at(format!("/test/{}", name))
         }
     }
 
@@ -0,0 +1,71 @@
+//! Shell integration
+//!
+//! A program can't change its parent shell's directory, so `px cd` only
+//! prints the project's path. `px shell-init <shell>` emits a `px` wrapper
+//! function that runs `cd` on that output and passes everything else through.
+//! From `fexplorer projects shell-init`, the same `px` function wraps
+//! `fexplorer projects`, for installs without the px binary.
+
+use clap::ValueEnum;
+
+/// Shells `px shell-init` supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const POSIX_INIT: &str = r#"# px shell integration: `px cd <query>` changes directory
+px() {
+    if [ "$1" = cd ]; then
+        shift
+        local __px_dir
+        __px_dir="$(command {px} cd "$@")" && cd -- "$__px_dir"
+    else
+        command {px} "$@"
+    fi
+}
+"#;
+
+const FISH_INIT: &str = r#"# px shell integration: `px cd <query>` changes directory
+function px --wraps '{px}' --description 'px with cd support'
+    if test (count $argv) -gt 0; and test "$argv[1]" = cd
+        set -l __px_dir (command {px} cd $argv[2..-1]); and cd $__px_dir
+    else
+        command {px} $argv
+    end
+end
+"#;
+
+/// The wrapper function for `shell` around `program` (`px` or
+/// `fexplorer projects`), to be evaluated at startup
+pub fn init_script(shell: Shell, program: &str) -> String {
+    let script = match shell {
+        Shell::Bash | Shell::Zsh => POSIX_INIT,
+        Shell::Fish => FISH_INIT,
+    };
+    script.replace("{px}", program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_scripts_wrap_cd() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = init_script(shell, "px");
+            assert!(script.contains("command px cd"), "{:?}", shell);
+            assert!(script.contains("command px "), "{:?}", shell);
+
+            let script = init_script(shell, "fexplorer projects");
+            assert!(
+                script.contains("command fexplorer projects cd"),
+                "{:?}",
+                shell
+            );
+            assert!(!script.contains("command px"), "{:?}", shell);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Index statistics for `px stats`
+//!
+//! A project's language comes from the build files at its root (`Cargo.toml`,
+//! `go.mod`, ...), or else from the most common source language among its
+//! files. Disk usage walks each project, ignored build output included, so
+//! it can be skipped.
+
+use crate::fs::size::compute_dir_sizes;
+use crate::fs::traverse::{walk_no_filter, TraverseConfig};
+use crate::models::{Entry, EntryKind, FileCategory};
+use crate::px::project::Project;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Build files that give away a project's language, most telling first
+const MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("requirements.txt", "python"),
+    ("Gemfile", "ruby"),
+    ("mix.exs", "elixir"),
+    ("pom.xml", "java"),
+    ("build.gradle", "java"),
+    ("build.gradle.kts", "kotlin"),
+    ("Package.swift", "swift"),
+    ("composer.json", "php"),
+    ("CMakeLists.txt", "cpp"),
+    ("package.json", "javascript"),
+];
+
+/// Directories whose sources are someone else's
+const VENDORED: &[&str] = &[".git", "node_modules", "vendor", "target"];
+
+/// How many projects the most/least active lists hold
+const TOP: usize = 5;
+
+/// Summary of the project index
+#[derive(Debug, Serialize)]
+pub struct IndexStats {
+    pub projects: Vec<ProjectStats>,
+    /// Number of projects per language, most first
+    pub languages: Vec<LanguageCount>,
+    /// Disk usage of all projects, when measured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<u64>,
+    /// Paths of the projects with the highest frecency
+    pub most_active: Vec<PathBuf>,
+    /// Paths of the projects with the lowest frecency, least recently changed
+    /// first; none of the most active
+    pub least_active: Vec<PathBuf>,
+    pub access: AccessDistribution,
+}
+
+impl IndexStats {
+    /// The statistics of the project at `path`
+    pub fn project(&self, path: &Path) -> Option<&ProjectStats> {
+        self.projects.iter().find(|p| p.path == path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub projects: usize,
+}
+
+/// One project's line in the statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectStats {
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    pub access_count: u32,
+    #[serde(
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_accessed: Option<DateTime<Utc>>,
+}
+
+/// Projects by how long ago they were last opened through px
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct AccessDistribution {
+    pub last_week: usize,
+    pub last_month: usize,
+    pub last_quarter: usize,
+    pub older: usize,
+    pub never: usize,
+}
+
+impl AccessDistribution {
+    fn add(&mut self, last_accessed: Option<DateTime<Utc>>, now: DateTime<Utc>) {
+        let Some(last) = last_accessed else {
+            self.never += 1;
+            return;
+        };
+        let age = now - last;
+        if age <= Duration::days(7) {
+            self.last_week += 1;
+        } else if age <= Duration::days(30) {
+            self.last_month += 1;
+        } else if age <= Duration::days(90) {
+            self.last_quarter += 1;
+        } else {
+            self.older += 1;
+        }
+    }
+}
+
+/// Gather statistics; `with_sizes` walks every project to measure it
+pub fn collect(projects: &[&Project], with_sizes: bool) -> IndexStats {
+    let now = Utc::now();
+    let mut access = AccessDistribution::default();
+    let mut languages: HashMap<String, usize> = HashMap::new();
+    let mut stats = Vec::with_capacity(projects.len());
+
+    for project in projects {
+        let entries = with_sizes.then(|| walk(&project.path));
+        let language = detect_language(&project.path, entries.as_deref());
+        let size = entries.as_deref().map(|entries| {
+            compute_dir_sizes(entries)
+                .get(&project.path)
+                .copied()
+                .unwrap_or(0)
+        });
+
+        if let Some(language) = &language {
+            *languages.entry(language.clone()).or_default() += 1;
+        }
+        access.add(project.last_accessed, now);
+        stats.push(ProjectStats {
+            name: project.name.clone(),
+            path: project.path.clone(),
+            language,
+            size,
+            access_count: project.access_count,
+            last_accessed: project.last_accessed,
+        });
+    }
+
+    let mut by_activity: Vec<&&Project> = projects.iter().collect();
+    by_activity.sort_by(|a, b| {
+        b.frecency_score
+            .total_cmp(&a.frecency_score)
+            .then(b.last_modified.cmp(&a.last_modified))
+    });
+    let most_active: Vec<PathBuf> = by_activity
+        .iter()
+        .filter(|p| p.frecency_score > 0.0)
+        .take(TOP)
+        .map(|p| p.path.clone())
+        .collect();
+    let least_active = by_activity[most_active.len()..]
+        .iter()
+        .rev()
+        .take(TOP)
+        .map(|p| p.path.clone())
+        .collect();
+
+    let mut languages: Vec<LanguageCount> = languages
+        .into_iter()
+        .map(|(language, projects)| LanguageCount { language, projects })
+        .collect();
+    languages.sort_by(|a, b| {
+        b.projects
+            .cmp(&a.projects)
+            .then_with(|| a.language.cmp(&b.language))
+    });
+
+    IndexStats {
+        total_size: with_sizes.then(|| stats.iter().filter_map(|p| p.size).sum()),
+        projects: stats,
+        languages,
+        most_active,
+        least_active,
+        access,
+    }
+}
+
+/// Everything under `path`, hidden and ignored files included
+fn walk(path: &Path) -> Vec<Entry> {
+    let config = TraverseConfig {
+        include_hidden: true,
+        respect_gitignore: false,
+        quiet: true,
+        ..TraverseConfig::default()
+    };
+    walk_no_filter(path, &config).unwrap_or_default()
+}
+
+/// Language by build file, or by the most common source language in `entries`
+pub fn detect_language(path: &Path, entries: Option<&[Entry]>) -> Option<String> {
+    if let Some((_, language)) = MARKERS.iter().find(|(file, _)| path.join(file).is_file()) {
+        return Some(language.to_string());
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries? {
+        let vendored = entry
+            .path
+            .strip_prefix(path)
+            .is_ok_and(|rel| rel.iter().any(|c| VENDORED.iter().any(|v| c == *v)));
+        if entry.kind != EntryKind::File || vendored {
+            continue;
+        }
+        let ext = entry.path.extension().unwrap_or_default().to_string_lossy();
+        if let FileCategory::Source { language } = FileCategory::from_extension(&ext) {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(language, _)| language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project(path: &Path, frecency_score: f64, days_since_access: Option<i64>) -> Project {
+        Project {
+            frecency_score,
+            last_accessed: days_since_access.map(|days| Utc::now() - Duration::days(days)),
+            access_count: days_since_access.map_or(0, |_| 1),
+            ..Project::test_at(path)
+        }
+    }
+
+    #[test]
+    fn test_collect() {
+        let dir = tempfile::tempdir().unwrap();
+        let cli = dir.path().join("cli");
+        fs::create_dir_all(cli.join("src")).unwrap();
+        fs::write(cli.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(cli.join("src/main.rs"), "fn main() {}\n").unwrap();
+
+        // No build file: the Python outnumbers the vendored JavaScript
+        let scripts = dir.path().join("scripts");
+        fs::create_dir_all(scripts.join("node_modules/dep")).unwrap();
+        fs::write(scripts.join("a.py"), "").unwrap();
+        fs::write(scripts.join("b.py"), "").unwrap();
+        for name in ["x.js", "y.js", "z.js"] {
+            fs::write(scripts.join("node_modules/dep").join(name), "").unwrap();
+        }
+
+        let notes = dir.path().join("notes");
+        fs::create_dir_all(&notes).unwrap();
+        fs::write(notes.join("todo.md"), "- [ ] stats\n").unwrap();
+
+        let projects = [
+            project(&cli, 80.0, Some(1)),
+            project(&scripts, 5.0, Some(45)),
+            project(&notes, 0.0, None),
+        ];
+        let refs: Vec<&Project> = projects.iter().collect();
+        let stats = collect(&refs, true);
+
+        let language = |i: usize| stats.projects[i].language.as_deref();
+        assert_eq!(language(0), Some("rust"));
+        assert_eq!(language(1), Some("python"));
+        assert_eq!(language(2), None);
+        let languages: Vec<(&str, usize)> = stats
+            .languages
+            .iter()
+            .map(|l| (l.language.as_str(), l.projects))
+            .collect();
+        assert_eq!(languages, [("python", 1), ("rust", 1)]);
+        assert_eq!(stats.projects[0].size, Some(23));
+        assert_eq!(stats.total_size, Some(23 + 12));
+        assert_eq!(stats.most_active, [cli.clone(), scripts.clone()]);
+        assert_eq!(stats.least_active, [notes]);
+        assert_eq!(
+            stats.access,
+            AccessDistribution {
+                last_week: 1,
+                last_quarter: 1,
+                never: 1,
+                ..Default::default()
+            }
+        );
+
+        let stats = collect(&refs, false);
+        assert_eq!(stats.total_size, None);
+        assert_eq!(stats.projects[1].language, None);
+        assert_eq!(stats.projects[0].language.as_deref(), Some("rust"));
+    }
+}
@@ -0,0 +1,348 @@
+//! Project templates for `px new`
+//!
+//! A template is a directory to copy or a git repository to clone, without
+//! its history. `[templates]` in the config names them; directories under
+//! `~/.config/px/templates/` are templates by their own name. As with
+//! `cargo generate`, `{{project-name}}` and `{{crate_name}}` in file names and
+//! contents become the new project's name.
+
+use crate::errors::{FsError, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+/// Files that configure a template rather than belong to the project
+const TEMPLATE_FILES: &[&str] = &["cargo-generate.toml"];
+
+/// Where a template comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Template {
+    Dir(PathBuf),
+    Git(String),
+}
+
+impl Template {
+    /// Look up `name` in `configured`, then in `templates_dir`; failing both,
+    /// `name` itself can be a git URL or a directory
+    pub fn resolve(
+        name: &str,
+        configured: &HashMap<String, String>,
+        templates_dir: &Path,
+    ) -> Result<Self> {
+        if let Some(source) = configured.get(name) {
+            return Ok(Self::from_source(source));
+        }
+        let dir = templates_dir.join(name);
+        if dir.is_dir() {
+            return Ok(Template::Dir(dir));
+        }
+        if is_git_url(name) || Path::new(name).is_dir() {
+            return Ok(Self::from_source(name));
+        }
+        Err(FsError::InvalidFormat {
+            format: format!(
+                "Unknown template '{}': add it under [templates] in the px config or to {}",
+                name,
+                templates_dir.display()
+            ),
+        })
+    }
+
+    /// A git URL, or a directory with `~` for the home directory
+    fn from_source(source: &str) -> Self {
+        if is_git_url(source) {
+            return Template::Git(source.to_string());
+        }
+        match source.strip_prefix("~/") {
+            Some(rest) => Template::Dir(dirs::home_dir().unwrap_or_default().join(rest)),
+            None => Template::Dir(PathBuf::from(source)),
+        }
+    }
+
+    /// Create `dest` from the template, filling in `project_name`
+    ///
+    /// `dest` must not exist; it's removed again if anything fails.
+    pub fn scaffold(&self, dest: &Path, project_name: &str) -> Result<()> {
+        let result = match self {
+            Template::Dir(source) => copy_tree(source, dest),
+            Template::Git(url) => clone(url, dest),
+        }
+        .and_then(|()| {
+            for name in TEMPLATE_FILES {
+                let _ = fs::remove_file(dest.join(name));
+            }
+            render_tree(dest, &placeholders(project_name))
+        });
+        if result.is_err() {
+            let _ = fs::remove_dir_all(dest);
+        }
+        result
+    }
+}
+
+/// `https://…`, `ssh://…`, `git@host:…` or anything ending in `.git`
+fn is_git_url(source: &str) -> bool {
+    source.contains("://") || source.starts_with("git@") || source.ends_with(".git")
+}
+
+/// Placeholders and their values, in both of Liquid's spacings
+fn placeholders(project_name: &str) -> Vec<(String, String)> {
+    let crate_name = project_name.replace('-', "_").to_lowercase();
+    [
+        ("project-name", project_name.to_string()),
+        ("crate_name", crate_name),
+    ]
+    .into_iter()
+    .flat_map(|(key, value)| {
+        [
+            (format!("{{{{{}}}}}", key), value.clone()),
+            (format!("{{{{ {} }}}}", key), value),
+        ]
+    })
+    .collect()
+}
+
+fn render(text: &str, placeholders: &[(String, String)]) -> String {
+    placeholders
+        .iter()
+        .fold(text.to_string(), |text, (placeholder, value)| {
+            text.replace(placeholder, value)
+        })
+}
+
+/// Fill in placeholders in the names and text files under `dir`
+///
+/// Symlinks are renamed like anything else but never followed: a template's
+/// link can point outside the project, or back at one of its parents.
+fn render_tree(dir: &Path, placeholders: &[(String, String)]) -> Result<()> {
+    for entry in fs::read_dir(dir).map_err(|e| access_error(dir, e))? {
+        let entry = entry.map_err(|e| access_error(dir, e))?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(|e| access_error(&path, e))?;
+        if file_type.is_dir() {
+            render_tree(&path, placeholders)?;
+        } else if file_type.is_file() {
+            if let Ok(text) = fs::read_to_string(&path) {
+                let rendered = render(&text, placeholders);
+                if rendered != text {
+                    fs::write(&path, rendered).map_err(|e| access_error(&path, e))?;
+                }
+            }
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let rendered = render(&name, placeholders);
+        if rendered != name {
+            let renamed = path.with_file_name(rendered);
+            fs::rename(&path, &renamed).map_err(|e| access_error(&path, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a directory tree, leaving out the template's own `.git`
+///
+/// Symlinks are copied as links, as `git clone` does, not followed.
+fn copy_tree(source: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir(dest).map_err(|e| access_error(dest, e))?;
+    for entry in fs::read_dir(source).map_err(|e| access_error(source, e))? {
+        let entry = entry.map_err(|e| access_error(source, e))?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let (from, to) = (entry.path(), dest.join(entry.file_name()));
+        let file_type = entry.file_type().map_err(|e| access_error(&from, e))?;
+        if file_type.is_dir() {
+            copy_tree(&from, &to)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&from, &to)?;
+        } else {
+            fs::copy(&from, &to).map_err(|e| access_error(&from, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, dest: &Path) -> Result<()> {
+    let target = fs::read_link(source).map_err(|e| access_error(source, e))?;
+    std::os::unix::fs::symlink(target, dest).map_err(|e| access_error(dest, e))
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, _dest: &Path) -> Result<()> {
+    warn!("Skipping symlink {} in the template", source.display());
+    Ok(())
+}
+
+/// Clone the latest commit of `url` and drop its history
+fn clone(url: &str, dest: &Path) -> Result<()> {
+    git(
+        Path::new("."),
+        &[
+            "clone",
+            "--quiet",
+            "--depth",
+            "1",
+            url,
+            &dest.to_string_lossy(),
+        ],
+    )?;
+    let git_dir = dest.join(".git");
+    fs::remove_dir_all(&git_dir).map_err(|e| access_error(&git_dir, e))
+}
+
+/// `git init` in `dir` and commit what's there, unless it's empty
+///
+/// A commit that fails (no `user.email` configured, say) only warns: the
+/// repository is there and the files are staged.
+pub fn init_repository(dir: &Path) -> Result<()> {
+    git(dir, &["init", "--quiet"])?;
+    let is_empty = fs::read_dir(dir)
+        .map_err(|e| access_error(dir, e))?
+        .flatten()
+        .all(|entry| entry.file_name() == ".git");
+    if is_empty {
+        return Ok(());
+    }
+    git(dir, &["add", "--all"])?;
+    if let Err(e) = git(dir, &["commit", "--quiet", "-m", "Initial commit"]) {
+        warn!(error = %e, "Initial commit failed");
+    }
+    Ok(())
+}
+
+/// Run git in `dir`, failing with its stderr
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| FsError::IoError {
+            context: "Failed to run git".to_string(),
+            source: e,
+        })?;
+    if !output.status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "git {}: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn access_error(path: &Path, source: std::io::Error) -> FsError {
+    FsError::PathAccess {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let templates_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(templates_dir.path().join("rust-cli")).unwrap();
+        let configured = HashMap::from([
+            (
+                "web".to_string(),
+                "git@github.com:me/web-template.git".to_string(),
+            ),
+            ("lib".to_string(), "/srv/templates/lib".to_string()),
+        ]);
+        let resolve = |name: &str| Template::resolve(name, &configured, templates_dir.path());
+
+        assert_eq!(
+            resolve("web").unwrap(),
+            Template::Git("git@github.com:me/web-template.git".to_string())
+        );
+        assert_eq!(
+            resolve("lib").unwrap(),
+            Template::Dir(PathBuf::from("/srv/templates/lib"))
+        );
+        assert_eq!(
+            resolve("rust-cli").unwrap(),
+            Template::Dir(templates_dir.path().join("rust-cli"))
+        );
+        assert!(matches!(
+            resolve("https://github.com/me/template").unwrap(),
+            Template::Git(_)
+        ));
+        assert!(resolve("nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_scaffold_from_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("template");
+        fs::create_dir_all(source.join("src/bin")).unwrap();
+        fs::create_dir_all(source.join(".git")).unwrap();
+        fs::write(
+            source.join("Cargo.toml"),
+            "[package]\nname = \"{{project-name}}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("src/main.rs"),
+            "use {{ crate_name }}::run;\nfn main() { run() }\n",
+        )
+        .unwrap();
+        fs::write(source.join("src/bin/{{project-name}}.rs"), "").unwrap();
+        fs::write(source.join("cargo-generate.toml"), "").unwrap();
+        fs::write(source.join("logo.bin"), [0xff, 0xfe, b'{', b'{']).unwrap();
+
+        let dest = temp.path().join("My-Tool");
+        Template::Dir(source).scaffold(&dest, "My-Tool").unwrap();
+
+        let read = |path: &str| fs::read_to_string(dest.join(path)).unwrap();
+        assert_eq!(read("Cargo.toml"), "[package]\nname = \"My-Tool\"\n");
+        assert!(read("src/main.rs").starts_with("use my_tool::run;"));
+        assert!(dest.join("src/bin/My-Tool.rs").exists());
+        assert!(!dest.join(".git").exists());
+        assert!(!dest.join("cargo-generate.toml").exists());
+        assert_eq!(fs::read(dest.join("logo.bin")).unwrap().len(), 4);
+
+        // A missing template leaves nothing behind
+        let failed = temp.path().join("failed");
+        assert!(Template::Dir(temp.path().join("missing"))
+            .scaffold(&failed, "failed")
+            .is_err());
+        assert!(!failed.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scaffold_keeps_symlinks() {
+        let temp = tempfile::tempdir().unwrap();
+        let outside = temp.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("{{project-name}}.txt"), "{{project-name}}").unwrap();
+        let source = temp.path().join("template");
+        fs::create_dir(&source).unwrap();
+        std::os::unix::fs::symlink(&outside, source.join("{{project-name}}-link")).unwrap();
+        std::os::unix::fs::symlink(".", source.join("loop")).unwrap();
+
+        let dest = temp.path().join("tool");
+        Template::Dir(source).scaffold(&dest, "tool").unwrap();
+
+        // Links are copied and renamed, but nothing is written through them
+        let link = dest.join("tool-link");
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_link(&link).unwrap(), outside);
+        assert!(fs::symlink_metadata(dest.join("loop"))
+            .unwrap()
+            .is_symlink());
+        assert_eq!(
+            fs::read_to_string(outside.join("{{project-name}}.txt")).unwrap(),
+            "{{project-name}}"
+        );
+    }
+}
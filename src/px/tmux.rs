@@ -0,0 +1,104 @@
+//! tmux sessions for projects
+//!
+//! `px tmux` and `px open` (with `terminal = "tmux"`) give each project a
+//! session named after it, started in its directory. An existing session is
+//! reused as it is; startup commands only run when the session is created.
+
+use crate::errors::{FsError, Result};
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Session name for a project; tmux doesn't allow `.` or `:` in names
+pub fn session_name(project_name: &str) -> String {
+    project_name
+        .chars()
+        .map(|c| if c == '.' || c == ':' { '_' } else { c })
+        .collect()
+}
+
+/// Create the session unless it exists, then switch to it (inside tmux) or
+/// attach to it if `attach`
+///
+/// The first startup command is typed into the first window, each further
+/// one into a new window; the first window is selected after. Without
+/// `attach` the session is left running in the background, so nothing but
+/// the caller writes to the terminal.
+pub fn open_session(name: &str, dir: &Path, startup: &[String], attach: bool) -> Result<()> {
+    // `=` matches the name exactly rather than as a prefix
+    let target = format!("={}", name);
+    let exists = tmux(&["has-session", "-t", &target])?.status.success();
+
+    if !exists {
+        let dir = dir.to_string_lossy();
+        run(&["new-session", "-d", "-s", name, "-c", &dir])?;
+        let window = format!("{}:", target);
+        for (i, command) in startup.iter().enumerate() {
+            if i > 0 {
+                run(&["new-window", "-t", &window, "-c", &dir])?;
+            }
+            run(&["send-keys", "-t", &window, command, "Enter"])?;
+        }
+        if startup.len() > 1 {
+            run(&["select-window", "-t", &format!("{}^", window)])?;
+        }
+    }
+    if !attach {
+        return Ok(());
+    }
+
+    let client = if std::env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach-session"
+    };
+    let status = Command::new("tmux")
+        .args([client, "-t", &target])
+        .status()
+        .map_err(spawn_error)?;
+    if !status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!("tmux {} -t {} failed", client, name),
+        });
+    }
+    Ok(())
+}
+
+fn tmux(args: &[&str]) -> Result<Output> {
+    Command::new("tmux")
+        .args(args)
+        .output()
+        .map_err(spawn_error)
+}
+
+/// Run a tmux command, failing with its stderr
+fn run(args: &[&str]) -> Result<()> {
+    let output = tmux(args)?;
+    if !output.status.success() {
+        return Err(FsError::InvalidFormat {
+            format: format!(
+                "tmux {}: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn spawn_error(e: std::io::Error) -> FsError {
+    FsError::IoError {
+        context: "Failed to run tmux (is it installed?)".to_string(),
+        source: e,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name() {
+        assert_eq!(session_name("rust_filesearch"), "rust_filesearch");
+        assert_eq!(session_name("example.com:api"), "example_com_api");
+    }
+}
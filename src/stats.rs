@@ -0,0 +1,186 @@
+//! Local, opt-in usage statistics
+//!
+//! When `preferences.record_stats` is enabled, every command run records its
+//! name and duration into `~/.cache/fexplorer/stats.json`. Nothing ever leaves
+//! the machine; `fexplorer stats self` just summarizes the file for the user.
+
+use crate::errors::{FsError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Aggregated usage statistics, keyed by command (e.g. `find`, `run:cleanup`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// When recording started
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub since: DateTime<Utc>,
+    /// Per-command counters
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandStats>,
+}
+
+/// Counters for a single command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandStats {
+    /// Number of runs
+    pub runs: u64,
+    /// Number of runs that returned an error
+    #[serde(default)]
+    pub failures: u64,
+    /// Total wall time across all runs
+    pub total_ms: u64,
+    /// Slowest run
+    pub max_ms: u64,
+    /// Last time the command was run
+    #[serde(
+        with = "chrono::serde::ts_seconds_option",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+impl CommandStats {
+    /// Average duration per run in milliseconds
+    pub fn avg_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.runs).unwrap_or(0)
+    }
+}
+
+impl UsageStats {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self {
+            since: Utc::now(),
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// Load statistics from the cache file, or start fresh if it's missing
+    pub fn load() -> Result<Self> {
+        let path = Self::stats_path()?;
+
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let data = fs::read_to_string(&path).map_err(|e| FsError::PathAccess {
+            path: path.clone(),
+            source: e,
+        })?;
+
+        serde_json::from_str(&data).map_err(|e| FsError::InvalidFormat {
+            format: format!("Invalid stats file {}: {}", path.display(), e),
+        })
+    }
+
+    /// Save statistics to the cache file
+    pub fn save(&self) -> Result<()> {
+        let path = Self::stats_path()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| FsError::PathAccess {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json).map_err(|e| FsError::PathAccess { path, source: e })
+    }
+
+    /// Delete all recorded statistics
+    pub fn reset() -> Result<()> {
+        let path = Self::stats_path()?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| FsError::PathAccess { path, source: e })?;
+        }
+        Ok(())
+    }
+
+    /// Record a single command run
+    pub fn record(&mut self, command: &str, elapsed: Duration, success: bool) {
+        let stats = self.commands.entry(command.to_string()).or_default();
+        let ms = elapsed.as_millis() as u64;
+
+        stats.runs += 1;
+        if !success {
+            stats.failures += 1;
+        }
+        stats.total_ms += ms;
+        stats.max_ms = stats.max_ms.max(ms);
+        stats.last_run = Some(Utc::now());
+    }
+
+    /// Commands ordered by run count (most used first)
+    pub fn by_usage(&self) -> Vec<(&String, &CommandStats)> {
+        let mut commands: Vec<_> = self.commands.iter().collect();
+        commands.sort_by(|a, b| b.1.runs.cmp(&a.1.runs).then_with(|| a.0.cmp(b.0)));
+        commands
+    }
+
+    /// Get the stats file path (~/.cache/fexplorer/stats.json)
+    pub fn stats_path() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir().ok_or_else(|| FsError::InvalidFormat {
+            format: "Could not determine cache directory".to_string(),
+        })?;
+
+        Ok(cache_dir.join("fexplorer").join("stats.json"))
+    }
+}
+
+impl Default for UsageStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Record a run in the stats file (best effort, errors are ignored)
+pub fn record_run(command: &str, elapsed: Duration, success: bool) {
+    if let Ok(mut stats) = UsageStats::load() {
+        stats.record(command, elapsed, success);
+        let _ = stats.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_accumulates() {
+        let mut stats = UsageStats::new();
+        stats.record("find", Duration::from_millis(100), true);
+        stats.record("find", Duration::from_millis(300), false);
+        stats.record("size", Duration::from_millis(50), true);
+
+        let find = &stats.commands["find"];
+        assert_eq!(find.runs, 2);
+        assert_eq!(find.failures, 1);
+        assert_eq!(find.total_ms, 400);
+        assert_eq!(find.max_ms, 300);
+        assert_eq!(find.avg_ms(), 200);
+        assert!(find.last_run.is_some());
+
+        let order: Vec<_> = stats
+            .by_usage()
+            .into_iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(order, vec!["find", "size"]);
+    }
+
+    #[test]
+    fn test_stats_roundtrip() {
+        let mut stats = UsageStats::new();
+        stats.record("run:cleanup", Duration::from_millis(12), true);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let loaded: UsageStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.commands["run:cleanup"].runs, 1);
+    }
+}
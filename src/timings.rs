@@ -0,0 +1,123 @@
+//! Lightweight per-phase timing (`--timings`)
+//!
+//! Subsystems wrap their work in [`phase`] guards (or call [`record`]
+//! directly); durations are accumulated per phase name in first-seen order.
+//! Collection is disabled unless [`enable`] was called, so the guards cost a
+//! single atomic load in normal runs.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Turn on timing collection for this process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether timing collection is enabled
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add `elapsed` to the running total for `phase`
+pub fn record(phase: &'static str, elapsed: Duration) {
+    if !is_enabled() {
+        return;
+    }
+
+    if let Ok(mut phases) = PHASES.lock() {
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+    }
+}
+
+/// Start timing a phase; the time is recorded when the guard is dropped
+pub fn phase(name: &'static str) -> PhaseGuard {
+    PhaseGuard {
+        name,
+        start: is_enabled().then(Instant::now),
+    }
+}
+
+/// Run `f`, adding its duration to `acc` when timing is enabled
+///
+/// Used for work interleaved with another phase (e.g. predicate checks
+/// during traversal) where a guard per call would be too coarse.
+pub fn measure<T>(acc: &mut Duration, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    *acc += start.elapsed();
+    result
+}
+
+/// Guard returned by [`phase`]
+pub struct PhaseGuard {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            record(self.name, start.elapsed());
+        }
+    }
+}
+
+/// Collected phase totals in first-seen order
+pub fn snapshot() -> Vec<(&'static str, Duration)> {
+    PHASES.lock().map(|p| p.clone()).unwrap_or_default()
+}
+
+/// Format phases as `walk: 1.20s, sort: 3.1ms, ...`
+pub fn format_report(phases: &[(&'static str, Duration)]) -> String {
+    phases
+        .iter()
+        .map(|(name, elapsed)| format!("{}: {}", name, format_duration(*elapsed)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_duration(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    if secs >= 1.0 {
+        format!("{:.2}s", secs)
+    } else if elapsed.as_millis() >= 1 {
+        format!("{:.1}ms", secs * 1000.0)
+    } else {
+        format!("{}µs", elapsed.as_micros())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let phases = vec![
+            ("walk", Duration::from_millis(1200)),
+            ("sort", Duration::from_micros(3100)),
+            ("filter", Duration::from_micros(42)),
+        ];
+        assert_eq!(
+            format_report(&phases),
+            "walk: 1.20s, sort: 3.1ms, filter: 42µs"
+        );
+    }
+
+    #[test]
+    fn test_measure_passes_through_result() {
+        let mut acc = Duration::ZERO;
+        assert_eq!(measure(&mut acc, || 42), 42);
+    }
+}
@@ -1,12 +1,77 @@
 #[cfg(feature = "tui")]
-use crate::errors::Result;
+use crate::config::{Config, ThemeName, TuiConfig};
 #[cfg(feature = "tui")]
-use crate::fs::traverse::{walk_no_filter, TraverseConfig};
+use crate::errors::{FsError, Result};
 #[cfg(feature = "tui")]
-use crate::models::{Entry, EntryKind};
+use crate::fs::filters::AndPredicate;
+#[cfg(feature = "tui")]
+use crate::fs::query::profile_predicates;
+#[cfg(feature = "tui")]
+use crate::fs::traverse::{walk, walk_no_filter, TraverseConfig};
+#[cfg(feature = "tui")]
+use crate::models::{Entry, EntryKind, SortKey};
+#[cfg(feature = "tui")]
+use crate::plain;
+#[cfg(feature = "tui")]
+use crate::tui::keys::Keymap;
+#[cfg(feature = "tui")]
+use crate::tui::ops::{self, Applied, Operation, Undo};
+#[cfg(feature = "tui")]
+use crate::tui::palette::{suggestions, Command, Suggestion};
+#[cfg(feature = "tui")]
+use crate::tui::path;
+#[cfg(feature = "tui")]
+use crate::tui::preview::Preview;
+#[cfg(feature = "tui")]
+use crate::tui::theme::Theme;
+#[cfg(feature = "tui")]
+use std::collections::BTreeSet;
 #[cfg(feature = "tui")]
 use std::path::PathBuf;
 
+#[cfg(feature = "tui")]
+/// What keys currently do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Navigation and commands
+    Normal,
+    /// Typing the filter
+    Filter,
+    /// Typing the target of a copy or move, or a new name
+    Prompt { operation: Operation, input: String },
+    /// Waiting for `y` to run an operation
+    Confirm {
+        operation: Operation,
+        sources: Vec<PathBuf>,
+        target: Option<PathBuf>,
+    },
+    /// Keybinding cheat sheet; any key closes it
+    Help,
+    /// Command palette, with the highlighted suggestion
+    Palette { input: String, selected: usize },
+    /// Typing a directory to jump to, with the last completion's matches
+    PathBar {
+        input: String,
+        candidates: Vec<String>,
+    },
+}
+
+#[cfg(feature = "tui")]
+/// A directory listing kept in a tab
+///
+/// The active tab lives in the [`App`] fields; its slot here is only filled
+/// in while another tab is active.
+#[derive(Debug, Clone, Default)]
+pub struct Tab {
+    pub path: PathBuf,
+    pub profile: Option<String>,
+    pub filter: String,
+    pub entries: Vec<Entry>,
+    pub filtered_entries: Vec<Entry>,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
 #[cfg(feature = "tui")]
 /// Application state for the TUI
 pub struct App {
@@ -17,82 +82,145 @@ pub struct App {
     pub filter: String,
     pub show_hidden: bool,
     pub dirs_first: bool,
+    pub sort: SortKey,
+    /// Saved profile whose results are listed instead of the directory
+    pub profile: Option<String>,
     pub scroll_offset: usize,
     pub should_quit: bool,
+    /// Preview of the selected entry, and the path it was built for
+    pub preview: Option<(PathBuf, Preview)>,
+    pub mode: Mode,
+    /// Entries marked for an operation, across directories
+    pub marked: BTreeSet<PathBuf>,
+    /// How to revert the last operation
+    pub last_undo: Option<Undo>,
+    /// Outcome of the last operation or command, shown in the footer
+    pub message: Option<String>,
+    /// Saved profiles and their descriptions, for the palette
+    pub profiles: Vec<(String, Option<String>)>,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    /// Open tabs, at least one
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    /// Tab shown in the other pane, and the one `Tab` switches to
+    pub alternate_tab: usize,
+    /// Two panes side by side instead of the listing and a preview
+    pub split: bool,
 }
 
 #[cfg(feature = "tui")]
 impl App {
-    pub fn new(path: PathBuf) -> Result<Self> {
-        let config = TraverseConfig {
-            max_depth: None,
-            follow_symlinks: false,
-            include_hidden: false,
-            respect_gitignore: true,
-            threads: 4,
-            quiet: true,
-        };
-
-        let entries = walk_no_filter(&path, &config)?;
-        let filtered_entries = entries.clone();
-
-        Ok(Self {
+    /// Start at `path` with the `[tui]` settings of the config
+    pub fn new(path: PathBuf, settings: &TuiConfig) -> Result<Self> {
+        let mut app = Self {
             path,
-            entries,
-            filtered_entries,
+            entries: Vec::new(),
+            filtered_entries: Vec::new(),
             selected_index: 0,
             filter: String::new(),
-            show_hidden: false,
-            dirs_first: true,
+            show_hidden: settings.show_hidden,
+            dirs_first: settings.dirs_first,
+            sort: settings.sort,
+            profile: None,
             scroll_offset: 0,
             should_quit: false,
-        })
+            preview: None,
+            mode: Mode::Normal,
+            marked: BTreeSet::new(),
+            last_undo: None,
+            message: None,
+            profiles: Vec::new(),
+            keymap: Keymap::from_config(settings)?,
+            // Plain output is colorless; mono still shows the selection
+            theme: Theme::new(if plain::is_enabled() {
+                ThemeName::Mono
+            } else {
+                settings.theme
+            }),
+            tabs: vec![Tab::default()],
+            active_tab: 0,
+            alternate_tab: 0,
+            split: false,
+        };
+        app.reload()?;
+        Ok(app)
     }
 
+    /// Re-read the children of the current directory, or rerun the profile
     pub fn reload(&mut self) -> Result<()> {
+        self.entries = self.list(&self.path, self.profile.as_deref())?;
+        self.preview = None;
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// Children of `path`, or the results of a profile run there
+    fn list(&self, path: &std::path::Path, profile: Option<&str>) -> Result<Vec<Entry>> {
         let config = TraverseConfig {
-            max_depth: None,
+            max_depth: if profile.is_some() { None } else { Some(1) },
             follow_symlinks: false,
             include_hidden: self.show_hidden,
             respect_gitignore: true,
             threads: 4,
+            unordered: false,
             quiet: true,
+            skip_other_users: false,
+            exclude: Vec::new(),
+            type_detection: Default::default(),
+            #[cfg(feature = "cache")]
+            cache: None,
         };
 
-        self.entries = walk_no_filter(&self.path, &config)?;
-        self.apply_filter();
-        Ok(())
+        let mut entries = match profile {
+            Some(name) => profile_entries(name, path, &config)?,
+            None => walk_no_filter(path, &config)?,
+        };
+        // The walk yields the directory itself first
+        entries.retain(|e| e.depth > 0);
+        Ok(entries)
     }
 
     pub fn apply_filter(&mut self) {
-        let filter_lower = self.filter.to_lowercase();
+        self.filtered_entries = self.filtered(&self.entries, &self.filter);
+
+        // Reset selection if needed
+        if self.selected_index >= self.filtered_entries.len() {
+            self.selected_index = self.filtered_entries.len().saturating_sub(1);
+        }
+    }
 
-        self.filtered_entries = if filter_lower.is_empty() {
-            self.entries.clone()
+    /// `entries` whose names contain `filter`, in display order
+    fn filtered(&self, entries: &[Entry], filter: &str) -> Vec<Entry> {
+        let filter_lower = filter.to_lowercase();
+        let mut filtered: Vec<Entry> = if filter_lower.is_empty() {
+            entries.to_vec()
         } else {
-            self.entries
+            entries
                 .iter()
                 .filter(|e| e.name.to_lowercase().contains(&filter_lower))
                 .cloned()
                 .collect()
         };
 
-        // Sort with dirs first if enabled
-        if self.dirs_first {
-            self.filtered_entries
-                .sort_by(|a, b| match (a.kind, b.kind) {
-                    (EntryKind::Dir, EntryKind::File) => std::cmp::Ordering::Less,
-                    (EntryKind::File, EntryKind::Dir) => std::cmp::Ordering::Greater,
-                    _ => a.name.cmp(&b.name),
-                });
-        } else {
-            self.filtered_entries.sort_by(|a, b| a.name.cmp(&b.name));
-        }
-
-        // Reset selection if needed
-        if self.selected_index >= self.filtered_entries.len() {
-            self.selected_index = self.filtered_entries.len().saturating_sub(1);
-        }
+        // Sort with dirs first if enabled, then by the sort key
+        let (dirs_first, sort) = (self.dirs_first, self.sort);
+        filtered.sort_by(|a, b| {
+            let is_dir = |e: &Entry| e.kind == EntryKind::Dir;
+            let group = if dirs_first {
+                is_dir(b).cmp(&is_dir(a))
+            } else {
+                std::cmp::Ordering::Equal
+            };
+            let key = match sort {
+                SortKey::Name => std::cmp::Ordering::Equal,
+                SortKey::Size => b.size.cmp(&a.size),
+                SortKey::Mtime => b.mtime.cmp(&a.mtime),
+                SortKey::Kind => kind_rank(a.kind).cmp(&kind_rank(b.kind)),
+            };
+            group.then(key).then_with(|| a.name.cmp(&b.name))
+        });
+        filtered
     }
 
     pub fn next(&mut self) {
@@ -135,11 +263,13 @@ impl App {
 
     pub fn toggle_hidden(&mut self) -> Result<()> {
         self.show_hidden = !self.show_hidden;
+        self.refresh_other_pane();
         self.reload()
     }
 
     pub fn toggle_dirs_first(&mut self) {
         self.dirs_first = !self.dirs_first;
+        self.refresh_other_pane();
         self.apply_filter();
     }
 
@@ -147,10 +277,27 @@ impl App {
         self.filtered_entries.get(self.selected_index)
     }
 
+    /// Rebuild the preview if the selection moved to another entry
+    pub fn refresh_preview(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            self.preview = None;
+            return;
+        };
+        if self
+            .preview
+            .as_ref()
+            .is_some_and(|(path, _)| *path == entry.path)
+        {
+            return;
+        }
+        self.preview = Some((entry.path.clone(), Preview::load(entry)));
+    }
+
     pub fn enter_selected(&mut self) -> Result<()> {
         if let Some(entry) = self.selected_entry() {
             if entry.kind == EntryKind::Dir {
                 self.path = entry.path.clone();
+                self.profile = None;
                 self.selected_index = 0;
                 self.scroll_offset = 0;
                 self.reload()?;
@@ -160,6 +307,12 @@ impl App {
     }
 
     pub fn go_up(&mut self) -> Result<()> {
+        // Leave profile results for the directory they were run in
+        if self.profile.take().is_some() {
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+            return self.reload();
+        }
         if let Some(parent) = self.path.parent() {
             self.path = parent.to_path_buf();
             self.selected_index = 0;
@@ -169,7 +322,490 @@ impl App {
         Ok(())
     }
 
+    pub fn toggle_mark(&mut self) {
+        if let Some(path) = self.selected_entry().map(|e| e.path.clone()) {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+            self.next();
+        }
+    }
+
+    /// Paths an operation applies to: the marked entries, or the selected one
+    pub fn targets(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            self.selected_entry()
+                .map(|e| vec![e.path.clone()])
+                .unwrap_or_default()
+        } else {
+            self.marked.iter().cloned().collect()
+        }
+    }
+
+    /// Ask for what `operation` needs: a target, a new name or confirmation
+    pub fn start_operation(&mut self, operation: Operation) {
+        let sources = self.targets();
+        if sources.is_empty() {
+            return;
+        }
+        self.message = None;
+        self.mode = match operation {
+            Operation::Delete => Mode::Confirm {
+                operation,
+                sources,
+                target: None,
+            },
+            // Into the other pane by default, like a two-pane commander
+            Operation::Copy | Operation::Move => Mode::Prompt {
+                operation,
+                input: format!(
+                    "{}{}",
+                    self.other_pane()
+                        .map_or(&self.path, |tab| &tab.path)
+                        .display(),
+                    std::path::MAIN_SEPARATOR
+                ),
+            },
+            Operation::Rename => match self.selected_entry() {
+                Some(entry) => Mode::Prompt {
+                    operation,
+                    input: entry.name.clone(),
+                },
+                None => return,
+            },
+        };
+    }
+
+    /// Finish typing a prompt: renames run, copies and moves ask to confirm
+    pub fn submit_prompt(&mut self) -> Result<()> {
+        let Mode::Prompt { operation, input } = std::mem::replace(&mut self.mode, Mode::Normal)
+        else {
+            return Ok(());
+        };
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        if operation == Operation::Rename {
+            let Some(entry) = self.selected_entry() else {
+                return Ok(());
+            };
+            let applied = ops::rename(&entry.path.clone(), &input);
+            return self.finish_operation(applied, 1);
+        }
+        // Relative targets are relative to the directory shown
+        self.mode = Mode::Confirm {
+            operation,
+            sources: self.targets(),
+            target: Some(self.path.join(input)),
+        };
+        Ok(())
+    }
+
+    /// Answer the confirmation dialog
+    pub fn confirm(&mut self, yes: bool) -> Result<()> {
+        let Mode::Confirm {
+            operation,
+            sources,
+            target,
+        } = std::mem::replace(&mut self.mode, Mode::Normal)
+        else {
+            return Ok(());
+        };
+        if !yes {
+            self.message = Some("Cancelled".to_string());
+            return Ok(());
+        }
+
+        let applied = match (operation, target) {
+            (Operation::Copy, Some(target)) => ops::copy(&sources, &target),
+            (Operation::Move, Some(target)) => ops::move_to(&sources, &target),
+            (Operation::Delete, _) => ops::delete(&sources),
+            _ => return Ok(()),
+        };
+        self.marked.clear();
+        self.finish_operation(applied, sources.len())
+    }
+
+    fn finish_operation(&mut self, applied: Applied, total: usize) -> Result<()> {
+        let Applied { undo, failed } = applied;
+        let message = format!("{} {} of {}", undo.operation.done(), undo.len(), total);
+        self.message = Some(with_first_failure(message, &failed));
+        if !undo.is_empty() {
+            self.last_undo = Some(undo);
+        }
+        self.refresh_other_pane();
+        self.reload()
+    }
+
+    /// Revert the last operation
+    pub fn undo(&mut self) -> Result<()> {
+        let Some(undo) = self.last_undo.take() else {
+            self.message = Some("Nothing to undo".to_string());
+            return Ok(());
+        };
+        let (operation, total) = (undo.operation, undo.len());
+        let failed = undo.undo();
+        let message = format!(
+            "Undid {}: {} of {} reverted",
+            operation.done().to_lowercase(),
+            total - failed.len(),
+            total
+        );
+        self.message = Some(with_first_failure(message, &failed));
+        self.refresh_other_pane();
+        self.reload()
+    }
+
+    /// Open a tab on the current directory and switch to it
+    pub fn new_tab(&mut self) -> Result<()> {
+        let tab = Tab {
+            path: self.path.clone(),
+            ..Tab::default()
+        };
+        self.tabs.push(tab);
+        self.switch_tab(self.tabs.len() - 1)
+    }
+
+    /// Switch to the tab at `index`; the one left becomes the alternate
+    pub fn switch_tab(&mut self, index: usize) -> Result<()> {
+        if index >= self.tabs.len() || index == self.active_tab {
+            return Ok(());
+        }
+        self.tabs[self.active_tab] = self.take_tab();
+        self.alternate_tab = self.active_tab;
+        self.load_tab(index)
+    }
+
+    /// Switch to the alternate tab: the other pane when split
+    pub fn switch_pane(&mut self) -> Result<()> {
+        self.switch_tab(self.alternate_tab)
+    }
+
+    /// Close the active tab, unless it is the last one
+    pub fn close_tab(&mut self) -> Result<()> {
+        if self.tabs.len() == 1 {
+            self.message = Some("Can't close the last tab".to_string());
+            return Ok(());
+        }
+        self.tabs.remove(self.active_tab);
+        let shift = |index: usize, removed: usize| {
+            if index > removed {
+                index - 1
+            } else {
+                index
+            }
+        };
+        let next = if self.alternate_tab == self.active_tab {
+            self.active_tab.min(self.tabs.len() - 1)
+        } else {
+            shift(self.alternate_tab, self.active_tab)
+        };
+        // Whatever tab is left next to it takes the other pane
+        self.alternate_tab = if next > 0 {
+            next - 1
+        } else {
+            (next + 1) % self.tabs.len()
+        };
+        if self.tabs.len() == 1 {
+            self.split = false;
+        }
+        self.load_tab(next)
+    }
+
+    /// Toggle two panes side by side, opening a second tab if there is none
+    pub fn toggle_split(&mut self) {
+        if self.tabs.len() == 1 {
+            self.tabs.push(Tab {
+                path: self.path.clone(),
+                ..Tab::default()
+            });
+            self.alternate_tab = 1;
+        }
+        self.split = !self.split;
+        self.refresh_other_pane();
+    }
+
+    /// The tab shown beside the active one, when split
+    pub fn other_pane(&self) -> Option<&Tab> {
+        if self.split && self.alternate_tab != self.active_tab {
+            self.tabs.get(self.alternate_tab)
+        } else {
+            None
+        }
+    }
+
+    /// Path of each tab, in order
+    pub fn tab_paths(&self) -> Vec<&std::path::Path> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                if i == self.active_tab {
+                    self.path.as_path()
+                } else {
+                    tab.path.as_path()
+                }
+            })
+            .collect()
+    }
+
+    /// Move the active tab's state out of the fields
+    fn take_tab(&mut self) -> Tab {
+        Tab {
+            path: self.path.clone(),
+            profile: self.profile.take(),
+            filter: std::mem::take(&mut self.filter),
+            entries: std::mem::take(&mut self.entries),
+            filtered_entries: std::mem::take(&mut self.filtered_entries),
+            selected_index: self.selected_index,
+            scroll_offset: self.scroll_offset,
+        }
+    }
+
+    /// Make the tab at `index` active, re-reading its directory
+    fn load_tab(&mut self, index: usize) -> Result<()> {
+        let tab = std::mem::take(&mut self.tabs[index]);
+        self.active_tab = index;
+        self.path = tab.path;
+        self.profile = tab.profile;
+        self.filter = tab.filter;
+        self.selected_index = tab.selected_index;
+        self.scroll_offset = tab.scroll_offset;
+        self.reload()
+    }
+
+    /// Re-read the other pane, which an operation may have changed
+    fn refresh_other_pane(&mut self) {
+        if self.other_pane().is_none() {
+            return;
+        }
+        let tab = &self.tabs[self.alternate_tab];
+        let Ok(entries) = self.list(&tab.path, tab.profile.as_deref()) else {
+            return;
+        };
+        let filtered = self.filtered(&entries, &tab.filter);
+        let tab = &mut self.tabs[self.alternate_tab];
+        tab.selected_index = tab.selected_index.min(filtered.len().saturating_sub(1));
+        tab.entries = entries;
+        tab.filtered_entries = filtered;
+    }
+
+    /// Open the command palette
+    pub fn open_palette(&mut self) {
+        // Profiles may have been saved since the TUI started
+        if let Ok(config) = Config::load() {
+            self.profiles = config
+                .profile_names()
+                .into_iter()
+                .map(|name| {
+                    let description = config
+                        .get_profile(&name)
+                        .and_then(|p| p.description.clone());
+                    (name, description)
+                })
+                .collect();
+        }
+        self.message = None;
+        self.mode = Mode::Palette {
+            input: String::new(),
+            selected: 0,
+        };
+    }
+
+    /// Palette lines matching what has been typed
+    pub fn palette_suggestions(&self) -> Vec<Suggestion> {
+        match &self.mode {
+            Mode::Palette { input, .. } => suggestions(input, &self.profiles),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Run the highlighted palette line, or the typed one if nothing matches
+    pub fn submit_palette(&mut self) -> Result<()> {
+        let suggestions = self.palette_suggestions();
+        let Mode::Palette { input, selected } = std::mem::replace(&mut self.mode, Mode::Normal)
+        else {
+            return Ok(());
+        };
+        let line = suggestions
+            .get(selected)
+            .map_or(input.as_str(), |s| s.text.as_str());
+        match Command::parse(line) {
+            Some(command) => self.run_command(command),
+            // A command picked before typing its argument
+            None if line.ends_with(' ') => {
+                self.mode = Mode::Palette {
+                    input: line.to_string(),
+                    selected: 0,
+                };
+                Ok(())
+            }
+            None => {
+                self.message = Some(format!("Unknown command: {}", line.trim()));
+                Ok(())
+            }
+        }
+    }
+
+    pub fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Sort(key) => {
+                self.sort = key;
+                self.refresh_other_pane();
+                self.apply_filter();
+            }
+            Command::ToggleHidden => self.toggle_hidden()?,
+            Command::ToggleDirsFirst => self.toggle_dirs_first(),
+            Command::Profile(name) => {
+                let previous = self.profile.replace(name.clone());
+                if let Err(e) = self.reload() {
+                    self.profile = previous;
+                    self.message = Some(format!("Profile {}: {}", name, e));
+                    return Ok(());
+                }
+                self.selected_index = 0;
+                self.scroll_offset = 0;
+            }
+            Command::Cd(path) => self.jump_to(&path)?,
+            Command::Help => self.mode = Mode::Help,
+            Command::Quit => self.quit(),
+        }
+        Ok(())
+    }
+
+    /// Open the path bar on the current directory
+    pub fn open_path_bar(&mut self) {
+        self.message = None;
+        self.mode = Mode::PathBar {
+            input: format!("{}{}", self.path.display(), std::path::MAIN_SEPARATOR),
+            candidates: Vec::new(),
+        };
+    }
+
+    /// Complete the directory name being typed in the path bar
+    pub fn complete_path(&mut self) {
+        if let Mode::PathBar { input, candidates } = &mut self.mode {
+            let completion = path::complete(input, &self.path);
+            *input = completion.input;
+            *candidates = completion.candidates;
+        }
+    }
+
+    /// Jump to the directory typed in the path bar
+    pub fn submit_path_bar(&mut self) -> Result<()> {
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::PathBar { input, .. } if !input.trim().is_empty() => self.jump_to(input.trim()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Show the directory at `path` (`~` expanded, relative to the current one)
+    pub fn jump_to(&mut self, path: &str) -> Result<()> {
+        let target = path::expand(path, &self.path);
+        if !target.is_dir() {
+            self.message = Some(format!("Not a directory: {}", target.display()));
+            return Ok(());
+        }
+        self.path = target;
+        self.profile = None;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.reload()
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
 }
+
+#[cfg(feature = "tui")]
+/// Entries under `root` matching a saved `find` or `list` profile
+fn profile_entries(
+    name: &str,
+    root: &std::path::Path,
+    config: &TraverseConfig,
+) -> Result<Vec<Entry>> {
+    let config_file = Config::load()?;
+    let profile = config_file
+        .get_profile(name)
+        .ok_or_else(|| FsError::InvalidFormat {
+            format: format!("Profile '{}' not found", name),
+        })?;
+    match profile.command.as_str() {
+        "find" => {
+            let predicate = AndPredicate::new(profile_predicates(
+                &profile.resolve_args(&[])?,
+                config.type_detection,
+            )?);
+            walk(root, config, Some(&predicate))
+        }
+        "list" => walk_no_filter(root, config),
+        command => Err(FsError::InvalidFormat {
+            format: format!(
+                "'{}' profiles can't be shown here, only find and list",
+                command
+            ),
+        }),
+    }
+}
+
+#[cfg(feature = "tui")]
+/// Order of kinds for `sort kind`
+fn kind_rank(kind: EntryKind) -> u8 {
+    match kind {
+        EntryKind::Dir => 0,
+        EntryKind::Symlink => 1,
+        EntryKind::File => 2,
+    }
+}
+
+#[cfg(feature = "tui")]
+/// `message`, plus the first path an operation failed on
+fn with_first_failure(mut message: String, failed: &[(PathBuf, String)]) -> String {
+    if let Some((path, error)) = failed.first() {
+        message.push_str(&format!(" | {}: {}", path.display(), error));
+    }
+    message
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_tabs_and_split() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/a.txt"), "a").unwrap();
+        let mut app = App::new(dir.path().to_path_buf(), &TuiConfig::default()).unwrap();
+
+        app.new_tab().unwrap();
+        app.enter_selected().unwrap();
+        assert_eq!((app.active_tab, app.alternate_tab), (1, 0));
+        assert_eq!(app.tab_paths(), [dir.path(), &dir.path().join("sub")]);
+
+        // The other pane is the tab left behind, and the default copy target
+        app.toggle_split();
+        assert_eq!(app.other_pane().unwrap().path, dir.path());
+        assert_eq!(app.other_pane().unwrap().filtered_entries.len(), 1);
+        app.start_operation(Operation::Copy);
+        let Mode::Prompt { input, .. } = &app.mode else {
+            panic!("expected a prompt");
+        };
+        assert!(input.starts_with(&dir.path().display().to_string()));
+        app.mode = Mode::Normal;
+
+        app.switch_pane().unwrap();
+        assert_eq!(app.path, dir.path());
+        assert_eq!(app.other_pane().unwrap().path, dir.path().join("sub"));
+
+        app.close_tab().unwrap();
+        assert_eq!(app.path, dir.path().join("sub"));
+        assert_eq!(app.tabs.len(), 1);
+        assert!(!app.split);
+        app.close_tab().unwrap();
+        assert_eq!(app.tabs.len(), 1);
+    }
+}
@@ -0,0 +1,385 @@
+//! Keybindings for the TUI's normal mode
+//!
+//! A [`Keymap`] starts from a preset (`vim` or `arrows`) and applies the
+//! `[tui.bindings]` of the config, which give each action its keys as
+//! specs like `x`, `ctrl+d`, `space` or `f5`, separated by commas.
+
+use crate::config::{KeyPreset, TuiConfig};
+use crate::errors::{FsError, Result};
+use crate::plain;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Something a key does in normal mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    Open,
+    Parent,
+    Filter,
+    ClearFilter,
+    ToggleHidden,
+    ToggleDirsFirst,
+    Mark,
+    Copy,
+    Move,
+    Rename,
+    Delete,
+    Undo,
+    NewTab,
+    CloseTab,
+    OtherTab,
+    Split,
+    PathBar,
+    Palette,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// Every action, in the order the help lists them
+    pub const ALL: [Action; 24] = [
+        Action::Down,
+        Action::Up,
+        Action::PageDown,
+        Action::PageUp,
+        Action::Open,
+        Action::Parent,
+        Action::Filter,
+        Action::ClearFilter,
+        Action::ToggleHidden,
+        Action::ToggleDirsFirst,
+        Action::Mark,
+        Action::Copy,
+        Action::Move,
+        Action::Rename,
+        Action::Delete,
+        Action::Undo,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::OtherTab,
+        Action::Split,
+        Action::PathBar,
+        Action::Palette,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Name used in `[tui.bindings]`
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::Open => "open",
+            Action::Parent => "parent",
+            Action::Filter => "filter",
+            Action::ClearFilter => "clear_filter",
+            Action::ToggleHidden => "toggle_hidden",
+            Action::ToggleDirsFirst => "toggle_dirs_first",
+            Action::Mark => "mark",
+            Action::Copy => "copy",
+            Action::Move => "move",
+            Action::Rename => "rename",
+            Action::Delete => "delete",
+            Action::Undo => "undo",
+            Action::NewTab => "new_tab",
+            Action::CloseTab => "close_tab",
+            Action::OtherTab => "other_tab",
+            Action::Split => "split",
+            Action::PathBar => "path_bar",
+            Action::Palette => "palette",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Down => "Move down",
+            Action::Up => "Move up",
+            Action::PageDown => "Move a page down",
+            Action::PageUp => "Move a page up",
+            Action::Open => "Open directory",
+            Action::Parent => "Go to parent / leave profile results",
+            Action::Filter => "Filter by name",
+            Action::ClearFilter => "Clear filter",
+            Action::ToggleHidden => "Toggle hidden files",
+            Action::ToggleDirsFirst => "Toggle dirs-first",
+            Action::Mark => "Mark / unmark",
+            Action::Copy => "Copy marked (or selected)",
+            Action::Move => "Move marked (or selected)",
+            Action::Rename => "Rename selected",
+            Action::Delete => "Move to the trash",
+            Action::Undo => "Undo the last operation",
+            Action::NewTab => "New tab here",
+            Action::CloseTab => "Close tab",
+            Action::OtherTab => "Other pane / previous tab",
+            Action::Split => "Toggle two panes side by side",
+            Action::PathBar => "Type a path to jump to (Tab completes)",
+            Action::Palette => "Command palette (sort, hidden, profile, cd)",
+            Action::Help => "This help",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// A key with its modifiers
+pub type Key = (KeyCode, KeyModifiers);
+
+/// Normal-mode keys and their actions
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Key, Action>,
+}
+
+impl Keymap {
+    /// The preset's keys, with the configured bindings applied
+    pub fn from_config(config: &TuiConfig) -> Result<Self> {
+        let mut keymap = Self::preset(config.keys);
+
+        // Sorted, so an error always names the same binding
+        let mut bindings: Vec<_> = config.bindings.iter().collect();
+        bindings.sort();
+        for (name, specs) in bindings {
+            let action = Action::ALL
+                .into_iter()
+                .find(|a| a.name() == name)
+                .ok_or_else(|| FsError::InvalidFormat {
+                    format: format!(
+                        "Unknown TUI action '{}' in [tui.bindings] (actions: {})",
+                        name,
+                        Action::ALL.map(Action::name).join(", ")
+                    ),
+                })?;
+            let keys = specs
+                .split(',')
+                .map(|spec| parse_key(spec.trim()))
+                .collect::<Result<Vec<_>>>()?;
+
+            keymap.bindings.retain(|_, bound| *bound != action);
+            for key in keys {
+                keymap.bindings.insert(key, action);
+            }
+        }
+        Ok(keymap)
+    }
+
+    fn preset(preset: KeyPreset) -> Self {
+        let plain = |c: char| (KeyCode::Char(c), KeyModifiers::NONE);
+        let ctrl = |c: char| (KeyCode::Char(c), KeyModifiers::CONTROL);
+        let key = |code: KeyCode| (code, KeyModifiers::NONE);
+
+        let mut bindings = HashMap::from([
+            (key(KeyCode::Down), Action::Down),
+            (key(KeyCode::Up), Action::Up),
+            (key(KeyCode::PageDown), Action::PageDown),
+            (key(KeyCode::PageUp), Action::PageUp),
+            (key(KeyCode::Enter), Action::Open),
+            (key(KeyCode::Right), Action::Open),
+            (key(KeyCode::Left), Action::Parent),
+            (key(KeyCode::Backspace), Action::Parent),
+            (plain('-'), Action::Parent),
+            (plain('/'), Action::Filter),
+            (ctrl('u'), Action::ClearFilter),
+            (ctrl('.'), Action::ToggleHidden),
+            (ctrl('d'), Action::ToggleDirsFirst),
+            (plain(' '), Action::Mark),
+            (plain('c'), Action::Copy),
+            (plain('m'), Action::Move),
+            (plain('r'), Action::Rename),
+            (plain('d'), Action::Delete),
+            (key(KeyCode::Delete), Action::Delete),
+            (plain('u'), Action::Undo),
+            (plain('t'), Action::NewTab),
+            (ctrl('w'), Action::CloseTab),
+            (key(KeyCode::Tab), Action::OtherTab),
+            (plain('s'), Action::Split),
+            (ctrl('l'), Action::PathBar),
+            (plain(':'), Action::Palette),
+            (plain('?'), Action::Help),
+            (plain('q'), Action::Quit),
+            (key(KeyCode::Esc), Action::Quit),
+        ]);
+        if preset == KeyPreset::Vim {
+            bindings.extend([
+                (plain('j'), Action::Down),
+                (plain('k'), Action::Up),
+                (plain('l'), Action::Open),
+                (plain('h'), Action::Parent),
+                (ctrl('f'), Action::PageDown),
+                (ctrl('b'), Action::PageUp),
+            ]);
+        }
+        Self { bindings }
+    }
+
+    /// The action bound to a key press
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        // Shifted characters (`?`, `:`) arrive with SHIFT on some terminals
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        self.bindings.get(&(event.code, modifiers)).copied()
+    }
+
+    /// The keys bound to `action`, formatted for the help
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key, _)| format_key(key))
+            .collect();
+        keys.sort_by_key(|k| (k.chars().count(), k.clone()));
+        keys
+    }
+}
+
+/// Parse a key spec such as `x`, `ctrl+d`, `space`, `del` or `f5`
+pub fn parse_key(spec: &str) -> Result<Key> {
+    let invalid = || FsError::InvalidFormat {
+        format: format!("Invalid key '{}' in [tui.bindings]", spec),
+    };
+
+    let mut parts: Vec<&str> = spec.split('+').collect();
+    // `ctrl++` and `+` bind the plus key itself
+    if spec.ends_with("++") || spec == "+" {
+        parts.truncate(parts.len().saturating_sub(2));
+        parts.push("+");
+    }
+    let name = parts.pop().filter(|n| !n.is_empty()).ok_or_else(invalid)?;
+
+    let mut modifiers = KeyModifiers::NONE;
+    for modifier in parts {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(invalid()),
+        };
+    }
+
+    let lower = name.to_lowercase();
+    let code = match lower.as_str() {
+        _ if name.chars().count() == 1 => KeyCode::Char(name.chars().next().unwrap_or(' ')),
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        f if f.starts_with('f') => KeyCode::F(f[1..].parse().map_err(|_| invalid())?),
+        _ => return Err(invalid()),
+    };
+    Ok((code, modifiers))
+}
+
+fn format_key((code, modifiers): &Key) -> String {
+    let name = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => plain::pick("⏎", "Enter").to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => plain::pick("⌫", "Backspace").to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Up => plain::pick("↑", "Up").to_string(),
+        KeyCode::Down => plain::pick("↓", "Down").to_string(),
+        KeyCode::Left => plain::pick("←", "Left").to_string(),
+        KeyCode::Right => plain::pick("→", "Right").to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    };
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("Ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("Alt+");
+    }
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("Shift+");
+    }
+    format!("{}{}", prefix, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("ctrl+d").unwrap(),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse_key("space").unwrap(),
+            (KeyCode::Char(' '), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("F5").unwrap(),
+            (KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("ctrl++").unwrap(),
+            (KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+        assert!(parse_key("hyper+x").is_err());
+        assert!(parse_key("fx").is_err());
+        assert!(parse_key("").is_err());
+    }
+
+    #[test]
+    fn test_presets_and_bindings() {
+        let j = press(KeyCode::Char('j'), KeyModifiers::NONE);
+        let vim = Keymap::from_config(&TuiConfig::default()).unwrap();
+        assert_eq!(vim.action(&j), Some(Action::Down));
+        assert_eq!(
+            vim.action(&press(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            Some(Action::Help)
+        );
+
+        let mut config = TuiConfig {
+            keys: KeyPreset::Arrows,
+            ..TuiConfig::default()
+        };
+        config
+            .bindings
+            .insert("delete".to_string(), "x, f8".to_string());
+        let arrows = Keymap::from_config(&config).unwrap();
+        assert_eq!(arrows.action(&j), None);
+        let x = press(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(arrows.action(&x), Some(Action::Delete));
+        // The preset's delete keys are replaced
+        let d = press(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(arrows.action(&d), None);
+        assert_eq!(arrows.keys_for(Action::Delete), ["x", "F8"]);
+
+        config
+            .bindings
+            .insert("explode".to_string(), "e".to_string());
+        assert!(Keymap::from_config(&config).is_err());
+    }
+}
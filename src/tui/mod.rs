@@ -1,7 +1,19 @@
 #[cfg(feature = "tui")]
 pub mod app;
 #[cfg(feature = "tui")]
+pub mod keys;
+#[cfg(feature = "tui")]
+pub mod ops;
+#[cfg(feature = "tui")]
+pub mod palette;
+#[cfg(feature = "tui")]
+pub mod path;
+#[cfg(feature = "tui")]
+pub mod preview;
+#[cfg(feature = "tui")]
+pub mod theme;
+#[cfg(feature = "tui")]
 pub mod ui;
 
 #[cfg(feature = "tui")]
-pub use app::App;
+pub use app::{App, Mode};
@@ -0,0 +1,316 @@
+//! File operations for the interactive mode
+//!
+//! Copy, move, rename and delete (to the trash) on a set of paths. Every
+//! operation returns an [`Undo`] record of what it did, so the TUI can
+//! revert the last one. Nothing is ever overwritten: a target that already
+//! exists fails that path and leaves it alone.
+
+use crate::fs::reflink::copy_file;
+use crate::fs::trash::{self, Trashed};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A file operation the TUI can run on the marked entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Copy,
+    Move,
+    Rename,
+    Delete,
+}
+
+impl Operation {
+    /// Past-tense verb for status messages
+    pub fn done(self) -> &'static str {
+        match self {
+            Operation::Copy => "Copied",
+            Operation::Move => "Moved",
+            Operation::Rename => "Renamed",
+            Operation::Delete => "Trashed",
+        }
+    }
+}
+
+/// One step that reverts part of an operation
+#[derive(Debug, Clone)]
+enum Step {
+    /// Remove a path the operation created
+    Remove(PathBuf),
+    /// Move a path back where it came from
+    Restore { from: PathBuf, to: PathBuf },
+    /// Take a path back out of the trash
+    Untrash(Trashed),
+}
+
+/// How to revert a completed operation
+#[derive(Debug, Clone)]
+pub struct Undo {
+    pub operation: Operation,
+    steps: Vec<Step>,
+}
+
+impl Undo {
+    fn new(operation: Operation) -> Self {
+        Self {
+            operation,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Number of paths the operation changed
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Revert the operation, most recent change first
+    ///
+    /// Returns the paths that couldn't be reverted with why.
+    pub fn undo(self) -> Vec<(PathBuf, String)> {
+        let mut failed = Vec::new();
+        for step in self.steps.into_iter().rev() {
+            let result = match &step {
+                Step::Remove(path) => remove_path(path),
+                Step::Restore { from, to } => move_path(from, to),
+                Step::Untrash(trashed) => trashed.restore(),
+            };
+            if let Err(e) = result {
+                let path = match step {
+                    Step::Remove(path) => path,
+                    Step::Restore { to, .. } => to,
+                    Step::Untrash(trashed) => trashed.original,
+                };
+                failed.push((path, e.to_string()));
+            }
+        }
+        failed
+    }
+}
+
+/// Result of an operation on several paths
+#[derive(Debug)]
+pub struct Applied {
+    pub undo: Undo,
+    /// Paths the operation failed on, with why
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Copy `sources` to `target`
+///
+/// See [`destination`] for where each source ends up.
+pub fn copy(sources: &[PathBuf], target: &Path) -> Applied {
+    apply(Operation::Copy, sources, |source| {
+        let dest = destination(source, target, sources.len())?;
+        copy_path(source, &dest)?;
+        Ok(Step::Remove(dest))
+    })
+}
+
+/// Move `sources` to `target`
+///
+/// See [`destination`] for where each source ends up.
+pub fn move_to(sources: &[PathBuf], target: &Path) -> Applied {
+    apply(Operation::Move, sources, |source| {
+        let dest = destination(source, target, sources.len())?;
+        move_path(source, &dest)?;
+        Ok(Step::Restore {
+            from: dest,
+            to: source.clone(),
+        })
+    })
+}
+
+/// Rename `source` within its directory
+pub fn rename(source: &Path, new_name: &str) -> Applied {
+    let sources = [source.to_path_buf()];
+    apply(Operation::Rename, &sources, |source| {
+        if new_name.is_empty() || new_name.contains(std::path::is_separator) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' is not a file name", new_name),
+            ));
+        }
+        let dest = source.with_file_name(new_name);
+        ensure_free(&dest)?;
+        move_path(source, &dest)?;
+        Ok(Step::Restore {
+            from: dest,
+            to: source.clone(),
+        })
+    })
+}
+
+/// Move `sources` to the platform trash
+pub fn delete(sources: &[PathBuf]) -> Applied {
+    apply(Operation::Delete, sources, |source| {
+        trash::trash(source).map(Step::Untrash)
+    })
+}
+
+fn apply(
+    operation: Operation,
+    sources: &[PathBuf],
+    mut run: impl FnMut(&PathBuf) -> io::Result<Step>,
+) -> Applied {
+    let mut undo = Undo::new(operation);
+    let mut failed = Vec::new();
+    for source in sources {
+        match run(source) {
+            Ok(step) => undo.steps.push(step),
+            Err(e) => failed.push((source.clone(), e.to_string())),
+        }
+    }
+    Applied { undo, failed }
+}
+
+/// Where `source` goes for a copy or move to `target`
+///
+/// Into `target` when it is a directory; to `target` itself when a single
+/// source is given and `target` doesn't exist yet.
+pub fn destination(source: &Path, target: &Path, sources: usize) -> io::Result<PathBuf> {
+    let dest = if target.is_dir() {
+        let name = source
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+        target.join(name)
+    } else if sources == 1 {
+        target.to_path_buf()
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", target.display()),
+        ));
+    };
+    if dest.starts_with(source) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "can't copy or move a directory into itself",
+        ));
+    }
+    ensure_free(&dest)?;
+    Ok(dest)
+}
+
+fn ensure_free(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", path.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Copy a file, symlink or directory tree
+fn copy_path(source: &Path, dest: &Path) -> io::Result<()> {
+    let metadata = source.symlink_metadata()?;
+    if metadata.is_symlink() {
+        copy_symlink(source, dest)
+    } else if metadata.is_dir() {
+        fs::create_dir(dest)?;
+        for child in fs::read_dir(source)? {
+            let child = child?;
+            copy_path(&child.path(), &dest.join(child.file_name()))?;
+        }
+        Ok(())
+    } else {
+        copy_file(source, dest).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, dest)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    fs::copy(source, dest).map(|_| ())
+}
+
+/// Rename, or copy and remove when `dest` is on another filesystem
+fn move_path(source: &Path, dest: &Path) -> io::Result<()> {
+    match fs::rename(source, dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(source, dest)?;
+            remove_path(source)
+        }
+        result => result,
+    }
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_and_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("b.txt"), "b").unwrap();
+        let target = dir.path().join("target");
+        fs::create_dir(&target).unwrap();
+
+        let sources = [src.clone(), dir.path().join("b.txt")];
+        let applied = copy(&sources, &target);
+        assert!(applied.failed.is_empty(), "{:?}", applied.failed);
+        assert_eq!(
+            fs::read_to_string(target.join("src/nested/a.txt")).unwrap(),
+            "a"
+        );
+
+        // Copying again would overwrite, so it fails and changes nothing
+        let again = copy(&sources, &target);
+        assert_eq!(again.failed.len(), 2);
+        assert!(again.undo.is_empty());
+
+        assert!(applied.undo.undo().is_empty());
+        assert!(!target.join("src").exists());
+        assert!(!target.join("b.txt").exists());
+        assert!(src.join("nested/a.txt").exists());
+    }
+
+    #[test]
+    fn test_move_rename_and_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, "a").unwrap();
+
+        // A single source may be moved to a new name
+        let moved = move_to(std::slice::from_ref(&file), &dir.path().join("b.txt"));
+        assert!(moved.failed.is_empty());
+        assert!(!file.exists());
+        assert!(moved.undo.undo().is_empty());
+        assert!(file.exists());
+
+        let renamed = rename(&file, "c.txt");
+        assert!(renamed.failed.is_empty());
+        assert!(dir.path().join("c.txt").exists());
+        assert_eq!(rename(&dir.path().join("c.txt"), "x/y").failed.len(), 1);
+        renamed.undo.undo();
+        assert!(file.exists());
+
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        assert_eq!(
+            move_to(std::slice::from_ref(&sub), &sub.join("inner"))
+                .failed
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! The `:` command palette
+//!
+//! Typing narrows a list of commands; `Enter` runs the highlighted one, or
+//! what was typed when it takes an argument (`cd ~/src`, `profile big`).
+
+use crate::models::SortKey;
+
+/// A palette command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Sort(SortKey),
+    ToggleHidden,
+    ToggleDirsFirst,
+    /// Show the results of a saved `find` or `list` profile
+    Profile(String),
+    /// Jump to a directory
+    Cd(String),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parse a command line such as `sort size` or `cd /tmp`
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        let (name, arg) = match input.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (input, ""),
+        };
+        let command = match (name, arg) {
+            ("sort", "name") => Command::Sort(SortKey::Name),
+            ("sort", "size") => Command::Sort(SortKey::Size),
+            ("sort", "mtime" | "modified") => Command::Sort(SortKey::Mtime),
+            ("sort", "kind") => Command::Sort(SortKey::Kind),
+            ("hidden", "") => Command::ToggleHidden,
+            ("dirs-first", "") => Command::ToggleDirsFirst,
+            ("profile", name) if !name.is_empty() => Command::Profile(name.to_string()),
+            ("cd", path) if !path.is_empty() => Command::Cd(path.to_string()),
+            ("help", "") => Command::Help,
+            ("quit", "") => Command::Quit,
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+/// A line of the palette: a command line and what it does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub text: String,
+    pub description: String,
+}
+
+impl Suggestion {
+    fn new(text: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            description: description.into(),
+        }
+    }
+}
+
+/// Commands matching `input`, given the saved profiles
+///
+/// Every word typed must appear in the command or its description.
+pub fn suggestions(input: &str, profiles: &[(String, Option<String>)]) -> Vec<Suggestion> {
+    let input = input.trim_start();
+    // A path being typed is its own suggestion
+    if let Some(path) = input.strip_prefix("cd ") {
+        return vec![Suggestion::new(
+            format!("cd {}", path.trim()),
+            "Jump to this directory",
+        )];
+    }
+
+    let mut all = vec![
+        Suggestion::new("sort name", "Sort by name"),
+        Suggestion::new("sort size", "Sort by size, largest first"),
+        Suggestion::new("sort mtime", "Sort by modification time, newest first"),
+        Suggestion::new("sort kind", "Sort by kind, then name"),
+        Suggestion::new("hidden", "Toggle hidden files"),
+        Suggestion::new("dirs-first", "Toggle listing directories first"),
+        Suggestion::new("cd ", "Jump to a path typed after `cd`"),
+    ];
+    for (name, description) in profiles {
+        let description = description
+            .clone()
+            .unwrap_or_else(|| "Saved profile".into());
+        all.push(Suggestion::new(format!("profile {}", name), description));
+    }
+    all.push(Suggestion::new("help", "Show the keybindings"));
+    all.push(Suggestion::new("quit", "Quit"));
+
+    let words: Vec<String> = input.split_whitespace().map(str::to_lowercase).collect();
+    all.retain(|s| {
+        let haystack = format!("{} {}", s.text, s.description).to_lowercase();
+        words.iter().all(|word| haystack.contains(word.as_str()))
+    });
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse("sort  size"),
+            Some(Command::Sort(SortKey::Size))
+        );
+        assert_eq!(
+            Command::parse("cd ~/my projects"),
+            Some(Command::Cd("~/my projects".to_string()))
+        );
+        assert_eq!(
+            Command::parse("profile big"),
+            Some(Command::Profile("big".to_string()))
+        );
+        assert_eq!(Command::parse("cd "), None);
+        assert_eq!(Command::parse("sort colour"), None);
+    }
+
+    #[test]
+    fn test_suggestions_narrow() {
+        let profiles = vec![("large-logs".to_string(), Some("Big log files".to_string()))];
+        let texts = |input: &str| -> Vec<String> {
+            suggestions(input, &profiles)
+                .into_iter()
+                .map(|s| s.text)
+                .collect()
+        };
+
+        assert_eq!(texts("sort si"), ["sort size"]);
+        assert_eq!(texts("log"), ["profile large-logs"]);
+        assert_eq!(texts("cd /tmp"), ["cd /tmp"]);
+        assert!(texts("").len() > 8);
+        assert!(texts("nothing matches").is_empty());
+    }
+}
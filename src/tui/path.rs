@@ -0,0 +1,123 @@
+//! The `Ctrl+L` path bar: `~` expansion, completion and breadcrumbs
+
+use std::fs;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+/// `input` as a path: `~` is the home directory, relative paths start at `base`
+pub fn expand(input: &str, base: &Path) -> PathBuf {
+    let path = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(std::path::is_separator) => {
+            dirs::home_dir()
+                .unwrap_or_default()
+                .join(rest.trim_start_matches(std::path::is_separator))
+        }
+        _ => PathBuf::from(input),
+    };
+    base.join(path)
+}
+
+/// Result of completing a path being typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The input extended as far as all candidates agree
+    pub input: String,
+    /// Names of the directories that still match
+    pub candidates: Vec<String>,
+}
+
+/// Complete the last component of `input` to a directory name
+///
+/// A single match gets a trailing separator, so completing again goes into
+/// it. Hidden directories only match once a `.` has been typed.
+pub fn complete(input: &str, base: &Path) -> Completion {
+    let split = input.rfind(std::path::is_separator).map_or(0, |i| i + 1);
+    let (dir, prefix) = input.split_at(split);
+
+    let mut candidates: Vec<String> = fs::read_dir(expand(dir, base))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| {
+            name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'))
+        })
+        .collect();
+    candidates.sort();
+
+    let completed = match candidates.as_slice() {
+        [] => prefix.to_string(),
+        [only] => format!("{}{}", only, MAIN_SEPARATOR),
+        [first, rest @ ..] => rest.iter().fold(first.clone(), |common, name| {
+            common
+                .chars()
+                .zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        }),
+    };
+    Completion {
+        input: format!("{}{}", dir, completed),
+        candidates,
+    }
+}
+
+/// The components of `path`, each with the directory it leads to
+///
+/// For `/home/ana` these are `/`, `home` and `ana`.
+pub fn breadcrumbs(path: &Path) -> Vec<(String, PathBuf)> {
+    let mut crumbs: Vec<(String, PathBuf)> = path
+        .ancestors()
+        .map(|dir| {
+            let name = match dir.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => dir.display().to_string(),
+            };
+            (name, dir.to_path_buf())
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect();
+    crumbs.reverse();
+    crumbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["projects", "proposals", "photos", ".private"] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+        }
+        fs::write(dir.path().join("profile.txt"), "").unwrap();
+
+        let completion = complete("pro", dir.path());
+        assert_eq!(completion.input, "pro");
+        assert_eq!(completion.candidates, ["projects", "proposals"]);
+
+        let completion = complete("proj", dir.path());
+        assert_eq!(completion.input, format!("projects{}", MAIN_SEPARATOR));
+
+        assert_eq!(complete("", dir.path()).candidates.len(), 3);
+        assert_eq!(complete(".", dir.path()).candidates, [".private"]);
+
+        let absolute = format!("{}/ph", dir.path().display());
+        assert_eq!(
+            complete(&absolute, Path::new("/")).input,
+            format!("{}/photos{}", dir.path().display(), MAIN_SEPARATOR)
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let names: Vec<String> = breadcrumbs(Path::new("/home/ana/src"))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["/", "home", "ana", "src"]);
+        assert_eq!(breadcrumbs(Path::new("/home/ana"))[1].1, Path::new("/home"));
+    }
+}
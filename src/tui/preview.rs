@@ -0,0 +1,260 @@
+//! Preview pane contents for the selected entry
+//!
+//! Files show the head of their text, or their dimensions when they are
+//! images (PNG, GIF, JPEG, BMP, WebP); directories show a summary of their
+//! direct children. FIFOs, sockets and devices are never opened.
+
+use crate::models::{Entry, EntryKind};
+use crate::util::top_k_by;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Lines of a text file the preview shows at most
+pub const TEXT_PREVIEW_LINES: usize = 200;
+
+/// Bytes read from a file for its preview
+const PREVIEW_BYTES: usize = 64 * 1024;
+
+/// Largest children listed in a directory summary
+const LARGEST_CHILDREN: usize = 5;
+
+/// What the preview pane shows for an entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Preview {
+    /// The first lines of a text file; `truncated` if there is more
+    Text {
+        lines: Vec<String>,
+        truncated: bool,
+    },
+    Image {
+        format: &'static str,
+        width: u32,
+        height: u32,
+        size: u64,
+    },
+    Binary {
+        size: u64,
+    },
+    /// A FIFO, socket or device, which reading could block on
+    Special,
+    Dir(DirSummary),
+    Symlink {
+        target: PathBuf,
+    },
+    /// The entry couldn't be read
+    Unavailable(String),
+}
+
+/// Direct children of a directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirSummary {
+    pub files: usize,
+    pub dirs: usize,
+    pub others: usize,
+    pub hidden: usize,
+    /// Total size of the files (not recursive)
+    pub total_size: u64,
+    /// Largest files, largest first
+    pub largest: Vec<(String, u64)>,
+}
+
+impl Preview {
+    /// Build the preview of `entry`
+    pub fn load(entry: &Entry) -> Self {
+        let result = match entry.kind {
+            EntryKind::Dir => summarize_dir(&entry.path).map(Preview::Dir),
+            EntryKind::Symlink => {
+                fs::read_link(&entry.path).map(|target| Preview::Symlink { target })
+            }
+            EntryKind::File => preview_file(&entry.path, entry.size),
+        };
+        result.unwrap_or_else(|e| Preview::Unavailable(e.to_string()))
+    }
+}
+
+fn preview_file(path: &Path, size: u64) -> std::io::Result<Preview> {
+    if !fs::symlink_metadata(path)?.file_type().is_file() {
+        return Ok(Preview::Special);
+    }
+    let mut head = Vec::with_capacity(PREVIEW_BYTES.min(size as usize));
+    File::open(path)?
+        .take(PREVIEW_BYTES as u64)
+        .read_to_end(&mut head)?;
+
+    if let Some((format, width, height)) = image_dimensions(&head) {
+        return Ok(Preview::Image {
+            format,
+            width,
+            height,
+            size,
+        });
+    }
+    if head.contains(&0) {
+        return Ok(Preview::Binary { size });
+    }
+
+    let text = String::from_utf8_lossy(&head);
+    let mut lines: Vec<String> = text
+        .lines()
+        .take(TEXT_PREVIEW_LINES + 1)
+        .map(|line| line.replace('\t', "    "))
+        .collect();
+    let truncated = lines.len() > TEXT_PREVIEW_LINES || size > head.len() as u64;
+    lines.truncate(TEXT_PREVIEW_LINES);
+    Ok(Preview::Text { lines, truncated })
+}
+
+fn summarize_dir(path: &Path) -> std::io::Result<DirSummary> {
+    let mut summary = DirSummary::default();
+    let mut sizes = Vec::new();
+
+    for child in fs::read_dir(path)? {
+        let child = child?;
+        let name = child.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            summary.hidden += 1;
+        }
+        match child.file_type() {
+            Ok(kind) if kind.is_dir() => summary.dirs += 1,
+            Ok(kind) if kind.is_file() => {
+                summary.files += 1;
+                let size = child.metadata().map_or(0, |m| m.len());
+                summary.total_size += size;
+                sizes.push((name, size));
+            }
+            _ => summary.others += 1,
+        }
+    }
+
+    summary.largest = top_k_by(sizes, LARGEST_CHILDREN, |a, b| {
+        b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))
+    });
+    Ok(summary)
+}
+
+/// Format and pixel dimensions of an image, from the start of its file
+pub fn image_dimensions(head: &[u8]) -> Option<(&'static str, u32, u32)> {
+    let be16 = |at: usize| Some(u16::from_be_bytes(head.get(at..at + 2)?.try_into().ok()?) as u32);
+    let le16 = |at: usize| Some(u16::from_le_bytes(head.get(at..at + 2)?.try_into().ok()?) as u32);
+    let be32 = |at: usize| Some(u32::from_be_bytes(head.get(at..at + 4)?.try_into().ok()?));
+    let le32 = |at: usize| Some(u32::from_le_bytes(head.get(at..at + 4)?.try_into().ok()?));
+
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") && head.get(12..16) == Some(b"IHDR") {
+        return Some(("PNG", be32(16)?, be32(20)?));
+    }
+    if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        return Some(("GIF", le16(6)?, le16(8)?));
+    }
+    if head.starts_with(b"BM") && head.len() >= 26 {
+        // Negative heights mark top-down bitmaps
+        let height = (le32(22)? as i32).unsigned_abs();
+        return Some(("BMP", le32(18)?, height));
+    }
+    if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        let le24 = |at: usize| Some(le32(at)? & 0x00ff_ffff);
+        return match head.get(12..16)? {
+            b"VP8 " => Some(("WebP", le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = le32(21)?;
+                Some(("WebP", (bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => Some(("WebP", le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        };
+    }
+    if head.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments up to the start-of-frame marker
+        let mut at = 2;
+        while *head.get(at)? == 0xff {
+            let marker = *head.get(at + 1)?;
+            let is_frame = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_frame {
+                return Some(("JPEG", be16(at + 7)?, be16(at + 5)?));
+            }
+            at += 2 + be16(at + 2)? as usize;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::metadata::extract_entry;
+
+    #[test]
+    fn test_image_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some(("PNG", 640, 480)));
+
+        let gif = b"GIF89a\x20\x03\x58\x02";
+        assert_eq!(image_dimensions(gif), Some(("GIF", 800, 600)));
+
+        // SOI, an APP0 segment, then a baseline frame header
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x01,
+            0x2c, 0x01, 0x90,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some(("JPEG", 400, 300)));
+
+        assert_eq!(image_dimensions(b"plain text"), None);
+        assert_eq!(image_dimensions(b"\x89PNG\r\n\x1a\n"), None);
+    }
+
+    #[test]
+    fn test_preview_text_and_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = dir.path().join("notes.txt");
+        let long: String = (0..TEXT_PREVIEW_LINES + 5)
+            .map(|i| format!("line\t{}\n", i))
+            .collect();
+        fs::write(&text, long).unwrap();
+        let binary = dir.path().join("data.bin");
+        fs::write(&binary, b"abc\0def").unwrap();
+
+        match Preview::load(&extract_entry(&text, 1).unwrap()) {
+            Preview::Text { lines, truncated } => {
+                assert_eq!(lines.len(), TEXT_PREVIEW_LINES);
+                assert_eq!(lines[0], "line    0");
+                assert!(truncated);
+            }
+            other => panic!("expected text, got {:?}", other),
+        }
+        assert_eq!(
+            Preview::load(&extract_entry(&binary, 1).unwrap()),
+            Preview::Binary { size: 7 }
+        );
+    }
+
+    #[test]
+    fn test_preview_dir_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("big.log"), vec![b'x'; 100]).unwrap();
+        fs::write(dir.path().join("small.txt"), "x").unwrap();
+        fs::write(dir.path().join(".env"), "x=1").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let Preview::Dir(summary) = Preview::load(&extract_entry(dir.path(), 0).unwrap()) else {
+            panic!("expected a directory summary");
+        };
+        assert_eq!((summary.files, summary.dirs, summary.hidden), (3, 1, 1));
+        assert_eq!(summary.total_size, 104);
+        assert_eq!(summary.largest[0], ("big.log".to_string(), 100));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preview_special_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("app.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+
+        assert_eq!(
+            Preview::load(&extract_entry(&socket, 1).unwrap()),
+            Preview::Special
+        );
+    }
+}
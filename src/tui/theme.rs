@@ -0,0 +1,85 @@
+//! Color themes for the TUI
+
+use crate::config::ThemeName;
+use ratatui::style::{Color, Modifier, Style};
+
+/// Styles the TUI draws with
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Path bar at the top
+    pub header: Style,
+    /// Status line at the bottom
+    pub footer: Style,
+    /// Selected row of a list
+    pub selection: Style,
+    pub dir: Style,
+    pub symlink: Style,
+    pub mark: Style,
+    /// Secondary text: sizes, descriptions, hints
+    pub dim: Style,
+    pub error: Style,
+    /// Key names in the help
+    pub key: Style,
+    /// Border of dialogs that ask for something
+    pub accent: Style,
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        let fg = |color: Color| Style::default().fg(color);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        match name {
+            ThemeName::Dark => Self {
+                header: Style::default().bg(Color::Blue).fg(Color::White),
+                footer: Style::default().bg(Color::DarkGray).fg(Color::White),
+                selection: Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .patch(bold),
+                dir: fg(Color::Cyan),
+                symlink: fg(Color::Magenta),
+                mark: fg(Color::Yellow),
+                dim: fg(Color::DarkGray),
+                error: fg(Color::Red),
+                key: fg(Color::Yellow).patch(bold),
+                accent: fg(Color::Yellow),
+            },
+            ThemeName::Light => Self {
+                header: Style::default().bg(Color::Blue).fg(Color::White),
+                footer: Style::default().bg(Color::Gray).fg(Color::Black),
+                selection: Style::default()
+                    .bg(Color::Gray)
+                    .fg(Color::Black)
+                    .patch(bold),
+                dir: fg(Color::Blue),
+                symlink: fg(Color::Magenta),
+                mark: fg(Color::Red),
+                dim: fg(Color::DarkGray),
+                error: fg(Color::Red),
+                key: fg(Color::Blue).patch(bold),
+                accent: fg(Color::Blue),
+            },
+            ThemeName::Mono => {
+                let reversed = Style::default().add_modifier(Modifier::REVERSED);
+                Self {
+                    header: reversed,
+                    footer: reversed,
+                    selection: reversed.patch(bold),
+                    dir: bold,
+                    symlink: Style::default().add_modifier(Modifier::ITALIC),
+                    mark: bold,
+                    dim: Style::default().add_modifier(Modifier::DIM),
+                    error: bold,
+                    key: bold,
+                    accent: Style::default(),
+                }
+            }
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Dark)
+    }
+}
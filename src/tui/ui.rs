@@ -1,45 +1,71 @@
 #[cfg(feature = "tui")]
-use crate::models::EntryKind;
+use crate::i18n::{tr, trf, Msg};
 #[cfg(feature = "tui")]
-use crate::tui::app::App;
+use crate::models::{Entry, EntryKind};
+#[cfg(feature = "tui")]
+use crate::plain;
+#[cfg(feature = "tui")]
+use crate::tui::app::{App, Mode};
+#[cfg(feature = "tui")]
+use crate::tui::keys::Action;
+#[cfg(feature = "tui")]
+use crate::tui::ops::Operation;
+#[cfg(feature = "tui")]
+use crate::tui::path;
+#[cfg(feature = "tui")]
+use crate::tui::preview::Preview;
+#[cfg(feature = "tui")]
+use crate::util::format_size_human;
 #[cfg(feature = "tui")]
 use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute, queue,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
-    terminal::{self, ClearType},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+#[cfg(feature = "tui")]
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
 };
 #[cfg(feature = "tui")]
-use std::io::{self, Write};
+use std::io::{self, Stdout};
+
+#[cfg(feature = "tui")]
+pub type Backend = CrosstermBackend<Stdout>;
 
 #[cfg(feature = "tui")]
 /// Run the interactive TUI
 pub fn run(app: &mut App) -> io::Result<()> {
+    with_terminal(|terminal| main_loop(app, terminal))
+}
+
+#[cfg(feature = "tui")]
+/// Run `f` on the alternate screen in raw mode, restoring the terminal after
+pub fn with_terminal<T>(f: impl FnOnce(&mut Terminal<Backend>) -> io::Result<T>) -> io::Result<T> {
     // Setup terminal
     terminal::enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
-    // Main loop
-    let result = main_loop(app, &mut stdout);
+    let result = f(&mut terminal);
 
     // Cleanup terminal
-    execute!(
-        stdout,
-        terminal::LeaveAlternateScreen,
-        cursor::Show,
-        ResetColor
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
     terminal::disable_raw_mode()?;
 
     result
 }
 
 #[cfg(feature = "tui")]
-fn main_loop(app: &mut App, stdout: &mut io::Stdout) -> io::Result<()> {
+fn main_loop(app: &mut App, terminal: &mut Terminal<Backend>) -> io::Result<()> {
     loop {
-        draw_ui(app, stdout)?;
+        app.refresh_preview();
+        terminal.draw(|frame| draw_ui(frame, app))?;
 
         if app.should_quit {
             break;
@@ -48,7 +74,9 @@ fn main_loop(app: &mut App, stdout: &mut io::Stdout) -> io::Result<()> {
         // Handle input
         if event::poll(std::time::Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
-                handle_key_event(app, key)?;
+                if key.kind == KeyEventKind::Press {
+                    handle_key_event(app, key)?;
+                }
             }
         }
     }
@@ -58,176 +86,654 @@ fn main_loop(app: &mut App, stdout: &mut io::Stdout) -> io::Result<()> {
 
 #[cfg(feature = "tui")]
 fn handle_key_event(app: &mut App, key: KeyEvent) -> io::Result<()> {
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => app.quit(),
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.quit(),
-        KeyCode::Down | KeyCode::Char('j') => app.next(),
-        KeyCode::Up | KeyCode::Char('k') => app.previous(),
-        KeyCode::PageDown => app.page_down(10),
-        KeyCode::PageUp => app.page_up(10),
-        KeyCode::Enter => app.enter_selected().map_err(io::Error::other)?,
-        KeyCode::Backspace if !app.filter.is_empty() => app.remove_filter_char(),
-        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => app.clear_filter(),
-        KeyCode::Char('.') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.toggle_hidden().map_err(io::Error::other)?
-        }
-        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.toggle_dirs_first()
-        }
-        KeyCode::Char('-') | KeyCode::Left => app.go_up().map_err(io::Error::other)?,
-        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.add_filter_char(c)
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.quit();
+        return Ok(());
+    }
+
+    match &mut app.mode {
+        Mode::Normal => handle_normal_key(app, key).map_err(io::Error::other)?,
+        Mode::Filter => match key.code {
+            KeyCode::Enter | KeyCode::Esc => app.mode = Mode::Normal,
+            KeyCode::Backspace => app.remove_filter_char(),
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.clear_filter()
+            }
+            KeyCode::Char(c) => app.add_filter_char(c),
+            _ => {}
+        },
+        Mode::Prompt { input, .. } => match key.code {
+            KeyCode::Esc => app.mode = Mode::Normal,
+            KeyCode::Enter => app.submit_prompt().map_err(io::Error::other)?,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => input.clear(),
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        },
+        Mode::Confirm { .. } => {
+            let yes = matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y'));
+            app.confirm(yes).map_err(io::Error::other)?
         }
-        _ => {}
+        Mode::Help => app.mode = Mode::Normal,
+        Mode::Palette { .. } => handle_palette_key(app, key).map_err(io::Error::other)?,
+        Mode::PathBar { input, candidates } => match key.code {
+            KeyCode::Esc => app.mode = Mode::Normal,
+            KeyCode::Enter => app.submit_path_bar().map_err(io::Error::other)?,
+            KeyCode::Tab => app.complete_path(),
+            KeyCode::Backspace => {
+                input.pop();
+                candidates.clear();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.clear();
+                candidates.clear();
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                candidates.clear();
+            }
+            _ => {}
+        },
     }
 
     Ok(())
 }
 
 #[cfg(feature = "tui")]
-fn draw_ui(app: &App, stdout: &mut io::Stdout) -> io::Result<()> {
-    queue!(
-        stdout,
-        terminal::Clear(ClearType::All),
-        cursor::MoveTo(0, 0)
-    )?;
-
-    let (width, height) = terminal::size()?;
-    let content_height = height.saturating_sub(3) as usize;
-
-    // Draw header
-    draw_header(app, stdout, width)?;
-
-    // Draw file list
-    draw_file_list(app, stdout, content_height)?;
+fn handle_normal_key(app: &mut App, key: KeyEvent) -> crate::errors::Result<()> {
+    let Some(action) = app.keymap.action(&key) else {
+        // Digits not bound to anything switch tabs
+        if let KeyCode::Char(c @ '1'..='9') = key.code {
+            let index = c as usize - '1' as usize;
+            app.switch_tab(index)?;
+        }
+        return Ok(());
+    };
+    match action {
+        Action::Down => app.next(),
+        Action::Up => app.previous(),
+        Action::PageDown => app.page_down(10),
+        Action::PageUp => app.page_up(10),
+        Action::Open => app.enter_selected()?,
+        Action::Parent => app.go_up()?,
+        Action::Filter => app.mode = Mode::Filter,
+        Action::ClearFilter => app.clear_filter(),
+        Action::ToggleHidden => app.toggle_hidden()?,
+        Action::ToggleDirsFirst => app.toggle_dirs_first(),
+        Action::Mark => app.toggle_mark(),
+        Action::Copy => app.start_operation(Operation::Copy),
+        Action::Move => app.start_operation(Operation::Move),
+        Action::Rename => app.start_operation(Operation::Rename),
+        Action::Delete => app.start_operation(Operation::Delete),
+        Action::Undo => app.undo()?,
+        Action::NewTab => app.new_tab()?,
+        Action::CloseTab => app.close_tab()?,
+        Action::OtherTab => app.switch_pane()?,
+        Action::Split => app.toggle_split(),
+        Action::PathBar => app.open_path_bar(),
+        Action::Palette => app.open_palette(),
+        Action::Help => app.mode = Mode::Help,
+        Action::Quit => app.quit(),
+    }
+    Ok(())
+}
 
-    // Draw footer
-    draw_footer(app, stdout, width, height)?;
+#[cfg(feature = "tui")]
+fn handle_palette_key(app: &mut App, key: KeyEvent) -> crate::errors::Result<()> {
+    let matches = app.palette_suggestions().len();
+    let completion = app
+        .palette_suggestions()
+        .into_iter()
+        .nth(match &app.mode {
+            Mode::Palette { selected, .. } => *selected,
+            _ => 0,
+        })
+        .map(|s| s.text);
+    let Mode::Palette { input, selected } = &mut app.mode else {
+        return Ok(());
+    };
 
-    stdout.flush()?;
+    match key.code {
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Enter => app.submit_palette()?,
+        KeyCode::Down => *selected = (*selected + 1).min(matches.saturating_sub(1)),
+        KeyCode::Up => *selected = selected.saturating_sub(1),
+        KeyCode::Tab => {
+            if let Some(text) = completion {
+                *input = text;
+                *selected = 0;
+            }
+        }
+        KeyCode::Backspace => {
+            input.pop();
+            *selected = 0;
+        }
+        KeyCode::Char(c) => {
+            input.push(c);
+            *selected = 0;
+        }
+        _ => {}
+    }
     Ok(())
 }
 
 #[cfg(feature = "tui")]
-fn draw_header(app: &App, stdout: &mut io::Stdout, width: u16) -> io::Result<()> {
-    let separator = "─".repeat(width as usize);
-    queue!(
-        stdout,
-        SetBackgroundColor(Color::Blue),
-        SetForegroundColor(Color::White),
-        Print(format!(
-            " {:<width$}",
-            app.path.display(),
-            width = width as usize - 1
-        )),
-        ResetColor,
-        cursor::MoveTo(0, 1),
-        Print(&separator),
-    )?;
-    Ok(())
+/// Header, then the directory and preview panes side by side, then the footer
+///
+/// When split, two directory panes replace the preview, lower tab on the left.
+fn draw_ui(frame: &mut Frame, app: &mut App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_header(frame, app, header);
+    if app.other_pane().is_some() {
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(body);
+        let (active, other) = if app.active_tab < app.alternate_tab {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        draw_file_list(frame, app, active);
+        draw_other_pane(frame, app, other);
+    } else {
+        let [list, preview] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(body);
+        draw_file_list(frame, app, list);
+        draw_preview(frame, app, preview);
+    }
+    draw_footer(frame, app, footer);
+    match app.mode {
+        Mode::Confirm { .. } => draw_confirm(frame, app, body),
+        Mode::Help => draw_help(frame, app, body),
+        Mode::Palette { .. } => draw_palette(frame, app, body),
+        Mode::PathBar { .. } => draw_path_candidates(frame, app, body),
+        _ => {}
+    }
 }
 
 #[cfg(feature = "tui")]
-fn draw_file_list(app: &App, stdout: &mut io::Stdout, content_height: usize) -> io::Result<()> {
-    let start_index = if app.selected_index >= content_height {
-        app.selected_index - content_height + 1
+/// The tabs, when there are several, then the current path
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = Vec::new();
+    let paths = app.tab_paths();
+    if paths.len() > 1 {
+        for (i, path) in paths.iter().enumerate() {
+            let name = path.file_name().map_or_else(
+                || path.display().to_string(),
+                |n| n.to_string_lossy().into(),
+            );
+            let label = format!(" {}:{} ", i + 1, name);
+            spans.push(if i == app.active_tab {
+                Span::styled(label, app.theme.selection)
+            } else {
+                Span::raw(label)
+            });
+        }
+        spans.push(Span::raw(plain::pick("│", "|")));
+    }
+    // Breadcrumbs, ready to be made clickable once the TUI reads the mouse
+    spans.push(Span::raw(" "));
+    let crumbs = path::breadcrumbs(&app.path);
+    let last = crumbs.len().saturating_sub(1);
+    for (i, (name, _)) in crumbs.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(plain::pick(" › ", " / "), app.theme.dim));
+        }
+        spans.push(if i == last {
+            Span::styled(name, Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            Span::raw(name)
+        });
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).style(app.theme.header),
+        area,
+    );
+}
+
+#[cfg(feature = "tui")]
+fn draw_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let count = if app.marked.is_empty() {
+        format!("{} entries", app.filtered_entries.len())
+    } else {
+        format!(
+            "{} entries, {} marked",
+            app.filtered_entries.len(),
+            app.marked.len()
+        )
+    };
+    // Split panes are told apart by their paths
+    let title = if app.split {
+        format!(" {} ({}) ", app.path.display(), count)
     } else {
-        0
+        format!(" {} ", count)
     };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(if app.other_pane().is_some() {
+            app.theme.accent
+        } else {
+            Style::default()
+        });
+
+    let mut state = ListState::default()
+        .with_offset(app.scroll_offset)
+        .with_selected(Some(app.selected_index));
+    draw_entries(
+        frame,
+        app,
+        &app.filtered_entries,
+        block,
+        app.theme.selection,
+        &mut state,
+        area,
+    );
+    app.scroll_offset = state.offset();
+}
+
+#[cfg(feature = "tui")]
+/// The listing of the alternate tab, beside the active one
+fn draw_other_pane(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(tab) = app.other_pane() else {
+        return;
+    };
+    let block = Block::default().borders(Borders::ALL).title(format!(
+        " {} ({} entries) ",
+        tab.path.display(),
+        tab.filtered_entries.len()
+    ));
+    let mut state = ListState::default()
+        .with_offset(tab.scroll_offset)
+        .with_selected(Some(tab.selected_index));
+    draw_entries(
+        frame,
+        app,
+        &tab.filtered_entries,
+        block,
+        app.theme.dim,
+        &mut state,
+        area,
+    );
+    let alternate = app.alternate_tab;
+    app.tabs[alternate].scroll_offset = state.offset();
+}
 
-    let end_index = (start_index + content_height).min(app.filtered_entries.len());
+#[cfg(feature = "tui")]
+fn draw_entries(
+    frame: &mut Frame,
+    app: &App,
+    entries: &[Entry],
+    block: Block,
+    highlight: Style,
+    state: &mut ListState,
+    area: Rect,
+) {
+    if entries.is_empty() {
+        let empty = Paragraph::new("  No entries found")
+            .style(app.theme.dim)
+            .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
 
-    for (i, entry) in app.filtered_entries[start_index..end_index]
+    // Room left for the name once the icon and size columns are drawn;
+    // plain kind words take two columns more than icons
+    let icon_width = if plain::is_enabled() { 4 } else { 2 };
+    let name_width = (area.width as usize).saturating_sub(17 + icon_width).max(8);
+    let items: Vec<ListItem> = entries
         .iter()
-        .enumerate()
-    {
-        let row = i as u16 + 2;
-        queue!(stdout, cursor::MoveTo(0, row))?;
-
-        let is_selected = start_index + i == app.selected_index;
-
-        if is_selected {
-            queue!(
-                stdout,
-                SetBackgroundColor(Color::DarkGrey),
-                SetForegroundColor(Color::White),
-            )?;
-        }
-
-        // Icon based on type
-        let icon = match entry.kind {
-            EntryKind::Dir => "📁",
-            EntryKind::File => "📄",
-            EntryKind::Symlink => "🔗",
-        };
+        .map(|entry| {
+            // Icon based on type
+            let (icon, style) = match entry.kind {
+                EntryKind::Dir => ("📁", app.theme.dir),
+                EntryKind::File => ("📄", Style::default()),
+                EntryKind::Symlink => ("🔗", app.theme.symlink),
+            };
+            let icon = plain::pick(icon, plain::kind_marker(entry.kind));
+
+            // Format size
+            let size_str = if entry.kind == EntryKind::File {
+                format_size_human(entry.size)
+            } else {
+                "-".to_string()
+            };
+
+            let mark = if app.marked.contains(&entry.path) {
+                Span::styled("*", app.theme.mark)
+            } else {
+                Span::raw(" ")
+            };
+            let name: String = entry.name.chars().take(name_width).collect();
+            ListItem::new(Line::from(vec![
+                mark,
+                Span::raw(format!("{} ", icon)),
+                Span::styled(format!("{:width$}", name, width = name_width), style),
+                Span::raw(format!(" {:>10}", size_str)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block).highlight_style(highlight);
+    frame.render_stateful_widget(list, area, state);
+}
 
-        // Format size
-        let size_str = if entry.kind == EntryKind::File {
-            humansize::format_size(entry.size, humansize::BINARY)
-        } else {
-            "-".to_string()
-        };
+#[cfg(feature = "tui")]
+fn draw_preview(frame: &mut Frame, app: &App, area: Rect) {
+    let title = app
+        .selected_entry()
+        .map(|entry| format!(" {} ", entry.name))
+        .unwrap_or_default();
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let dim = app.theme.dim;
+
+    let lines: Vec<Line> = match app.preview.as_ref().map(|(_, preview)| preview) {
+        None => Vec::new(),
+        Some(Preview::Text { lines, truncated }) => {
+            let mut text: Vec<Line> = lines.iter().map(|l| Line::raw(l.as_str())).collect();
+            if *truncated {
+                text.push(Line::styled(plain::pick("…", "..."), dim));
+            }
+            text
+        }
+        Some(Preview::Image {
+            format,
+            width,
+            height,
+            size,
+        }) => vec![
+            Line::raw(format!("{} image", format)),
+            Line::raw(format!(
+                "{} {} {} pixels",
+                width,
+                plain::pick("×", "x"),
+                height
+            )),
+            Line::styled(format_size_human(*size), dim),
+        ],
+        Some(Preview::Binary { size }) => vec![
+            Line::raw("Binary file"),
+            Line::styled(format_size_human(*size), dim),
+        ],
+        Some(Preview::Special) => vec![Line::styled("Special file, not read", dim)],
+        Some(Preview::Symlink { target }) => {
+            let arrow = plain::pick("→", "Link to");
+            vec![Line::raw(format!("{} {}", arrow, target.display()))]
+        }
+        Some(Preview::Dir(summary)) => {
+            let mut text = vec![
+                Line::raw(format!(
+                    "{} files, {} directories, {} other",
+                    summary.files, summary.dirs, summary.others
+                )),
+                Line::raw(format!(
+                    "{} in files",
+                    format_size_human(summary.total_size)
+                )),
+            ];
+            if summary.hidden > 0 {
+                text.push(Line::styled(format!("{} hidden", summary.hidden), dim));
+            }
+            if !summary.largest.is_empty() {
+                text.push(Line::raw(""));
+                text.push(Line::styled("Largest:", dim));
+                for (name, size) in &summary.largest {
+                    text.push(Line::raw(format!(
+                        "{:>10}  {}",
+                        format_size_human(*size),
+                        name
+                    )));
+                }
+            }
+            text
+        }
+        Some(Preview::Unavailable(error)) => {
+            vec![Line::styled(error.as_str(), app.theme.error)]
+        }
+    };
 
-        queue!(
-            stdout,
-            Print(format!(
-                " {} {:40} {:>10}",
-                icon,
-                &entry.name.chars().take(40).collect::<String>(),
-                size_str
-            ))
-        )?;
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
 
-        if is_selected {
-            queue!(stdout, ResetColor)?;
+#[cfg(feature = "tui")]
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let status = match &app.mode {
+        Mode::Prompt { operation, input } => {
+            let label = tr(match operation {
+                Operation::Copy => Msg::TuiCopyTo,
+                Operation::Move => Msg::TuiMoveTo,
+                _ => Msg::TuiRenameTo,
+            });
+            frame.set_cursor_position((
+                area.x + (label.chars().count() + 3 + input.chars().count()) as u16,
+                area.y,
+            ));
+            format!(" {}: {}", label, input)
         }
-    }
+        Mode::Filter => format!(" /{}", app.filter),
+        Mode::Palette { input, .. } => {
+            frame.set_cursor_position((area.x + 2 + input.chars().count() as u16, area.y));
+            format!(" :{}", input)
+        }
+        Mode::PathBar { input, .. } => {
+            let label = tr(Msg::TuiGoTo);
+            frame.set_cursor_position((
+                area.x + (label.chars().count() + 3 + input.chars().count()) as u16,
+                area.y,
+            ));
+            format!(" {}: {}", label, input)
+        }
+        _ => match &app.message {
+            Some(message) => format!(" {}", message),
+            // Status line
+            None => {
+                let filter = if app.filter.is_empty() {
+                    tr(Msg::TuiNoFilter)
+                } else {
+                    app.filter.as_str()
+                };
+                let hidden = tr(if app.show_hidden {
+                    Msg::TuiOn
+                } else {
+                    Msg::TuiOff
+                });
+                let status = trf(
+                    Msg::TuiStatus,
+                    &[&app.filtered_entries.len(), &filter, &hidden],
+                );
+                format!(" {}", status)
+            }
+        },
+    };
 
-    // Show empty message if no entries
-    if app.filtered_entries.is_empty() {
-        queue!(
-            stdout,
-            cursor::MoveTo(0, 2),
-            SetForegroundColor(Color::DarkGrey),
-            Print("  No entries found"),
-            ResetColor
-        )?;
+    frame.render_widget(Paragraph::new(status).style(app.theme.footer), area);
+}
+
+#[cfg(feature = "tui")]
+/// Dialog listing what an operation is about to change
+fn draw_confirm(frame: &mut Frame, app: &App, area: Rect) {
+    let Mode::Confirm {
+        operation,
+        sources,
+        target,
+    } = &app.mode
+    else {
+        return;
+    };
+
+    let question = match (operation, target) {
+        (Operation::Delete, _) => format!("Move {} item(s) to the trash?", sources.len()),
+        (operation, Some(target)) => format!(
+            "{} {} item(s) to {}?",
+            if *operation == Operation::Copy {
+                "Copy"
+            } else {
+                "Move"
+            },
+            sources.len(),
+            target.display()
+        ),
+        _ => return,
+    };
+
+    const LISTED: usize = 8;
+    let mut lines = vec![Line::raw(question), Line::raw("")];
+    for source in sources.iter().take(LISTED) {
+        lines.push(Line::raw(format!("  {}", source.display())));
+    }
+    if sources.len() > LISTED {
+        lines.push(Line::raw(format!(
+            "  {} and {} more",
+            plain::pick("…", "..."),
+            sources.len() - LISTED
+        )));
     }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "y: confirm, any other key: cancel",
+        app.theme.dim,
+    ));
+
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let dialog = centered(area, width, lines.len() as u16 + 2);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Confirm ")
+        .border_style(app.theme.accent);
+    frame.render_widget(Clear, dialog);
+    frame.render_widget(Paragraph::new(lines).block(block), dialog);
+}
 
-    Ok(())
+#[cfg(feature = "tui")]
+/// A `width` × `height` area centered in `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
 }
 
 #[cfg(feature = "tui")]
-fn draw_footer(app: &App, stdout: &mut io::Stdout, width: u16, height: u16) -> io::Result<()> {
-    let footer_row = height - 1;
+/// The configured keys of every action
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines: Vec<Line> = Action::ALL
+        .into_iter()
+        .map(|action| {
+            let keys = app.keymap.keys_for(action).join(" ");
+            let keys = if keys.is_empty() {
+                "-".to_string()
+            } else {
+                keys
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:<16}", keys), app.theme.key),
+                Span::raw(action.description()),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(vec![
+        Span::styled(format!(" {:<16}", "1-9"), app.theme.key),
+        Span::raw("Switch to tab"),
+    ]));
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(" Any key closes this help", app.theme.dim));
+
+    let dialog = centered(area, 66, lines.len() as u16 + 2);
+    let block = Block::default().borders(Borders::ALL).title(" Keys ");
+    frame.render_widget(Clear, dialog);
+    frame.render_widget(Paragraph::new(lines).block(block), dialog);
+}
 
-    let separator = "─".repeat(width as usize);
-    queue!(
-        stdout,
-        cursor::MoveTo(0, footer_row - 1),
-        Print(&separator),
-        cursor::MoveTo(0, footer_row),
-    )?;
+#[cfg(feature = "tui")]
+fn draw_palette(frame: &mut Frame, app: &App, area: Rect) {
+    let Mode::Palette { input, selected } = &app.mode else {
+        return;
+    };
+    let suggestions = app.palette_suggestions();
+    let items: Vec<ListItem> = suggestions
+        .iter()
+        .map(|s| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(" {:<24}", s.text)),
+                Span::styled(s.description.as_str(), app.theme.dim),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).clamp(3, 14);
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let dialog = Rect {
+        y: area.y + 1,
+        ..centered(area, width, height)
+    };
+    let title = if input.is_empty() {
+        " Commands ".to_string()
+    } else {
+        format!(" :{} ", input)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(app.theme.accent);
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    let mut state = ListState::default().with_selected(Some(*selected));
+    frame.render_widget(Clear, dialog);
+    frame.render_stateful_widget(list, dialog, &mut state);
+}
 
-    // Status line
-    let status = format!(
-        " {} entries | Filter: {} | Hidden: {} | q:quit ↑↓:navigate ⏎:enter -:up",
-        app.filtered_entries.len(),
-        if app.filter.is_empty() {
-            "<none>"
-        } else {
-            &app.filter
-        },
-        if app.show_hidden { "on" } else { "off" }
-    );
+#[cfg(feature = "tui")]
+/// Directories the last `Tab` in the path bar could complete to
+fn draw_path_candidates(frame: &mut Frame, app: &App, area: Rect) {
+    let Mode::PathBar { candidates, .. } = &app.mode else {
+        return;
+    };
+    if candidates.len() < 2 {
+        return;
+    }
 
-    queue!(
-        stdout,
-        SetBackgroundColor(Color::DarkGrey),
-        SetForegroundColor(Color::White),
-        Print(format!("{:<width$}", status, width = width as usize)),
-        ResetColor,
-    )?;
+    const LISTED: usize = 12;
+    let mut lines: Vec<Line> = candidates
+        .iter()
+        .take(LISTED)
+        .map(|name| Line::styled(format!(" {}", name), app.theme.dir))
+        .collect();
+    if candidates.len() > LISTED {
+        lines.push(Line::styled(
+            format!(
+                " {} and {} more",
+                plain::pick("…", "..."),
+                candidates.len() - LISTED
+            ),
+            app.theme.dim,
+        ));
+    }
 
-    Ok(())
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let width = area.width.saturating_sub(8).clamp(20, 60).min(area.width);
+    let dialog = Rect {
+        x: area.x + 1,
+        y: area.y + area.height - height,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Matches ")
+        .border_style(app.theme.accent);
+    frame.render_widget(Clear, dialog);
+    frame.render_widget(Paragraph::new(lines).block(block), dialog);
 }
@@ -1,8 +1,14 @@
 use crate::errors::{FsError, Result};
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use humansize::{format_size, BINARY};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
 
-/// Parse human-readable size string (e.g., "10KB", "2 MiB", "500B")
+/// Parse human-readable size string (e.g., "10KB", "2 MiB", "1.5G", "500B")
+///
+/// Two-letter decimal units (`KB`, `MB`, ...) are powers of 1000; binary units
+/// (`KiB`, `MiB`, ...) and the single-letter shorthands used by find/du
+/// (`k`, `M`, `G`, ...) are powers of 1024. Units are case-insensitive.
 pub fn parse_size(input: &str) -> Result<u64> {
     let input = input.trim().to_uppercase();
     let input = input.replace(" ", "");
@@ -14,20 +20,24 @@ pub fn parse_size(input: &str) -> Result<u64> {
         .map(|(idx, _)| input.split_at(idx))
         .unwrap_or((&input, ""));
 
-    let number: f64 = num_str.parse().map_err(|_| FsError::InvalidSize {
-        input: input.clone(),
-    })?;
+    let number: f64 = num_str
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n >= 0.0)
+        .ok_or_else(|| FsError::InvalidSize {
+            input: input.clone(),
+        })?;
 
     let multiplier: u64 = match unit {
         "" | "B" => 1,
         "KB" => 1_000,
-        "KIB" => 1_024,
+        "K" | "KI" | "KIB" => 1_024,
         "MB" => 1_000_000,
-        "MIB" => 1_048_576,
+        "M" | "MI" | "MIB" => 1_048_576,
         "GB" => 1_000_000_000,
-        "GIB" => 1_073_741_824,
+        "G" | "GI" | "GIB" => 1_073_741_824,
         "TB" => 1_000_000_000_000,
-        "TIB" => 1_099_511_627_776,
+        "T" | "TI" | "TIB" => 1_099_511_627_776,
         _ => {
             return Err(FsError::InvalidSize {
                 input: input.clone(),
@@ -38,36 +48,204 @@ pub fn parse_size(input: &str) -> Result<u64> {
     Ok((number * multiplier as f64) as u64)
 }
 
+/// A single `--size` constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeBound {
+    /// `+SIZE`: at least this many bytes
+    AtLeast(u64),
+    /// `-SIZE`: at most this many bytes
+    AtMost(u64),
+    /// `SIZE`: exactly this many bytes
+    Exactly(u64),
+}
+
+/// Parse a signed size constraint like "+10M", "-1.5G" or "512k" (fd-style)
+pub fn parse_size_bound(input: &str) -> Result<SizeBound> {
+    let input = input.trim();
+
+    if let Some(rest) = input.strip_prefix('+') {
+        Ok(SizeBound::AtLeast(parse_size(rest)?))
+    } else if let Some(rest) = input.strip_prefix('-') {
+        Ok(SizeBound::AtMost(parse_size(rest)?))
+    } else {
+        Ok(SizeBound::Exactly(parse_size(input)?))
+    }
+}
+
+/// Parse a count such as "5000", "1_000_000" or "1e6"
+pub fn parse_count(input: &str) -> Result<u64> {
+    let invalid = || FsError::InvalidFormat {
+        format: format!("Invalid count: {}", input),
+    };
+    let cleaned = input.trim().replace('_', "");
+
+    if let Ok(count) = cleaned.parse::<u64>() {
+        return Ok(count);
+    }
+
+    // Scientific notation must still describe a whole number
+    let value: f64 = cleaned.parse().map_err(|_| invalid())?;
+    if value.is_finite() && value >= 0.0 && value.fract() == 0.0 && value <= u64::MAX as f64 {
+        Ok(value as u64)
+    } else {
+        Err(invalid())
+    }
+}
+
 /// Format size in human-readable format using binary units
 pub fn format_size_human(size: u64) -> String {
     format_size(size, BINARY)
 }
 
-/// Parse date string (ISO8601, YYYY-MM-DD, or relative like "7 days ago")
+/// Format allocated vs apparent size, e.g. `1 GiB (5% of 20 GiB)` for sparse files
+pub fn format_allocated(allocated: u64, apparent: u64) -> String {
+    if apparent == 0 || allocated >= apparent {
+        return format_size_human(allocated);
+    }
+
+    let percent = match allocated * 100 / apparent {
+        0 => "<1".to_string(),
+        p => p.to_string(),
+    };
+    format!(
+        "{} ({}% of {})",
+        format_size_human(allocated),
+        percent,
+        format_size_human(apparent)
+    )
+}
+
+/// Format size like `du -h`: powers of 1024, rounded up, one decimal below 10
+pub fn format_size_du(size: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64;
+    for unit in UNITS {
+        value /= 1024.0;
+
+        if value < 10.0 {
+            let rounded = (value * 10.0).ceil() / 10.0;
+            if rounded < 10.0 {
+                return format!("{:.1}{}", rounded, unit);
+            }
+        }
+
+        let rounded = value.ceil();
+        if rounded < 1024.0 {
+            return format!("{}{}", rounded as u64, unit);
+        }
+    }
+
+    format!("{}E", value.ceil() as u64)
+}
+
+/// Parse date string (ISO8601, YYYY-MM-DD [HH:MM[:SS]], named days, or relative)
+///
+/// Dates without an explicit offset are interpreted in the local timezone, so
+/// `--after 2024-01-01` means local midnight rather than UTC midnight.
+/// Accepted forms:
+/// - `2024-01-01T12:00:00Z`, `2024-01-01T12:00:00+02:00` (RFC 3339)
+/// - `2024-01-01`, `2024-01-01 14:30`, `2024-01-01T14:30:15` (local)
+/// - `now`, `today`, `yesterday`, `tomorrow`, optionally followed by `HH:MM`
+/// - `14:30` (today at that local time)
+/// - `last week`, `last month`, `last year`, `7 days ago`
 pub fn parse_date(input: &str) -> Result<DateTime<Utc>> {
+    let invalid = || FsError::InvalidDate {
+        input: input.to_string(),
+    };
+
     // Try parsing as RFC3339/ISO8601 first
     if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
         return Ok(dt.with_timezone(&Utc));
     }
 
-    // Try YYYY-MM-DD format
-    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
-        return Utc
-            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
-            .single()
-            .ok_or_else(|| FsError::InvalidDate {
-                input: input.to_string(),
-            });
+    let trimmed = input.trim();
+
+    // Date with time of day, in local time
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(trimmed, format) {
+            return local_to_utc(naive).ok_or_else(invalid);
+        }
+    }
+
+    // Try YYYY-MM-DD format (local midnight)
+    if let Ok(date) = NaiveDate::parse_from_str(trimmed, "%Y-%m-%d") {
+        return local_to_utc(date.and_time(NaiveTime::MIN)).ok_or_else(invalid);
+    }
+
+    // Named days and bare times of day
+    if let Some(named_date) = parse_named_date(trimmed) {
+        return named_date.ok_or_else(invalid);
     }
 
     // Try relative date parsing (e.g., "7 days ago", "2 weeks ago", "1 month ago")
-    if let Some(relative_date) = parse_relative_date(input) {
+    if let Some(relative_date) = parse_relative_date(trimmed) {
         return Ok(relative_date);
     }
 
-    Err(FsError::InvalidDate {
-        input: input.to_string(),
-    })
+    Err(invalid())
+}
+
+/// Convert a local wall-clock time to UTC (earliest match across DST changes)
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse "today", "yesterday 09:00", "14:30", "last week", ...
+///
+/// Returns `None` if the input isn't a named date, `Some(None)` if it is one
+/// but doesn't map to a valid local time.
+fn parse_named_date(input: &str) -> Option<Option<DateTime<Utc>>> {
+    use chrono::Duration;
+
+    let input = input.to_lowercase();
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    let today = Local::now().date_naive();
+    let parse_time = |s: &str| {
+        NaiveTime::parse_from_str(s, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+            .ok()
+    };
+
+    let (day, time) = match parts.as_slice() {
+        ["now"] => return Some(Some(Utc::now())),
+        ["last", unit] => {
+            let days = match *unit {
+                "week" => 7,
+                "month" => 30,
+                "year" => 365,
+                _ => return None,
+            };
+            return Some(Some(Utc::now() - Duration::days(days)));
+        }
+        [time] if time.contains(':') => (today, parse_time(time)?),
+        [day] => (named_day(day, today)?, NaiveTime::MIN),
+        [day, time] => (named_day(day, today)?, parse_time(time)?),
+        _ => return None,
+    };
+
+    Some(local_to_utc(day.and_time(time)))
+}
+
+fn named_day(name: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match name {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        "tomorrow" => today.succ_opt(),
+        _ => None,
+    }
 }
 
 /// Parse relative date strings like "7 days ago", "2 weeks ago", "1 month ago"
@@ -99,15 +277,303 @@ fn parse_relative_date(input: &str) -> Option<DateTime<Utc>> {
     }
 }
 
+/// Parse a duration like "30d", "2h", "90min", "1w" or "1h30m"
+///
+/// Units: `s`, `m`/`min`, `h`, `d`, `w`, `mo` (30 days) and `y` (365 days),
+/// plus their long forms (`hours`, `days`, ...). Components can be combined.
+pub fn parse_duration(input: &str) -> Result<chrono::Duration> {
+    use chrono::Duration;
+
+    let invalid = || FsError::InvalidDate {
+        input: input.to_string(),
+    };
+
+    let normalized = input.trim().to_lowercase().replace(' ', "");
+    if normalized.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::zero();
+    let mut rest = normalized.as_str();
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_len = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - digits);
+
+        let number: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit = &rest[digits..digits + unit_len];
+
+        let component = match unit {
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(number),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(number),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(number),
+            "d" | "day" | "days" => Duration::try_days(number),
+            "w" | "week" | "weeks" => Duration::try_weeks(number),
+            "mo" | "month" | "months" => number.checked_mul(30).and_then(Duration::try_days),
+            "y" | "yr" | "year" | "years" => number.checked_mul(365).and_then(Duration::try_days),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+
+        total = total.checked_add(&component).ok_or_else(invalid)?;
+        rest = &rest[digits + unit_len..];
+    }
+
+    Ok(total)
+}
+
 /// Check if output is to a TTY (terminal)
 pub fn is_tty() -> bool {
     crossterm::tty::IsTty::is_tty(&std::io::stdout())
 }
 
+/// A command running `script` through the platform shell (`sh -c`, or
+/// `cmd /C` on Windows)
+pub fn shell_command(script: &str) -> std::process::Command {
+    let mut command = if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = std::process::Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(script);
+    command
+}
+
+/// Printable form of a path that keeps non-UTF-8 bytes visible
+///
+/// UTF-8 paths are returned unchanged. Otherwise each invalid byte becomes
+/// `\xNN` and backslashes are doubled, so [`unescape_path`] can restore the
+/// original bytes (`bad\xFFname`).
+pub fn escape_path(path: &Path) -> Cow<'_, str> {
+    if let Some(s) = path.to_str() {
+        return Cow::Borrowed(s);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::fmt::Write;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut out = String::new();
+        for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+            out.push_str(&chunk.valid().replace('\\', "\\\\"));
+            for byte in chunk.invalid() {
+                let _ = write!(out, "\\x{:02X}", byte);
+            }
+        }
+        Cow::Owned(out)
+    }
+
+    #[cfg(not(unix))]
+    path.to_string_lossy()
+}
+
+/// Reverse [`escape_path`]
+///
+/// Escapes are only decoded when that yields a non-UTF-8 path (the only case
+/// `escape_path` produces them), so ordinary paths containing backslashes
+/// pass through untouched.
+pub fn unescape_path(s: &str) -> PathBuf {
+    #[cfg(unix)]
+    if s.contains('\\') {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut bytes = Vec::with_capacity(s.len());
+        let mut rest = s.as_bytes();
+        while let Some((&b, tail)) = rest.split_first() {
+            let hex = tail
+                .strip_prefix(b"x")
+                .and_then(|t| t.get(..2))
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok());
+            match (b, tail.first(), hex) {
+                (b'\\', _, Some(byte)) => {
+                    bytes.push(byte);
+                    rest = &tail[3..];
+                }
+                (b'\\', Some(b'\\'), _) => {
+                    bytes.push(b'\\');
+                    rest = &tail[1..];
+                }
+                _ => {
+                    bytes.push(b);
+                    rest = tail;
+                }
+            }
+        }
+
+        if std::str::from_utf8(&bytes).is_err() {
+            return PathBuf::from(OsString::from_vec(bytes));
+        }
+    }
+
+    PathBuf::from(s)
+}
+
+/// Path relative to the current directory, for CI reports
+///
+/// Code-scanning tools resolve file locations against the checkout, which is
+/// the working directory of a CI step. Paths outside it are left absolute.
+pub fn workspace_path(path: &Path) -> String {
+    escape_path(workspace_relative(path)).into_owned()
+}
+
+/// [`workspace_path`] before escaping
+pub fn workspace_relative(path: &Path) -> &Path {
+    let cwd = std::env::current_dir().ok();
+    let relative = cwd
+        .as_deref()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    relative.strip_prefix(".").unwrap_or(relative)
+}
+
+/// Serde adapter for paths that may not be valid UTF-8 (see [`escape_path`])
+pub mod serde_path {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::path::{Path, PathBuf};
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::escape_path(path))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(super::unescape_path(&s))
+    }
+}
+
+/// The first `k` items in `cmp` order, sorted, without sorting everything
+///
+/// Keeps a bounded heap of `k` items, so selecting the largest 100 of
+/// millions of entries costs O(n log k) time and O(k) memory. Ties keep
+/// their input order, matching a stable sort followed by `take(k)`.
+pub fn top_k_by<T, F>(items: impl IntoIterator<Item = T>, k: usize, cmp: F) -> Vec<T>
+where
+    F: Fn(&T, &T) -> std::cmp::Ordering,
+{
+    use std::cmp::Ordering;
+    use std::collections::BinaryHeap;
+
+    /// Heap slot ordered by `cmp`, then by input position
+    struct Ranked<'a, T, F> {
+        item: T,
+        seq: usize,
+        cmp: &'a F,
+    }
+
+    impl<T, F: Fn(&T, &T) -> Ordering> Ord for Ranked<'_, T, F> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            (self.cmp)(&self.item, &other.item).then(self.seq.cmp(&other.seq))
+        }
+    }
+
+    impl<T, F: Fn(&T, &T) -> Ordering> PartialOrd for Ranked<'_, T, F> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl<T, F: Fn(&T, &T) -> Ordering> PartialEq for Ranked<'_, T, F> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl<T, F: Fn(&T, &T) -> Ordering> Eq for Ranked<'_, T, F> {}
+
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // Max-heap: the root is the worst item kept so far. Never reserve more
+    // than the input is known to hold, `k` may be far larger than that.
+    let items = items.into_iter();
+    let mut heap = BinaryHeap::with_capacity(k.min(items.size_hint().0));
+    for (seq, item) in items.enumerate() {
+        if heap.len() < k {
+            heap.push(Ranked {
+                item,
+                seq,
+                cmp: &cmp,
+            });
+        } else if heap
+            .peek()
+            .is_some_and(|worst| cmp(&item, &worst.item) == Ordering::Less)
+        {
+            heap.pop();
+            heap.push(Ranked {
+                item,
+                seq,
+                cmp: &cmp,
+            });
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|ranked| ranked.item)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_top_k_by() {
+        let items = vec![(5, 'a'), (1, 'b'), (9, 'c'), (5, 'd'), (7, 'e'), (5, 'f')];
+        let largest = top_k_by(items.clone(), 4, |a, b| b.0.cmp(&a.0));
+        assert_eq!(largest, vec![(9, 'c'), (7, 'e'), (5, 'a'), (5, 'd')]);
+
+        let smallest = top_k_by(items.clone(), 2, |a, b| a.0.cmp(&b.0));
+        assert_eq!(smallest, vec![(1, 'b'), (5, 'a')]);
+
+        assert!(top_k_by(items.clone(), 0, |a, b| a.cmp(b)).is_empty());
+        assert_eq!(top_k_by(items.clone(), 100, |a, b| a.cmp(b)).len(), 6);
+        assert_eq!(
+            top_k_by(items.clone(), usize::MAX, |a, b| a.cmp(b)).len(),
+            6
+        );
+        let unsized_iter = items.into_iter().filter(|_| true);
+        assert_eq!(top_k_by(unsized_iter, 1 << 40, |a, b| a.cmp(b)).len(), 6);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_escape_path_roundtrip() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let plain = Path::new("dir\\name\\x41.txt");
+        assert_eq!(escape_path(plain), "dir\\name\\x41.txt");
+        assert_eq!(unescape_path(&escape_path(plain)), plain);
+
+        let raw = Path::new(OsStr::from_bytes(b"dir/bad\xff\\name"));
+        assert_eq!(escape_path(raw), "dir/bad\\xFF\\\\name");
+        assert_eq!(unescape_path(&escape_path(raw)), raw);
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("5000").unwrap(), 5000);
+        assert_eq!(parse_count("1_000_000").unwrap(), 1_000_000);
+        assert_eq!(parse_count("1e6").unwrap(), 1_000_000);
+        assert_eq!(parse_count("2.5e3").unwrap(), 2500);
+        assert!(parse_count("1.5").is_err());
+        assert!(parse_count("-3").is_err());
+        assert!(parse_count("lots").is_err());
+    }
+
     #[test]
     fn test_parse_size() {
         assert_eq!(parse_size("100").unwrap(), 100);
@@ -121,6 +587,27 @@ mod tests {
         assert!(parse_size("10XB").is_err());
     }
 
+    #[test]
+    fn test_parse_size_shorthand() {
+        assert_eq!(parse_size("1.5G").unwrap(), 1_610_612_736);
+        assert_eq!(parse_size("10k").unwrap(), 10_240);
+        assert_eq!(parse_size("2m").unwrap(), 2_097_152);
+        assert_eq!(parse_size("1t").unwrap(), 1_099_511_627_776);
+        assert_eq!(parse_size("0.5kb").unwrap(), 500);
+        assert!(parse_size("-5M").is_err());
+    }
+
+    #[test]
+    fn test_parse_size_bound() {
+        assert_eq!(
+            parse_size_bound("+10M").unwrap(),
+            SizeBound::AtLeast(10_485_760)
+        );
+        assert_eq!(parse_size_bound("-1k").unwrap(), SizeBound::AtMost(1_024));
+        assert_eq!(parse_size_bound("512").unwrap(), SizeBound::Exactly(512));
+        assert!(parse_size_bound("+").is_err());
+    }
+
     #[test]
     fn test_format_size_human() {
         assert_eq!(format_size_human(0), "0 B");
@@ -129,6 +616,25 @@ mod tests {
         assert_eq!(format_size_human(1_048_576), "1 MiB");
     }
 
+    #[test]
+    fn test_format_allocated() {
+        assert_eq!(format_allocated(4096, 100), "4 KiB");
+        assert_eq!(format_allocated(1 << 20, 20 << 20), "1 MiB (5% of 20 MiB)");
+        assert_eq!(format_allocated(0, 1 << 30), "0 B (<1% of 1 GiB)");
+    }
+
+    #[test]
+    fn test_format_size_du() {
+        assert_eq!(format_size_du(0), "0");
+        assert_eq!(format_size_du(512), "512");
+        assert_eq!(format_size_du(4096), "4.0K");
+        assert_eq!(format_size_du(1536), "1.5K");
+        assert_eq!(format_size_du(10_240), "10K");
+        assert_eq!(format_size_du(1_048_576), "1.0M");
+        assert_eq!(format_size_du(1_048_575), "1.0M");
+        assert_eq!(format_size_du(1_073_741_824), "1.0G");
+    }
+
     #[test]
     fn test_parse_date() {
         // YYYY-MM-DD format
@@ -142,4 +648,61 @@ mod tests {
         // Invalid format
         assert!(parse_date("invalid").is_err());
     }
+
+    #[test]
+    fn test_parse_date_local_timezone() {
+        let expected = Local
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_date("2024-01-01").unwrap(), expected);
+
+        let expected = Local
+            .with_ymd_and_hms(2024, 1, 1, 14, 30, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_date("2024-01-01 14:30").unwrap(), expected);
+        assert_eq!(parse_date("2024-01-01T14:30").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use chrono::Duration;
+
+        assert_eq!(parse_duration("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+        assert_eq!(parse_duration("90min").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("1w").unwrap(), Duration::weeks(1));
+        assert_eq!(parse_duration("6mo").unwrap(), Duration::days(180));
+        assert_eq!(parse_duration("1y").unwrap(), Duration::days(365));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2 days").unwrap(), Duration::days(2));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3 fortnights").is_err());
+    }
+
+    #[test]
+    fn test_parse_date_named() {
+        let today = Local::now().date_naive();
+        let midnight = |d: NaiveDate| local_to_utc(d.and_time(NaiveTime::MIN)).unwrap();
+
+        assert_eq!(parse_date("today").unwrap(), midnight(today));
+        assert_eq!(
+            parse_date("Yesterday").unwrap(),
+            midnight(today.pred_opt().unwrap())
+        );
+
+        let expected = local_to_utc(today.and_hms_opt(9, 15, 0).unwrap()).unwrap();
+        assert_eq!(parse_date("09:15").unwrap(), expected);
+        assert_eq!(parse_date("today 09:15").unwrap(), expected);
+
+        let last_week = parse_date("last week").unwrap();
+        let diff = Utc::now() - last_week;
+        assert!(diff.num_days() == 7 || diff.num_days() == 6);
+
+        assert!(parse_date("last fortnight").is_err());
+        assert!(parse_date("yesterday 25:00").is_err());
+    }
 }
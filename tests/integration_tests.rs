@@ -117,6 +117,172 @@ fn test_find_by_extension() {
         .stdout(predicate::str::contains("file1.txt").not());
 }
 
+#[test]
+fn test_find_by_size_bounds() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--kind")
+        .arg("file")
+        .arg("--size")
+        .arg("+10")
+        .arg("--size")
+        .arg("-14b")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file2.rs"))
+        .stdout(predicate::str::contains("file1.txt").not())
+        .stdout(predicate::str::contains("file4.md").not());
+}
+
+#[test]
+fn test_find_by_age() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--newer-than")
+        .arg("1h")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt"));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--older-than")
+        .arg("1d")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt").not());
+}
+
+#[test]
+fn test_find_accessed_before() {
+    let test_dir = create_test_tree();
+    let stale = test_dir.path().join("file1.txt");
+    filetime::set_file_atime(&stale, filetime::FileTime::from_unix_time(1_577_836_800, 0))
+        .unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--accessed-before")
+        .arg("2021-01-01")
+        .arg("--columns")
+        .arg("name,atime")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt  2020-01-01 00:00:00"))
+        .stdout(predicate::str::contains("file2.rs").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_world_writable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_dir = create_test_tree();
+    let shared = test_dir.path().join("file1.txt");
+    fs::set_permissions(&shared, fs::Permissions::from_mode(0o666)).unwrap();
+    fs::set_permissions(
+        test_dir.path().join("file2.rs"),
+        fs::Permissions::from_mode(0o644),
+    )
+    .unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--world-writable")
+        .arg("--kind")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt"))
+        .stdout(predicate::str::contains("file2.rs").not());
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--perm")
+        .arg("-020")
+        .arg("--kind")
+        .arg("file")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt"))
+        .stdout(predicate::str::contains("file2.rs").not());
+}
+
+#[test]
+fn test_find_multiple_paths() {
+    let test_dir = create_test_tree();
+    let subdir = test_dir.path().join("subdir");
+    let nested = subdir.join("nested");
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(&nested)
+        .arg(&subdir)
+        .arg(test_dir.path().join("file1.txt"))
+        .arg("--kind")
+        .arg("file")
+        .arg("--format")
+        .arg("ndjson")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("file1.txt"));
+    assert!(stdout.contains("file3.txt"));
+    assert!(!stdout.contains("file2.rs"));
+    // nested/ is inside subdir/, so file4.md must only be listed once
+    assert_eq!(stdout.lines().filter(|l| l.contains("file4.md")).count(), 1);
+}
+
+#[test]
+fn test_find_paths_from_stdin() {
+    let test_dir = create_test_tree();
+    let input = format!(
+        "{}\0{}\0",
+        test_dir.path().join("file1.txt").display(),
+        test_dir.path().join("file2.rs").display()
+    );
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg("--stdin")
+        .arg("--ext")
+        .arg("rs")
+        .arg("--format")
+        .arg("json")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file2.rs"))
+        .stdout(predicate::str::contains("file1.txt").not())
+        .stdout(predicate::str::contains("file3.txt").not());
+}
+
 #[test]
 fn test_find_by_name_glob() {
     let test_dir = create_test_tree();
@@ -239,7 +405,7 @@ fn test_gitignore_respect() {
 
     // Initialize git repository (required for ignore crate to work)
     StdCommand::new("git")
-        .args(&["init"])
+        .args(["init"])
         .current_dir(test_dir.path())
         .output()
         .expect("failed to initialize git repo");
@@ -276,3 +442,1791 @@ fn test_gitignore_respect() {
         .stdout(predicate::str::contains("visible.txt"))
         .stdout(predicate::str::contains("ignored.txt"));
 }
+
+#[test]
+fn test_list_ls_mode() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .arg("--ls")
+        .arg("--no-color")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("total "))
+        .stdout(predicate::str::contains(".hidden"))
+        .stdout(predicate::str::contains("subdir/"))
+        .stdout(predicate::str::contains("file4.md").not());
+}
+
+#[test]
+fn test_size_du_mode() {
+    let test_dir = create_test_tree();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("size")
+        .arg(test_dir.path())
+        .arg("--du")
+        .arg("--apparent-size")
+        .arg("--max-depth")
+        .arg("1")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    // Only the root and its direct subdirectory are printed, root last
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("subdir"));
+    assert!(lines[1].ends_with(&test_dir.path().display().to_string()));
+    assert!(lines.iter().all(|l| l.contains('\t')));
+}
+
+#[test]
+fn test_findx_expression() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("findx")
+        .arg(test_dir.path())
+        .args(["(", "-name", "*.md", "-o", "-name", "*.rs", ")", "-type", "f"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file4.md"))
+        .stdout(predicate::str::contains("file2.rs"))
+        .stdout(predicate::str::contains("file1.txt").not());
+}
+
+#[test]
+fn test_findx_delete() {
+    let test_dir = create_test_tree();
+    let data_dir = TempDir::new().unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_DATA_HOME", data_dir.path())
+        .arg("findx")
+        .arg(test_dir.path())
+        .args(["-name", "*.txt", "-delete"])
+        .assert()
+        .success();
+
+    assert!(!test_dir.path().join("file1.txt").exists());
+    assert!(!test_dir.path().join("subdir/file3.txt").exists());
+    assert!(test_dir.path().join("file2.rs").exists());
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let trash = data_dir.path().join("Trash");
+        assert!(trash.join("files/file1.txt").exists());
+        assert!(trash.join("info/file3.txt.trashinfo").exists());
+    }
+
+    // Directories still have to be empty, trash or not
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_DATA_HOME", data_dir.path())
+        .arg("findx")
+        .arg(test_dir.path())
+        .args(["-name", "nested", "-delete"])
+        .assert()
+        .failure();
+    assert!(test_dir.path().join("subdir/nested/file4.md").exists());
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_DATA_HOME", data_dir.path())
+        .arg("findx")
+        .arg("--permanent")
+        .arg(test_dir.path())
+        .args(["-name", "*.md", "-delete"])
+        .assert()
+        .success();
+    assert!(!test_dir.path().join("subdir/nested/file4.md").exists());
+    assert!(!data_dir.path().join("Trash/files/file4.md").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_chmod_dry_run_and_apply() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_dir = create_test_tree();
+    let script = test_dir.path().join("file2.rs");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("chmod")
+        .arg("u+x")
+        .arg(test_dir.path())
+        .arg("--ext")
+        .arg("rs")
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("would change"))
+        .stdout(predicate::str::contains("0644 -> 0744"))
+        .stdout(predicate::str::contains("file1.txt").not());
+
+    let mode = fs::metadata(&script).unwrap().permissions().mode() & 0o7777;
+    assert_eq!(mode, 0o644);
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("chmod")
+        .arg("u+x")
+        .arg(test_dir.path())
+        .arg("--ext")
+        .arg("rs")
+        .assert()
+        .success();
+
+    let mode = fs::metadata(&script).unwrap().permissions().mode() & 0o7777;
+    assert_eq!(mode, 0o744);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_touch_set_mtime_and_undo() {
+    let test_dir = create_test_tree();
+    let cache_dir = TempDir::new().unwrap();
+    let file = test_dir.path().join("file1.txt");
+    let original = fs::metadata(&file).unwrap().modified().unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .arg("touch")
+        .arg(test_dir.path())
+        .arg("--set-mtime")
+        .arg("2020-05-17T10:30:00Z")
+        .arg("--name")
+        .arg("file1.txt")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 changed"))
+        .stderr(predicate::str::contains("Journal written"));
+
+    let touched = fs::metadata(&file).unwrap().modified().unwrap();
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1589711400);
+    assert_eq!(touched, expected);
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .arg("touch")
+        .arg("--undo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 changed"));
+
+    assert_eq!(fs::metadata(&file).unwrap().modified().unwrap(), original);
+}
+
+#[test]
+fn test_pack_verifies_and_removes() {
+    let test_dir = create_test_tree();
+    let out_dir = TempDir::new().unwrap();
+    let archive = out_dir.path().join("text.tar.zst");
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_DATA_HOME", out_dir.path())
+        .arg("pack")
+        .arg(test_dir.path())
+        .arg("--ext")
+        .arg("txt")
+        .arg("--into")
+        .arg(&archive)
+        .arg("--remove")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Packed 2 files"))
+        .stdout(predicate::str::contains(
+            "Moved 2 original files to the trash",
+        ));
+
+    assert!(archive.exists());
+    assert!(out_dir.path().join("text.tar.zst.manifest.json").exists());
+    assert!(!test_dir.path().join("file1.txt").exists());
+    assert!(test_dir.path().join("file2.rs").exists());
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        assert!(out_dir.path().join("Trash/files/file1.txt").exists());
+    }
+}
+
+#[test]
+fn test_archive_filtered_results() {
+    let test_dir = create_test_tree();
+    let out_dir = TempDir::new().unwrap();
+    let archive = out_dir.path().join("text.tar.gz");
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("archive")
+        .arg(test_dir.path())
+        .arg("--ext")
+        .arg("txt")
+        .arg("--output")
+        .arg(&archive)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived 2 files"));
+
+    let file = fs::File::open(&archive).unwrap();
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let mut names: Vec<String> = tar
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["file1.txt", "subdir/file3.txt"]);
+
+    // An existing archive is never overwritten
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("archive")
+        .arg(test_dir.path())
+        .arg("--output")
+        .arg(&archive)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_projects_shell_init() {
+    let px_dir = TempDir::new().unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("PX_CONFIG_DIR", px_dir.path().join("config"))
+        .env("PX_CACHE_DIR", px_dir.path().join("cache"))
+        .arg("projects")
+        .arg("shell-init")
+        .arg("bash")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "command fexplorer projects cd \"$@\"",
+        ));
+}
+
+#[test]
+fn test_px_json_envelope() {
+    let home = TempDir::new().unwrap();
+    let repo = home.path().join("code/api");
+    fs::create_dir_all(&repo).unwrap();
+    std::process::Command::new("git")
+        .args(["init", "--quiet"])
+        .current_dir(&repo)
+        .status()
+        .unwrap();
+    let config_dir = home.path().join("config");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        format!("scan_dirs = [{:?}]\n", home.path().join("code")),
+    )
+    .unwrap();
+    let px = || {
+        let mut cmd = Command::cargo_bin("px").unwrap();
+        cmd.env("PX_CONFIG_DIR", &config_dir)
+            .env("PX_CACHE_DIR", home.path().join("cache"));
+        cmd
+    };
+
+    let output = px().args(["sync", "--format", "json"]).output().unwrap();
+    assert!(output.status.success());
+    let sync: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(sync["ok"], true);
+    assert_eq!(sync["result"]["projects"], 1);
+    assert_eq!(sync["result"]["added"][0], repo.to_string_lossy().as_ref());
+
+    let output = px().args(["info", "api", "--format", "json"]).output().unwrap();
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["result"]["name"], "api");
+
+    let output = px().args(["info", "nothing", "--format", "json"]).output().unwrap();
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(error["ok"], false);
+    assert_eq!(
+        error["error"]["message"],
+        "No projects found matching 'nothing'"
+    );
+}
+
+#[test]
+fn test_find_detect_type() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("deploy"), "#!/usr/bin/env bash\nexit 0\n").unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "plain text").unwrap();
+
+    // By extension, the script has no category
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--category", "executable"])
+        .arg(test_dir.path())
+        .assert()
+        .stdout(predicate::str::contains("deploy").not());
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--detect-type", "--category", "executable"])
+        .args(["--columns", "name,mime"])
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("text/x-shellscript"))
+        .stdout(predicate::str::contains("notes.txt").not());
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--mime", "text/plain"])
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.txt"))
+        .stdout(predicate::str::contains("deploy").not());
+}
+
+#[test]
+fn test_find_dir_size() {
+    let test_dir = TempDir::new().unwrap();
+    fs::create_dir_all(test_dir.path().join("cache/blobs")).unwrap();
+    fs::write(test_dir.path().join("cache/blobs/a.bin"), vec![0u8; 4096]).unwrap();
+    fs::create_dir(test_dir.path().join("docs")).unwrap();
+    fs::write(test_dir.path().join("docs/readme.md"), "hello").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--kind", "dir", "--dir-size", "--min-size", "4KB"])
+        .args(["--columns", "name,size"])
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("blobs"))
+        .stdout(predicate::str::contains("cache"))
+        .stdout(predicate::str::contains("docs").not());
+}
+
+#[test]
+fn test_from_results() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("notes.md"), "TODO: write").unwrap();
+    fs::write(test_dir.path().join("main.rs"), "// TODO: test").unwrap();
+    let export = test_dir.path().join("results.json");
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--kind", "file", "--format", "json", "--output"])
+        .arg(&export)
+        .arg(test_dir.path())
+        .assert()
+        .success();
+    // Not in the export, so never looked at
+    fs::write(test_dir.path().join("later.md"), "TODO: ignored").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["size", "--from-results"])
+        .arg(&export)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("main.rs"))
+        .stdout(predicate::str::contains("later.md").not());
+
+    let ndjson = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--ext", "md", "--format", "ndjson"])
+        .arg(test_dir.path())
+        .output()
+        .unwrap()
+        .stdout;
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["grep", "--from-results", "-", "--ext", "md", ".", "TODO"])
+        .write_stdin(ndjson)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("notes.md"))
+        .stdout(predicate::str::contains("main.rs").not());
+
+    // Commands that walk on their own refuse it rather than ignore it
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["tree", "--from-results"])
+        .arg(&export)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("tree doesn't support --from-results"));
+}
+
+#[test]
+fn test_metadata_cache() {
+    let test_dir = TempDir::new().unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("a.txt"), "hello").unwrap();
+    let fexplorer = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("XDG_CACHE_HOME", cache_dir.path());
+        cmd
+    };
+
+    // The second run reads a.txt from the cache
+    for _ in 0..2 {
+        fexplorer()
+            .args(["find", "--name", "*.txt"])
+            .arg(test_dir.path())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("a.txt"));
+    }
+    assert!(cache_dir.path().join("fexplorer/metadata.db").exists());
+
+    // A new file changes the directory, so it is walked again
+    fs::write(test_dir.path().join("b.txt"), "world").unwrap();
+    fexplorer()
+        .arg("size")
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("b.txt"));
+
+    fexplorer()
+        .args(["cache", "clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cleared"));
+    fexplorer()
+        .args(["cache", "clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already empty"));
+}
+
+#[test]
+fn test_json_schema_version() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("a.txt"), "hello").unwrap();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["find", "--format", "ndjson"])
+        .arg(test_dir.path())
+        .output()
+        .unwrap();
+    let line: serde_json::Value =
+        serde_json::from_str(String::from_utf8_lossy(&output.stdout).lines().next().unwrap())
+            .unwrap();
+    assert_eq!(line["schema_version"], 1);
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("schema")
+        .output()
+        .unwrap();
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["properties"]["schema_version"]["type"], "integer");
+}
+
+#[test]
+fn test_reproducible_output() {
+    let test_dir = create_test_tree();
+    let run = |format: &str| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .args(["list", "--reproducible", "--threads", "2", "--unordered", "--format"])
+            .arg(format)
+            .arg(test_dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let ndjson = run("ndjson");
+    assert_eq!(ndjson, run("ndjson"));
+    assert!(!ndjson.contains("\"owner\""));
+    assert!(!ndjson.contains("\"atime\""));
+    let paths: Vec<String> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["path"].to_string())
+        .collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+
+    let pretty = run("pretty");
+    let mtime = pretty.lines().next().unwrap().split("  ").nth(2).unwrap();
+    assert!(mtime.ends_with('Z') && mtime.contains('T'), "{}", mtime);
+
+    // Machine-readable formats use the same RFC 3339 times
+    let first: serde_json::Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+    let mtime = first["mtime"].as_str().unwrap();
+    assert!(mtime.ends_with('Z') && mtime.contains('T'), "{}", mtime);
+    let json: serde_json::Value = serde_json::from_str(&run("json")).unwrap();
+    assert_eq!(json[0]["mtime"], first["mtime"]);
+    let csv = run("csv");
+    let mtime = csv.lines().nth(1).unwrap().split(',').nth(2).unwrap();
+    assert!(mtime.ends_with('Z'), "{}", mtime);
+
+    // Commands whose reports it doesn't normalize refuse it
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["tree", "--reproducible"])
+        .arg(test_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("doesn't support --reproducible"));
+}
+
+#[test]
+fn test_grep_output_formats() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("a.txt"), "one TODO\nnothing\nTODO and TODO\n").unwrap();
+    fs::write(test_dir.path().join("b.txt"), "TODO\n").unwrap();
+    let grep = |format: &str| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .args(["grep", "--line-numbers", "--format", format])
+            .arg(test_dir.path())
+            .arg("TODO")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let ndjson = grep("ndjson");
+    let mut records: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    records.sort_by_key(|r| (r["entry"]["name"].to_string(), r["line_number"].as_u64()));
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1]["line_number"], 3);
+    assert_eq!(records[1]["submatches"].as_array().unwrap().len(), 2);
+
+    let json: serde_json::Value = serde_json::from_str(&grep("json")).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 3);
+
+    assert!(grep("csv").starts_with("path,line,column,text\n"));
+
+    // Pretty output names each file once, above its matches
+    let pretty = grep("pretty");
+    assert_eq!(pretty.matches("a.txt").count(), 1, "{}", pretty);
+    assert!(pretty.contains("  3:1: TODO and TODO"), "{}", pretty);
+    assert!(pretty.contains("Found 3 matches in 2 files"), "{}", pretty);
+}
+
+#[test]
+fn test_grep_word_and_multiline() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("a.rs"), "fn cat(\n    x: u8,\n) {}\nconcat();\n").unwrap();
+    let grep = |args: &[&str], pattern: &str| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .arg("grep")
+            .args(args)
+            .arg(test_dir.path())
+            .arg(pattern)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let words = grep(&["-n", "-w", "-F"], "cat");
+    assert!(words.contains("Found 1 matches in 1 files"), "{}", words);
+
+    let multiline = grep(&["-n", "-U", "-e"], r"fn cat\(.*?\)");
+    assert!(multiline.contains("  1:1: fn cat(\n  2:     x: u8,\n  3: )"), "{}", multiline);
+
+    // Multiline matches are searched, not replaced
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["grep", "-U", "--replace", "x"])
+        .arg(test_dir.path())
+        .arg("cat")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_grep_file_limits() {
+    let test_dir = TempDir::new().unwrap();
+    let root = test_dir.path();
+    fs::create_dir(root.join(".git")).unwrap();
+    fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+    fs::write(root.join("small.txt"), "needle\n").unwrap();
+    fs::write(root.join("big.txt"), format!("needle\n{}", "x".repeat(4096))).unwrap();
+    fs::write(root.join("app.bin"), b"\0\0needle\n").unwrap();
+    fs::write(root.join("debug.log"), "needle\n").unwrap();
+    fs::write(root.join(".env"), "needle\n").unwrap();
+    let grep = |args: &[&str], stdin: &str| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .current_dir(root)
+            .args(["grep", "--format", "csv"])
+            .args(args)
+            .write_stdin(stdin)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let mut paths: Vec<String> = String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap().to_string())
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    assert_eq!(grep(&[".", "needle"], ""), ["./big.txt", "./small.txt"]);
+    assert_eq!(
+        grep(&["--max-filesize", "1KB", ".", "needle"], ""),
+        ["./small.txt"]
+    );
+    assert_eq!(
+        grep(&["--binary", ".", "needle"], ""),
+        ["./app.bin", "./big.txt", "./small.txt"]
+    );
+
+    // Path lists skip hidden and ignored files like a walk does
+    let list = "small.txt\ndebug.log\n.env\n";
+    assert_eq!(grep(&["-", "needle"], list), ["small.txt"]);
+    assert_eq!(
+        grep(&["--hidden", "--no-gitignore", "-", "needle"], list),
+        [".env", "debug.log", "small.txt"]
+    );
+}
+
+#[test]
+fn test_lang() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("a.txt"), "hola TODO").unwrap();
+    let grep = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env_remove("LC_ALL")
+            .env_remove("LC_MESSAGES")
+            .arg("grep")
+            .arg(test_dir.path())
+            .arg("TODO");
+        cmd
+    };
+
+    grep()
+        .env("LANG", "es_ES.UTF-8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Se encontraron 1 coincidencias en 1 archivos"));
+    // --lang wins over the locale
+    grep()
+        .env("LANG", "es_ES.UTF-8")
+        .args(["--lang", "en"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 matches in 1 files"));
+    grep()
+        .env("LANG", "fr_FR.UTF-8")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 matches"));
+    grep()
+        .args(["--lang", "fr"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported language"));
+}
+
+#[test]
+fn test_plain_output() {
+    let test_dir = TempDir::new().unwrap();
+    fs::create_dir(test_dir.path().join("src")).unwrap();
+    fs::write(test_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .arg("--plain")
+            .args(args)
+            .arg(test_dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let tree = run(&["tree"]);
+    assert!(tree.contains("  DIR src\n    FILE main.rs\n"), "{}", tree);
+    assert!(tree.is_ascii(), "{}", tree);
+
+    let list = run(&["list", "--columns", "name"]);
+    assert!(list.contains("DIR   src\n"), "{}", list);
+}
+
+#[test]
+fn test_color_choice() {
+    let test_dir = create_test_tree();
+    let list = |args: &[&str], env: &[(&str, &str)]| {
+        let output = Command::cargo_bin("fexplorer")
+            .unwrap()
+            .env_remove("NO_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .envs(env.iter().copied())
+            .arg("list")
+            .arg(test_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap().contains('\x1b')
+    };
+
+    // Piped output is plain unless color is forced
+    assert!(!list(&[], &[]));
+    assert!(list(&["--color", "always"], &[]));
+    assert!(list(&[], &[("CLICOLOR_FORCE", "1")]));
+    assert!(!list(&["--color", "never"], &[("CLICOLOR_FORCE", "1")]));
+    assert!(!list(&["--no-color", "--color", "always"], &[]));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["--color", "sometimes", "list"])
+        .arg(test_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_similar_names() {
+    let test_dir = TempDir::new().unwrap();
+    for name in ["budget.xlsx", "budget (1).xlsx", "budget_final_v2.xlsx", "notes.md"] {
+        fs::write(test_dir.path().join(name), "data").unwrap();
+    }
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("similar")
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Similar Group #1 (budget.xlsx)"))
+        .stdout(predicate::str::contains("budget_final_v2.xlsx"))
+        .stdout(predicate::str::contains("notes.md").not())
+        .stdout(predicate::str::contains("Total: 1 groups, 3 files"));
+}
+
+#[test]
+fn test_find_case_collisions() {
+    let test_dir = TempDir::new().unwrap();
+    for name in [
+        "README.md",
+        "readme.md",
+        "cafe\u{301}.txt",
+        "caf\u{e9}.txt",
+        "other.txt",
+    ] {
+        fs::write(test_dir.path().join(name), "").unwrap();
+    }
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .arg("--case-collisions")
+        .args(["--format", "ndjson"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("README.md"))
+        .stdout(predicate::str::contains("readme.md"))
+        .stdout(predicate::str::contains("caf\u{e9}.txt"))
+        .stdout(predicate::str::contains("other.txt").not())
+        .stderr(predicate::str::contains("differ only in unicode normalization"));
+}
+
+#[test]
+fn test_find_baseline_diff() {
+    let test_dir = create_test_tree();
+    let out_dir = TempDir::new().unwrap();
+    let baseline = out_dir.path().join("baseline.json");
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--kind", "file", "--format", "json"])
+        .output()
+        .unwrap();
+    fs::write(&baseline, output.stdout).unwrap();
+
+    fs::write(test_dir.path().join("file1.txt"), "changed content").unwrap();
+    fs::remove_file(test_dir.path().join("file2.rs")).unwrap();
+    fs::write(test_dir.path().join("new.txt"), "").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--kind", "file"])
+        .arg("--baseline")
+        .arg(&baseline)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~ "))
+        .stdout(predicate::str::contains("file1.txt"))
+        .stdout(predicate::str::contains("- "))
+        .stdout(predicate::str::contains("+ "))
+        .stdout(predicate::str::contains("file3.txt").not())
+        .stdout(predicate::str::contains("1 added, 1 removed, 1 changed"));
+}
+
+#[test]
+fn test_portability_audit() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("aux.txt"), "").unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("portability")
+        .arg(test_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[reserved-name]"))
+        .stdout(predicate::str::contains("fix: rename to '_aux.txt'"))
+        .stdout(predicate::str::contains("notes.txt").not());
+
+    fs::remove_file(test_dir.path().join("aux.txt")).unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("portability")
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No portability issues found"));
+}
+
+#[test]
+fn test_find_where_expression() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--where", "kind = file and (ext = rs or name ~ 'file4*')"])
+        .args(["--format", "ndjson"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file2.rs"))
+        .stdout(predicate::str::contains("file4.md"))
+        .stdout(predicate::str::contains("file1.txt").not());
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--where", "size >"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--where"));
+}
+
+#[test]
+fn test_enriched_columns() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .args(["--columns", "name,category,mime", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name,category,mime"))
+        .stdout(predicate::str::contains("file2.rs,source/rust,text/x-rust"))
+        .stdout(predicate::str::contains("file4.md,docs,text/markdown"));
+}
+
+#[test]
+fn test_select_computed_fields() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .args(["--select", "name, size * 2 as double, upper(ext)", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name,double,upper(ext)"))
+        .stdout(predicate::str::contains("file2.rs,24,RS"));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .args(["--select", "name, category", "--format", "ndjson"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"{"name":"file2.rs","category":"source/rust"}"#,
+        ));
+}
+
+#[test]
+fn test_group_by_aggregates() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--kind", "file", "--group-by", "ext", "--agg", "count, sum(size)"])
+        .args(["--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ext,count,sum(size)"))
+        .stdout(predicate::str::contains("md,1,15"))
+        .stdout(predicate::str::contains("txt,2,16"));
+}
+
+#[test]
+fn test_list_sort_limit() {
+    let test_dir = create_test_tree();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .args(["--sort", "size", "--order", "desc", "--limit", "2"])
+        .args(["--columns", "name", "--format", "csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let names: Vec<&str> = stdout.lines().skip(1).collect();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[0], "file4.md");
+}
+
+#[test]
+fn test_list_multi_key_sort() {
+    let test_dir = create_test_tree();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("list")
+        .arg(test_dir.path())
+        .args(["--sort", "kind,size:desc,name", "--order", "asc"])
+        .args(["--columns", "name", "--format", "csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let names: Vec<&str> = stdout.lines().skip(4).collect();
+    // Files by size descending; file1.txt and file3.txt tie on size
+    assert_eq!(names, ["file4.md", "file2.rs", "file1.txt", "file3.txt"]);
+}
+
+#[test]
+fn test_find_dirs_and_files_first() {
+    let temp_dir = create_test_tree();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--dirs-first")
+        .arg("--format")
+        .arg("ndjson")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let kinds: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["kind"].as_str().unwrap().to_string()
+        })
+        .collect();
+    let first_file = kinds.iter().position(|k| k == "file").unwrap();
+    assert!(kinds[..first_file].iter().all(|k| k == "dir"));
+    assert!(kinds[first_file..].iter().all(|k| k != "dir"));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--dirs-first")
+        .arg("--files-first")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_find_per_dir_limit() {
+    let temp_dir = create_test_tree();
+    fs::write(temp_dir.path().join("big.txt"), "x".repeat(100)).unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--kind")
+        .arg("file")
+        .arg("--sort")
+        .arg("size:desc")
+        .arg("--per-dir-limit")
+        .arg("1")
+        .arg("--columns")
+        .arg("name")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("big.txt"))
+        .stdout(predicate::str::contains("file3.txt"))
+        .stdout(predicate::str::contains("file4.md"))
+        .stdout(predicate::str::contains("file1.txt").not())
+        .stdout(predicate::str::contains("file2.rs").not());
+}
+
+#[test]
+fn test_find_unique_by_name() {
+    let temp_dir = create_test_tree();
+    fs::write(temp_dir.path().join("subdir/file1.txt"), "copy").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--kind")
+        .arg("file")
+        .arg("--sort")
+        .arg("size")
+        .arg("--unique-by")
+        .arg("name")
+        .arg("--columns")
+        .arg("name,size,count")
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt,4,2"))
+        .stdout(predicate::str::contains("file1.txt,8").not())
+        .stdout(predicate::str::contains("file2.rs,12,1"));
+}
+
+#[test]
+fn test_dev_caches_clean() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path().join("home");
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(home.join(".cache/pip")).unwrap();
+    fs::write(home.join(".cache/pip/wheel.whl"), "wheel").unwrap();
+    fs::create_dir_all(project.join("target/debug")).unwrap();
+    fs::write(project.join("target/CACHEDIR.TAG"), "Signature").unwrap();
+    fs::write(project.join("target/debug/app"), "binary").unwrap();
+
+    let dev_caches = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("HOME", &home)
+            .env("CARGO_HOME", home.join(".cargo"))
+            .env("XDG_CACHE_HOME", home.join(".cache"))
+            .env("XDG_DATA_HOME", home.join(".local/share"))
+            .arg("dev-caches")
+            .arg(&project);
+        cmd
+    };
+
+    dev_caches()
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"kind\": \"cargo-target\""))
+        .stdout(predicate::str::contains("\"kind\": \"pip\""));
+
+    dev_caches()
+        .arg("--clean")
+        .arg("cargo-target")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("trashed"));
+    assert!(!project.join("target").exists());
+    assert!(home.join(".cache/pip").exists());
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        assert!(home
+            .join(".local/share/Trash/files/target/debug/app")
+            .exists());
+    }
+
+    fs::create_dir_all(home.join(".cache/pip")).unwrap();
+    dev_caches()
+        .args(["--clean", "pip", "--permanent"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("removed"));
+    assert!(!home.join(".cache/pip").exists());
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_backup_exclusions_outside_macos() {
+    let temp_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("backup")
+        .arg("exclude")
+        .arg(temp_dir.path().join("subdir"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only supported on macOS"));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--backup-excluded")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_locate_backend() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_dir = create_test_tree();
+    let root = test_dir.path().canonicalize().unwrap();
+    let bin_dir = TempDir::new().unwrap();
+
+    // Stand-in locate database: a match outside the root, a stale entry and a
+    // file the --name filter still has to reject
+    let locate = bin_dir.path().join("locate");
+    fs::write(
+        &locate,
+        format!(
+            "#!/bin/sh\nprintf '%s\\0' /elsewhere/a.txt {0}/gone.txt {0}/file1.txt {0}/subdir/file3.txt {0}/file2.rs\n",
+            root.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&locate, fs::Permissions::from_mode(0o755)).unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("PATH", bin_dir.path())
+        .arg("find")
+        .arg(&root)
+        .arg("--backend")
+        .arg("locate")
+        .arg("--name")
+        .arg("*.txt")
+        .arg("--format")
+        .arg("ndjson")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt"))
+        .stdout(predicate::str::contains("file3.txt"))
+        .stdout(predicate::str::contains("file2.rs").not())
+        .stdout(predicate::str::contains("gone.txt").not())
+        .stdout(predicate::str::contains("elsewhere").not());
+
+    // Without the tool the tree is walked instead
+    fs::remove_file(&locate).unwrap();
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("PATH", bin_dir.path())
+        .arg("find")
+        .arg(&root)
+        .arg("--backend")
+        .arg("locate")
+        .arg("--name")
+        .arg("*.md")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file4.md"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_errors() {
+    let temp_dir = create_test_tree();
+    std::os::unix::fs::symlink("..", temp_dir.path().join("subdir/up")).unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("-q")
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--follow-symlinks")
+        .arg("--errors")
+        .arg("--columns")
+        .arg("name,error")
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up,File system loop found"))
+        .stdout(predicate::str::contains("file1.txt").not());
+}
+
+#[test]
+fn test_find_exclude() {
+    let temp_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(temp_dir.path())
+        .arg("--exclude")
+        .arg("subdir")
+        .arg("--exclude")
+        .arg("*.rs")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file1.txt"))
+        .stdout(predicate::str::contains("file2.rs").not())
+        .stdout(predicate::str::contains("file3.txt").not())
+        .stdout(predicate::str::contains("file4.md").not());
+}
+
+#[test]
+#[cfg(feature = "trends")]
+fn test_snapshot_create_list_delete() {
+    let test_dir = create_test_tree();
+    let data_dir = TempDir::new().unwrap();
+    let fexplorer = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path());
+        cmd
+    };
+
+    fexplorer()
+        .arg("snapshot")
+        .arg(test_dir.path())
+        .arg("--description")
+        .arg("baseline")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("5 files, 3 directories"));
+
+    let output = fexplorer()
+        .args(["snapshot", "list", "--format", "json"])
+        .output()
+        .unwrap();
+    let snapshots: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(snapshots.as_array().unwrap().len(), 1);
+    assert_eq!(snapshots[0]["description"], "baseline");
+    let id = snapshots[0]["id"].as_str().unwrap();
+
+    fexplorer().args(["snapshot", "delete", id]).assert().success();
+    fexplorer()
+        .args(["snapshot", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No snapshots"));
+}
+
+#[test]
+#[cfg(feature = "trends")]
+fn test_trends_between_snapshots() {
+    let test_dir = create_test_tree();
+    let data_dir = TempDir::new().unwrap();
+    let fexplorer = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path());
+        cmd
+    };
+
+    fexplorer()
+        .arg("snapshot")
+        .arg(test_dir.path())
+        .assert()
+        .success();
+    fexplorer()
+        .arg("trends")
+        .arg(test_dir.path())
+        .assert()
+        .failure();
+
+    fs::write(test_dir.path().join("report.pdf"), vec![0u8; 4096]).unwrap();
+    fs::remove_file(test_dir.path().join("file1.txt")).unwrap();
+    fexplorer()
+        .arg("snapshot")
+        .arg(test_dir.path())
+        .assert()
+        .success();
+
+    fexplorer()
+        .arg("trends")
+        .arg(test_dir.path())
+        .arg("--chart")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 snapshots"))
+        .stdout(predicate::str::contains("report.pdf"))
+        .stdout(predicate::str::contains("Deleted files: 1"))
+        .stdout(predicate::str::contains("Chart:"));
+}
+
+#[cfg(feature = "ndjson-zst")]
+#[test]
+fn test_ndjson_zst_output() {
+    let temp = create_test_tree();
+    let out = temp.path().join("entries.ndjson.zst");
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("find")
+        .arg(temp.path())
+        .arg("--ext")
+        .arg("txt")
+        .arg("--format")
+        .arg("ndjson.zst")
+        .arg("--out")
+        .arg(&out);
+    cmd.assert().success().stdout("");
+
+    let compressed = fs::read(&out).unwrap();
+    let text = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+    assert_eq!(text.lines().count(), 2);
+    for line in text.lines() {
+        let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(entry["path"].as_str().unwrap().ends_with(".txt"));
+    }
+}
+
+#[cfg(feature = "trends")]
+#[test]
+fn test_export_since_snapshot() {
+    let test_dir = create_test_tree();
+    let data_dir = TempDir::new().unwrap();
+    let fexplorer = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("XDG_DATA_HOME", data_dir.path());
+        cmd
+    };
+
+    let full = fexplorer()
+        .arg("export")
+        .arg(test_dir.path())
+        .arg("--snapshot")
+        .output()
+        .unwrap();
+    assert!(full.status.success());
+    // Root, 2 subdirectories and 5 files
+    assert_eq!(String::from_utf8_lossy(&full.stdout).lines().count(), 8);
+
+    fs::remove_file(test_dir.path().join("file1.txt")).unwrap();
+    fs::write(test_dir.path().join("subdir/nested/new.md"), "# new").unwrap();
+
+    let since = chrono::Utc::now().to_rfc3339();
+    let output = fexplorer()
+        .arg("export")
+        .arg(test_dir.path())
+        .arg("--since")
+        .arg(&since)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let changes: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let change_of = |name: &str| {
+        changes
+            .iter()
+            .find(|c| c["path"].as_str().unwrap().ends_with(name))
+            .map(|c| c["change"].as_str().unwrap().to_string())
+    };
+    assert_eq!(change_of("file1.txt").as_deref(), Some("removed"));
+    assert_eq!(change_of("new.md").as_deref(), Some("added"));
+    assert_eq!(change_of("file2.rs"), None);
+}
+
+#[test]
+fn test_find_explain() {
+    let temp = create_test_tree();
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("find")
+        .arg(temp.path())
+        .arg("--ext")
+        .arg("txt")
+        .arg("--where")
+        .arg("size > 1MB or name = file1.txt")
+        .arg("--max-depth")
+        .arg("3")
+        .arg("--explain");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("max depth:        3"))
+        .stdout(predicate::str::contains("├─ extension txt"))
+        .stdout(predicate::str::contains("└─ or"))
+        .stdout(predicate::str::contains("name = file1.txt"))
+        // Nothing was walked
+        .stdout(predicate::str::contains("subdir").not());
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    let output = cmd
+        .arg("find")
+        .arg(temp.path())
+        .arg("--size")
+        .arg("+2M")
+        .arg("--size")
+        .arg("-1M")
+        .arg("--explain")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(plan["selectivity"], 0.0);
+    assert_eq!(plan["filter"]["children"][0]["filter"], "size (files only)");
+}
+
+#[cfg(feature = "grep")]
+#[test]
+fn test_grep_replace() {
+    let temp = create_test_tree();
+    let file = temp.path().join("file2.rs");
+    fs::write(&file, "fn old_name() {}\nold_name();\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("grep")
+        .arg(temp.path())
+        .arg("old_name")
+        .arg("--replace")
+        .arg("new_name")
+        .arg("--dry-run");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("-old_name();\n+new_name();"))
+        .stdout(predicate::str::contains(
+            "Would replace 2 matches on 2 lines in 1 files",
+        ));
+    assert!(fs::read_to_string(&file).unwrap().contains("old_name"));
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("grep")
+        .arg(temp.path())
+        .arg(r"(old)_(\w+)")
+        .arg("--regex")
+        .arg("--replace")
+        .arg("${2}_new");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Replaced 2 matches"));
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "fn name_new() {}\nname_new();\n"
+    );
+}
+
+#[test]
+fn test_run_pipeline_profile() {
+    let test_dir = create_test_tree();
+    let config_dir = TempDir::new().unwrap();
+    fs::create_dir_all(config_dir.path().join("fexplorer")).unwrap();
+    fs::write(
+        config_dir.path().join("fexplorer/config.toml"),
+        r#"
+            [profiles.text-files]
+            command = "pipeline"
+            params = { ext = "txt" }
+            stages = [
+                { command = "find", args = { ext = "{{ext}}" } },
+                { command = "sort", args = { by = "name:desc" } },
+                { command = "limit", args = { n = 1 } },
+                { command = "export", args = { format = "json", out = "out.json" } },
+            ]
+        "#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", config_dir.path())
+        .arg("run")
+        .arg("text-files")
+        .current_dir(test_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[1/4] find: 2 entries"), "{}", stderr);
+    assert!(stderr.contains("[3/4] limit: 1 entries"), "{}", stderr);
+
+    let exported: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(test_dir.path().join("out.json")).unwrap())
+            .unwrap();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0]["name"], "file3.txt");
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_grep_output_gzip() {
+    use std::io::Read;
+
+    let temp = create_test_tree();
+    let out = temp.path().join("matches.txt.gz");
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("grep")
+        .arg(temp.path())
+        .arg("content")
+        .arg("--output")
+        .arg(&out);
+    cmd.assert().success().stdout("");
+
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(fs::File::open(&out).unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    assert!(text.contains("file1.txt"), "{}", text);
+    assert!(text.contains("Found"), "{}", text);
+}
+
+#[test]
+fn test_report_output_file() {
+    let temp = create_test_tree();
+    let out_dir = TempDir::new().unwrap();
+    let out = out_dir.path().join("tree.txt");
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("tree").arg(temp.path()).arg("--output").arg(&out);
+    cmd.assert().success().stdout("");
+    assert!(fs::read_to_string(&out).unwrap().contains("file1.txt"));
+
+    // An unknown format fails before the file is truncated
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("find")
+        .arg(temp.path())
+        .args(["--format", "yaml", "--output"])
+        .arg(&out);
+    cmd.assert().failure();
+    assert!(fs::read_to_string(&out).unwrap().contains("file1.txt"));
+
+    // Commands that can't write their report to a file say so
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.args(["touch", "--dry-run", "--set-mtime", "2020-01-01"])
+        .arg(temp.path())
+        .arg("--output")
+        .arg(&out);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("doesn't support --output"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_hooks() {
+    let test_dir = create_test_tree();
+    let config_dir = TempDir::new().unwrap();
+    let summary = config_dir.path().join("summary.json");
+    fs::create_dir_all(config_dir.path().join("fexplorer")).unwrap();
+    fs::write(
+        config_dir.path().join("fexplorer/config.toml"),
+        format!(
+            "[hooks]\npost_list = \"cat > '{}'\"\npre_tree = \"exit 1\"\n",
+            summary.display()
+        ),
+    )
+    .unwrap();
+    let fexplorer = || {
+        let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+        cmd.env("XDG_CONFIG_HOME", config_dir.path());
+        cmd
+    };
+
+    fexplorer().arg("list").arg(test_dir.path()).assert().success();
+    let summary: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&summary).unwrap()).unwrap();
+    assert_eq!(summary["command"], "list");
+    assert_eq!(summary["success"], true);
+    assert_eq!(summary["args"][1], "list");
+
+    // A failing pre hook stops the command
+    fexplorer()
+        .arg("tree")
+        .arg(test_dir.path())
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[cfg(feature = "scripting")]
+#[test]
+fn test_find_filter_script() {
+    let temp = create_test_tree();
+    let script = temp.path().join("filter.rhai");
+    fs::write(&script, r#"entry.ext == "txt" && entry.name.contains("3")"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("find")
+        .arg(temp.path())
+        .arg("--filter-script")
+        .arg(&script)
+        .arg("--columns")
+        .arg("name");
+    cmd.assert().success().stdout("file3.txt\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_find_filter_exec() {
+    let temp = create_test_tree();
+
+    let mut cmd = Command::cargo_bin("fexplorer").unwrap();
+    cmd.arg("find")
+        .arg(temp.path())
+        .arg("--kind")
+        .arg("file")
+        .arg("--filter-exec")
+        .arg("grep -q content {}")
+        .arg("--exec-jobs")
+        .arg("2")
+        .arg("--sort")
+        .arg("name")
+        .arg("--columns")
+        .arg("name");
+    cmd.assert()
+        .success()
+        .stdout("file1.txt\nfile3.txt\n");
+}
+
+#[test]
+fn test_quota_check() {
+    let test_dir = create_test_tree();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["quota", "check", "--max-files", "2"])
+        .arg(test_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("FAIL"))
+        .stdout(predicate::str::contains("EXCEEDED"))
+        .stdout(predicate::str::contains("subdir"));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .args(["quota", "check", "--max-files", "1e3", "--max-size", "1MB"])
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("OK"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_non_utf8_names() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join(OsStr::from_bytes(b"bad\xffname.txt")), "").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--name", "bad*", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#"bad\\xFFname.txt""#))
+        .stdout(predicate::str::contains("\"name\": \"bad\u{fffd}name.txt\""));
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("find")
+        .arg(test_dir.path())
+        .args(["--ext", "txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r"bad\xFFname.txt"));
+}
+
+#[test]
+fn test_lint_rules() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "one \r\ntwo\n").unwrap();
+    fs::write(test_dir.path().join("yarn.lock"), "").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("lint")
+        .arg(test_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[mixed-line-endings]"))
+        .stdout(predicate::str::contains("notes.txt:1: info [trailing-whitespace]"))
+        .stdout(predicate::str::contains("lock file without package.json"));
+
+    // Case conflicts are errors and fail the run
+    fs::write(test_dir.path().join("README.md"), "").unwrap();
+    fs::write(test_dir.path().join("readme.md"), "").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .arg("lint")
+        .arg(test_dir.path())
+        .args(["--rule", "case-conflict"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[case-conflict]"))
+        .stdout(predicate::str::contains("[mixed-line-endings]").not());
+}
+
+#[test]
+fn test_lint_gha_annotations() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "one \r\ntwo\n").unwrap();
+
+    Command::cargo_bin("fexplorer")
+        .unwrap()
+        .current_dir(test_dir.path())
+        .args(["lint", ".", "--format", "gha"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "::warning file=notes.txt,line=2,title=fexplorer lint%3A mixed-line-endings::",
+        ))
+        .stdout(predicate::str::contains("::notice file=notes.txt,line=1,"));
+}
+
+#[test]
+fn test_lint_sarif_output() {
+    let test_dir = TempDir::new().unwrap();
+    fs::write(test_dir.path().join("notes.txt"), "one \n").unwrap();
+
+    let output = Command::cargo_bin("fexplorer")
+        .unwrap()
+        .current_dir(test_dir.path())
+        .args(["lint", ".", "--format", "sarif"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "trailing-whitespace");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "notes.txt"
+    );
+}